@@ -0,0 +1,345 @@
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, Utc};
+use reqwest::tls::TlsInfo;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use x509_parser::objects::{oid2sn, oid_registry};
+use x509_parser::pem::Pem;
+use x509_parser::public_key::PublicKey;
+
+use crate::models::CertificateStatus;
+
+/// RSA keys below this size are reported as weak
+const MIN_RSA_BITS: usize = 2048;
+/// EC keys below this size are reported as weak
+const MIN_EC_BITS: usize = 256;
+
+/// Inspect a DER-encoded certificate for expiry and weak parameters
+pub fn inspect_der(source: &str, der: &[u8], now: DateTime<Utc>, warn_days: i64) -> CertificateStatus {
+    let mut status = CertificateStatus {
+        source: source.to_string(),
+        subject: None,
+        issuer: None,
+        not_after: None,
+        days_left: None,
+        key: None,
+        signature: None,
+        issues: Vec::new(),
+    };
+
+    let cert = match x509_parser::parse_x509_certificate(der) {
+        Ok((_, cert)) => cert,
+        Err(e) => {
+            status.issues.push(format!("unparseable certificate: {}", e));
+            return status;
+        }
+    };
+
+    status.subject = Some(cert.subject().to_string());
+    status.issuer = Some(cert.issuer().to_string());
+
+    let not_after = DateTime::from_timestamp(cert.validity().not_after.timestamp(), 0);
+    if let Some(not_after) = not_after {
+        let days_left = (not_after - now).num_days();
+        if not_after < now {
+            status.issues.push("expired".to_string());
+        } else if days_left < warn_days {
+            status.issues.push(format!("expires in {} days", days_left));
+        }
+        status.not_after = Some(not_after);
+        status.days_left = Some(days_left);
+    }
+
+    match cert.public_key().parsed() {
+        Ok(PublicKey::RSA(rsa)) => {
+            let bits = rsa.key_size();
+            if bits < MIN_RSA_BITS {
+                status.issues.push(format!("weak RSA key ({} bits)", bits));
+            }
+            status.key = Some(format!("RSA {}", bits));
+        }
+        Ok(PublicKey::EC(ec)) => {
+            let bits = ec.key_size();
+            if bits < MIN_EC_BITS {
+                status.issues.push(format!("weak EC key ({} bits)", bits));
+            }
+            status.key = Some(format!("EC {}", bits));
+        }
+        Ok(_) => status.key = Some("other".to_string()),
+        Err(_) => status.issues.push("unknown public key type".to_string()),
+    }
+
+    let algorithm = &cert.signature_algorithm.algorithm;
+    let signature = oid2sn(algorithm, oid_registry())
+        .map(String::from)
+        .unwrap_or_else(|_| algorithm.to_id_string());
+    let lower = signature.to_lowercase();
+    if lower.contains("md5") || lower.contains("sha1") {
+        status.issues.push(format!("weak signature ({})", signature));
+    }
+    status.signature = Some(signature);
+
+    status
+}
+
+/// Inspect every certificate in a PEM bundle
+pub fn inspect_pem(source: &str, pem: &[u8], now: DateTime<Utc>, warn_days: i64) -> Vec<CertificateStatus> {
+    let certificates: Vec<Vec<u8>> = Pem::iter_from_buffer(pem)
+        .filter_map(|block| block.ok())
+        .filter(|block| block.label == "CERTIFICATE")
+        .map(|block| block.contents)
+        .collect();
+
+    match certificates.len() {
+        0 => vec![missing(source, "no PEM certificate found")],
+        1 => vec![inspect_der(source, &certificates[0], now, warn_days)],
+        _ => certificates
+            .iter()
+            .enumerate()
+            .map(|(i, der)| inspect_der(&format!("{} [{}]", source, i + 1), der, now, warn_days))
+            .collect(),
+    }
+}
+
+/// Status row for a certificate that could not be read
+pub fn missing(source: &str, reason: &str) -> CertificateStatus {
+    CertificateStatus {
+        source: source.to_string(),
+        subject: None,
+        issuer: None,
+        not_after: None,
+        days_left: None,
+        key: None,
+        signature: None,
+        issues: vec![reason.to_string()],
+    }
+}
+
+/// SHA-256 fingerprint of a DER certificate as colon-separated hex
+pub fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Wrap a DER certificate in PEM armor
+pub fn der_to_pem(der: &[u8]) -> String {
+    let encoded = BASE64.encode(der);
+    let mut pem = String::from("-----BEGIN CERTIFICATE-----\n");
+    for line in encoded.as_bytes().chunks(64) {
+        pem.push_str(&String::from_utf8_lossy(line));
+        pem.push('\n');
+    }
+    pem.push_str("-----END CERTIFICATE-----\n");
+    pem
+}
+
+/// Fetch the leaf certificate presented by a TLS endpoint
+///
+/// Verification is disabled on purpose: expired or self-signed certificates
+/// are exactly what the audit needs to see.
+pub async fn fetch_peer_certificate(url: &str, timeout: Duration) -> Result<Vec<u8>> {
+    let client = reqwest::Client::builder()
+        .tls_info(true)
+        .danger_accept_invalid_certs(true)
+        .timeout(timeout)
+        .build()
+        .context("Failed to build HTTP client")?;
+
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to connect to {}", url))?;
+
+    response
+        .extensions()
+        .get::<TlsInfo>()
+        .and_then(|info| info.peer_certificate())
+        .map(|der| der.to_vec())
+        .ok_or_else(|| anyhow!("{} did not present a TLS certificate", url))
+}
+
+/// Fetch every certificate a TLS endpoint presents, leaf first
+///
+/// Like [`fetch_peer_certificate`], nothing is verified; the chain is only
+/// recorded while the handshake runs.
+pub async fn fetch_peer_chain(url: &str, timeout: Duration) -> Result<Vec<Vec<u8>>> {
+    let recorder = Arc::new(ChainRecorder::default());
+    let tls = rustls::ClientConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .context("Failed to configure TLS")?
+    .dangerous()
+    .with_custom_certificate_verifier(recorder.clone())
+    .with_no_client_auth();
+    let client = reqwest::Client::builder()
+        .use_preconfigured_tls(tls)
+        .timeout(timeout)
+        .build()
+        .context("Failed to build HTTP client")?;
+
+    client
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to connect to {}", url))?;
+
+    let chain = recorder.chain.lock().map(|chain| chain.clone()).unwrap_or_default();
+    if chain.is_empty() {
+        return Err(anyhow!("{} did not present a TLS certificate", url));
+    }
+    Ok(chain)
+}
+
+/// The certificate to trust for a presented chain: the last issuer the server
+/// sent, or the leaf itself when it is self-signed
+///
+/// `None` when the server sent only a leaf signed by someone else, whose
+/// issuer has to be found elsewhere.
+pub fn trust_anchor(chain: &[Vec<u8>]) -> Option<&[u8]> {
+    match chain {
+        [leaf] => {
+            let (_, cert) = x509_parser::parse_x509_certificate(leaf).ok()?;
+            (cert.subject() == cert.issuer()).then_some(leaf.as_slice())
+        }
+        [_, .., issuer] => Some(issuer.as_slice()),
+        [] => None,
+    }
+}
+
+/// Accepts any server certificate, keeping the chain it was sent
+#[derive(Debug, Default)]
+struct ChainRecorder {
+    chain: Mutex<Vec<Vec<u8>>>,
+}
+
+impl ServerCertVerifier for ChainRecorder {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if let Ok(mut chain) = self.chain.lock() {
+            *chain = std::iter::once(end_entity)
+                .chain(intermediates)
+                .map(|der| der.to_vec())
+                .collect();
+        }
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        rustls::crypto::ring::default_provider()
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Self-signed RSA 1024 / SHA-1 certificate valid until 2126-09-22
+    const WEAK_CERT: &str = "-----BEGIN CERTIFICATE-----
+MIICCDCCAXGgAwIBAgIUJMWUo6/+nBG3LE4AZ/zDBhFrEUUwDQYJKoZIhvcNAQEF
+BQAwFTETMBEGA1UEAwwKd2F6dWgtdGVzdDAgFw0yNjEwMTYxMjU2MTlaGA8yMTI2
+MDkyMjEyNTYxOVowFTETMBEGA1UEAwwKd2F6dWgtdGVzdDCBnzANBgkqhkiG9w0B
+AQEFAAOBjQAwgYkCgYEAsfl/MPlCQazpovf0md4qW+DdUCZgGjBPg2dSAQHxjGED
+npJU4OQPCbvZd7tXwZoEI+ludTV9XhmsgwVooYrJd/em/PivOoX8Evh1zGFMkuOY
+gjL5urtzQFSUQBVCKnqkqcf3WT4FfQcNThU6eICDDKls6GMhEQYTptRpGIfKSr8C
+AwEAAaNTMFEwHQYDVR0OBBYEFO8JMqNnTE4qyyDiDVpnMHNfBsg6MB8GA1UdIwQY
+MBaAFO8JMqNnTE4qyyDiDVpnMHNfBsg6MA8GA1UdEwEB/wQFMAMBAf8wDQYJKoZI
+hvcNAQEFBQADgYEAm3uyli1hnVb0rW+5PD5Vg/lfMrrX6xEg1bzfQo3sw/ltZpcJ
+dNSJk7g9/RoGlG15spyVwPy4ywz+lKYWDhPmIK4R1+BHIc+aI2p1IE9UCTrK2O/j
+zQ5lts/GByLDbvW/HGZqcsaqy4IHenHV/OpahiykPvcgwVmQMSrL0Mk4kLM=
+-----END CERTIFICATE-----
+";
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_inspect_weak_certificate() {
+        let statuses = inspect_pem("server.crt", WEAK_CERT.as_bytes(), at("2026-10-16T00:00:00Z"), 30);
+        assert_eq!(statuses.len(), 1);
+
+        let status = &statuses[0];
+        assert_eq!(status.subject.as_deref(), Some("CN=wazuh-test"));
+        assert_eq!(status.key.as_deref(), Some("RSA 1024"));
+        assert!(status.issues.iter().any(|i| i.starts_with("weak RSA key")));
+        assert!(status.issues.iter().any(|i| i.starts_with("weak signature")));
+        assert!(!status.issues.iter().any(|i| i == "expired"));
+    }
+
+    #[test]
+    fn test_inspect_expiry() {
+        let expired = inspect_pem("server.crt", WEAK_CERT.as_bytes(), at("2127-01-01T00:00:00Z"), 30);
+        assert!(expired[0].issues.contains(&"expired".to_string()));
+        assert!(expired[0].days_left.unwrap() < 0);
+
+        let soon = inspect_pem("server.crt", WEAK_CERT.as_bytes(), at("2126-09-01T00:00:00Z"), 30);
+        assert!(soon[0].issues.iter().any(|i| i.starts_with("expires in")));
+
+        let none = inspect_pem("empty.pem", b"not a certificate", at("2026-01-01T00:00:00Z"), 30);
+        assert_eq!(none[0].issues, ["no PEM certificate found"]);
+    }
+
+    #[test]
+    fn test_pem_round_trip() {
+        let der = Pem::iter_from_buffer(WEAK_CERT.as_bytes())
+            .next()
+            .unwrap()
+            .unwrap()
+            .contents;
+        assert_eq!(der_to_pem(&der), WEAK_CERT);
+        assert_eq!(fingerprint(&der).split(':').count(), 32);
+    }
+
+    #[test]
+    fn test_trust_anchor() {
+        let der = Pem::iter_from_buffer(WEAK_CERT.as_bytes())
+            .next()
+            .unwrap()
+            .unwrap()
+            .contents;
+        // Self-signed, so the leaf is its own issuer
+        assert_eq!(trust_anchor(std::slice::from_ref(&der)), Some(der.as_slice()));
+
+        let chain = vec![b"leaf".to_vec(), b"intermediate".to_vec(), der.clone()];
+        assert_eq!(trust_anchor(&chain), Some(der.as_slice()));
+
+        assert_eq!(trust_anchor(&[b"not a certificate".to_vec()]), None);
+        assert_eq!(trust_anchor(&[]), None);
+    }
+}
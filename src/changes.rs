@@ -0,0 +1,211 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::config::Config;
+use crate::diff;
+use crate::lock::{self, Resource};
+
+const CHANGES_DIR: &str = "changes";
+
+/// Changes staged by this process, so IDs made in the same instant differ
+static STAGED: AtomicU32 = AtomicU32::new(0);
+
+/// A manager configuration change staged for review before it is pushed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StagedChange {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub author: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub source_file: PathBuf,
+    /// Checksum of the manager's configuration when the change was staged
+    pub base_checksum: String,
+    /// Checksum of the staged content
+    pub checksum: String,
+    pub diff: String,
+    pub content: String,
+    pub status: ChangeStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approver: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeStatus {
+    Pending,
+    Applied,
+    Discarded,
+}
+
+impl fmt::Display for ChangeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeStatus::Pending => write!(f, "Pending"),
+            ChangeStatus::Applied => write!(f, "Applied"),
+            ChangeStatus::Discarded => write!(f, "Discarded"),
+        }
+    }
+}
+
+impl StagedChange {
+    /// Stage `content` as the replacement for the manager's `current` configuration
+    pub fn new(
+        source_file: PathBuf,
+        current: &str,
+        content: String,
+        message: Option<String>,
+    ) -> Self {
+        let created_at = Utc::now();
+        Self {
+            id: change_id(created_at, &content),
+            created_at,
+            author: std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .unwrap_or_else(|_| "unknown".to_string()),
+            message,
+            base_checksum: checksum(current),
+            checksum: checksum(&content),
+            diff: diff::unified(
+                current,
+                &content,
+                "manager/ossec.conf",
+                &source_file.display().to_string(),
+            ),
+            source_file,
+            content,
+            status: ChangeStatus::Pending,
+            approver: None,
+            applied_at: None,
+        }
+    }
+
+    /// Whether the stored content still matches the checksum recorded at staging
+    pub fn is_intact(&self) -> bool {
+        checksum(&self.content) == self.checksum
+    }
+}
+
+/// A timestamp readable in listings plus a suffix that tells apart changes
+/// staged in the same second, by this or another process
+fn change_id(created_at: DateTime<Utc>, content: &str) -> String {
+    let seed = format!(
+        "{} {} {} {}",
+        created_at.timestamp_nanos_opt().unwrap_or_default(),
+        std::process::id(),
+        STAGED.fetch_add(1, Ordering::Relaxed),
+        content
+    );
+    format!("{}-{}", created_at.format("%Y%m%d-%H%M%S"), &checksum(&seed)[..6])
+}
+
+/// Refuse IDs that would reach outside the changes directory
+fn validate_id(id: &str) -> Result<()> {
+    if id.is_empty() || id.contains(['/', '\\']) || id.contains("..") {
+        return Err(anyhow!("Invalid change ID '{}'", id));
+    }
+    Ok(())
+}
+
+/// SHA-256 of a configuration as lowercase hex
+pub fn checksum(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// Directory holding staged changes, one JSON file per change
+pub fn changes_dir() -> Result<PathBuf> {
+    Ok(Config::data_dir()?.join(CHANGES_DIR))
+}
+
+/// Write a change to disk, replacing any previous version
+pub fn save(change: &StagedChange) -> Result<()> {
+    let dir = changes_dir()?;
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create changes directory: {:?}", dir))?;
+
+    let path = dir.join(format!("{}.json", change.id));
+    let _lock = lock::acquire(Resource::Changes)?;
+    fs::write(&path, serde_json::to_string_pretty(change)?)
+        .with_context(|| format!("Failed to write change file: {:?}", path))?;
+
+    Ok(())
+}
+
+/// Load a change by ID
+pub fn load(id: &str) -> Result<StagedChange> {
+    validate_id(id)?;
+    let path = changes_dir()?.join(format!("{}.json", id));
+    if !path.exists() {
+        return Err(anyhow!("No staged change with ID '{}'", id));
+    }
+
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read change file: {:?}", path))?;
+    serde_json::from_str(&contents).with_context(|| format!("Failed to parse change file: {:?}", path))
+}
+
+/// Load every change, oldest first
+pub fn list() -> Result<Vec<StagedChange>> {
+    let dir = changes_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut changes = Vec::new();
+    for entry in fs::read_dir(&dir).with_context(|| format!("Failed to read {:?}", dir))? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            if let Some(id) = path.file_stem().and_then(|s| s.to_str()) {
+                changes.push(load(id)?);
+            }
+        }
+    }
+    changes.sort_by_key(|change| change.created_at);
+
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_staged_change() {
+        let current = "<ossec_config>\n  <global>\n    <logall>no</logall>\n  </global>\n</ossec_config>\n";
+        let updated = current.replace("<logall>no</logall>", "<logall>yes</logall>");
+
+        let change = StagedChange::new(PathBuf::from("ossec.conf"), current, updated, None);
+        assert_eq!(change.status, ChangeStatus::Pending);
+        assert_eq!(change.base_checksum, checksum(current));
+        assert!(change.is_intact());
+        assert!(change.diff.contains("-    <logall>no</logall>"));
+        assert!(change.diff.contains("+    <logall>yes</logall>"));
+
+        let mut tampered = change.clone();
+        tampered.content.push_str("<!-- extra -->");
+        assert!(!tampered.is_intact());
+    }
+
+    #[test]
+    fn test_change_ids() {
+        let now = Utc::now();
+        let first = change_id(now, "<ossec_config/>");
+        let second = change_id(now, "<ossec_config/>");
+        assert_ne!(first, second);
+        assert!(first.starts_with(&now.format("%Y%m%d-%H%M%S").to_string()));
+        assert!(validate_id(&first).is_ok());
+
+        assert!(validate_id("../config").is_err());
+        assert!(validate_id("a/b").is_err());
+        assert!(validate_id("a\\b").is_err());
+        assert!(validate_id("").is_err());
+    }
+}
@@ -0,0 +1,313 @@
+//! Agent check-in snapshots and the anomaly heuristics run over them.
+//!
+//! Every `agent anomalies` run records the status of each agent and the
+//! active count of each group, so the history builds up when the command is
+//! scheduled (e.g. hourly from cron). A group whose active count falls far
+//! below its baseline usually means a dead collector or site link; agents
+//! switching status many times within the window are flapping.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::lock::{self, Resource};
+use crate::models::{Agent, AgentStatus};
+
+const CHECKINS_FILE: &str = "checkins.jsonl";
+
+/// Snapshots kept per file; older ones are dropped when a new one is recorded
+const MAX_SNAPSHOTS: usize = 500;
+
+/// Snapshots needed before a baseline is meaningful
+pub const MIN_BASELINE: usize = 3;
+
+/// Agents counted in a group at one point in time
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GroupCounts {
+    pub total: u32,
+    pub active: u32,
+}
+
+/// Fleet check-in state at one point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckinSnapshot {
+    pub taken_at: DateTime<Utc>,
+    /// API URL of the manager, so profiles do not mix their histories
+    pub source: String,
+    pub groups: BTreeMap<String, GroupCounts>,
+    /// Status of each agent by ID
+    pub statuses: BTreeMap<String, AgentStatus>,
+}
+
+impl CheckinSnapshot {
+    pub fn from_agents(source: &str, agents: &[Agent], taken_at: DateTime<Utc>) -> Self {
+        let mut groups: BTreeMap<String, GroupCounts> = BTreeMap::new();
+        let mut statuses = BTreeMap::new();
+        for agent in agents {
+            statuses.insert(agent.id.clone(), agent.status.clone());
+            for group in agent.group.iter().flatten() {
+                let counts = groups.entry(group.clone()).or_default();
+                counts.total += 1;
+                if agent.status == AgentStatus::Active {
+                    counts.active += 1;
+                }
+            }
+        }
+        Self {
+            taken_at,
+            source: source.to_string(),
+            groups,
+            statuses,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    /// A group has far fewer active agents than usual
+    ActiveDrop,
+    /// An agent keeps switching between statuses
+    Flapping,
+}
+
+impl std::fmt::Display for AnomalyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnomalyKind::ActiveDrop => write!(f, "Active drop"),
+            AnomalyKind::Flapping => write!(f, "Flapping"),
+        }
+    }
+}
+
+/// A deviation from the recorded baseline, with the numbers behind it
+#[derive(Debug, Clone, Serialize)]
+pub struct Anomaly {
+    pub kind: AnomalyKind,
+    /// Group name or agent ID
+    pub subject: String,
+    pub detail: String,
+}
+
+/// Thresholds of the heuristics
+#[derive(Debug, Clone, Copy)]
+pub struct Sensitivity {
+    /// Standard deviations below the mean that count as a drop
+    pub deviations: f64,
+    /// Smallest relative drop reported, so stable groups with no variance
+    /// are not flagged for losing a single agent
+    pub min_drop: f64,
+    /// Status changes within the window that make an agent flapping
+    pub flap_changes: usize,
+}
+
+fn checkins_path() -> Result<PathBuf> {
+    Ok(Config::data_dir()?.join(CHECKINS_FILE))
+}
+
+/// Recorded snapshots of a manager, oldest first
+pub fn load(source: &str) -> Result<Vec<CheckinSnapshot>> {
+    Ok(load_all()?.into_iter().filter(|s| s.source == source).collect())
+}
+
+fn load_all() -> Result<Vec<CheckinSnapshot>> {
+    let path = checkins_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read check-in history: {:?}", path))?;
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Append a snapshot, dropping the oldest beyond `MAX_SNAPSHOTS`
+pub fn record(snapshot: &CheckinSnapshot) -> Result<()> {
+    let path = checkins_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create data directory: {:?}", parent))?;
+    }
+
+    let _lock = lock::acquire(Resource::Checkins)?;
+    let mut snapshots = load_all()?;
+    snapshots.push(snapshot.clone());
+    let skip = snapshots.len().saturating_sub(MAX_SNAPSHOTS);
+
+    let mut contents = String::new();
+    for snapshot in &snapshots[skip..] {
+        contents.push_str(&serde_json::to_string(snapshot)?);
+        contents.push('\n');
+    }
+    // Write aside and rename so a crash never leaves a truncated history
+    let temp = path.with_extension("jsonl.tmp");
+    fs::write(&temp, contents)
+        .with_context(|| format!("Failed to write check-in history: {:?}", temp))?;
+    fs::rename(&temp, &path)
+        .with_context(|| format!("Failed to replace check-in history: {:?}", path))
+}
+
+/// Groups whose active count is far below the mean of the baseline snapshots
+pub fn detect_drops(
+    baseline: &[CheckinSnapshot],
+    current: &CheckinSnapshot,
+    sensitivity: &Sensitivity,
+) -> Vec<Anomaly> {
+    if baseline.len() < MIN_BASELINE {
+        return Vec::new();
+    }
+
+    let mut anomalies = Vec::new();
+    for (group, counts) in &current.groups {
+        let history: Vec<f64> = baseline
+            .iter()
+            .filter_map(|snapshot| snapshot.groups.get(group))
+            .map(|counts| counts.active as f64)
+            .collect();
+        if history.len() < MIN_BASELINE {
+            continue;
+        }
+
+        let mean = history.iter().sum::<f64>() / history.len() as f64;
+        let variance =
+            history.iter().map(|n| (n - mean).powi(2)).sum::<f64>() / history.len() as f64;
+        let stddev = variance.sqrt();
+        let active = counts.active as f64;
+        let drop = if mean > 0.0 { (mean - active) / mean } else { 0.0 };
+
+        if active < mean - sensitivity.deviations * stddev && drop >= sensitivity.min_drop {
+            anomalies.push(Anomaly {
+                kind: AnomalyKind::ActiveDrop,
+                subject: group.clone(),
+                detail: format!(
+                    "{} of {} agents active, baseline {:.1} ± {:.1} over {} snapshots \
+                     ({:.0}% drop)",
+                    counts.active,
+                    counts.total,
+                    mean,
+                    stddev,
+                    history.len(),
+                    drop * 100.0
+                ),
+            });
+        }
+    }
+    anomalies
+}
+
+/// Agents whose status changed at least `flap_changes` times across the snapshots
+pub fn detect_flapping(
+    baseline: &[CheckinSnapshot],
+    current: &CheckinSnapshot,
+    sensitivity: &Sensitivity,
+) -> Vec<Anomaly> {
+    let mut anomalies = Vec::new();
+    for (agent_id, status) in &current.statuses {
+        let mut sequence: Vec<&AgentStatus> = baseline
+            .iter()
+            .filter_map(|snapshot| snapshot.statuses.get(agent_id))
+            .collect();
+        sequence.push(status);
+        let changes = sequence.windows(2).filter(|pair| pair[0] != pair[1]).count();
+
+        if changes >= sensitivity.flap_changes {
+            let first = baseline
+                .iter()
+                .find(|snapshot| snapshot.statuses.contains_key(agent_id))
+                .map(|snapshot| snapshot.taken_at)
+                .unwrap_or(current.taken_at);
+            anomalies.push(Anomaly {
+                kind: AnomalyKind::Flapping,
+                subject: agent_id.clone(),
+                detail: format!(
+                    "{} status changes across {} snapshots since {}, now {}",
+                    changes,
+                    sequence.len(),
+                    first.format("%Y-%m-%d %H:%M UTC"),
+                    status
+                ),
+            });
+        }
+    }
+    anomalies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn snapshot(hours_ago: i64, web_active: u32, agent_status: AgentStatus) -> CheckinSnapshot {
+        let mut groups = BTreeMap::new();
+        groups.insert(
+            "web".to_string(),
+            GroupCounts {
+                total: 50,
+                active: web_active,
+            },
+        );
+        groups.insert("db".to_string(), GroupCounts { total: 4, active: 4 });
+        let mut statuses = BTreeMap::new();
+        statuses.insert("001".to_string(), agent_status);
+        statuses.insert("002".to_string(), AgentStatus::Active);
+        CheckinSnapshot {
+            taken_at: Utc::now() - Duration::hours(hours_ago),
+            source: "https://wazuh:55000".to_string(),
+            groups,
+            statuses,
+        }
+    }
+
+    const SENSITIVITY: Sensitivity = Sensitivity {
+        deviations: 3.0,
+        min_drop: 0.2,
+        flap_changes: 4,
+    };
+
+    #[test]
+    fn test_detect_drops() {
+        let baseline: Vec<CheckinSnapshot> = [48, 49, 47, 48]
+            .iter()
+            .enumerate()
+            .map(|(i, active)| snapshot(4 - i as i64, *active, AgentStatus::Active))
+            .collect();
+
+        let current = snapshot(0, 12, AgentStatus::Active);
+        let drops = detect_drops(&baseline, &current, &SENSITIVITY);
+        assert_eq!(drops.len(), 1);
+        assert_eq!(drops[0].subject, "web");
+
+        // A small dip stays within the baseline
+        let current = snapshot(0, 46, AgentStatus::Active);
+        assert!(detect_drops(&baseline, &current, &SENSITIVITY).is_empty());
+        // Too little history for a baseline
+        assert!(detect_drops(&baseline[..2], &current, &SENSITIVITY).is_empty());
+    }
+
+    #[test]
+    fn test_detect_flapping() {
+        let statuses = [
+            AgentStatus::Active,
+            AgentStatus::Disconnected,
+            AgentStatus::Active,
+            AgentStatus::Disconnected,
+        ];
+        let baseline: Vec<CheckinSnapshot> = statuses
+            .iter()
+            .enumerate()
+            .map(|(i, status)| snapshot(4 - i as i64, 48, status.clone()))
+            .collect();
+
+        let current = snapshot(0, 48, AgentStatus::Active);
+        let flapping = detect_flapping(&baseline, &current, &SENSITIVITY);
+        assert_eq!(flapping.len(), 1);
+        assert_eq!(flapping[0].subject, "001");
+    }
+}
@@ -1,11 +1,18 @@
-use clap::{Parser, Subcommand};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, CommandFactory, Parser, Subcommand};
+use clap_complete::engine::ArgValueCandidates;
+use std::ffi::OsString;
 use std::path::PathBuf;
+use std::process::Command;
+use std::sync::OnceLock;
+
+use crate::completion;
+use crate::storage::Destination;
 
 #[derive(Parser)]
 #[command(
     name = "wazuh-cli",
     author = "Security Engineer",
-    version,
     about = "Modern CLI for Wazuh SIEM management",
     long_about = "A powerful command-line interface for managing Wazuh security platform.\n\
                   Supports agent management, service control, and configuration."
@@ -15,14 +22,119 @@ pub struct Cli {
     #[arg(short, long, value_name = "FILE", default_value = "~/.wazuh-cli/config.toml")]
     pub config: PathBuf,
 
-    /// Output format (json or table)
-    #[arg(short, long, default_value = "table")]
+    /// Settings profile (a [profiles.NAME] section) to use; overrides current_profile
+    #[arg(
+        short = 'P',
+        long,
+        value_name = "NAME",
+        env = "WAZUH_PROFILE",
+        add = ArgValueCandidates::new(completion::profiles)
+    )]
+    pub profile: Option<String>,
+
+    /// Output format: table, json, or ndjson (one JSON object per line for list commands)
+    #[arg(
+        short,
+        long,
+        visible_alias = "format",
+        default_value = "table",
+        value_parser = ["table", "json", "ndjson"]
+    )]
     pub output: String,
 
     /// Enable JSON output
     #[arg(short = 'j', long)]
     pub json: bool,
 
+    /// Print each item through a template instead, e.g. '{{.id}}\t{{.name}} {{.status}}'
+    #[arg(long, value_name = "TEMPLATE", conflicts_with = "envelope")]
+    pub template: Option<String>,
+
+    /// Write command output to this file instead of stdout, replacing it only on success
+    #[arg(long, value_name = "PATH")]
+    pub output_file: Option<PathBuf>,
+
+    /// Comma-separated table columns to show, in order (e.g. id,name,ip,status)
+    #[arg(long, value_delimiter = ',', value_name = "COLUMNS")]
+    pub fields: Vec<String>,
+
+    /// Stop fetching list results after N items (by default all pages are fetched)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_results: Option<u32>,
+
+    /// Wrap JSON output in {data, warnings, error} (implies --json)
+    #[arg(long)]
+    pub envelope: bool,
+
+    /// JSON output format version to emit; overrides output.format_version for this run
+    #[arg(long, value_name = "N")]
+    pub format_version: Option<u32>,
+
+    /// Diff layout: unified or side-by-side; overrides output.diff_style for this run
+    #[arg(long, value_name = "STYLE")]
+    pub diff_style: Option<String>,
+
+    /// Unchanged lines around each change in diffs; overrides output.diff_context.
+    /// Diffs saved earlier keep at most the context they were made with
+    #[arg(long, value_name = "N")]
+    pub diff_context: Option<usize>,
+
+    /// Disable colors in output (also NO_COLOR or output.color = false)
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Screen-reader friendly output (labeled lines instead of tables, no spinners or colors)
+    #[arg(long)]
+    pub a11y: bool,
+
+    /// Do not ask before fetching large result sets
+    #[arg(long)]
+    pub no_prompt: bool,
+
+    /// Extra header sent with every API request, as 'Name: value' (repeatable)
+    #[arg(short = 'H', long = "header", value_name = "HEADER")]
+    pub headers: Vec<String>,
+
+    /// Request timeout in seconds for this run, long-running endpoints included;
+    /// overrides api.timeout and api.long_timeout
+    #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub timeout: Option<u64>,
+
+    /// TLS profile (a [tls.profiles.NAME] section) used for every connection; overrides tls.profile
+    #[arg(long, value_name = "NAME", add = ArgValueCandidates::new(completion::tls_profiles))]
+    pub tls_profile: Option<String>,
+
+    /// API token (JWT) to use instead of logging in with a username and password
+    #[arg(long, value_name = "TOKEN", env = "WAZUH_TOKEN", hide_env_values = true)]
+    pub token: Option<String>,
+
+    /// Log in with run_as, sending this JSON authorization context for RBAC role mapping
+    #[arg(long, value_name = "FILE")]
+    pub auth_context: Option<PathBuf>,
+
+    /// Wait for local state locked by another wazuh-cli instance instead of giving up after 10s
+    #[arg(long, conflicts_with = "no_lock")]
+    pub wait_lock: bool,
+
+    /// Do not lock local state (config, staged changes, history) while writing it
+    #[arg(long)]
+    pub no_lock: bool,
+
+    /// Progress reporting: spinners, or NDJSON events on stderr for automation
+    #[arg(long, value_name = "MODE", default_value = "spinner", value_parser = ["spinner", "json"])]
+    pub progress: String,
+
+    /// Write every API request and response to FILE, or to stderr without one
+    /// (credentials are redacted)
+    #[arg(
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "-"
+    )]
+    pub trace_http: Option<PathBuf>,
+
     /// Verbosity level (can be repeated)
     #[arg(short, long, action = clap::ArgAction::Count)]
     pub verbose: u8,
@@ -41,6 +153,14 @@ pub enum Commands {
     #[command(aliases = &["agents", "a"])]
     Agent(AgentCommand),
 
+    /// Run active-response commands on agents
+    #[command(aliases = &["active-response"])]
+    Ar(ArCommand),
+
+    /// Follow agent upgrades and other tasks run by the manager
+    #[command(aliases = &["task"])]
+    Tasks(TasksCommand),
+
     /// Control Wazuh services
     #[command(aliases = &["ctl", "c"])]
     Control(ControlCommand),
@@ -49,17 +169,330 @@ pub enum Commands {
     #[command(aliases = &["cfg"])]
     Config(ConfigCommand),
 
+    /// Log in and out, keeping credentials in the OS credential store
+    Auth(AuthCommand),
+
+    /// Inspect the manager cluster
+    Cluster(ClusterCommand),
+
+    /// Browse the ruleset
+    #[command(aliases = &["rule"])]
+    Rules(RulesCommand),
+
+    /// Browse the decoders that parse incoming events
+    #[command(aliases = &["decoder"])]
+    Decoders(DecodersCommand),
+
+    /// Manage CDB lists used by rules (e.g. blocked IPs)
+    #[command(aliases = &["list", "cdb"])]
+    Lists(ListsCommand),
+
+    /// Test log lines against the ruleset without raising alerts
+    Logtest(LogtestCommand),
+
+    /// Look up MITRE ATT&CK techniques, tactics, groups and software
+    Mitre(MitreCommand),
+
+    /// Search alerts stored in the indexer
+    #[command(aliases = &["alert"])]
+    Alerts(AlertsCommand),
+
+    /// Search raw events archived in the indexer
+    #[command(aliases = &["archive"])]
+    Archives(ArchivesCommand),
+
+    /// Generate reports
+    #[command(aliases = &["reports"])]
+    Report(ReportCommand),
+
+    /// Watch the manager and notify when thresholds are crossed
+    Monitor(MonitorCommand),
+
+    /// Audit security settings
+    #[command(aliases = &["sec"])]
+    Security(SecurityCommand),
+
+    /// Compare the manager configuration against a stored baseline
+    Drift(DriftCommand),
+
+    /// Manage agent groups, the same as `agent group`
+    #[command(aliases = &["groups"])]
+    Group(GroupCommand),
+
+    /// Query and run file integrity monitoring (syscheck)
+    #[command(aliases = &["syscheck"])]
+    Fim(FimCommand),
+
+    /// Query and run rootkit and policy checks (rootcheck)
+    Rootcheck(RootcheckCommand),
+
+    /// Query vulnerabilities detected on agents
+    #[command(aliases = &["vulns", "vulnerability"])]
+    Vuln(VulnCommand),
+
+    /// Inject events into analysisd, e.g. to test rules or replay logs
+    #[command(aliases = &["event"])]
+    Events(EventsCommand),
+
+    /// Query agent inventory collected by syscollector
+    #[command(aliases = &["inv"])]
+    Inventory(InventoryCommand),
+
+    /// Export fleet metadata for offline analysis
+    Export(ExportCommand),
+
+    /// Inspect indices in the Wazuh indexer
+    Indexer(IndexerCommand),
+
+    /// Local command usage statistics (opt-in, never sent anywhere)
+    Stats(StatsCommand),
+
+    /// Show which local state locks are held, and by which process
+    Locks,
+
+    /// Manage the cache behind shell completion of agents, groups and rules
+    Completion(CompletionCommand),
+
+    /// End-to-end checks of a running deployment
+    Selftest(SelftestCommand),
+
     /// Start interactive mode
     #[command(aliases = &["i", "shell"])]
     Interactive,
 }
 
+/// Global flags that choose the manager and how to reach it, passed on to the
+/// `wazuh-cli` processes a run starts (command substitutions, completion
+/// refreshes) so they talk to the same manager
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionFlags {
+    args: Vec<OsString>,
+    /// Sent through the environment, where other users cannot read it
+    token: Option<String>,
+}
+
+impl ConnectionFlags {
+    pub fn apply(&self, command: &mut Command) {
+        command.args(&self.args);
+        if let Some(token) = &self.token {
+            command.env("WAZUH_TOKEN", token);
+        }
+    }
+}
+
+/// Connection flags of this run, set once by main
+static CONNECTION_FLAGS: OnceLock<ConnectionFlags> = OnceLock::new();
+
+pub fn set_connection_flags(flags: ConnectionFlags) {
+    let _ = CONNECTION_FLAGS.set(flags);
+}
+
+pub fn connection_flags() -> ConnectionFlags {
+    CONNECTION_FLAGS.get().cloned().unwrap_or_default()
+}
+
+impl Cli {
+    pub fn output_flags(&self) -> OutputFlags {
+        OutputFlags {
+            output: self.output.clone(),
+            json: self.json,
+            template: self.template.clone(),
+            fields: self.fields.clone(),
+        }
+    }
+
+    pub fn connection_flags(&self) -> ConnectionFlags {
+        let mut args: Vec<OsString> = Vec::new();
+        if let Some(profile) = &self.profile {
+            args.extend(["--profile".into(), profile.into()]);
+        }
+        if let Some(profile) = &self.tls_profile {
+            args.extend(["--tls-profile".into(), profile.into()]);
+        }
+        if let Some(secs) = self.timeout {
+            args.extend(["--timeout".into(), secs.to_string().into()]);
+        }
+        if let Some(path) = &self.auth_context {
+            args.extend(["--auth-context".into(), path.into()]);
+        }
+        for header in &self.headers {
+            args.extend(["--header".into(), header.into()]);
+        }
+        ConnectionFlags {
+            args,
+            token: self.token.clone(),
+        }
+    }
+}
+
+/// Global flags a line of the interactive shell or `default_command` may give
+/// for itself; every other global flag is fixed for the whole run
+const LINE_FLAGS: &[&str] = &["output", "json", "template", "fields"];
+
+/// The first global flag given in `matches` that is fixed for the whole run
+pub fn run_wide_flag(matches: &ArgMatches) -> Option<String> {
+    Cli::command()
+        .get_arguments()
+        .filter(|arg| !LINE_FLAGS.contains(&arg.get_id().as_str()))
+        .find(|arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine))
+        .map(|arg| match (arg.get_long(), arg.get_short()) {
+            (Some(long), _) => format!("--{}", long),
+            (None, short) => format!("-{}", short.unwrap_or_default()),
+        })
+}
+
+/// Output flags, which each line of the interactive shell may choose
+#[derive(Debug, Clone)]
+pub struct OutputFlags {
+    pub output: String,
+    pub json: bool,
+    pub template: Option<String>,
+    pub fields: Vec<String>,
+}
+
+impl Default for OutputFlags {
+    fn default() -> Self {
+        OutputFlags {
+            output: "table".to_string(),
+            json: false,
+            template: None,
+            fields: Vec::new(),
+        }
+    }
+}
+
+impl OutputFlags {
+    /// These flags, taking any not given in `matches` from the session's
+    pub fn or_session(self, matches: &ArgMatches, session: &OutputFlags) -> OutputFlags {
+        let given = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+        OutputFlags {
+            output: if given("output") { self.output } else { session.output.clone() },
+            json: self.json || session.json,
+            template: if given("template") { self.template } else { session.template.clone() },
+            fields: if given("fields") { self.fields } else { session.fields.clone() },
+        }
+    }
+
+    /// Whether these flags ask for machine-readable output
+    pub fn json_output(&self) -> bool {
+        self.json || self.output != "table" || self.template.is_some()
+    }
+}
+
+/// Output flags of this run, set once by main
+static OUTPUT_FLAGS: OnceLock<OutputFlags> = OnceLock::new();
+
+pub fn set_output_flags(flags: OutputFlags) {
+    let _ = OUTPUT_FLAGS.set(flags);
+}
+
+pub fn output_flags() -> OutputFlags {
+    OUTPUT_FLAGS.get().cloned().unwrap_or_default()
+}
+
+impl Commands {
+    /// Commands that only read local state and never call the API
+    pub fn is_local(&self) -> bool {
+        matches!(self, Commands::Locks | Commands::Stats(_))
+    }
+
+    /// Read-only listings and logs, whose output may be long enough for a pager;
+    /// commands that prompt, follow or redraw are never paged
+    pub fn pageable(&self) -> bool {
+        matches!(
+            self,
+            Commands::Agent(AgentCommand {
+                action: AgentAction::List { .. } | AgentAction::Versions { .. },
+            })
+                | Commands::Rules(RulesCommand {
+                    action: RulesAction::List { .. }
+                        | RulesAction::Search { .. }
+                        | RulesAction::Groups
+                        | RulesAction::Requirements { .. },
+                })
+                | Commands::Decoders(DecodersCommand {
+                    action: DecodersAction::List { .. } | DecodersAction::Files { .. },
+                })
+                | Commands::Lists(ListsCommand {
+                    action: ListsAction::Show { .. } | ListsAction::Get { .. },
+                })
+                | Commands::Mitre(_)
+                | Commands::Alerts(AlertsCommand {
+                    action: AlertsAction::Search { .. }
+                        | AlertsAction::Stats { .. }
+                        | AlertsAction::Sample { .. },
+                })
+                | Commands::Archives(_)
+                | Commands::Control(ControlCommand {
+                    action: ControlAction::Logs { follow: false, .. },
+                })
+                | Commands::Tasks(TasksCommand {
+                    action: TasksAction::List { watch: false, .. },
+                })
+                | Commands::Drift(DriftCommand {
+                    action: DriftAction::Check { .. },
+                })
+                | Commands::Fim(FimCommand {
+                    action: FimAction::List { .. },
+                })
+                | Commands::Rootcheck(RootcheckCommand {
+                    action: RootcheckAction::List { .. },
+                })
+                | Commands::Vuln(VulnCommand {
+                    action: VulnAction::List { .. },
+                })
+        )
+    }
+}
+
 #[derive(Parser)]
 pub struct AgentCommand {
     #[command(subcommand)]
     pub action: AgentAction,
 }
 
+/// Ordering and paging flags passed through to API list endpoints
+#[derive(Args, Debug, Clone, Default)]
+pub struct ListArgs {
+    /// Sort by fields, '-' for descending (e.g. -dateAdd or +name,-id)
+    #[arg(long, value_name = "FIELDS")]
+    pub sort: Option<String>,
+
+    /// Maximum number of items to return (default: all)
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..=100000))]
+    pub limit: Option<u32>,
+
+    /// Number of items to skip
+    #[arg(long)]
+    pub offset: Option<u32>,
+
+    /// Only items with this text in any field ('-' prefix to exclude)
+    #[arg(long, value_name = "TEXT")]
+    pub search: Option<String>,
+}
+
+/// Target agents by group, platform or API query instead of by ID
+#[derive(Args, Debug, Clone, Default)]
+pub struct AgentFilterArgs {
+    /// Only agents in this group
+    #[arg(long, value_name = "GROUP", add = ArgValueCandidates::new(completion::groups))]
+    pub group: Option<String>,
+
+    /// Only agents on this OS platform (e.g. ubuntu, windows, darwin)
+    #[arg(long, value_name = "PLATFORM")]
+    pub os: Option<String>,
+
+    /// Only agents matching a Wazuh API query, e.g. 'version<4.8.0;status=active'
+    #[arg(long, value_name = "Q")]
+    pub query: Option<String>,
+}
+
+impl AgentFilterArgs {
+    pub fn is_set(&self) -> bool {
+        self.group.is_some() || self.os.is_some() || self.query.is_some()
+    }
+}
+
 #[derive(Subcommand)]
 pub enum AgentAction {
     /// List all agents
@@ -77,16 +510,57 @@ pub enum AgentAction {
         #[arg(short = 'v', long)]
         version: Option<String>,
 
+        /// Filter by agent group
+        #[arg(short, long, add = ArgValueCandidates::new(completion::groups))]
+        group: Option<String>,
+
+        /// Only agents matching a selector, e.g. 'status==active && last_keep_alive > 1h'
+        #[arg(long, value_name = "EXPR")]
+        select: Option<String>,
+
         /// Show only agent count
         #[arg(short, long)]
         count: bool,
+
+        /// Left-join the agents with a local CSV file (e.g. a CMDB export)
+        #[arg(long, value_name = "FILE", requires = "add_columns")]
+        join: Option<PathBuf>,
+
+        /// Join key: an agent field matching a CSV column, or FIELD=COLUMN
+        #[arg(long, default_value = "name", requires = "join")]
+        on: String,
+
+        /// CSV columns to add to the output
+        #[arg(long, value_delimiter = ',', requires = "join")]
+        add_columns: Vec<String>,
+
+        #[command(flatten)]
+        list: ListArgs,
     },
 
     /// Show agent details
     #[command(aliases = &["info", "show", "i"])]
     Get {
         /// Agent ID or name
+        #[arg(add = ArgValueCandidates::new(completion::agents))]
+        agent: String,
+    },
+
+    /// Show the configuration an agent is running (e.g. logcollector localfile)
+    Config {
+        /// Agent ID or name
+        #[arg(add = ArgValueCandidates::new(completion::agents))]
         agent: String,
+
+        /// Component of the agent
+        #[arg(value_parser = [
+            "agent", "agentless", "analysis", "auth", "com", "csyslog", "integrator",
+            "logcollector", "mail", "monitor", "request", "syscheck", "wazuh-db", "wmodules",
+        ])]
+        component: String,
+
+        /// Configuration of the component (e.g. localfile, syscheck, client)
+        configuration: String,
     },
 
     /// Add a new agent
@@ -105,27 +579,152 @@ pub enum AgentAction {
         force: bool,
     },
 
-    /// Remove an agent
+    /// Remove agents
     #[command(aliases = &["rm", "del", "delete"])]
     Remove {
+        /// Agent IDs or names, space- or comma-separated
+        #[arg(
+            value_delimiter = ',',
+            required_unless_present_any = ["select", "group", "os", "query"],
+            conflicts_with_all = ["group", "os", "query"],
+            add = ArgValueCandidates::new(completion::agents)
+        )]
+        agents: Vec<String>,
+
+        /// Remove every agent matching a selector instead
+        #[arg(long, value_name = "EXPR", conflicts_with = "agents")]
+        select: Option<String>,
+
+        #[command(flatten)]
+        filter: AgentFilterArgs,
+
+        /// Removal requests of up to 500 agents sent at the same time
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 10,
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        parallel: usize,
+
+        /// Skip confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// Remove stale agents, e.g. disconnected for more than 30 days
+    ///
+    /// Matching agents are listed and removed after confirmation. Deletion
+    /// repeats the status and age filter, so an agent that reconnects in the
+    /// meantime is kept.
+    Prune {
+        /// Status of the agents to remove
+        #[arg(
+            long,
+            default_value = "disconnected",
+            value_parser = ["disconnected", "never_connected", "pending"]
+        )]
+        status: String,
+
+        /// Only agents last seen longer ago than this positive span (e.g. 30d);
+        /// for agents that never connected, since they were added
+        #[arg(long, value_name = "DURATION")]
+        older_than: String,
+
+        /// Also remove agents that never connected
+        #[arg(long)]
+        never_connected: bool,
+
+        /// Show the agents that would be removed without removing them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// Rename an agent, keeping its ID, key and groups
+    ///
+    /// The manager cannot rename agents in place: the agent is removed and
+    /// re-inserted under the new name. Manager-side state (FIM, inventory, SCA)
+    /// is rebuilt by the next scans, and the agent's own client.keys keeps the
+    /// old name until updated with `agent key push`.
+    Rename {
         /// Agent ID or name
+        #[arg(add = ArgValueCandidates::new(completion::agents))]
         agent: String,
 
+        /// New agent name
+        new_name: String,
+
+        /// Show the plan without changing anything
+        #[arg(long)]
+        dry_run: bool,
+
         /// Skip confirmation
         #[arg(short = 'y', long)]
         yes: bool,
     },
 
-    /// Restart an agent
+    /// Restart agents
     Restart {
-        /// Agent ID, name, or "all"
-        agent: String,
+        /// Agent IDs or names, space- or comma-separated, or "all"
+        #[arg(
+            value_delimiter = ',',
+            required_unless_present_any = ["select", "group", "os", "query"],
+            conflicts_with_all = ["group", "os", "query"],
+            add = ArgValueCandidates::new(completion::agents)
+        )]
+        agents: Vec<String>,
+
+        /// Restart every agent matching a selector instead
+        #[arg(long, value_name = "EXPR", conflicts_with = "agents")]
+        select: Option<String>,
+
+        #[command(flatten)]
+        filter: AgentFilterArgs,
+
+        /// Restart requests of up to 500 agents sent at the same time
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 10,
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        parallel: usize,
+
+        /// Skip confirmation
+        #[arg(short, long)]
+        yes: bool,
     },
 
-    /// Upgrade agent
+    /// Upgrade agents
     Upgrade {
-        /// Agent ID, name, or "all"
-        agent: String,
+        /// Agent IDs or names, space- or comma-separated, or "all"
+        #[arg(
+            value_delimiter = ',',
+            required_unless_present_any = ["select", "group", "os", "query"],
+            conflicts_with_all = ["group", "os", "query"],
+            add = ArgValueCandidates::new(completion::agents)
+        )]
+        agents: Vec<String>,
+
+        /// Upgrade every agent matching a selector instead
+        #[arg(long, value_name = "EXPR", conflicts_with = "agents")]
+        select: Option<String>,
+
+        #[command(flatten)]
+        filter: AgentFilterArgs,
+
+        /// Upgrade requests of up to 500 agents sent at the same time
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 10,
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        parallel: usize,
 
         /// Target version
         #[arg(short, long)]
@@ -134,12 +733,267 @@ pub enum AgentAction {
         /// Force upgrade
         #[arg(short, long)]
         force: bool,
+
+        /// Skip confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// One-shot fleet overview: status counts, platforms, capacity and new agents
+    #[command(aliases = &["overview"])]
+    Summary {
+        /// Number of most recently registered agents to show
+        #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+        recent: u32,
+    },
+
+    /// Show the distribution of agent versions
+    Versions {
+        /// Break counts down by OS platform
+        #[arg(short, long)]
+        matrix: bool,
+
+        /// Output format
+        #[arg(short, long, default_value = "table", value_parser = ["table", "csv", "markdown"])]
+        format: String,
+
+        /// Highlight versions older than this (defaults to agents.min_version)
+        #[arg(long)]
+        min_version: Option<String>,
+    },
+
+    /// Flag groups with sudden drops in active agents and flapping agents
+    Anomalies {
+        /// History used as the baseline (e.g. 24h, 7d)
+        #[arg(long, default_value = "7d")]
+        window: String,
+
+        /// Standard deviations below the baseline that count as a drop
+        #[arg(long, default_value_t = 3.0)]
+        deviations: f64,
+
+        /// Smallest drop reported, as a fraction of the baseline
+        #[arg(long, default_value_t = 0.2)]
+        min_drop: f64,
+
+        /// Status changes within the window that make an agent flapping
+        #[arg(long, default_value_t = 4)]
+        flap_changes: usize,
+
+        /// Check without recording this run in the check-in history
+        #[arg(long)]
+        no_record: bool,
+    },
+
+    /// Diagnose why an agent is disconnected from logs, remoted counters and alerts
+    WhyDisconnected {
+        /// Agent ID or name
+        #[arg(add = ArgValueCandidates::new(completion::agents))]
+        agent: String,
+
+        /// Manager log entries to examine
+        #[arg(long, default_value_t = 100)]
+        logs: u32,
+
+        /// How far back to look for agent alerts (e.g. 24h, 7d)
+        #[arg(long, default_value = "7d")]
+        since: String,
+    },
+
+    /// Run an allowed active-response command on agents and collect the results
+    Run {
+        /// Agent IDs or names (comma-separated), a name pattern such as 'web-*', or 'all'
+        #[arg(required_unless_present = "select")]
+        agents: Option<String>,
+
+        /// Run on every agent matching a selector instead
+        #[arg(long, value_name = "EXPR", conflicts_with = "agents")]
+        select: Option<String>,
+
+        /// Active-response command, listed in active_response.allowed_commands
+        #[arg(short, long)]
+        command: String,
+
+        /// Argument passed to the command (repeatable)
+        #[arg(long = "arg", value_name = "ARG")]
+        args: Vec<String>,
+
+        /// Seconds to wait for results
+        #[arg(long, default_value_t = 60)]
+        timeout: u64,
+
+        /// Skip confirmation
+        #[arg(short, long)]
+        yes: bool,
     },
 
     /// Get agent key
+    #[command(args_conflicts_with_subcommands = true)]
     Key {
         /// Agent ID or name
+        #[arg(
+            conflicts_with_all = ["group", "os", "query"],
+            add = ArgValueCandidates::new(completion::agents)
+        )]
+        agent: Option<String>,
+
+        #[command(flatten)]
+        filter: AgentFilterArgs,
+
+        #[command(subcommand)]
+        action: Option<KeyAction>,
+    },
+
+    /// Manage agent groups and group membership
+    Group {
+        #[command(subcommand)]
+        action: AgentGroupAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AgentGroupAction {
+    /// List groups with their agent counts
+    List,
+
+    /// Create an empty group
+    Create {
+        /// Group name
+        name: String,
+    },
+
+    /// Delete a group; its agents stay registered
+    Delete {
+        /// Group name
+        #[arg(add = ArgValueCandidates::new(completion::groups))]
+        name: String,
+
+        /// Skip confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// Add an agent, or every agent matching a pattern or selector, to a group
+    #[command(allow_missing_positional = true)]
+    Assign {
+        /// Agent ID or name
+        #[arg(
+            required_unless_present_any = ["pattern", "select"],
+            conflicts_with_all = ["pattern", "select", "os", "dry_run"],
+            add = ArgValueCandidates::new(completion::agents)
+        )]
+        agent: Option<String>,
+
+        /// Group name
+        #[arg(add = ArgValueCandidates::new(completion::groups))]
+        group: String,
+
+        /// Agent name pattern; * matches any characters, ? one (e.g. 'db-*')
+        #[arg(short, long)]
+        pattern: Option<String>,
+
+        /// Only agents whose OS platform or name contains this (e.g. windows)
+        #[arg(long)]
+        os: Option<String>,
+
+        /// Agents matching a selector, e.g. 'os.platform==windows && group!=db'
+        #[arg(long, value_name = "EXPR")]
+        select: Option<String>,
+
+        /// Show the agents that would be assigned without changing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Agents assigned per API request
+        #[arg(
+            long,
+            default_value_t = 500,
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        batch_size: usize,
+
+        /// Skip confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Remove an agent from a group
+    Remove {
+        /// Agent ID or name
+        #[arg(add = ArgValueCandidates::new(completion::agents))]
+        agent: String,
+
+        /// Group name
+        #[arg(add = ArgValueCandidates::new(completion::groups))]
+        group: String,
+
+        /// Skip confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// Print a group's shared agent.conf
+    GetConfig {
+        /// Group name
+        #[arg(add = ArgValueCandidates::new(completion::groups))]
+        group: String,
+    },
+
+    /// Replace a group's shared agent.conf, showing a diff first
+    SetConfig {
+        /// Group name
+        #[arg(add = ArgValueCandidates::new(completion::groups))]
+        group: String,
+
+        /// New agent.conf
+        #[arg(short, long)]
+        file: PathBuf,
+
+        /// Only check the file and show the diff
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum KeyAction {
+    /// Install the agent key on its host over SSH and restart the agent
+    Push {
+        /// Agent ID or name
+        #[arg(add = ArgValueCandidates::new(completion::agents))]
         agent: String,
+
+        /// SSH destination (user@host or ssh config alias)
+        #[arg(long)]
+        host: String,
+
+        /// SSH port
+        #[arg(short, long)]
+        port: Option<u16>,
+
+        /// SSH identity file
+        #[arg(short, long)]
+        identity: Option<String>,
+
+        /// Path of client.keys on the target
+        #[arg(long, default_value = "/var/ossec/etc/client.keys")]
+        keys_path: String,
+
+        /// Run remote commands without sudo (when connecting as root)
+        #[arg(long)]
+        no_sudo: bool,
+
+        /// Do not restart the agent after installing the key
+        #[arg(long)]
+        no_restart: bool,
+
+        /// Skip confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
     },
 }
 
@@ -154,52 +1008,185 @@ pub enum ControlAction {
     /// Show service status
     Status {
         /// Service name (optional)
+        #[arg(add = ArgValueCandidates::new(completion::services))]
         service: Option<String>,
     },
 
     /// Start services
     Start {
         /// Service name or "all"
+        #[arg(add = ArgValueCandidates::new(completion::services))]
         service: Option<String>,
     },
 
     /// Stop services
     Stop {
         /// Service name or "all"
+        #[arg(add = ArgValueCandidates::new(completion::services))]
         service: Option<String>,
     },
 
     /// Restart services
     Restart {
         /// Service name or "all"
+        #[arg(add = ArgValueCandidates::new(completion::services))]
         service: Option<String>,
     },
 
     /// Show service information
     Info,
-}
 
-#[derive(Parser)]
-pub struct ConfigCommand {
-    #[command(subcommand)]
-    pub action: ConfigAction,
-}
+    /// Show the manager's ossec.log, optionally following new lines
+    Logs {
+        /// Only lines of this level
+        #[arg(long, value_parser = ["critical", "error", "warning", "info", "debug"])]
+        level: Option<String>,
 
-#[derive(Subcommand)]
-pub enum ConfigAction {
-    /// Show current configuration
-    Show,
+        /// Only lines of this daemon (e.g. wazuh-modulesd)
+        #[arg(long)]
+        tag: Option<String>,
 
-    /// Set configuration value
-    Set {
-        /// Configuration key
-        key: String,
+        /// Number of most recent lines to show
+        #[arg(short = 'n', long, default_value_t = 100)]
+        tail: u32,
 
-        /// Configuration value
-        value: String,
+        /// Keep polling for new lines until interrupted
+        #[arg(short, long, conflicts_with = "summary")]
+        follow: bool,
+
+        /// Polling interval with --follow (e.g. 5s, 1m)
+        #[arg(short, long, default_value = "5s")]
+        interval: String,
+
+        /// Count lines per daemon and level instead
+        #[arg(long, conflicts_with_all = ["level", "tail"])]
+        summary: bool,
     },
 
-    /// Get configuration value
+    /// Inspect the manager configuration (ossec.conf)
+    Config {
+        #[command(subcommand)]
+        action: ManagerConfigAction,
+    },
+
+    /// Show agent and API user counts against their limits
+    Limits {
+        /// Usage percentage reported as a warning
+        #[arg(long, default_value_t = 80.0)]
+        warn: f64,
+
+        /// Usage percentage reported as critical
+        #[arg(long, default_value_t = 95.0)]
+        critical: f64,
+    },
+
+    /// Enter or leave maintenance mode
+    Maintenance {
+        #[command(subcommand)]
+        action: MaintenanceAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ManagerConfigAction {
+    /// Show a section of ossec.conf, or what a daemon is actually running
+    Get {
+        /// Section (e.g. global, alerts, remote); all sections when omitted
+        section: Option<String>,
+
+        /// Field of the section (e.g. email_notification)
+        #[arg(requires = "section")]
+        field: Option<String>,
+
+        /// Ask a daemon for its running configuration instead (e.g. analysis)
+        #[arg(long, requires = "configuration", conflicts_with = "section")]
+        component: Option<String>,
+
+        /// Configuration of the component (e.g. global, internal)
+        #[arg(long, requires = "component")]
+        configuration: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum MaintenanceAction {
+    /// Notify, snapshot stats, pause integrations and drain queues
+    Enter {
+        /// Reason recorded with the maintenance window
+        #[arg(short, long)]
+        reason: Option<String>,
+
+        /// Manager host reached over SSH to pause integrations (user@host or ssh config alias)
+        #[arg(long)]
+        host: Option<String>,
+
+        /// SSH port
+        #[arg(short, long)]
+        port: Option<u16>,
+
+        /// SSH identity file
+        #[arg(short, long)]
+        identity: Option<String>,
+
+        /// Consider queues drained below this usage percentage
+        #[arg(long, default_value_t = 5.0)]
+        drain_below: f64,
+
+        /// Stop waiting for queues to drain after this long (e.g. 5m)
+        #[arg(long, default_value = "5m")]
+        drain_timeout: String,
+
+        /// Webhook URL receiving start and end notifications
+        #[arg(long)]
+        webhook: Option<String>,
+
+        /// Send desktop notifications
+        #[arg(long)]
+        desktop: bool,
+    },
+
+    /// Optionally restart daemons, resume integrations, snapshot stats and notify
+    Exit {
+        /// Restart cluster nodes one at a time, workers before the master
+        #[arg(long)]
+        restart: bool,
+    },
+
+    /// Show the open maintenance window
+    Status,
+}
+
+#[derive(Parser)]
+pub struct ConfigCommand {
+    #[command(subcommand)]
+    pub action: ConfigAction,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Show current configuration
+    Show {
+        /// Show passwords, tokens, API keys and header values instead of masking them
+        #[arg(long)]
+        show_secrets: bool,
+    },
+
+    /// Set a configuration value, in the profile in use
+    Set {
+        /// Configuration key, e.g. api.host, tls.verify or profiles.lab.api.port
+        key: String,
+
+        /// Configuration value: true/false for flags, comma-separated for lists
+        value: String,
+    },
+
+    /// Remove a configuration value, so its default (or top-level value) applies
+    Unset {
+        /// Configuration key
+        key: String,
+    },
+
+    /// Get configuration value
     Get {
         /// Configuration key
         key: String,
@@ -214,4 +1201,1361 @@ pub enum ConfigAction {
 
     /// Edit configuration in editor
     Edit,
-}
\ No newline at end of file
+
+    /// Check DNS, connection, TLS, login and API version of the configured manager
+    #[command(alias = "doctor")]
+    Test,
+
+    /// Make a profile the default for later commands ('default' for the top-level settings)
+    UseContext {
+        /// Profile name
+        #[arg(add = ArgValueCandidates::new(completion::profiles))]
+        name: String,
+    },
+
+    /// List the profiles, marking the one in use
+    GetContexts,
+
+    /// Stage, review and apply manager configuration (ossec.conf) changes
+    Remote {
+        #[command(subcommand)]
+        action: RemoteConfigAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RemoteConfigAction {
+    /// Stage a new ossec.conf locally with a diff against the manager's copy
+    Stage {
+        /// New ossec.conf to stage
+        #[arg(short, long)]
+        file: PathBuf,
+
+        /// Description of the change
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+
+    /// Push a staged change to the manager
+    Apply {
+        /// Change ID
+        #[arg(short, long)]
+        change: String,
+
+        /// Name of the person who reviewed the change
+        #[arg(long)]
+        approver: Option<String>,
+
+        /// Apply even if the manager's configuration changed since staging
+        #[arg(long)]
+        force: bool,
+
+        /// Skip confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// List staged changes
+    #[command(aliases = &["ls"])]
+    List {
+        /// Include applied and discarded changes
+        #[arg(short, long)]
+        all: bool,
+    },
+
+    /// Show a staged change and its diff
+    Show {
+        /// Change ID
+        change: String,
+    },
+
+    /// Discard a pending change
+    Discard {
+        /// Change ID
+        change: String,
+    },
+}
+
+#[derive(Parser)]
+pub struct RulesCommand {
+    #[command(subcommand)]
+    pub action: RulesAction,
+}
+
+#[derive(Subcommand)]
+pub enum RulesAction {
+    /// List rules
+    #[command(aliases = &["ls", "l"])]
+    List {
+        /// Filter by rule level (e.g. 10 or 10-15)
+        #[arg(short, long)]
+        level: Option<String>,
+
+        /// Filter by rule group
+        #[arg(short, long)]
+        group: Option<String>,
+
+        /// Filter by rule file (e.g. 0095-sshd_rules.xml)
+        #[arg(short, long)]
+        filename: Option<String>,
+
+        /// Filter by compliance requirement (e.g. pci_dss_10.2.4, gdpr)
+        #[arg(long)]
+        compliance: Option<String>,
+
+        /// Maximum number of rules to return
+        #[arg(long, default_value_t = 500)]
+        limit: u32,
+    },
+
+    /// Show a rule with its details and compliance mappings
+    Get {
+        /// Rule ID
+        #[arg(add = ArgValueCandidates::new(completion::rules))]
+        rule: u32,
+    },
+
+    /// Search rule descriptions and fields for text
+    Search {
+        /// Text to search for
+        query: String,
+
+        /// Filter by rule level (e.g. 10 or 10-15)
+        #[arg(short, long)]
+        level: Option<String>,
+
+        /// Filter by rule group
+        #[arg(short, long)]
+        group: Option<String>,
+
+        /// Filter by rule file (e.g. 0095-sshd_rules.xml)
+        #[arg(short, long)]
+        filename: Option<String>,
+
+        /// Filter by compliance requirement (e.g. pci_dss_10.2.4, gdpr)
+        #[arg(long)]
+        compliance: Option<String>,
+
+        /// Maximum number of rules to return
+        #[arg(long, default_value_t = 500)]
+        limit: u32,
+    },
+
+    /// List the groups rules belong to
+    Groups,
+
+    /// List the requirements of a compliance standard used by the ruleset
+    Requirements {
+        /// Compliance standard (pci, gdpr, hipaa, nist, gpg13, tsc)
+        standard: String,
+    },
+}
+
+#[derive(Parser)]
+pub struct DecodersCommand {
+    #[command(subcommand)]
+    pub action: DecodersAction,
+}
+
+#[derive(Subcommand)]
+pub enum DecodersAction {
+    /// List decoders
+    #[command(aliases = &["ls", "l"])]
+    List {
+        /// Filter by decoder name
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// Filter by decoder file (e.g. 0310-ssh_decoders.xml)
+        #[arg(short, long)]
+        filename: Option<String>,
+
+        /// Only list parent decoders
+        #[arg(long)]
+        parents: bool,
+
+        /// Maximum number of decoders to return
+        #[arg(long, default_value_t = 500)]
+        limit: u32,
+    },
+
+    /// Show every definition of a decoder name with its details
+    Get {
+        /// Decoder name
+        name: String,
+    },
+
+    /// List decoder files
+    Files {
+        /// Filter by file name
+        #[arg(short, long)]
+        filename: Option<String>,
+    },
+}
+
+#[derive(Parser)]
+pub struct ListsCommand {
+    #[command(subcommand)]
+    pub action: ListsAction,
+}
+
+#[derive(Subcommand)]
+pub enum ListsAction {
+    /// Show the CDB lists with their entry counts
+    Show {
+        /// Filter by list file name
+        #[arg(short, long)]
+        filename: Option<String>,
+    },
+
+    /// Print the entries of a list
+    Get {
+        /// List file name (e.g. malicious-ioc)
+        filename: String,
+
+        /// Print the file as stored instead of a table of entries
+        #[arg(long)]
+        raw: bool,
+    },
+
+    /// Upload a local file as the content of a list
+    Upload {
+        /// List file name on the manager
+        filename: String,
+
+        /// Local file with one `key:value` entry per line
+        path: PathBuf,
+
+        /// Replace the list if it already exists
+        #[arg(long)]
+        overwrite: bool,
+    },
+
+    /// Delete a list
+    Delete {
+        /// List file name
+        filename: String,
+
+        /// Skip confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+}
+
+#[derive(Parser)]
+pub struct ArCommand {
+    #[command(subcommand)]
+    pub action: ArAction,
+}
+
+#[derive(Subcommand)]
+pub enum ArAction {
+    /// List the commands defined in the manager configuration
+    #[command(aliases = &["ls", "l"])]
+    List,
+
+    /// Send an active-response command to agents
+    Run {
+        /// Active-response command, listed in active_response.allowed_commands
+        #[arg(short, long)]
+        command: String,
+
+        /// Agent IDs or names (comma-separated), a name pattern such as 'web-*', or 'all'
+        #[arg(long, add = ArgValueCandidates::new(completion::agents))]
+        agents: String,
+
+        /// Arguments passed to the command
+        #[arg(long, num_args = 1.., allow_hyphen_values = true)]
+        args: Vec<String>,
+
+        /// Wait for the agents' results from the indexer
+        #[arg(short, long)]
+        wait: bool,
+
+        /// Seconds to wait for results with --wait
+        #[arg(long, default_value_t = 60, requires = "wait")]
+        timeout: u64,
+
+        /// Skip confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Parser)]
+pub struct TasksCommand {
+    #[command(subcommand)]
+    pub action: TasksAction,
+}
+
+#[derive(Subcommand)]
+pub enum TasksAction {
+    /// List tasks, most recently updated first
+    #[command(aliases = &["ls", "l"])]
+    List {
+        /// Only tasks of these agents (comma-separated IDs or names)
+        #[arg(long, add = ArgValueCandidates::new(completion::agents))]
+        agent: Option<String>,
+
+        /// Only tasks of this command (e.g. upgrade, upgrade_custom)
+        #[arg(long)]
+        command: Option<String>,
+
+        /// Only tasks in this state (e.g. in-progress, done, failed)
+        #[arg(long)]
+        status: Option<String>,
+
+        /// Maximum number of tasks to return
+        #[arg(long, default_value_t = 500)]
+        limit: u32,
+
+        /// Poll until every listed task has finished
+        #[arg(short, long)]
+        watch: bool,
+
+        /// Polling interval with --watch (e.g. 5s, 1m)
+        #[arg(short, long, default_value = "5s")]
+        interval: String,
+    },
+
+    /// Show one task
+    Status {
+        /// Task ID
+        task_id: u32,
+
+        /// Poll until the task has finished
+        #[arg(short, long)]
+        watch: bool,
+
+        /// Polling interval with --watch (e.g. 5s, 1m)
+        #[arg(short, long, default_value = "5s")]
+        interval: String,
+    },
+}
+
+#[derive(Parser)]
+pub struct EventsCommand {
+    #[command(subcommand)]
+    pub action: EventsAction,
+}
+
+#[derive(Subcommand)]
+pub enum EventsAction {
+    /// Send events from a JSON array or one event per line
+    Send {
+        /// File of events; standard input when omitted or '-'
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+
+        /// Events per request (the API accepts at most 100)
+        #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..=100))]
+        batch_size: u32,
+
+        /// How the input is read: a JSON array, one event per line, or auto
+        /// (a JSON array when it parses as one, else lines)
+        #[arg(long, default_value = "auto", value_parser = ["auto", "json", "lines"])]
+        input_format: String,
+    },
+}
+
+#[derive(Parser)]
+pub struct LogtestCommand {
+    /// Raw log line to test
+    #[arg(short, long, required_unless_present = "interactive")]
+    pub log: Option<String>,
+
+    /// Location the line is reported from (e.g. /var/log/auth.log)
+    #[arg(long, default_value = "stdin")]
+    pub location: String,
+
+    /// Log format, as in a <localfile> block (e.g. syslog, json, apache)
+    #[arg(long, default_value = "syslog")]
+    pub log_format: String,
+
+    /// Token of an existing session, keeping rule state such as frequency counters
+    #[arg(long)]
+    pub session: Option<String>,
+
+    /// Test lines typed one by one in a single session
+    #[arg(short, long, conflicts_with = "log")]
+    pub interactive: bool,
+}
+
+#[derive(Parser)]
+pub struct MitreCommand {
+    #[command(subcommand)]
+    pub action: MitreAction,
+}
+
+#[derive(Subcommand)]
+pub enum MitreAction {
+    /// Look up attack techniques
+    Techniques {
+        /// ID (e.g. T1059) or text to search names and descriptions for
+        query: Option<String>,
+
+        /// Maximum number of entries to return
+        #[arg(long, default_value_t = 500)]
+        limit: u32,
+    },
+
+    /// Look up tactics
+    Tactics {
+        /// ID (e.g. TA0002) or text to search names and descriptions for
+        query: Option<String>,
+
+        /// Maximum number of entries to return
+        #[arg(long, default_value_t = 500)]
+        limit: u32,
+    },
+
+    /// Look up threat groups
+    Groups {
+        /// ID (e.g. G0016) or text to search names and descriptions for
+        query: Option<String>,
+
+        /// Maximum number of entries to return
+        #[arg(long, default_value_t = 500)]
+        limit: u32,
+    },
+
+    /// Look up malware and tools
+    Software {
+        /// ID (e.g. S0154) or text to search names and descriptions for
+        query: Option<String>,
+
+        /// Maximum number of entries to return
+        #[arg(long, default_value_t = 500)]
+        limit: u32,
+    },
+
+    /// Search the references cited by ATT&CK entries
+    References {
+        /// Text to search sources, URLs and descriptions for
+        query: Option<String>,
+
+        /// Maximum number of entries to return
+        #[arg(long, default_value_t = 500)]
+        limit: u32,
+    },
+}
+
+#[derive(Parser)]
+pub struct AlertsCommand {
+    #[command(subcommand)]
+    pub action: AlertsAction,
+}
+
+#[derive(Subcommand)]
+pub enum AlertsAction {
+    /// Search alerts
+    #[command(aliases = &["find", "s"])]
+    Search {
+        /// Query string (Lucene syntax)
+        #[arg(short, long)]
+        query: Option<String>,
+
+        /// Only alerts newer than this (e.g. 15m, 24h, 7d)
+        #[arg(long, default_value = "24h")]
+        since: String,
+
+        /// Minimum rule level
+        #[arg(short, long)]
+        level: Option<u32>,
+
+        /// Agent ID or name
+        #[arg(short, long, add = ArgValueCandidates::new(completion::agents))]
+        agent: Option<String>,
+
+        /// Rule ID
+        #[arg(short, long, add = ArgValueCandidates::new(completion::rules))]
+        rule: Option<String>,
+
+        /// Filter by compliance requirement (e.g. pci_dss_10.2.4, gdpr)
+        #[arg(long)]
+        compliance: Option<String>,
+
+        /// Maximum number of alerts to return
+        #[arg(long, default_value_t = 50)]
+        limit: u32,
+    },
+
+    /// Rank alerts by rule, agent or level to see what's noisy
+    #[command(alias = "top")]
+    Stats {
+        /// Only alerts newer than this (e.g. 15m, 24h, 7d)
+        #[arg(long, default_value = "24h")]
+        since: String,
+
+        /// Field to group alerts by
+        #[arg(
+            short,
+            long,
+            value_parser = ["rule.id", "agent.name", "rule.level"],
+            default_value = "rule.id"
+        )]
+        group_by: String,
+
+        /// Query string (Lucene syntax)
+        #[arg(short, long)]
+        query: Option<String>,
+
+        /// Minimum rule level
+        #[arg(short, long)]
+        level: Option<u32>,
+
+        /// Agent ID or name
+        #[arg(short, long, add = ArgValueCandidates::new(completion::agents))]
+        agent: Option<String>,
+
+        /// Number of groups to show (all levels are shown with --group-by rule.level)
+        #[arg(long, default_value_t = 10)]
+        top: u32,
+    },
+
+    /// Pull a sample of a rule's alerts and print selected fields, e.g. for logtest cases
+    Sample {
+        /// Rule ID
+        #[arg(short, long, add = ArgValueCandidates::new(completion::rules))]
+        rule: String,
+
+        /// Number of alerts to sample
+        #[arg(short = 'n', long = "n", default_value_t = 50)]
+        count: u32,
+
+        /// Comma-separated alert fields to print (dotted paths)
+        #[arg(short, long, value_delimiter = ',', default_value = "full_log")]
+        fields: Vec<String>,
+
+        /// Only alerts newer than this (e.g. 15m, 24h, 7d)
+        #[arg(long, default_value = "7d")]
+        since: String,
+
+        /// Take the most recent alerts instead of a random sample
+        #[arg(long)]
+        recent: bool,
+
+        /// Seed for a reproducible random sample
+        #[arg(long, conflicts_with = "recent")]
+        seed: Option<u64>,
+
+        /// Print tab-separated values, one alert per line, without a table
+        #[arg(long)]
+        raw: bool,
+    },
+
+    /// Create alerts in TheHive or DFIR-IRIS from Wazuh alerts
+    Escalate {
+        /// Alert ID (e.g. 1700000000.12345) or a query (Lucene syntax)
+        target: String,
+
+        /// Incident response platform configured under [escalation]
+        #[arg(long, value_parser = ["thehive", "iris"])]
+        to: String,
+
+        /// Only alerts newer than this when escalating a query (e.g. 24h, 7d)
+        #[arg(long, default_value = "7d")]
+        since: String,
+
+        /// Maximum number of alerts to escalate for a query
+        #[arg(long, default_value_t = 10)]
+        limit: u32,
+
+        /// Print the payloads without sending them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Parser)]
+pub struct ArchivesCommand {
+    #[command(subcommand)]
+    pub action: ArchivesAction,
+}
+
+#[derive(Subcommand)]
+pub enum ArchivesAction {
+    /// Search archived events, including those that did not generate an alert
+    #[command(aliases = &["find", "s"])]
+    Search {
+        /// Query string (Lucene syntax)
+        #[arg(short, long)]
+        query: Option<String>,
+
+        /// Only events newer than this (e.g. 15m, 24h, 7d)
+        #[arg(long, default_value = "24h")]
+        since: String,
+
+        /// Minimum rule level
+        #[arg(short, long)]
+        level: Option<u32>,
+
+        /// Agent ID or name
+        #[arg(short, long, add = ArgValueCandidates::new(completion::agents))]
+        agent: Option<String>,
+
+        /// Rule ID
+        #[arg(short, long, add = ArgValueCandidates::new(completion::rules))]
+        rule: Option<String>,
+
+        /// Filter by compliance requirement (e.g. pci_dss_10.2.4, gdpr)
+        #[arg(long)]
+        compliance: Option<String>,
+
+        /// Maximum number of events to return
+        #[arg(long, default_value_t = 50)]
+        limit: u32,
+    },
+}
+
+#[derive(Parser)]
+pub struct ReportCommand {
+    #[command(subcommand)]
+    pub action: ReportAction,
+}
+
+#[derive(Subcommand)]
+pub enum ReportAction {
+    /// Summarize alerts per compliance requirement
+    Compliance {
+        /// Compliance standard (pci, gdpr, hipaa, nist, gpg13, tsc)
+        #[arg(short, long)]
+        standard: String,
+
+        /// Time window to report on (e.g. 7d, 30d)
+        #[arg(long, default_value = "30d")]
+        last: String,
+
+        /// Restrict the report to one agent (ID or name)
+        #[arg(short, long, add = ArgValueCandidates::new(completion::agents))]
+        agent: Option<String>,
+
+        /// Write the report as JSON to a file, s3://bucket/key or gs://bucket/key
+        #[arg(long, value_name = "DEST")]
+        out: Option<Destination>,
+    },
+
+    /// Chart agent registrations and removals over time
+    FleetGrowth {
+        /// Period to group by
+        #[arg(short, long, default_value = "month", value_parser = ["day", "week", "month"])]
+        group_by: String,
+
+        /// Only include periods within this window (e.g. 90d, 52w)
+        #[arg(long)]
+        last: Option<String>,
+
+        /// Write the report as JSON to a file, s3://bucket/key or gs://bucket/key
+        #[arg(long, value_name = "DEST")]
+        out: Option<Destination>,
+    },
+
+    /// Render a self-contained HTML or Markdown report to attach to a ticket
+    Generate {
+        /// Data to report on
+        #[arg(long = "type", value_parser = ["agents", "vulnerabilities", "sca"])]
+        report_type: String,
+
+        /// Document format
+        #[arg(long, default_value = "html", value_parser = ["html", "md"])]
+        format: String,
+
+        /// Write the report to a file, s3://bucket/key or gs://bucket/key instead of stdout
+        #[arg(long, value_name = "DEST")]
+        out: Option<Destination>,
+    },
+}
+
+#[derive(Parser)]
+pub struct DriftCommand {
+    #[command(subcommand)]
+    pub action: DriftAction,
+}
+
+#[derive(Subcommand)]
+pub enum DriftAction {
+    /// Report configuration, ruleset and group files that differ from the baseline
+    ///
+    /// Exits with status 2 when files drifted and 1 when the check failed.
+    Check {
+        /// Baseline archive created by 'drift baseline update'
+        #[arg(short, long, value_name = "FILE", default_value = "baseline.tar.gz")]
+        baseline: PathBuf,
+
+        /// List changed files without their diffs
+        #[arg(long)]
+        no_diff: bool,
+    },
+
+    /// Manage the baseline archive
+    Baseline {
+        #[command(subcommand)]
+        action: BaselineAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BaselineAction {
+    /// Save the current configuration as the baseline after approved changes
+    Update {
+        /// Baseline archive to write
+        #[arg(short, long, value_name = "FILE", default_value = "baseline.tar.gz")]
+        baseline: PathBuf,
+
+        /// Skip confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Parser)]
+pub struct GroupCommand {
+    #[command(subcommand)]
+    pub action: AgentGroupAction,
+}
+
+#[derive(Parser)]
+pub struct FimCommand {
+    #[command(subcommand)]
+    pub action: FimAction,
+}
+
+#[derive(Subcommand)]
+pub enum FimAction {
+    /// List files and registry entries monitored on an agent
+    #[command(aliases = &["ls", "l"])]
+    List {
+        /// Agent ID or name
+        #[arg(add = ArgValueCandidates::new(completion::agents))]
+        agent: String,
+
+        /// File path, or a glob such as '/etc/*.conf'
+        #[arg(short, long)]
+        file: Option<String>,
+
+        /// MD5, SHA1 or SHA256 checksum of the file
+        #[arg(long)]
+        hash: Option<String>,
+
+        /// Only entries modified within this span (e.g. 24h, 7d)
+        #[arg(long, value_name = "SPAN")]
+        modified_since: Option<String>,
+
+        /// Entry type (file, registry_key, registry_value)
+        #[arg(short = 't', long = "type")]
+        entry_type: Option<String>,
+
+        /// Maximum number of entries to return
+        #[arg(long, default_value_t = 500)]
+        limit: u32,
+    },
+
+    /// Show when the last scan of an agent started and ended
+    LastScan {
+        /// Agent ID or name
+        #[arg(add = ArgValueCandidates::new(completion::agents))]
+        agent: String,
+    },
+
+    /// Start a scan on agents
+    Run {
+        /// Agent ID or name
+        #[arg(
+            required_unless_present_any = ["group", "all"],
+            add = ArgValueCandidates::new(completion::agents)
+        )]
+        agent: Option<String>,
+
+        /// Scan every agent in a group
+        #[arg(
+            long,
+            conflicts_with_all = ["agent", "all"],
+            add = ArgValueCandidates::new(completion::groups)
+        )]
+        group: Option<String>,
+
+        /// Scan every agent
+        #[arg(long, conflicts_with = "agent")]
+        all: bool,
+
+        /// Skip confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// Clear the stored results of an agent
+    Clear {
+        /// Agent ID or name
+        #[arg(add = ArgValueCandidates::new(completion::agents))]
+        agent: String,
+
+        /// Skip confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+}
+
+#[derive(Parser)]
+pub struct RootcheckCommand {
+    #[command(subcommand)]
+    pub action: RootcheckAction,
+}
+
+#[derive(Subcommand)]
+pub enum RootcheckAction {
+    /// List rootcheck findings of an agent
+    #[command(aliases = &["ls", "l"])]
+    List {
+        /// Agent ID or name
+        #[arg(add = ArgValueCandidates::new(completion::agents))]
+        agent: String,
+
+        /// Finding status (outstanding, solved, all)
+        #[arg(short, long, default_value = "outstanding")]
+        status: String,
+
+        /// Filter by PCI DSS requirement (e.g. 2.2.4)
+        #[arg(long)]
+        pci_dss: Option<String>,
+
+        /// Filter by CIS requirement
+        #[arg(long)]
+        cis: Option<String>,
+
+        /// Maximum number of findings to return
+        #[arg(long, default_value_t = 500)]
+        limit: u32,
+    },
+
+    /// Show when the last scan of an agent started and ended
+    LastScan {
+        /// Agent ID or name
+        #[arg(add = ArgValueCandidates::new(completion::agents))]
+        agent: String,
+    },
+
+    /// Start a scan on agents
+    Run {
+        /// Agent ID or name
+        #[arg(
+            required_unless_present_any = ["group", "all"],
+            add = ArgValueCandidates::new(completion::agents)
+        )]
+        agent: Option<String>,
+
+        /// Scan every agent in a group
+        #[arg(
+            long,
+            conflicts_with_all = ["agent", "all"],
+            add = ArgValueCandidates::new(completion::groups)
+        )]
+        group: Option<String>,
+
+        /// Scan every agent
+        #[arg(long, conflicts_with = "agent")]
+        all: bool,
+
+        /// Skip confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// Clear the stored findings of an agent
+    Clear {
+        /// Agent ID or name
+        #[arg(add = ArgValueCandidates::new(completion::agents))]
+        agent: String,
+
+        /// Skip confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+}
+
+#[derive(Parser)]
+pub struct VulnCommand {
+    #[command(subcommand)]
+    pub action: VulnAction,
+}
+
+#[derive(Subcommand)]
+pub enum VulnAction {
+    /// List the vulnerabilities of an agent, most severe first
+    #[command(aliases = &["ls", "l"])]
+    List {
+        /// Agent ID or name
+        #[arg(add = ArgValueCandidates::new(completion::agents))]
+        agent: String,
+
+        /// Only this severity (critical, high, medium, low)
+        #[arg(short, long)]
+        severity: Option<String>,
+
+        /// Only this CVE (e.g. CVE-2024-3094)
+        #[arg(long)]
+        cve: Option<String>,
+
+        /// Maximum number of vulnerabilities to return
+        #[arg(long, default_value_t = 500)]
+        limit: u32,
+    },
+
+    /// Count the vulnerabilities of an agent per severity
+    Summary {
+        /// Agent ID or name
+        #[arg(add = ArgValueCandidates::new(completion::agents))]
+        agent: String,
+    },
+}
+
+#[derive(Parser)]
+pub struct InventoryCommand {
+    #[command(subcommand)]
+    pub action: InventoryAction,
+}
+
+#[derive(Subcommand)]
+pub enum InventoryAction {
+    /// List installed hotfixes of Windows agents
+    Hotfixes {
+        /// Agent ID or name, or 'all' for every Windows agent
+        #[arg(add = ArgValueCandidates::new(completion::agents))]
+        agent: String,
+
+        /// Check every agent for this KB and report where it is missing
+        #[arg(long, value_name = "KB")]
+        missing_kb: Option<String>,
+
+        /// Agents queried at the same time
+        #[arg(
+            long,
+            default_value_t = 10,
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        concurrency: usize,
+    },
+}
+
+#[derive(Parser)]
+pub struct ExportCommand {
+    #[command(subcommand)]
+    pub action: ExportAction,
+}
+
+#[derive(Subcommand)]
+pub enum ExportAction {
+    /// Write agents, groups, memberships, SCA and vulnerability counts to a SQLite file
+    Sqlite {
+        /// Database file, s3://bucket/key or gs://bucket/key; replaced if it exists
+        #[arg(short, long, value_name = "DEST", default_value = "wazuh.db")]
+        out: Destination,
+
+        /// Agents queried at the same time
+        #[arg(
+            long,
+            default_value_t = 10,
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        concurrency: usize,
+    },
+}
+
+#[derive(Parser)]
+pub struct CompletionCommand {
+    #[command(subcommand)]
+    pub action: CompletionAction,
+}
+
+#[derive(Subcommand)]
+pub enum CompletionAction {
+    /// Fetch agents, groups and rules into the completion cache now
+    Refresh,
+}
+
+#[derive(Parser)]
+pub struct SelftestCommand {
+    #[command(subcommand)]
+    pub action: SelftestAction,
+}
+
+#[derive(Subcommand)]
+pub enum SelftestAction {
+    /// Send a tagged synthetic event and wait for its alert to reach the indexer
+    Pipeline {
+        /// Give up when the alert has not been indexed after this long (e.g. 2m)
+        #[arg(long, default_value = "2m")]
+        timeout: String,
+
+        /// Send the event without first checking it against the ruleset with logtest
+        #[arg(long)]
+        skip_logtest: bool,
+
+        /// Skip confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Parser)]
+pub struct AuthCommand {
+    #[command(subcommand)]
+    pub action: AuthAction,
+}
+
+#[derive(Subcommand)]
+pub enum AuthAction {
+    /// Check credentials against the manager and store them in the OS credential store
+    Login {
+        /// API user (defaults to auth.username)
+        #[arg(short, long)]
+        username: Option<String>,
+
+        /// Read the password from standard input instead of prompting
+        #[arg(long, conflicts_with = "with_token")]
+        password_stdin: bool,
+
+        /// Store an API token read from standard input instead of a password
+        #[arg(long)]
+        with_token: bool,
+    },
+
+    /// Remove the stored password, token and cached session
+    Logout,
+}
+
+#[derive(Parser)]
+pub struct StatsCommand {
+    #[command(subcommand)]
+    pub action: StatsAction,
+}
+
+#[derive(Subcommand)]
+pub enum StatsAction {
+    /// Summarize which commands and flags were used and how long they took
+    Cli {
+        /// Only include runs within this window (e.g. 7d, 4w)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Number of most used flags to show
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+
+    /// Start recording command usage to the local data directory
+    Enable,
+
+    /// Stop recording command usage
+    Disable,
+
+    /// Delete the recorded usage
+    Clear,
+}
+
+#[derive(Parser)]
+pub struct MonitorCommand {
+    #[command(subcommand)]
+    pub action: MonitorAction,
+}
+
+#[derive(Subcommand)]
+pub enum MonitorAction {
+    /// Poll analysisd/remoted statistics for queue and EPS pressure
+    Manager {
+        /// Thresholds to alert on (e.g. queue=80%,eps=90%)
+        #[arg(short, long, default_value = "queue=80%")]
+        thresholds: String,
+
+        /// Polling interval (e.g. 30s, 5m)
+        #[arg(short, long, default_value = "30s")]
+        interval: String,
+
+        /// Events per second the manager is sized for (needed for eps=N%)
+        #[arg(long)]
+        max_eps: Option<f64>,
+
+        /// Webhook URL receiving a JSON notification
+        #[arg(long)]
+        webhook: Option<String>,
+
+        /// Send desktop notifications
+        #[arg(long)]
+        desktop: bool,
+
+        /// Check once and exit non-zero if any threshold is exceeded; an EPS
+        /// threshold is measured over one interval
+        #[arg(long)]
+        once: bool,
+    },
+
+    /// Fleet-wide SCA panel: average score per policy and the most failed checks
+    Sca {
+        /// Refresh interval (e.g. 5m, 1h)
+        #[arg(short, long, default_value = "5m")]
+        interval: String,
+
+        /// Number of most failed checks to show
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+
+        /// Drill down into the agents failing this check ID
+        #[arg(long)]
+        check: Option<String>,
+
+        /// Agents queried at the same time
+        #[arg(
+            long,
+            default_value_t = 10,
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        concurrency: usize,
+
+        /// Show the panel once and exit
+        #[arg(long)]
+        once: bool,
+    },
+}
+
+#[derive(Parser)]
+pub struct IndexerCommand {
+    #[command(subcommand)]
+    pub action: IndexerAction,
+}
+
+#[derive(Subcommand)]
+pub enum IndexerAction {
+    /// Check index ages and ISM policies against a data-retention requirement
+    RetentionCheck {
+        /// Minimum time every index must be kept (e.g. 90d, 52w)
+        #[arg(long, value_name = "DURATION")]
+        policy: String,
+
+        /// Maximum time an index may be kept; older indices are violations
+        #[arg(long, value_name = "DURATION")]
+        max_age: Option<String>,
+
+        /// Index pattern to check
+        #[arg(long, default_value = "wazuh-*")]
+        index: String,
+    },
+}
+
+#[derive(Parser)]
+pub struct ClusterCommand {
+    #[command(subcommand)]
+    pub action: ClusterAction,
+}
+
+#[derive(Subcommand)]
+pub enum ClusterAction {
+    /// Probe every node for API reachability, daemon status and clock skew
+    Ping,
+
+    /// Show file integrity and agent-info synchronization between master and workers
+    SyncStatus {
+        /// Only show this worker node
+        #[arg(short, long)]
+        node: Option<String>,
+
+        /// Recent integrity log lines to show per worker (0 to skip)
+        #[arg(long, default_value_t = 5)]
+        logs: u32,
+    },
+}
+
+#[derive(Parser)]
+pub struct SecurityCommand {
+    #[command(subcommand)]
+    pub action: SecurityAction,
+}
+
+#[derive(Subcommand)]
+pub enum SecurityAction {
+    /// Report expiry dates and weak parameters of API, indexer and dashboard certificates
+    CertAudit {
+        /// Certificate file to check (repeatable, defaults to [cert_audit] paths)
+        #[arg(long = "path", value_name = "PATH")]
+        paths: Vec<PathBuf>,
+
+        /// Read the certificate files on this host over SSH (user@host or ssh config alias)
+        #[arg(long)]
+        host: Option<String>,
+
+        /// SSH port
+        #[arg(short, long)]
+        port: Option<u16>,
+
+        /// SSH identity file
+        #[arg(short, long)]
+        identity: Option<String>,
+
+        /// Report certificates expiring within this many days
+        #[arg(long)]
+        warn_days: Option<i64>,
+
+        /// Only check files, not the live API, indexer and dashboard endpoints
+        #[arg(long)]
+        no_endpoints: bool,
+    },
+
+    /// Manage Wazuh API users
+    Users {
+        #[command(subcommand)]
+        action: UsersAction,
+    },
+
+    /// Manage RBAC roles and their policies and users
+    Roles {
+        #[command(subcommand)]
+        action: RolesAction,
+    },
+
+    /// Manage RBAC policies
+    Policies {
+        #[command(subcommand)]
+        action: PoliciesAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RolesAction {
+    /// List roles with their policies and users
+    #[command(aliases = &["ls", "l"])]
+    List,
+
+    /// Create an empty role
+    Create {
+        /// Role name
+        name: String,
+    },
+
+    /// Delete a role
+    Delete {
+        /// Role ID or name
+        role: String,
+
+        /// Skip confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// Link policies to a role
+    AddPolicy {
+        /// Role ID or name
+        role: String,
+
+        /// Policy IDs or names
+        #[arg(required = true)]
+        policies: Vec<String>,
+    },
+
+    /// Unlink policies from a role
+    RemovePolicy {
+        /// Role ID or name
+        role: String,
+
+        /// Policy IDs or names
+        #[arg(required = true)]
+        policies: Vec<String>,
+    },
+
+    /// Give a user a role
+    AssignUser {
+        /// Role ID or name
+        role: String,
+
+        /// User ID or name
+        user: String,
+    },
+
+    /// Take a role away from a user
+    UnassignUser {
+        /// Role ID or name
+        role: String,
+
+        /// User ID or name
+        user: String,
+
+        /// Skip confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PoliciesAction {
+    /// List policies with their actions, resources and effect
+    #[command(aliases = &["ls", "l"])]
+    List,
+
+    /// Create a policy
+    Create {
+        /// Policy name
+        name: String,
+
+        /// Permitted or denied actions, e.g. agent:read (repeatable)
+        #[arg(long = "action", value_name = "ACTION", required = true)]
+        actions: Vec<String>,
+
+        /// Resources the actions apply to, e.g. agent:id:* (repeatable)
+        #[arg(long = "resource", value_name = "RESOURCE", required = true)]
+        resources: Vec<String>,
+
+        /// Whether the policy allows or denies the actions
+        #[arg(long, default_value = "allow", value_parser = ["allow", "deny"])]
+        effect: String,
+    },
+
+    /// Delete a policy
+    Delete {
+        /// Policy ID or name
+        policy: String,
+
+        /// Skip confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum UsersAction {
+    /// List API users with their roles
+    #[command(aliases = &["ls", "l"])]
+    List,
+
+    /// Create a user; the password is prompted for
+    Create {
+        /// User name
+        username: String,
+
+        /// Allow the user to authenticate with authorization context (run_as)
+        #[arg(long)]
+        run_as: bool,
+    },
+
+    /// Delete a user
+    Delete {
+        /// User ID or name
+        user: String,
+
+        /// Skip confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// Change a user's password; the new password is prompted for
+    UpdatePassword {
+        /// User ID or name
+        user: String,
+    },
+
+    /// Allow a user to authenticate with authorization context (run_as)
+    EnableRunas {
+        /// User ID or name
+        user: String,
+
+        /// Revoke run_as instead
+        #[arg(long)]
+        disable: bool,
+    },
+}
@@ -1,22 +1,142 @@
 use anyhow::{anyhow, Context, Result};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
-use reqwest::{Client, ClientBuilder, Response, StatusCode};
+use chrono::{Duration, Utc};
+use futures::stream::{self, Stream, TryStreamExt};
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, ACCEPT_LANGUAGE, AUTHORIZATION, CONTENT_LENGTH,
+    CONTENT_TYPE, RETRY_AFTER,
+};
+use reqwest::{Client, ClientBuilder, RequestBuilder, Response, StatusCode};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use std::sync::Arc;
-use std::time::Duration as StdDuration;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, Write};
+use std::path::Path;
+use std::pin::pin;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration as StdDuration, Instant};
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
-use crate::config::Config;
+use crate::config::{Config, TlsProfile};
 use crate::error::WazuhError;
+use crate::models::{ApiResponse, ListResponse};
+use crate::output;
+use crate::ssh::{self, Tunnel, TunnelEnd};
+use crate::tokens::{self, CachedToken};
+use crate::trace;
+
+/// Longest pause applied when the API gives no Retry-After hint; Wazuh
+/// rate limits are per minute, so waiting longer never helps
+const MAX_BACKOFF_SECS: u64 = 60;
+
+/// `api.max_response_mb` in bytes, shared by the static response readers
+static MAX_RESPONSE_BYTES: AtomicU64 = AtomicU64::new(64 * 1024 * 1024);
+
+/// Items requested per page when a list endpoint is fetched page by page
+pub const PAGE_SIZE: u32 = 500;
+
+/// `--max-results`, 0 when list endpoints are fetched to the end
+static MAX_RESULTS: AtomicU32 = AtomicU32::new(0);
+
+/// Stop automatic pagination of list endpoints after `max` items
+pub fn set_max_results(max: Option<u32>) {
+    MAX_RESULTS.store(max.unwrap_or(0), Ordering::Relaxed);
+}
+
+fn max_results() -> Option<u32> {
+    Some(MAX_RESULTS.load(Ordering::Relaxed)).filter(|max| *max > 0)
+}
+
+/// Path prefixes of endpoints that may take minutes to answer, which get
+/// `api.long_timeout` instead of `api.timeout`
+const LONG_RUNNING_ENDPOINTS: &[&str] = &[
+    "/agents/upgrade",
+    "/manager/configuration/validation",
+    "/cluster/configuration/validation",
+    "/manager/restart",
+    "/cluster/restart",
+];
+
+/// Endpoints that exceeded their latency budget during this run, once per request
+static SLOW_ENDPOINTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Endpoints that exceeded their latency budget so far, for usage statistics
+pub fn take_slow_endpoints() -> Vec<String> {
+    SLOW_ENDPOINTS
+        .lock()
+        .map(|mut slow| std::mem::take(&mut *slow))
+        .unwrap_or_default()
+}
 
 #[derive(Debug, Clone)]
 pub struct WazuhClient {
     client: Client,
     config: Arc<RwLock<Config>>,
     base_url: String,
+    max_retries: u32,
+    /// Per-request timeout of long-running endpoints
+    long_timeout: StdDuration,
+    /// Shared by all clones so a rate limit pauses every in-flight operation
+    paused_until: Arc<Mutex<Option<Instant>>>,
+    latency_budget_ms: u64,
+    latency_budgets: Arc<BTreeMap<String, u64>>,
+    /// Sent with every request, kept to show them in `--trace-http`
+    default_headers: Arc<HeaderMap>,
+    /// Kept open while any clone of the client is alive
+    _tunnel: Option<Arc<Tunnel>>,
+}
+
+
+/// Payload of an API request
+enum RequestBody {
+    Json(serde_json::Value),
+    Raw {
+        content_type: &'static str,
+        data: Vec<u8>,
+    },
 }
 
+impl RequestBody {
+    fn json<T: Serialize>(body: Option<T>) -> Result<Option<Self>> {
+        body.map(|b| serde_json::to_value(b).map(RequestBody::Json))
+            .transpose()
+            .context("Failed to serialize request body")
+    }
+}
+
+/// A response body too large for memory lives in an unnamed temporary file
+pub enum SpooledBody {
+    Memory(Vec<u8>),
+    Disk(File),
+}
+
+impl SpooledBody {
+    /// Read the body back from wherever it was stored
+    pub fn into_reader(self) -> Result<Box<dyn Read>> {
+        Ok(match self {
+            SpooledBody::Memory(data) => Box::new(Cursor::new(data)),
+            SpooledBody::Disk(mut file) => {
+                file.rewind()?;
+                Box::new(BufReader::new(file))
+            }
+        })
+    }
+
+    /// Deserialize the body without first reading it into one string
+    pub fn parse<T: DeserializeOwned>(self) -> Result<T> {
+        serde_json::from_reader(self.into_reader()?).context("Failed to parse response")
+    }
+}
+
+/// Outcome of an unauthenticated reachability probe
+#[derive(Debug)]
+pub struct ProbeResult {
+    pub status: StatusCode,
+    pub latency: StdDuration,
+    pub server_date: Option<chrono::DateTime<chrono::Utc>>,
+}
 
 #[derive(Debug, Deserialize)]
 struct LoginResponse {
@@ -34,35 +154,171 @@ struct ApiError {
     message: String,
 }
 
+/// Header map for `api.extra_headers`, rejecting names or values HTTP cannot carry
+fn extra_headers(headers: &BTreeMap<String, String>) -> Result<HeaderMap, WazuhError> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let header_name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| WazuhError::InvalidInput(format!("Invalid header name '{}'", name)))?;
+        // Set on each request, where they would silently win over these
+        if [AUTHORIZATION, CONTENT_TYPE, CONTENT_LENGTH].contains(&header_name) {
+            return Err(WazuhError::InvalidInput(format!(
+                "Header '{}' is set by wazuh-cli and cannot be an extra header",
+                name
+            )));
+        }
+        let mut header_value = HeaderValue::from_str(value).map_err(|_| {
+            WazuhError::InvalidInput(format!("Invalid value for header '{}'", name))
+        })?;
+        header_value.set_sensitive(true);
+        map.insert(header_name, header_value);
+    }
+    Ok(map)
+}
+
+/// Default headers of every API request: `api.extra_headers` plus
+/// `Accept-Language` from `api.language`, unless a header already sets it
+fn default_headers(cfg: &Config) -> Result<HeaderMap, WazuhError> {
+    let mut map = extra_headers(&cfg.api.extra_headers)?;
+    if let Some(language) = &cfg.api.language {
+        let value = HeaderValue::from_str(language).map_err(|_| {
+            WazuhError::InvalidInput(format!("Invalid api.language '{}'", language))
+        })?;
+        map.entry(ACCEPT_LANGUAGE).or_insert(value);
+    }
+    Ok(map)
+}
+
+#[cfg(unix)]
+fn unix_socket(builder: ClientBuilder, socket: &Path) -> Result<ClientBuilder> {
+    debug!("Connecting through Unix socket {:?}", socket);
+    Ok(builder.unix_socket(socket))
+}
+
+#[cfg(not(unix))]
+fn unix_socket(_builder: ClientBuilder, _socket: &Path) -> Result<ClientBuilder> {
+    Err(WazuhError::InvalidInput("api.unix_socket is only supported on Unix".to_string()).into())
+}
+
+/// Apply the trust roots and client identity of a TLS profile
+pub fn apply_tls(mut builder: ClientBuilder, tls: &TlsProfile) -> Result<ClientBuilder> {
+    builder = builder
+        .danger_accept_invalid_certs(!tls.verify)
+        .tls_built_in_root_certs(tls.builtin_roots);
+
+    for path in &tls.ca_certs {
+        let pem = std::fs::read(path)
+            .with_context(|| format!("Failed to read CA bundle: {:?}", path))?;
+        let certs = reqwest::Certificate::from_pem_bundle(&pem)
+            .with_context(|| format!("Failed to parse CA bundle: {:?}", path))?;
+        if certs.is_empty() {
+            return Err(anyhow!("No certificates found in CA bundle: {:?}", path));
+        }
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    if tls.system_roots {
+        let native = rustls_native_certs::load_native_certs();
+        if native.certs.is_empty() {
+            let reason = native.errors.first().map(|e| format!(": {}", e)).unwrap_or_default();
+            return Err(anyhow!("No certificates found in the system trust store{}", reason));
+        }
+        for cert in native.certs {
+            // Stores often carry a few certificates rustls cannot parse; skip those
+            if let Ok(cert) = reqwest::Certificate::from_der(&cert) {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+    }
+
+    if let (Some(cert_path), Some(key_path)) = (&tls.client_cert, &tls.client_key) {
+        let cert = std::fs::read(cert_path)
+            .with_context(|| format!("Failed to read client certificate: {:?}", cert_path))?;
+        let key = std::fs::read(key_path)
+            .with_context(|| format!("Failed to read client key: {:?}", key_path))?;
+
+        let identity = reqwest::Identity::from_pem(&[cert, key].concat())
+            .context("Failed to create client identity")?;
+        builder = builder.identity(identity);
+    }
+
+    Ok(builder)
+}
+
+/// Send a client's requests through an SSH tunnel while its URLs, and so
+/// TLS verification, keep naming the manager
+fn through_tunnel(
+    builder: ClientBuilder,
+    base_url: String,
+    host: &str,
+    local: &TunnelEnd,
+) -> Result<(ClientBuilder, String)> {
+    match local {
+        #[cfg(unix)]
+        TunnelEnd::Unix(socket) => Ok((unix_socket(builder, socket)?, base_url)),
+        TunnelEnd::Tcp(addr) => {
+            let mut url = reqwest::Url::parse(&base_url).context("Invalid API URL")?;
+            if host.parse::<std::net::IpAddr>().is_ok() {
+                // An address cannot be resolved elsewhere, and TLS checks it against the certificate
+                return Err(WazuhError::InvalidInput(format!(
+                    "api.host must be a hostname to tunnel over SSH on this platform, not {}",
+                    host
+                ))
+                .into());
+            }
+            url.set_port(Some(addr.port()))
+                .map_err(|_| anyhow!("Cannot set a port on {}", base_url))?;
+            let base_url = url.as_str().trim_end_matches('/').to_string();
+            Ok((builder.resolve(host, *addr), base_url))
+        }
+    }
+}
+
 impl WazuhClient {
     /// Create a new Wazuh API client
     pub async fn new(config: Arc<RwLock<Config>>) -> Result<Self> {
         let cfg = config.read().await;
-        let base_url = cfg.api_url();
-        
-        let mut client_builder = ClientBuilder::new()
+        let mut base_url = cfg.api_url();
+        let max_retries = cfg.api.max_retries;
+        let long_timeout = StdDuration::from_secs(cfg.api.long_timeout);
+        let latency_budget_ms = cfg.api.latency_budget_ms;
+        let latency_budgets = Arc::new(cfg.api.latency_budgets.clone());
+        MAX_RESPONSE_BYTES.store(cfg.api.max_response_mb * 1024 * 1024, Ordering::Relaxed);
+
+        let headers = default_headers(&cfg)?;
+        let client_builder = ClientBuilder::new()
             .timeout(StdDuration::from_secs(cfg.api.timeout))
-            .danger_accept_invalid_certs(!cfg.tls.verify);
-
-        // Add custom CA certificate if provided
-        if let Some(ca_cert_path) = &cfg.tls.ca_cert {
-            let cert = std::fs::read(ca_cert_path)
-                .with_context(|| format!("Failed to read CA certificate: {:?}", ca_cert_path))?;
-            let cert = reqwest::Certificate::from_pem(&cert)
-                .context("Failed to parse CA certificate")?;
-            client_builder = client_builder.add_root_certificate(cert);
+            .default_headers(headers.clone());
+        let mut client_builder = apply_tls(client_builder, &cfg.tls.for_host(&cfg.api.host)?)?;
+
+        let tunnel = match &cfg.api.ssh_tunnel {
+            Some(_) if cfg.api.unix_socket.is_some() => {
+                return Err(WazuhError::InvalidInput(
+                    "api.ssh_tunnel cannot be combined with api.unix_socket".to_string(),
+                )
+                .into());
+            }
+            Some(jump_host) => {
+                let timeout = StdDuration::from_secs(cfg.api.timeout);
+                let tunnel =
+                    ssh::shared_tunnel(jump_host, &cfg.api.host, cfg.api.port, timeout).await?;
+                debug!("Tunneling {} through {} on {}", base_url, jump_host, tunnel.local);
+                (client_builder, base_url) =
+                    through_tunnel(client_builder, base_url, &cfg.api.host, &tunnel.local)?;
+                Some(tunnel)
+            }
+            None => None,
+        };
+
+        if let Some((host, addr)) = cfg.resolve_override()?.filter(|_| tunnel.is_none()) {
+            debug!("Resolving {} to {}", host, addr);
+            client_builder = client_builder.resolve(&host, addr);
         }
 
-        // Add client certificate if provided
-        if let (Some(cert_path), Some(key_path)) = (&cfg.tls.client_cert, &cfg.tls.client_key) {
-            let cert = std::fs::read(cert_path)
-                .with_context(|| format!("Failed to read client certificate: {:?}", cert_path))?;
-            let key = std::fs::read(key_path)
-                .with_context(|| format!("Failed to read client key: {:?}", key_path))?;
-            
-            let identity = reqwest::Identity::from_pem(&[cert, key].concat())
-                .context("Failed to create client identity")?;
-            client_builder = client_builder.identity(identity);
+        if let Some(socket) = &cfg.api.unix_socket {
+            client_builder = unix_socket(client_builder, socket)?;
         }
 
         let client = client_builder.build()
@@ -74,39 +330,93 @@ impl WazuhClient {
             client,
             config,
             base_url,
+            max_retries,
+            long_timeout,
+            paused_until: Arc::new(Mutex::new(None)),
+            latency_budget_ms,
+            latency_budgets,
+            default_headers: Arc::new(headers),
+            _tunnel: tunnel,
         })
     }
 
     /// Authenticate with the Wazuh API
+    ///
+    /// A token from an earlier invocation is reused until it expires; a new
+    /// one is requested only when there is none.
     pub async fn authenticate(&self) -> Result<()> {
-        let config = self.config.write().await;
+        let mut config = self.config.write().await;
+        let clock_skew = Duration::seconds(config.auth.clock_skew_secs as i64);
         
         // Check if we already have a valid token
         if let Some(token) = &config.auth.token {
-            if self.is_token_valid(token).await? {
+            if self.is_token_valid(token, clock_skew).await? {
                 info!("Using existing valid token");
                 return Ok(());
             }
+        } else if let Some(cached) = tokens::cache_key(&config)
+            .and_then(|key| tokens::load(&key, Utc::now() + clock_skew))
+        {
+            debug!("Using cached token valid until {}", cached.expires_at);
+            config.update_token(cached.token);
+            return Ok(());
+        }
+
+        drop(config);
+
+        let token = self.login().await?;
+        let config = self.config.read().await;
+        if let Some(key) = tokens::cache_key(&config) {
+            let expires_at = tokens::token_expiry(&token).unwrap_or_else(|| {
+                Utc::now() + Duration::hours(config.auth.token_expiry_hours as i64)
+            });
+            let cached = CachedToken { token, expires_at };
+            // Not being able to cache only costs a login next time
+            if let Err(e) = tokens::store(&key, cached) {
+                debug!("Could not cache the API token: {:#}", e);
+            }
         }
+        Ok(())
+    }
+
+    /// Log in with the configured username and password, ignoring any cached
+    /// token, and use the new token for this client's requests only
+    pub async fn login(&self) -> Result<String> {
+        let config = self.config.read().await;
 
         // Get credentials
         let (username, password) = match (&config.auth.username, &config.auth.password) {
             (Some(u), Some(p)) => (u.clone(), p.clone()),
-            _ => return Err(anyhow!("Username and password required for authentication")),
+            _ => {
+                return Err(anyhow!(
+                    "No valid API token and no username and password to log in with: supply a \
+                     token with --token, WAZUH_TOKEN or auth.token, or set auth.username and \
+                     auth.password"
+                ))
+            }
         };
 
-        drop(config); // Release write lock before making request
+        let auth_context = config.auth_context()?;
+
+        drop(config); // Release the read lock before making the request
 
         // Make login request
-        let login_url = format!("{}/security/user/authenticate", self.base_url);
+        let login_url = match auth_context {
+            Some(_) => format!("{}/security/user/authenticate/run_as", self.base_url),
+            None => format!("{}/security/user/authenticate", self.base_url),
+        };
         let auth_header = format!("Basic {}", BASE64.encode(format!("{}:{}", username, password)));
         
         debug!("Authenticating with Wazuh API at: {}", login_url);
         
-        let response = self.client
+        let mut request = self.client
             .post(&login_url)
-            .header("Authorization", auth_header)
-            .send()
+            .header("Authorization", auth_header);
+        if let Some(context) = &auth_context {
+            request = request.json(context);
+        }
+        let response = self
+            .send(request)
             .await
             .context("Failed to send authentication request")?;
 
@@ -128,16 +438,32 @@ impl WazuhClient {
         let login_response: LoginResponse = serde_json::from_str(&body)
             .context("Failed to parse login response")?;
 
-        // Update config with new token
         let mut config = self.config.write().await;
-        config.update_token(login_response.data.token);
-        
+        config.update_token(login_response.data.token.clone());
+
         info!("Successfully authenticated with Wazuh API");
-        Ok(())
+        Ok(login_response.data.token)
     }
 
-    /// Check if a token is still valid
-    async fn is_token_valid(&self, token: &str) -> Result<bool> {
+    /// Drop the current token, in memory and in the cache, after the API
+    /// rejected it
+    async fn forget_token(&self) {
+        let mut config = self.config.write().await;
+        config.auth.token = None;
+        if let Some(key) = tokens::cache_key(&config) {
+            if let Err(e) = tokens::remove(&key) {
+                debug!("Could not remove the cached API token: {:#}", e);
+            }
+        }
+    }
+
+    /// Check if a token is still valid: by its `exp` claim, minus the clock
+    /// skew margin, or by asking the API when it cannot be decoded
+    async fn is_token_valid(&self, token: &str, clock_skew: Duration) -> Result<bool> {
+        if let Some(expires_at) = tokens::token_expiry(token) {
+            return Ok(expires_at - clock_skew > Utc::now());
+        }
+
         // Make a direct request without going through the request method to avoid recursion
         let test_url = format!("{}/security/user/authenticate/run_as", self.base_url);
         
@@ -152,30 +478,142 @@ impl WazuhClient {
 
     /// Make a GET request to the API
     pub async fn get(&self, endpoint: &str) -> Result<Response> {
-        self.request(reqwest::Method::GET, endpoint, None::<()>).await
+        self.request(reqwest::Method::GET, endpoint, None).await
     }
 
     /// Make a POST request to the API
     pub async fn post<T: Serialize>(&self, endpoint: &str, body: Option<T>) -> Result<Response> {
-        self.request(reqwest::Method::POST, endpoint, body).await
+        self.request(reqwest::Method::POST, endpoint, RequestBody::json(body)?).await
     }
 
     /// Make a PUT request to the API
     pub async fn put<T: Serialize>(&self, endpoint: &str, body: Option<T>) -> Result<Response> {
-        self.request(reqwest::Method::PUT, endpoint, body).await
+        self.request(reqwest::Method::PUT, endpoint, RequestBody::json(body)?).await
+    }
+
+    /// Upload a raw payload, such as an XML configuration file, with PUT
+    pub async fn put_raw(
+        &self,
+        endpoint: &str,
+        content_type: &'static str,
+        data: Vec<u8>,
+    ) -> Result<Response> {
+        let body = RequestBody::Raw { content_type, data };
+        self.request(reqwest::Method::PUT, endpoint, Some(body)).await
     }
 
     /// Make a DELETE request to the API
     pub async fn delete(&self, endpoint: &str) -> Result<Response> {
-        self.request(reqwest::Method::DELETE, endpoint, None::<()>).await
+        self.request(reqwest::Method::DELETE, endpoint, None).await
+    }
+
+    /// GET a list endpoint page by page, so results are not cut at the API's
+    /// default of 500 items
+    ///
+    /// Items are fetched from the endpoint's `offset` until its `limit`, the
+    /// total reported by the API or `--max-results` is reached; without a
+    /// `limit` every item is fetched. `total_affected_items` stays the API total.
+    pub async fn get_list<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+    ) -> Result<ApiResponse<ListResponse<T>>> {
+        self.get_pages(endpoint, PAGE_SIZE, max_results()).await
+    }
+
+    /// Stream the items of a list endpoint, fetching the next page only once
+    /// the previous one has been consumed
+    ///
+    /// Paging works as in [`WazuhClient::get_list`], but at most one page is
+    /// held in memory, so large exports can start writing right away.
+    pub fn paginate<'a, T: DeserializeOwned + 'a>(
+        &'a self,
+        endpoint: &'a str,
+    ) -> Result<impl Stream<Item = Result<T>> + 'a> {
+        let pages = self.pages(endpoint, PAGE_SIZE, max_results())?;
+        Ok(pages
+            .map_ok(|page| stream::iter(page.data.affected_items.into_iter().map(Ok)))
+            .try_flatten())
+    }
+
+    async fn get_pages<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        max_page_size: u32,
+        max_results: Option<u32>,
+    ) -> Result<ApiResponse<ListResponse<T>>> {
+        let mut pages = pin!(self.pages(endpoint, max_page_size, max_results)?);
+        let mut list: Option<ApiResponse<ListResponse<T>>> = None;
+        while let Some(page) = pages.try_next().await? {
+            match &mut list {
+                None => list = Some(page),
+                Some(list) => {
+                    list.data.affected_items.extend(page.data.affected_items);
+                    list.data.failed_items.extend(page.data.failed_items);
+                    list.data.total_failed_items += page.data.total_failed_items;
+                }
+            }
+        }
+        list.ok_or_else(|| anyhow!("No page fetched from {}", endpoint))
+    }
+
+    /// Pages of a list endpoint, each requested when the stream is polled
+    fn pages<'a, T: DeserializeOwned + 'a>(
+        &'a self,
+        endpoint: &'a str,
+        max_page_size: u32,
+        max_results: Option<u32>,
+    ) -> Result<impl Stream<Item = Result<ApiResponse<ListResponse<T>>>> + 'a> {
+        let endpoint = split_paging(endpoint)?;
+        let wanted = match (endpoint.limit, max_results) {
+            (Some(limit), Some(max)) => Some(limit.min(max)),
+            (limit, max) => limit.or(max),
+        };
+        let state = PageState {
+            offset: endpoint.offset,
+            endpoint,
+            wanted,
+            fetched: 0,
+            done: false,
+        };
+
+        Ok(stream::try_unfold(state, move |mut state| async move {
+            if state.done {
+                return Ok(None);
+            }
+            let page_size = state
+                .wanted
+                .map_or(max_page_size, |w| (w - state.fetched).min(max_page_size));
+            let mut query = state.endpoint.params.clone();
+            query.push(("limit".to_string(), page_size.to_string()));
+            query.push(("offset".to_string(), state.offset.to_string()));
+            let url = format!("{}?{}", state.endpoint.path, serde_urlencoded::to_string(&query)?);
+
+            let response = self.get(&url).await?;
+            let page: ApiResponse<ListResponse<T>> = Self::parse_spooled_response(response).await?;
+            let received = page.data.affected_items.len() as u32;
+            let total = page.data.total_affected_items;
+            state.fetched += received;
+            state.offset += received;
+            debug!("Fetched {} of {} items from {}", state.fetched, total, state.endpoint.path);
+
+            state.done = received < page_size
+                || state.offset >= total
+                || state.wanted.is_some_and(|w| state.fetched >= w);
+            Ok(Some((page, state)))
+        }))
     }
 
     /// Make a generic request to the API
-    async fn request<T: Serialize>(
+    ///
+    /// Re-authenticates once on 401 and retries rate-limited (429) or
+    /// unavailable (503) responses up to `api.max_retries` times. A backoff
+    /// pauses every request made through this client, not just the one that
+    /// was throttled, so bulk operations slow down instead of failing.
+    async fn request(
         &self,
         method: reqwest::Method,
         endpoint: &str,
-        body: Option<T>,
+        body: Option<RequestBody>,
     ) -> Result<Response> {
         let url = if endpoint.starts_with("http") {
             endpoint.to_string()
@@ -183,76 +621,386 @@ impl WazuhClient {
             format!("{}{}", self.base_url, endpoint)
         };
 
-        let config = self.config.read().await;
-        let token = config.auth.token.as_ref()
-            .ok_or_else(|| anyhow!("Not authenticated"))?;
+        let mut attempt = 0;
+        let mut reauthenticated = false;
 
-        let mut request = self.client
-            .request(method.clone(), &url)
-            .header("Authorization", format!("Bearer {}", token));
-
-        if let Some(ref body) = body {
-            request = request
-                .header("Content-Type", "application/json")
-                .json(body);
-        }
+        loop {
+            self.wait_for_backoff().await;
 
-        drop(config); // Release read lock
-
-        debug!("{} {}", method, url);
-        
-        let response = request.send().await
-            .with_context(|| format!("Failed to send {} request to {}", method, url))?;
-
-        // Handle authentication errors by trying to re-authenticate once
-        if response.status() == StatusCode::UNAUTHORIZED {
-            warn!("Token expired, attempting to re-authenticate");
-            self.authenticate().await?;
-            
-            // Retry the request with new token
             let config = self.config.read().await;
             let token = config.auth.token.as_ref()
-                .ok_or_else(|| anyhow!("Failed to get new token"))?;
+                .ok_or_else(|| anyhow!("Not authenticated"))?;
 
             let mut request = self.client
                 .request(method.clone(), &url)
                 .header("Authorization", format!("Bearer {}", token));
+            if is_long_running(&endpoint_path(url.strip_prefix(&self.base_url).unwrap_or(&url))) {
+                request = request.timeout(self.long_timeout);
+            }
 
-            if let Some(ref body) = body {
-                request = request
-                    .header("Content-Type", "application/json")
-                    .json(body);
+            match &body {
+                Some(RequestBody::Json(value)) => {
+                    request = request
+                        .header("Content-Type", "application/json")
+                        .json(value);
+                }
+                Some(RequestBody::Raw { content_type, data }) => {
+                    request = request
+                        .header("Content-Type", *content_type)
+                        .body(data.clone());
+                }
+                None => {}
             }
 
-            drop(config);
+            drop(config); // Release read lock
+
+            debug!("{} {}", method, url);
+
+            let started = Instant::now();
+            let response = self.send(request).await
+                .with_context(|| format!("Failed to send {} request to {}", method, url))?;
+            self.check_latency(&url, started.elapsed());
+
+            match response.status() {
+                // Handle authentication errors by trying to re-authenticate once
+                StatusCode::UNAUTHORIZED if !reauthenticated => {
+                    warn!("Token expired, attempting to re-authenticate");
+                    self.forget_token().await;
+                    self.authenticate().await?;
+                    reauthenticated = true;
+                }
+                StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+                    if attempt < self.max_retries =>
+                {
+                    let delay = response
+                        .headers()
+                        .get(RETRY_AFTER)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(parse_retry_after)
+                        .unwrap_or_else(|| backoff_delay(attempt));
+
+                    attempt += 1;
+                    warn!(
+                        "{} {} returned {}, retrying in {:?} (attempt {}/{})",
+                        method, url, response.status(), delay, attempt, self.max_retries
+                    );
+                    self.start_backoff(delay);
+                    output::report_backoff(delay);
+                }
+                _ => {
+                    if attempt > 0 {
+                        output::clear_backoff();
+                    }
+                    return Ok(response);
+                }
+            }
+        }
+    }
+
+    /// Send a request, writing it and its response to the `--trace-http` trace
+    /// when there is one
+    async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        if !trace::enabled() {
+            return Ok(request.send().await?);
+        }
+        let request = request.build()?;
+        trace::request(&request, &self.default_headers);
+        let started = Instant::now();
+        match self.client.execute(request).await {
+            Ok(response) => {
+                let limit = MAX_RESPONSE_BYTES.load(Ordering::Relaxed);
+                trace::response(response, started.elapsed(), limit).await
+            }
+            Err(e) => {
+                trace::failure(&e);
+                Err(e.into())
+            }
+        }
+    }
 
-            return request.send().await
-                .with_context(|| format!("Failed to retry {} request to {}", method, url));
+    /// Hint at expensive queries when a response takes longer than its budget
+    ///
+    /// `elapsed` runs until the response headers arrive: the body is read
+    /// later by the caller, and the API only sends headers once it has
+    /// computed the result, so the wait for the query is what is measured.
+    fn check_latency(&self, url: &str, elapsed: StdDuration) {
+        let path = endpoint_path(url.strip_prefix(&self.base_url).unwrap_or(url));
+        let budget = latency_budget(&self.latency_budgets, self.latency_budget_ms, &path);
+        if elapsed.as_millis() <= budget as u128 {
+            return;
         }
 
-        Ok(response)
+        let first = match SLOW_ENDPOINTS.lock() {
+            Ok(mut slow) => {
+                let first = !slow.contains(&path);
+                slow.push(path.clone());
+                first
+            }
+            Err(_) => false,
+        };
+        // Bulk operations hit the same endpoint many times; one hint is enough
+        if first {
+            output::print_warning(
+                output::WarningKind::SlowResponse,
+                &format!(
+                    "{} is slow ({:.1}s, budget {:.1}s); consider adding filters or lowering limit",
+                    path.trim_start_matches('/'),
+                    elapsed.as_secs_f64(),
+                    budget as f64 / 1000.0
+                ),
+            );
+        }
+    }
+
+    /// Pause all requests made through this client for `delay`
+    fn start_backoff(&self, delay: StdDuration) {
+        let until = Instant::now() + delay;
+        if let Ok(mut paused) = self.paused_until.lock() {
+            // Never shorten a pause another request already started
+            if paused.is_none_or(|current| current < until) {
+                *paused = Some(until);
+            }
+        }
+    }
+
+    /// Wait until any client-wide backoff has elapsed
+    async fn wait_for_backoff(&self) {
+        let until = self.paused_until.lock().ok().and_then(|paused| *paused);
+        if let Some(until) = until {
+            tokio::time::sleep_until(until.into()).await;
+        }
+    }
+
+    /// Probe an API endpoint without authentication, measuring latency
+    ///
+    /// Any HTTP response (including 401) counts as reachable; the server's
+    /// `Date` header is returned so callers can estimate clock skew.
+    pub async fn probe(&self, url: &str) -> Result<ProbeResult> {
+        let started = Instant::now();
+        let response = self.client
+            .get(url)
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach {}", url))?;
+        let latency = started.elapsed();
+
+        let server_date = response
+            .headers()
+            .get(reqwest::header::DATE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| chrono::DateTime::parse_from_rfc2822(v).ok())
+            .map(|d| d.with_timezone(&chrono::Utc));
+
+        Ok(ProbeResult {
+            status: response.status(),
+            latency,
+            server_date,
+        })
+    }
+
+    /// URL requests are sent to; on a local port when tunneled over SSH
+    /// where Unix sockets are not available
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Get the API port used for this client
+    pub async fn api_port(&self) -> u16 {
+        self.config.read().await.api.port
+    }
+
+    /// Get the API protocol used for this client
+    pub async fn api_protocol(&self) -> String {
+        self.config.read().await.api.protocol.clone()
     }
 
     /// Parse JSON response from the API
     pub async fn parse_response<T: DeserializeOwned>(response: Response) -> Result<T> {
+        let body = Self::response_text(response).await?;
+
+        serde_json::from_str(&body)
+            .with_context(|| format!("Failed to parse response: {}", body))
+    }
+
+    /// Parse a JSON response that may be too large for memory, such as a
+    /// full export, spooling it to a temporary file past `api.max_response_mb`
+    pub async fn parse_spooled_response<T: DeserializeOwned>(response: Response) -> Result<T> {
+        Self::spool_response(response).await?.parse()
+    }
+
+    /// Read a response body, spilling it to a temporary file once it outgrows
+    /// `api.max_response_mb`
+    pub async fn spool_response(response: Response) -> Result<SpooledBody> {
+        let response = Self::check_status(response).await?;
+        spool_body(response, MAX_RESPONSE_BYTES.load(Ordering::Relaxed)).await
+    }
+
+    /// Read a plain-text response body (e.g. `?raw=true` endpoints), mapping
+    /// error statuses to API errors
+    ///
+    /// Bodies larger than `api.max_response_mb` are rejected rather than
+    /// buffered, so a huge result cannot exhaust memory on a small host.
+    pub async fn response_text(response: Response) -> Result<String> {
+        let response = Self::check_status(response).await?;
+        let body = read_limited(response, MAX_RESPONSE_BYTES.load(Ordering::Relaxed)).await?;
+        String::from_utf8(body).context("Response is not valid UTF-8")
+    }
+
+    /// Map error statuses to API errors, passing successful responses through
+    async fn check_status(response: Response) -> Result<Response> {
         let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
         let body = response.text().await?;
+        let error: ApiError = serde_json::from_str(&body)
+            .unwrap_or(ApiError {
+                error: status.as_u16() as i32,
+                message: body,
+            });
+        Err(WazuhError::ApiError {
+            code: error.error,
+            message: error.message,
+        }.into())
+    }
+}
 
-        if !status.is_success() {
-            let error: ApiError = serde_json::from_str(&body)
-                .unwrap_or(ApiError {
-                    error: status.as_u16() as i32,
-                    message: body,
-                });
-            return Err(WazuhError::ApiError {
-                code: error.error,
-                message: error.message,
-            }.into());
+/// Path of an endpoint without query string, with numeric IDs replaced by `{id}`
+/// so `/sca/001` and `/sca/002` are reported as one endpoint
+fn endpoint_path(endpoint: &str) -> String {
+    let path = endpoint.split('?').next().unwrap_or_default();
+    path.split('/')
+        .map(|segment| {
+            if !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()) {
+                "{id}"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Budget in milliseconds of the longest configured prefix matching `path`
+fn latency_budget(budgets: &BTreeMap<String, u64>, default_ms: u64, path: &str) -> u64 {
+    budgets
+        .iter()
+        .filter(|(prefix, _)| path.starts_with(prefix.as_str()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, budget)| *budget)
+        .unwrap_or(default_ms)
+}
+
+fn is_long_running(path: &str) -> bool {
+    LONG_RUNNING_ENDPOINTS.iter().any(|prefix| path.starts_with(prefix))
+}
+
+fn too_large(limit: u64) -> anyhow::Error {
+    WazuhError::ResponseTooLarge {
+        limit_mb: limit / (1024 * 1024),
+    }
+    .into()
+}
+
+/// Read a whole body into memory, failing as soon as it passes `limit` bytes
+async fn read_limited(mut response: Response, limit: u64) -> Result<Vec<u8>> {
+    if response.content_length().is_some_and(|len| len > limit) {
+        return Err(too_large(limit));
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if (body.len() + chunk.len()) as u64 > limit {
+            return Err(too_large(limit));
         }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
 
-        serde_json::from_str(&body)
-            .with_context(|| format!("Failed to parse response: {}", body))
+/// Read a body into memory up to `limit` bytes, continuing in a temporary file past it
+async fn spool_body(mut response: Response, limit: u64) -> Result<SpooledBody> {
+    let mut body = Vec::new();
+    let mut file: Option<File> = None;
+
+    while let Some(chunk) = response.chunk().await? {
+        match file.as_mut() {
+            Some(file) => file.write_all(&chunk)?,
+            None if (body.len() + chunk.len()) as u64 > limit => {
+                debug!("Response exceeds {} bytes, spooling to disk", limit);
+                let mut spool = tempfile::tempfile().context("Failed to create spool file")?;
+                spool.write_all(&body)?;
+                spool.write_all(&chunk)?;
+                body = Vec::new();
+                file = Some(spool);
+            }
+            None => body.extend_from_slice(&chunk),
+        }
     }
+
+    Ok(match file {
+        Some(file) => SpooledBody::Disk(file),
+        None => SpooledBody::Memory(body),
+    })
+}
+
+/// Parse a Retry-After header given either as seconds or an HTTP date
+fn parse_retry_after(value: &str) -> Option<StdDuration> {
+    if let Ok(secs) = value.trim().parse::<u64>() {
+        return Some(StdDuration::from_secs(secs));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    let wait = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or(StdDuration::ZERO))
+}
+
+/// Exponential backoff used when the API does not say how long to wait
+fn backoff_delay(attempt: u32) -> StdDuration {
+    StdDuration::from_secs(2u64.saturating_pow(attempt + 1).min(MAX_BACKOFF_SECS))
+}
+
+/// A list endpoint with its paging parameters taken out of the query
+#[derive(Debug, PartialEq)]
+struct PagedEndpoint<'a> {
+    path: &'a str,
+    params: Vec<(String, String)>,
+    offset: u32,
+    limit: Option<u32>,
+}
+
+/// Progress of [`WazuhClient::pages`] through a list endpoint
+struct PageState<'a> {
+    endpoint: PagedEndpoint<'a>,
+    offset: u32,
+    /// Items to fetch in total, `None` for all of them
+    wanted: Option<u32>,
+    fetched: u32,
+    done: bool,
+}
+
+fn split_paging(endpoint: &str) -> Result<PagedEndpoint<'_>> {
+    let (path, query) = endpoint.split_once('?').unwrap_or((endpoint, ""));
+    let mut params: Vec<(String, String)> = serde_urlencoded::from_str(query)?;
+    let mut paging = |name: &str| -> Result<Option<u32>> {
+        let Some(index) = params.iter().position(|(key, _)| key == name) else {
+            return Ok(None);
+        };
+        let (_, value) = params.remove(index);
+        let value = value
+            .parse()
+            .with_context(|| format!("Invalid {} '{}' in {}", name, value, endpoint))?;
+        Ok(Some(value))
+    };
+    let offset = paging("offset")?.unwrap_or(0);
+    let limit = paging("limit")?;
+    Ok(PagedEndpoint {
+        path,
+        params,
+        offset,
+        limit,
+    })
 }
 
 #[cfg(test)]
@@ -267,4 +1015,324 @@ mod tests {
         let client = WazuhClient::new(config).await;
         assert!(client.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_retries_rate_limited_requests() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/agents"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/agents"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let address = server.address();
+        let mut config = Config::default();
+        config.api.protocol = "http".to_string();
+        config.api.host = address.ip().to_string();
+        config.api.port = address.port();
+        config.auth.token = Some("token".to_string());
+
+        let client = WazuhClient::new(Arc::new(RwLock::new(config))).await.unwrap();
+        let response = client.get("/agents").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_sends_extra_headers() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/agents"))
+            .and(header("X-Proxy-Auth", "secret"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let address = server.address();
+        let mut config = Config::default();
+        config.api.protocol = "http".to_string();
+        config.api.host = address.ip().to_string();
+        config.api.port = address.port();
+        config.auth.token = Some("token".to_string());
+        config
+            .api
+            .extra_headers
+            .insert("X-Proxy-Auth".to_string(), "secret".to_string());
+
+        let client = WazuhClient::new(Arc::new(RwLock::new(config))).await.unwrap();
+        let response = client.get("/agents").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut invalid = Config::default();
+        invalid
+            .api
+            .extra_headers
+            .insert("Bad Header".to_string(), "x".to_string());
+        assert!(WazuhClient::new(Arc::new(RwLock::new(invalid))).await.is_err());
+
+        let mut reserved = Config::default();
+        reserved
+            .api
+            .extra_headers
+            .insert("authorization".to_string(), "Basic x".to_string());
+        assert!(WazuhClient::new(Arc::new(RwLock::new(reserved))).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sends_accept_language() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/agents"))
+            .and(header("Accept-Language", "es-ES"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let address = server.address();
+        let mut config = Config::default();
+        config.api.protocol = "http".to_string();
+        config.api.host = address.ip().to_string();
+        config.api.port = address.port();
+        config.auth.token = Some("token".to_string());
+        config.api.language = Some("es-ES".to_string());
+
+        let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await.unwrap();
+        assert_eq!(client.get("/agents").await.unwrap().status(), StatusCode::OK);
+
+        // An explicit header wins over the configured language
+        config.api.language = Some("fr".to_string());
+        config
+            .api
+            .extra_headers
+            .insert("Accept-Language".to_string(), "es-ES".to_string());
+        let client = WazuhClient::new(Arc::new(RwLock::new(config))).await.unwrap();
+        assert_eq!(client.get("/agents").await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_response_size_limit() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let items: Vec<u32> = (0..1000).collect();
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&items))
+            .mount(&server)
+            .await;
+
+        let response = reqwest::get(server.uri()).await.unwrap();
+        let err = read_limited(response, 1024).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<WazuhError>(),
+            Some(WazuhError::ResponseTooLarge { .. })
+        ));
+
+        let response = reqwest::get(server.uri()).await.unwrap();
+        let spooled = spool_body(response, 1024).await.unwrap();
+        assert!(matches!(spooled, SpooledBody::Disk(_)));
+        assert_eq!(spooled.parse::<Vec<u32>>().unwrap(), items);
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_with_token() {
+        use jsonwebtoken::{encode, EncodingKey, Header};
+        use wiremock::MockServer;
+
+        // Nothing is mounted: a usable token must not cause any request
+        let server = MockServer::start().await;
+        let address = server.address();
+        let jwt = |exp: chrono::DateTime<Utc>| {
+            let claims = serde_json::json!({ "iss": "wazuh", "exp": exp.timestamp() });
+            encode(&Header::default(), &claims, &EncodingKey::from_secret(b"s")).unwrap()
+        };
+        let mut config = Config::default();
+        config.api.protocol = "http".to_string();
+        config.api.host = address.ip().to_string();
+        config.api.port = address.port();
+
+        config.auth.token = Some(jwt(Utc::now() + Duration::minutes(15)));
+        let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await.unwrap();
+        client.authenticate().await.unwrap();
+
+        // Within the clock skew margin the token counts as expired
+        config.auth.token = Some(jwt(Utc::now() + Duration::seconds(10)));
+        let client = WazuhClient::new(Arc::new(RwLock::new(config))).await.unwrap();
+        let error = client.authenticate().await.unwrap_err().to_string();
+        assert!(error.contains("--token"), "{}", error);
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_pages() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        for (offset, limit) in (0..5u32).flat_map(|offset| [(offset, 1), (offset, 2)]) {
+            let items: Vec<u32> = (offset..5).take(limit).collect();
+            Mock::given(method("GET"))
+                .and(path("/agents"))
+                .and(query_param("offset", offset.to_string()))
+                .and(query_param("limit", limit.to_string()))
+                .and(query_param("status", "active"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "error": 0,
+                    "data": { "affected_items": items, "total_affected_items": 5 },
+                })))
+                .mount(&server)
+                .await;
+        }
+
+        let address = server.address();
+        let mut config = Config::default();
+        config.api.protocol = "http".to_string();
+        config.api.host = address.ip().to_string();
+        config.api.port = address.port();
+        config.auth.token = Some("token".to_string());
+        let client = WazuhClient::new(Arc::new(RwLock::new(config))).await.unwrap();
+
+        let list: ApiResponse<ListResponse<u32>> =
+            client.get_pages("/agents?status=active", 2, None).await.unwrap();
+        assert_eq!(list.data.affected_items, vec![0, 1, 2, 3, 4]);
+        assert_eq!(list.data.total_affected_items, 5);
+
+        let list: ApiResponse<ListResponse<u32>> =
+            client.get_pages("/agents?status=active&offset=1", 2, Some(3)).await.unwrap();
+        assert_eq!(list.data.affected_items, vec![1, 2, 3]);
+
+        let list: ApiResponse<ListResponse<u32>> =
+            client.get_pages("/agents?limit=1&status=active", 2, None).await.unwrap();
+        assert_eq!(list.data.affected_items, vec![0]);
+
+        let items: Vec<u32> = client
+            .paginate::<u32>("/agents?status=active&offset=3&limit=2")
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(items, vec![3, 4]);
+
+        assert!(split_paging("/agents?limit=all").is_err());
+    }
+
+    #[test]
+    fn test_latency_budget() {
+        assert_eq!(endpoint_path("/sca/001/checks/cis?limit=10"), "/sca/{id}/checks/cis");
+
+        let budgets = BTreeMap::from([
+            ("/manager".to_string(), 5000),
+            ("/manager/logs".to_string(), 2000),
+        ]);
+        assert_eq!(latency_budget(&budgets, 10_000, "/manager/logs/summary"), 2000);
+        assert_eq!(latency_budget(&budgets, 10_000, "/manager/info"), 5000);
+        assert_eq!(latency_budget(&budgets, 10_000, "/agents"), 10_000);
+    }
+
+    #[test]
+    fn test_is_long_running() {
+        assert!(is_long_running(&endpoint_path("/agents/upgrade?agents_list=001,002")));
+        assert!(is_long_running("/agents/upgrade_custom"));
+        assert!(is_long_running("/manager/configuration/validation"));
+        assert!(!is_long_running("/agents"));
+        assert!(!is_long_running("/manager/info"));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("30"), Some(StdDuration::from_secs(30)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(StdDuration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        assert_eq!(backoff_delay(0), StdDuration::from_secs(2));
+        assert_eq!(backoff_delay(2), StdDuration::from_secs(8));
+        assert_eq!(backoff_delay(10), StdDuration::from_secs(MAX_BACKOFF_SECS));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_tunnel_socket_keeps_the_api_host() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("api.sock");
+        let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0u8; 4096];
+            let read = stream.read(&mut request).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&request[..read]).to_lowercase()
+        });
+
+        let (builder, base_url) = through_tunnel(
+            ClientBuilder::new(),
+            "http://10.0.0.5:55000".to_string(),
+            "10.0.0.5",
+            &TunnelEnd::Unix(socket),
+        )
+        .unwrap();
+        assert_eq!(base_url, "http://10.0.0.5:55000");
+        let response = builder.build().unwrap().get(format!("{}/", base_url)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(server.await.unwrap().contains("host: 10.0.0.5:55000"));
+    }
+
+    #[tokio::test]
+    async fn test_tunnel_port_keeps_the_api_hostname() {
+        use wiremock::matchers::{header, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let local = *server.address();
+        Mock::given(method("GET"))
+            .and(header("host", format!("manager.example:{}", local.port()).as_str()))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let (builder, base_url) = through_tunnel(
+            ClientBuilder::new(),
+            "http://manager.example:55000".to_string(),
+            "manager.example",
+            &TunnelEnd::Tcp(local),
+        )
+        .unwrap();
+        assert_eq!(base_url, format!("http://manager.example:{}", local.port()));
+        let response = builder.build().unwrap().get(format!("{}/", base_url)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // An address cannot be redirected without losing the name TLS checks
+        let ip = through_tunnel(
+            ClientBuilder::new(),
+            "https://10.0.0.5:55000".to_string(),
+            "10.0.0.5",
+            &TunnelEnd::Tcp(local),
+        );
+        assert!(ip.is_err());
+    }
 }
\ No newline at end of file
@@ -1,27 +1,77 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use colored::Colorize;
 use dialoguer::Confirm;
-use indicatif::{ProgressBar, ProgressStyle};
+use futures::TryStreamExt;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::pin::pin;
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::{RwLock, Semaphore};
+use tokio::task::JoinSet;
 use tracing::{debug, info};
 
 use crate::{
-    cli::{AgentAction, AgentCommand},
+    checkins::{self, AnomalyKind, CheckinSnapshot, Sensitivity},
+    cli::{AgentAction, AgentCommand, AgentFilterArgs, KeyAction},
     client::WazuhClient,
+    commands::{
+        group::{bulk_failures, handle_agent_group},
+        control::fetch_max_agents,
+        monitor::fetch_daemon_stats,
+    },
     config::Config,
-    models::{AddAgentRequest, AgentListResponse, AgentParams, ApiResponse},
-    output::{print_agents_table, print_json, print_single_agent},
+    disconnect::{self, DisconnectReport},
+    error::WazuhError,
+    history::{self, HistoryAction, HistoryEvent},
+    indexer::{AlertQuery, IndexerClient},
+    join::{CsvLookup, JoinedColumns},
+    models::{
+        AddAgentRequest, Agent, AgentKeyResult, AgentListResponse, AgentParams, AgentRunResult,
+        AgentStatus, AgentSummary, ApiResponse, BulkResult, ListResponse, RunStatus,
+        VersionMatrix, VersionMatrixRow,
+    },
+    output::{
+        format_age, line_per_item, print_agent_keys, print_agent_summary, print_agents_table,
+        print_anomalies, print_bulk_results, print_config_tree, print_csv,
+        print_disconnect_report, print_info, print_json, print_markdown, print_run_results,
+        print_single_agent, print_success, print_version_matrix, print_warning, spinner,
+        WarningKind,
+    },
+    progress::Progress,
+    selector::Selector,
+    ssh::{shell_quote, SshTarget},
+    utils::{
+        escape_lucene, glob_match, is_agent_id, json_field, json_to_text, normalize_agent_id,
+        parse_duration, parse_version, validate_agent_name,
+    },
 };
 
+/// Agent IDs per bulk request, and per lookup of the targets' names
+const BULK_BATCH_SIZE: usize = 500;
+
+/// Matching agents listed before a bulk action asks for confirmation
+const PREVIEW_LIMIT: usize = 20;
+
+/// Agent keys fetched at the same time by `agent key --group/--os/--query`
+const KEY_CONCURRENCY: usize = 10;
+
+/// Agents with similar names suggested when a name matches none or several
+const NAME_CANDIDATES: u32 = 10;
+
+/// Manager's own agent entry, never a target of bulk actions
+const MANAGER_AGENT_ID: &str = "000";
+
+/// How often `agent run` checks the indexer for active-response results
+const RUN_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
 pub async fn handle_agent_command(
     cmd: AgentCommand,
     config: &Config,
     json_output: bool,
 ) -> Result<()> {
-    let config = Arc::new(RwLock::new(config.clone()));
-    let client = WazuhClient::new(config).await?;
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
     
     // Ensure we're authenticated
     client.authenticate().await?;
@@ -31,78 +81,380 @@ pub async fn handle_agent_command(
             status,
             os,
             version,
+            group,
+            select,
             count,
-        } => list_agents(&client, status, os, version, count, json_output).await?,
+            join,
+            on,
+            add_columns,
+            list,
+        } => {
+            let selector = select.as_deref().map(Selector::parse).transpose()?;
+            let params = AgentParams {
+                status,
+                os_platform: os,
+                version,
+                group,
+                sort: list.sort,
+                limit: list.limit,
+                offset: list.offset,
+                search: list.search,
+                ..Default::default()
+            };
+            let lookup = join
+                .map(|path| CsvLookup::load(&path, &on, &add_columns))
+                .transpose()?;
+            let filter = AgentListFilter { params, selector };
+            list_agents(&client, filter, count, lookup.as_ref(), json_output).await?
+        }
         
-        AgentAction::Get { agent } => get_agent(&client, &agent, json_output).await?,
+        AgentAction::Get { agent } => {
+            let agent = resolve_agent_id(&client, &agent).await?;
+            get_agent(&client, &agent, json_output).await?
+        }
+
+        AgentAction::Config {
+            agent,
+            component,
+            configuration,
+        } => {
+            let agent = resolve_agent_id(&client, &agent).await?;
+            get_agent_config(&client, &agent, &component, &configuration, json_output).await?
+        }
         
         AgentAction::Add { name, ip, force } => {
             add_agent(&client, name, ip, force, json_output).await?
         }
         
-        AgentAction::Remove { agent, yes } => {
-            remove_agent(&client, &agent, yes, json_output).await?
-        }
+        AgentAction::Remove {
+            agents,
+            select,
+            filter,
+            parallel,
+            yes,
+        } => match bulk_targets(agents, select, filter)?.resolve_names(&client).await? {
+            BulkTargets::Listed(ids) if ids.len() == 1 => {
+                remove_agent(&client, &ids[0], yes, json_output).await?
+            }
+            targets => {
+                let options = BulkOptions { parallel, skip_confirm: yes };
+                bulk_action(&client, BulkAction::Remove, &targets, &options, json_output).await?
+            }
+        },
         
-        AgentAction::Restart { agent } => restart_agent(&client, &agent, json_output).await?,
+        AgentAction::Prune {
+            status,
+            older_than,
+            never_connected,
+            dry_run,
+            yes,
+        } => {
+            let mut statuses = vec![status];
+            if never_connected && statuses[0] != "never_connected" {
+                statuses.push("never_connected".to_string());
+            }
+            let filter = PruneFilter::new(statuses, &older_than)?;
+            prune_agents(&client, &filter, dry_run, yes, json_output).await?
+        }
+
+        AgentAction::Rename {
+            agent,
+            new_name,
+            dry_run,
+            yes,
+        } => {
+            let agent = resolve_agent_id(&client, &agent).await?;
+            rename_agent(&client, &agent, &new_name, dry_run, yes, json_output).await?
+        }
+
+        AgentAction::Restart {
+            agents,
+            select,
+            filter,
+            parallel,
+            yes,
+        } => match bulk_targets(agents, select, filter)?.resolve_names(&client).await? {
+            BulkTargets::Listed(ids) if ids.len() == 1 => {
+                restart_agent(&client, &ids[0], json_output).await?
+            }
+            targets => {
+                let options = BulkOptions { parallel, skip_confirm: yes };
+                bulk_action(&client, BulkAction::Restart, &targets, &options, json_output).await?
+            }
+        },
         
         AgentAction::Upgrade {
-            agent,
+            agents,
+            select,
+            filter,
+            parallel,
             version,
             force,
-        } => upgrade_agent(&client, &agent, version, force, json_output).await?,
+            yes,
+        } => match bulk_targets(agents, select, filter)?.resolve_names(&client).await? {
+            BulkTargets::Listed(ids) if ids.len() == 1 => {
+                upgrade_agent(&client, &ids[0], version, force, json_output).await?
+            }
+            targets => {
+                let action = BulkAction::Upgrade(upgrade_body(version, force));
+                let options = BulkOptions { parallel, skip_confirm: yes };
+                bulk_action(&client, action, &targets, &options, json_output).await?
+            }
+        },
         
-        AgentAction::Key { agent } => get_agent_key(&client, &agent, json_output).await?,
+        AgentAction::Summary { recent } => agent_summary(&client, recent, json_output).await?,
+
+        AgentAction::Versions {
+            matrix,
+            format,
+            min_version,
+        } => {
+            let min_version = min_version.or_else(|| config.agents.min_version.clone());
+            agent_versions(&client, matrix, &format, min_version.as_deref(), json_output).await?
+        }
+
+        AgentAction::Anomalies {
+            window,
+            deviations,
+            min_drop,
+            flap_changes,
+            no_record,
+        } => {
+            let sensitivity = Sensitivity {
+                deviations,
+                min_drop,
+                flap_changes: flap_changes.max(1),
+            };
+            agent_anomalies(&client, config, &window, &sensitivity, !no_record, json_output)
+                .await?
+        }
+
+        AgentAction::WhyDisconnected { agent, logs, since } => {
+            let agent = resolve_agent_id(&client, &agent).await?;
+            why_disconnected(&client, config, &agent, logs, &since, json_output).await?
+        }
+
+        AgentAction::Run {
+            agents,
+            select,
+            command,
+            args,
+            timeout,
+            yes,
+        } => {
+            let request = RunRequest {
+                command,
+                args,
+                wait: true,
+                timeout: Duration::from_secs(timeout),
+                skip_confirm: yes,
+            };
+            let targets = match (agents, select) {
+                (_, Some(select)) => RunTargets::Selected(Selector::parse(&select)?),
+                (Some(agents), None) => RunTargets::Named(agents),
+                (None, None) => return Err(missing_target()),
+            };
+            run_command(&client, config, &targets, &request, json_output).await?
+        }
+
+        AgentAction::Key {
+            agent,
+            filter,
+            action,
+        } => match (agent, action) {
+            (_, Some(KeyAction::Push {
+                agent,
+                host,
+                port,
+                identity,
+                keys_path,
+                no_sudo,
+                no_restart,
+                yes,
+            })) => {
+                let target = SshTarget {
+                    destination: host,
+                    port,
+                    identity,
+                };
+                let options = KeyPushOptions {
+                    keys_path,
+                    sudo: !no_sudo,
+                    restart: !no_restart,
+                    skip_confirm: yes,
+                };
+                let agent = resolve_agent_id(&client, &agent).await?;
+                push_agent_key(&client, &agent, &target, &options, json_output).await?
+            }
+            (Some(agent), None) => {
+                let agent = resolve_agent_id(&client, &agent).await?;
+                get_agent_key(&client, &agent, json_output).await?
+            }
+            (None, None) if filter.is_set() => {
+                let matching = AgentMatch {
+                    filter,
+                    selector: None,
+                };
+                get_agent_keys(&client, &matching, json_output).await?
+            }
+            (None, None) => {
+                return Err(WazuhError::InvalidInput(
+                    "An agent ID is required (or use 'agent key push')".to_string(),
+                )
+                .into())
+            }
+        },
+
+        AgentAction::Group { action } => handle_agent_group(&client, action, json_output).await?,
+    }
+
+    Ok(())
+}
+
+/// Filter flags of `agent list`, plus an optional selector checked locally
+struct AgentListFilter {
+    params: AgentParams,
+    selector: Option<Selector>,
+}
+
+/// Error for a command given neither agents nor `--select` (normally caught by clap)
+fn missing_target() -> anyhow::Error {
+    WazuhError::InvalidInput("An agent or --select is required".to_string()).into()
+}
+
+/// Agents matching a selector, in their original order
+fn retain_selected(agents: Vec<Agent>, selector: &Selector) -> Result<Vec<Agent>> {
+    let now = chrono::Utc::now();
+    let mut selected = Vec::new();
+    for agent in agents {
+        if selector.matches(&serde_json::to_value(&agent)?, now) {
+            selected.push(agent);
+        }
     }
+    Ok(selected)
+}
+
+/// The items in `[offset, offset + limit)`, as the API pages its own results
+fn page<T>(items: Vec<T>, offset: Option<u32>, limit: Option<u32>) -> Vec<T> {
+    let offset = offset.unwrap_or(0) as usize;
+    let limit = limit.map_or(usize::MAX, |limit| limit as usize);
+    items.into_iter().skip(offset).take(limit).collect()
+}
+
+/// IDs of the agents in a group, for commands that take a group as target
+pub async fn group_agent_ids(client: &WazuhClient, group: &str) -> Result<Vec<String>> {
+    let params = AgentParams {
+        group: Some(group.to_string()),
+        ..Default::default()
+    };
+    let url = format!("/agents?{}", serde_urlencoded::to_string(&params)?);
+    let api_response: ApiResponse<AgentListResponse> = client.get_list(&url).await?;
+    Ok(api_response
+        .data
+        .affected_items
+        .into_iter()
+        .map(|agent| agent.id)
+        .filter(|id| id != MANAGER_AGENT_ID)
+        .collect())
+}
+
+/// Every agent matching a selector; its plain equality terms narrow the API
+/// query and the full selector is then checked locally
+pub async fn select_agents(client: &WazuhClient, selector: &Selector) -> Result<Vec<Agent>> {
+    let matching = AgentMatch {
+        filter: AgentFilterArgs::default(),
+        selector: Some(selector.clone()),
+    };
+    match_agents(client, &matching).await
+}
 
+/// Print agents one line each as their pages arrive, for very large fleets
+async fn stream_agents(client: &WazuhClient, url: &str, mut progress: Progress) -> Result<()> {
+    let mut agents = pin!(client.paginate::<Agent>(url)?);
+    while let Some(agent) = agents.try_next().await? {
+        progress.page_fetched(1);
+        print_json(&agent)?;
+    }
+    progress.finish();
     Ok(())
 }
 
 async fn list_agents(
     client: &WazuhClient,
-    status: Option<String>,
-    os: Option<String>,
-    version: Option<String>,
+    filter: AgentListFilter,
     count_only: bool,
+    lookup: Option<&CsvLookup>,
     json_output: bool,
 ) -> Result<()> {
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.green} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Fetching agents...");
-    pb.enable_steady_tick(Duration::from_millis(120));
-
-    let mut params = AgentParams::default();
-    params.status = status;
-    params.os_platform = os;
-    params.version = version;
+    let pb = spinner("Fetching agents...");
+    let mut progress = Progress::start("agent list", None);
 
+    let mut params = filter.params;
+    // With a selector, --offset and --limit page through the locally filtered agents
+    let (offset, limit) = (params.offset, params.limit);
+    if let Some(selector) = &filter.selector {
+        // Local filtering needs every candidate, not just the first page
+        params.limit = None;
+        params.offset = None;
+        params.q = selector.api_query();
+    } else if count_only {
+        // The manager reports the total with any page, so one agent is enough
+        params.limit = Some(1);
+        params.offset = None;
+    }
     let query_string = serde_urlencoded::to_string(&params)?;
     let url = format!("/agents?{}", query_string);
     
     debug!("Fetching agents with params: {:?}", params);
-    let response = client.get(&url).await?;
-    let api_response: ApiResponse<AgentListResponse> = 
-        WazuhClient::parse_response(response).await?;
+    if count_only && filter.selector.is_none() {
+        let response = client.get(&url).await?;
+        let api_response: ApiResponse<AgentListResponse> =
+            WazuhClient::parse_response(response).await?;
+        pb.finish_and_clear();
+        progress.finish();
+        println!("Total agents: {}", api_response.data.total_affected_items);
+        return Ok(());
+    }
+    let streamable = filter.selector.is_none() && lookup.is_none() && !count_only;
+    if json_output && line_per_item() && streamable {
+        pb.finish_and_clear();
+        return stream_agents(client, &url, progress).await;
+    }
+    let api_response: ApiResponse<AgentListResponse> = client.get_list(&url).await?;
     
     pb.finish_and_clear();
+    progress.page_fetched(api_response.data.affected_items.len() as u64);
+    progress.finish();
+
+    let mut total = api_response.data.total_affected_items;
+    let mut agents = api_response.data.affected_items;
+    if let Some(selector) = &filter.selector {
+        agents = retain_selected(agents, selector)?;
+        total = agents.len() as _;
+        agents = page(agents, offset, limit);
+    }
 
     if count_only {
-        println!("Total agents: {}", api_response.data.total_affected_items);
+        println!("Total agents: {}", total);
         return Ok(());
     }
 
+    let agents = &agents;
+    let joined = lookup.map(|lookup| lookup.join(agents)).unwrap_or_default();
+
     if json_output {
-        print_json(&api_response.data.affected_items)?;
+        if lookup.is_some() {
+            print_json(&joined.merge(agents)?)?;
+        } else {
+            print_json(agents)?;
+        }
     } else {
-        print_agents_table(&api_response.data.affected_items);
-        println!(
-            "\nTotal: {} agents",
-            api_response.data.total_affected_items
-        );
+        print_agents_table(agents, &joined);
+        if (agents.len() as u32) < total {
+            println!("\nShowing: {} of {} agents", agents.len(), total);
+        } else {
+            println!("\nTotal: {} agents", total);
+        }
     }
 
     Ok(())
@@ -129,6 +481,35 @@ async fn get_agent(client: &WazuhClient, agent_id: &str, json_output: bool) -> R
     Ok(())
 }
 
+/// Configuration the agent has loaded, as opposed to what agent.conf says
+async fn get_agent_config(
+    client: &WazuhClient,
+    agent_id: &str,
+    component: &str,
+    configuration: &str,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Fetching agent configuration...");
+    let url = format!("/agents/{}/config/{}/{}", agent_id, component, configuration);
+    let response = client.get(&url).await?;
+    let api_response: ApiResponse<serde_json::Value> =
+        WazuhClient::parse_response(response).await?;
+    pb.finish_and_clear();
+
+    if json_output {
+        return print_json(&api_response.data);
+    }
+    if api_response.data.as_object().is_none_or(|config| config.is_empty()) {
+        print_info(&format!(
+            "Agent '{}' reported no {} {} configuration",
+            agent_id, component, configuration
+        ));
+        return Ok(());
+    }
+    print_config_tree(&api_response.data, 0);
+    Ok(())
+}
+
 async fn add_agent(
     client: &WazuhClient,
     name: String,
@@ -136,14 +517,7 @@ async fn add_agent(
     force: bool,
     json_output: bool,
 ) -> Result<()> {
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.green} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Adding new agent...");
-    pb.enable_steady_tick(Duration::from_millis(120));
+    let pb = spinner("Adding new agent...");
 
     let request = AddAgentRequest {
         name: name.clone(),
@@ -157,6 +531,10 @@ async fn add_agent(
     
     pb.finish_and_clear();
 
+    if let Some(id) = api_response.data.get("id").and_then(|id| id.as_str()) {
+        history::record_quietly(HistoryEvent::new(HistoryAction::Added, id, Some(&name)));
+    }
+
     if json_output {
         print_json(&api_response)?;
     } else {
@@ -195,14 +573,7 @@ async fn remove_agent(
         }
     }
 
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.green} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Removing agent...");
-    pb.enable_steady_tick(Duration::from_millis(120));
+    let pb = spinner("Removing agent...");
 
     let url = format!("/agents/{}", agent_id);
     let response = client.delete(&url).await?;
@@ -211,6 +582,8 @@ async fn remove_agent(
     
     pb.finish_and_clear();
 
+    history::record_quietly(HistoryEvent::new(HistoryAction::Removed, agent_id, None));
+
     if json_output {
         print_json(&api_response)?;
     } else {
@@ -224,19 +597,370 @@ async fn remove_agent(
     Ok(())
 }
 
-async fn restart_agent(
+/// Agents removed by `agent prune`
+struct PruneFilter {
+    statuses: Vec<String>,
+    /// Time since the last keep-alive (or registration, for agents that never
+    /// connected)
+    older_than: chrono::Duration,
+}
+
+impl PruneFilter {
+    /// A zero age would match every agent with the status, so it is refused
+    fn new(statuses: Vec<String>, older_than: &str) -> Result<Self> {
+        let older_than_span = parse_duration(older_than)?;
+        if older_than_span <= chrono::Duration::zero() {
+            return Err(WazuhError::InvalidInput(format!(
+                "--older-than must be a positive duration, got '{}'",
+                older_than
+            ))
+            .into());
+        }
+        Ok(PruneFilter {
+            statuses,
+            older_than: older_than_span,
+        })
+    }
+
+    /// The API's `status` and `older_than` parameters
+    fn query(&self) -> String {
+        format!(
+            "status={}&older_than={}s",
+            self.statuses.join(","),
+            self.older_than.num_seconds()
+        )
+    }
+}
+
+/// Remove the agents matching a status and age, in batches of agent IDs
+async fn prune_agents(
+    client: &WazuhClient,
+    filter: &PruneFilter,
+    dry_run: bool,
+    skip_confirm: bool,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Finding stale agents...");
+    let url = format!("/agents?{}", filter.query());
+    let api_response: ApiResponse<AgentListResponse> = client.get_list(&url).await?;
+    pb.finish_and_clear();
+    let agents: Vec<Agent> = api_response
+        .data
+        .affected_items
+        .into_iter()
+        .filter(|agent| agent.id != MANAGER_AGENT_ID)
+        .collect();
+
+    let description = format!(
+        "{} for more than {}",
+        filter.statuses.join(" or "),
+        format_age(filter.older_than.num_seconds())
+    );
+    if agents.is_empty() {
+        if json_output {
+            return print_json(&agents);
+        }
+        print_info(&format!("No agents {}", description));
+        return Ok(());
+    }
+
+    if json_output && dry_run {
+        return print_json(&agents);
+    }
+    if !json_output {
+        print_agents_table(&agents, &JoinedColumns::default());
+        println!();
+    }
+    if dry_run {
+        println!("{} agents {} would be removed (dry run)", agents.len(), description);
+        return Ok(());
+    }
+
+    if !skip_confirm {
+        let confirm = Confirm::new()
+            .with_prompt(format!("Remove {} agents {}?", agents.len(), description))
+            .default(false)
+            .interact()?;
+        if !confirm {
+            println!("Operation cancelled");
+            return Ok(());
+        }
+    }
+
+    let mut progress = Progress::start("agent prune", Some(agents.len() as u64));
+    let mut removed = Vec::new();
+    let mut failures = HashMap::new();
+    for batch in agents.chunks(BULK_BATCH_SIZE) {
+        let ids: Vec<&str> = batch.iter().map(|agent| agent.id.as_str()).collect();
+        // The filter is sent again, so agents that reconnected since are kept
+        let url = format!("/agents?agents_list={}&{}", ids.join(","), filter.query());
+        let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+            match prune_batch(client, &url).await {
+                Ok(api_response) => api_response,
+                // Earlier batches are already removed; report them with this failure
+                Err(e) => {
+                    let error = format!("{:#}", e);
+                    for id in ids {
+                        progress.item_done(id, Some(&error));
+                        failures.insert(id.to_string(), error.clone());
+                    }
+                    continue;
+                }
+            };
+        let data = serde_json::to_value(&api_response.data)?;
+        record_bulk_result(&mut progress, &data);
+        for item in &api_response.data.affected_items {
+            let id = json_to_text(Some(item));
+            history::record_quietly(HistoryEvent::new(HistoryAction::Removed, &id, None));
+            removed.push(id);
+        }
+        failures.extend(bulk_failures(&api_response.data.failed_items));
+    }
+    progress.finish();
+
+    if json_output {
+        print_json(&serde_json::json!({
+            "affected_items": removed,
+            "failed_items": failures,
+        }))?;
+    } else {
+        println!("{} Removed {} agents", "✓".green().bold(), removed.len());
+        let kept = agents.len().saturating_sub(removed.len() + failures.len());
+        if kept > 0 {
+            print_info(&format!("{} agents no longer matched and were kept", kept));
+        }
+        if !failures.is_empty() {
+            let mut failed: Vec<String> =
+                failures.iter().map(|(id, error)| format!("{} ({})", id, error)).collect();
+            failed.sort();
+            print_warning(
+                WarningKind::PartialFailure,
+                &format!("{} agents failed: {}", failed.len(), failed.join(", ")),
+            );
+        }
+    }
+
+    if !failures.is_empty() {
+        return Err(anyhow!("Could not remove {} of {} agents", failures.len(), agents.len()));
+    }
+    Ok(())
+}
+
+/// Send one batch of a prune
+async fn prune_batch(
+    client: &WazuhClient,
+    url: &str,
+) -> Result<ApiResponse<ListResponse<serde_json::Value>>> {
+    let response = client.delete(url).await?;
+    WazuhClient::parse_response(response).await
+}
+
+/// Group every agent belongs to; it is re-applied by the manager on insert
+const DEFAULT_GROUP: &str = "default";
+
+async fn rename_agent(
     client: &WazuhClient,
     agent_id: &str,
+    new_name: &str,
+    dry_run: bool,
+    skip_confirm: bool,
     json_output: bool,
 ) -> Result<()> {
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.green} {msg}")
-            .unwrap(),
+    validate_agent_name(new_name)?;
+    let pb = spinner("Preparing rename...");
+    let url = format!("/agents?agents_list={}", agent_id);
+    let response = client.get(&url).await?;
+    let api_response: ApiResponse<AgentListResponse> =
+        WazuhClient::parse_response(response).await?;
+    let Some(agent) = api_response.data.affected_items.into_iter().next() else {
+        pb.finish_and_clear();
+        return Err(anyhow!("Agent '{}' not found", agent_id));
+    };
+    if agent.id == MANAGER_AGENT_ID {
+        pb.finish_and_clear();
+        return Err(anyhow!("The manager's own agent entry cannot be renamed"));
+    }
+    if agent.name == new_name {
+        pb.finish_and_clear();
+        return Err(anyhow!("Agent {} is already named '{}'", agent.id, new_name));
+    }
+
+    let query = serde_urlencoded::to_string([("name", new_name), ("select", "id")])?;
+    let url = format!("/agents?{}", query);
+    let response = client.get(&url).await?;
+    let existing: ApiResponse<AgentListResponse> = WazuhClient::parse_response(response).await?;
+    if let Some(other) = existing.data.affected_items.first() {
+        pb.finish_and_clear();
+        return Err(anyhow!("Agent {} is already named '{}'", other.id, new_name));
+    }
+
+    // The key is carried over so the agent reconnects without re-enrolling
+    let url = format!("/agents/{}/key", agent.id);
+    let response = client.get(&url).await?;
+    let key_response: ApiResponse<serde_json::Value> =
+        WazuhClient::parse_response(response).await?;
+    let encoded = extract_agent_key(&key_response.data)
+        .ok_or_else(|| WazuhError::NotFound(format!("No key returned for agent '{}'", agent.id)))?;
+    let keys_line = decode_agent_key(encoded)?;
+    let (registered_ip, secret) = match keys_line.split_whitespace().collect::<Vec<_>>()[..] {
+        [_, _, ip, secret] => (ip.to_string(), secret.to_string()),
+        _ => unreachable!("decode_agent_key checks the field count"),
+    };
+    pb.finish_and_clear();
+
+    let groups: Vec<String> = agent
+        .group
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|g| g != DEFAULT_GROUP)
+        .collect();
+
+    if !json_output {
+        println!(
+            "{} Renaming agent {} from '{}' to '{}'",
+            "→".blue().bold(),
+            agent.id,
+            agent.name,
+            new_name
+        );
+        println!(
+            "  • The agent is removed and re-inserted with the same ID, key and IP ({})",
+            registered_ip
+        );
+        if !groups.is_empty() {
+            println!("  • Group membership is re-applied: {}", groups.join(", "));
+        }
+        println!("  • FIM, inventory, SCA and vulnerability data for this agent is deleted");
+        println!("    and rebuilt by its next scans");
+        println!("  • The agent's own client.keys keeps the old name until you run");
+        println!("    'wazuh-cli agent key push {} --host <agent host>'", agent.id);
+    }
+
+    if dry_run {
+        if json_output {
+            print_json(&serde_json::json!({
+                "agent": agent.id,
+                "old_name": agent.name,
+                "new_name": new_name,
+                "ip": registered_ip,
+                "groups": groups,
+                "dry_run": true,
+            }))?;
+        }
+        return Ok(());
+    }
+
+    if !skip_confirm {
+        let confirm = Confirm::new()
+            .with_prompt(format!("Rename agent {} to '{}'?", agent.id, new_name))
+            .default(false)
+            .interact()?;
+        if !confirm {
+            println!("Operation cancelled");
+            return Ok(());
+        }
+    }
+
+    let pb = spinner("Removing agent...");
+    // Purging frees the ID and key for the re-insert
+    let url = format!(
+        "/agents?agents_list={}&status=all&older_than=0s&purge=true",
+        agent.id
     );
-    pb.set_message("Restarting agent...");
-    pb.enable_steady_tick(Duration::from_millis(120));
+    let response = client.delete(&url).await?;
+    let removed: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+    if let Some(error) = bulk_failures(&removed.data.failed_items).remove(&agent.id) {
+        pb.finish_and_clear();
+        return Err(anyhow!("Failed to remove agent {}: {}", agent.id, error));
+    }
+
+    pb.set_message("Registering agent under the new name...");
+    if let Err(e) = insert_agent(client, &agent.id, new_name, &registered_ip, &secret).await {
+        // Put the agent back as it was rather than leaving it unregistered
+        pb.set_message("Rename failed, restoring the agent...");
+        let restored =
+            insert_agent(client, &agent.id, &agent.name, &registered_ip, &secret).await;
+        pb.finish_and_clear();
+        return Err(match restored {
+            Ok(()) => e.context(format!(
+                "Rename failed; agent {} was restored as '{}'",
+                agent.id, agent.name
+            )),
+            // The exported key still holds the old name, so importing it restores the agent
+            Err(_) => e.context(format!(
+                "Rename failed and agent {} could not be restored; restore it on the manager \
+                 with '/var/ossec/bin/manage_agents -i {}'",
+                agent.id, encoded
+            )),
+        });
+    }
+
+    let mut failed_groups = Vec::new();
+    for group in &groups {
+        pb.set_message(format!("Re-assigning group {}...", group));
+        let url = format!("/agents/{}/group/{}", agent.id, group);
+        let result = match client.put(&url, None::<()>).await {
+            Ok(response) => WazuhClient::parse_response::<ApiResponse<serde_json::Value>>(response)
+                .await
+                .map(|_| ()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            debug!("Failed to re-assign group {}: {:#}", group, e);
+            failed_groups.push(group.clone());
+        }
+    }
+    pb.finish_and_clear();
+
+    history::record_quietly(HistoryEvent::new(HistoryAction::Renamed, &agent.id, Some(new_name)));
+
+    if json_output {
+        print_json(&serde_json::json!({
+            "agent": agent.id,
+            "old_name": agent.name,
+            "new_name": new_name,
+            "groups": groups,
+            "failed_groups": failed_groups,
+        }))?;
+    } else {
+        print_success(&format!("Agent {} renamed to '{}'", agent.id, new_name));
+        if !failed_groups.is_empty() {
+            print_warning(
+                WarningKind::PartialFailure,
+                &format!(
+                    "Could not re-assign groups {}; use 'wazuh-cli agent group assign'",
+                    failed_groups.join(", ")
+                ),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Register an agent with a known ID and key (`POST /agents/insert`)
+async fn insert_agent(
+    client: &WazuhClient,
+    id: &str,
+    name: &str,
+    ip: &str,
+    key: &str,
+) -> Result<()> {
+    let body = serde_json::json!({ "id": id, "name": name, "ip": ip, "key": key });
+    let response = client.post("/agents/insert", Some(body)).await?;
+    WazuhClient::parse_response::<ApiResponse<serde_json::Value>>(response).await?;
+    Ok(())
+}
+
+async fn restart_agent(
+    client: &WazuhClient,
+    agent_id: &str,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Restarting agent...");
+    let mut progress = Progress::start("agent restart", None);
 
     let url = if agent_id.to_lowercase() == "all" {
         "/agents/restart".to_string()
@@ -249,6 +973,8 @@ async fn restart_agent(
         WazuhClient::parse_response(response).await?;
     
     pb.finish_and_clear();
+    record_bulk_result(&mut progress, &api_response.data);
+    progress.finish();
 
     if json_output {
         print_json(&api_response)?;
@@ -267,6 +993,26 @@ async fn restart_agent(
     Ok(())
 }
 
+/// Report each affected and failed agent of a bulk API call as progress events
+fn record_bulk_result(progress: &mut Progress, data: &serde_json::Value) {
+    if let Some(items) = data.get("affected_items").and_then(|v| v.as_array()) {
+        for item in items {
+            progress.item_done(&json_to_text(Some(item)), None);
+        }
+    }
+
+    if let Some(failures) = data.get("failed_items").and_then(|v| v.as_array()) {
+        for failure in failures {
+            let error = json_to_text(failure.pointer("/error/message"));
+            if let Some(ids) = failure.get("id").and_then(|v| v.as_array()) {
+                for id in ids {
+                    progress.item_done(&json_to_text(Some(id)), Some(&error));
+                }
+            }
+        }
+    }
+}
+
 async fn upgrade_agent(
     client: &WazuhClient,
     agent_id: &str,
@@ -274,23 +1020,10 @@ async fn upgrade_agent(
     force: bool,
     json_output: bool,
 ) -> Result<()> {
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.green} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Upgrading agent...");
-    pb.enable_steady_tick(Duration::from_millis(120));
-
-    let mut body = serde_json::json!({});
-    if let Some(v) = version {
-        body["version"] = serde_json::json!(v);
-    }
-    if force {
-        body["force"] = serde_json::json!(true);
-    }
+    let pb = spinner("Upgrading agent...");
+    let mut progress = Progress::start("agent upgrade", None);
 
+    let body = upgrade_body(version, force);
     let url = if agent_id.to_lowercase() == "all" {
         "/agents/upgrade".to_string()
     } else {
@@ -302,6 +1035,8 @@ async fn upgrade_agent(
         WazuhClient::parse_response(response).await?;
     
     pb.finish_and_clear();
+    record_bulk_result(&mut progress, &api_response.data);
+    progress.finish();
 
     if json_output {
         print_json(&api_response)?;
@@ -315,32 +1050,1658 @@ async fn upgrade_agent(
                 agent_id
             );
         }
+        print_info("Follow the upgrade with: wazuh-cli tasks list --command upgrade --watch");
     }
 
     Ok(())
 }
 
-async fn get_agent_key(
-    client: &WazuhClient,
-    agent_id: &str,
-    json_output: bool,
-) -> Result<()> {
-    info!("Fetching key for agent: {}", agent_id);
-    
-    let url = format!("/agents/{}/key", agent_id);
-    let response = client.get(&url).await?;
-    let api_response: ApiResponse<serde_json::Value> = 
-        WazuhClient::parse_response(response).await?;
-    
-    if json_output {
-        print_json(&api_response)?;
-    } else {
-        if let Some(key) = api_response.data.get("key") {
-            println!("Agent key for '{}': {}", agent_id, key);
-        } else {
-            eprintln!("{} Could not retrieve agent key", "Error:".red().bold());
-        }
+fn upgrade_body(version: Option<String>, force: bool) -> serde_json::Value {
+    let mut body = serde_json::json!({});
+    if let Some(v) = version {
+        body["version"] = serde_json::json!(v);
+    }
+    if force {
+        body["force"] = serde_json::json!(true);
+    }
+    body
+}
+
+/// Agents matched by `--group`, `--os`, `--query` and `--select` together
+pub struct AgentMatch {
+    pub filter: AgentFilterArgs,
+    pub selector: Option<Selector>,
+}
+
+impl fmt::Display for AgentMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(group) = &self.filter.group {
+            parts.push(format!("in group '{}'", group));
+        }
+        if let Some(os) = &self.filter.os {
+            parts.push(format!("on {}", os));
+        }
+        if let Some(query) = &self.filter.query {
+            parts.push(format!("matching query '{}'", query));
+        }
+        if let Some(selector) = &self.selector {
+            parts.push(format!("matching '{}'", selector));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Agents matching a filter and selector, with the API filtering all it can
+pub async fn match_agents(client: &WazuhClient, matching: &AgentMatch) -> Result<Vec<Agent>> {
+    let selector_query = matching.selector.as_ref().and_then(|s| s.api_query());
+    let q = match (&matching.filter.query, selector_query) {
+        (Some(query), Some(selected)) => Some(format!("({});{}", query, selected)),
+        (query, selected) => query.clone().or(selected),
+    };
+    let params = AgentParams {
+        q,
+        os_platform: matching.filter.os.clone(),
+        group: matching.filter.group.clone(),
+        ..Default::default()
+    };
+    let url = format!("/agents?{}", serde_urlencoded::to_string(&params)?);
+    debug!("Selecting agents {} with {}", matching, url);
+
+    let api_response: ApiResponse<AgentListResponse> = client.get_list(&url).await?;
+    match &matching.selector {
+        Some(selector) => retain_selected(api_response.data.affected_items, selector),
+        None => Ok(api_response.data.affected_items),
+    }
+}
+
+/// The ID of an agent given by ID or name
+///
+/// Numeric arguments are normalized (`7` → `007`) and `all` is passed
+/// through. Anything else must be the name of exactly one agent, compared
+/// exactly and then ignoring case; when it is not, the error lists the agents
+/// it could have meant.
+pub async fn resolve_agent_id(client: &WazuhClient, agent: &str) -> Result<String> {
+    let agent = agent.trim();
+    if agent.eq_ignore_ascii_case("all") {
+        return Ok(agent.to_string());
+    }
+    if is_agent_id(agent) {
+        return Ok(normalize_agent_id(agent)?);
+    }
+    // Valid names need no encoding in the query
+    validate_agent_name(agent)?;
+
+    let url = format!("/agents?name={}&select=id,name,status", agent);
+    let response = client.get(&url).await?;
+    let exact: ApiResponse<AgentListResponse> = WazuhClient::parse_response(response).await?;
+    let mut candidates = exact.data.affected_items;
+    if candidates.is_empty() {
+        let url = format!(
+            "/agents?search={}&select=id,name,status&limit={}",
+            agent, NAME_CANDIDATES
+        );
+        let response = client.get(&url).await?;
+        let similar: ApiResponse<AgentListResponse> =
+            WazuhClient::parse_response(response).await?;
+        candidates = similar.data.affected_items;
+    }
+    let id = pick_agent(agent, &candidates)?;
+    debug!("Agent '{}' is {}", agent, id);
+    Ok(id)
+}
+
+/// [`resolve_agent_id`] for each of several agents
+pub async fn resolve_agent_ids(client: &WazuhClient, agents: &[String]) -> Result<Vec<String>> {
+    let mut ids = Vec::with_capacity(agents.len());
+    for agent in agents {
+        ids.push(resolve_agent_id(client, agent).await?);
+    }
+    Ok(ids)
+}
+
+/// The one agent among `candidates` named `name`, preferring an exact match
+fn pick_agent(name: &str, candidates: &[Agent]) -> Result<String> {
+    let exact: Vec<&Agent> = candidates.iter().filter(|a| a.name == name).collect();
+    let named = if exact.is_empty() {
+        candidates.iter().filter(|a| a.name.eq_ignore_ascii_case(name)).collect()
+    } else {
+        exact
+    };
+    if let [agent] = named.as_slice() {
+        return Ok(agent.id.clone());
+    }
+
+    let listed = |agents: &mut dyn Iterator<Item = &Agent>| {
+        agents
+            .map(|a| format!("{} ({}, {})", a.name, a.id, a.status))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if named.len() > 1 {
+        return Err(anyhow!(
+            "'{}' names {} agents: {}; use the agent ID instead",
+            name,
+            named.len(),
+            listed(&mut named.into_iter())
+        ));
+    }
+    if candidates.is_empty() {
+        return Err(WazuhError::NotFound(format!("agent '{}'", name)).into());
+    }
+    Err(anyhow!(
+        "No agent is named '{}'; similar names: {}",
+        name,
+        listed(&mut candidates.iter())
+    ))
+}
+
+/// Agents of a bulk restart, upgrade or removal
+enum BulkTargets {
+    /// IDs given on the command line, or just `all`
+    Listed(Vec<String>),
+    Matching(AgentMatch),
+}
+
+impl BulkTargets {
+    /// Listed agent names replaced by their IDs
+    async fn resolve_names(self, client: &WazuhClient) -> Result<Self> {
+        match self {
+            BulkTargets::Listed(agents) => {
+                Ok(BulkTargets::Listed(resolve_agent_ids(client, &agents).await?))
+            }
+            matching => Ok(matching),
+        }
+    }
+}
+
+impl fmt::Display for BulkTargets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BulkTargets::Listed(ids) => write!(f, "{}", ids.join(", ")),
+            BulkTargets::Matching(matching) => write!(f, "{}", matching),
+        }
+    }
+}
+
+fn bulk_targets(
+    agents: Vec<String>,
+    select: Option<String>,
+    filter: AgentFilterArgs,
+) -> Result<BulkTargets> {
+    if select.is_some() || filter.is_set() {
+        let selector = select.as_deref().map(Selector::parse).transpose()?;
+        return Ok(BulkTargets::Matching(AgentMatch { filter, selector }));
+    }
+    let ids: Vec<String> = agents
+        .into_iter()
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect();
+    if ids.is_empty() {
+        return Err(missing_target());
+    }
+    if ids.len() > 1 && ids.iter().any(|id| id.eq_ignore_ascii_case("all")) {
+        return Err(WazuhError::InvalidInput(
+            "'all' cannot be combined with agent IDs".to_string(),
+        )
+        .into());
+    }
+    Ok(BulkTargets::Listed(ids))
+}
+
+/// What a bulk command does to each agent
+enum BulkAction {
+    Restart,
+    /// Upgrade with this request body
+    Upgrade(serde_json::Value),
+    Remove,
+}
+
+impl BulkAction {
+    fn name(&self) -> &'static str {
+        match self {
+            BulkAction::Restart => "restart",
+            BulkAction::Upgrade(_) => "upgrade",
+            BulkAction::Remove => "remove",
+        }
+    }
+
+    /// Act on a batch of agents with one request
+    async fn apply(
+        &self,
+        client: &WazuhClient,
+        agent_ids: &[String],
+    ) -> Result<ListResponse<serde_json::Value>> {
+        let agents_list = agent_ids.join(",");
+        let response = match self {
+            BulkAction::Restart => {
+                let url = format!("/agents/restart?agents_list={}", agents_list);
+                client.put(&url, None::<()>).await?
+            }
+            BulkAction::Upgrade(body) => {
+                let url = format!("/agents/upgrade?agents_list={}", agents_list);
+                client.put(&url, Some(body)).await?
+            }
+            BulkAction::Remove => {
+                let url = format!("/agents?agents_list={}&status=all&older_than=0s", agents_list);
+                client.delete(&url).await?
+            }
+        };
+        let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+            WazuhClient::parse_response(response).await?;
+        Ok(api_response.data)
+    }
+}
+
+struct BulkOptions {
+    /// Batch requests in flight at the same time
+    parallel: usize,
+    skip_confirm: bool,
+}
+
+/// Outcome of each agent of a batch, given the agents the API listed as failed
+fn batch_results(
+    batch: Vec<(String, String)>,
+    failures: &HashMap<String, String>,
+) -> Vec<BulkResult> {
+    batch
+        .into_iter()
+        .map(|(agent_id, agent_name)| {
+            let error = failures.get(&agent_id).cloned();
+            BulkResult {
+                agent_id,
+                agent_name,
+                success: error.is_none(),
+                error,
+            }
+        })
+        .collect()
+}
+
+/// Restart, upgrade or remove several agents in batches of `BULK_BATCH_SIZE`
+/// IDs, at most `parallel` requests at a time, then summarize the outcome of
+/// each agent
+async fn bulk_action(
+    client: &WazuhClient,
+    action: BulkAction,
+    targets: &BulkTargets,
+    options: &BulkOptions,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Resolving agents...");
+    let agents: Vec<(String, String)> = match targets {
+        BulkTargets::Matching(matching) => match_agents(client, matching)
+            .await?
+            .into_iter()
+            .filter(|agent| agent.id != MANAGER_AGENT_ID)
+            .map(|agent| (agent.id, agent.name))
+            .collect(),
+        BulkTargets::Listed(ids) => {
+            let mut names = agent_names(client, ids).await?;
+            ids.iter()
+                .map(|id| (id.clone(), names.remove(id).unwrap_or_default()))
+                .collect()
+        }
+    };
+    pb.finish_and_clear();
+    if agents.is_empty() {
+        return Err(anyhow!("No agents {}", targets));
+    }
+
+    let mut verb = action.name().to_string();
+    verb[..1].make_ascii_uppercase();
+    if !options.skip_confirm {
+        if let BulkTargets::Matching(_) = targets {
+            print_preview(&agents);
+        }
+        let confirm = Confirm::new()
+            .with_prompt(format!("{} {} agents ({})?", verb, agents.len(), targets))
+            .default(false)
+            .interact()?;
+        if !confirm {
+            println!("Operation cancelled");
+            return Ok(());
+        }
+    }
+
+    let mut progress =
+        Progress::start(&format!("agent {}", action.name()), Some(agents.len() as u64));
+    let action = Arc::new(action);
+    let permits = Arc::new(Semaphore::new(options.parallel));
+    let mut tasks = JoinSet::new();
+    for (index, batch) in agents.chunks(BULK_BATCH_SIZE).enumerate() {
+        let batch = batch.to_vec();
+        let client = client.clone();
+        let action = action.clone();
+        let permits = permits.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let ids: Vec<String> = batch.iter().map(|(id, _)| id.clone()).collect();
+            let outcome = action.apply(&client, &ids).await;
+            (index, batch, outcome)
+        });
+    }
+
+    let mut batches = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        let (index, batch, outcome) = joined?;
+        // A request that failed as a whole fails every agent of its batch
+        let (affected, failures) = match outcome {
+            Ok(data) => (data.affected_items, bulk_failures(&data.failed_items)),
+            Err(e) => {
+                let error = format!("{:#}", e);
+                let failures = batch.iter().map(|(id, _)| (id.clone(), error.clone())).collect();
+                (Vec::new(), failures)
+            }
+        };
+        let results = batch_results(batch, &failures);
+        for result in &results {
+            progress.item_done(&result.agent_id, result.error.as_deref());
+            if result.success && matches!(*action, BulkAction::Remove) {
+                let event = HistoryEvent::new(HistoryAction::Removed, &result.agent_id, None);
+                history::record_quietly(event);
+            }
+        }
+        batches.push((index, affected, results));
+    }
+    progress.finish();
+    batches.sort_by_key(|(index, _, _)| *index);
+    let mut affected = Vec::new();
+    let mut results = Vec::new();
+    for (_, batch_affected, batch_results) in batches {
+        affected.extend(batch_affected);
+        results.extend(batch_results);
+    }
+
+    let failed = results.iter().filter(|result| !result.success).count();
+    if json_output {
+        let failures: BTreeMap<&str, &str> = results
+            .iter()
+            .filter_map(|result| Some((result.agent_id.as_str(), result.error.as_deref()?)))
+            .collect();
+        print_json(&serde_json::json!({
+            "affected_items": affected,
+            "failed_items": failures,
+        }))?;
+    } else {
+        print_bulk_results(&results);
+        if failed == 0 {
+            println!("{} {} requested for {} agents", "✓".green().bold(), verb, results.len());
+        }
+        if matches!(*action, BulkAction::Upgrade(_)) && failed < results.len() {
+            print_info("Follow the upgrade with: wazuh-cli tasks list --command upgrade --watch");
+        }
+    }
+
+    if failed > 0 {
+        return Err(anyhow!("{} failed for {} of {} agents", verb, failed, results.len()));
+    }
+    Ok(())
+}
+
+/// List the agents a filter resolved to before asking to act on them
+fn print_preview(agents: &[(String, String)]) {
+    for (id, name) in agents.iter().take(PREVIEW_LIMIT) {
+        println!("  {}  {}", id, name);
+    }
+    if agents.len() > PREVIEW_LIMIT {
+        println!("  ... and {} more", agents.len() - PREVIEW_LIMIT);
+    }
+}
+
+/// Names of the given agent IDs; IDs the manager does not know are left out
+async fn agent_names(client: &WazuhClient, ids: &[String]) -> Result<HashMap<String, String>> {
+    let mut names = HashMap::new();
+    for batch in ids.chunks(BULK_BATCH_SIZE) {
+        let url = format!("/agents?agents_list={}&select=id,name", batch.join(","));
+        let response = client.get(&url).await?;
+        let api_response: ApiResponse<AgentListResponse> =
+            WazuhClient::parse_response(response).await?;
+        names.extend(
+            api_response
+                .data
+                .affected_items
+                .into_iter()
+                .map(|agent| (agent.id, agent.name)),
+        );
+    }
+    Ok(names)
+}
+
+async fn get_agent_key(
+    client: &WazuhClient,
+    agent_id: &str,
+    json_output: bool,
+) -> Result<()> {
+    info!("Fetching key for agent: {}", agent_id);
+    
+    let url = format!("/agents/{}/key", agent_id);
+    let response = client.get(&url).await?;
+    let api_response: ApiResponse<serde_json::Value> = 
+        WazuhClient::parse_response(response).await?;
+    
+    if json_output {
+        print_json(&api_response)?;
+    } else {
+        if let Some(key) = api_response.data.get("key") {
+            println!("Agent key for '{}': {}", agent_id, key);
+        } else {
+            eprintln!("{} Could not retrieve agent key", "Error:".red().bold());
+        }
+    }
+
+    Ok(())
+}
+
+/// How `agent key push` installs the key on the target host
+struct KeyPushOptions {
+    keys_path: String,
+    sudo: bool,
+    restart: bool,
+    skip_confirm: bool,
+}
+
+/// Keys of every agent matching a filter, fetched concurrently
+async fn get_agent_keys(
+    client: &WazuhClient,
+    matching: &AgentMatch,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Resolving agents...");
+    let agents: Vec<Agent> = match_agents(client, matching)
+        .await?
+        .into_iter()
+        .filter(|agent| agent.id != MANAGER_AGENT_ID)
+        .collect();
+    pb.finish_and_clear();
+    if agents.is_empty() {
+        return Err(anyhow!("No agents {}", matching));
+    }
+
+    let mut progress = Progress::start("agent key", Some(agents.len() as u64));
+    let permits = Arc::new(Semaphore::new(KEY_CONCURRENCY));
+    let mut tasks = JoinSet::new();
+    for (index, agent) in agents.into_iter().enumerate() {
+        let client = client.clone();
+        let permits = permits.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let result = fetch_agent_key(&client, &agent.id).await;
+            (index, agent, result)
+        });
+    }
+
+    let mut keys = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        let (index, agent, result) = joined?;
+        let error = result.as_ref().err().map(|e| format!("{:#}", e));
+        progress.item_done(&agent.id, error.as_deref());
+        keys.push((
+            index,
+            AgentKeyResult {
+                agent_id: agent.id,
+                agent_name: agent.name,
+                key: result.ok(),
+                error,
+            },
+        ));
+    }
+    progress.finish();
+    keys.sort_by_key(|(index, _)| *index);
+    let keys: Vec<AgentKeyResult> = keys.into_iter().map(|(_, key)| key).collect();
+
+    if json_output {
+        print_json(&keys)?;
+    } else {
+        print_agent_keys(&keys);
+    }
+
+    let failed = keys.iter().filter(|key| key.key.is_none()).count();
+    if failed > 0 {
+        return Err(anyhow!("Could not retrieve the keys of {} agents", failed));
+    }
+    Ok(())
+}
+
+async fn fetch_agent_key(client: &WazuhClient, agent_id: &str) -> Result<String> {
+    let response = client.get(&format!("/agents/{}/key", agent_id)).await?;
+    let api_response: ApiResponse<serde_json::Value> =
+        WazuhClient::parse_response(response).await?;
+    extract_agent_key(&api_response.data)
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("No key in the API response"))
+}
+
+/// Find the base64 key in a `/agents/{id}/key` response
+fn extract_agent_key(data: &serde_json::Value) -> Option<&str> {
+    data.pointer("/affected_items/0/key")
+        .or_else(|| data.get("key"))
+        .and_then(|k| k.as_str())
+}
+
+/// Decode an exported agent key into its `client.keys` line (`ID NAME IP KEY`)
+fn decode_agent_key(encoded: &str) -> Result<String> {
+    let decoded = BASE64
+        .decode(encoded.trim())
+        .context("Agent key is not valid base64")?;
+    let line = String::from_utf8(decoded).context("Agent key is not valid UTF-8")?;
+    let line = line.trim().to_string();
+
+    if line.split_whitespace().count() != 4 {
+        return Err(WazuhError::InvalidInput(
+            "Agent key does not have the expected 'ID NAME IP KEY' format".to_string(),
+        )
+        .into());
+    }
+
+    Ok(line)
+}
+
+async fn push_agent_key(
+    client: &WazuhClient,
+    agent_id: &str,
+    target: &SshTarget,
+    options: &KeyPushOptions,
+    json_output: bool,
+) -> Result<()> {
+    let url = format!("/agents/{}/key", agent_id);
+    let response = client.get(&url).await?;
+    let api_response: ApiResponse<serde_json::Value> =
+        WazuhClient::parse_response(response).await?;
+
+    let encoded = extract_agent_key(&api_response.data)
+        .ok_or_else(|| WazuhError::NotFound(format!("No key returned for agent '{}'", agent_id)))?;
+    let keys_line = decode_agent_key(encoded)?;
+
+    if !options.skip_confirm {
+        let confirm = Confirm::new()
+            .with_prompt(format!(
+                "Overwrite {} on '{}' with the key of agent '{}'?",
+                options.keys_path, target.destination, agent_id
+            ))
+            .default(false)
+            .interact()?;
+
+        if !confirm {
+            println!("Operation cancelled");
+            return Ok(());
+        }
+    }
+
+    let sudo = if options.sudo { "sudo -n " } else { "" };
+    let path = shell_quote(&options.keys_path);
+
+    // The key travels over stdin so it never appears in a remote process listing
+    let install = format!(
+        "umask 027 && cat > {path} && chmod 640 {path} && (chgrp wazuh {path} 2>/dev/null || chgrp ossec {path} 2>/dev/null || true)",
+        path = path
+    );
+
+    let pb = spinner(&format!("Installing key on {}...", target.destination));
+    let output = target
+        .run(
+            &format!("{}sh -c {}", sudo, shell_quote(&install)),
+            Some(format!("{}\n", keys_line).as_bytes()),
+        )
+        .await?;
+    if !output.success {
+        pb.finish_and_clear();
+        return Err(anyhow::anyhow!(
+            "Failed to install key on {}: {}",
+            target.destination,
+            output.stderr.trim()
+        ));
+    }
+
+    let mut restarted = false;
+    if options.restart {
+        pb.set_message("Restarting agent service...");
+        let restart = "systemctl restart wazuh-agent 2>/dev/null || /var/ossec/bin/wazuh-control restart";
+        let output = target
+            .run(&format!("{}sh -c {}", sudo, shell_quote(restart)), None)
+            .await?;
+        if !output.success {
+            pb.finish_and_clear();
+            return Err(anyhow::anyhow!(
+                "Key installed but the agent restart failed on {}: {}",
+                target.destination,
+                output.stderr.trim()
+            ));
+        }
+        restarted = true;
+    }
+
+    pb.finish_and_clear();
+
+    if json_output {
+        print_json(&serde_json::json!({
+            "agent": agent_id,
+            "host": target.destination,
+            "keys_path": options.keys_path,
+            "restarted": restarted,
+        }))?;
+    } else {
+        print_success(&format!(
+            "Key for agent '{}' installed on {}{}",
+            agent_id,
+            target.destination,
+            if restarted { " and agent restarted" } else { "" }
+        ));
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+async fn agent_anomalies(
+    client: &WazuhClient,
+    config: &Config,
+    window: &str,
+    sensitivity: &Sensitivity,
+    record: bool,
+    json_output: bool,
+) -> Result<()> {
+    let since = chrono::Utc::now() - parse_duration(window)?;
+
+    let pb = spinner("Fetching agents...");
+    let url = "/agents?select=id,name,status,group";
+    let api_response: ApiResponse<AgentListResponse> = client.get_list(url).await?;
+    pb.finish_and_clear();
+    let agents = api_response.data.affected_items;
+
+    let source = config.api_url();
+    let current = CheckinSnapshot::from_agents(&source, &agents, chrono::Utc::now());
+    let baseline: Vec<CheckinSnapshot> = checkins::load(&source)?
+        .into_iter()
+        .filter(|snapshot| snapshot.taken_at >= since)
+        .collect();
+    if record {
+        checkins::record(&current)?;
+    }
+    if baseline.len() < checkins::MIN_BASELINE {
+        print_info(&format!(
+            "Only {} check-in snapshots in the last {}; drops are detected from {} on. \
+             Schedule this command (e.g. hourly) to build the baseline",
+            baseline.len(),
+            window,
+            checkins::MIN_BASELINE
+        ));
+    }
+
+    let mut anomalies = checkins::detect_drops(&baseline, &current, sensitivity);
+    anomalies.extend(checkins::detect_flapping(&baseline, &current, sensitivity));
+    let names: HashMap<&str, &str> =
+        agents.iter().map(|agent| (agent.id.as_str(), agent.name.as_str())).collect();
+    for anomaly in anomalies.iter_mut().filter(|a| a.kind == AnomalyKind::Flapping) {
+        if let Some(name) = names.get(anomaly.subject.as_str()) {
+            anomaly.subject = format!("{} ({})", anomaly.subject, name);
+        }
+    }
+
+    if json_output {
+        print_json(&anomalies)?;
+    } else if anomalies.is_empty() {
+        println!(
+            "{} No anomalies against {} snapshots since {}",
+            "✓".green().bold(),
+            baseline.len(),
+            since.format("%Y-%m-%d %H:%M UTC")
+        );
+    } else {
+        print_anomalies(&anomalies);
+    }
+
+    if !anomalies.is_empty() {
+        return Err(anyhow!("{} anomalies found", anomalies.len()));
+    }
+    Ok(())
+}
+
+async fn why_disconnected(
+    client: &WazuhClient,
+    config: &Config,
+    agent_id: &str,
+    log_limit: u32,
+    since: &str,
+    json_output: bool,
+) -> Result<()> {
+    let since = parse_duration(since)?;
+    let pb = spinner("Gathering connection evidence...");
+
+    let url = format!("/agents?agents_list={}", agent_id);
+    let response = client.get(&url).await?;
+    let api_response: ApiResponse<AgentListResponse> =
+        WazuhClient::parse_response(response).await?;
+    let agent = match api_response.data.affected_items.into_iter().next() {
+        Some(agent) => agent,
+        None => {
+            pb.finish_and_clear();
+            return Err(anyhow!("Agent '{}' not found", agent_id));
+        }
+    };
+
+    let log_lines = agent_log_lines(client, &agent, log_limit).await?;
+    let remoted = fetch_daemon_stats(client, "remoted").await.unwrap_or_default();
+
+    // The indexer is optional here; the manager-side evidence is usually enough
+    let query = AlertQuery {
+        since: Some(since),
+        query: Some(format!(
+            "rule.id:(503 OR 504 OR 505 OR 506) AND (agent.id:\"{}\" OR full_log:\"{}\")",
+            escape_lucene(&agent.id),
+            escape_lucene(&agent.name)
+        )),
+        limit: 20,
+        ..Default::default()
+    };
+    let alerts = match IndexerClient::new(config) {
+        Ok(indexer) => indexer.search_alerts(&query).await.unwrap_or_else(|e| {
+            debug!("Skipping agent alerts: {:#}", e);
+            Vec::new()
+        }),
+        Err(e) => {
+            debug!("Skipping agent alerts: {:#}", e);
+            Vec::new()
+        }
+    };
+    pb.finish_and_clear();
+
+    let causes = disconnect::diagnose(&agent, &log_lines, &alerts, &remoted);
+    let report = DisconnectReport {
+        offline_secs: agent
+            .last_keep_alive
+            .map(|seen| (chrono::Utc::now() - seen).num_seconds()),
+        agent_id: agent.id,
+        agent_name: agent.name,
+        status: agent.status,
+        last_keep_alive: agent.last_keep_alive,
+        causes,
+        log_lines,
+        alerts: alerts
+            .iter()
+            .map(|alert| {
+                format!(
+                    "{} [{}] {}",
+                    json_to_text(alert.get("timestamp")),
+                    json_to_text(alert.pointer("/rule/id")),
+                    json_to_text(alert.pointer("/rule/description"))
+                )
+            })
+            .collect(),
+        remoted,
+    };
+
+    if json_output {
+        print_json(&report)
+    } else {
+        print_disconnect_report(&report);
+        Ok(())
+    }
+}
+
+/// Manager log lines mentioning the agent, newest first
+async fn agent_log_lines(client: &WazuhClient, agent: &Agent, limit: u32) -> Result<Vec<String>> {
+    let url = format!("/manager/logs?limit={}&sort=-timestamp&search={}", limit, agent.id);
+    let response = client.get(&url).await?;
+    let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+
+    // The search also matches unrelated numbers, so keep lines naming the agent
+    let quoted_id = format!("'{}'", agent.id);
+    Ok(api_response
+        .data
+        .affected_items
+        .iter()
+        .map(|line| {
+            format!(
+                "{} {} {}: {}",
+                line["timestamp"].as_str().unwrap_or(""),
+                line["tag"].as_str().unwrap_or(""),
+                line["level"].as_str().unwrap_or(""),
+                line["description"].as_str().unwrap_or("").trim()
+            )
+        })
+        .filter(|line| {
+            line.contains(&quoted_id)
+                || line.contains(&format!("ID {}", agent.id))
+                || mentions_name(line, &agent.name)
+        })
+        .collect())
+}
+
+/// Whether `line` names the agent `name` as a whole word, so `web-1` does not
+/// match a line about `web-10`
+fn mentions_name(line: &str, name: &str) -> bool {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.');
+    line.match_indices(name).any(|(start, _)| {
+        let before = line[..start].chars().next_back();
+        let mut after = line[start + name.len()..].chars();
+        let next = after.next();
+        // A full stop ending a sentence is not part of the name
+        let ends_sentence = next == Some('.') && after.next().is_none_or(|c| !is_name_char(c));
+        !before.is_some_and(is_name_char) && (!next.is_some_and(is_name_char) || ends_sentence)
+    })
+}
+
+/// Agents named on the command line, or a `--select` expression
+pub enum RunTargets {
+    Named(String),
+    Selected(Selector),
+}
+
+impl fmt::Display for RunTargets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunTargets::Named(target) => write!(f, "{}", target),
+            RunTargets::Selected(selector) => write!(f, "{}", selector),
+        }
+    }
+}
+
+/// An active-response command to run on a set of agents
+pub struct RunRequest {
+    pub command: String,
+    pub args: Vec<String>,
+    /// Wait up to `timeout` for the agents' results instead of returning once
+    /// the manager accepted the command
+    pub wait: bool,
+    pub timeout: Duration,
+    pub skip_confirm: bool,
+}
+
+/// Refuse commands missing from active_response.allowed_commands
+fn check_allowed(config: &Config, command: &str) -> Result<()> {
+    let allowed = &config.active_response.allowed_commands;
+    if allowed.iter().any(|a| a == command) {
+        return Ok(());
+    }
+    Err(WazuhError::InvalidInput(format!(
+        "'{}' is not in active_response.allowed_commands ({})",
+        command,
+        if allowed.is_empty() { "empty".to_string() } else { allowed.join(", ") }
+    ))
+    .into())
+}
+
+pub async fn run_command(
+    client: &WazuhClient,
+    config: &Config,
+    targets: &RunTargets,
+    request: &RunRequest,
+    json_output: bool,
+) -> Result<()> {
+    check_allowed(config, &request.command)?;
+
+    let pb = spinner("Resolving agents...");
+    let agents = resolve_run_targets(client, targets).await?;
+    pb.finish_and_clear();
+    if agents.is_empty() {
+        return Err(anyhow!("No agents match '{}'", targets));
+    }
+
+    let mut results: Vec<AgentRunResult> = agents
+        .iter()
+        .map(|agent| AgentRunResult {
+            agent_id: agent.id.clone(),
+            agent_name: agent.name.clone(),
+            status: if agent.status == AgentStatus::Active {
+                RunStatus::TimedOut
+            } else {
+                RunStatus::Skipped
+            },
+            output: Vec::new(),
+            error: (agent.status != AgentStatus::Active)
+                .then(|| format!("agent is {}", agent.status)),
+        })
+        .collect();
+    let targets: Vec<String> = results
+        .iter()
+        .filter(|r| r.status != RunStatus::Skipped)
+        .map(|r| r.agent_id.clone())
+        .collect();
+    if targets.is_empty() {
+        return Err(anyhow!("None of the {} matching agents is active", results.len()));
+    }
+
+    if !request.skip_confirm {
+        let confirm = Confirm::new()
+            .with_prompt(format!(
+                "Run '{}' on {} agents?",
+                request.command,
+                targets.len()
+            ))
+            .default(false)
+            .interact()?;
+        if !confirm {
+            println!("Operation cancelled");
+            return Ok(());
+        }
+    }
+
+    let sent_at = chrono::Utc::now();
+    let body = serde_json::json!({
+        "command": request.command,
+        "arguments": request.args,
+    });
+    let url = format!("/active-response?agents_list={}", targets.join(","));
+    let response = client.put(&url, Some(body)).await?;
+    let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+    let failures = bulk_failures(&api_response.data.failed_items);
+    for result in results.iter_mut() {
+        if let Some(error) = failures.get(&result.agent_id) {
+            result.status = RunStatus::Failed;
+            result.error = Some(error.clone());
+        }
+    }
+
+    let waiting: Vec<String> = targets
+        .into_iter()
+        .filter(|id| !failures.contains_key(id))
+        .collect();
+    let output = if request.wait && !waiting.is_empty() {
+        collect_run_output(config, &waiting, &request.command, sent_at, request.timeout).await
+    } else {
+        None
+    };
+    for result in results.iter_mut() {
+        if !waiting.contains(&result.agent_id) {
+            continue;
+        }
+        let Some(output) = &output else {
+            // Accepted by the manager, which is all that is known without results
+            result.status = RunStatus::Sent;
+            continue;
+        };
+        match (output.lines.get(&result.agent_id), &output.error) {
+            (Some(lines), _) => {
+                result.status = RunStatus::Completed;
+                result.output = lines.clone();
+            }
+            (None, Some(error)) => {
+                result.status = RunStatus::Unknown;
+                result.error = Some(error.clone());
+            }
+            (None, None) => {}
+        }
+    }
+
+    if json_output {
+        print_json(&results)?;
+    } else {
+        print_run_results(&results);
+    }
+
+    // Success is what the manager reported; a missing result may only be late
+    let failed = results.iter().filter(|r| r.status == RunStatus::Failed).count();
+    if failed > 0 {
+        return Err(anyhow!("The manager could not run '{}' on {} agents", request.command, failed));
+    }
+    Ok(())
+}
+
+/// Comma-separated agent IDs or names, every agent whose name matches a
+/// pattern, `all` agents but the manager, or a selection
+async fn resolve_run_targets(client: &WazuhClient, targets: &RunTargets) -> Result<Vec<Agent>> {
+    let target = match targets {
+        RunTargets::Named(target) => target.as_str(),
+        RunTargets::Selected(selector) => return select_agents(client, selector).await,
+    };
+    let every_agent = target == "all";
+    let target = if every_agent { "*" } else { target };
+    let is_pattern = target.contains(['*', '?']);
+    let url = if is_pattern {
+        "/agents?select=id,name,status".to_string()
+    } else {
+        let agents: Vec<String> = target.split(',').map(|a| a.trim().to_string()).collect();
+        let ids = resolve_agent_ids(client, &agents).await?;
+        format!("/agents?agents_list={}&select=id,name,status", ids.join(","))
+    };
+
+    let api_response: ApiResponse<AgentListResponse> = client.get_list(&url).await?;
+
+    Ok(api_response
+        .data
+        .affected_items
+        .into_iter()
+        .filter(|agent| !is_pattern || glob_match(target, &agent.name))
+        .filter(|agent| !every_agent || agent.id != MANAGER_AGENT_ID)
+        .collect())
+}
+
+/// Active-response alerts of `command` on the agents, raised from `sent_at` on
+fn run_output_query(
+    agent_ids: &[String],
+    command: &str,
+    sent_at: chrono::DateTime<chrono::Utc>,
+) -> String {
+    // Custom commands are sent as `!name` but logged under their script name
+    let command = command.trim_start_matches('!');
+    format!(
+        "rule.groups:active_response AND agent.id:({}) AND full_log:\"{}\" \
+         AND timestamp:>=\"{}\"",
+        agent_ids.join(" OR "),
+        escape_lucene(command),
+        sent_at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+    )
+}
+
+/// Active-response log lines collected per agent
+struct RunOutput {
+    lines: HashMap<String, Vec<String>>,
+    /// Why the last search failed; agents without lines have an unknown result
+    error: Option<String>,
+}
+
+/// Wait for the agents' active-response log alerts, up to `timeout`
+///
+/// Agents report execution through their active-responses.log, which the
+/// manager turns into alerts in the `active_response` rule group. A failed
+/// search is retried until the timeout. `None` when there is no indexer to
+/// query, after warning about it.
+async fn collect_run_output(
+    config: &Config,
+    agent_ids: &[String],
+    command: &str,
+    sent_at: chrono::DateTime<chrono::Utc>,
+    timeout: Duration,
+) -> Option<RunOutput> {
+    let indexer = match IndexerClient::new(config) {
+        Ok(indexer) => indexer,
+        Err(e) => {
+            print_warning(
+                WarningKind::Fallback,
+                &format!("Cannot collect active-response results: {:#}", e),
+            );
+            return None;
+        }
+    };
+    let pb = spinner("Waiting for results...");
+    let deadline = Instant::now() + timeout;
+    let mut output = RunOutput {
+        lines: HashMap::new(),
+        error: None,
+    };
+
+    loop {
+        let query = AlertQuery {
+            query: Some(run_output_query(agent_ids, command, sent_at)),
+            limit: 1000,
+            ..Default::default()
+        };
+        match indexer.search_alerts(&query).await {
+            Ok(alerts) => {
+                output.lines.clear();
+                output.error = None;
+                for alert in alerts.iter().rev() {
+                    let agent_id = json_to_text(json_field(alert, "agent.id"));
+                    let line = json_to_text(json_field(alert, "full_log"));
+                    output.lines.entry(agent_id).or_default().push(line);
+                }
+            }
+            // Keep what earlier searches found and try again on the next poll
+            Err(e) => {
+                debug!("Active-response result search failed: {:#}", e);
+                output.error = Some(format!("cannot read the result: {:#}", e));
+            }
+        }
+
+        let done = agent_ids.iter().filter(|id| output.lines.contains_key(*id)).count();
+        pb.set_message(format!(
+            "Waiting for results ({} of {} agents)...",
+            done,
+            agent_ids.len()
+        ));
+        if done == agent_ids.len() || Instant::now() >= deadline {
+            break;
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        tokio::time::sleep(RUN_POLL_INTERVAL.min(remaining)).await;
+    }
+    pb.finish_and_clear();
+
+    Some(output)
+}
+
+async fn agent_summary(client: &WazuhClient, recent: u32, json_output: bool) -> Result<()> {
+    let pb = spinner("Building agent summary...");
+    let response = client.get("/agents/summary/status").await?;
+    let status: ApiResponse<serde_json::Value> = WazuhClient::parse_response(response).await?;
+
+    // /agents/summary/os only names the platforms, so count them with distinct
+    let url = "/agents/stats/distinct?fields=os.platform&q=id!=000";
+    let response = client.get(url).await?;
+    let platforms: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+
+    let url = format!(
+        "/agents?limit={}&sort=-dateAdd&q=id!={}&select=id,name,status,dateAdd",
+        recent, MANAGER_AGENT_ID
+    );
+    let response = client.get(&url).await?;
+    let recent: ApiResponse<AgentListResponse> = WazuhClient::parse_response(response).await?;
+
+    // The status summary counts the manager as an agent; the other counts do not
+    let url = format!(
+        "/agents?agents_list={}&select=id,status,group_config_status",
+        MANAGER_AGENT_ID
+    );
+    let response = client.get(&url).await?;
+    let manager: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+
+    let max_agents = fetch_max_agents(client).await?;
+    pb.finish_and_clear();
+
+    let (mut total, mut by_status, mut config_synced) = status_counts(&status.data);
+    if let Some(manager) = manager.data.affected_items.first() {
+        exclude_manager(&mut total, &mut by_status, &mut config_synced, manager);
+    }
+    let summary = AgentSummary {
+        total,
+        by_status,
+        config_synced,
+        platforms: platform_counts(&platforms.data.affected_items),
+        max_agents,
+        recent: recent.data.affected_items,
+    };
+
+    if json_output {
+        print_json(&summary)
+    } else {
+        print_agent_summary(&summary);
+        Ok(())
+    }
+}
+
+/// Total, count per status and synced configurations from `/agents/summary/status`
+///
+/// Wazuh 4.4+ nests the counts under `connection` next to `configuration`;
+/// older managers return the counts at the top level.
+fn status_counts(data: &serde_json::Value) -> (u64, Vec<(AgentStatus, u64)>, Option<u64>) {
+    let connection = data.get("connection").unwrap_or(data);
+    let count = |key: &str| connection[key].as_u64().unwrap_or(0);
+    let by_status = vec![
+        (AgentStatus::Active, count("active")),
+        (AgentStatus::Disconnected, count("disconnected")),
+        (AgentStatus::NeverConnected, count("never_connected")),
+        (AgentStatus::Pending, count("pending")),
+    ];
+    let total = match connection["total"].as_u64() {
+        Some(total) => total,
+        None => by_status.iter().map(|(_, count)| count).sum(),
+    };
+    (total, by_status, data["configuration"]["synced"].as_u64())
+}
+
+/// Take the manager, as listed by `/agents`, out of the counts of [`status_counts`]
+fn exclude_manager(
+    total: &mut u64,
+    by_status: &mut [(AgentStatus, u64)],
+    config_synced: &mut Option<u64>,
+    manager: &serde_json::Value,
+) {
+    *total = total.saturating_sub(1);
+    let status = serde_json::from_value::<AgentStatus>(manager["status"].clone()).ok();
+    if let Some((_, count)) = by_status.iter_mut().find(|(s, _)| Some(s) == status.as_ref()) {
+        *count = count.saturating_sub(1);
+    }
+    if manager["group_config_status"] == "synced" {
+        *config_synced = config_synced.map(|synced| synced.saturating_sub(1));
+    }
+}
+
+/// Agent count per platform from `/agents/stats/distinct`, largest first
+fn platform_counts(items: &[serde_json::Value]) -> Vec<(String, u64)> {
+    let mut platforms: Vec<(String, u64)> = items
+        .iter()
+        .map(|item| {
+            let platform = json_field(item, "os.platform")
+                .and_then(|p| p.as_str())
+                .unwrap_or("unknown");
+            (platform.to_string(), item["count"].as_u64().unwrap_or(0))
+        })
+        .collect();
+    platforms.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    platforms
+}
+
+async fn agent_versions(
+    client: &WazuhClient,
+    by_platform: bool,
+    format: &str,
+    min_version: Option<&str>,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Fetching agent versions...");
+    let mut progress = Progress::start("agent versions", None);
+
+    let url = "/agents?select=id,name,status,version,os.platform";
+    let api_response: ApiResponse<AgentListResponse> = client.get_list(url).await?;
+
+    pb.finish_and_clear();
+    progress.page_fetched(api_response.data.affected_items.len() as u64);
+    progress.finish();
+
+    // Agent 000 is the manager itself
+    let agents: Vec<Agent> = api_response
+        .data
+        .affected_items
+        .into_iter()
+        .filter(|agent| agent.id != "000")
+        .collect();
+    let matrix = version_matrix(&agents, by_platform, min_version);
+
+    if json_output {
+        return print_json(&matrix);
+    }
+
+    let mut headers = vec!["Version".to_string()];
+    headers.extend(matrix.platforms.iter().cloned());
+    headers.push("Total".to_string());
+    if min_version.is_some() {
+        headers.push("Below Minimum".to_string());
+    }
+    let rows: Vec<Vec<String>> = matrix
+        .rows
+        .iter()
+        .map(|row| {
+            let mut cells = vec![row.version.clone()];
+            cells.extend(row.counts.iter().map(u64::to_string));
+            cells.push(row.total.to_string());
+            if min_version.is_some() {
+                cells.push(if row.below_minimum { "yes" } else { "no" }.to_string());
+            }
+            cells
+        })
+        .collect();
+
+    match format {
+        "csv" => print_csv(&headers, &rows),
+        "markdown" => print_markdown(&headers, &rows),
+        _ => {
+            print_version_matrix(&matrix);
+            let outdated: u64 = matrix
+                .rows
+                .iter()
+                .filter(|row| row.below_minimum)
+                .map(|row| row.total)
+                .sum();
+            match min_version {
+                Some(min) => println!(
+                    "\nAgents: {} ({} below minimum version {})",
+                    agents.len(),
+                    outdated,
+                    min
+                ),
+                None => println!("\nAgents: {}", agents.len()),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Count agents per version (newest first) and, for a matrix, per OS platform
+fn version_matrix(agents: &[Agent], by_platform: bool, min_version: Option<&str>) -> VersionMatrix {
+    let minimum = min_version.and_then(parse_version);
+
+    let mut platforms: Vec<String> = Vec::new();
+    let mut counts: Vec<(Option<Vec<u64>>, String, Vec<u64>)> = Vec::new();
+
+    for agent in agents {
+        let parsed = agent.version.as_deref().and_then(parse_version);
+        let label = parsed
+            .as_ref()
+            .map(|parts| parts.iter().map(u64::to_string).collect::<Vec<_>>().join("."))
+            .unwrap_or_else(|| "unknown".to_string());
+
+        let column = if by_platform {
+            let platform = agent
+                .os
+                .as_ref()
+                .and_then(|os| os.platform.clone())
+                .unwrap_or_else(|| "unknown".to_string());
+            match platforms.iter().position(|p| *p == platform) {
+                Some(index) => index,
+                None => {
+                    platforms.push(platform);
+                    platforms.len() - 1
+                }
+            }
+        } else {
+            0
+        };
+
+        let row = match counts.iter().position(|(_, l, _)| *l == label) {
+            Some(index) => index,
+            None => {
+                counts.push((parsed, label, Vec::new()));
+                counts.len() - 1
+            }
+        };
+        let cells = &mut counts[row].2;
+        if cells.len() <= column {
+            cells.resize(column + 1, 0);
+        }
+        cells[column] += 1;
+    }
+
+    // Newest versions first; unknown versions sort last
+    counts.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let columns = platforms.len();
+    let rows = counts
+        .into_iter()
+        .map(|(parsed, version, mut cells)| {
+            let total = cells.iter().sum();
+            cells.resize(columns, 0);
+            VersionMatrixRow {
+                below_minimum: matches!((&parsed, &minimum), (Some(v), Some(min)) if v < min),
+                version,
+                counts: if by_platform { cells } else { Vec::new() },
+                total,
+            }
+        })
+        .collect();
+
+    VersionMatrix {
+        min_version: min_version.map(String::from),
+        platforms,
+        rows,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page() {
+        let items: Vec<u32> = (1..=10).collect();
+        assert_eq!(page(items.clone(), Some(2), Some(3)), vec![3, 4, 5]);
+        assert_eq!(page(items.clone(), None, Some(2)), vec![1, 2]);
+        assert_eq!(page(items.clone(), Some(8), None), vec![9, 10]);
+        assert!(page(items, Some(20), None).is_empty());
+    }
+
+    #[test]
+    fn test_prune_query() {
+        let filter = PruneFilter {
+            statuses: vec!["disconnected".to_string(), "never_connected".to_string()],
+            older_than: parse_duration("30d").unwrap(),
+        };
+        assert_eq!(filter.query(), "status=disconnected,never_connected&older_than=2592000s");
+    }
+
+    #[test]
+    fn test_prune_filter_rejects_non_positive_age() {
+        let statuses = || vec!["disconnected".to_string()];
+        assert!(PruneFilter::new(statuses(), "0s").is_err());
+        assert!(PruneFilter::new(statuses(), "0d").is_err());
+        let filter = PruneFilter::new(statuses(), "7d").unwrap();
+        assert_eq!(filter.older_than, chrono::Duration::days(7));
+    }
+
+    #[test]
+    fn test_mentions_name() {
+        assert!(mentions_name("Agent 'web-1' disconnected", "web-1"));
+        assert!(mentions_name("Lost connection to web-1.", "web-1"));
+        assert!(!mentions_name("Agent 'web-10' disconnected", "web-1"));
+        assert!(!mentions_name("Agent 'old-web-1' disconnected", "web-1"));
+        assert!(!mentions_name("Host web-1.corp is down", "web-1"));
+    }
+
+    #[test]
+    fn test_run_output_query() {
+        let sent_at = chrono::DateTime::parse_from_rfc3339("2024-05-01T10:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let ids = vec!["001".to_string(), "002".to_string()];
+        assert_eq!(
+            run_output_query(&ids, "!firewall-drop", sent_at),
+            "rule.groups:active_response AND agent.id:(001 OR 002) \
+             AND full_log:\"firewall\\-drop\" AND timestamp:>=\"2024-05-01T10:00:00.000Z\""
+        );
+    }
+
+    #[test]
+    fn test_batch_results() {
+        let batch = vec![
+            ("001".to_string(), "web-01".to_string()),
+            ("002".to_string(), "web-02".to_string()),
+        ];
+        let failures = HashMap::from([("002".to_string(), "Agent is not active".to_string())]);
+        let results = batch_results(batch, &failures);
+        assert!(results[0].success);
+        assert_eq!(results[0].error, None);
+        assert!(!results[1].success);
+        assert_eq!(results[1].agent_name, "web-02");
+        assert_eq!(results[1].error.as_deref(), Some("Agent is not active"));
+    }
+
+    #[test]
+    fn test_bulk_targets() {
+        let ids = |list: &[&str]| list.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        let none = AgentFilterArgs::default;
+        match bulk_targets(ids(&["001", " 002", ""]), None, none()).unwrap() {
+            BulkTargets::Listed(listed) => assert_eq!(listed, ids(&["001", "002"])),
+            BulkTargets::Matching(_) => panic!("expected listed agents"),
+        }
+        let filter = AgentFilterArgs {
+            group: Some("web".to_string()),
+            ..Default::default()
+        };
+        match bulk_targets(Vec::new(), Some("status==active".to_string()), filter).unwrap() {
+            BulkTargets::Matching(matching) => {
+                assert_eq!(matching.to_string(), "in group 'web', matching 'status==active'")
+            }
+            BulkTargets::Listed(_) => panic!("expected matching agents"),
+        }
+        assert!(bulk_targets(ids(&["all", "001"]), None, none()).is_err());
+        assert!(bulk_targets(Vec::new(), None, none()).is_err());
+    }
+
+    #[test]
+    fn test_pick_agent() {
+        let agents: Vec<Agent> = serde_json::from_value(serde_json::json!([
+            {"id": "001", "name": "web-01", "status": "active"},
+            {"id": "002", "name": "WEB-01", "status": "disconnected"},
+            {"id": "003", "name": "db-01", "status": "active"}
+        ]))
+        .unwrap();
+        assert_eq!(pick_agent("web-01", &agents).unwrap(), "001");
+        assert_eq!(pick_agent("DB-01", &agents).unwrap(), "003");
+
+        let error = pick_agent("Web-01", &agents).unwrap_err().to_string();
+        assert!(error.contains("names 2 agents: web-01 (001, Active), WEB-01 (002"));
+        let error = pick_agent("web", &agents[..1]).unwrap_err().to_string();
+        assert_eq!(error, "No agent is named 'web'; similar names: web-01 (001, Active)");
+        assert!(pick_agent("mail", &[]).is_err());
+    }
+
+    #[test]
+    fn test_status_counts_both_shapes() {
+        let nested = serde_json::json!({
+            "connection": {"active": 40, "disconnected": 2, "never_connected": 1, "pending": 0,
+                           "total": 43},
+            "configuration": {"synced": 41, "not_synced": 2, "total": 43}
+        });
+        let (total, by_status, synced) = status_counts(&nested);
+        assert_eq!(total, 43);
+        assert_eq!(by_status[1], (AgentStatus::Disconnected, 2));
+        assert_eq!(synced, Some(41));
+
+        let flat = serde_json::json!({"active": 3, "disconnected": 1});
+        let (total, _, synced) = status_counts(&flat);
+        assert_eq!(total, 4);
+        assert_eq!(synced, None);
+
+        let platforms = platform_counts(&[
+            serde_json::json!({"os": {"platform": "windows"}, "count": 2}),
+            serde_json::json!({"os": {"platform": "ubuntu"}, "count": 7}),
+        ]);
+        assert_eq!(platforms[0], ("ubuntu".to_string(), 7));
+    }
+
+    #[test]
+    fn test_exclude_manager() {
+        let nested = serde_json::json!({
+            "connection": {"active": 40, "disconnected": 2, "total": 42},
+            "configuration": {"synced": 41}
+        });
+        let (mut total, mut by_status, mut synced) = status_counts(&nested);
+        let manager = serde_json::json!({
+            "id": "000", "status": "active", "group_config_status": "synced"
+        });
+        exclude_manager(&mut total, &mut by_status, &mut synced, &manager);
+        assert_eq!(total, 41);
+        assert_eq!(by_status[0], (AgentStatus::Active, 39));
+        assert_eq!(by_status[1], (AgentStatus::Disconnected, 2));
+        assert_eq!(synced, Some(40));
+    }
+
+    #[test]
+    fn test_decode_agent_key() {
+        let encoded = BASE64.encode("003 web-01 any 0123456789abcdef");
+        let data = serde_json::json!({"affected_items": [{"id": "003", "key": encoded}]});
+
+        let key = extract_agent_key(&data).unwrap();
+        assert_eq!(decode_agent_key(key).unwrap(), "003 web-01 any 0123456789abcdef");
+        assert!(decode_agent_key(&BASE64.encode("garbage")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rename_restores_agent_when_insert_fails() {
+        use serde_json::json;
+        use wiremock::matchers::{body_partial_json, method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let list = |items: serde_json::Value| {
+            ResponseTemplate::new(200).set_body_json(json!({
+                "error": 0,
+                "data": {"affected_items": items, "total_affected_items": 1, "failed_items": []}
+            }))
+        };
+        Mock::given(method("GET"))
+            .and(path("/agents"))
+            .and(query_param("agents_list", "003"))
+            .respond_with(list(json!([
+                {"id": "003", "name": "web-o1", "status": "active", "group": ["default"]}
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/agents"))
+            .and(query_param("name", "web-01"))
+            .respond_with(list(json!([])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/agents/003/key"))
+            .respond_with(list(json!([
+                {"id": "003", "key": BASE64.encode("003 web-o1 any 0123456789abcdef")}
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/agents"))
+            .respond_with(list(json!([])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/agents/insert"))
+            .and(body_partial_json(json!({"name": "web-01"})))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "title": "Bad Request", "detail": "Invalid name"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/agents/insert"))
+            .and(body_partial_json(
+                json!({"id": "003", "name": "web-o1", "key": "0123456789abcdef"}),
+            ))
+            .respond_with(list(json!([])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let address = server.address();
+        let mut config = Config::default();
+        config.api.protocol = "http".to_string();
+        config.api.host = address.ip().to_string();
+        config.api.port = address.port();
+        config.auth.token = Some("token".to_string());
+        let client = WazuhClient::new(Arc::new(RwLock::new(config))).await.unwrap();
+
+        let err = rename_agent(&client, "003", "web-01", false, true, true)
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("restored as 'web-o1'"));
+    }
+
+    #[tokio::test]
+    async fn test_list_count_fetches_one_agent() {
+        use serde_json::json;
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/agents"))
+            .and(query_param("limit", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "error": 0,
+                "data": {
+                    "affected_items": [{"id": "000", "name": "manager", "status": "active"}],
+                    "total_affected_items": 25000,
+                    "failed_items": [],
+                },
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let address = server.address();
+        let mut config = Config::default();
+        config.api.protocol = "http".to_string();
+        config.api.host = address.ip().to_string();
+        config.api.port = address.port();
+        config.auth.token = Some("token".to_string());
+        let client = WazuhClient::new(Arc::new(RwLock::new(config))).await.unwrap();
+
+        let filter = AgentListFilter {
+            params: AgentParams::default(),
+            selector: None,
+        };
+        list_agents(&client, filter, true, None, false).await.unwrap();
+    }
+
+    #[test]
+    fn test_version_matrix() {
+        let agents: Vec<Agent> = serde_json::from_value(serde_json::json!([
+            {"id": "001", "name": "a", "status": "active", "version": "Wazuh v4.7.2", "os": {"platform": "ubuntu"}},
+            {"id": "002", "name": "b", "status": "active", "version": "Wazuh v4.3.10", "os": {"platform": "windows"}},
+            {"id": "003", "name": "c", "status": "active", "version": "Wazuh v4.7.2", "os": {"platform": "windows"}},
+            {"id": "004", "name": "d", "status": "never_connected"}
+        ]))
+        .unwrap();
+
+        let matrix = version_matrix(&agents, true, Some("4.5.0"));
+        assert_eq!(matrix.platforms, ["ubuntu", "windows", "unknown"]);
+        let versions: Vec<&str> = matrix.rows.iter().map(|r| r.version.as_str()).collect();
+        assert_eq!(versions, ["4.7.2", "4.3.10", "unknown"]);
+        assert_eq!(matrix.rows[0].counts, [1, 1, 0]);
+        assert_eq!(matrix.rows[0].total, 2);
+        assert!(!matrix.rows[0].below_minimum);
+        assert!(matrix.rows[1].below_minimum);
+        assert!(!matrix.rows[2].below_minimum);
+
+        let summary = version_matrix(&agents, false, None);
+        assert!(summary.platforms.is_empty());
+        assert!(summary.rows[0].counts.is_empty());
+    }
+}
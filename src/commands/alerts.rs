@@ -0,0 +1,408 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use colored::Colorize;
+use serde_json::{json, Value};
+
+use crate::{
+    cli::{AlertsAction, AlertsCommand},
+    compliance::ComplianceFilter,
+    config::{Config, OutputConfig},
+    escalate::{Escalator, Platform},
+    estimate::{confirm_large_result, Estimate},
+    indexer::{is_unavailable, AlertQuery, IndexerClient},
+    models::{AlertStats, AlertStatsBucket},
+    output::{
+        print_alert_sample, print_alert_stats, print_alerts_table, print_json, print_warning,
+        spinner, WarningKind,
+    },
+    progress::Progress,
+    utils::{json_field, json_to_text, parse_duration},
+};
+
+pub async fn handle_alerts_command(
+    cmd: AlertsCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    let indexer = IndexerClient::new(config)?;
+
+    match cmd.action {
+        AlertsAction::Search {
+            query,
+            since,
+            level,
+            agent,
+            rule,
+            compliance,
+            limit,
+        } => {
+            let query = AlertQuery {
+                since: Some(parse_duration(&since)?),
+                query,
+                level,
+                agent,
+                rule_id: rule,
+                compliance: compliance
+                    .map(|c| c.parse::<ComplianceFilter>())
+                    .transpose()?,
+                limit,
+            };
+            search_alerts(&indexer, &query, &config.output, json_output).await?
+        }
+
+        AlertsAction::Stats {
+            since,
+            group_by,
+            query,
+            level,
+            agent,
+            top,
+        } => {
+            let window = parse_duration(&since)?;
+            let query = AlertQuery {
+                since: Some(window),
+                query,
+                level,
+                agent,
+                ..Default::default()
+            };
+            let stats = alert_stats(&indexer, &query, &group_by, top).await?;
+            if json_output {
+                print_json(&stats)?;
+            } else {
+                print_alert_stats(&stats);
+            }
+        }
+
+        AlertsAction::Sample {
+            rule,
+            count,
+            fields,
+            since,
+            recent,
+            seed,
+            raw,
+        } => {
+            let query = AlertQuery {
+                since: Some(parse_duration(&since)?),
+                rule_id: Some(rule),
+                limit: count,
+                ..Default::default()
+            };
+            // A seed only makes sense for random samples; clap rejects it with --recent
+            let seed = if recent { None } else { Some(seed) };
+            sample_alerts(&indexer, &query, &fields, seed, raw, json_output).await?
+        }
+
+        AlertsAction::Escalate {
+            target,
+            to,
+            since,
+            limit,
+            dry_run,
+        } => {
+            let escalator = Escalator::new(to.parse::<Platform>()?, &config.escalation)?;
+            let query = if is_alert_id(&target) {
+                AlertQuery {
+                    query: Some(format!("id:\"{}\"", target)),
+                    limit: 1,
+                    ..Default::default()
+                }
+            } else {
+                AlertQuery {
+                    since: Some(parse_duration(&since)?),
+                    query: Some(target),
+                    limit,
+                    ..Default::default()
+                }
+            };
+            escalate_alerts(&indexer, &escalator, &query, dry_run, json_output).await?
+        }
+    }
+
+    Ok(())
+}
+
+async fn search_alerts(
+    indexer: &IndexerClient,
+    query: &AlertQuery,
+    output: &OutputConfig,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Counting matching alerts...");
+    let count = indexer.count_alerts(query).await;
+    pb.finish_and_clear();
+
+    // An unreachable indexer is handled by the search, which can fall back to cached results
+    let estimate = match count {
+        Ok((total, latency)) => Estimate {
+            total: total.min(query.limit as u64),
+            page_size: query.limit as u64,
+            page_latency: latency,
+        },
+        Err(e) if is_unavailable(&e) => Estimate {
+            total: 0,
+            page_size: query.limit as u64,
+            page_latency: Default::default(),
+        },
+        Err(e) => return Err(e),
+    };
+    if !confirm_large_result(&estimate, "alerts", output)? {
+        println!("Operation cancelled");
+        return Ok(());
+    }
+
+    let pb = spinner("Searching alerts...");
+    let mut progress = Progress::start("alerts search", Some(estimate.total));
+    let alerts = indexer.search_alerts(query).await?;
+    pb.finish_and_clear();
+    progress.page_fetched(alerts.len() as u64);
+    progress.finish();
+
+    if json_output {
+        print_json(&alerts)?;
+    } else {
+        print_alerts_table(&alerts);
+        println!("\nShowing: {} alerts", alerts.len());
+    }
+
+    Ok(())
+}
+
+/// Rule levels range from 0 to 15, so a level histogram never needs more buckets
+const RULE_LEVELS: u32 = 16;
+
+async fn alert_stats(
+    indexer: &IndexerClient,
+    query: &AlertQuery,
+    group_by: &str,
+    top: u32,
+) -> Result<AlertStats> {
+    let to = Utc::now();
+    let pb = spinner("Aggregating alerts...");
+    let key = format!("alert stats {} {} {:?}", group_by, top, query);
+    let response = indexer
+        .search_or_cached(&key, indexer.alerts_index(), &stats_aggregation(query, group_by, top))
+        .await?;
+    pb.finish_and_clear();
+
+    Ok(AlertStats {
+        group_by: group_by.to_string(),
+        from: to - query.since.unwrap_or_default(),
+        to,
+        total_alerts: response
+            .pointer("/hits/total/value")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0),
+        buckets: parse_stats_buckets(&response),
+    })
+}
+
+/// Terms aggregation on the grouping field: the noisiest rules and agents first,
+/// or every level from the most severe down
+fn stats_aggregation(query: &AlertQuery, group_by: &str, top: u32) -> Value {
+    let mut terms = json!({ "field": group_by, "size": top });
+    let label_field = match group_by {
+        "rule.id" => Some("rule.description"),
+        "agent.name" => Some("agent.id"),
+        _ => {
+            terms["size"] = json!(RULE_LEVELS);
+            terms["order"] = json!({ "_key": "desc" });
+            None
+        }
+    };
+
+    let mut group = json!({ "terms": terms });
+    if let Some(field) = label_field {
+        group["aggs"] = json!({ "label": { "terms": { "field": field, "size": 1 } } });
+    }
+
+    json!({
+        "size": 0,
+        "track_total_hits": true,
+        "query": { "bool": { "filter": query.filters() } },
+        "aggs": { "group": group }
+    })
+}
+
+fn parse_stats_buckets(response: &Value) -> Vec<AlertStatsBucket> {
+    response
+        .pointer("/aggregations/group/buckets")
+        .and_then(|b| b.as_array())
+        .into_iter()
+        .flatten()
+        .map(|bucket| AlertStatsBucket {
+            // Levels are numeric keys; rule IDs and agent names are strings
+            key: json_to_text(bucket.get("key")),
+            label: bucket
+                .pointer("/label/buckets/0/key")
+                .map(|label| json_to_text(Some(label))),
+            alerts: bucket["doc_count"].as_u64().unwrap_or(0),
+        })
+        .collect()
+}
+
+/// Print selected fields of a sample of alerts
+///
+/// `seed` is `None` for the most recent alerts, or `Some` for a random sample.
+async fn sample_alerts(
+    indexer: &IndexerClient,
+    query: &AlertQuery,
+    fields: &[String],
+    seed: Option<Option<u64>>,
+    raw: bool,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Sampling alerts...");
+    let alerts = match seed {
+        Some(seed) => indexer.sample_alerts(query, seed).await?,
+        None => indexer.search_alerts(query).await?,
+    };
+    pb.finish_and_clear();
+
+    let rows: Vec<Vec<String>> = alerts
+        .iter()
+        .map(|alert| {
+            fields
+                .iter()
+                .map(|field| json_to_text(json_field(alert, field)))
+                .collect()
+        })
+        .collect();
+
+    if json_output {
+        let samples: Vec<Value> = alerts
+            .iter()
+            .map(|alert| {
+                let selected: serde_json::Map<String, Value> = fields
+                    .iter()
+                    .map(|field| {
+                        let value = json_field(alert, field).cloned().unwrap_or(Value::Null);
+                        (field.clone(), value)
+                    })
+                    .collect();
+                Value::Object(selected)
+            })
+            .collect();
+        print_json(&samples)?;
+    } else if raw {
+        for row in &rows {
+            println!("{}", row.join("\t"));
+        }
+    } else {
+        print_alert_sample(fields, &rows);
+        println!("\nSampled: {} alerts", rows.len());
+    }
+
+    Ok(())
+}
+
+/// Alert IDs look like `<epoch>.<offset>`; anything else is treated as a query
+fn is_alert_id(target: &str) -> bool {
+    target
+        .split_once('.')
+        .is_some_and(|(secs, offset)| {
+            !secs.is_empty()
+                && !offset.is_empty()
+                && secs.chars().chain(offset.chars()).all(|c| c.is_ascii_digit())
+        })
+}
+
+async fn escalate_alerts(
+    indexer: &IndexerClient,
+    escalator: &Escalator,
+    query: &AlertQuery,
+    dry_run: bool,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Searching alerts...");
+    let alerts = indexer.search_alerts(query).await?;
+    pb.finish_and_clear();
+
+    if alerts.is_empty() {
+        return Err(anyhow!("No alerts match '{}'", query.query.as_deref().unwrap_or("")));
+    }
+
+    if dry_run {
+        let payloads: Vec<Value> = alerts.iter().map(|alert| escalator.payload(alert)).collect();
+        return print_json(&payloads);
+    }
+
+    let mut progress = Progress::start("alerts escalate", Some(alerts.len() as u64));
+    let mut results = Vec::new();
+    for alert in &alerts {
+        let alert_id = json_to_text(json_field(alert, "id"));
+        let result = escalator.escalate(alert).await;
+        let error = result.as_ref().err().map(|e| format!("{:#}", e));
+        progress.item_done(&alert_id, error.as_deref());
+
+        if !json_output {
+            match &result {
+                Ok(created) => println!(
+                    "{} Alert {} escalated as {}",
+                    "✓".green().bold(),
+                    alert_id,
+                    created
+                ),
+                Err(e) => print_warning(
+                    WarningKind::PartialFailure,
+                    &format!("Alert {} not escalated: {:#}", alert_id, e),
+                ),
+            }
+        }
+        results.push(json!({
+            "alert_id": alert_id,
+            "created": result.as_ref().ok(),
+            "error": error,
+        }));
+    }
+    progress.finish();
+
+    if json_output {
+        print_json(&results)?;
+    }
+
+    let failed = results.iter().filter(|r| !r["error"].is_null()).count();
+    if failed > 0 {
+        return Err(anyhow!("{} of {} alerts could not be escalated", failed, results.len()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stats_buckets() {
+        let response = json!({
+            "hits": { "total": { "value": 120 } },
+            "aggregations": {
+                "group": {
+                    "buckets": [
+                        {
+                            "key": "5710",
+                            "doc_count": 90,
+                            "label": {
+                                "buckets": [{ "key": "sshd: Attempt to login", "doc_count": 90 }]
+                            }
+                        },
+                        { "key": 10, "doc_count": 30, "label": { "buckets": [] } }
+                    ]
+                }
+            }
+        });
+
+        let buckets = parse_stats_buckets(&response);
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].key, "5710");
+        assert_eq!(buckets[0].label.as_deref(), Some("sshd: Attempt to login"));
+        assert_eq!(buckets[0].alerts, 90);
+        assert_eq!(buckets[1].key, "10");
+        assert_eq!(buckets[1].label, None);
+
+        let body = stats_aggregation(&AlertQuery::default(), "rule.level", 5);
+        assert_eq!(body["aggs"]["group"]["terms"]["size"], RULE_LEVELS);
+        assert!(body["aggs"]["group"].get("aggs").is_none());
+    }
+}
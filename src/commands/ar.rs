@@ -0,0 +1,104 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+use crate::{
+    cli::{ArAction, ArCommand},
+    client::WazuhClient,
+    commands::agent::{run_command, RunRequest, RunTargets},
+    config::Config,
+    models::{ActiveResponseCommand, ApiResponse, ListResponse},
+    output::{print_ar_commands, print_info, print_json, spinner},
+};
+
+pub async fn handle_ar_command(cmd: ArCommand, config: &Config, json_output: bool) -> Result<()> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+
+    // Ensure we're authenticated
+    client.authenticate().await?;
+
+    match cmd.action {
+        ArAction::List => list_commands(&client, config, json_output).await?,
+
+        ArAction::Run {
+            command,
+            agents,
+            args,
+            wait,
+            timeout,
+            yes,
+        } => {
+            let request = RunRequest {
+                command,
+                args,
+                wait,
+                timeout: Duration::from_secs(timeout),
+                skip_confirm: yes,
+            };
+            let targets = RunTargets::Named(agents);
+            run_command(&client, config, &targets, &request, json_output).await?
+        }
+    }
+
+    Ok(())
+}
+
+async fn list_commands(client: &WazuhClient, config: &Config, json_output: bool) -> Result<()> {
+    let pb = spinner("Fetching active-response commands...");
+    let response = client.get("/manager/configuration?section=command").await?;
+    let api_response: ApiResponse<ListResponse<Value>> =
+        WazuhClient::parse_response(response).await?;
+    pb.finish_and_clear();
+
+    let commands = configured_commands(
+        &api_response.data.affected_items,
+        &config.active_response.allowed_commands,
+    );
+    if json_output {
+        return print_json(&commands);
+    }
+    print_ar_commands(&commands);
+    println!("\nTotal: {} commands", commands.len());
+    if !commands.iter().any(|command| command.allowed) {
+        print_info("Add commands to active_response.allowed_commands to run them");
+    }
+    Ok(())
+}
+
+/// Commands of the `command` configuration section, marked with whether
+/// `agent run` and `ar run` are allowed to run them
+fn configured_commands(items: &[Value], allowed: &[String]) -> Vec<ActiveResponseCommand> {
+    let mut commands: Vec<ActiveResponseCommand> = items
+        .iter()
+        .filter_map(|item| item["command"].as_array())
+        .flatten()
+        .filter_map(|command| serde_json::from_value(command.clone()).ok())
+        .collect();
+    for command in commands.iter_mut() {
+        command.allowed = allowed.contains(&command.name);
+    }
+    commands.sort_by(|a, b| a.name.cmp(&b.name));
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_configured_commands() {
+        let items = [json!({"command": [
+            {"name": "restart-wazuh", "executable": "restart-wazuh"},
+            {"name": "firewall-drop", "executable": "firewall-drop", "timeout_allowed": "yes"},
+        ]})];
+        let commands = configured_commands(&items, &["firewall-drop".to_string()]);
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].name, "firewall-drop");
+        assert!(commands[0].allowed);
+        assert_eq!(commands[0].timeout_allowed, "yes");
+        assert!(!commands[1].allowed);
+    }
+}
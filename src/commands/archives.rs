@@ -0,0 +1,114 @@
+use anyhow::Result;
+
+use crate::{
+    cli::{ArchivesAction, ArchivesCommand},
+    compliance::ComplianceFilter,
+    config::{Config, OutputConfig},
+    estimate::{confirm_large_result, Estimate},
+    indexer::{is_unavailable, AlertQuery, IndexerClient},
+    output::{print_archives_table, print_json, print_warning, spinner, WarningKind},
+    progress::Progress,
+    utils::parse_duration,
+};
+
+pub async fn handle_archives_command(
+    cmd: ArchivesCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    let indexer = IndexerClient::new(config)?;
+
+    match cmd.action {
+        ArchivesAction::Search {
+            query,
+            since,
+            level,
+            agent,
+            rule,
+            compliance,
+            limit,
+        } => {
+            let query = AlertQuery {
+                since: Some(parse_duration(&since)?),
+                query,
+                level,
+                agent,
+                rule_id: rule,
+                compliance: compliance
+                    .map(|c| c.parse::<ComplianceFilter>())
+                    .transpose()?,
+                limit,
+            };
+            search_archives(&indexer, &query, &config.output, json_output).await?
+        }
+    }
+
+    Ok(())
+}
+
+async fn search_archives(
+    indexer: &IndexerClient,
+    query: &AlertQuery,
+    output: &OutputConfig,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Counting matching events...");
+    let count = indexer.count_archives(query).await;
+    pb.finish_and_clear();
+
+    // An unreachable indexer is handled by the search, which can fall back to cached results
+    let estimate = match count {
+        Ok((total, latency)) => Estimate {
+            total: total.min(query.limit as u64),
+            page_size: query.limit as u64,
+            page_latency: latency,
+        },
+        Err(e) if is_unavailable(&e) => Estimate {
+            total: 0,
+            page_size: query.limit as u64,
+            page_latency: Default::default(),
+        },
+        Err(e) => return Err(e),
+    };
+    if !confirm_large_result(&estimate, "events", output)? {
+        println!("Operation cancelled");
+        return Ok(());
+    }
+
+    let pb = spinner("Searching archives...");
+    let mut progress = Progress::start("archives search", Some(estimate.total));
+    let events = indexer.search_archives(query).await?;
+    pb.finish_and_clear();
+    progress.page_fetched(events.len() as u64);
+    progress.finish();
+
+    if events.is_empty() && !archives_indexed(indexer).await {
+        print_warning(
+            WarningKind::Unsupported,
+            &format!(
+                "No index matches {}; enable logall_json on the manager and the archives \
+                 input in Filebeat to index archived events",
+                indexer.archives_index()
+            ),
+        );
+    }
+
+    if json_output {
+        print_json(&events)?;
+    } else {
+        print_archives_table(&events);
+        println!("\nShowing: {} events", events.len());
+    }
+
+    Ok(())
+}
+
+/// Whether any index matches the archives pattern; archiving is off by default
+async fn archives_indexed(indexer: &IndexerClient) -> bool {
+    let path = format!("_cat/indices/{}?format=json", indexer.archives_index());
+    match indexer.get(&path).await {
+        Ok(indices) => indices.as_array().is_some_and(|indices| !indices.is_empty()),
+        // Unknown rather than missing: don't blame the manager configuration
+        Err(_) => true,
+    }
+}
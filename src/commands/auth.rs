@@ -0,0 +1,182 @@
+use anyhow::{anyhow, Context, Result};
+use dialoguer::{Input, Password};
+use serde_json::json;
+use std::io::{IsTerminal, Read};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::{
+    cli::{AuthAction, AuthCommand},
+    client::WazuhClient,
+    config::Config,
+    credentials::{self, Secret},
+    output::{print_info, print_json, print_success, print_warning, spinner, WarningKind},
+    tokens,
+};
+
+pub async fn handle_auth_command(
+    cmd: AuthCommand,
+    config: &Config,
+    config_path: &Path,
+    json_output: bool,
+) -> Result<()> {
+    match cmd.action {
+        AuthAction::Login {
+            username,
+            password_stdin,
+            with_token,
+        } => {
+            let secret = if with_token { Secret::Token } else { Secret::Password };
+            login(config, config_path, username, secret, password_stdin, json_output).await
+        }
+        AuthAction::Logout => logout(config, config_path, json_output),
+    }
+}
+
+async fn login(
+    config: &Config,
+    config_path: &Path,
+    username: Option<String>,
+    secret: Secret,
+    from_stdin: bool,
+    json_output: bool,
+) -> Result<()> {
+    // Credentials are checked with the effective settings (TLS, headers) but
+    // saved to the file, in the profile in use, without this run's overrides
+    let mut session = config.clone();
+    let profile = config.current_profile.as_deref();
+    let keyring = Config::load(config_path)?.with_profile(profile)?.auth.keyring;
+
+    let value = match secret {
+        Secret::Token => read_stdin("token")?,
+        Secret::Password => {
+            let username = match username.or_else(|| config.auth.username.clone()) {
+                Some(username) => username,
+                None => Input::new().with_prompt("API username").interact_text()?,
+            };
+            session.auth.username = Some(username);
+            if from_stdin {
+                read_stdin("password")?
+            } else {
+                Password::new().with_prompt("API password").interact()?
+            }
+        }
+    };
+
+    match secret {
+        Secret::Token => session.auth.token = Some(value.clone()),
+        Secret::Password => {
+            session.auth.password = Some(value.clone());
+            session.auth.token = None;
+            // Log in for real rather than reusing the session of earlier credentials
+            if let Some(key) = tokens::cache_key(&session) {
+                tokens::remove(&key)?;
+            }
+        }
+    }
+    let mut check = session.clone();
+    check.auth.keyring = false;
+    let pb = spinner("Checking credentials...");
+    let result = check_credentials(check).await;
+    pb.finish_and_clear();
+    result?;
+
+    let key = format!("auth.{}", secret.name());
+    let stored_in = if keyring {
+        credentials::store(&session, secret, &value)?;
+        "the OS credential store".to_string()
+    } else {
+        config_path.display().to_string()
+    };
+    let username = session.auth.username.clone().filter(|_| secret == Secret::Password);
+    Config::update(config_path, |saved| {
+        if let Some(username) = username {
+            saved.set_value(profile, "auth.username", Some(username.into()))?;
+        }
+        let stored = (!keyring).then(|| value.clone().into());
+        Ok(saved.set_value(profile, &key, stored)?)
+    })?;
+
+    if json_output {
+        return print_json(&json!({
+            "api_url": session.api_url(),
+            "username": session.auth.username,
+            "stored": secret.name(),
+            "keyring": keyring,
+        }));
+    }
+    print_success(&format!(
+        "Logged in to {}; {} stored in {}",
+        session.api_url(),
+        secret.name(),
+        stored_in
+    ));
+    if !keyring {
+        print_warning(
+            WarningKind::Fallback,
+            "auth.keyring is off, so the credential is kept in plain text in the config file",
+        );
+    }
+    Ok(())
+}
+
+/// Log in with the credentials, and make a request so a token is also checked
+/// by the manager rather than only by its expiry
+async fn check_credentials(config: Config) -> Result<()> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config))).await?;
+    client.authenticate().await?;
+    let response = client.get("/").await?;
+    WazuhClient::response_text(response).await?;
+    Ok(())
+}
+
+fn read_stdin(what: &str) -> Result<String> {
+    if std::io::stdin().is_terminal() {
+        return Err(anyhow!("Pipe the {} into standard input", what));
+    }
+    let mut value = String::new();
+    std::io::stdin()
+        .read_to_string(&mut value)
+        .with_context(|| format!("Failed to read the {} from standard input", what))?;
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(anyhow!("No {} on standard input", what));
+    }
+    Ok(value.to_string())
+}
+
+fn logout(config: &Config, config_path: &Path, json_output: bool) -> Result<()> {
+    if let Some(key) = tokens::cache_key(config) {
+        tokens::remove(&key)?;
+    }
+    // With the store turned off there is nothing in it to clear
+    let (password, token) = if config.auth.keyring {
+        (
+            credentials::remove(config, Secret::Password)?,
+            credentials::remove(config, Secret::Token)?,
+        )
+    } else {
+        (false, false)
+    };
+
+    let saved = Config::load(config_path)?.with_profile(config.current_profile.as_deref())?;
+    let plaintext = saved.auth.password.is_some() || saved.auth.token.is_some();
+
+    if json_output {
+        return print_json(&json!({
+            "api_url": config.api_url(),
+            "removed_password": password,
+            "removed_token": token,
+            "credentials_in_config": plaintext,
+        }));
+    }
+    print_success(&format!("Logged out of {}", config.api_url()));
+    if plaintext {
+        print_info(&format!(
+            "auth.password or auth.token is still set in {}",
+            config_path.display()
+        ));
+    }
+    Ok(())
+}
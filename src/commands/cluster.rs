@@ -0,0 +1,285 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::task::JoinSet;
+
+use crate::{
+    cli::{ClusterAction, ClusterCommand},
+    client::WazuhClient,
+    config::Config,
+    models::{ApiResponse, ClusterNode, ListResponse, NodeHealth, NodeSyncStatus},
+    output::{print_cluster_health, print_cluster_sync, print_json, spinner},
+};
+
+pub async fn handle_cluster_command(
+    cmd: ClusterCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    let config = Arc::new(RwLock::new(config.clone()));
+    let client = WazuhClient::new(config).await?;
+
+    // Ensure we're authenticated
+    client.authenticate().await?;
+
+    match cmd.action {
+        ClusterAction::Ping => ping_cluster(&client, json_output).await?,
+        ClusterAction::SyncStatus { node, logs } => {
+            sync_status(&client, node.as_deref(), logs, json_output).await?
+        }
+    }
+
+    Ok(())
+}
+
+async fn ping_cluster(client: &WazuhClient, json_output: bool) -> Result<()> {
+    let pb = spinner("Probing cluster nodes...");
+
+    let response = client.get("/cluster/nodes").await?;
+    let api_response: ApiResponse<ListResponse<ClusterNode>> =
+        WazuhClient::parse_response(response).await?;
+
+    let mut probes = JoinSet::new();
+    for (index, node) in api_response.data.affected_items.into_iter().enumerate() {
+        let client = client.clone();
+        probes.spawn(async move { (index, probe_node(&client, node).await) });
+    }
+
+    let mut results = Vec::new();
+    while let Some(joined) = probes.join_next().await {
+        results.push(joined?);
+    }
+    results.sort_by_key(|(index, _)| *index);
+    let nodes: Vec<NodeHealth> = results.into_iter().map(|(_, health)| health).collect();
+
+    pb.finish_and_clear();
+
+    if json_output {
+        print_json(&nodes)?;
+    } else {
+        print_cluster_health(&nodes);
+        let unreachable = nodes.iter().filter(|n| !n.api_reachable).count();
+        println!("\nNodes: {} ({} unreachable)", nodes.len(), unreachable);
+    }
+
+    Ok(())
+}
+
+/// Probe a node's API directly and ask the master for its daemon status
+async fn probe_node(client: &WazuhClient, node: ClusterNode) -> NodeHealth {
+    let mut health = NodeHealth {
+        name: node.name.clone(),
+        node_type: node.node_type.clone(),
+        ip: node.ip.clone(),
+        api_reachable: false,
+        latency_ms: None,
+        clock_skew_secs: None,
+        daemons_running: None,
+        daemons_total: None,
+        error: None,
+    };
+
+    if let Some(ip) = &node.ip {
+        let url = format!("{}://{}:{}/", client.api_protocol().await, ip, client.api_port().await);
+        match client.probe(&url).await {
+            Ok(probe) => {
+                // 401 is expected for an unauthenticated probe; 5xx means the API is unhealthy
+                health.api_reachable = !probe.status.is_server_error();
+                if probe.status.is_server_error() {
+                    health.error = Some(format!("API returned {}", probe.status));
+                }
+                health.latency_ms = Some(probe.latency.as_millis() as u64);
+                health.clock_skew_secs = probe
+                    .server_date
+                    .map(|date| (date - Utc::now()).num_seconds());
+            }
+            Err(e) => health.error = Some(format!("{:#}", e)),
+        }
+    }
+
+    match node_daemons(client, &node.name).await {
+        Ok((running, total)) => {
+            health.daemons_running = Some(running);
+            health.daemons_total = Some(total);
+        }
+        Err(e) => {
+            health.error.get_or_insert_with(|| e.to_string());
+        }
+    }
+
+    health
+}
+
+/// Count running daemons on a node (optional daemons are often stopped by design)
+async fn node_daemons(client: &WazuhClient, node: &str) -> Result<(usize, usize)> {
+    let response = client.get(&format!("/cluster/{}/status", node)).await?;
+    let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+
+    let statuses: Vec<String> = api_response
+        .data
+        .affected_items
+        .first()
+        .and_then(|item| item.as_object())
+        .map(|daemons| {
+            daemons
+                .values()
+                .filter_map(|status| status.as_str().map(str::to_lowercase))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let running = statuses.iter().filter(|s| s.as_str() == "running").count();
+    Ok((running, statuses.len()))
+}
+
+async fn sync_status(
+    client: &WazuhClient,
+    node: Option<&str>,
+    logs: u32,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Fetching cluster synchronization status...");
+
+    let url = match node {
+        Some(node) => format!("/cluster/healthcheck?nodes_list={}", node),
+        None => "/cluster/healthcheck".to_string(),
+    };
+    let response = client.get(&url).await?;
+    let api_response: ApiResponse<ListResponse<Value>> =
+        WazuhClient::parse_response(response).await?;
+
+    let mut nodes: Vec<NodeSyncStatus> = api_response
+        .data
+        .affected_items
+        .iter()
+        .map(node_sync_status)
+        .collect();
+    if let Some(node) = node {
+        if nodes.is_empty() {
+            pb.finish_and_clear();
+            return Err(anyhow!("Node '{}' not found in the cluster", node));
+        }
+    }
+
+    if logs > 0 {
+        pb.set_message("Fetching integrity logs...");
+        for status in nodes.iter_mut().filter(|n| n.node_type == "worker") {
+            // Logs are best effort; a node that cannot be reached still has a sync status
+            status.recent_logs = integrity_logs(client, &status.name, logs)
+                .await
+                .unwrap_or_default();
+        }
+    }
+
+    pb.finish_and_clear();
+
+    if json_output {
+        print_json(&nodes)?;
+    } else {
+        print_cluster_sync(&nodes);
+        for status in nodes.iter().filter(|n| !n.recent_logs.is_empty()) {
+            println!("\n{}:", status.name);
+            for line in &status.recent_logs {
+                println!("  {}", line);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Build a node's sync status from one `/cluster/healthcheck` item
+fn node_sync_status(item: &Value) -> NodeSyncStatus {
+    let info = &item["info"];
+    let status = &item["status"];
+    let text = |value: &Value| value.as_str().map(String::from);
+    let files = |category: &str| status["last_sync_integrity"]["total_files"][category].as_u64();
+    let end_date = |task: &Value| parse_cluster_date(&task["date_end_master"]);
+
+    NodeSyncStatus {
+        name: text(&info["name"]).unwrap_or_default(),
+        node_type: text(&info["type"]).unwrap_or_default(),
+        version: text(&info["version"]),
+        last_keep_alive: parse_cluster_date(&status["last_keep_alive"]),
+        last_integrity_check: end_date(&status["last_check_integrity"]),
+        last_integrity_sync: end_date(&status["last_sync_integrity"]),
+        last_agent_info_sync: end_date(&status["last_sync_agentinfo"]),
+        files_missing: files("missing"),
+        files_shared: files("shared"),
+        files_extra: files("extra"),
+        // `sync_integrity_free` is false while the master is sending files to the worker
+        syncing: status["sync_integrity_free"].as_bool() == Some(false),
+        recent_logs: Vec::new(),
+    }
+}
+
+/// Cluster timestamps are RFC 3339 strings, or "n/a" before the first run
+fn parse_cluster_date(value: &Value) -> Option<DateTime<Utc>> {
+    value
+        .as_str()
+        .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// Most recent integrity-related log lines of a node, newest first
+async fn integrity_logs(client: &WazuhClient, node: &str, limit: u32) -> Result<Vec<String>> {
+    let url = format!(
+        "/cluster/{}/logs?limit={}&search=Integrity&sort=-timestamp",
+        node, limit
+    );
+    let response = client.get(&url).await?;
+    let api_response: ApiResponse<ListResponse<Value>> =
+        WazuhClient::parse_response(response).await?;
+
+    Ok(api_response
+        .data
+        .affected_items
+        .iter()
+        .map(|line| {
+            format!(
+                "{} {}: {}",
+                line["timestamp"].as_str().unwrap_or(""),
+                line["level"].as_str().unwrap_or(""),
+                line["description"].as_str().unwrap_or("").trim()
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_node_sync_status() {
+        let worker = json!({
+            "info": {"name": "worker1", "type": "worker", "version": "4.7.2", "ip": "10.0.0.2"},
+            "status": {
+                "last_keep_alive": "2024-05-01T10:00:00.000Z",
+                "sync_integrity_free": false,
+                "last_check_integrity": {"date_end_master": "2024-05-01T09:59:50.000Z"},
+                "last_sync_integrity": {
+                    "date_end_master": "n/a",
+                    "total_files": {"missing": 2, "shared": 1, "extra": 0}
+                },
+                "last_sync_agentinfo": {"date_end_master": "2024-05-01T09:59:55.000Z"}
+            }
+        });
+
+        let status = node_sync_status(&worker);
+        assert_eq!(status.name, "worker1");
+        assert!(status.syncing);
+        assert!(status.last_integrity_check.is_some());
+        assert!(status.last_integrity_sync.is_none());
+        assert_eq!(status.files_missing, Some(2));
+        assert_eq!(status.files_shared, Some(1));
+
+        let master = node_sync_status(&json!({"info": {"name": "master", "type": "master"}}));
+        assert!(!master.syncing);
+        assert!(master.last_keep_alive.is_none());
+    }
+}
@@ -0,0 +1,176 @@
+use anyhow::Result;
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::{
+    cli::{CompletionAction, CompletionCommand},
+    client::WazuhClient,
+    completion::{self, CachedResources, Candidate},
+    config::Config,
+    models::{ApiResponse, ListResponse},
+    output::{print_json, print_success, spinner},
+};
+
+pub async fn handle_completion_command(
+    cmd: CompletionCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    match cmd.action {
+        CompletionAction::Refresh => refresh(config, json_output).await,
+    }
+}
+
+async fn refresh(config: &Config, json_output: bool) -> Result<()> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+    client.authenticate().await?;
+
+    let pb = spinner("Fetching agents, groups and rules...");
+    let resources = fetch_resources(&client).await?;
+    pb.finish_and_clear();
+    let counts = (resources.agents.len(), resources.groups.len(), resources.rules.len());
+    completion::save(&config.api_url(), resources)?;
+
+    if json_output {
+        return print_json(&json!({
+            "agents": counts.0,
+            "groups": counts.1,
+            "rules": counts.2,
+        }));
+    }
+    print_success(&format!(
+        "Cached {} agents, {} groups and {} rules for shell completion",
+        counts.0, counts.1, counts.2
+    ));
+    Ok(())
+}
+
+/// Everything completion offers for the client's manager
+async fn fetch_resources(client: &WazuhClient) -> Result<CachedResources> {
+    let agents = fetch(client, "/agents?select=id,name").await?;
+    let groups = fetch(client, "/groups").await?;
+    let rules = fetch(client, "/rules?select=id,description").await?;
+    // Users without manager:read still get the other completions
+    let services = fetch_services(client).await.unwrap_or_else(|e| {
+        debug!("No services to complete: {:#}", e);
+        Vec::new()
+    });
+
+    let mut groups = candidates(&groups, "name", "count");
+    for group in &mut groups {
+        group.help = group.help.take().map(|count| format!("{} agents", count));
+    }
+
+    Ok(CachedResources {
+        refreshed_at: Utc::now(),
+        agents: candidates(&agents, "id", "name"),
+        groups,
+        rules: candidates(&rules, "id", "description"),
+        services,
+    })
+}
+
+async fn fetch(client: &WazuhClient, url: &str) -> Result<Vec<Value>> {
+    let items: ApiResponse<ListResponse<Value>> = client.get_list(url).await?;
+    Ok(items.data.affected_items)
+}
+
+/// Manager daemons from `/manager/status`, with their status as help
+async fn fetch_services(client: &WazuhClient) -> Result<Vec<Candidate>> {
+    let response = client.get("/manager/status").await?;
+    let status: ApiResponse<Value> = WazuhClient::parse_response(response).await?;
+    let daemons = status.data.pointer("/affected_items/0").unwrap_or(&status.data);
+    Ok(daemons
+        .as_object()
+        .map(|daemons| {
+            daemons
+                .iter()
+                .map(|(name, state)| Candidate {
+                    value: name.clone(),
+                    help: state.as_str().map(String::from),
+                })
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Completion values from one field of each item, described by another
+fn candidates(items: &[Value], value: &str, help: &str) -> Vec<Candidate> {
+    let text = |v: &Value| match v {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            Some(Candidate {
+                value: text(&item[value])?,
+                help: text(&item[help]),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fetch_resources() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let list = |items: Value| {
+            let total = items.as_array().map_or(0, Vec::len);
+            ResponseTemplate::new(200).set_body_json(json!({
+                "error": 0,
+                "data": {
+                    "affected_items": items,
+                    "failed_items": [],
+                    "total_affected_items": total,
+                    "total_failed_items": 0,
+                },
+            }))
+        };
+        Mock::given(method("GET"))
+            .and(path("/agents"))
+            .respond_with(list(json!([{"id": "001", "name": "web-01"}])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups"))
+            .respond_with(list(json!([{"name": "default", "count": 3}])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rules"))
+            .respond_with(list(json!([{"id": 5710, "description": "sshd: invalid user"}])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/manager/status"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+
+        let address = server.address();
+        let mut config = Config::default();
+        config.api.protocol = "http".to_string();
+        config.api.host = address.ip().to_string();
+        config.api.port = address.port();
+        config.auth.token = Some("token".to_string());
+        let client = WazuhClient::new(Arc::new(RwLock::new(config))).await.unwrap();
+
+        let resources = fetch_resources(&client).await.unwrap();
+        assert_eq!(resources.agents[0].value, "001");
+        assert_eq!(resources.agents[0].help.as_deref(), Some("web-01"));
+        assert_eq!(resources.groups[0].help.as_deref(), Some("3 agents"));
+        assert_eq!(resources.rules[0].value, "5710");
+        assert!(resources.services.is_empty());
+    }
+}
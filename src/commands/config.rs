@@ -1,54 +1,340 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
 use colored::Colorize;
+use dialoguer::Confirm;
 use std::env;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Arc;
+use tokio::sync::RwLock;
 
 use crate::{
-    cli::{ConfigAction, ConfigCommand},
-    config::Config,
-    output::{print_json, print_success, print_info},
+    changes::{self, ChangeStatus, StagedChange},
+    cli::{ConfigAction, ConfigCommand, RemoteConfigAction},
+    client::WazuhClient,
+    config::{Config, DEFAULT_PROFILE},
+    doctor::{self, CheckStatus},
+    error::WazuhError,
+    models::{ApiResponse, ListResponse},
+    output::{
+        print_changes_table, print_checks, print_diff, print_info, print_json, print_profiles,
+        print_success, spinner,
+    },
 };
 
+/// Manager configuration endpoint; `?raw=true` returns ossec.conf as text
+const MANAGER_CONFIG_ENDPOINT: &str = "/manager/configuration";
+
 pub async fn handle_config_command(
     cmd: ConfigCommand,
     config: &Config,
+    config_path: &Path,
     json_output: bool,
 ) -> Result<()> {
     match cmd.action {
-        ConfigAction::Show => show_config(config, json_output),
-        ConfigAction::Set { key, value } => set_config_value(config, &key, &value),
+        ConfigAction::Show { show_secrets } => show_config(config, show_secrets, json_output),
+        ConfigAction::Set { key, value } => {
+            set_config_value(config, config_path, &key, &value, json_output)
+        }
+        ConfigAction::Unset { key } => unset_config_value(config, config_path, &key, json_output),
         ConfigAction::Get { key } => get_config_value(config, &key, json_output),
         ConfigAction::Init { force } => init_config(force),
         ConfigAction::Edit => edit_config(),
+        ConfigAction::Test => test_connection(config, json_output).await,
+        ConfigAction::UseContext { name } => use_context(config_path, &name, json_output),
+        ConfigAction::GetContexts => get_contexts(config, config_path, json_output),
+        ConfigAction::Remote { action } => handle_remote_action(action, config, json_output).await,
+    }
+}
+
+async fn handle_remote_action(
+    action: RemoteConfigAction,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    match action {
+        RemoteConfigAction::Stage { file, message } => {
+            stage_change(config, file, message, json_output).await
+        }
+        RemoteConfigAction::Apply {
+            change,
+            approver,
+            force,
+            yes,
+        } => apply_change(config, &change, approver, force, yes).await,
+        RemoteConfigAction::List { all } => list_changes(all, json_output),
+        RemoteConfigAction::Show { change } => show_change(&change, json_output),
+        RemoteConfigAction::Discard { change } => discard_change(&change),
+    }
+}
+
+async fn connect(config: &Config) -> Result<WazuhClient> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+    client.authenticate().await?;
+    Ok(client)
+}
+
+async fn fetch_manager_config(client: &WazuhClient) -> Result<String> {
+    let response = client
+        .get(&format!("{}?raw=true", MANAGER_CONFIG_ENDPOINT))
+        .await?;
+    WazuhClient::response_text(response).await
+}
+
+async fn stage_change(
+    config: &Config,
+    file: PathBuf,
+    message: Option<String>,
+    json_output: bool,
+) -> Result<()> {
+    let content = std::fs::read_to_string(&file)
+        .with_context(|| format!("Failed to read {:?}", file))?;
+
+    let client = connect(config).await?;
+    let pb = spinner("Fetching manager configuration...");
+    let current = fetch_manager_config(&client).await?;
+    pb.finish_and_clear();
+
+    if current == content {
+        print_info("Staged file is identical to the manager's configuration; nothing to stage");
+        return Ok(());
+    }
+
+    let change = StagedChange::new(file, &current, content, message);
+    changes::save(&change)?;
+
+    if json_output {
+        print_json(&change)?;
+    } else {
+        print_diff(&change.diff);
+        println!();
+        print_success(&format!("Staged change {}", change.id));
+        print_info(&format!(
+            "Review with 'config remote show {0}', then 'config remote apply --change {0}'",
+            change.id
+        ));
+    }
+
+    Ok(())
+}
+
+async fn apply_change(
+    config: &Config,
+    id: &str,
+    approver: Option<String>,
+    force: bool,
+    skip_confirm: bool,
+) -> Result<()> {
+    let mut change = changes::load(id)?;
+    if change.status != ChangeStatus::Pending {
+        let status = change.status.to_string().to_lowercase();
+        return Err(anyhow!("Change {} is already {}", id, status));
+    }
+    if !change.is_intact() {
+        return Err(anyhow!(
+            "Change {} was modified after staging (checksum mismatch); discard and stage it again",
+            id
+        ));
+    }
+
+    let client = connect(config).await?;
+    let current = fetch_manager_config(&client).await?;
+    if changes::checksum(&current) != change.base_checksum && !force {
+        return Err(anyhow!(
+            "The manager's configuration changed since change {} was staged; \
+             stage it again or use --force",
+            id
+        ));
     }
+
+    if !skip_confirm {
+        print_diff(&change.diff);
+        let confirm = Confirm::new()
+            .with_prompt(format!("Push change {} to the manager?", id))
+            .default(false)
+            .interact()?;
+
+        if !confirm {
+            println!("Operation cancelled");
+            return Ok(());
+        }
+    }
+
+    let pb = spinner("Uploading configuration...");
+    let response = client
+        .put_raw(
+            MANAGER_CONFIG_ENDPOINT,
+            "application/octet-stream",
+            change.content.clone().into_bytes(),
+        )
+        .await?;
+    let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+    pb.finish_and_clear();
+
+    if api_response.data.total_failed_items > 0 {
+        return Err(anyhow!(
+            "Manager rejected the configuration: {}",
+            serde_json::to_string(&api_response.data.failed_items)?
+        ));
+    }
+
+    change.status = ChangeStatus::Applied;
+    change.approver = approver;
+    change.applied_at = Some(Utc::now());
+    changes::save(&change)?;
+
+    print_success(&format!("Applied change {}", id));
+    print_info("Restart the manager to load the new configuration: wazuh-cli control restart");
+
+    Ok(())
+}
+
+fn list_changes(all: bool, json_output: bool) -> Result<()> {
+    let changes: Vec<StagedChange> = changes::list()?
+        .into_iter()
+        .filter(|change| all || change.status == ChangeStatus::Pending)
+        .collect();
+
+    if json_output {
+        print_json(&changes)?;
+    } else if changes.is_empty() {
+        print_info("No staged changes");
+    } else {
+        print_changes_table(&changes);
+    }
+
+    Ok(())
+}
+
+fn show_change(id: &str, json_output: bool) -> Result<()> {
+    let change = changes::load(id)?;
+
+    if json_output {
+        print_json(&change)?;
+        return Ok(());
+    }
+
+    println!("{}: {}", "Change".bold(), change.id);
+    println!("{}: {}", "Status".bold(), change.status);
+    println!("{}: {}", "Author".bold(), change.author);
+    println!("{}: {}", "Staged".bold(), change.created_at.format("%Y-%m-%d %H:%M:%S UTC"));
+    println!("{}: {}", "Source".bold(), change.source_file.display());
+    if let Some(message) = &change.message {
+        println!("{}: {}", "Message".bold(), message);
+    }
+    println!("{}: {}", "Checksum".bold(), change.checksum);
+    if let Some(approver) = &change.approver {
+        println!("{}: {}", "Approver".bold(), approver);
+    }
+    if let Some(applied_at) = change.applied_at {
+        println!("{}: {}", "Applied".bold(), applied_at.format("%Y-%m-%d %H:%M:%S UTC"));
+    }
+    if !change.is_intact() {
+        println!("{}", "Warning: content does not match the recorded checksum".red().bold());
+    }
+    println!();
+    print_diff(&change.diff);
+
+    Ok(())
+}
+
+fn discard_change(id: &str) -> Result<()> {
+    let mut change = changes::load(id)?;
+    if change.status != ChangeStatus::Pending {
+        let status = change.status.to_string().to_lowercase();
+        return Err(anyhow!("Change {} is already {}", id, status));
+    }
+
+    change.status = ChangeStatus::Discarded;
+    changes::save(&change)?;
+    print_success(&format!("Discarded change {}", id));
+
+    Ok(())
 }
 
-fn show_config(config: &Config, json_output: bool) -> Result<()> {
+fn show_config(config: &Config, show_secrets: bool, json_output: bool) -> Result<()> {
+    let redacted;
+    let config = if show_secrets {
+        config
+    } else {
+        redacted = config.redacted();
+        &redacted
+    };
+
     if json_output {
         print_json(config)?;
     } else {
         println!("{}", "Current Configuration".bold().underline());
         println!();
-        
+        if let Some(name) = &config.name {
+            println!("{}: {}", "Name".bold(), name);
+        }
+        println!(
+            "{}: {}",
+            "Profile".bold(),
+            config.current_profile.as_deref().unwrap_or(DEFAULT_PROFILE)
+        );
+        println!();
+        if let Some(command) = &config.default_command {
+            println!("{}: {}", "Default Command".bold(), command);
+            println!();
+        }
+
         println!("{}", "API Settings:".bold());
         println!("  Host: {}", config.api.host);
         println!("  Port: {}", config.api.port);
         println!("  Protocol: {}", config.api.protocol);
         println!("  Timeout: {} seconds", config.api.timeout);
+        println!("  Long-Running Timeout: {} seconds", config.api.long_timeout);
         println!("  Max Retries: {}", config.api.max_retries);
+        if !config.api.extra_headers.is_empty() {
+            // Header values are often secrets, so only the names are shown by default
+            let headers: Vec<String> = config
+                .api
+                .extra_headers
+                .iter()
+                .map(|(name, value)| {
+                    if show_secrets {
+                        format!("{}: {}", name, value)
+                    } else {
+                        name.clone()
+                    }
+                })
+                .collect();
+            println!("  Extra Headers: {}", headers.join(", "));
+        }
+        if let Some(language) = &config.api.language {
+            println!("  Language: {}", language);
+        }
+        if let Some(destination) = &config.api.ssh_tunnel {
+            println!("  SSH Tunnel: {}", destination);
+        }
         println!();
         
         println!("{}", "Authentication:".bold());
         println!("  Username: {}", config.auth.username.as_deref().unwrap_or("(not set)"));
-        println!("  Password: {}", if config.auth.password.is_some() { "***" } else { "(not set)" });
-        println!("  Token: {}", if config.auth.token.is_some() { "(set)" } else { "(not set)" });
+        println!("  Password: {}", config.auth.password.as_deref().unwrap_or("(not set)"));
+        println!("  Token: {}", config.auth.token.as_deref().unwrap_or("(not set)"));
         println!("  Token Expiry: {} hours", config.auth.token_expiry_hours);
+        println!("  Clock Skew: {}s", config.auth.clock_skew_secs);
+        println!("  Keyring: {}", config.auth.keyring);
+        println!("  Run As: {}", config.auth.run_as);
+        if let Some(path) = &config.auth.auth_context {
+            println!("  Auth Context: {}", path.display());
+        }
         println!();
         
         println!("{}", "Output Settings:".bold());
         println!("  Format: {}", config.output.format);
         println!("  Color: {}", config.output.color);
         println!("  Pager: {}", config.output.pager);
+        println!("  Accessible: {}", config.output.a11y);
+        println!("  Large Result Threshold: {}", config.output.large_result_threshold);
+        println!("  Prompt On Large Results: {}", config.output.prompt_large_results);
+        println!("  Format Version: {}", config.output.format_version);
+        println!("  Diff Style: {}", config.output.diff_style);
+        println!("  Diff Context: {}", config.output.diff_context);
         println!();
         
         println!("{}", "TLS Settings:".bold());
@@ -56,51 +342,186 @@ fn show_config(config: &Config, json_output: bool) -> Result<()> {
         println!("  CA Certificate: {}", config.tls.ca_cert.as_ref().map(|p| p.display().to_string()).unwrap_or("(not set)".to_string()));
         println!("  Client Certificate: {}", config.tls.client_cert.as_ref().map(|p| p.display().to_string()).unwrap_or("(not set)".to_string()));
         println!("  Client Key: {}", config.tls.client_key.as_ref().map(|p| p.display().to_string()).unwrap_or("(not set)".to_string()));
+        if !config.tls.ca_certs.is_empty() {
+            let bundles: Vec<String> =
+                config.tls.ca_certs.iter().map(|p| p.display().to_string()).collect();
+            println!("  CA Bundles: {}", bundles.join(", "));
+        }
+        println!("  System Trust Store: {}", config.tls.system_roots);
+        println!("  Built-in Roots: {}", config.tls.builtin_roots);
+        for (name, profile) in &config.tls.profiles {
+            let selected = config.tls.profile.as_deref() == Some(name.as_str());
+            let hosts = if profile.hosts.is_empty() {
+                "(no hosts)".to_string()
+            } else {
+                profile.hosts.join(", ")
+            };
+            let marker = if selected { " (selected)" } else { "" };
+            println!("  Profile {}{}: {}", name, marker, hosts);
+        }
+        println!();
+
+        println!("{}", "Indexer Settings:".bold());
+        println!("  URL: {}", config.indexer_url());
+        println!("  Username: {}", config.indexer.username.as_deref().unwrap_or("(not set)"));
+        println!("  Password: {}", config.indexer.password.as_deref().unwrap_or("(not set)"));
+        println!("  Alerts Index: {}", config.indexer.alerts_index);
+        println!("  Archives Index: {}", config.indexer.archives_index);
     }
     
     Ok(())
 }
 
-fn set_config_value(_config: &Config, key: &str, value: &str) -> Result<()> {
-    print_info(&format!("Setting {} = {}", key, value));
-    
-    // In a real implementation, this would modify the config and save it
-    // For now, we'll just show a message
-    print_info("Configuration update not yet implemented");
-    print_info("Please edit the configuration file manually");
-    
+/// The profile a key is written to and the key within it: the profile in use,
+/// unless the key names one as `profiles.NAME.key`
+fn key_target<'a>(config: &'a Config, key: &'a str) -> Result<(Option<&'a str>, &'a str)> {
+    if key == "current_profile" {
+        return Err(anyhow!("Use 'config use-context NAME' to change the default profile"));
+    }
+    if let Some(rest) = key.strip_prefix("profiles.") {
+        return match rest.split_once('.') {
+            Some((profile, key)) => Ok((Some(profile), key)),
+            None => Err(anyhow!("Expected profiles.NAME.KEY, e.g. profiles.lab.api.host")),
+        };
+    }
+    Ok((config.current_profile.as_deref(), key))
+}
+
+/// Passwords, tokens and header values are not echoed back
+fn shown_value(key: &str, value: &toml::Value) -> String {
+    let secret = key.ends_with("password") || key.ends_with("token") || key.contains("headers.");
+    match value {
+        _ if secret => "***".to_string(),
+        toml::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn set_config_value(
+    config: &Config,
+    config_path: &Path,
+    key: &str,
+    value: &str,
+    json_output: bool,
+) -> Result<()> {
+    let (profile, key) = key_target(config, key)?;
+    // Reload the file so this run's overrides are not saved with it
+    let stored = Config::update(config_path, |saved| {
+        if let Some(name) = profile {
+            saved.profiles.entry(name.to_string()).or_default();
+        }
+        Ok(saved.set_from_str(profile, key, value)?)
+    })?;
+
+    let shown = shown_value(key, &stored);
+    if json_output {
+        return print_json(&serde_json::json!({ "key": key, "value": shown, "profile": profile }));
+    }
+    match profile {
+        Some(name) => print_success(&format!("Set {} = {} in profile {}", key, shown, name)),
+        None => print_success(&format!("Set {} = {}", key, shown)),
+    }
     Ok(())
 }
 
-fn get_config_value(config: &Config, key: &str, json_output: bool) -> Result<()> {
-    let value = match key {
-        "api.host" => Some(config.api.host.clone()),
-        "api.port" => Some(config.api.port.to_string()),
-        "api.protocol" => Some(config.api.protocol.clone()),
-        "api.timeout" => Some(config.api.timeout.to_string()),
-        "api.max_retries" => Some(config.api.max_retries.to_string()),
-        "auth.username" => config.auth.username.clone(),
-        "auth.token_expiry_hours" => Some(config.auth.token_expiry_hours.to_string()),
-        "output.format" => Some(config.output.format.clone()),
-        "output.color" => Some(config.output.color.to_string()),
-        "output.pager" => Some(config.output.pager.to_string()),
-        "tls.verify" => Some(config.tls.verify.to_string()),
-        _ => None,
-    };
-    
-    if let Some(val) = value {
-        if json_output {
-            print_json(&serde_json::json!({ key: val }))?;
-        } else {
-            println!("{} = {}", key, val);
+fn unset_config_value(
+    config: &Config,
+    config_path: &Path,
+    key: &str,
+    json_output: bool,
+) -> Result<()> {
+    let (profile, key) = key_target(config, key)?;
+    let was_set = Config::update(config_path, |saved| {
+        let was_set = saved.is_set(profile, key);
+        if was_set {
+            saved.set_value(profile, key, None)?;
         }
+        Ok(was_set)
+    })?;
+
+    if json_output {
+        return print_json(&serde_json::json!({
+            "key": key,
+            "profile": profile,
+            "removed": was_set,
+        }));
+    }
+    let place = profile.map(|name| format!(" in profile {}", name)).unwrap_or_default();
+    if was_set {
+        print_success(&format!("Unset {}{}", key, place));
     } else {
-        eprintln!("{} Unknown configuration key: {}", "Error:".red().bold(), key);
+        print_info(&format!("{} is not set{}", key, place));
     }
-    
     Ok(())
 }
 
+fn get_config_value(config: &Config, key: &str, json_output: bool) -> Result<()> {
+    if !config.is_setting(key) {
+        return Err(WazuhError::InvalidInput(format!("Unknown configuration key: {}", key)).into());
+    }
+    // Passwords, tokens, API keys and header values are not shown
+    let value = config.redacted().value_at(key);
+
+    if json_output {
+        return print_json(&serde_json::json!({ key: value }));
+    }
+    match value {
+        Some(toml::Value::String(text)) => println!("{} = {}", key, text),
+        Some(value) => println!("{} = {}", key, value),
+        None => println!("{} = (not set)", key),
+    }
+    Ok(())
+}
+
+async fn test_connection(config: &Config, json_output: bool) -> Result<()> {
+    let pb = spinner(&format!("Checking {}...", config.api_url()));
+    let checks = doctor::run(config).await;
+    pb.finish_and_clear();
+
+    if json_output {
+        print_json(&checks)?;
+    } else {
+        println!("{}", format!("Connection to {}", config.api_url()).bold().underline());
+        println!();
+        print_checks(&checks);
+    }
+
+    match checks.iter().find(|check| check.status == CheckStatus::Fail) {
+        Some(failed) => Err(anyhow!("Connection test failed at: {}", failed.name)),
+        None => Ok(()),
+    }
+}
+
+/// Save `name` as the profile used when `--profile` is not given
+fn use_context(config_path: &Path, name: &str, json_output: bool) -> Result<()> {
+    // Reload the file so this run's overrides are not saved with it
+    let profile = Config::update(config_path, |saved| {
+        let profile = saved.clone().with_profile(Some(name))?;
+        saved.current_profile = profile.current_profile.clone();
+        Ok(profile)
+    })?;
+
+    if json_output {
+        return print_json(&serde_json::json!({
+            "profile": name,
+            "api_url": profile.api_url(),
+        }));
+    }
+    print_success(&format!("Switched to profile {} ({})", name, profile.api_url()));
+    Ok(())
+}
+
+fn get_contexts(config: &Config, config_path: &Path, json_output: bool) -> Result<()> {
+    let saved = Config::load(config_path)?;
+    let profiles = saved.profile_summaries(config.current_profile.as_deref());
+    if json_output {
+        print_json(&profiles)
+    } else {
+        print_profiles(&profiles);
+        Ok(())
+    }
+}
+
 fn init_config(force: bool) -> Result<()> {
     let config_path = Config::default_config_path()?;
     
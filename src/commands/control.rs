@@ -1,19 +1,41 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
 use colored::Colorize;
-use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
-use tracing::info;
+use tracing::{debug, info};
 
 use crate::{
-    cli::{ControlAction, ControlCommand},
+    cli::{ControlAction, ControlCommand, MaintenanceAction, ManagerConfigAction},
     client::WazuhClient,
+    commands::monitor::{fetch_daemon_stats, queue_usages},
     config::Config,
-    models::{ApiResponse, Service},
-    output::{print_json, print_services_table, print_success},
+    maintenance::{self, MaintenanceStep, MaintenanceWindow, StepOutcome},
+    models::{
+        ApiResponse, ClusterNode, LimitLevel, LimitUsage, LimitsReport, ListResponse, ManagerLog,
+        ManagerLogSummary, Service,
+    },
+    notify::Notifier,
+    output::{
+        print_config_tree, print_info, print_json, print_limits, print_maintenance_window,
+        print_manager_log, print_manager_log_summary, print_services_table, print_success,
+        print_warning, spinner, WarningKind,
+    },
+    progress::Progress,
+    ssh::SshTarget,
+    utils::{json_to_text, parse_duration},
 };
 
+/// How often queue usage is polled while draining
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// How long to wait for a restarted node to answer again
+const RESTART_TIMEOUT: Duration = Duration::from_secs(300);
+/// Stops integratord using its pid file; `exit` starts it again
+const PAUSE_INTEGRATIONS: &str = r#"sudo -n sh -c 'for f in /var/ossec/var/run/wazuh-integratord-*.pid; do [ -f "$f" ] && kill "$(cat "$f")"; done; true'"#;
+const RESUME_INTEGRATIONS: &str = "sudo -n /var/ossec/bin/wazuh-integratord";
+
 pub async fn handle_control_command(
     cmd: ControlCommand,
     config: &Config,
@@ -39,6 +61,79 @@ pub async fn handle_control_command(
             restart_service(&client, service, json_output).await?
         }
         ControlAction::Info => get_manager_info(&client, json_output).await?,
+        ControlAction::Logs {
+            level,
+            tag,
+            tail,
+            follow,
+            interval,
+            summary,
+        } => {
+            if summary {
+                show_log_summary(&client, tag.as_deref(), json_output).await?
+            } else {
+                let filter = LogFilter { level, tag };
+                let follow = if follow {
+                    Some(parse_duration(&interval)?.to_std()?)
+                } else {
+                    None
+                };
+                show_logs(&client, &filter, tail, follow, json_output).await?
+            }
+        }
+        ControlAction::Config { action } => match action {
+            ManagerConfigAction::Get {
+                section,
+                field,
+                component,
+                configuration,
+            } => {
+                let source = match (component, configuration) {
+                    (Some(component), Some(configuration)) => ConfigSource::Active {
+                        component,
+                        configuration,
+                    },
+                    _ => ConfigSource::File { section, field },
+                };
+                show_manager_config(&client, &source, json_output).await?
+            }
+        },
+        ControlAction::Limits { warn, critical } => {
+            show_limits(&client, warn, critical, json_output).await?
+        }
+        ControlAction::Maintenance { action } => match action {
+            MaintenanceAction::Enter {
+                reason,
+                host,
+                port,
+                identity,
+                drain_below,
+                drain_timeout,
+                webhook,
+                desktop,
+            } => {
+                let window = MaintenanceWindow {
+                    started_at: Utc::now(),
+                    ended_at: None,
+                    operator: std::env::var("USER").unwrap_or_else(|_| "unknown".to_string()),
+                    reason,
+                    host,
+                    port,
+                    identity,
+                    webhook,
+                    desktop,
+                    stats_before: None,
+                    stats_after: None,
+                    steps: Vec::new(),
+                };
+                let drain_timeout = parse_duration(&drain_timeout)?.to_std()?;
+                enter_maintenance(&client, window, drain_below, drain_timeout, json_output).await?
+            }
+            MaintenanceAction::Exit { restart } => {
+                exit_maintenance(&client, restart, json_output).await?
+            }
+            MaintenanceAction::Status => maintenance_status(json_output)?,
+        },
     }
 
     Ok(())
@@ -49,14 +144,7 @@ async fn get_service_status(
     service: Option<String>,
     json_output: bool,
 ) -> Result<()> {
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.green} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Fetching service status...");
-    pb.enable_steady_tick(Duration::from_millis(120));
+    let pb = spinner("Fetching service status...");
 
     // Get manager status which includes service information
     let response = client.get("/manager/status").await?;
@@ -108,19 +196,11 @@ async fn start_service(
     service: Option<String>,
     json_output: bool,
 ) -> Result<()> {
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.green} {msg}")
-            .unwrap(),
-    );
-    
     let service_name = service.as_deref().unwrap_or("all");
-    pb.set_message(format!("Starting {}...", service_name));
-    pb.enable_steady_tick(Duration::from_millis(120));
+    let pb = spinner(&format!("Starting {}...", service_name));
 
-    let url = if service.is_some() && service.as_ref().unwrap() != "all" {
-        format!("/manager/restart?service={}", service.as_ref().unwrap())
+    let url = if let Some(name) = service.as_deref().filter(|s| *s != "all") {
+        format!("/manager/restart?service={}", name)
     } else {
         "/manager/restart".to_string()
     };
@@ -161,11 +241,11 @@ async fn stop_service(
     
     // Note: Wazuh API doesn't have a direct stop endpoint for individual services
     // This is a limitation of the current API
-    eprintln!(
-        "{} Stopping individual services is not supported by the Wazuh API.",
-        "Warning:".yellow().bold()
+    print_warning(
+        WarningKind::Unsupported,
+        "Stopping individual services is not supported by the Wazuh API; \
+         use systemctl or service on the manager host instead",
     );
-    eprintln!("Please use system service management commands (systemctl, service) to stop services.");
     
     Ok(())
 }
@@ -175,16 +255,8 @@ async fn restart_service(
     service: Option<String>,
     json_output: bool,
 ) -> Result<()> {
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.green} {msg}")
-            .unwrap(),
-    );
-    
     let service_name = service.as_deref().unwrap_or("all");
-    pb.set_message(format!("Restarting {}...", service_name));
-    pb.enable_steady_tick(Duration::from_millis(120));
+    let pb = spinner(&format!("Restarting {}...", service_name));
 
     let url = "/manager/restart".to_string();
 
@@ -200,7 +272,7 @@ async fn restart_service(
         if service_name == "all" {
             print_success("All services restarted successfully");
         } else {
-            print_success(&format!("Manager restart initiated (affects all services)"));
+            print_success("Manager restart initiated (affects all services)");
         }
     }
 
@@ -210,14 +282,7 @@ async fn restart_service(
 async fn get_manager_info(client: &WazuhClient, json_output: bool) -> Result<()> {
     info!("Fetching manager information");
     
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.green} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Fetching manager info...");
-    pb.enable_steady_tick(Duration::from_millis(120));
+    let pb = spinner("Fetching manager info...");
 
     let response = client.get("/manager/info").await?;
     let api_response: ApiResponse<serde_json::Value> = 
@@ -234,6 +299,583 @@ async fn get_manager_info(client: &WazuhClient, json_output: bool) -> Result<()>
     Ok(())
 }
 
+/// Lines fetched per poll with `logs --follow`
+const FOLLOW_PAGE_SIZE: u32 = 500;
+
+/// Filters of `control logs`
+struct LogFilter {
+    level: Option<String>,
+    tag: Option<String>,
+}
+
+async fn fetch_logs(
+    client: &WazuhClient,
+    filter: &LogFilter,
+    limit: u32,
+) -> Result<Vec<ManagerLog>> {
+    let mut params = vec![("limit", limit.to_string()), ("sort", "-timestamp".to_string())];
+    if let Some(level) = &filter.level {
+        params.push(("level", level.clone()));
+    }
+    if let Some(tag) = &filter.tag {
+        params.push(("tag", tag.clone()));
+    }
+    let url = format!("/manager/logs?{}", serde_urlencoded::to_string(&params)?);
+    debug!("Fetching manager logs with {}", url);
+    let response = client.get(&url).await?;
+    let api_response: ApiResponse<ListResponse<ManagerLog>> =
+        WazuhClient::parse_spooled_response(response).await?;
+    Ok(api_response.data.affected_items)
+}
+
+/// Show the last `tail` lines, then poll for new ones every `follow` until interrupted
+async fn show_logs(
+    client: &WazuhClient,
+    filter: &LogFilter,
+    tail: u32,
+    follow: Option<Duration>,
+    json_output: bool,
+) -> Result<()> {
+    let mut cursor = LogCursor::default();
+    let lines = cursor.advance(fetch_logs(client, filter, tail).await?);
+
+    let Some(interval) = follow else {
+        if json_output {
+            return print_json(&lines);
+        }
+        lines.iter().for_each(print_manager_log);
+        return Ok(());
+    };
+
+    // Followed lines are printed as they arrive, one JSON object per line
+    let print = |lines: &[ManagerLog]| -> Result<()> {
+        for line in lines {
+            if json_output {
+                println!("{}", serde_json::to_string(line)?);
+            } else {
+                print_manager_log(line);
+            }
+        }
+        Ok(())
+    };
+    print(&lines)?;
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = tokio::time::sleep(interval) => {}
+        }
+        match fetch_logs(client, filter, FOLLOW_PAGE_SIZE).await {
+            Ok(page) => print(&cursor.advance(page))?,
+            // A restarting manager answers again shortly
+            Err(e) => debug!("Polling manager logs failed: {:#}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Position reached in the log, so polls only print lines not shown yet
+#[derive(Default)]
+struct LogCursor {
+    last: Option<String>,
+    /// Lines already shown with the `last` timestamp
+    seen: HashSet<(String, String)>,
+}
+
+impl LogCursor {
+    /// Lines of a page sorted newest first that were not shown yet, oldest first
+    fn advance(&mut self, mut page: Vec<ManagerLog>) -> Vec<ManagerLog> {
+        page.reverse();
+        let fresh: Vec<ManagerLog> = page
+            .into_iter()
+            .filter(|line| match &self.last {
+                None => true,
+                Some(last) => {
+                    line.timestamp > *last
+                        || (line.timestamp == *last
+                            && !self.seen.contains(&(line.tag.clone(), line.description.clone())))
+                }
+            })
+            .collect();
+
+        for line in &fresh {
+            if self.last.as_ref() != Some(&line.timestamp) {
+                self.last = Some(line.timestamp.clone());
+                self.seen.clear();
+            }
+            self.seen.insert((line.tag.clone(), line.description.clone()));
+        }
+        fresh
+    }
+}
+
+async fn show_log_summary(
+    client: &WazuhClient,
+    tag: Option<&str>,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Fetching log summary...");
+    let response = client.get("/manager/logs/summary").await?;
+    let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+    pb.finish_and_clear();
+
+    let summaries: Vec<ManagerLogSummary> = log_summaries(&api_response.data.affected_items)
+        .into_iter()
+        .filter(|summary| tag.is_none_or(|tag| summary.tag == tag))
+        .collect();
+    if json_output {
+        print_json(&summaries)
+    } else {
+        print_manager_log_summary(&summaries);
+        Ok(())
+    }
+}
+
+/// Counts from the summary items, each a `{tag: {level: count}}` map
+fn log_summaries(items: &[serde_json::Value]) -> Vec<ManagerLogSummary> {
+    let mut summaries: Vec<ManagerLogSummary> = items
+        .iter()
+        .filter_map(|item| item.as_object())
+        .flatten()
+        .map(|(tag, counts)| ManagerLogSummary {
+            tag: tag.clone(),
+            ..serde_json::from_value(counts.clone()).unwrap_or_default()
+        })
+        .collect();
+    summaries.sort_by(|a, b| a.tag.cmp(&b.tag));
+    summaries
+}
+
+/// Where `control config get` reads the configuration from
+enum ConfigSource {
+    /// ossec.conf, optionally narrowed to a section and field
+    File {
+        section: Option<String>,
+        field: Option<String>,
+    },
+    /// Configuration a daemon has loaded
+    Active {
+        component: String,
+        configuration: String,
+    },
+}
+
+async fn show_manager_config(
+    client: &WazuhClient,
+    source: &ConfigSource,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Fetching manager configuration...");
+    let config = match source {
+        ConfigSource::File { section, field } => {
+            let mut params = Vec::new();
+            if let Some(section) = section {
+                params.push(("section", section.as_str()));
+            }
+            if let Some(field) = field {
+                params.push(("field", field.as_str()));
+            }
+            let url = format!("/manager/configuration?{}", serde_urlencoded::to_string(&params)?);
+            let response = client.get(&url).await?;
+            let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+                WazuhClient::parse_response(response).await?;
+            api_response.data.affected_items.into_iter().next().unwrap_or_default()
+        }
+        ConfigSource::Active {
+            component,
+            configuration,
+        } => {
+            let url = format!("/manager/configuration/{}/{}", component, configuration);
+            let response = client.get(&url).await?;
+            let api_response: ApiResponse<serde_json::Value> =
+                WazuhClient::parse_response(response).await?;
+            api_response.data
+        }
+    };
+    pb.finish_and_clear();
+
+    if json_output {
+        return print_json(&config);
+    }
+    if config.as_object().is_none_or(|config| config.is_empty()) {
+        print_info("The manager returned no configuration for this section");
+        return Ok(());
+    }
+    print_config_tree(&config, 0);
+    Ok(())
+}
+
+/// Settings of the manager's `<auth>` section that limit enrollment
+const ENROLLMENT_SETTINGS: &[&str] = &["disabled", "use_password", "limit_maxagents", "purge"];
+
+async fn show_limits(
+    client: &WazuhClient,
+    warn: f64,
+    critical: f64,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Fetching limits...");
+
+    let max_agents = fetch_max_agents(client).await?;
+
+    let response = client.get("/agents?limit=1&select=id").await?;
+    let agents: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+    // The manager itself is listed as agent 000
+    let agent_count = (agents.data.total_affected_items as u64).saturating_sub(1);
+
+    let response = client.get("/security/users?limit=1").await?;
+    let users: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+
+    // Reading the configuration needs extra permissions, so it is optional
+    let enrollment = match client.get("/manager/configuration?section=auth").await {
+        Ok(response) => {
+            WazuhClient::parse_response::<ApiResponse<ListResponse<serde_json::Value>>>(response)
+                .await
+                .map(|config| enrollment_settings(&config.data))
+                .unwrap_or_default()
+        }
+        Err(_) => BTreeMap::new(),
+    };
+
+    pb.finish_and_clear();
+
+    let report = LimitsReport {
+        limits: vec![
+            limit_usage("Agents", agent_count, max_agents, warn, critical),
+            limit_usage("API users", users.data.total_affected_items as u64, None, warn, critical),
+        ],
+        enrollment,
+    };
+
+    if json_output {
+        print_json(&report)?;
+    } else {
+        print_limits(&report);
+    }
+
+    Ok(())
+}
+
+/// Agent limit of the manager's license, if it has one
+pub async fn fetch_max_agents(client: &WazuhClient) -> Result<Option<u64>> {
+    let response = client.get("/manager/info").await?;
+    let info: ApiResponse<serde_json::Value> = WazuhClient::parse_response(response).await?;
+    // Newer managers wrap the info in affected_items
+    let info = info.data.pointer("/affected_items/0").unwrap_or(&info.data);
+    Ok(info.get("max_agents").and_then(|max| match max {
+        serde_json::Value::Number(n) => n.as_u64(),
+        serde_json::Value::String(s) => s.parse().ok(),
+        _ => None,
+    }))
+}
+
+fn limit_usage(name: &str, used: u64, max: Option<u64>, warn: f64, critical: f64) -> LimitUsage {
+    let percent = max.filter(|max| *max > 0).map(|max| used as f64 * 100.0 / max as f64);
+    let level = match percent {
+        None => LimitLevel::Unlimited,
+        Some(p) if p >= critical => LimitLevel::Critical,
+        Some(p) if p >= warn => LimitLevel::Warning,
+        Some(_) => LimitLevel::Ok,
+    };
+
+    LimitUsage {
+        name: name.to_string(),
+        used,
+        max,
+        percent,
+        level,
+    }
+}
+
+fn enrollment_settings(config: &ListResponse<serde_json::Value>) -> BTreeMap<String, String> {
+    let auth = config
+        .affected_items
+        .first()
+        .and_then(|item| item.get("auth"))
+        .cloned()
+        .unwrap_or_default();
+
+    ENROLLMENT_SETTINGS
+        .iter()
+        .filter_map(|key| auth.get(*key).map(|value| (key.to_string(), json_to_text(Some(value)))))
+        .collect()
+}
+
+impl MaintenanceWindow {
+    fn ssh_target(&self) -> Option<SshTarget> {
+        self.host.clone().map(|destination| SshTarget {
+            destination,
+            port: self.port,
+            identity: self.identity.clone(),
+        })
+    }
+
+    fn notifier(&self) -> Option<Notifier> {
+        (self.webhook.is_some() || self.desktop)
+            .then(|| Notifier::new(self.webhook.clone(), self.desktop))
+    }
+}
+
+async fn enter_maintenance(
+    client: &WazuhClient,
+    mut window: MaintenanceWindow,
+    drain_below: f64,
+    drain_timeout: Duration,
+    json_output: bool,
+) -> Result<()> {
+    if let Some(active) = maintenance::load_active()? {
+        return Err(anyhow!(
+            "Maintenance mode is already active since {}; run 'control maintenance exit' first",
+            active.started_at.format("%Y-%m-%d %H:%M:%S UTC")
+        ));
+    }
+
+    let pb = spinner("Entering maintenance mode...");
+    let mut progress = Progress::start("control maintenance enter", None);
+
+    pb.set_message("Sending notifications...");
+    let step = match window.notifier() {
+        Some(notifier) => {
+            let message = window
+                .reason
+                .clone()
+                .unwrap_or_else(|| "Maintenance started".to_string());
+            MaintenanceStep::timed("notify", async {
+                notifier.send("Wazuh maintenance started", &message).await;
+                Ok(String::new())
+            })
+            .await
+        }
+        None => MaintenanceStep::skipped("notify", "no --webhook or --desktop given"),
+    };
+    record_step(&mut window, &mut progress, step);
+
+    pb.set_message("Snapshotting statistics...");
+    let mut stats = None;
+    let step = MaintenanceStep::timed("snapshot stats", async {
+        stats = Some(snapshot_stats(client).await?);
+        Ok(String::new())
+    })
+    .await;
+    window.stats_before = stats;
+    record_step(&mut window, &mut progress, step);
+
+    pb.set_message("Pausing integrations...");
+    let step = match window.ssh_target() {
+        Some(target) => {
+            let pause = run_remote(&target, PAUSE_INTEGRATIONS);
+            MaintenanceStep::timed("pause integrations", pause).await
+        }
+        None => MaintenanceStep::skipped("pause integrations", "no --host given"),
+    };
+    record_step(&mut window, &mut progress, step);
+
+    pb.set_message("Draining queues...");
+    let drain = drain_queues(client, drain_below, drain_timeout);
+    let step = MaintenanceStep::timed("drain queues", drain).await;
+    record_step(&mut window, &mut progress, step);
+
+    pb.finish_and_clear();
+    progress.finish();
+    maintenance::save_active(&window)?;
+
+    if json_output {
+        print_json(&window)?;
+    } else {
+        print_maintenance_window(&window);
+        print_success("Maintenance mode entered");
+    }
+
+    Ok(())
+}
+
+async fn exit_maintenance(client: &WazuhClient, restart: bool, json_output: bool) -> Result<()> {
+    let mut window = maintenance::load_active()?
+        .ok_or_else(|| anyhow!("Maintenance mode is not active"))?;
+
+    let pb = spinner("Leaving maintenance mode...");
+    let mut progress = Progress::start("control maintenance exit", None);
+
+    let step = if restart {
+        pb.set_message("Restarting daemons...");
+        MaintenanceStep::timed("restart daemons", restart_in_order(client)).await
+    } else {
+        MaintenanceStep::skipped("restart daemons", "no --restart given")
+    };
+    record_step(&mut window, &mut progress, step);
+
+    pb.set_message("Resuming integrations...");
+    let step = match window.ssh_target() {
+        Some(target) => {
+            let resume = run_remote(&target, RESUME_INTEGRATIONS);
+            MaintenanceStep::timed("resume integrations", resume).await
+        }
+        None => MaintenanceStep::skipped("resume integrations", "no --host given on enter"),
+    };
+    record_step(&mut window, &mut progress, step);
+
+    pb.set_message("Snapshotting statistics...");
+    let mut stats = None;
+    let step = MaintenanceStep::timed("snapshot stats", async {
+        stats = Some(snapshot_stats(client).await?);
+        Ok(String::new())
+    })
+    .await;
+    window.stats_after = stats;
+    record_step(&mut window, &mut progress, step);
+
+    let ended_at = Utc::now();
+    window.ended_at = Some(ended_at);
+    let minutes = (ended_at - window.started_at).num_minutes();
+
+    pb.set_message("Sending notifications...");
+    let step = match window.notifier() {
+        Some(notifier) => {
+            let message = format!("Maintenance finished after {} minutes", minutes);
+            MaintenanceStep::timed("notify", async {
+                notifier.send("Wazuh maintenance finished", &message).await;
+                Ok(String::new())
+            })
+            .await
+        }
+        None => MaintenanceStep::skipped("notify", "no --webhook or --desktop given on enter"),
+    };
+    record_step(&mut window, &mut progress, step);
+
+    pb.finish_and_clear();
+    progress.finish();
+    maintenance::finish(&window)?;
+
+    if json_output {
+        print_json(&window)?;
+    } else {
+        print_maintenance_window(&window);
+        print_success(&format!("Maintenance mode exited after {} minutes", minutes));
+    }
+
+    Ok(())
+}
+
+/// Add a finished step to the checklist and report it as a progress event
+fn record_step(window: &mut MaintenanceWindow, progress: &mut Progress, step: MaintenanceStep) {
+    let error = (step.outcome == StepOutcome::Failed)
+        .then(|| step.detail.clone().unwrap_or_default());
+    progress.item_done(&step.name, error.as_deref());
+    window.steps.push(step);
+}
+
+fn maintenance_status(json_output: bool) -> Result<()> {
+    let window = maintenance::load_active()?;
+
+    match (window, json_output) {
+        (window, true) => print_json(&window)?,
+        (Some(window), false) => print_maintenance_window(&window),
+        (None, false) => print_info("Maintenance mode is not active"),
+    }
+
+    Ok(())
+}
+
+async fn snapshot_stats(client: &WazuhClient) -> Result<serde_json::Value> {
+    Ok(serde_json::json!({
+        "analysisd": fetch_daemon_stats(client, "analysisd").await?,
+        "remoted": fetch_daemon_stats(client, "remoted").await?,
+    }))
+}
+
+async fn run_remote(target: &SshTarget, command: &str) -> Result<String> {
+    let output = target.run(command, None).await?;
+    if !output.success {
+        return Err(anyhow!("{}", output.stderr.trim()));
+    }
+    Ok(format!("via {}", target.destination))
+}
+
+/// Wait until every analysisd/remoted queue is below `below` percent
+async fn drain_queues(client: &WazuhClient, below: f64, timeout: Duration) -> Result<String> {
+    let started = Instant::now();
+
+    loop {
+        let analysisd = fetch_daemon_stats(client, "analysisd").await?;
+        let remoted = fetch_daemon_stats(client, "remoted").await?;
+        let fullest = queue_usages(&analysisd, &remoted)
+            .into_iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        match fullest {
+            Some((queue, usage)) if usage >= below => {
+                if started.elapsed() >= timeout {
+                    return Err(anyhow!("{} still at {:.1}% after {:?}", queue, usage, timeout));
+                }
+                debug!("{} at {:.1}%, waiting to drain", queue, usage);
+            }
+            _ => return Ok(format!("queues below {}%", below)),
+        }
+
+        tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+    }
+}
+
+/// Restart cluster nodes one by one, workers first and the master last, waiting
+/// for each to answer again; standalone managers are restarted as a whole
+async fn restart_in_order(client: &WazuhClient) -> Result<String> {
+    // `/cluster/nodes` fails when clustering is disabled
+    let nodes: Vec<ClusterNode> = match client.get("/cluster/nodes").await {
+        Ok(response) => {
+            let parsed: Result<ApiResponse<ListResponse<ClusterNode>>> =
+                WazuhClient::parse_response(response).await;
+            parsed.map(|r| r.data.affected_items).unwrap_or_default()
+        }
+        Err(_) => Vec::new(),
+    };
+
+    if nodes.is_empty() {
+        let response = client.put("/manager/restart", None::<()>).await?;
+        WazuhClient::parse_response::<ApiResponse<serde_json::Value>>(response).await?;
+        wait_until_up(client, "/manager/status").await?;
+        return Ok("manager restarted".to_string());
+    }
+
+    let mut ordered = nodes;
+    ordered.sort_by_key(|node| node.node_type == "master");
+
+    for node in &ordered {
+        let response = client
+            .put(&format!("/cluster/restart?nodes_list={}", node.name), None::<()>)
+            .await?;
+        WazuhClient::parse_response::<ApiResponse<serde_json::Value>>(response).await?;
+        wait_until_up(client, &format!("/cluster/{}/status", node.name)).await?;
+    }
+
+    Ok(ordered
+        .iter()
+        .map(|node| node.name.as_str())
+        .collect::<Vec<_>>()
+        .join(" -> "))
+}
+
+/// Poll an endpoint until it answers successfully again after a restart
+async fn wait_until_up(client: &WazuhClient, endpoint: &str) -> Result<()> {
+    let started = Instant::now();
+    // Give the daemons time to actually go down before polling
+    tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+
+    loop {
+        if let Ok(response) = client.get(endpoint).await {
+            if response.status().is_success() {
+                return Ok(());
+            }
+        }
+        if started.elapsed() >= RESTART_TIMEOUT {
+            return Err(anyhow!("{} did not come back within {:?}", endpoint, RESTART_TIMEOUT));
+        }
+        tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+    }
+}
+
 fn parse_services_from_status(data: &serde_json::Value) -> Result<Vec<Service>> {
     let mut services = Vec::new();
 
@@ -315,4 +957,59 @@ fn print_manager_info(data: &serde_json::Value) -> Result<()> {
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_cursor_skips_shown_lines() {
+        let line = |timestamp: &str, description: &str| ManagerLog {
+            timestamp: timestamp.to_string(),
+            tag: "wazuh-remoted".to_string(),
+            level: "info".to_string(),
+            description: description.to_string(),
+        };
+        let mut cursor = LogCursor::default();
+        let shown = cursor.advance(vec![
+            line("2024-03-05T09:00:01Z", "b"),
+            line("2024-03-05T09:00:00Z", "a"),
+        ]);
+        assert_eq!(shown.len(), 2);
+        assert_eq!(shown[0].description, "a");
+
+        // Same second as the last shown line, plus an older one already shown
+        let shown = cursor.advance(vec![
+            line("2024-03-05T09:00:01Z", "c"),
+            line("2024-03-05T09:00:01Z", "b"),
+            line("2024-03-05T09:00:00Z", "a"),
+        ]);
+        let descriptions: Vec<&str> = shown.iter().map(|l| l.description.as_str()).collect();
+        assert_eq!(descriptions, ["c"]);
+    }
+
+    #[test]
+    fn test_log_summaries() {
+        let items = [
+            serde_json::json!({"wazuh-remoted": {"all": 12, "info": 10, "warning": 2}}),
+            serde_json::json!({"wazuh-analysisd": {"all": 3, "error": 1, "info": 2}}),
+        ];
+        let summaries = log_summaries(&items);
+        assert_eq!(summaries[0].tag, "wazuh-analysisd");
+        assert_eq!(summaries[0].error, 1);
+        assert_eq!(summaries[1].warning, 2);
+        assert_eq!(summaries[1].critical, 0);
+    }
+
+    #[test]
+    fn test_limit_usage_levels() {
+        assert_eq!(limit_usage("Agents", 50, Some(100), 80.0, 95.0).level, LimitLevel::Ok);
+        assert_eq!(limit_usage("Agents", 85, Some(100), 80.0, 95.0).level, LimitLevel::Warning);
+        assert_eq!(limit_usage("Agents", 99, Some(100), 80.0, 95.0).level, LimitLevel::Critical);
+
+        let unlimited = limit_usage("API users", 12, None, 80.0, 95.0);
+        assert_eq!(unlimited.level, LimitLevel::Unlimited);
+        assert!(unlimited.percent.is_none());
+    }
+}
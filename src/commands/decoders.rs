@@ -0,0 +1,192 @@
+use anyhow::Result;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::{
+    cli::{DecodersAction, DecodersCommand},
+    client::WazuhClient,
+    config::Config,
+    error::WazuhError,
+    models::{ApiResponse, Decoder, DecoderParams, ListResponse, RulesetFile},
+    output::{
+        print_decoders_table, print_json, print_ruleset_files, print_single_decoder, spinner,
+    },
+};
+
+pub async fn handle_decoders_command(
+    cmd: DecodersCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+
+    // Ensure we're authenticated
+    client.authenticate().await?;
+
+    match cmd.action {
+        DecodersAction::List {
+            name,
+            filename,
+            parents,
+            limit,
+        } => {
+            let params = DecoderParams {
+                limit: Some(limit),
+                decoder_names: name,
+                filename,
+            };
+            list_decoders(&client, params, parents, json_output).await?
+        }
+
+        DecodersAction::Get { name } => get_decoder(&client, &name, json_output).await?,
+
+        DecodersAction::Files { filename } => {
+            list_files(&client, filename.as_deref(), json_output).await?
+        }
+    }
+
+    Ok(())
+}
+
+async fn list_decoders(
+    client: &WazuhClient,
+    params: DecoderParams,
+    parents: bool,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Fetching decoders...");
+
+    let mut decoders = if parents {
+        let decoders = fetch_decoders(client, &parents_url(&params)).await?;
+        filter_parents(decoders, &params)
+    } else {
+        let url = format!("/decoders?{}", serde_urlencoded::to_string(&params)?);
+        fetch_decoders(client, &url).await?
+    };
+
+    pb.finish_and_clear();
+    decoders.sort_by(|a, b| a.name.cmp(&b.name).then(a.position.cmp(&b.position)));
+
+    if json_output {
+        print_json(&decoders)?;
+    } else {
+        print_decoders_table(&decoders);
+        println!("\nTotal: {} decoders", decoders.len());
+    }
+
+    Ok(())
+}
+
+/// The parents endpoint has no name or file filters; with either, every parent
+/// is fetched so the limit applies after filtering
+fn parents_url(params: &DecoderParams) -> String {
+    let filtered = params.decoder_names.is_some() || params.filename.is_some();
+    match params.limit {
+        Some(limit) if !filtered => format!("/decoders/parents?limit={}", limit),
+        _ => "/decoders/parents".to_string(),
+    }
+}
+
+/// Parent decoders matching the name and file filters, up to the limit
+fn filter_parents(decoders: Vec<Decoder>, params: &DecoderParams) -> Vec<Decoder> {
+    decoders
+        .into_iter()
+        .filter(|decoder| {
+            params.decoder_names.as_ref().is_none_or(|name| &decoder.name == name)
+                && params
+                    .filename
+                    .as_ref()
+                    .is_none_or(|file| decoder.filename.as_ref() == Some(file))
+        })
+        .take(params.limit.map_or(usize::MAX, |limit| limit as usize))
+        .collect()
+}
+
+async fn get_decoder(client: &WazuhClient, name: &str, json_output: bool) -> Result<()> {
+    let params = DecoderParams {
+        decoder_names: Some(name.to_string()),
+        ..Default::default()
+    };
+    let url = format!("/decoders?{}", serde_urlencoded::to_string(&params)?);
+    let mut decoders = fetch_decoders(client, &url).await?;
+    if decoders.is_empty() {
+        return Err(WazuhError::NotFound(format!("decoder '{}'", name)).into());
+    }
+    decoders.sort_by_key(|decoder| decoder.position);
+
+    if json_output {
+        return print_json(&decoders);
+    }
+    for (i, decoder) in decoders.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        print_single_decoder(decoder);
+    }
+    Ok(())
+}
+
+async fn fetch_decoders(client: &WazuhClient, url: &str) -> Result<Vec<Decoder>> {
+    debug!("Fetching decoders: {}", url);
+    let api_response: ApiResponse<ListResponse<Decoder>> = client.get_list(url).await?;
+    Ok(api_response.data.affected_items)
+}
+
+async fn list_files(client: &WazuhClient, filename: Option<&str>, json_output: bool) -> Result<()> {
+    let mut url = "/decoders/files".to_string();
+    if let Some(filename) = filename {
+        url.push('?');
+        url.push_str(&serde_urlencoded::to_string([("filename", filename)])?);
+    }
+    let api_response: ApiResponse<ListResponse<RulesetFile>> = client.get_list(&url).await?;
+    let files = api_response.data.affected_items;
+
+    if json_output {
+        print_json(&files)
+    } else {
+        print_ruleset_files(&files);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn decoder(name: &str, filename: &str) -> Decoder {
+        serde_json::from_value(json!({"name": name, "filename": filename})).unwrap()
+    }
+
+    #[test]
+    fn test_parents_url() {
+        let params = |name: Option<&str>| DecoderParams {
+            limit: Some(2),
+            decoder_names: name.map(String::from),
+            filename: None,
+        };
+        assert_eq!(parents_url(&params(None)), "/decoders/parents?limit=2");
+        assert_eq!(parents_url(&params(Some("sshd"))), "/decoders/parents");
+    }
+
+    #[test]
+    fn test_filter_parents() {
+        let decoders = vec![
+            decoder("apache-errorlog", "0025-apache_decoders.xml"),
+            decoder("sshd", "0310-ssh_decoders.xml"),
+            decoder("apache-accesslog", "0025-apache_decoders.xml"),
+            decoder("apache-modsec", "0025-apache_decoders.xml"),
+        ];
+        let params = DecoderParams {
+            limit: Some(2),
+            decoder_names: None,
+            filename: Some("0025-apache_decoders.xml".to_string()),
+        };
+        let names: Vec<String> = filter_parents(decoders, &params)
+            .into_iter()
+            .map(|decoder| decoder.name)
+            .collect();
+        assert_eq!(names, ["apache-errorlog", "apache-accesslog"]);
+    }
+}
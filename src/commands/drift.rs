@@ -0,0 +1,168 @@
+use anyhow::Result;
+use colored::Colorize;
+use dialoguer::Confirm;
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::{
+    cli::{BaselineAction, DriftAction, DriftCommand},
+    client::WazuhClient,
+    config::Config,
+    drift::{self, Snapshot},
+    error::WazuhError,
+    models::{ApiResponse, ListResponse},
+    output::{print_drift, print_info, print_json, print_success, spinner},
+};
+
+/// User-editable ruleset directories; the stock ruleset changes with every
+/// upgrade and is deliberately left out
+const RULESET_DIRS: &[(&str, &str)] = &[
+    ("rules", "etc/rules"),
+    ("decoders", "etc/decoders"),
+    ("lists", "etc/lists"),
+];
+
+pub async fn handle_drift_command(
+    cmd: DriftCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+
+    // Ensure we're authenticated
+    client.authenticate().await?;
+
+    match cmd.action {
+        DriftAction::Check { baseline, no_diff } => {
+            check_drift(&client, &baseline, !no_diff, json_output).await
+        }
+        DriftAction::Baseline {
+            action: BaselineAction::Update { baseline, yes },
+        } => update_baseline(&client, &baseline, yes, json_output).await,
+    }
+}
+
+async fn check_drift(
+    client: &WazuhClient,
+    baseline_path: &Path,
+    show_diff: bool,
+    json_output: bool,
+) -> Result<()> {
+    let baseline = drift::read_baseline(baseline_path)?;
+
+    let pb = spinner("Fetching current configuration...");
+    let current = fetch_snapshot(client).await?;
+    pb.finish_and_clear();
+
+    let drift = drift::compare(&baseline, &current);
+    if json_output {
+        print_json(&drift)?;
+    } else if drift.is_empty() {
+        print_success(&format!(
+            "No drift: {} files match {}",
+            current.len(),
+            baseline_path.display()
+        ));
+    } else {
+        print_drift(&drift, show_diff);
+    }
+
+    // Exit status 2 lets cron and CI jobs alert on drift, and 1 on failures
+    if !drift.is_empty() {
+        return Err(WazuhError::Drift {
+            drifted: drift.len(),
+            total: baseline.len().max(current.len()),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+async fn update_baseline(
+    client: &WazuhClient,
+    baseline_path: &Path,
+    skip_confirm: bool,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Fetching current configuration...");
+    let current = fetch_snapshot(client).await?;
+    pb.finish_and_clear();
+
+    if baseline_path.exists() && !skip_confirm {
+        let drift = drift::compare(&drift::read_baseline(baseline_path)?, &current);
+        if drift.is_empty() {
+            print_info("The baseline is already up to date");
+            return Ok(());
+        }
+        print_drift(&drift, false);
+        let confirm = Confirm::new()
+            .with_prompt(format!("Accept these changes into {}?", baseline_path.display()))
+            .default(false)
+            .interact()?;
+        if !confirm {
+            println!("Operation cancelled");
+            return Ok(());
+        }
+    }
+
+    drift::write_baseline(baseline_path, &current)?;
+
+    if json_output {
+        print_json(&current.keys().collect::<Vec<_>>())?;
+    } else {
+        println!(
+            "{} Baseline {} updated ({} files)",
+            "✓".green().bold(),
+            baseline_path.display(),
+            current.len()
+        );
+    }
+    Ok(())
+}
+
+/// Download the manager configuration, custom ruleset files and group configurations
+async fn fetch_snapshot(client: &WazuhClient) -> Result<Snapshot> {
+    let mut snapshot = Snapshot::new();
+
+    let response = client.get("/manager/configuration?raw=true").await?;
+    snapshot.insert(
+        "manager/ossec.conf".to_string(),
+        WazuhClient::response_text(response).await?,
+    );
+
+    for (kind, dir) in RULESET_DIRS {
+        let url = format!("/{}/files?relative_dirname={}", kind, dir);
+        for file in list_names(client, &url, "filename").await? {
+            let url = format!("/{}/files/{}?raw=true&relative_dirname={}", kind, file, dir);
+            let response = client.get(&url).await?;
+            snapshot.insert(
+                format!("{}/{}", kind, file),
+                WazuhClient::response_text(response).await?,
+            );
+        }
+    }
+
+    for group in list_names(client, "/groups", "name").await? {
+        let url = format!("/groups/{}/files/agent.conf?raw=true", group);
+        let response = client.get(&url).await?;
+        snapshot.insert(
+            format!("groups/{}/agent.conf", group),
+            WazuhClient::response_text(response).await?,
+        );
+    }
+
+    Ok(snapshot)
+}
+
+/// The `field` of every item of a list endpoint
+async fn list_names(client: &WazuhClient, url: &str, field: &str) -> Result<Vec<String>> {
+    let api_response: ApiResponse<ListResponse<Value>> = client.get_list(url).await?;
+    Ok(api_response
+        .data
+        .affected_items
+        .iter()
+        .filter_map(|item| item[field].as_str().map(String::from))
+        .collect())
+}
@@ -0,0 +1,176 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::{
+    cli::{EventsAction, EventsCommand},
+    client::WazuhClient,
+    config::Config,
+    error::WazuhError,
+    models::{ApiResponse, ListResponse},
+    output::{print_json, print_success, print_warning, spinner, WarningKind},
+    progress::Progress,
+};
+
+pub async fn handle_events_command(
+    cmd: EventsCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    match cmd.action {
+        EventsAction::Send {
+            file,
+            batch_size,
+            input_format,
+        } => {
+            let content = read_input(file.as_deref())?;
+            let events = parse_events(&content, &input_format)?;
+            if events.is_empty() {
+                return Err(WazuhError::InvalidInput("No events to send".to_string()).into());
+            }
+
+            let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+
+            // Ensure we're authenticated
+            client.authenticate().await?;
+
+            send_events(&client, &events, batch_size as usize, json_output).await
+        }
+    }
+}
+
+fn read_input(file: Option<&Path>) -> Result<String> {
+    match file.filter(|file| *file != Path::new("-")) {
+        Some(file) => std::fs::read_to_string(file)
+            .with_context(|| format!("Failed to read events file: {:?}", file)),
+        None => {
+            let mut content = String::new();
+            std::io::stdin()
+                .read_to_string(&mut content)
+                .context("Failed to read events from standard input")?;
+            Ok(content)
+        }
+    }
+}
+
+/// Events from a JSON array, whose objects are sent as compact JSON, or one
+/// event per non-empty line; `auto` reads lines that only look like an array,
+/// such as a bracketed log line, as lines
+fn parse_events(content: &str, format: &str) -> Result<Vec<String>, WazuhError> {
+    let lines = || {
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(String::from)
+            .collect()
+    };
+    let array = || -> Result<Vec<String>, WazuhError> {
+        let events: Vec<Value> = serde_json::from_str(content).map_err(|e| {
+            WazuhError::InvalidInput(format!("Invalid JSON array of events: {}", e))
+        })?;
+        Ok(events
+            .into_iter()
+            .map(|event| match event {
+                Value::String(event) => event,
+                event => event.to_string(),
+            })
+            .collect())
+    };
+
+    match format {
+        "json" => array(),
+        "lines" => Ok(lines()),
+        _ if content.trim_start().starts_with('[') => Ok(array().unwrap_or_else(|e| {
+            debug!("Reading events as lines: {}", e);
+            lines()
+        })),
+        _ => Ok(lines()),
+    }
+}
+
+async fn send_events(
+    client: &WazuhClient,
+    events: &[String],
+    batch_size: usize,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Sending events...");
+    let mut progress = Progress::start("events send", Some(events.len() as u64));
+    let mut sent = 0;
+    let mut failed = 0;
+    let mut failures = Vec::new();
+
+    for (index, batch) in events.chunks(batch_size).enumerate() {
+        let first = index * batch_size + 1;
+        let label = format!("events {}-{}", first, first + batch.len() - 1);
+        pb.set_message(format!("Sending {} of {}...", label, events.len()));
+
+        let response = client
+            .post("/events", Some(json!({ "events": batch })))
+            .await
+            .with_context(|| format!("Failed to send {} ({} sent before)", label, sent))?;
+        let api_response: ApiResponse<ListResponse<Value>> =
+            WazuhClient::parse_response(response).await?;
+
+        let data = api_response.data;
+        sent += data.total_affected_items as usize;
+        failed += data.total_failed_items as usize;
+        let error = match data.total_failed_items {
+            0 => None,
+            _ => Some(serde_json::to_string(&data.failed_items)?),
+        };
+        progress.item_done(&label, error.as_deref());
+        failures.extend(data.failed_items);
+    }
+    pb.finish_and_clear();
+    progress.finish();
+
+    if json_output {
+        return print_json(&json!({
+            "sent": sent,
+            "failed": failed,
+            "failed_items": failures,
+        }));
+    }
+    print_success(&format!("Sent {} of {} events", sent, events.len()));
+    if !failures.is_empty() {
+        print_warning(
+            WarningKind::PartialFailure,
+            &format!(
+                "{} events were rejected: {}",
+                failed,
+                serde_json::to_string(&failures)?
+            ),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_events() {
+        let events = parse_events("Mar  5 09:04:05 web sshd[42]: Failed password\n\nsecond\n", "auto");
+        assert_eq!(events.unwrap().len(), 2);
+
+        let events =
+            parse_events(r#"["raw line", {"integration": "test", "n": 1}]"#, "auto").unwrap();
+        assert_eq!(events[0], "raw line");
+        assert_eq!(events[1], r#"{"integration":"test","n":1}"#);
+
+        assert!(parse_events("[not json", "json").is_err());
+    }
+
+    #[test]
+    fn test_parse_bracketed_lines() {
+        let log = "[2024-03-05 09:04:05] sshd: Failed password\n[2024-03-05 09:04:06] sshd: retry\n";
+        assert_eq!(parse_events(log, "auto").unwrap().len(), 2);
+        assert_eq!(parse_events("[1, 2]\n", "lines").unwrap(), ["[1, 2]"]);
+    }
+}
@@ -0,0 +1,282 @@
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::{RwLock, Semaphore};
+use tokio::task::JoinSet;
+use tracing::debug;
+
+use crate::{
+    cli::{ExportAction, ExportCommand},
+    client::WazuhClient,
+    config::Config,
+    export::{self, ExportData},
+    commands::vuln::severity_counts_from_api,
+    indexer::{is_unavailable, IndexerClient, VULNERABILITIES_INDEX},
+    models::{AgentListResponse, ApiResponse, ListResponse},
+    output::{print_json, print_warning, spinner, WarningKind},
+    progress::Progress,
+    storage::Destination,
+    utils::json_to_text,
+};
+
+/// Upper bound on agents in the vulnerability aggregation
+const MAX_AGENTS: u32 = 100000;
+
+pub async fn handle_export_command(
+    cmd: ExportCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+
+    // Ensure we're authenticated
+    client.authenticate().await?;
+
+    match cmd.action {
+        ExportAction::Sqlite { out, concurrency } => {
+            export_sqlite(&client, config, &out, concurrency, json_output).await
+        }
+    }
+}
+
+async fn export_sqlite(
+    client: &WazuhClient,
+    config: &Config,
+    out: &Destination,
+    concurrency: usize,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Fetching agents and groups...");
+    let url = "/agents?select=id,name,ip,status,os,version,group,node_name,lastKeepAlive,dateAdd";
+    let agents: ApiResponse<AgentListResponse> = client.get_list(url).await?;
+    let groups: ApiResponse<ListResponse<Value>> = client.get_list("/groups").await?;
+    pb.finish_and_clear();
+
+    let mut data = ExportData {
+        source: config.api_url(),
+        agents: agents.data.affected_items,
+        groups: groups
+            .data
+            .affected_items
+            .iter()
+            .filter_map(|g| Some((g["name"].as_str()?.to_string(), g["count"].as_u64()?)))
+            .collect(),
+        ..Default::default()
+    };
+
+    data.sca = fetch_sca(client, &data, concurrency).await?;
+
+    let pb = spinner("Counting vulnerabilities...");
+    let counts = match vulnerability_counts(config).await {
+        Err(e) if is_unavailable(&e) => {
+            print_warning(
+                WarningKind::Fallback,
+                &format!(
+                    "Indexer unreachable ({}); counting vulnerabilities with the manager API",
+                    e.root_cause()
+                ),
+            );
+            vulnerability_counts_from_api(client, &data, concurrency).await
+        }
+        counts => counts,
+    };
+    match counts {
+        Ok(mut counts) => {
+            // States of removed agents linger in the index until it is cleaned up
+            counts.retain(|(agent_id, _, _)| data.agents.iter().any(|a| &a.id == agent_id));
+            data.vulnerabilities = counts;
+        }
+        // Older managers and API-only setups have no vulnerability states index
+        Err(e) => print_warning(
+            WarningKind::PartialFailure,
+            &format!("Vulnerability counts not exported: {:#}", e),
+        ),
+    }
+    pb.finish_and_clear();
+
+    let summary = match out {
+        Destination::File(path) => export::write_sqlite(path, &data)?,
+        _ => {
+            // SQLite needs a file to write to; it is uploaded once complete
+            let dir = tempfile::tempdir()?;
+            let path = dir.path().join("wazuh.db");
+            let mut summary = export::write_sqlite(&path, &data)?;
+            let pb = spinner(&format!("Uploading to {}...", out));
+            out.write(std::fs::read(&path)?, "application/vnd.sqlite3", &config.storage)
+                .await?;
+            pb.finish_and_clear();
+            summary.path = out.to_string();
+            summary
+        }
+    };
+
+    if json_output {
+        return print_json(&summary);
+    }
+    println!(
+        "{} Exported {} agents, {} groups, {} memberships, {} SCA policy results and {} \
+         vulnerability counts to {}",
+        "✓".green().bold(),
+        summary.agents,
+        summary.groups,
+        summary.memberships,
+        summary.sca_policies,
+        summary.vulnerability_counts,
+        summary.path
+    );
+    Ok(())
+}
+
+/// SCA policy results of every agent that has them
+async fn fetch_sca(
+    client: &WazuhClient,
+    data: &ExportData,
+    concurrency: usize,
+) -> Result<Vec<(String, Vec<Value>)>> {
+    let agent_ids: Vec<String> = data
+        .agents
+        .iter()
+        .filter(|agent| agent.id != "000")
+        .map(|agent| agent.id.clone())
+        .collect();
+
+    let pb = spinner("Fetching SCA results...");
+    let mut progress = Progress::start("export sqlite", Some(agent_ids.len() as u64));
+    let permits = Arc::new(Semaphore::new(concurrency));
+    let mut tasks = JoinSet::new();
+    for agent_id in agent_ids {
+        let client = client.clone();
+        let permits = permits.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let result = async {
+                let response = client.get(&format!("/sca/{}", agent_id)).await?;
+                let api_response: ApiResponse<ListResponse<Value>> =
+                    WazuhClient::parse_response(response).await?;
+                anyhow::Ok(api_response.data.affected_items)
+            }
+            .await;
+            (agent_id, result)
+        });
+    }
+
+    let mut sca = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined? {
+            (agent_id, Ok(policies)) => {
+                progress.item_done(&agent_id, None);
+                sca.push((agent_id, policies));
+            }
+            // Agents that never ran a scan have no SCA database
+            (agent_id, Err(e)) => {
+                debug!("Skipping SCA of agent {}: {:#}", agent_id, e);
+                progress.item_done(&agent_id, Some(&format!("{:#}", e)));
+            }
+        }
+    }
+    progress.finish();
+    pb.finish_and_clear();
+
+    sca.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(sca)
+}
+
+/// Vulnerabilities per agent and severity from the indexer
+async fn vulnerability_counts(config: &Config) -> Result<Vec<(String, String, u64)>> {
+    let indexer = IndexerClient::new(config)?;
+    let body = json!({
+        "size": 0,
+        "aggs": {
+            "agents": {
+                "terms": {"field": "agent.id", "size": MAX_AGENTS},
+                "aggs": {"severity": {"terms": {"field": "vulnerability.severity"}}}
+            }
+        }
+    });
+    let response = indexer.search(VULNERABILITIES_INDEX, &body).await?;
+    Ok(parse_vulnerability_counts(&response))
+}
+
+/// Vulnerabilities per agent and severity from the manager API, for managers
+/// that still serve them or while the indexer is down
+async fn vulnerability_counts_from_api(
+    client: &WazuhClient,
+    data: &ExportData,
+    concurrency: usize,
+) -> Result<Vec<(String, String, u64)>> {
+    let permits = Arc::new(Semaphore::new(concurrency));
+    let mut tasks = JoinSet::new();
+    for agent in data.agents.iter().filter(|agent| agent.id != "000") {
+        let client = client.clone();
+        let permits = permits.clone();
+        let agent_id = agent.id.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let result = severity_counts_from_api(&client, &agent_id).await;
+            (agent_id, result)
+        });
+    }
+
+    let mut counts = Vec::new();
+    let mut failed = 0;
+    while let Some(joined) = tasks.join_next().await {
+        match joined? {
+            (agent_id, Ok(severities)) => counts.extend(
+                severities
+                    .into_iter()
+                    .map(|(severity, count)| (agent_id.clone(), severity, count)),
+            ),
+            (agent_id, Err(e)) => {
+                debug!("No vulnerability counts for agent {}: {:#}", agent_id, e);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 && counts.is_empty() {
+        return Err(anyhow!("the manager API has no vulnerability data for {} agents", failed));
+    }
+    counts.sort();
+    Ok(counts)
+}
+
+fn parse_vulnerability_counts(response: &Value) -> Vec<(String, String, u64)> {
+    let buckets = |value: &Value| value["buckets"].as_array().cloned().unwrap_or_default();
+
+    let mut counts = Vec::new();
+    for agent in buckets(&response["aggregations"]["agents"]) {
+        for severity in buckets(&agent["severity"]) {
+            counts.push((
+                json_to_text(Some(&agent["key"])),
+                json_to_text(Some(&severity["key"])),
+                severity["doc_count"].as_u64().unwrap_or(0),
+            ));
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vulnerability_counts() {
+        let response = json!({"aggregations": {"agents": {"buckets": [{
+            "key": "001",
+            "doc_count": 5,
+            "severity": {"buckets": [
+                {"key": "High", "doc_count": 4},
+                {"key": "Critical", "doc_count": 1}
+            ]}
+        }]}}});
+        assert_eq!(
+            parse_vulnerability_counts(&response),
+            [
+                ("001".to_string(), "High".to_string(), 4),
+                ("001".to_string(), "Critical".to_string(), 1)
+            ]
+        );
+        assert!(parse_vulnerability_counts(&json!({})).is_empty());
+    }
+}
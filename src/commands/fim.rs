@@ -0,0 +1,420 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use dialoguer::Confirm;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::{
+    cli::{FimAction, FimCommand},
+    client::WazuhClient,
+    commands::{
+        agent::{group_agent_ids, resolve_agent_id},
+        group::bulk_failures,
+    },
+    config::Config,
+    models::{ApiResponse, FimEntry, FimParams, LastScan, ListResponse},
+    output::{
+        print_fim_entries, print_json, print_success, print_warning, spinner, WarningKind,
+    },
+    utils::{glob_match, parse_duration},
+};
+
+/// Agent IDs per request when starting scans on a group
+const SCAN_BATCH_SIZE: usize = 500;
+
+pub async fn handle_fim_command(cmd: FimCommand, config: &Config, json_output: bool) -> Result<()> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+
+    // Ensure we're authenticated
+    client.authenticate().await?;
+
+    match cmd.action {
+        FimAction::List {
+            agent,
+            file,
+            hash,
+            modified_since,
+            entry_type,
+            limit,
+        } => {
+            let agent = resolve_agent_id(&client, &agent).await?;
+            let modified_since = modified_since
+                .map(|span| parse_duration(&span).map(|span| Utc::now() - span))
+                .transpose()?;
+            // A glob is matched here; a plain path is filtered by the API
+            let (file, pattern) = match file {
+                Some(file) if file.contains(['*', '?']) => (None, Some(file)),
+                file => (file, None),
+            };
+            let filter = FimFilter {
+                pattern,
+                modified_since,
+            };
+            let params = FimParams {
+                // Filtered here first, so the limit applies to what is left
+                limit: (!filter.is_local()).then_some(limit),
+                // Newest first, so the limit keeps the most recent changes
+                sort: Some("-mtime".to_string()),
+                file,
+                hash,
+                entry_type,
+            };
+            list_entries(&client, &agent, &params, &filter, limit, json_output).await?
+        }
+
+        FimAction::LastScan { agent } => {
+            let agent = resolve_agent_id(&client, &agent).await?;
+            let scan = last_scan(&client, "syscheck", &agent).await?;
+            if json_output {
+                print_json(&scan)?;
+            } else {
+                print_last_scan("FIM", &agent, &scan);
+            }
+        }
+
+        FimAction::Run { agent, group, yes, .. } => {
+            let agent = match agent {
+                Some(agent) => Some(resolve_agent_id(&client, &agent).await?),
+                None => None,
+            };
+            let target = ScanTarget::from_args(agent, group);
+            run_scan(&client, "syscheck", "FIM", &target, yes, json_output).await?
+        }
+
+        FimAction::Clear { agent, yes } => {
+            let agent = resolve_agent_id(&client, &agent).await?;
+            let url = format!("/syscheck/{}", agent);
+            clear_results(&client, &url, "FIM", &agent, yes, json_output).await?
+        }
+    }
+
+    Ok(())
+}
+
+/// Filters applied to the entries the API returns
+struct FimFilter {
+    pattern: Option<String>,
+    modified_since: Option<DateTime<Utc>>,
+}
+
+impl FimFilter {
+    /// Whether entries are filtered here rather than by the API
+    fn is_local(&self) -> bool {
+        self.pattern.is_some() || self.modified_since.is_some()
+    }
+
+    fn matches(&self, entry: &FimEntry) -> bool {
+        if let Some(pattern) = &self.pattern {
+            if !glob_match(pattern, &entry.file) {
+                return false;
+            }
+        }
+        if let Some(since) = self.modified_since {
+            let modified = entry
+                .mtime
+                .as_deref()
+                .and_then(|mtime| DateTime::parse_from_rfc3339(mtime).ok());
+            if modified.is_none_or(|modified| modified < since) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+async fn list_entries(
+    client: &WazuhClient,
+    agent: &str,
+    params: &FimParams,
+    filter: &FimFilter,
+    limit: u32,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Fetching FIM entries...");
+    let url = format!("/syscheck/{}?{}", agent, serde_urlencoded::to_string(params)?);
+    debug!("Fetching FIM entries with {}", url);
+    let api_response: ApiResponse<ListResponse<FimEntry>> = client.get_list(&url).await?;
+    pb.finish_and_clear();
+
+    let entries: Vec<FimEntry> = api_response
+        .data
+        .affected_items
+        .into_iter()
+        .filter(|entry| filter.matches(entry))
+        .take(limit as usize)
+        .collect();
+
+    if json_output {
+        print_json(&entries)?;
+    } else {
+        print_fim_entries(&entries);
+        println!("\nTotal: {} entries", entries.len());
+    }
+
+    Ok(())
+}
+
+/// Agents a scan is started on
+pub enum ScanTarget {
+    Agent(String),
+    Group(String),
+    All,
+}
+
+impl ScanTarget {
+    /// Target from the `[agent] --group --all` arguments; clap requires one of
+    /// them, so neither an agent nor a group means `--all`
+    pub fn from_args(agent: Option<String>, group: Option<String>) -> Self {
+        match (agent, group) {
+            (Some(agent), _) => ScanTarget::Agent(agent),
+            (None, Some(group)) => ScanTarget::Group(group),
+            (None, None) => ScanTarget::All,
+        }
+    }
+}
+
+/// Start a syscheck or rootcheck scan, confirming first for groups and the whole fleet
+pub async fn run_scan(
+    client: &WazuhClient,
+    endpoint: &str,
+    label: &str,
+    target: &ScanTarget,
+    skip_confirm: bool,
+    json_output: bool,
+) -> Result<()> {
+    let batches: Vec<Option<Vec<String>>> = match target {
+        ScanTarget::Agent(agent) => vec![Some(vec![agent.clone()])],
+        ScanTarget::Group(group) => {
+            let pb = spinner("Resolving agents...");
+            let ids = group_agent_ids(client, group).await?;
+            pb.finish_and_clear();
+            if ids.is_empty() {
+                return Err(anyhow!("Group '{}' has no agents", group));
+            }
+            ids.chunks(SCAN_BATCH_SIZE).map(|ids| Some(ids.to_vec())).collect()
+        }
+        // Without an agents_list the API targets every agent
+        ScanTarget::All => vec![None],
+    };
+
+    if !skip_confirm && !matches!(target, ScanTarget::Agent(_)) {
+        let prompt = match target {
+            ScanTarget::Group(group) => format!(
+                "Start a {} scan on {} agents in group '{}'?",
+                label,
+                batches.iter().flatten().map(Vec::len).sum::<usize>(),
+                group
+            ),
+            _ => format!("Start a {} scan on every agent?", label),
+        };
+        let confirm = Confirm::new().with_prompt(prompt).default(false).interact()?;
+        if !confirm {
+            println!("Operation cancelled");
+            return Ok(());
+        }
+    }
+
+    let pb = spinner(&format!("Starting {} scan...", label));
+    let mut affected = Vec::new();
+    let mut failures = HashMap::new();
+    for batch in batches {
+        let url = match &batch {
+            Some(ids) => format!("/{}?agents_list={}", endpoint, ids.join(",")),
+            None => format!("/{}", endpoint),
+        };
+        let result = async {
+            let response = client.put(&url, None::<()>).await?;
+            WazuhClient::parse_response::<ApiResponse<ListResponse<serde_json::Value>>>(response)
+                .await
+        }
+        .await;
+        match (result, batch) {
+            (Ok(api_response), _) => {
+                affected.extend(api_response.data.affected_items);
+                failures.extend(bulk_failures(&api_response.data.failed_items));
+            }
+            // The other batches still run; every agent of this one failed
+            (Err(e), Some(ids)) => {
+                let error = format!("{:#}", e);
+                failures.extend(ids.into_iter().map(|id| (id, error.clone())));
+            }
+            (Err(e), None) => return Err(e),
+        }
+    }
+    pb.finish_and_clear();
+
+    if json_output {
+        print_json(&serde_json::json!({
+            "affected_items": affected,
+            "failed_items": failures,
+        }))?;
+    }
+    if let ScanTarget::Agent(agent) = target {
+        if let Some(error) = failures.get(agent) {
+            return Err(anyhow!("Failed to start {} scan on agent '{}': {}", label, agent, error));
+        }
+    }
+    if json_output {
+        return Ok(());
+    }
+    print_success(&format!("Started a {} scan on {} agents", label, affected.len()));
+    if !failures.is_empty() {
+        let mut failed: Vec<String> =
+            failures.iter().map(|(id, error)| format!("{} ({})", id, error)).collect();
+        failed.sort();
+        print_warning(
+            WarningKind::PartialFailure,
+            &format!("{} agents failed: {}", failed.len(), failed.join(", ")),
+        );
+    }
+
+    Ok(())
+}
+
+pub async fn last_scan(client: &WazuhClient, endpoint: &str, agent: &str) -> Result<LastScan> {
+    let url = format!("/{}/{}/last_scan", endpoint, agent);
+    let response = client.get(&url).await?;
+    let api_response: ApiResponse<ListResponse<LastScan>> =
+        WazuhClient::parse_response(response).await?;
+    api_response
+        .data
+        .affected_items
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("No scan information for agent '{}'", agent))
+}
+
+pub fn print_last_scan(label: &str, agent: &str, scan: &LastScan) {
+    let (Some(start), end) = (&scan.start, &scan.end) else {
+        println!("No {} scan has run on agent '{}'", label, agent);
+        return;
+    };
+    // A scan in progress has a start later than the end of the previous one
+    match end.as_deref().filter(|end| *end >= start.as_str()) {
+        Some(end) => println!(
+            "{} Last {} scan of agent '{}': {} to {}",
+            "✓".green().bold(),
+            label,
+            agent,
+            start,
+            end
+        ),
+        None => println!(
+            "{} {} scan of agent '{}' in progress since {}",
+            "…".cyan().bold(),
+            label,
+            agent,
+            start
+        ),
+    }
+}
+
+/// Delete the stored scan results of an agent with the given DELETE request
+pub async fn clear_results(
+    client: &WazuhClient,
+    url: &str,
+    label: &str,
+    agent: &str,
+    skip_confirm: bool,
+    json_output: bool,
+) -> Result<()> {
+    if !skip_confirm {
+        let confirm = Confirm::new()
+            .with_prompt(format!("Clear the {} results of agent '{}'?", label, agent))
+            .default(false)
+            .interact()?;
+
+        if !confirm {
+            println!("Operation cancelled");
+            return Ok(());
+        }
+    }
+
+    let response = client.delete(url).await?;
+    let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+
+    if let Some(error) = bulk_failures(&api_response.data.failed_items).remove(agent) {
+        return Err(anyhow!("Failed to clear {} results of agent '{}': {}", label, agent, error));
+    }
+    if json_output {
+        return print_json(&api_response);
+    }
+    print_success(&format!("Cleared the {} results of agent '{}'", label, agent));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_fim_filter() {
+        let entry: FimEntry = serde_json::from_value(json!({
+            "file": "/etc/ssh/sshd_config",
+            "type": "file",
+            "mtime": "2024-03-05T09:04:05Z",
+        }))
+        .unwrap();
+        let since = |ts: &str| Some(DateTime::parse_from_rfc3339(ts).unwrap().to_utc());
+
+        let filter = FimFilter {
+            pattern: Some("/etc/ssh/*".to_string()),
+            modified_since: since("2024-03-01T00:00:00Z"),
+        };
+        assert!(filter.matches(&entry));
+
+        let filter = FimFilter {
+            pattern: None,
+            modified_since: since("2024-03-06T00:00:00Z"),
+        };
+        assert!(!filter.matches(&entry));
+
+        let filter = FimFilter {
+            pattern: Some("/var/*".to_string()),
+            modified_since: None,
+        };
+        assert!(!filter.matches(&entry));
+    }
+
+    #[tokio::test]
+    async fn test_run_scan_fails_for_a_failed_agent() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/syscheck"))
+            .and(query_param("agents_list", "001"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "error": 1,
+                "data": {
+                    "affected_items": [],
+                    "failed_items": [{
+                        "error": {"code": 1707, "message": "Agent is not active"},
+                        "id": ["001"],
+                    }],
+                    "total_affected_items": 0,
+                    "total_failed_items": 1,
+                },
+            })))
+            .mount(&server)
+            .await;
+
+        let address = server.address();
+        let mut config = Config::default();
+        config.api.protocol = "http".to_string();
+        config.api.host = address.ip().to_string();
+        config.api.port = address.port();
+        config.auth.token = Some("token".to_string());
+        let client = WazuhClient::new(Arc::new(RwLock::new(config))).await.unwrap();
+
+        let target = ScanTarget::Agent("001".to_string());
+        let result = run_scan(&client, "syscheck", "FIM", &target, true, true).await;
+        assert!(result.unwrap_err().to_string().contains("Agent is not active"));
+    }
+}
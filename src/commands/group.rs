@@ -0,0 +1,568 @@
+use anyhow::{anyhow, Context, Result};
+use colored::Colorize;
+use dialoguer::Confirm;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::{
+    cli::{AgentGroupAction, GroupCommand},
+    client::WazuhClient,
+    commands::agent::{resolve_agent_id, select_agents},
+    config::Config,
+    diff,
+    models::{
+        Agent, AgentGroup, AgentListResponse, ApiResponse, AssignmentStatus, GroupAssignment,
+        ListResponse,
+    },
+    output::{
+        print_agent_groups, print_diff, print_group_assignments, print_info, print_json,
+        print_success, spinner,
+    },
+    progress::Progress,
+    selector::Selector,
+    utils::{glob_match, json_to_text, validate_group_name},
+    xml,
+};
+
+/// Manager's own agent entry, which cannot be assigned to groups
+const MANAGER_AGENT_ID: &str = "000";
+
+/// `group` is `agent group` under a shorter name
+pub async fn handle_group_command(
+    cmd: GroupCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+
+    // Ensure we're authenticated
+    client.authenticate().await?;
+
+    handle_agent_group(&client, cmd.action, json_output).await
+}
+
+/// `agent group` subcommands, run with the client of the agent or group command
+pub async fn handle_agent_group(
+    client: &WazuhClient,
+    action: AgentGroupAction,
+    json_output: bool,
+) -> Result<()> {
+    match action {
+        AgentGroupAction::List => list_groups(client, json_output).await,
+        AgentGroupAction::Create { name } => create_group(client, &name, json_output).await,
+        AgentGroupAction::Delete { name, yes } => {
+            delete_group(client, &name, yes, json_output).await
+        }
+        AgentGroupAction::Assign {
+            agent: None,
+            group,
+            pattern,
+            os,
+            select,
+            dry_run,
+            batch_size,
+            yes,
+        } => {
+            let filter = AgentFilter {
+                pattern,
+                os,
+                selector: select.as_deref().map(Selector::parse).transpose()?,
+            };
+            let options = AssignOptions {
+                dry_run,
+                batch_size,
+                skip_confirm: yes,
+            };
+            assign_group(client, &group, &filter, &options, json_output).await
+        }
+        AgentGroupAction::Assign {
+            agent: Some(agent),
+            group,
+            ..
+        } => {
+            validate_group_name(&group)?;
+            let agent = resolve_agent_id(client, &agent).await?;
+            let url = format!("/agents/{}/group/{}", agent, group);
+            let response = client.put(&url, None::<()>).await?;
+            let api_response: ApiResponse<serde_json::Value> =
+                WazuhClient::parse_response(response).await?;
+            if json_output {
+                return print_json(&api_response);
+            }
+            print_success(&format!("Agent '{}' added to group '{}'", agent, group));
+            Ok(())
+        }
+        AgentGroupAction::Remove { agent, group, yes } => {
+            validate_group_name(&group)?;
+            let agent = resolve_agent_id(client, &agent).await?;
+            remove_from_group(client, &agent, &group, yes, json_output).await
+        }
+        AgentGroupAction::GetConfig { group } => {
+            get_group_config(client, &group, json_output).await
+        }
+        AgentGroupAction::SetConfig {
+            group,
+            file,
+            dry_run,
+            yes,
+        } => {
+            let options = SetConfigOptions {
+                dry_run,
+                skip_confirm: yes,
+            };
+            set_group_config(client, &group, &file, &options, json_output).await
+        }
+    }
+}
+
+async fn list_groups(client: &WazuhClient, json_output: bool) -> Result<()> {
+    let pb = spinner("Fetching groups...");
+    let api_response: ApiResponse<ListResponse<AgentGroup>> = client.get_list("/groups").await?;
+    pb.finish_and_clear();
+
+    if json_output {
+        print_json(&api_response.data.affected_items)
+    } else {
+        print_agent_groups(&api_response.data.affected_items);
+        Ok(())
+    }
+}
+
+async fn create_group(client: &WazuhClient, name: &str, json_output: bool) -> Result<()> {
+    validate_group_name(name)?;
+    let response = client
+        .post("/groups", Some(serde_json::json!({ "group_id": name })))
+        .await?;
+    let api_response: ApiResponse<serde_json::Value> =
+        WazuhClient::parse_response(response).await?;
+
+    if json_output {
+        return print_json(&api_response);
+    }
+    print_success(&format!("Group '{}' created", name));
+    Ok(())
+}
+
+async fn delete_group(
+    client: &WazuhClient,
+    name: &str,
+    skip_confirm: bool,
+    json_output: bool,
+) -> Result<()> {
+    validate_group_name(name)?;
+    if !skip_confirm {
+        let confirm = Confirm::new()
+            .with_prompt(format!(
+                "Delete group '{}'? Its agents are removed from the group",
+                name
+            ))
+            .default(false)
+            .interact()?;
+
+        if !confirm {
+            println!("Operation cancelled");
+            return Ok(());
+        }
+    }
+
+    let url = format!("/groups?{}", serde_urlencoded::to_string([("groups_list", name)])?);
+    let response = client.delete(&url).await?;
+    let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+
+    // The endpoint reports a missing or protected group as a failed item
+    if let Some(error) = bulk_failures(&api_response.data.failed_items).remove(name) {
+        return Err(anyhow!("Failed to delete group '{}': {}", name, error));
+    }
+    if json_output {
+        return print_json(&api_response);
+    }
+    print_success(&format!("Group '{}' deleted", name));
+    Ok(())
+}
+
+async fn remove_from_group(
+    client: &WazuhClient,
+    agent: &str,
+    group: &str,
+    skip_confirm: bool,
+    json_output: bool,
+) -> Result<()> {
+    if !skip_confirm {
+        let confirm = Confirm::new()
+            .with_prompt(format!("Remove agent '{}' from group '{}'?", agent, group))
+            .default(false)
+            .interact()?;
+
+        if !confirm {
+            println!("Operation cancelled");
+            return Ok(());
+        }
+    }
+
+    let url = format!("/agents/{}/group/{}", agent, group);
+    let response = client.delete(&url).await?;
+    let api_response: ApiResponse<serde_json::Value> =
+        WazuhClient::parse_response(response).await?;
+
+    if json_output {
+        return print_json(&api_response);
+    }
+    print_success(&format!("Agent '{}' removed from group '{}'", agent, group));
+    Ok(())
+}
+
+/// Shared agent.conf of a group as text
+async fn fetch_group_config(client: &WazuhClient, group: &str) -> Result<String> {
+    let url = format!("/groups/{}/files/agent.conf?raw=true", group);
+    let response = client.get(&url).await?;
+    WazuhClient::response_text(response).await
+}
+
+/// Print the agent.conf as XML, or as the API's JSON conversion with `--json`
+async fn get_group_config(client: &WazuhClient, group: &str, json_output: bool) -> Result<()> {
+    validate_group_name(group)?;
+    if json_output {
+        let url = format!("/groups/{}/configuration", group);
+        let response = client.get(&url).await?;
+        let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+            WazuhClient::parse_response(response).await?;
+        return print_json(&api_response.data.affected_items);
+    }
+
+    print!("{}", fetch_group_config(client, group).await?);
+    Ok(())
+}
+
+struct SetConfigOptions {
+    dry_run: bool,
+    skip_confirm: bool,
+}
+
+async fn set_group_config(
+    client: &WazuhClient,
+    group: &str,
+    file: &Path,
+    options: &SetConfigOptions,
+    json_output: bool,
+) -> Result<()> {
+    validate_group_name(group)?;
+    let content = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read agent.conf: {:?}", file))?;
+    xml::check_well_formed(&content)?;
+
+    let pb = spinner("Fetching current agent.conf...");
+    let current = fetch_group_config(client, group).await?;
+    pb.finish_and_clear();
+
+    if current == content {
+        print_info(&format!("agent.conf of group '{}' is already up to date", group));
+        return Ok(());
+    }
+    if !json_output {
+        print_diff(&diff::unified(
+            &current,
+            &content,
+            &format!("{}/agent.conf", group),
+            &file.display().to_string(),
+        ));
+        println!();
+    }
+    if options.dry_run {
+        print_info("Dry run; agent.conf was not changed");
+        return Ok(());
+    }
+
+    if !options.skip_confirm {
+        let confirm = Confirm::new()
+            .with_prompt(format!("Push this agent.conf to every agent in group '{}'?", group))
+            .default(false)
+            .interact()?;
+
+        if !confirm {
+            println!("Operation cancelled");
+            return Ok(());
+        }
+    }
+
+    let pb = spinner("Uploading agent.conf...");
+    let url = format!("/groups/{}/configuration", group);
+    let response = client
+        .put_raw(&url, "application/xml", content.into_bytes())
+        .await?;
+    let api_response: ApiResponse<serde_json::Value> =
+        WazuhClient::parse_response(response).await?;
+    pb.finish_and_clear();
+
+    if json_output {
+        return print_json(&api_response);
+    }
+    print_success(&format!("Updated agent.conf of group '{}'", group));
+    Ok(())
+}
+
+/// Name, OS and selector filters resolving to the agents to assign
+struct AgentFilter {
+    pattern: Option<String>,
+    os: Option<String>,
+    /// Applied while fetching, see `select_agents`
+    selector: Option<Selector>,
+}
+
+impl fmt::Display for AgentFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = [
+            self.pattern.clone(),
+            self.selector.as_ref().map(Selector::to_string),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        write!(f, "{}", parts.join(" and "))
+    }
+}
+
+impl AgentFilter {
+    fn matches(&self, agent: &Agent) -> bool {
+        let name_matches = self
+            .pattern
+            .as_ref()
+            .is_none_or(|pattern| glob_match(pattern, &agent.name));
+        if agent.id == MANAGER_AGENT_ID || !name_matches {
+            return false;
+        }
+        match &self.os {
+            Some(os) => agent.os.as_ref().is_some_and(|agent_os| {
+                let os = os.to_lowercase();
+                [&agent_os.platform, &agent_os.name]
+                    .into_iter()
+                    .flatten()
+                    .any(|value| value.to_lowercase().contains(&os))
+            }),
+            None => true,
+        }
+    }
+}
+
+struct AssignOptions {
+    dry_run: bool,
+    batch_size: usize,
+    skip_confirm: bool,
+}
+
+async fn assign_group(
+    client: &WazuhClient,
+    group: &str,
+    filter: &AgentFilter,
+    options: &AssignOptions,
+    json_output: bool,
+) -> Result<()> {
+    validate_group_name(group)?;
+    let pb = spinner("Resolving agents...");
+    let agents = match &filter.selector {
+        Some(selector) => select_agents(client, selector).await?,
+        None => {
+            let url = "/agents?select=id,name,group,os.platform,os.name";
+            let api_response: ApiResponse<AgentListResponse> = client.get_list(url).await?;
+            api_response.data.affected_items
+        }
+    };
+    pb.finish_and_clear();
+
+    let mut assignments: Vec<GroupAssignment> = agents
+        .iter()
+        .filter(|agent| filter.matches(agent))
+        .map(|agent| plan_assignment(agent, group))
+        .collect();
+    if assignments.is_empty() {
+        return Err(anyhow!("No agents match '{}'", filter));
+    }
+
+    let planned: Vec<String> = assignments
+        .iter()
+        .filter(|a| a.status == AssignmentStatus::Planned)
+        .map(|a| a.agent_id.clone())
+        .collect();
+
+    if options.dry_run || planned.is_empty() {
+        if json_output {
+            return print_json(&assignments);
+        }
+        print_group_assignments(&assignments, group);
+        println!(
+            "\n{} agents would be assigned to '{}' ({} already members)",
+            planned.len(),
+            group,
+            assignments.len() - planned.len()
+        );
+        return Ok(());
+    }
+
+    if !options.skip_confirm {
+        if !json_output {
+            print_group_assignments(&assignments, group);
+        }
+        let confirm = Confirm::new()
+            .with_prompt(format!("Assign {} agents to group '{}'?", planned.len(), group))
+            .default(false)
+            .interact()?;
+        if !confirm {
+            println!("Operation cancelled");
+            return Ok(());
+        }
+    }
+
+    let pb = spinner("Assigning agents...");
+    let mut progress = Progress::start("group assign", Some(planned.len() as u64));
+    let mut failures: HashMap<String, String> = HashMap::new();
+
+    for (index, batch) in planned.chunks(options.batch_size).enumerate() {
+        pb.set_message(format!(
+            "Assigning agents (batch {} of {})...",
+            index + 1,
+            planned.len().div_ceil(options.batch_size)
+        ));
+        // A failed request fails every agent of the batch; other batches still run
+        match assign_batch(client, group, batch).await {
+            Ok(batch_failures) => failures.extend(batch_failures),
+            Err(e) => {
+                let error = format!("{:#}", e);
+                failures.extend(batch.iter().map(|id| (id.clone(), error.clone())));
+            }
+        }
+        for id in batch {
+            progress.item_done(id, failures.get(id).map(String::as_str));
+        }
+    }
+    progress.finish();
+    pb.finish_and_clear();
+
+    for assignment in assignments
+        .iter_mut()
+        .filter(|a| a.status == AssignmentStatus::Planned)
+    {
+        match failures.remove(&assignment.agent_id) {
+            Some(error) => {
+                assignment.status = AssignmentStatus::Failed;
+                assignment.error = Some(error);
+            }
+            None => {
+                assignment.status = AssignmentStatus::Assigned;
+                assignment.groups.push(group.to_string());
+            }
+        }
+    }
+
+    if json_output {
+        return print_json(&assignments);
+    }
+
+    let failed = assignments
+        .iter()
+        .filter(|a| a.status == AssignmentStatus::Failed)
+        .count();
+    if failed > 0 {
+        print_group_assignments(&assignments, group);
+    }
+    println!(
+        "\n{} {} of {} agents assigned to '{}'",
+        if failed == 0 { "✓".green().bold() } else { "!".yellow().bold() },
+        planned.len() - failed,
+        planned.len(),
+        group
+    );
+    if failed > 0 {
+        print_info(&format!("{} assignments failed; see the Result column", failed));
+    }
+
+    Ok(())
+}
+
+fn plan_assignment(agent: &Agent, group: &str) -> GroupAssignment {
+    let groups = agent.group.clone().unwrap_or_default();
+    let status = if groups.iter().any(|g| g == group) {
+        AssignmentStatus::AlreadyMember
+    } else {
+        AssignmentStatus::Planned
+    };
+
+    GroupAssignment {
+        agent_id: agent.id.clone(),
+        agent_name: agent.name.clone(),
+        platform: agent.os.as_ref().and_then(|os| os.platform.clone()),
+        groups,
+        status,
+        error: None,
+    }
+}
+
+/// Assign one batch with the multi-agent endpoint, returning per-agent failures
+async fn assign_batch(
+    client: &WazuhClient,
+    group: &str,
+    agent_ids: &[String],
+) -> Result<HashMap<String, String>> {
+    let url = format!(
+        "/agents/group?group_id={}&agents_list={}",
+        group,
+        agent_ids.join(",")
+    );
+    let response = client.put(&url, None::<()>).await?;
+    let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+
+    Ok(bulk_failures(&api_response.data.failed_items))
+}
+
+/// Map `failed_items` (`{error: {message}, id: [..]}`) to agent ID and message
+pub fn bulk_failures(failed_items: &[serde_json::Value]) -> HashMap<String, String> {
+    let mut failures = HashMap::new();
+    for failure in failed_items {
+        let error = json_to_text(failure.pointer("/error/message"));
+        if let Some(ids) = failure.get("id").and_then(|v| v.as_array()) {
+            for id in ids {
+                failures.insert(json_to_text(Some(id)), error.clone());
+            }
+        }
+    }
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_agent_filter() {
+        let agent: Agent = serde_json::from_value(json!({
+            "id": "002", "name": "db-01", "status": "active",
+            "os": {"platform": "windows", "name": "Microsoft Windows Server 2019"}
+        }))
+        .unwrap();
+
+        let filter = |pattern: &str, os: Option<&str>| AgentFilter {
+            pattern: Some(pattern.to_string()),
+            os: os.map(String::from),
+            selector: None,
+        };
+        assert!(filter("db-*", None).matches(&agent));
+        assert!(filter("db-*", Some("Windows")).matches(&agent));
+        assert!(!filter("db-*", Some("ubuntu")).matches(&agent));
+        assert!(!filter("web-*", None).matches(&agent));
+    }
+
+    #[test]
+    fn test_bulk_failures() {
+        let failed = vec![json!({
+            "error": {"code": 1701, "message": "Agent does not exist"},
+            "id": ["005", "006"]
+        })];
+        let failures = bulk_failures(&failed);
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures["006"], "Agent does not exist");
+    }
+}
@@ -0,0 +1,264 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use colored::Colorize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::{
+    cli::{IndexerAction, IndexerCommand},
+    config::Config,
+    error::WazuhError,
+    indexer::IndexerClient,
+    models::{IndexRetention, RetentionViolation},
+    output::{print_json, print_retention_check, spinner},
+    utils::parse_duration,
+};
+
+/// Indices holding current agent state rather than time-series data, which
+/// retention policies do not apply to
+const STATE_INDEX_PREFIX: &str = "wazuh-states-";
+
+pub async fn handle_indexer_command(
+    cmd: IndexerCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    let indexer = IndexerClient::new(config)?;
+
+    match cmd.action {
+        IndexerAction::RetentionCheck {
+            policy,
+            max_age,
+            index,
+        } => {
+            let requirement = Requirement {
+                min: parse_duration(&policy)?,
+                max: max_age.as_deref().map(parse_duration).transpose()?,
+            };
+            retention_check(&indexer, &index, &requirement, json_output).await
+        }
+    }
+}
+
+/// How long indices must be kept, and optionally how long they may be kept
+struct Requirement {
+    min: Duration,
+    max: Option<Duration>,
+}
+
+async fn retention_check(
+    indexer: &IndexerClient,
+    pattern: &str,
+    requirement: &Requirement,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Fetching indices and ISM policies...");
+    let indices = indexer
+        .get(&format!("_cat/indices/{}?format=json&h=index,creation.date&s=index", pattern))
+        .await?;
+    let explain = indexer.get(&format!("_plugins/_ism/explain/{}", pattern)).await?;
+
+    let mut policies: HashMap<String, Option<Value>> = HashMap::new();
+    for entry in explain.as_object().into_iter().flat_map(|map| map.values()) {
+        let Some(policy_id) = attached_policy(entry) else {
+            continue;
+        };
+        if policies.contains_key(policy_id) {
+            continue;
+        }
+        let policy = match indexer.get(&format!("_plugins/_ism/policies/{}", policy_id)).await {
+            Ok(policy) => Some(policy),
+            Err(e) if is_not_found(&e) => None,
+            Err(e) => return Err(e),
+        };
+        policies.insert(policy_id.to_string(), policy);
+    }
+    pb.finish_and_clear();
+
+    let now = Utc::now();
+    let results: Vec<IndexRetention> = indices
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|row| {
+            let index = row["index"].as_str()?;
+            if index.starts_with(STATE_INDEX_PREFIX) {
+                return None;
+            }
+            let created = row["creation.date"]
+                .as_str()
+                .and_then(|millis| millis.parse().ok())
+                .and_then(DateTime::from_timestamp_millis);
+            let policy_id = attached_policy(&explain[index]);
+            let policy = policy_id.and_then(|id| policies.get(id)).and_then(Option::as_ref);
+            Some(check_index(index, created, policy_id, policy, requirement, now))
+        })
+        .collect();
+
+    if results.is_empty() {
+        return Err(anyhow!("No indices match '{}'", pattern));
+    }
+
+    let violating = results.iter().filter(|r| !r.violations.is_empty()).count();
+    if json_output {
+        print_json(&results)?;
+    } else {
+        print_retention_check(&results);
+        if violating == 0 {
+            println!(
+                "\n{} All {} indices meet the retention requirement",
+                "✓".green().bold(),
+                results.len()
+            );
+        }
+    }
+
+    if violating > 0 {
+        return Err(anyhow!(
+            "{} of {} indices violate the retention requirement",
+            violating,
+            results.len()
+        ));
+    }
+    Ok(())
+}
+
+/// ISM policy managing an index, from its `_plugins/_ism/explain` entry
+fn attached_policy(entry: &Value) -> Option<&str> {
+    [
+        "policy_id",
+        "index.plugins.index_state_management.policy_id",
+        "index.opendistro.index_state_management.policy_id",
+    ]
+    .iter()
+    .find_map(|key| entry[*key].as_str())
+}
+
+fn is_not_found(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<WazuhError>(),
+        Some(WazuhError::IndexerError { status: 404, .. })
+    )
+}
+
+/// Index age at which a policy deletes indices: the `min_index_age` of the
+/// earliest transition into a state with a delete action
+fn delete_age(policy: &Value) -> Option<Duration> {
+    let states = policy["policy"]["states"].as_array()?;
+    let deleting: Vec<&str> = states
+        .iter()
+        .filter(|state| {
+            state["actions"]
+                .as_array()
+                .is_some_and(|actions| actions.iter().any(|action| action.get("delete").is_some()))
+        })
+        .filter_map(|state| state["name"].as_str())
+        .collect();
+
+    states
+        .iter()
+        .flat_map(|state| state["transitions"].as_array().into_iter().flatten())
+        .filter(|transition| {
+            transition["state_name"].as_str().is_some_and(|name| deleting.contains(&name))
+        })
+        .filter_map(|transition| transition["conditions"]["min_index_age"].as_str())
+        .filter_map(|age| parse_duration(age).ok())
+        .min()
+}
+
+fn check_index(
+    index: &str,
+    created: Option<DateTime<Utc>>,
+    policy_id: Option<&str>,
+    policy: Option<&Value>,
+    requirement: &Requirement,
+    now: DateTime<Utc>,
+) -> IndexRetention {
+    let age = created.map(|created| now - created);
+    let delete_after = policy.and_then(delete_age);
+
+    let mut violations = Vec::new();
+    match (policy_id, policy) {
+        (None, _) => violations.push(RetentionViolation::NoPolicy),
+        (Some(_), None) => violations.push(RetentionViolation::PolicyMissing),
+        (Some(_), Some(_)) => {
+            if delete_after.is_some_and(|after| after < requirement.min) {
+                violations.push(RetentionViolation::DeletedTooEarly);
+            }
+            if let Some(max) = requirement.max {
+                if delete_after.is_none_or(|after| after > max) {
+                    violations.push(RetentionViolation::KeptTooLong);
+                }
+            }
+        }
+    }
+    if let (Some(age), Some(max)) = (age, requirement.max) {
+        if age > max {
+            violations.push(RetentionViolation::Overdue);
+        }
+    }
+
+    IndexRetention {
+        index: index.to_string(),
+        created,
+        age_days: age.map(|age| age.num_days()),
+        policy_id: policy_id.map(String::from),
+        delete_after_days: delete_after.map(|after| after.num_days()),
+        violations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_index() {
+        let policy = json!({"policy": {"states": [
+            {"name": "hot", "actions": [], "transitions": [
+                {"state_name": "warm", "conditions": {"min_index_age": "7d"}}
+            ]},
+            {"name": "warm", "actions": [{"replica_count": {"number_of_replicas": 0}}],
+             "transitions": [{"state_name": "delete", "conditions": {"min_index_age": "30d"}}]},
+            {"name": "delete", "actions": [{"delete": {}}], "transitions": []}
+        ]}});
+        assert_eq!(delete_age(&policy), Some(Duration::days(30)));
+
+        let now = Utc::now();
+        let created = Some(now - Duration::days(45));
+        let requirement = Requirement {
+            min: Duration::days(90),
+            max: Some(Duration::days(40)),
+        };
+        let result =
+            check_index("wazuh-alerts", created, Some("p"), Some(&policy), &requirement, now);
+        assert_eq!(result.delete_after_days, Some(30));
+        assert_eq!(
+            result.violations,
+            [RetentionViolation::DeletedTooEarly, RetentionViolation::Overdue]
+        );
+
+        let unmanaged = check_index("wazuh-alerts", created, None, None, &requirement, now);
+        assert_eq!(
+            unmanaged.violations,
+            [RetentionViolation::NoPolicy, RetentionViolation::Overdue]
+        );
+
+        let relaxed = Requirement {
+            min: Duration::days(30),
+            max: None,
+        };
+        let ok = check_index("wazuh-alerts", created, Some("p"), Some(&policy), &relaxed, now);
+        assert!(ok.violations.is_empty());
+    }
+
+    #[test]
+    fn test_attached_policy() {
+        let managed = json!({"index.plugins.index_state_management.policy_id": "rollover"});
+        let unmanaged = json!({"index.plugins.index_state_management.policy_id": null});
+        assert_eq!(attached_policy(&managed), Some("rollover"));
+        assert_eq!(attached_policy(&unmanaged), None);
+        assert_eq!(attached_policy(&json!({})["missing"]), None);
+    }
+}
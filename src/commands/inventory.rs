@@ -0,0 +1,191 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::{RwLock, Semaphore};
+use tokio::task::JoinSet;
+
+use crate::{
+    cli::{InventoryAction, InventoryCommand},
+    client::WazuhClient,
+    commands::agent::resolve_agent_id,
+    config::Config,
+    models::{Agent, AgentHotfixes, AgentListResponse, ApiResponse, ListResponse},
+    output::{print_hotfix_list, print_hotfix_summary, print_json, spinner},
+    progress::Progress,
+};
+
+pub async fn handle_inventory_command(
+    cmd: InventoryCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+
+    // Ensure we're authenticated
+    client.authenticate().await?;
+
+    match cmd.action {
+        InventoryAction::Hotfixes {
+            agent,
+            missing_kb,
+            concurrency,
+        } => {
+            let agent = resolve_agent_id(&client, &agent).await?;
+            let kb = missing_kb.as_deref().map(normalize_kb);
+            hotfixes(&client, &agent, kb.as_deref(), concurrency, json_output).await?
+        }
+    }
+
+    Ok(())
+}
+
+async fn hotfixes(
+    client: &WazuhClient,
+    agent: &str,
+    kb: Option<&str>,
+    concurrency: usize,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Fetching Windows agents...");
+
+    let url = if agent.eq_ignore_ascii_case("all") {
+        "/agents?os.platform=windows&select=id,name,status".to_string()
+    } else {
+        format!("/agents?agents_list={}&select=id,name,status", agent)
+    };
+    let api_response: ApiResponse<AgentListResponse> = client.get_list(&url).await?;
+    let agents = api_response.data.affected_items;
+    if agents.is_empty() {
+        pb.finish_and_clear();
+        return Err(anyhow!("No matching agents found for '{}'", agent));
+    }
+
+    pb.set_message(format!("Fetching hotfixes of {} agents...", agents.len()));
+    let mut progress = Progress::start("inventory hotfixes", Some(agents.len() as u64));
+    let mut results = fetch_all(client, agents, concurrency, &mut progress).await?;
+    progress.finish();
+    pb.finish_and_clear();
+
+    if let Some(kb) = kb {
+        for result in results.iter_mut().filter(|r| r.error.is_none()) {
+            result.has_kb = Some(result.hotfixes.iter().any(|h| normalize_kb(h) == kb));
+        }
+        // Agents missing the KB first, then those that could not be checked
+        results.sort_by_key(|r| (r.has_kb != Some(false), r.has_kb.is_some()));
+    }
+
+    if json_output {
+        return print_json(&results);
+    }
+
+    match (results.as_slice(), kb) {
+        ([single], None) if single.error.is_none() => print_hotfix_list(single),
+        _ => {
+            print_hotfix_summary(&results, kb);
+            if let Some(kb) = kb {
+                let missing = results.iter().filter(|r| r.has_kb == Some(false)).count();
+                let unknown = results.iter().filter(|r| r.has_kb.is_none()).count();
+                println!(
+                    "\n{} of {} agents are missing {} ({} could not be checked)",
+                    missing,
+                    results.len(),
+                    kb,
+                    unknown
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Query every agent's hotfixes, at most `concurrency` at a time, keeping agent order
+async fn fetch_all(
+    client: &WazuhClient,
+    agents: Vec<Agent>,
+    concurrency: usize,
+    progress: &mut Progress,
+) -> Result<Vec<AgentHotfixes>> {
+    let permits = Arc::new(Semaphore::new(concurrency));
+    let mut tasks = JoinSet::new();
+
+    for (index, agent) in agents.into_iter().enumerate() {
+        let client = client.clone();
+        let permits = permits.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let result = agent_hotfixes(&client, &agent.id).await;
+            (index, agent, result)
+        });
+    }
+
+    let mut results = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        let (index, agent, result) = joined?;
+        let error = result.as_ref().err().map(|e| format!("{:#}", e));
+        progress.item_done(&agent.id, error.as_deref());
+
+        let (hotfixes, scan_time) = result.unwrap_or_default();
+        results.push((
+            index,
+            AgentHotfixes {
+                agent_id: agent.id,
+                agent_name: agent.name,
+                hotfixes,
+                scan_time,
+                has_kb: None,
+                error,
+            },
+        ));
+    }
+    results.sort_by_key(|(index, _)| *index);
+
+    Ok(results.into_iter().map(|(_, result)| result).collect())
+}
+
+/// Installed hotfixes of one agent and the time of the syscollector scan
+async fn agent_hotfixes(
+    client: &WazuhClient,
+    agent_id: &str,
+) -> Result<(Vec<String>, Option<String>)> {
+    let url = format!("/syscollector/{}/hotfixes", agent_id);
+    let api_response: ApiResponse<ListResponse<Value>> = client.get_list(&url).await?;
+    let items = api_response.data.affected_items;
+
+    let mut hotfixes: Vec<String> = items
+        .iter()
+        .filter_map(|item| item["hotfix"].as_str().map(String::from))
+        .collect();
+    hotfixes.sort();
+    hotfixes.dedup();
+
+    let scan_time = items
+        .iter()
+        .filter_map(|item| item.pointer("/scan/time").and_then(|t| t.as_str()))
+        .max()
+        .map(String::from);
+
+    Ok((hotfixes, scan_time))
+}
+
+/// `kb5031361` and `5031361` both become `KB5031361`
+fn normalize_kb(kb: &str) -> String {
+    let kb = kb.trim().to_uppercase();
+    if kb.starts_with("KB") {
+        kb
+    } else {
+        format!("KB{}", kb)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_kb() {
+        assert_eq!(normalize_kb("kb5031361"), "KB5031361");
+        assert_eq!(normalize_kb(" 5031361 "), "KB5031361");
+        assert_eq!(normalize_kb("KB5031361"), "KB5031361");
+    }
+}
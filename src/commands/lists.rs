@@ -0,0 +1,267 @@
+use anyhow::{anyhow, Context, Result};
+use dialoguer::Confirm;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::{
+    cli::{ListsAction, ListsCommand},
+    client::WazuhClient,
+    config::Config,
+    error::WazuhError,
+    models::{ApiResponse, CdbList, CdbListItem, ListResponse},
+    output::{
+        print_cdb_list_entries, print_cdb_lists, print_info, print_json, print_success, spinner,
+    },
+    utils::json_to_text,
+};
+
+pub async fn handle_lists_command(
+    cmd: ListsCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+
+    // Ensure we're authenticated
+    client.authenticate().await?;
+
+    match cmd.action {
+        ListsAction::Show { filename } => {
+            show_lists(&client, filename.as_deref(), json_output).await?
+        }
+        ListsAction::Get { filename, raw } => {
+            get_list(&client, &filename, raw, json_output).await?
+        }
+        ListsAction::Upload {
+            filename,
+            path,
+            overwrite,
+        } => upload_list(&client, &filename, &path, overwrite, json_output).await?,
+        ListsAction::Delete { filename, yes } => {
+            delete_list(&client, &filename, yes, json_output).await?
+        }
+    }
+
+    Ok(())
+}
+
+async fn show_lists(client: &WazuhClient, filename: Option<&str>, json_output: bool) -> Result<()> {
+    let pb = spinner("Fetching CDB lists...");
+    let mut url = "/lists".to_string();
+    if let Some(filename) = filename {
+        url.push('?');
+        url.push_str(&serde_urlencoded::to_string([("filename", filename)])?);
+    }
+    let api_response: ApiResponse<ListResponse<CdbList>> = client.get_list(&url).await?;
+    pb.finish_and_clear();
+
+    let lists = api_response.data.affected_items;
+    if json_output {
+        print_json(&lists)
+    } else {
+        print_cdb_lists(&lists);
+        Ok(())
+    }
+}
+
+async fn get_list(
+    client: &WazuhClient,
+    filename: &str,
+    raw: bool,
+    json_output: bool,
+) -> Result<()> {
+    validate_list_name(filename)?;
+    if raw {
+        let url = format!("/lists/files/{}?raw=true", filename);
+        let response = client.get(&url).await?;
+        print!("{}", WazuhClient::response_text(response).await?);
+        return Ok(());
+    }
+
+    let url = format!("/lists/files/{}", filename);
+    let response = client.get(&url).await?;
+    let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_spooled_response(response).await?;
+    let items = list_items(&api_response.data.affected_items);
+
+    if json_output {
+        print_json(&items)
+    } else {
+        print_cdb_list_entries(&items, filename);
+        Ok(())
+    }
+}
+
+/// Entries of a list file, returned either as one `{key: value}` map or as
+/// `{key, value}` objects depending on the manager version
+fn list_items(affected_items: &[serde_json::Value]) -> Vec<CdbListItem> {
+    let mut items = Vec::new();
+    for item in affected_items {
+        let Some(object) = item.as_object() else {
+            continue;
+        };
+        if let Some(key) = object.get("key") {
+            items.push(CdbListItem {
+                key: json_to_text(Some(key)),
+                value: object.get("value").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            });
+        } else {
+            items.extend(object.iter().map(|(key, value)| CdbListItem {
+                key: key.clone(),
+                value: value.as_str().unwrap_or("").to_string(),
+            }));
+        }
+    }
+    items
+}
+
+/// Check that a list filename names one file, since it becomes part of the URL
+fn validate_list_name(filename: &str) -> Result<(), WazuhError> {
+    let invalid = filename.is_empty()
+        || filename.contains(['/', '\\', '?', '#'])
+        || filename.contains("..");
+    if invalid {
+        return Err(WazuhError::InvalidInput(format!(
+            "'{}' is not a list filename: it may not contain '/', '?', '#' or '..'",
+            filename
+        )));
+    }
+    Ok(())
+}
+
+/// Check that every non-empty line is a `key:value` entry, returning the entry count
+fn validate_list(content: &str) -> Result<usize, WazuhError> {
+    let mut entries = 0;
+    for (number, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        // Quoted keys may contain colons themselves, e.g. IPv6 addresses
+        let rest = match line.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').map(|(_, rest)| rest),
+            None => Some(line),
+        };
+        if !rest.is_some_and(|rest| rest.contains(':')) {
+            return Err(WazuhError::InvalidInput(format!(
+                "Line {} is not a key:value entry: {}",
+                number + 1,
+                line
+            )));
+        }
+        entries += 1;
+    }
+    Ok(entries)
+}
+
+async fn upload_list(
+    client: &WazuhClient,
+    filename: &str,
+    path: &Path,
+    overwrite: bool,
+    json_output: bool,
+) -> Result<()> {
+    validate_list_name(filename)?;
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read list file: {:?}", path))?;
+    let entries = validate_list(&content)?;
+
+    let pb = spinner("Uploading list...");
+    let url = format!("/lists/files/{}?overwrite={}", filename, overwrite);
+    let response = client
+        .put_raw(&url, "application/octet-stream", content.into_bytes())
+        .await?;
+    let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+    pb.finish_and_clear();
+
+    if api_response.data.total_failed_items > 0 {
+        return Err(anyhow!(
+            "Manager rejected the list: {}",
+            serde_json::to_string(&api_response.data.failed_items)?
+        ));
+    }
+
+    if json_output {
+        return print_json(&api_response);
+    }
+    print_success(&format!("Uploaded list '{}' with {} entries", filename, entries));
+    print_info("Restart the manager to load the list: wazuh-cli control restart");
+    Ok(())
+}
+
+async fn delete_list(
+    client: &WazuhClient,
+    filename: &str,
+    skip_confirm: bool,
+    json_output: bool,
+) -> Result<()> {
+    validate_list_name(filename)?;
+    if !skip_confirm {
+        let confirm = Confirm::new()
+            .with_prompt(format!(
+                "Delete list '{}'? Rules that look it up stop matching",
+                filename
+            ))
+            .default(false)
+            .interact()?;
+
+        if !confirm {
+            println!("Operation cancelled");
+            return Ok(());
+        }
+    }
+
+    let url = format!("/lists/files/{}", filename);
+    let response = client.delete(&url).await?;
+    let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+
+    if api_response.data.total_failed_items > 0 {
+        return Err(anyhow!(
+            "Failed to delete list '{}': {}",
+            filename,
+            serde_json::to_string(&api_response.data.failed_items)?
+        ));
+    }
+
+    if json_output {
+        return print_json(&api_response);
+    }
+    print_success(&format!("Deleted list '{}'", filename));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_list() {
+        assert_eq!(validate_list("10.0.0.1:blocked\n\n10.0.0.2:\n").unwrap(), 2);
+        assert_eq!(validate_list("\"fe80::1\":router\n").unwrap(), 1);
+        assert!(validate_list("10.0.0.1:blocked\n10.0.0.2\n").is_err());
+        assert!(validate_list("\"fe80::1\"\n").is_err());
+    }
+
+    #[test]
+    fn test_validate_list_name() {
+        assert!(validate_list_name("malicious-ioc").is_ok());
+        assert!(validate_list_name("blocked.ips").is_ok());
+        for name in ["", "../etc/passwd", "a/b", "a?overwrite=true", "a#b"] {
+            assert!(validate_list_name(name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_list_items() {
+        let items = list_items(&[json!({"10.0.0.1": "blocked", "10.0.0.2": ""})]);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].key, "10.0.0.1");
+        assert_eq!(items[0].value, "blocked");
+
+        let items = list_items(&[json!({"key": "10.0.0.3", "value": "scanner"})]);
+        assert_eq!(items[0].value, "scanner");
+    }
+}
@@ -0,0 +1,124 @@
+use anyhow::Result;
+use colored::Colorize;
+use dialoguer::Input;
+use serde_json::{json, Value};
+use std::io::{self, Write};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::{
+    cli::LogtestCommand,
+    client::WazuhClient,
+    config::Config,
+    models::ApiResponse,
+    output::{print_info, print_json, print_logtest, print_warning, WarningKind},
+};
+
+pub async fn handle_logtest_command(
+    cmd: LogtestCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+
+    // Ensure we're authenticated
+    client.authenticate().await?;
+
+    let mut session = LogtestSession {
+        location: cmd.location,
+        log_format: cmd.log_format,
+        token: cmd.session.clone(),
+    };
+
+    if let Some(log) = cmd.log {
+        let result = session.test(&client, &log).await?;
+        print_result(&result, json_output)?;
+        if !json_output && cmd.session.is_none() {
+            if let Some(token) = &session.token {
+                print_info(&format!("Continue this session with --session {}", token));
+            }
+        }
+        return Ok(());
+    }
+
+    println!("Type a log line to test it, 'exit' to quit\n");
+    loop {
+        print!("{} ", "logtest>".green().bold());
+        io::stdout().flush()?;
+        // End of input (Ctrl-D) ends the session like 'exit'
+        let Ok(line) = Input::<String>::new().allow_empty(true).interact_text() else {
+            break;
+        };
+        match line.trim() {
+            "" => continue,
+            "exit" | "quit" | "q" => break,
+            _ => {}
+        }
+        match session.test(&client, &line).await {
+            Ok(result) => print_result(&result, json_output)?,
+            Err(e) => eprintln!("{} {}", "Error:".red().bold(), e),
+        }
+        println!();
+    }
+
+    // A session given with --session belongs to the caller; ours is closed
+    if cmd.session.is_none() {
+        if let Some(token) = &session.token {
+            session_close(&client, token).await;
+        }
+    }
+    Ok(())
+}
+
+/// Settings and token shared by the lines tested in one session
+struct LogtestSession {
+    location: String,
+    log_format: String,
+    token: Option<String>,
+}
+
+impl LogtestSession {
+    async fn test(&mut self, client: &WazuhClient, log: &str) -> Result<Value> {
+        let mut body = json!({
+            "event": log,
+            "location": self.location,
+            "log_format": self.log_format,
+        });
+        if let Some(token) = &self.token {
+            body["token"] = json!(token);
+        }
+
+        let response = client.put("/logtest", Some(body)).await?;
+        let api_response: ApiResponse<Value> = WazuhClient::parse_response(response).await?;
+        let result = api_response.data;
+        // The manager opens a new session when the token has expired
+        if let Some(token) = result["token"].as_str() {
+            self.token = Some(token.to_string());
+        }
+        Ok(result)
+    }
+}
+
+fn print_result(result: &Value, json_output: bool) -> Result<()> {
+    if json_output {
+        return print_json(result);
+    }
+    for message in result["messages"].as_array().into_iter().flatten() {
+        let message = message.as_str().unwrap_or_default();
+        if let Some(warning) = message.strip_prefix("WARNING: ") {
+            print_warning(WarningKind::Fallback, warning);
+        } else {
+            debug!("logtest: {}", message);
+        }
+    }
+    print_logtest(result);
+    Ok(())
+}
+
+async fn session_close(client: &WazuhClient, token: &str) {
+    let url = format!("/logtest/sessions/{}", token);
+    if let Err(e) = client.delete(&url).await {
+        debug!("Could not close logtest session {}: {:#}", token, e);
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::{
+    cli::{MitreAction, MitreCommand},
+    client::WazuhClient,
+    config::Config,
+    models::{ApiResponse, ListResponse},
+    output::{print_info, print_json, print_mitre_entries, print_mitre_entry, spinner},
+};
+
+pub async fn handle_mitre_command(
+    cmd: MitreCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+
+    // Ensure we're authenticated
+    client.authenticate().await?;
+
+    let (kind, query, limit) = match cmd.action {
+        MitreAction::Techniques { query, limit } => ("techniques", query, limit),
+        MitreAction::Tactics { query, limit } => ("tactics", query, limit),
+        MitreAction::Groups { query, limit } => ("groups", query, limit),
+        MitreAction::Software { query, limit } => ("software", query, limit),
+        MitreAction::References { query, limit } => ("references", query, limit),
+    };
+
+    let pb = spinner(&format!("Fetching MITRE {}...", kind));
+    let url = format!("/mitre/{}?{}", kind, query_params(kind, query.as_deref(), limit)?);
+    debug!("Fetching MITRE entries with {}", url);
+    let api_response: ApiResponse<ListResponse<Value>> = client.get_list(&url).await?;
+    pb.finish_and_clear();
+
+    let entries = api_response.data.affected_items;
+    if json_output {
+        return print_json(&entries);
+    }
+    match entries.as_slice() {
+        [] => print_info(&format!("No MITRE {} match", kind)),
+        // A lookup by ID is answered with the whole entry
+        [entry] if kind != "references" => print_mitre_entry(entry),
+        _ => {
+            print_mitre_entries(&entries, kind == "references");
+            println!("\nTotal: {} {}", entries.len(), kind);
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a query is an ATT&CK ID such as `T1059`, `T1059.001`, `TA0002` or `G0016`
+fn is_attack_id(query: &str) -> bool {
+    let query = query.to_uppercase();
+    let digits = ["TA", "T", "G", "S", "M"]
+        .iter()
+        .find_map(|prefix| query.strip_prefix(prefix));
+    let Some(digits) = digits else {
+        return false;
+    };
+    let (main, sub) = digits.split_once('.').unwrap_or((digits, "000"));
+    main.len() == 4
+        && sub.len() == 3
+        && main.chars().chain(sub.chars()).all(|c| c.is_ascii_digit())
+}
+
+/// Query string for a lookup: IDs match `external_id` exactly, anything else
+/// is a free-text search
+fn query_params(kind: &str, query: Option<&str>, limit: u32) -> Result<String> {
+    let mut params = vec![("limit", limit.to_string())];
+    match query {
+        Some(query) if kind != "references" && is_attack_id(query) => {
+            params.push(("q", format!("external_id={}", query.to_uppercase())));
+        }
+        Some(query) => params.push(("search", query.to_string())),
+        None => {}
+    }
+    Ok(serde_urlencoded::to_string(&params)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_params() {
+        assert!(is_attack_id("T1059"));
+        assert!(is_attack_id("t1059.001"));
+        assert!(is_attack_id("TA0002"));
+        assert!(!is_attack_id("T105"));
+        assert!(!is_attack_id("Tomcat"));
+
+        assert_eq!(
+            query_params("techniques", Some("t1059"), 10).unwrap(),
+            "limit=10&q=external_id%3DT1059"
+        );
+        assert_eq!(
+            query_params("techniques", Some("command line"), 10).unwrap(),
+            "limit=10&search=command+line"
+        );
+    }
+}
@@ -1,3 +1,30 @@
 pub mod agent;
+pub mod alerts;
+pub mod ar;
+pub mod archives;
+pub mod auth;
+pub mod cluster;
+pub mod completion;
 pub mod config;
-pub mod control;
\ No newline at end of file
+pub mod control;
+pub mod decoders;
+pub mod drift;
+pub mod events;
+pub mod export;
+pub mod fim;
+pub mod group;
+pub mod indexer;
+pub mod inventory;
+pub mod lists;
+pub mod logtest;
+pub mod mitre;
+pub mod monitor;
+pub mod rbac;
+pub mod report;
+pub mod rootcheck;
+pub mod rules;
+pub mod security;
+pub mod selftest;
+pub mod stats;
+pub mod tasks;
+pub mod vuln;
@@ -0,0 +1,550 @@
+use anyhow::Result;
+use chrono::Utc;
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{RwLock, Semaphore};
+use tokio::task::JoinSet;
+use tracing::debug;
+
+use crate::{
+    cli::{MonitorAction, MonitorCommand},
+    client::WazuhClient,
+    config::Config,
+    error::WazuhError,
+    models::{
+        AgentListResponse, ApiResponse, ListResponse, ScaCheckSummary, ScaPolicySummary,
+        ScaSummary,
+    },
+    notify::Notifier,
+    output::{print_info, print_sca_summary, spinner, stdout_is_terminal},
+    utils::parse_duration,
+};
+
+pub async fn handle_monitor_command(
+    cmd: MonitorCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    let config = Arc::new(RwLock::new(config.clone()));
+    let client = WazuhClient::new(config).await?;
+
+    // Ensure we're authenticated
+    client.authenticate().await?;
+
+    match cmd.action {
+        MonitorAction::Manager {
+            thresholds,
+            interval,
+            max_eps,
+            webhook,
+            desktop,
+            once,
+        } => {
+            let thresholds = parse_thresholds(&thresholds)?;
+            if matches!(thresholds.eps, Some(Threshold::Percent(_))) && max_eps.is_none() {
+                return Err(WazuhError::InvalidInput(
+                    "A percentage EPS threshold requires --max-eps".to_string(),
+                )
+                .into());
+            }
+            let interval = parse_duration(&interval)?.to_std()?;
+            let notifier = Notifier::new(webhook, desktop);
+            monitor_manager(&client, &thresholds, interval, max_eps, &notifier, once, json_output)
+                .await?
+        }
+        MonitorAction::Sca {
+            interval,
+            top,
+            check,
+            concurrency,
+            once,
+        } => {
+            let interval = parse_duration(&interval)?.to_std()?;
+            let panel = ScaPanel {
+                top,
+                check,
+                concurrency,
+            };
+            monitor_sca(&client, &panel, interval, once, json_output).await?
+        }
+    }
+
+    Ok(())
+}
+
+/// A limit expressed either as a percentage of capacity or an absolute value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Threshold {
+    Percent(f64),
+    Absolute(f64),
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Thresholds {
+    pub queue: Option<Threshold>,
+    pub eps: Option<Threshold>,
+}
+
+/// Parse `queue=80%,eps=90%` style threshold lists
+pub fn parse_thresholds(input: &str) -> Result<Thresholds, WazuhError> {
+    let mut thresholds = Thresholds::default();
+
+    for part in input.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (key, value) = part.split_once('=').ok_or_else(|| {
+            WazuhError::InvalidInput(format!("Invalid threshold '{}': expected key=value", part))
+        })?;
+
+        let threshold = match value.strip_suffix('%') {
+            Some(pct) => pct.parse().map(Threshold::Percent),
+            None => value.parse().map(Threshold::Absolute),
+        }
+        .map_err(|_| WazuhError::InvalidInput(format!("Invalid threshold value '{}'", value)))?;
+
+        match key.trim() {
+            "queue" => {
+                if matches!(threshold, Threshold::Absolute(_)) {
+                    return Err(WazuhError::InvalidInput(
+                        "Queue thresholds must be percentages (e.g. queue=80%)".to_string(),
+                    ));
+                }
+                thresholds.queue = Some(threshold);
+            }
+            "eps" => thresholds.eps = Some(threshold),
+            other => {
+                return Err(WazuhError::InvalidInput(format!(
+                    "Unknown threshold '{}' (expected queue or eps)",
+                    other
+                )))
+            }
+        }
+    }
+
+    if thresholds == Thresholds::default() {
+        return Err(WazuhError::InvalidInput("No thresholds given".to_string()));
+    }
+
+    Ok(thresholds)
+}
+
+/// A single metric sampled from the manager
+#[derive(Debug, Serialize)]
+struct Reading {
+    name: String,
+    value: f64,
+    limit: f64,
+    breached: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Sample {
+    timestamp: chrono::DateTime<Utc>,
+    readings: Vec<Reading>,
+}
+
+/// Statistics of one manager daemon (`analysisd`, `remoted`)
+pub async fn fetch_daemon_stats(client: &WazuhClient, daemon: &str) -> Result<serde_json::Value> {
+    let response = client.get(&format!("/manager/stats/{}", daemon)).await?;
+    let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+    Ok(api_response
+        .data
+        .affected_items
+        .into_iter()
+        .next()
+        .unwrap_or_default())
+}
+
+/// Queue usage percentages reported by analysisd and remoted
+pub fn queue_usages(analysisd: &serde_json::Value, remoted: &serde_json::Value) -> Vec<(String, f64)> {
+    let mut usages: Vec<(String, f64)> = analysisd
+        .as_object()
+        .map(|stats| {
+            stats
+                .iter()
+                .filter_map(|(key, value)| {
+                    let queue = key.strip_suffix("_queue_usage")?;
+                    Some((format!("analysisd {} queue", queue), value.as_f64()? * 100.0))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if let (Some(size), Some(total)) = (
+        remoted.get("queue_size").and_then(|v| v.as_f64()),
+        remoted.get("total_queue_size").and_then(|v| v.as_f64()),
+    ) {
+        if total > 0.0 {
+            usages.push(("remoted queue".to_string(), size / total * 100.0));
+        }
+    }
+
+    usages
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn monitor_manager(
+    client: &WazuhClient,
+    thresholds: &Thresholds,
+    interval: Duration,
+    max_eps: Option<f64>,
+    notifier: &Notifier,
+    once: bool,
+    json_output: bool,
+) -> Result<()> {
+    if !once && !json_output {
+        print_info(&format!(
+            "Monitoring manager every {}s (Ctrl-C to stop)",
+            interval.as_secs()
+        ));
+    }
+
+    let mut breached: HashSet<String> = HashSet::new();
+    let mut last_received: Option<(f64, Instant)> = None;
+
+    loop {
+        let analysisd = fetch_daemon_stats(client, "analysisd").await?;
+        let remoted = fetch_daemon_stats(client, "remoted").await?;
+        debug!("analysisd stats: {}", analysisd);
+
+        let mut readings = Vec::new();
+
+        if let Some(Threshold::Percent(limit)) = thresholds.queue {
+            for (name, usage) in queue_usages(&analysisd, &remoted) {
+                readings.push(Reading {
+                    breached: usage >= limit,
+                    name,
+                    value: usage,
+                    limit,
+                });
+            }
+        }
+
+        if let Some(eps_threshold) = thresholds.eps {
+            let received = analysisd
+                .get("events_received")
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.0);
+            let now = Instant::now();
+
+            if let Some((previous, at)) = last_received {
+                let elapsed = now.duration_since(at).as_secs_f64().max(1.0);
+                let eps = (received - previous).max(0.0) / elapsed;
+                let (value, limit) = match eps_threshold {
+                    Threshold::Percent(pct) => {
+                        (eps / max_eps.unwrap_or(f64::MAX) * 100.0, pct)
+                    }
+                    Threshold::Absolute(abs) => (eps, abs),
+                };
+                readings.push(Reading {
+                    name: "eps".to_string(),
+                    breached: value >= limit,
+                    value,
+                    limit,
+                });
+            }
+            last_received = Some((received, now));
+        }
+
+        // EPS is a rate, so a one-shot check takes a second sample an interval later
+        if once && thresholds.eps.is_some() && !readings.iter().any(|r| r.name == "eps") {
+            tokio::time::sleep(interval).await;
+            continue;
+        }
+
+        for reading in &readings {
+            let was_breached = breached.contains(&reading.name);
+            if reading.breached && !was_breached {
+                breached.insert(reading.name.clone());
+                let message = format!(
+                    "{} at {:.1} (threshold {:.1})",
+                    reading.name, reading.value, reading.limit
+                );
+                if !json_output {
+                    println!("{} {}", "ALERT".red().bold(), message);
+                }
+                notifier.send("Wazuh manager pressure", &message).await;
+            } else if !reading.breached && was_breached {
+                breached.remove(&reading.name);
+                let message = format!("{} recovered at {:.1}", reading.name, reading.value);
+                if !json_output {
+                    println!("{} {}", "OK".green().bold(), message);
+                }
+                notifier.send("Wazuh manager recovered", &message).await;
+            }
+        }
+
+        let sample = Sample {
+            timestamp: Utc::now(),
+            readings,
+        };
+
+        if json_output {
+            println!("{}", serde_json::to_string(&sample)?);
+        } else {
+            let peak = sample
+                .readings
+                .iter()
+                .filter(|r| r.name != "eps")
+                .map(|r| r.value)
+                .fold(0.0, f64::max);
+            let eps = sample.readings.iter().find(|r| r.name == "eps");
+            println!(
+                "[{}] peak queue usage {:.1}%{}",
+                sample.timestamp.format("%H:%M:%S"),
+                peak,
+                eps.map(|r| format!(", eps {:.1}", r.value)).unwrap_or_default()
+            );
+        }
+
+        if once {
+            if !breached.is_empty() {
+                return Err(WazuhError::Unknown(format!(
+                    "Thresholds exceeded: {}",
+                    breached.into_iter().collect::<Vec<_>>().join(", ")
+                ))
+                .into());
+            }
+            return Ok(());
+        }
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = tokio::time::sleep(interval) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// What the SCA panel shows
+struct ScaPanel {
+    top: usize,
+    check: Option<String>,
+    concurrency: usize,
+}
+
+/// SCA results fetched from one agent
+struct AgentSca {
+    agent: String,
+    /// `/sca/{agent}` items: policy_id, name, score
+    policies: Vec<Value>,
+    /// Failed checks as (policy ID, check ID, title)
+    failed: Vec<(String, String, String)>,
+}
+
+async fn monitor_sca(
+    client: &WazuhClient,
+    panel: &ScaPanel,
+    interval: Duration,
+    once: bool,
+    json_output: bool,
+) -> Result<()> {
+    // Redraw in place like a dashboard panel, unless output is piped
+    let redraw = !once && !json_output && stdout_is_terminal();
+
+    loop {
+        let pb = spinner("Collecting SCA results...");
+        let results = fetch_fleet_sca(client, panel.concurrency).await?;
+        pb.finish_and_clear();
+
+        let mut summary = summarize_sca(&results);
+        if let Some(check) = &panel.check {
+            summary.checks.retain(|c| &c.check_id == check);
+        }
+
+        if json_output {
+            println!("{}", serde_json::to_string(&summary)?);
+        } else {
+            if redraw {
+                print!("\x1B[2J\x1B[H");
+            }
+            print_sca_summary(&summary, panel.top, panel.check.as_deref());
+            if !once {
+                println!(
+                    "\nRefreshing every {}s (Ctrl-C to stop)",
+                    interval.as_secs()
+                );
+            }
+        }
+
+        if once {
+            return Ok(());
+        }
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = tokio::time::sleep(interval) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Fleet-wide SCA summary of every active agent, as shown by `monitor sca`
+pub async fn fleet_sca_summary(client: &WazuhClient, concurrency: usize) -> Result<ScaSummary> {
+    Ok(summarize_sca(&fetch_fleet_sca(client, concurrency).await?))
+}
+
+/// SCA policies and failed checks of every active agent
+async fn fetch_fleet_sca(client: &WazuhClient, concurrency: usize) -> Result<Vec<AgentSca>> {
+    let url = "/agents?status=active&select=id,name";
+    let api_response: ApiResponse<AgentListResponse> = client.get_list(url).await?;
+
+    let permits = Arc::new(Semaphore::new(concurrency));
+    let mut tasks = JoinSet::new();
+    for agent in api_response.data.affected_items {
+        let client = client.clone();
+        let permits = permits.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let result = agent_sca(&client, &agent.id, &agent.name).await;
+            (agent.id, result)
+        });
+    }
+
+    let mut results = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined? {
+            (_, Ok(result)) => results.push(result),
+            // Agents without SCA (e.g. the scan is disabled) are left out of the panel
+            (id, Err(e)) => debug!("Skipping SCA of agent {}: {:#}", id, e),
+        }
+    }
+    Ok(results)
+}
+
+async fn agent_sca(client: &WazuhClient, agent_id: &str, agent_name: &str) -> Result<AgentSca> {
+    let api_response: ApiResponse<ListResponse<Value>> =
+        client.get_list(&format!("/sca/{}", agent_id)).await?;
+    let policies = api_response.data.affected_items;
+
+    let mut failed = Vec::new();
+    for policy in &policies {
+        let Some(policy_id) = policy["policy_id"].as_str() else {
+            continue;
+        };
+        let url = format!("/sca/{}/checks/{}?result=failed&select=id,title", agent_id, policy_id);
+        let checks: ApiResponse<ListResponse<Value>> = client.get_list(&url).await?;
+        failed.extend(checks.data.affected_items.iter().map(|check| {
+            (
+                policy_id.to_string(),
+                check["id"].to_string().trim_matches('"').to_string(),
+                check["title"].as_str().unwrap_or("").to_string(),
+            )
+        }));
+    }
+
+    Ok(AgentSca {
+        agent: format!("{} ({})", agent_name, agent_id),
+        policies,
+        failed,
+    })
+}
+
+/// Average score per policy and agents failing each check, most failed first
+fn summarize_sca(results: &[AgentSca]) -> ScaSummary {
+    let mut policies: BTreeMap<String, (String, Vec<f64>)> = BTreeMap::new();
+    let mut checks: BTreeMap<(String, String), ScaCheckSummary> = BTreeMap::new();
+
+    for result in results {
+        for policy in &result.policies {
+            let Some(policy_id) = policy["policy_id"].as_str() else {
+                continue;
+            };
+            let name = policy["name"].as_str().unwrap_or(policy_id);
+            let entry = policies
+                .entry(policy_id.to_string())
+                .or_insert_with(|| (name.to_string(), Vec::new()));
+            if let Some(score) = policy["score"].as_f64() {
+                entry.1.push(score);
+            }
+        }
+        for (policy_id, check_id, title) in &result.failed {
+            checks
+                .entry((policy_id.clone(), check_id.clone()))
+                .or_insert_with(|| ScaCheckSummary {
+                    policy_id: policy_id.clone(),
+                    check_id: check_id.clone(),
+                    title: title.clone(),
+                    failing_agents: Vec::new(),
+                })
+                .failing_agents
+                .push(result.agent.clone());
+        }
+    }
+
+    let mut checks: Vec<ScaCheckSummary> = checks.into_values().collect();
+    checks.sort_by_key(|c| std::cmp::Reverse(c.failing_agents.len()));
+
+    ScaSummary {
+        timestamp: Utc::now(),
+        agents_scanned: results.len(),
+        policies: policies
+            .into_iter()
+            .map(|(policy_id, (name, scores))| ScaPolicySummary {
+                policy_id,
+                name,
+                agents: scores.len(),
+                average_score: scores.iter().sum::<f64>() / scores.len().max(1) as f64,
+            })
+            .collect(),
+        checks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_thresholds() {
+        let thresholds = parse_thresholds("queue=80%,eps=900").unwrap();
+        assert_eq!(thresholds.queue, Some(Threshold::Percent(80.0)));
+        assert_eq!(thresholds.eps, Some(Threshold::Absolute(900.0)));
+
+        assert!(parse_thresholds("queue=80").is_err());
+        assert!(parse_thresholds("disk=10%").is_err());
+        assert!(parse_thresholds("").is_err());
+    }
+
+    #[test]
+    fn test_summarize_sca() {
+        let agent = |name: &str, score: u64, failed: &[&str]| AgentSca {
+            agent: name.to_string(),
+            policies: vec![
+                json!({"policy_id": "cis_ubuntu22", "name": "CIS Ubuntu", "score": score}),
+            ],
+            failed: failed
+                .iter()
+                .map(|id| ("cis_ubuntu22".to_string(), id.to_string(), format!("Check {}", id)))
+                .collect(),
+        };
+        let results = vec![
+            agent("web-01", 60, &["28500", "28501"]),
+            agent("web-02", 80, &["28501"]),
+        ];
+
+        let summary = summarize_sca(&results);
+        assert_eq!(summary.policies.len(), 1);
+        assert_eq!(summary.policies[0].agents, 2);
+        assert_eq!(summary.policies[0].average_score, 70.0);
+        assert_eq!(summary.checks[0].check_id, "28501");
+        assert_eq!(summary.checks[0].failing_agents, ["web-01", "web-02"]);
+    }
+
+    #[test]
+    fn test_queue_usages() {
+        let analysisd = json!({"event_queue_usage": 0.5, "alerts_queue_usage": 0.1, "events_received": 10});
+        let remoted = json!({"queue_size": 25, "total_queue_size": 100});
+        let usages = queue_usages(&analysisd, &remoted);
+
+        assert_eq!(usages.len(), 3);
+        assert!(usages.contains(&("analysisd event queue".to_string(), 50.0)));
+        assert!(usages.contains(&("remoted queue".to_string(), 25.0)));
+    }
+}
@@ -0,0 +1,473 @@
+use anyhow::{anyhow, Result};
+use dialoguer::{Confirm, Password};
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::{
+    cli::{PoliciesAction, RolesAction, UsersAction},
+    client::WazuhClient,
+    commands::group::bulk_failures,
+    config::Config,
+    error::WazuhError,
+    models::{ApiPolicy, ApiResponse, ApiRole, ApiUser, ListResponse, PolicyDocument},
+    output::{
+        print_api_policies, print_api_roles, print_api_users, print_json, print_success, spinner,
+    },
+};
+
+async fn connect(config: &Config) -> Result<WazuhClient> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+    client.authenticate().await?;
+    Ok(client)
+}
+
+pub async fn handle_users(action: UsersAction, config: &Config, json_output: bool) -> Result<()> {
+    let client = connect(config).await?;
+
+    match action {
+        UsersAction::List => {
+            let pb = spinner("Fetching users...");
+            let users = fetch_users(&client).await?;
+            pb.finish_and_clear();
+            if json_output {
+                print_json(&users)?;
+            } else {
+                print_api_users(&users);
+            }
+        }
+
+        UsersAction::Create { username, run_as } => {
+            let password = prompt_password("Password")?;
+            let body = json!({ "username": username, "password": password });
+            let response = client.post("/security/users", Some(body)).await?;
+            let api_response: ApiResponse<ListResponse<ApiUser>> =
+                WazuhClient::parse_response(response).await?;
+            check_failures(&api_response.data, &format!("create user '{}'", username))?;
+            let user = api_response
+                .data
+                .affected_items
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow!("The manager did not return the new user"))?;
+            if run_as {
+                set_run_as(&client, user.id, true).await?;
+            }
+
+            if json_output {
+                print_json(&user)?;
+            } else {
+                print_success(&format!("Created user '{}' (ID {})", user.username, user.id));
+            }
+        }
+
+        UsersAction::Delete { user, yes } => {
+            let user = resolve_user(&client, &user).await?;
+            let prompt = format!("Delete API user '{}' (ID {})?", user.username, user.id);
+            if !confirm(yes, &prompt)? {
+                println!("Operation cancelled");
+                return Ok(());
+            }
+
+            let url = format!("/security/users?user_ids={}", user.id);
+            let action = format!("delete user '{}'", user.username);
+            let api_response = send_delete(&client, &url, &action).await?;
+
+            if json_output {
+                print_json(&api_response)?;
+            } else {
+                print_success(&format!("Deleted user '{}'", user.username));
+            }
+        }
+
+        UsersAction::UpdatePassword { user } => {
+            let user = resolve_user(&client, &user).await?;
+            let password = prompt_password(&format!("New password for '{}'", user.username))?;
+            let url = format!("/security/users/{}", user.id);
+            let response = client.put(&url, Some(json!({ "password": password }))).await?;
+            let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+                WazuhClient::parse_response(response).await?;
+            check_failures(&api_response.data, &format!("update user '{}'", user.username))?;
+
+            if json_output {
+                print_json(&api_response)?;
+            } else {
+                // Existing tokens of the user are revoked by the manager
+                print_success(&format!("Updated the password of '{}'", user.username));
+            }
+        }
+
+        UsersAction::EnableRunas { user, disable } => {
+            let user = resolve_user(&client, &user).await?;
+            set_run_as(&client, user.id, !disable).await?;
+
+            if json_output {
+                print_json(&json!({ "id": user.id, "allow_run_as": !disable }))?;
+            } else {
+                print_success(&format!(
+                    "{} run_as for '{}'",
+                    if disable { "Disabled" } else { "Enabled" },
+                    user.username
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn handle_roles(action: RolesAction, config: &Config, json_output: bool) -> Result<()> {
+    let client = connect(config).await?;
+
+    match action {
+        RolesAction::List => {
+            let pb = spinner("Fetching roles...");
+            let roles = fetch_roles(&client).await?;
+            pb.finish_and_clear();
+            if json_output {
+                print_json(&roles)?;
+            } else {
+                // Policies and users are listed by name
+                let policies = fetch_policies(&client).await?;
+                let users = fetch_users(&client).await?;
+                print_api_roles(&roles, &policies, &users);
+            }
+        }
+
+        RolesAction::Create { name } => {
+            let response = client.post("/security/roles", Some(json!({ "name": name }))).await?;
+            let api_response: ApiResponse<ListResponse<ApiRole>> =
+                WazuhClient::parse_response(response).await?;
+            check_failures(&api_response.data, &format!("create role '{}'", name))?;
+
+            if json_output {
+                print_json(&api_response.data.affected_items)?;
+            } else {
+                print_success(&format!("Created role '{}'", name));
+            }
+        }
+
+        RolesAction::Delete { role, yes } => {
+            let role = resolve(fetch_roles(&client).await?, &role, "role", |r| (r.id, &r.name))?;
+            if !confirm(yes, &format!("Delete role '{}' (ID {})?", role.name, role.id))? {
+                println!("Operation cancelled");
+                return Ok(());
+            }
+
+            let url = format!("/security/roles?role_ids={}", role.id);
+            let action = format!("delete role '{}'", role.name);
+            let api_response = send_delete(&client, &url, &action).await?;
+            if json_output {
+                print_json(&api_response)?;
+            } else {
+                print_success(&format!("Deleted role '{}'", role.name));
+            }
+        }
+
+        RolesAction::AddPolicy { role, policies } => {
+            let (role, ids) = resolve_role_policies(&client, &role, &policies).await?;
+            let url = format!("/security/roles/{}/policies?policy_ids={}", role.id, ids);
+            let response = client.post(&url, None::<()>).await?;
+            let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+                WazuhClient::parse_response(response).await?;
+            check_failures(&api_response.data, &format!("link policies to '{}'", role.name))?;
+
+            if json_output {
+                print_json(&api_response)?;
+            } else {
+                print_success(&format!(
+                    "Linked {} policies to role '{}'",
+                    policies.len(),
+                    role.name
+                ));
+            }
+        }
+
+        RolesAction::RemovePolicy { role, policies } => {
+            let (role, ids) = resolve_role_policies(&client, &role, &policies).await?;
+            let url = format!("/security/roles/{}/policies?policy_ids={}", role.id, ids);
+            let action = format!("unlink policies from '{}'", role.name);
+            let api_response = send_delete(&client, &url, &action).await?;
+
+            if json_output {
+                print_json(&api_response)?;
+            } else {
+                print_success(&format!(
+                    "Unlinked {} policies from role '{}'",
+                    policies.len(),
+                    role.name
+                ));
+            }
+        }
+
+        RolesAction::AssignUser { role, user } => {
+            let role = resolve(fetch_roles(&client).await?, &role, "role", |r| (r.id, &r.name))?;
+            let user = resolve_user(&client, &user).await?;
+            let url = format!("/security/users/{}/roles?role_ids={}", user.id, role.id);
+            let response = client.post(&url, None::<()>).await?;
+            let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+                WazuhClient::parse_response(response).await?;
+            check_failures(&api_response.data, &format!("assign role '{}'", role.name))?;
+
+            if json_output {
+                print_json(&api_response)?;
+            } else {
+                print_success(&format!("Gave role '{}' to '{}'", role.name, user.username));
+            }
+        }
+
+        RolesAction::UnassignUser { role, user, yes } => {
+            let role = resolve(fetch_roles(&client).await?, &role, "role", |r| (r.id, &r.name))?;
+            let user = resolve_user(&client, &user).await?;
+            let prompt = format!("Take role '{}' away from '{}'?", role.name, user.username);
+            if !confirm(yes, &prompt)? {
+                println!("Operation cancelled");
+                return Ok(());
+            }
+
+            let url = format!("/security/users/{}/roles?role_ids={}", user.id, role.id);
+            let action = format!("unassign role '{}'", role.name);
+            let api_response = send_delete(&client, &url, &action).await?;
+
+            if json_output {
+                print_json(&api_response)?;
+            } else {
+                print_success(&format!("Took role '{}' from '{}'", role.name, user.username));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn handle_policies(
+    action: PoliciesAction,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    let client = connect(config).await?;
+
+    match action {
+        PoliciesAction::List => {
+            let pb = spinner("Fetching policies...");
+            let policies = fetch_policies(&client).await?;
+            pb.finish_and_clear();
+            if json_output {
+                print_json(&policies)?;
+            } else {
+                print_api_policies(&policies);
+            }
+        }
+
+        PoliciesAction::Create {
+            name,
+            actions,
+            resources,
+            effect,
+        } => {
+            let body = json!({
+                "name": name,
+                "policy": PolicyDocument {
+                    actions,
+                    resources,
+                    effect,
+                },
+            });
+            let response = client.post("/security/policies", Some(body)).await?;
+            let api_response: ApiResponse<ListResponse<ApiPolicy>> =
+                WazuhClient::parse_response(response).await?;
+            check_failures(&api_response.data, &format!("create policy '{}'", name))?;
+
+            if json_output {
+                print_json(&api_response.data.affected_items)?;
+            } else {
+                print_success(&format!("Created policy '{}'", name));
+            }
+        }
+
+        PoliciesAction::Delete { policy, yes } => {
+            let policies = fetch_policies(&client).await?;
+            let policy = resolve(policies, &policy, "policy", |p| (p.id, &p.name))?;
+            let prompt = format!("Delete policy '{}' (ID {})?", policy.name, policy.id);
+            if !confirm(yes, &prompt)? {
+                println!("Operation cancelled");
+                return Ok(());
+            }
+
+            let url = format!("/security/policies?policy_ids={}", policy.id);
+            let action = format!("delete policy '{}'", policy.name);
+            let api_response = send_delete(&client, &url, &action).await?;
+
+            if json_output {
+                print_json(&api_response)?;
+            } else {
+                print_success(&format!("Deleted policy '{}'", policy.name));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A role and the comma-separated IDs of the named policies
+async fn resolve_role_policies(
+    client: &WazuhClient,
+    role: &str,
+    policies: &[String],
+) -> Result<(ApiRole, String)> {
+    let role = resolve(fetch_roles(client).await?, role, "role", |r| (r.id, &r.name))?;
+    let all = fetch_policies(client).await?;
+    let ids = policies
+        .iter()
+        .map(|policy| {
+            resolve(all.clone(), policy, "policy", |p| (p.id, &p.name)).map(|p| p.id.to_string())
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok((role, ids.join(",")))
+}
+
+/// Ask before a destructive action unless `-y` was given
+fn confirm(skip_confirm: bool, prompt: &str) -> Result<bool> {
+    if skip_confirm {
+        return Ok(true);
+    }
+    Ok(Confirm::new().with_prompt(prompt).default(false).interact()?)
+}
+
+/// Send a DELETE, turning failed items into an error
+async fn send_delete(
+    client: &WazuhClient,
+    url: &str,
+    action: &str,
+) -> Result<ApiResponse<ListResponse<serde_json::Value>>> {
+    let response = client.delete(url).await?;
+    let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+    check_failures(&api_response.data, action)?;
+    Ok(api_response)
+}
+
+async fn fetch_users(client: &WazuhClient) -> Result<Vec<ApiUser>> {
+    fetch(client, "/security/users").await
+}
+
+async fn fetch_roles(client: &WazuhClient) -> Result<Vec<ApiRole>> {
+    fetch(client, "/security/roles").await
+}
+
+async fn fetch_policies(client: &WazuhClient) -> Result<Vec<ApiPolicy>> {
+    fetch(client, "/security/policies").await
+}
+
+async fn fetch<T: serde::de::DeserializeOwned>(client: &WazuhClient, url: &str) -> Result<Vec<T>> {
+    let api_response: ApiResponse<ListResponse<T>> = client.get_list(url).await?;
+    Ok(api_response.data.affected_items)
+}
+
+/// Find a user by numeric ID or user name
+async fn resolve_user(client: &WazuhClient, user: &str) -> Result<ApiUser> {
+    resolve(fetch_users(client).await?, user, "API user", |u| (u.id, &u.username))
+}
+
+/// Find a user, role or policy by numeric ID or name
+fn resolve<T>(
+    items: Vec<T>,
+    wanted: &str,
+    kind: &str,
+    key: impl Fn(&T) -> (u32, &String),
+) -> Result<T> {
+    items
+        .into_iter()
+        .find(|item| {
+            let (id, name) = key(item);
+            id.to_string() == wanted || name == wanted
+        })
+        .ok_or_else(|| WazuhError::NotFound(format!("{} '{}'", kind, wanted)).into())
+}
+
+async fn set_run_as(client: &WazuhClient, user_id: u32, allow: bool) -> Result<()> {
+    let url = format!("/security/users/{}/run_as?allow_run_as={}", user_id, allow);
+    let response = client.put(&url, None::<()>).await?;
+    let api_response: ApiResponse<ListResponse<serde_json::Value>> =
+        WazuhClient::parse_response(response).await?;
+    check_failures(&api_response.data, &format!("change run_as of user {}", user_id))
+}
+
+/// Turn the failed items of a single-resource call into an error
+fn check_failures<T>(data: &ListResponse<T>, action: &str) -> Result<()> {
+    if data.total_failed_items == 0 {
+        return Ok(());
+    }
+    let errors: Vec<String> = bulk_failures(&data.failed_items).into_values().collect();
+    let errors = if errors.is_empty() {
+        serde_json::to_string(&data.failed_items)?
+    } else {
+        errors.join("; ")
+    };
+    Err(anyhow!("Failed to {}: {}", action, errors))
+}
+
+/// Prompt twice for a password without echoing it, checked against the
+/// manager's password policy before anything is sent
+fn prompt_password(prompt: &str) -> Result<String> {
+    let password = Password::new()
+        .with_prompt(prompt)
+        .with_confirmation("Repeat password", "Passwords do not match")
+        .validate_with(|input: &String| check_password_policy(input))
+        .interact()?;
+    Ok(password)
+}
+
+/// The API's policy: 8-64 characters with upper and lower case letters, a
+/// digit and a symbol
+fn check_password_policy(password: &str) -> Result<(), String> {
+    let length = password.chars().count();
+    if !(8..=64).contains(&length) {
+        return Err("Password must be 8 to 64 characters long".to_string());
+    }
+    let checks = [
+        (password.chars().any(|c| c.is_ascii_uppercase()), "an upper case letter"),
+        (password.chars().any(|c| c.is_ascii_lowercase()), "a lower case letter"),
+        (password.chars().any(|c| c.is_ascii_digit()), "a digit"),
+        (password.chars().any(|c| !c.is_ascii_alphanumeric()), "a symbol"),
+    ];
+    match checks.iter().find(|(ok, _)| !ok) {
+        Some((_, missing)) => Err(format!("Password must contain {}", missing)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_password_policy() {
+        assert!(check_password_policy("Sup3r.Secret").is_ok());
+        assert!(check_password_policy("Sh0rt.").is_err());
+        assert_eq!(
+            check_password_policy("sup3r.secret").unwrap_err(),
+            "Password must contain an upper case letter"
+        );
+        assert_eq!(
+            check_password_policy("Sup3rSecret").unwrap_err(),
+            "Password must contain a symbol"
+        );
+    }
+
+    #[test]
+    fn test_resolve_by_id_or_name() {
+        let roles: Vec<ApiRole> = serde_json::from_value(serde_json::json!([
+            {"id": 1, "name": "administrator"},
+            {"id": 7, "name": "readonly"},
+        ]))
+        .unwrap();
+        fn key(role: &ApiRole) -> (u32, &String) {
+            (role.id, &role.name)
+        }
+        assert_eq!(resolve(roles.clone(), "7", "role", key).unwrap().name, "readonly");
+        assert_eq!(resolve(roles.clone(), "administrator", "role", key).unwrap().id, 1);
+        let error = resolve(roles, "auditor", "role", key).unwrap_err();
+        assert_eq!(error.to_string(), "Resource not found: role 'auditor'");
+    }
+}
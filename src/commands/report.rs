@@ -0,0 +1,656 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::RwLock;
+
+use crate::{
+    cli::{ReportAction, ReportCommand},
+    client::{WazuhClient, PAGE_SIZE},
+    commands::{monitor::fleet_sca_summary, vuln::severity_rank},
+    compliance::{ComplianceFilter, Standard},
+    config::{Config, OutputConfig, StorageConfig},
+    document::{Document, DocumentFormat, Section},
+    estimate::{confirm_large_result, Estimate},
+    history::{self, HistoryAction},
+    indexer::{AlertQuery, IndexerClient, VULNERABILITIES_INDEX},
+    models::{
+        Agent, AgentListResponse, ApiResponse, ComplianceReport, ComplianceRequirementSummary,
+        FleetGrowthPeriod, FleetGrowthReport,
+    },
+    output::{print_compliance_report, print_fleet_growth, print_json, print_success, spinner},
+    progress::Progress,
+    storage::Destination,
+    utils::parse_duration,
+};
+
+/// Upper bound on distinct requirements returned per standard
+const MAX_REQUIREMENTS: u32 = 500;
+
+pub async fn handle_report_command(
+    cmd: ReportCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    match cmd.action {
+        ReportAction::Compliance {
+            standard,
+            last,
+            agent,
+            out,
+        } => {
+            let indexer = IndexerClient::new(config)?;
+            let report = compliance_report(&indexer, &standard, &last, agent).await?;
+            match out {
+                Some(out) => write_report(&report, &out, &config.storage).await?,
+                None if json_output => print_json(&report)?,
+                None => print_compliance_report(&report),
+            }
+        }
+        ReportAction::FleetGrowth {
+            group_by,
+            last,
+            out,
+        } => {
+            let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+            client.authenticate().await?;
+            let Some(report) = fleet_growth_report(&client, &group_by, last, &config.output).await?
+            else {
+                return Ok(());
+            };
+            match out {
+                Some(out) => write_report(&report, &out, &config.storage).await?,
+                None if json_output => print_json(&report)?,
+                None => print_fleet_growth(&report),
+            }
+        }
+        ReportAction::Generate {
+            report_type,
+            format,
+            out,
+        } => {
+            let format: DocumentFormat = format.parse()?;
+            let document = match report_type.as_str() {
+                "vulnerabilities" => vulnerabilities_document(&IndexerClient::new(config)?).await?,
+                report_type => {
+                    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+                    client.authenticate().await?;
+                    match report_type {
+                        "sca" => sca_document(&client).await?,
+                        _ => agents_document(&client).await?,
+                    }
+                }
+            };
+            match out {
+                Some(out) => {
+                    let content = document.render(format).into_bytes();
+                    out.write(content, format.content_type(), &config.storage).await?;
+                    print_success(&format!("Report written to {}", out));
+                }
+                None if json_output => print_json(&document)?,
+                None => print!("{}", document.render(format)),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn compliance_report(
+    indexer: &IndexerClient,
+    standard: &str,
+    last: &str,
+    agent: Option<String>,
+) -> Result<ComplianceReport> {
+    let standard: Standard = standard.parse()?;
+    let window = parse_duration(last)?;
+    let to = Utc::now();
+
+    let query = AlertQuery {
+        since: Some(window),
+        agent,
+        compliance: Some(ComplianceFilter {
+            standard,
+            requirement: None,
+        }),
+        ..Default::default()
+    };
+
+    let pb = spinner(&format!("Building {} compliance report...", standard));
+    let key = format!("compliance {} {:?}", standard, query);
+    let response = indexer
+        .search_or_cached(&key, indexer.alerts_index(), &compliance_aggregation(&query, standard))
+        .await?;
+    pb.finish_and_clear();
+
+    Ok(ComplianceReport {
+        standard: standard.to_string(),
+        from: to - window,
+        to,
+        total_alerts: response
+            .pointer("/hits/total/value")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0),
+        requirements: parse_requirement_buckets(&response),
+    })
+}
+
+/// Write a report as JSON to `--out` instead of printing it, for scheduled runs
+async fn write_report<T: Serialize>(
+    report: &T,
+    out: &Destination,
+    storage: &StorageConfig,
+) -> Result<()> {
+    out.write(serde_json::to_vec_pretty(report)?, "application/json", storage).await?;
+    print_success(&format!("Report written to {}", out));
+    Ok(())
+}
+
+/// Aggregate matching alerts by requirement with per-requirement agent/rule counts
+fn compliance_aggregation(query: &AlertQuery, standard: Standard) -> Value {
+    json!({
+        "size": 0,
+        "track_total_hits": true,
+        "query": { "bool": { "filter": query.filters() } },
+        "aggs": {
+            "requirements": {
+                "terms": {
+                    "field": format!("rule.{}", standard.field()),
+                    "size": MAX_REQUIREMENTS,
+                    "order": { "_key": "asc" }
+                },
+                "aggs": {
+                    "agents": { "cardinality": { "field": "agent.id" } },
+                    "rules": { "cardinality": { "field": "rule.id" } },
+                    "max_level": { "max": { "field": "rule.level" } }
+                }
+            }
+        }
+    })
+}
+
+fn parse_requirement_buckets(response: &Value) -> Vec<ComplianceRequirementSummary> {
+    let buckets = response
+        .pointer("/aggregations/requirements/buckets")
+        .and_then(|b| b.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    buckets
+        .iter()
+        .map(|bucket| ComplianceRequirementSummary {
+            requirement: bucket["key"].as_str().unwrap_or_default().to_string(),
+            alerts: bucket["doc_count"].as_u64().unwrap_or(0),
+            agents: bucket.pointer("/agents/value").and_then(|v| v.as_u64()).unwrap_or(0),
+            rules: bucket.pointer("/rules/value").and_then(|v| v.as_u64()).unwrap_or(0),
+            max_level: bucket
+                .pointer("/max_level/value")
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.0) as u32,
+        })
+        .collect()
+}
+
+/// When each agent but the manager (000) was registered
+fn registration_dates(agents: &[Agent]) -> Vec<DateTime<Utc>> {
+    agents
+        .iter()
+        .filter(|agent| agent.id != "000")
+        .filter_map(|agent| agent.date_add)
+        .collect()
+}
+
+/// Registrations and removals per period; `None` when the large result is declined
+async fn fleet_growth_report(
+    client: &WazuhClient,
+    group_by: &str,
+    last: Option<String>,
+    output: &OutputConfig,
+) -> Result<Option<FleetGrowthReport>> {
+    let cutoff = last
+        .map(|l| parse_duration(&l))
+        .transpose()?
+        .map(|window| Utc::now() - window);
+
+    let pb = spinner("Counting agents...");
+    let started = Instant::now();
+    let response = client.get("/agents?limit=1&select=id").await?;
+    let count: ApiResponse<AgentListResponse> = WazuhClient::parse_response(response).await?;
+    let estimate = Estimate {
+        total: count.data.total_affected_items as u64,
+        page_size: PAGE_SIZE as u64,
+        page_latency: started.elapsed(),
+    };
+    pb.finish_and_clear();
+
+    if !confirm_large_result(&estimate, "agents", output)? {
+        println!("Operation cancelled");
+        return Ok(None);
+    }
+
+    let pb = spinner("Fetching agent registrations...");
+    let mut progress = Progress::start("report fleet-growth", Some(estimate.total));
+    let url = "/agents?select=id,name,status,dateAdd";
+    let api_response: ApiResponse<AgentListResponse> = client.get_list(url).await?;
+    pb.finish_and_clear();
+    progress.page_fetched(api_response.data.affected_items.len() as u64);
+    progress.finish();
+
+    let registrations = registration_dates(&api_response.data.affected_items);
+
+    // The API only knows about agents that still exist; removals come from local history
+    let removals: Vec<DateTime<Utc>> = history::load()?
+        .into_iter()
+        .filter(|event| event.action == HistoryAction::Removed)
+        .map(|event| event.timestamp)
+        .collect();
+
+    Ok(Some(FleetGrowthReport {
+        group_by: group_by.to_string(),
+        current_agents: registrations.len() as u32,
+        periods: growth_periods(&registrations, &removals, group_by, cutoff),
+    }))
+}
+
+/// Rows in the ranked tables of generated reports
+const TOP_ROWS: usize = 25;
+
+/// Agents whose SCA results are fetched at once for the SCA report
+const SCA_CONCURRENCY: usize = 10;
+
+/// Counts as table rows, largest first, with their share of `total`
+fn count_rows(counts: BTreeMap<String, u64>, total: u64) -> Vec<Vec<String>> {
+    let mut counts: Vec<(String, u64)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    counts
+        .into_iter()
+        .map(|(key, count)| {
+            let share = count as f64 * 100.0 / total.max(1) as f64;
+            vec![key, count.to_string(), format!("{:.1}%", share)]
+        })
+        .collect()
+}
+
+async fn agents_document(client: &WazuhClient) -> Result<Document> {
+    let pb = spinner("Fetching agents...");
+    let url = "/agents?q=id!=000&select=id,name,ip,status,os.name,os.version,os.platform,\
+               version,lastKeepAlive";
+    let api_response: ApiResponse<AgentListResponse> = client.get_list(url).await?;
+    pb.finish_and_clear();
+
+    let agents = api_response.data.affected_items;
+    let total = agents.len() as u64;
+    let mut statuses = BTreeMap::new();
+    let mut systems = BTreeMap::new();
+    let mut versions = BTreeMap::new();
+    let mut rows = Vec::new();
+    for agent in &agents {
+        let os = agent
+            .os
+            .as_ref()
+            .and_then(|os| {
+                let name = os.name.as_ref().or(os.platform.as_ref())?;
+                Some(format!("{} {}", name, os.version.as_deref().unwrap_or("")))
+            })
+            .map(|os| os.trim().to_string())
+            .unwrap_or_else(|| "Unknown".to_string());
+        let version = agent.version.clone().unwrap_or_else(|| "Unknown".to_string());
+        *statuses.entry(agent.status.to_string()).or_insert(0) += 1;
+        *systems.entry(os.clone()).or_insert(0) += 1;
+        *versions.entry(version.clone()).or_insert(0) += 1;
+        rows.push(vec![
+            agent.id.clone(),
+            agent.name.clone(),
+            agent.ip.clone().unwrap_or_default(),
+            agent.status.to_string(),
+            os,
+            version,
+            agent
+                .last_keep_alive
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default(),
+        ]);
+    }
+
+    let mut summary = Section::new("Summary").fact("Agents", total);
+    for (status, count) in &statuses {
+        summary = summary.fact(status, count);
+    }
+
+    Ok(Document::new(
+        "Agent Inventory Report",
+        vec![
+            summary,
+            Section::new("Operating Systems").table(
+                &["Operating System", "Agents", "Share"],
+                count_rows(systems, total),
+                "No agents",
+            ),
+            Section::new("Agent Versions").table(
+                &["Version", "Agents", "Share"],
+                count_rows(versions, total),
+                "No agents",
+            ),
+            Section::new("Agents").table(
+                &["ID", "Name", "IP", "Status", "OS", "Version", "Last Keep Alive"],
+                rows,
+                "No agents are registered",
+            ),
+        ],
+    ))
+}
+
+async fn vulnerabilities_document(indexer: &IndexerClient) -> Result<Document> {
+    let pb = spinner("Aggregating vulnerabilities...");
+    let response = indexer
+        .search(VULNERABILITIES_INDEX, &vulnerability_aggregation())
+        .await?;
+    pb.finish_and_clear();
+    Ok(parse_vulnerability_document(&response))
+}
+
+/// Fleet-wide severity counts, the most widespread CVEs and the most exposed agents
+fn vulnerability_aggregation() -> Value {
+    json!({
+        "size": 0,
+        "track_total_hits": true,
+        "aggs": {
+            "agents": { "cardinality": { "field": "agent.id" } },
+            "severity": { "terms": { "field": "vulnerability.severity", "size": 10 } },
+            "cves": {
+                "terms": { "field": "vulnerability.id", "size": TOP_ROWS },
+                "aggs": {
+                    "severity": { "terms": { "field": "vulnerability.severity", "size": 1 } },
+                    "package": { "terms": { "field": "package.name", "size": 1 } },
+                    "agents": { "cardinality": { "field": "agent.id" } }
+                }
+            },
+            "exposed": {
+                "terms": { "field": "agent.name", "size": TOP_ROWS },
+                "aggs": {
+                    "critical": { "filter": { "term": { "vulnerability.severity": "Critical" } } }
+                }
+            }
+        }
+    })
+}
+
+fn parse_vulnerability_document(response: &Value) -> Document {
+    let buckets = |name: &str| {
+        response
+            .pointer(&format!("/aggregations/{}/buckets", name))
+            .and_then(|b| b.as_array())
+            .cloned()
+            .unwrap_or_default()
+    };
+    let first_key = |bucket: &Value, name: &str| {
+        bucket
+            .pointer(&format!("/{}/buckets/0/key", name))
+            .and_then(|k| k.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let count = |value: &Value, pointer: &str| value.pointer(pointer).and_then(|v| v.as_u64());
+
+    let total = count(response, "/hits/total/value").unwrap_or(0);
+    let mut severities: Vec<(String, u64)> = buckets("severity")
+        .iter()
+        .map(|b| {
+            let severity = b["key"].as_str().unwrap_or_default().to_string();
+            (severity, b["doc_count"].as_u64().unwrap_or(0))
+        })
+        .collect();
+    severities.sort_by_key(|(severity, _)| severity_rank(severity));
+
+    let mut summary = Section::new("Summary")
+        .fact("Vulnerabilities", total)
+        .fact("Affected Agents", count(response, "/aggregations/agents/value").unwrap_or(0));
+    for (severity, count) in &severities {
+        summary = summary.fact(severity, count);
+    }
+
+    let cves = buckets("cves")
+        .iter()
+        .map(|b| {
+            vec![
+                b["key"].as_str().unwrap_or_default().to_string(),
+                first_key(b, "severity"),
+                first_key(b, "package"),
+                count(b, "/agents/value").unwrap_or(0).to_string(),
+            ]
+        })
+        .collect();
+    let exposed = buckets("exposed")
+        .iter()
+        .map(|b| {
+            vec![
+                b["key"].as_str().unwrap_or_default().to_string(),
+                count(b, "/critical/doc_count").unwrap_or(0).to_string(),
+                b["doc_count"].as_u64().unwrap_or(0).to_string(),
+            ]
+        })
+        .collect();
+
+    Document::new(
+        "Vulnerability Report",
+        vec![
+            summary,
+            Section::new("Most Widespread CVEs").table(
+                &["CVE", "Severity", "Package", "Agents"],
+                cves,
+                "No vulnerabilities detected",
+            ),
+            Section::new("Most Exposed Agents").table(
+                &["Agent", "Critical", "Total"],
+                exposed,
+                "No vulnerabilities detected",
+            ),
+        ],
+    )
+}
+
+async fn sca_document(client: &WazuhClient) -> Result<Document> {
+    let pb = spinner("Collecting SCA results...");
+    let summary = fleet_sca_summary(client, SCA_CONCURRENCY).await?;
+    pb.finish_and_clear();
+
+    let policies = summary
+        .policies
+        .iter()
+        .map(|p| {
+            vec![
+                p.policy_id.clone(),
+                p.name.clone(),
+                p.agents.to_string(),
+                format!("{:.0}%", p.average_score),
+            ]
+        })
+        .collect();
+    let checks = summary
+        .checks
+        .iter()
+        .take(TOP_ROWS)
+        .map(|c| {
+            vec![
+                c.policy_id.clone(),
+                c.check_id.clone(),
+                c.title.clone(),
+                c.failing_agents.len().to_string(),
+            ]
+        })
+        .collect();
+
+    Ok(Document::new(
+        "Security Configuration Assessment Report",
+        vec![
+            Section::new("Summary")
+                .fact("Agents Scanned", summary.agents_scanned)
+                .fact("Policies", summary.policies.len())
+                .fact("Failed Checks", summary.checks.len()),
+            Section::new("Policies").table(
+                &["Policy", "Name", "Agents", "Average Score"],
+                policies,
+                "No SCA results from active agents",
+            ),
+            Section::new("Most Failed Checks").table(
+                &["Policy", "Check", "Title", "Failing Agents"],
+                checks,
+                "No failed checks",
+            ),
+        ],
+    ))
+}
+
+/// Label of the period a timestamp falls into
+fn period_key(ts: &DateTime<Utc>, group_by: &str) -> String {
+    match group_by {
+        "day" => ts.format("%Y-%m-%d").to_string(),
+        "week" => {
+            let week = ts.iso_week();
+            format!("{}-W{:02}", week.year(), week.week())
+        }
+        _ => ts.format("%Y-%m").to_string(),
+    }
+}
+
+fn growth_periods(
+    registrations: &[DateTime<Utc>],
+    removals: &[DateTime<Utc>],
+    group_by: &str,
+    cutoff: Option<DateTime<Utc>>,
+) -> Vec<FleetGrowthPeriod> {
+    let mut buckets: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+    for ts in registrations {
+        buckets.entry(period_key(ts, group_by)).or_default().0 += 1;
+    }
+    for ts in removals {
+        buckets.entry(period_key(ts, group_by)).or_default().1 += 1;
+    }
+
+    let first_shown = cutoff.map(|c| period_key(&c, group_by));
+    let mut cumulative = 0i64;
+
+    buckets
+        .into_iter()
+        .filter_map(|(period, (registered, removed))| {
+            let net = registered as i64 - removed as i64;
+            cumulative += net;
+            let shown = first_shown.as_ref().is_none_or(|first| period >= *first);
+            shown.then_some(FleetGrowthPeriod {
+                period,
+                registered,
+                removed,
+                net,
+                cumulative,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_growth_periods() {
+        let ts = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let registrations = vec![
+            ts("2024-01-05T00:00:00Z"),
+            ts("2024-01-20T00:00:00Z"),
+            ts("2024-03-01T00:00:00Z"),
+        ];
+        let removals = vec![ts("2024-03-15T00:00:00Z")];
+
+        let periods = growth_periods(&registrations, &removals, "month", None);
+        assert_eq!(periods.len(), 2);
+        assert_eq!(periods[0].period, "2024-01");
+        assert_eq!(periods[0].registered, 2);
+        assert_eq!(periods[1].net, 0);
+        assert_eq!(periods[1].cumulative, 2);
+
+        let cutoff = Some(ts("2024-02-10T00:00:00Z"));
+        let periods = growth_periods(&registrations, &removals, "month", cutoff);
+        assert_eq!(periods.len(), 1);
+        assert_eq!(periods[0].cumulative, 2);
+    }
+
+    #[test]
+    fn test_registration_dates() {
+        // Shaped like `GET /agents?select=id,name,status,dateAdd`
+        let response: ApiResponse<AgentListResponse> = serde_json::from_value(serde_json::json!({
+            "data": {
+                "affected_items": [
+                    {"id": "000", "name": "manager", "status": "active",
+                     "dateAdd": "2024-01-01T00:00:00Z"},
+                    {"id": "001", "name": "web-01", "status": "active",
+                     "dateAdd": "2024-03-05T10:00:00Z"},
+                    {"id": "002", "name": "db-01", "status": "never_connected"}
+                ],
+                "total_affected_items": 3,
+                "total_failed_items": 0,
+                "failed_items": []
+            },
+            "message": "All selected agents information was returned",
+            "error": 0
+        }))
+        .unwrap();
+        let dates = registration_dates(&response.data.affected_items);
+        assert_eq!(dates, vec![DateTime::parse_from_rfc3339("2024-03-05T10:00:00Z").unwrap()]);
+    }
+
+    #[test]
+    fn test_parse_vulnerability_document() {
+        let response = json!({
+            "hits": { "total": { "value": 7 } },
+            "aggregations": {
+                "agents": { "value": 2 },
+                "severity": { "buckets": [
+                    { "key": "Medium", "doc_count": 4 },
+                    { "key": "Critical", "doc_count": 3 }
+                ] },
+                "cves": { "buckets": [{
+                    "key": "CVE-2024-3094",
+                    "doc_count": 2,
+                    "severity": { "buckets": [{ "key": "Critical", "doc_count": 2 }] },
+                    "package": { "buckets": [{ "key": "xz-utils", "doc_count": 2 }] },
+                    "agents": { "value": 2 }
+                }] },
+                "exposed": { "buckets": [] }
+            }
+        });
+
+        let document = parse_vulnerability_document(&response);
+        let facts: Vec<&str> = document.sections[0].facts.iter().map(|f| f.0.as_str()).collect();
+        assert_eq!(facts, vec!["Vulnerabilities", "Affected Agents", "Critical", "Medium"]);
+        let cves = document.sections[1].table.as_ref().unwrap();
+        assert_eq!(cves.rows[0], vec!["CVE-2024-3094", "Critical", "xz-utils", "2"]);
+        assert!(document.sections[2].table.is_none());
+    }
+
+    #[test]
+    fn test_parse_requirement_buckets() {
+        let response = json!({
+            "aggregations": {
+                "requirements": {
+                    "buckets": [{
+                        "key": "10.2.4",
+                        "doc_count": 42,
+                        "agents": { "value": 3 },
+                        "rules": { "value": 2 },
+                        "max_level": { "value": 10.0 }
+                    }]
+                }
+            }
+        });
+
+        let rows = parse_requirement_buckets(&response);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].requirement, "10.2.4");
+        assert_eq!(rows[0].alerts, 42);
+        assert_eq!(rows[0].agents, 3);
+        assert_eq!(rows[0].max_level, 10);
+    }
+}
@@ -0,0 +1,94 @@
+use anyhow::Result;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::{
+    cli::{RootcheckAction, RootcheckCommand},
+    client::WazuhClient,
+    commands::agent::resolve_agent_id,
+    commands::fim::{clear_results, last_scan, print_last_scan, run_scan, ScanTarget},
+    config::Config,
+    models::{ApiResponse, ListResponse, RootcheckFinding, RootcheckParams},
+    output::{print_json, print_rootcheck_findings, spinner},
+};
+
+pub async fn handle_rootcheck_command(
+    cmd: RootcheckCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+
+    // Ensure we're authenticated
+    client.authenticate().await?;
+
+    match cmd.action {
+        RootcheckAction::List {
+            agent,
+            status,
+            pci_dss,
+            cis,
+            limit,
+        } => {
+            let agent = resolve_agent_id(&client, &agent).await?;
+            let params = RootcheckParams {
+                limit: Some(limit),
+                status: Some(status),
+                pci_dss,
+                cis,
+            };
+            list_findings(&client, &agent, &params, json_output).await?
+        }
+
+        RootcheckAction::LastScan { agent } => {
+            let agent = resolve_agent_id(&client, &agent).await?;
+            let scan = last_scan(&client, "rootcheck", &agent).await?;
+            if json_output {
+                print_json(&scan)?;
+            } else {
+                print_last_scan("rootcheck", &agent, &scan);
+            }
+        }
+
+        RootcheckAction::Run { agent, group, yes, .. } => {
+            let agent = match agent {
+                Some(agent) => Some(resolve_agent_id(&client, &agent).await?),
+                None => None,
+            };
+            let target = ScanTarget::from_args(agent, group);
+            run_scan(&client, "rootcheck", "rootcheck", &target, yes, json_output).await?
+        }
+
+        RootcheckAction::Clear { agent, yes } => {
+            let agent = resolve_agent_id(&client, &agent).await?;
+            let url = format!("/rootcheck?agents_list={}", agent);
+            clear_results(&client, &url, "rootcheck", &agent, yes, json_output).await?
+        }
+    }
+
+    Ok(())
+}
+
+async fn list_findings(
+    client: &WazuhClient,
+    agent: &str,
+    params: &RootcheckParams,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Fetching rootcheck findings...");
+    let url = format!("/rootcheck/{}?{}", agent, serde_urlencoded::to_string(params)?);
+    debug!("Fetching rootcheck findings with {}", url);
+    let api_response: ApiResponse<ListResponse<RootcheckFinding>> = client.get_list(&url).await?;
+    pb.finish_and_clear();
+
+    let findings = api_response.data.affected_items;
+    if json_output {
+        print_json(&findings)?;
+    } else {
+        print_rootcheck_findings(&findings);
+        println!("\nTotal: {} findings", findings.len());
+    }
+
+    Ok(())
+}
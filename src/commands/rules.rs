@@ -0,0 +1,176 @@
+use anyhow::Result;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::{
+    cli::{RulesAction, RulesCommand},
+    client::WazuhClient,
+    compliance::{ComplianceFilter, Standard},
+    config::Config,
+    error::WazuhError,
+    models::{ApiResponse, ListResponse, Rule, RuleParams},
+    output::{print_json, print_names, print_rules_table, print_single_rule, spinner},
+};
+
+pub async fn handle_rules_command(
+    cmd: RulesCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    let config = Arc::new(RwLock::new(config.clone()));
+    let client = WazuhClient::new(config).await?;
+
+    // Ensure we're authenticated
+    client.authenticate().await?;
+
+    match cmd.action {
+        RulesAction::List {
+            level,
+            group,
+            filename,
+            compliance,
+            limit,
+        } => {
+            let filter = RuleFilter {
+                search: None,
+                level,
+                group,
+                filename,
+                compliance,
+                limit,
+            };
+            list_rules(&client, filter, json_output).await?
+        }
+
+        RulesAction::Get { rule } => get_rule(&client, rule, json_output).await?,
+
+        RulesAction::Search {
+            query,
+            level,
+            group,
+            filename,
+            compliance,
+            limit,
+        } => {
+            let filter = RuleFilter {
+                search: Some(query),
+                level,
+                group,
+                filename,
+                compliance,
+                limit,
+            };
+            list_rules(&client, filter, json_output).await?
+        }
+
+        RulesAction::Groups => {
+            list_names(&client, "/rules/groups", "Group", json_output).await?
+        }
+
+        RulesAction::Requirements { standard } => {
+            let standard: Standard = standard.parse()?;
+            let url = format!("/rules/requirement/{}", standard.api_param());
+            list_names(&client, &url, "Requirement", json_output).await?
+        }
+    }
+
+    Ok(())
+}
+
+/// Filters shared by `rules list` and `rules search`
+struct RuleFilter {
+    search: Option<String>,
+    level: Option<String>,
+    group: Option<String>,
+    filename: Option<String>,
+    compliance: Option<String>,
+    limit: u32,
+}
+
+async fn list_rules(client: &WazuhClient, filter: RuleFilter, json_output: bool) -> Result<()> {
+    let compliance: Option<ComplianceFilter> =
+        filter.compliance.map(|c| c.parse()).transpose()?;
+
+    let pb = spinner("Fetching rules...");
+
+    let params = RuleParams {
+        limit: Some(filter.limit),
+        search: filter.search,
+        level: filter.level,
+        group: filter.group,
+        filename: filter.filename,
+        compliance: compliance.as_ref().and_then(|c| {
+            c.requirement
+                .clone()
+                .map(|req| (c.standard.api_param().to_string(), req))
+        }),
+        ..Default::default()
+    };
+
+    let url = format!("/rules?{}", params.to_query_string()?);
+
+    debug!("Fetching rules with params: {:?}", params);
+    let api_response: ApiResponse<ListResponse<Rule>> = client.get_list(&url).await?;
+
+    pb.finish_and_clear();
+
+    let mut rules = api_response.data.affected_items;
+
+    // The API filters by requirement value only; a bare standard matches any mapping
+    if let Some(filter) = compliance.filter(|c| c.requirement.is_none()) {
+        rules.retain(|rule| !rule.requirements(filter.standard).is_empty());
+    }
+
+    if json_output {
+        print_json(&rules)?;
+    } else {
+        print_rules_table(&rules);
+        println!("\nTotal: {} rules", rules.len());
+    }
+
+    Ok(())
+}
+
+async fn get_rule(client: &WazuhClient, rule_id: u32, json_output: bool) -> Result<()> {
+    let params = RuleParams {
+        rule_ids: Some(rule_id.to_string()),
+        ..Default::default()
+    };
+    let url = format!("/rules?{}", params.to_query_string()?);
+    let response = client.get(&url).await?;
+    let api_response: ApiResponse<ListResponse<Rule>> =
+        WazuhClient::parse_response(response).await?;
+
+    let rule = api_response
+        .data
+        .affected_items
+        .into_iter()
+        .next()
+        .ok_or_else(|| WazuhError::NotFound(format!("rule {}", rule_id)))?;
+
+    if json_output {
+        print_json(&rule)
+    } else {
+        print_single_rule(&rule);
+        Ok(())
+    }
+}
+
+/// List an endpoint whose items are plain names, such as rule groups
+async fn list_names(
+    client: &WazuhClient,
+    url: &str,
+    subject: &str,
+    json_output: bool,
+) -> Result<()> {
+    let api_response: ApiResponse<ListResponse<String>> = client.get_list(url).await?;
+    let names = api_response.data.affected_items;
+
+    if json_output {
+        print_json(&names)
+    } else {
+        print_names(&names, subject);
+        Ok(())
+    }
+}
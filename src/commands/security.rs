@@ -0,0 +1,158 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::{
+    certs,
+    cli::{SecurityAction, SecurityCommand},
+    commands::rbac,
+    config::Config,
+    models::CertificateStatus,
+    output::{print_certificate_audit, print_info, print_json, spinner},
+    progress::Progress,
+    ssh::{shell_quote, SshTarget},
+};
+
+pub async fn handle_security_command(
+    cmd: SecurityCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    match cmd.action {
+        SecurityAction::CertAudit {
+            paths,
+            host,
+            port,
+            identity,
+            warn_days,
+            no_endpoints,
+        } => {
+            let target = host.map(|destination| SshTarget {
+                destination,
+                port,
+                identity,
+            });
+            let options = CertAuditOptions {
+                explicit_paths: !paths.is_empty(),
+                paths: if paths.is_empty() {
+                    config.cert_audit.paths.clone()
+                } else {
+                    paths
+                },
+                warn_days: warn_days.unwrap_or(config.cert_audit.warn_days),
+                endpoints: !no_endpoints,
+            };
+            cert_audit(config, target.as_ref(), options, json_output).await?
+        }
+
+        SecurityAction::Users { action } => rbac::handle_users(action, config, json_output).await?,
+        SecurityAction::Roles { action } => rbac::handle_roles(action, config, json_output).await?,
+        SecurityAction::Policies { action } => {
+            rbac::handle_policies(action, config, json_output).await?
+        }
+    }
+
+    Ok(())
+}
+
+struct CertAuditOptions {
+    paths: Vec<PathBuf>,
+    /// Paths were given on the command line, so missing files are reported
+    explicit_paths: bool,
+    warn_days: i64,
+    endpoints: bool,
+}
+
+async fn cert_audit(
+    config: &Config,
+    target: Option<&SshTarget>,
+    options: CertAuditOptions,
+    json_output: bool,
+) -> Result<()> {
+    let pb = spinner("Auditing certificates...");
+    let mut progress = Progress::start("security cert-audit", None);
+    let now = Utc::now();
+    let mut statuses: Vec<CertificateStatus> = Vec::new();
+
+    if options.endpoints {
+        let mut endpoints = vec![config.api_url(), config.indexer_url()];
+        endpoints.extend(config.cert_audit.dashboard_url.clone());
+        let timeout = Duration::from_secs(config.api.timeout);
+
+        for url in endpoints.into_iter().filter(|url| url.starts_with("https://")) {
+            let status = match certs::fetch_peer_certificate(&url, timeout).await {
+                Ok(der) => certs::inspect_der(&url, &der, now, options.warn_days),
+                Err(e) => certs::missing(&url, &format!("unreachable: {:#}", e)),
+            };
+            record_status(&mut progress, &status);
+            statuses.push(status);
+        }
+    }
+
+    let mut skipped = 0;
+    for path in &options.paths {
+        let source = match target {
+            Some(target) => format!("{}:{}", target.destination, path.display()),
+            None => path.display().to_string(),
+        };
+
+        let contents = match target {
+            Some(target) => {
+                let quoted = shell_quote(&path.to_string_lossy());
+                let command = format!("sudo -n cat {0} 2>/dev/null || cat {0}", quoted);
+                let output = target.run(&command, None).await?;
+                if output.success {
+                    Ok(output.stdout)
+                } else {
+                    Err(output.stderr.trim().to_string())
+                }
+            }
+            None if !path.exists() && !options.explicit_paths => {
+                skipped += 1;
+                continue;
+            }
+            None => std::fs::read(path).map_err(|e| e.to_string()),
+        };
+
+        let found = match contents {
+            Ok(pem) => certs::inspect_pem(&source, &pem, now, options.warn_days),
+            Err(e) => vec![certs::missing(&source, &format!("not readable: {}", e))],
+        };
+        for status in found {
+            record_status(&mut progress, &status);
+            statuses.push(status);
+        }
+    }
+
+    pb.finish_and_clear();
+    progress.finish();
+
+    if json_output {
+        print_json(&statuses)?;
+    } else {
+        print_certificate_audit(&statuses, options.warn_days);
+        if skipped > 0 {
+            print_info(&format!(
+                "{} configured certificate files not found locally; use --host to audit them on the manager",
+                skipped
+            ));
+        }
+    }
+
+    let expired = statuses
+        .iter()
+        .filter(|s| s.issues.iter().any(|i| i == "expired"))
+        .count();
+    if expired > 0 {
+        return Err(anyhow!("{} certificate(s) have expired", expired));
+    }
+
+    Ok(())
+}
+
+/// Report an audited certificate; any issue counts as a failed item
+fn record_status(progress: &mut Progress, status: &CertificateStatus) {
+    let issues = status.issues.join(", ");
+    progress.item_done(&status.source, (!issues.is_empty()).then_some(issues.as_str()));
+}
@@ -0,0 +1,238 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use colored::Colorize;
+use dialoguer::Confirm;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::{Duration as StdDuration, Instant};
+use tokio::sync::RwLock;
+
+use crate::{
+    cli::{SelftestAction, SelftestCommand},
+    client::WazuhClient,
+    config::Config,
+    indexer::IndexerClient,
+    output::{print_json, print_warning, spinner, WarningKind},
+    utils::parse_duration,
+};
+
+/// Time between indexer searches for the synthetic alert
+const POLL_INTERVAL: StdDuration = StdDuration::from_secs(2);
+
+/// Source address of the synthetic event, from the documentation range (RFC 5737)
+const TEST_SOURCE_IP: &str = "192.0.2.1";
+
+pub async fn handle_selftest_command(
+    cmd: SelftestCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    match cmd.action {
+        SelftestAction::Pipeline {
+            timeout,
+            skip_logtest,
+            yes,
+        } => {
+            let timeout = parse_duration(&timeout)?.to_std()?;
+            pipeline(config, timeout, skip_logtest, yes, json_output).await
+        }
+    }
+}
+
+/// Outcome of one round trip through the ingestion pipeline
+#[derive(Debug, Serialize)]
+struct PipelineResult {
+    tag: String,
+    sent_at: DateTime<Utc>,
+    /// Rule logtest matched the event against, when it was run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    logtest_rule: Option<String>,
+    rule_id: Option<String>,
+    alert_id: Option<String>,
+    /// Time until analysisd raised the alert, from the alert's own timestamp
+    processed_after_ms: Option<i64>,
+    /// Time until the alert could be found in the indexer
+    indexed_after_ms: u128,
+}
+
+/// Syslog line the stock sshd rules turn into an alert; the tag in the user
+/// name identifies it among all other alerts
+fn synthetic_event(tag: &str, now: DateTime<Utc>) -> String {
+    format!(
+        "{} wazuh-cli sshd[{}]: Invalid user {} from {} port 22",
+        now.format("%b %e %H:%M:%S"),
+        std::process::id(),
+        tag,
+        TEST_SOURCE_IP
+    )
+}
+
+fn alert_query(tag: &str, sent_at: DateTime<Utc>) -> Value {
+    // The tag is unique; the time range only narrows the search, with slack
+    // for a manager clock running behind ours
+    let from = sent_at - Duration::minutes(10);
+    json!({
+        "size": 1,
+        "query": {"bool": {"filter": [
+            {"match_phrase": {"full_log": tag}},
+            {"range": {"timestamp": {"gte": from.to_rfc3339()}}}
+        ]}},
+        "sort": [{"timestamp": {"order": "asc"}}]
+    })
+}
+
+async fn pipeline(
+    config: &Config,
+    timeout: StdDuration,
+    skip_logtest: bool,
+    yes: bool,
+    json_output: bool,
+) -> Result<()> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+    client.authenticate().await?;
+    let indexer = IndexerClient::new(config)?;
+
+    let tag = format!("wazuh-cli-selftest-{}", Utc::now().timestamp_millis());
+    if !yes {
+        let confirm = Confirm::new()
+            .with_prompt(format!(
+                "Send a synthetic sshd event to {}? It raises a real alert tagged '{}'",
+                config.api_url(),
+                tag
+            ))
+            .default(false)
+            .interact()?;
+        if !confirm {
+            println!("Operation cancelled");
+            return Ok(());
+        }
+    }
+
+    let logtest_rule = if skip_logtest {
+        None
+    } else {
+        logtest(&client, &synthetic_event(&tag, Utc::now())).await?
+    };
+
+    let pb = spinner("Sending synthetic event...");
+    let sent_at = Utc::now();
+    let started = Instant::now();
+    let body = json!({ "events": [synthetic_event(&tag, sent_at)] });
+    let response = client.post("/events", Some(body)).await?;
+    let sent: Value = WazuhClient::parse_response(response).await?;
+    pb.finish_and_clear();
+    if sent["data"]["total_failed_items"].as_u64().unwrap_or(0) > 0 {
+        return Err(anyhow!(
+            "The manager rejected the synthetic event: {}",
+            sent["data"]["failed_items"]
+        ));
+    }
+
+    let pb = spinner(&format!("Waiting for the alert tagged {}...", tag));
+    let query = alert_query(&tag, sent_at);
+    let alert = loop {
+        let response = indexer.search(indexer.alerts_index(), &query).await?;
+        if let Some(hit) = response.pointer("/hits/hits/0/_source") {
+            break hit.clone();
+        }
+        if started.elapsed() >= timeout {
+            pb.finish_and_clear();
+            return Err(anyhow!(
+                "No alert for the synthetic event {} appeared in {} within {}s; \
+                 check analysisd, the indexer connector and Filebeat",
+                tag,
+                indexer.alerts_index(),
+                timeout.as_secs()
+            ));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    };
+    pb.finish_and_clear();
+
+    let processed_at = alert["timestamp"]
+        .as_str()
+        .and_then(|ts| DateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S%.3f%z").ok())
+        .map(|ts| ts.with_timezone(&Utc));
+    let result = PipelineResult {
+        tag,
+        sent_at,
+        logtest_rule,
+        rule_id: alert["rule"]["id"].as_str().map(String::from),
+        alert_id: alert["id"].as_str().map(String::from),
+        processed_after_ms: processed_at.map(|at| (at - sent_at).num_milliseconds()),
+        indexed_after_ms: started.elapsed().as_millis(),
+    };
+
+    if json_output {
+        return print_json(&result);
+    }
+    println!(
+        "{} Synthetic alert {} (rule {}) indexed after {:.1}s",
+        "✓".green().bold(),
+        result.alert_id.as_deref().unwrap_or("-"),
+        result.rule_id.as_deref().unwrap_or("-"),
+        result.indexed_after_ms as f64 / 1000.0
+    );
+    if let Some(ms) = result.processed_after_ms {
+        println!("  Processed by analysisd after {:.1}s", ms as f64 / 1000.0);
+    }
+    Ok(())
+}
+
+/// Check that the ruleset turns the event into an alert before sending it for
+/// real; managers without logtest only get a warning
+async fn logtest(client: &WazuhClient, event: &str) -> Result<Option<String>> {
+    let body = json!({
+        "event": event,
+        "log_format": "syslog",
+        "location": "wazuh-cli",
+    });
+    let output: Result<Value> =
+        async { WazuhClient::parse_response(client.put("/logtest", Some(body)).await?).await }
+            .await;
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => {
+            print_warning(
+                WarningKind::Unsupported,
+                &format!("Logtest unavailable, sending without a dry run: {:#}", e),
+            );
+            return Ok(None);
+        }
+    };
+
+    if let Some(token) = output["data"]["token"].as_str() {
+        // Sessions count against the manager's logtest limit until they expire
+        let _ = client.delete(&format!("/logtest/sessions/{}", token)).await;
+    }
+
+    let rule = &output["data"]["output"]["rule"];
+    if output["data"]["alert"].as_bool() != Some(true) {
+        return Err(anyhow!(
+            "Logtest did not raise an alert for the synthetic event (rule {}); \
+             the ruleset may have been changed",
+            rule["id"].as_str().unwrap_or("none")
+        ));
+    }
+    Ok(rule["id"].as_str().map(String::from))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_event() {
+        let now = DateTime::parse_from_rfc3339("2024-03-05T09:04:05Z").unwrap().to_utc();
+        let event = synthetic_event("wazuh-cli-selftest-1", now);
+        assert!(event.starts_with("Mar  5 09:04:05 wazuh-cli sshd["));
+        assert!(event.ends_with("]: Invalid user wazuh-cli-selftest-1 from 192.0.2.1 port 22"));
+
+        let query = alert_query("wazuh-cli-selftest-1", now);
+        assert_eq!(
+            query["query"]["bool"]["filter"][0]["match_phrase"]["full_log"],
+            "wazuh-cli-selftest-1"
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use anyhow::Result;
+use chrono::Utc;
+use std::path::Path;
+
+use crate::{
+    cli::{StatsAction, StatsCommand},
+    config::Config,
+    output::{print_info, print_json, print_success, print_usage_summary},
+    usage,
+    utils::parse_duration,
+};
+
+pub async fn handle_stats_command(
+    cmd: StatsCommand,
+    config: &Config,
+    config_path: &Path,
+    json_output: bool,
+) -> Result<()> {
+    match cmd.action {
+        StatsAction::Cli { since, top } => show_usage(config, since.as_deref(), top, json_output),
+        StatsAction::Enable => set_enabled(config_path, true),
+        StatsAction::Disable => set_enabled(config_path, false),
+        StatsAction::Clear => {
+            usage::clear()?;
+            print_success("Usage statistics cleared");
+            Ok(())
+        }
+    }
+}
+
+fn show_usage(config: &Config, since: Option<&str>, top: usize, json_output: bool) -> Result<()> {
+    let since = since.map(parse_duration).transpose()?.map(|span| Utc::now() - span);
+    let summary = usage::summarize(&usage::load()?, since);
+
+    if json_output {
+        return print_json(&summary);
+    }
+
+    if !config.stats.enabled {
+        print_info("Usage statistics are disabled; run 'wazuh-cli stats enable' to record them");
+    }
+    if summary.total_runs == 0 {
+        print_info("No usage recorded yet");
+        return Ok(());
+    }
+
+    print_usage_summary(&summary, top);
+    println!(
+        "\n{} runs recorded in {}",
+        summary.total_runs,
+        usage::usage_path()?.display()
+    );
+
+    Ok(())
+}
+
+/// Toggle `[stats] enabled` in the configuration file itself
+///
+/// The file is reloaded so flags such as `--header` are not saved with it.
+fn set_enabled(config_path: &Path, enabled: bool) -> Result<()> {
+    Config::update(config_path, |config| {
+        config.stats.enabled = enabled;
+        Ok(())
+    })?;
+
+    if enabled {
+        print_success(&format!(
+            "Usage statistics enabled; recorded locally in {}",
+            usage::usage_path()?.display()
+        ));
+    } else {
+        print_success("Usage statistics disabled");
+    }
+
+    Ok(())
+}
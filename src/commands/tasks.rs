@@ -0,0 +1,267 @@
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::{
+    cli::{TasksAction, TasksCommand},
+    client::WazuhClient,
+    commands::agent::resolve_agent_ids,
+    config::Config,
+    error::WazuhError,
+    models::{ApiResponse, ListResponse, Task, TaskParams},
+    output::{print_json, print_task, print_tasks, spinner},
+    utils::parse_duration,
+};
+
+pub async fn handle_tasks_command(
+    cmd: TasksCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+
+    // Ensure we're authenticated
+    client.authenticate().await?;
+
+    match cmd.action {
+        TasksAction::List {
+            agent,
+            command,
+            status,
+            limit,
+            watch,
+            interval,
+        } => {
+            let agent = match agent {
+                Some(agent) => {
+                    let agents: Vec<String> =
+                        agent.split(',').map(|a| a.trim().to_string()).collect();
+                    Some(resolve_agent_ids(&client, &agents).await?.join(","))
+                }
+                None => None,
+            };
+            let params = TaskParams {
+                limit: Some(limit),
+                sort: Some("-last_update_time".to_string()),
+                agents_list: agent,
+                command,
+                status: status.as_deref().map(api_status),
+                ..Default::default()
+            };
+            let watched = if watch {
+                let interval = parse_duration(&interval)?.to_std()?;
+                watch_tasks(&client, &params, interval).await?
+            } else {
+                let pb = spinner("Fetching tasks...");
+                let tasks = fetch_tasks(&client, &params).await?;
+                pb.finish_and_clear();
+                Watched::none(tasks)
+            };
+            let tasks = &watched.tasks;
+
+            if json_output {
+                print_json(tasks)?;
+            } else {
+                print_tasks(tasks);
+                println!("\nTotal: {} tasks", tasks.len());
+            }
+            if watch {
+                watched.check_outcome()?;
+            }
+        }
+
+        TasksAction::Status {
+            task_id,
+            watch,
+            interval,
+        } => {
+            let params = TaskParams {
+                tasks_list: Some(task_id.to_string()),
+                ..Default::default()
+            };
+            let watched = if watch {
+                let interval = parse_duration(&interval)?.to_std()?;
+                watch_tasks(&client, &params, interval).await?
+            } else {
+                Watched::none(fetch_tasks(&client, &params).await?)
+            };
+            let task = watched
+                .tasks
+                .first()
+                .ok_or_else(|| WazuhError::NotFound(format!("task {}", task_id)))?;
+
+            if json_output {
+                print_json(task)?;
+            } else {
+                print_task(task);
+            }
+            if watch {
+                watched.check_outcome()?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn fetch_tasks(client: &WazuhClient, params: &TaskParams) -> Result<Vec<Task>> {
+    let url = format!("/tasks/status?{}", serde_urlencoded::to_string(params)?);
+    debug!("Fetching tasks with {}", url);
+    let api_response: ApiResponse<ListResponse<Task>> = client.get_list(&url).await?;
+    Ok(api_response.data.affected_items)
+}
+
+/// Tasks as last fetched, with the IDs of those a watch waited on
+struct Watched {
+    tasks: Vec<Task>,
+    pending: HashSet<u32>,
+}
+
+impl Watched {
+    fn none(tasks: Vec<Task>) -> Self {
+        Self {
+            tasks,
+            pending: HashSet::new(),
+        }
+    }
+
+    /// Fail when a watched task did not complete, so scripts can wait on upgrades
+    fn check_outcome(&self) -> Result<()> {
+        let watched = || self.tasks.iter().filter(|task| self.pending.contains(&task.task_id));
+        let unfinished = watched().filter(|task| !task.is_finished()).count();
+        let failed = watched()
+            .filter(|task| task.is_finished() && task.status != "Done")
+            .count();
+        if unfinished > 0 {
+            return Err(anyhow!("{} tasks have not finished", unfinished));
+        }
+        if failed > 0 {
+            return Err(anyhow!("{} tasks did not complete successfully", failed));
+        }
+        Ok(())
+    }
+}
+
+/// Poll until every task pending at the start has finished, or until interrupted
+///
+/// Tasks that had already finished are history and are not judged; the pending
+/// ones are followed by ID, since they leave filters such as `--status` as they
+/// change state.
+async fn watch_tasks(
+    client: &WazuhClient,
+    params: &TaskParams,
+    interval: Duration,
+) -> Result<Watched> {
+    let pb = spinner("Waiting for tasks...");
+    let mut tasks = fetch_tasks(client, params).await?;
+    let pending: HashSet<u32> = tasks
+        .iter()
+        .filter(|task| !task.is_finished())
+        .map(|task| task.task_id)
+        .collect();
+    let mut ids: Vec<u32> = pending.iter().copied().collect();
+    ids.sort_unstable();
+    let follow = TaskParams {
+        tasks_list: Some(ids.iter().map(u32::to_string).collect::<Vec<_>>().join(",")),
+        ..Default::default()
+    };
+
+    loop {
+        let running = tasks
+            .iter()
+            .filter(|task| pending.contains(&task.task_id) && !task.is_finished())
+            .count();
+        if running == 0 {
+            pb.finish_and_clear();
+            return Ok(Watched { tasks, pending });
+        }
+        pb.set_message(format!(
+            "Waiting for tasks ({} of {} finished)...",
+            pending.len() - running,
+            pending.len()
+        ));
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {
+                pb.finish_and_clear();
+                return Ok(Watched { tasks, pending });
+            }
+            _ = tokio::time::sleep(interval) => {}
+        }
+
+        for update in fetch_tasks(client, &follow).await? {
+            if let Some(task) = tasks.iter_mut().find(|task| task.task_id == update.task_id) {
+                *task = update;
+            }
+        }
+    }
+}
+
+/// Status as the API spells it: `in-progress` becomes `In progress`
+fn api_status(status: &str) -> String {
+    let status = status.trim().replace(['-', '_'], " ").to_lowercase();
+    let mut chars = status.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => status,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_api_status() {
+        assert_eq!(api_status("in-progress"), "In progress");
+        assert_eq!(api_status("IN_QUEUE"), "In queue");
+        assert_eq!(api_status("Done"), "Done");
+    }
+
+    #[test]
+    fn test_check_outcome() {
+        let task = |id: u32, status: &str| -> Task {
+            serde_json::from_value(json!({
+                "task_id": id,
+                "agent_id": "001",
+                "command": "upgrade",
+                "status": status,
+                "create_time": null,
+                "last_update_time": null,
+            }))
+            .unwrap()
+        };
+        let watched = |tasks: Vec<Task>| Watched {
+            pending: tasks.iter().map(|task| task.task_id).collect(),
+            tasks,
+        };
+        assert!(watched(vec![task(1, "Done"), task(2, "Done")]).check_outcome().is_ok());
+        assert!(watched(vec![task(1, "Done"), task(2, "Failed")]).check_outcome().is_err());
+        assert!(watched(vec![task(1, "In progress")]).check_outcome().is_err());
+    }
+
+    #[test]
+    fn test_check_outcome_ignores_unwatched_tasks() {
+        let task = |id: u32, status: &str| -> Task {
+            serde_json::from_value(json!({
+                "task_id": id,
+                "agent_id": "001",
+                "command": "upgrade",
+                "status": status,
+                "create_time": null,
+                "last_update_time": null,
+            }))
+            .unwrap()
+        };
+        // Task 1 failed before the watch started
+        let watched = Watched {
+            tasks: vec![task(1, "Failed"), task(2, "Done")],
+            pending: HashSet::from([2]),
+        };
+        assert!(watched.check_outcome().is_ok());
+    }
+}
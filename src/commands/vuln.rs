@@ -0,0 +1,305 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::{
+    cli::{VulnAction, VulnCommand},
+    client::WazuhClient,
+    commands::agent::resolve_agent_id,
+    config::Config,
+    indexer::{hits, IndexerClient, VULNERABILITIES_INDEX},
+    models::{ApiResponse, ListResponse, Vulnerability, VulnerabilitySummary},
+    output::{
+        print_json, print_vulnerabilities, print_vulnerability_summary, print_warning, spinner,
+        WarningKind,
+    },
+    utils::{is_agent_id, json_field, json_to_text, normalize_agent_id},
+};
+
+/// Filters of `vuln list`
+struct VulnFilter {
+    severity: Option<String>,
+    cve: Option<String>,
+    limit: u32,
+}
+
+pub async fn handle_vuln_command(
+    cmd: VulnCommand,
+    config: &Config,
+    json_output: bool,
+) -> Result<()> {
+    match cmd.action {
+        VulnAction::List {
+            agent,
+            severity,
+            cve,
+            limit,
+        } => {
+            let agent = agent_id(config, &agent).await?;
+            let filter = VulnFilter {
+                severity: severity.as_deref().map(capitalize),
+                cve,
+                limit,
+            };
+            let pb = spinner("Fetching vulnerabilities...");
+            // Wazuh 4.8+ keeps vulnerability states in the indexer; older
+            // managers serve them from the API
+            let result = match list_from_indexer(config, &agent, &filter).await {
+                Ok(vulnerabilities) => Ok(vulnerabilities),
+                Err(e) => {
+                    print_warning(WarningKind::Fallback, &fallback_message(&e));
+                    list_from_api(config, &agent, &filter).await
+                }
+            };
+            pb.finish_and_clear();
+
+            let mut vulnerabilities = result?;
+            sort_by_severity(&mut vulnerabilities);
+            if json_output {
+                print_json(&vulnerabilities)?;
+            } else {
+                print_vulnerabilities(&vulnerabilities);
+                println!("\nTotal: {} vulnerabilities", vulnerabilities.len());
+            }
+        }
+
+        VulnAction::Summary { agent } => {
+            let agent = agent_id(config, &agent).await?;
+            let pb = spinner("Counting vulnerabilities...");
+            let result = match summary_from_indexer(config, &agent).await {
+                Ok(counts) => Ok(counts),
+                Err(e) => {
+                    print_warning(WarningKind::Fallback, &fallback_message(&e));
+                    summary_from_api(config, &agent).await
+                }
+            };
+            pb.finish_and_clear();
+
+            let summary = summarize(&agent, result?);
+            if json_output {
+                print_json(&summary)?;
+            } else {
+                print_vulnerability_summary(&summary);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The ID of an agent given by ID or name; only a name needs the manager API
+async fn agent_id(config: &Config, agent: &str) -> Result<String> {
+    if is_agent_id(agent) {
+        return Ok(normalize_agent_id(agent)?);
+    }
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+    client.authenticate().await?;
+    resolve_agent_id(&client, agent).await
+}
+
+fn fallback_message(error: &anyhow::Error) -> String {
+    format!(
+        "Vulnerability states index unavailable ({:#}); querying the manager API instead",
+        error
+    )
+}
+
+/// `critical` as stored by the vulnerability detector: `Critical`
+fn capitalize(severity: &str) -> String {
+    let lower = severity.to_lowercase();
+    let mut chars = lower.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => lower,
+    }
+}
+
+/// Ordering of severities from critical down, unknown values last
+pub fn severity_rank(severity: &str) -> u8 {
+    match severity.to_lowercase().as_str() {
+        "critical" => 0,
+        "high" => 1,
+        "medium" => 2,
+        "low" => 3,
+        _ => 4,
+    }
+}
+
+fn sort_by_severity(vulnerabilities: &mut [Vulnerability]) {
+    vulnerabilities.sort_by(|a, b| {
+        severity_rank(&a.severity)
+            .cmp(&severity_rank(&b.severity))
+            .then(b.score.unwrap_or(0.0).total_cmp(&a.score.unwrap_or(0.0)))
+            .then(a.cve.cmp(&b.cve))
+    });
+}
+
+fn summarize(agent: &str, mut severities: Vec<(String, u64)>) -> VulnerabilitySummary {
+    severities.sort_by_key(|(severity, _)| severity_rank(severity));
+    VulnerabilitySummary {
+        agent_id: agent.to_string(),
+        total: severities.iter().map(|(_, count)| count).sum(),
+        severities,
+    }
+}
+
+fn agent_filters(agent: &str, filter: Option<&VulnFilter>) -> Vec<Value> {
+    let mut filters = vec![json!({"term": {"agent.id": agent}})];
+    if let Some(filter) = filter {
+        if let Some(severity) = &filter.severity {
+            filters.push(json!({"term": {"vulnerability.severity": severity}}));
+        }
+        if let Some(cve) = &filter.cve {
+            filters.push(json!({"term": {"vulnerability.id": cve}}));
+        }
+    }
+    filters
+}
+
+async fn list_from_indexer(
+    config: &Config,
+    agent: &str,
+    filter: &VulnFilter,
+) -> Result<Vec<Vulnerability>> {
+    let indexer = IndexerClient::new(config)?;
+    let body = json!({
+        "size": filter.limit,
+        "query": {"bool": {"filter": agent_filters(agent, Some(filter))}},
+        "sort": [{"vulnerability.score.base": {"order": "desc", "unmapped_type": "float"}}]
+    });
+    let response = indexer.search(VULNERABILITIES_INDEX, &body).await?;
+    Ok(hits(&response).iter().map(from_state).collect())
+}
+
+/// Vulnerability from a document of the states index
+fn from_state(doc: &Value) -> Vulnerability {
+    let text = |path: &str| json_field(doc, path).and_then(Value::as_str).map(String::from);
+    Vulnerability {
+        cve: json_to_text(json_field(doc, "vulnerability.id")),
+        severity: json_to_text(json_field(doc, "vulnerability.severity")),
+        score: json_field(doc, "vulnerability.score.base").and_then(Value::as_f64),
+        package: text("package.name"),
+        version: text("package.version"),
+        title: text("vulnerability.description"),
+        published: text("vulnerability.published_at"),
+    }
+}
+
+async fn list_from_api(
+    config: &Config,
+    agent: &str,
+    filter: &VulnFilter,
+) -> Result<Vec<Vulnerability>> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+    client.authenticate().await?;
+
+    let mut params = vec![("limit", filter.limit.to_string())];
+    if let Some(severity) = &filter.severity {
+        params.push(("severity", severity.clone()));
+    }
+    if let Some(cve) = &filter.cve {
+        params.push(("cve", cve.clone()));
+    }
+    let url = format!("/vulnerability/{}?{}", agent, serde_urlencoded::to_string(&params)?);
+    let api_response: ApiResponse<ListResponse<Value>> = client.get_list(&url).await?;
+    Ok(api_response.data.affected_items.iter().map(from_api_item).collect())
+}
+
+/// Vulnerability from an item of the pre-4.8 `/vulnerability/{agent_id}` endpoint
+fn from_api_item(item: &Value) -> Vulnerability {
+    let text = |key: &str| item[key].as_str().filter(|s| !s.is_empty()).map(String::from);
+    Vulnerability {
+        cve: json_to_text(item.get("cve")),
+        severity: json_to_text(item.get("severity")),
+        score: item["cvss3_score"].as_f64().or_else(|| item["cvss2_score"].as_f64()),
+        package: text("name"),
+        version: text("version"),
+        title: text("title"),
+        published: text("published"),
+    }
+}
+
+async fn summary_from_indexer(config: &Config, agent: &str) -> Result<Vec<(String, u64)>> {
+    let indexer = IndexerClient::new(config)?;
+    let body = json!({
+        "size": 0,
+        "query": {"bool": {"filter": agent_filters(agent, None)}},
+        "aggs": {"severity": {"terms": {"field": "vulnerability.severity"}}}
+    });
+    let response = indexer.search(VULNERABILITIES_INDEX, &body).await?;
+    let buckets = response["aggregations"]["severity"]["buckets"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    Ok(buckets
+        .iter()
+        .map(|bucket| {
+            (
+                json_to_text(Some(&bucket["key"])),
+                bucket["doc_count"].as_u64().unwrap_or(0),
+            )
+        })
+        .collect())
+}
+
+async fn summary_from_api(config: &Config, agent: &str) -> Result<Vec<(String, u64)>> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+    client.authenticate().await?;
+    severity_counts_from_api(&client, agent).await
+}
+
+/// Vulnerabilities of an agent per severity from the pre-4.8 API endpoint
+pub async fn severity_counts_from_api(
+    client: &WazuhClient,
+    agent: &str,
+) -> Result<Vec<(String, u64)>> {
+    let url = format!("/vulnerability/{}/summary/severity", agent);
+    let response = client.get(&url).await?;
+    let api_response: ApiResponse<Value> = WazuhClient::parse_response(response).await?;
+    let counts = api_response.data["severity"].as_object().cloned().unwrap_or_default();
+    Ok(counts
+        .into_iter()
+        .map(|(severity, count)| (severity, count.as_u64().unwrap_or(0)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_state() {
+        let doc = json!({
+            "agent": {"id": "001"},
+            "package": {"name": "xz-utils", "version": "5.6.0-0.2"},
+            "vulnerability": {
+                "id": "CVE-2024-3094",
+                "severity": "Critical",
+                "score": {"base": 10.0},
+                "published_at": "2024-03-29T17:15:21Z"
+            }
+        });
+        let vulnerability = from_state(&doc);
+        assert_eq!(vulnerability.cve, "CVE-2024-3094");
+        assert_eq!(vulnerability.score, Some(10.0));
+        assert_eq!(vulnerability.package.as_deref(), Some("xz-utils"));
+        assert_eq!(vulnerability.title, None);
+    }
+
+    #[test]
+    fn test_summarize_orders_by_severity() {
+        let summary = summarize(
+            "001",
+            vec![
+                ("Low".to_string(), 7),
+                ("Critical".to_string(), 1),
+                ("High".to_string(), 3),
+            ],
+        );
+        assert_eq!(summary.total, 11);
+        let order: Vec<&str> = summary.severities.iter().map(|(s, _)| s.as_str()).collect();
+        assert_eq!(order, ["Critical", "High", "Low"]);
+        assert_eq!(capitalize("CRITICAL"), "Critical");
+    }
+}
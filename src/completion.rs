@@ -0,0 +1,271 @@
+//! Dynamic shell completion of agents, groups, rules, manager services, and
+//! TLS and settings profiles.
+//!
+//! Completion has to answer instantly, so candidates only ever come from a
+//! local cache. Once the cache is older than `completion.cache_ttl_secs`, a
+//! detached `wazuh-cli completion refresh` updates it in the background while
+//! the stale values are offered, which also keeps completion working offline.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use clap_complete::engine::CompletionCandidate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::time::SystemTime;
+use tempfile::NamedTempFile;
+
+use crate::cli;
+use crate::config::Config;
+use crate::lock::{self, Resource};
+
+const CACHE_FILE: &str = "completions.json";
+
+/// Selects the profile, like `--profile`
+const PROFILE_ENV: &str = "WAZUH_PROFILE";
+
+/// Touched whenever a background refresh starts
+const REFRESH_MARKER: &str = "completions.refresh";
+
+/// Least time between background refreshes, so an unreachable manager is not
+/// retried on every key press
+const REFRESH_RETRY_SECS: u64 = 30;
+
+/// Configuration of the interactive shell, completed for instead of the
+/// default configuration
+static SESSION: OnceLock<(Config, PathBuf)> = OnceLock::new();
+
+/// Complete for this configuration from now on, as the interactive shell does
+pub fn use_config(config: &Config, config_path: PathBuf) {
+    let _ = SESSION.set((config.clone(), config_path));
+}
+
+/// A completion value with the text shown next to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candidate {
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub help: Option<String>,
+}
+
+/// Resources of one manager as last fetched by `completion refresh`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResources {
+    pub refreshed_at: DateTime<Utc>,
+    /// Agent IDs with the agent name as help
+    pub agents: Vec<Candidate>,
+    pub groups: Vec<Candidate>,
+    /// Rule IDs with the rule description as help
+    pub rules: Vec<Candidate>,
+    /// Manager daemons with their status as help
+    #[serde(default)]
+    pub services: Vec<Candidate>,
+}
+
+fn cache_path() -> Result<PathBuf> {
+    Ok(Config::data_dir()?.join(CACHE_FILE))
+}
+
+/// Cached resources of every manager, keyed by API URL
+fn load_all() -> Result<BTreeMap<String, CachedResources>> {
+    load_from(&cache_path()?)
+}
+
+fn load_from(path: &Path) -> Result<BTreeMap<String, CachedResources>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read completion cache: {:?}", path))?;
+    // A corrupt cache is rebuilt by the next refresh
+    Ok(serde_json::from_str(&contents).unwrap_or_default())
+}
+
+/// Replace the cached resources of a manager
+pub fn save(source: &str, resources: CachedResources) -> Result<()> {
+    let path = cache_path()?;
+    let _lock = lock::acquire(Resource::Completions)?;
+    save_to(&path, source, resources)
+}
+
+fn save_to(path: &Path, source: &str, resources: CachedResources) -> Result<()> {
+    let dir = path.parent().context("Completion cache has no parent directory")?;
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create data directory: {:?}", dir))?;
+
+    let mut all = load_from(path)?;
+    all.insert(source.to_string(), resources);
+    // Agent names and rules are not for other users; temporary files are 0600
+    let mut temp = NamedTempFile::new_in(dir)
+        .with_context(|| format!("Failed to create completion cache in {:?}", dir))?;
+    temp.write_all(serde_json::to_string(&all)?.as_bytes())?;
+    temp.persist(path)
+        .with_context(|| format!("Failed to replace completion cache: {:?}", path))?;
+    Ok(())
+}
+
+fn is_stale(resources: Option<&CachedResources>, ttl_secs: u64, now: DateTime<Utc>) -> bool {
+    resources.is_none_or(|r| now - r.refreshed_at > Duration::seconds(ttl_secs as i64))
+}
+
+/// Start `wazuh-cli completion refresh` without waiting for it, unless one
+/// started recently
+fn spawn_refresh() -> Result<()> {
+    let marker = Config::data_dir()?.join(REFRESH_MARKER);
+    let recent = fs::metadata(&marker)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age.as_secs() < REFRESH_RETRY_SECS);
+    if recent {
+        return Ok(());
+    }
+    fs::write(&marker, Utc::now().to_rfc3339())?;
+
+    let mut command = Command::new(std::env::current_exe()?);
+    if let Some((_, config_path)) = SESSION.get() {
+        // Refresh for the shell's manager, whatever overrides it was started with
+        command.arg("--config").arg(config_path);
+        cli::connection_flags().apply(&mut command);
+    }
+    command
+        .args(["completion", "refresh"])
+        // The shell's completion request must not be inherited by the child
+        .env_remove("COMPLETE")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+/// Cached resources of the default configuration's manager, refreshed in
+/// the background when stale; errors only ever mean no candidates
+fn cached() -> Option<CachedResources> {
+    let config = match SESSION.get() {
+        Some((config, _)) => config.clone(),
+        None => {
+            let config = Config::load(&Config::default_config_path().ok()?).ok()?;
+            config.with_profile(std::env::var(PROFILE_ENV).ok().as_deref()).ok()?
+        }
+    };
+    let resources = load_all().ok()?.remove(&config.api_url());
+    if is_stale(resources.as_ref(), config.completion.cache_ttl_secs, Utc::now()) {
+        let _ = spawn_refresh();
+    }
+    resources
+}
+
+fn candidates(items: &[Candidate]) -> Vec<CompletionCandidate> {
+    items
+        .iter()
+        .map(|item| {
+            CompletionCandidate::new(&item.value).help(item.help.clone().map(Into::into))
+        })
+        .collect()
+}
+
+/// Agent IDs, and agent names for arguments that accept either
+pub fn agents() -> Vec<CompletionCandidate> {
+    let Some(resources) = cached() else {
+        return Vec::new();
+    };
+    let names: Vec<Candidate> = resources
+        .agents
+        .iter()
+        .filter_map(|agent| {
+            Some(Candidate {
+                value: agent.help.clone()?,
+                help: Some(format!("agent {}", agent.value)),
+            })
+        })
+        .collect();
+    let mut all = candidates(&resources.agents);
+    all.extend(candidates(&names));
+    all
+}
+
+pub fn groups() -> Vec<CompletionCandidate> {
+    cached().map(|r| candidates(&r.groups)).unwrap_or_default()
+}
+
+pub fn rules() -> Vec<CompletionCandidate> {
+    cached().map(|r| candidates(&r.rules)).unwrap_or_default()
+}
+
+pub fn services() -> Vec<CompletionCandidate> {
+    cached().map(|r| candidates(&r.services)).unwrap_or_default()
+}
+
+/// TLS profile names, straight from the configuration
+pub fn tls_profiles() -> Vec<CompletionCandidate> {
+    let Some(config) = Config::default_config_path().ok().and_then(|p| Config::load(&p).ok())
+    else {
+        return Vec::new();
+    };
+    config.tls.profiles.keys().map(CompletionCandidate::new).collect()
+}
+
+/// Profile names, straight from the configuration
+pub fn profiles() -> Vec<CompletionCandidate> {
+    let Some(config) = Config::default_config_path().ok().and_then(|p| Config::load(&p).ok())
+    else {
+        return Vec::new();
+    };
+    config.profiles.keys().map(CompletionCandidate::new).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_stale() {
+        let now = Utc::now();
+        let resources = CachedResources {
+            refreshed_at: now - Duration::seconds(120),
+            agents: Vec::new(),
+            groups: Vec::new(),
+            rules: Vec::new(),
+            services: Vec::new(),
+        };
+        assert!(is_stale(None, 300, now));
+        assert!(!is_stale(Some(&resources), 300, now));
+        assert!(is_stale(Some(&resources), 60, now));
+    }
+
+    #[test]
+    fn test_save_keeps_other_managers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data").join(CACHE_FILE);
+        let resources = |agent: &str| CachedResources {
+            refreshed_at: Utc::now(),
+            agents: vec![Candidate {
+                value: agent.to_string(),
+                help: None,
+            }],
+            groups: Vec::new(),
+            rules: Vec::new(),
+            services: Vec::new(),
+        };
+
+        save_to(&path, "https://a:55000", resources("001")).unwrap();
+        save_to(&path, "https://b:55000", resources("002")).unwrap();
+        save_to(&path, "https://a:55000", resources("003")).unwrap();
+        let all = load_from(&path).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all["https://a:55000"].agents[0].value, "003");
+        assert_eq!(all["https://b:55000"].agents[0].value, "002");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+}
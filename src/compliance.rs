@@ -0,0 +1,147 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::WazuhError;
+
+/// Compliance standards that Wazuh maps rules to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Standard {
+    PciDss,
+    Gdpr,
+    Hipaa,
+    Nist80053,
+    Gpg13,
+    Tsc,
+}
+
+impl Standard {
+    pub const ALL: [Standard; 6] = [
+        Standard::PciDss,
+        Standard::Gdpr,
+        Standard::Hipaa,
+        Standard::Nist80053,
+        Standard::Gpg13,
+        Standard::Tsc,
+    ];
+
+    /// Field name used in rule definitions and alert documents (`rule.<field>`)
+    pub fn field(&self) -> &'static str {
+        match self {
+            Standard::PciDss => "pci_dss",
+            Standard::Gdpr => "gdpr",
+            Standard::Hipaa => "hipaa",
+            Standard::Nist80053 => "nist_800_53",
+            Standard::Gpg13 => "gpg13",
+            Standard::Tsc => "tsc",
+        }
+    }
+
+    /// Query parameter name accepted by the `/rules` endpoint
+    pub fn api_param(&self) -> &'static str {
+        match self {
+            Standard::Nist80053 => "nist-800-53",
+            other => other.field(),
+        }
+    }
+}
+
+impl fmt::Display for Standard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Standard::PciDss => write!(f, "PCI DSS"),
+            Standard::Gdpr => write!(f, "GDPR"),
+            Standard::Hipaa => write!(f, "HIPAA"),
+            Standard::Nist80053 => write!(f, "NIST 800-53"),
+            Standard::Gpg13 => write!(f, "GPG13"),
+            Standard::Tsc => write!(f, "TSC"),
+        }
+    }
+}
+
+impl FromStr for Standard {
+    type Err = WazuhError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "pci" | "pci_dss" => Ok(Standard::PciDss),
+            "gdpr" => Ok(Standard::Gdpr),
+            "hipaa" => Ok(Standard::Hipaa),
+            "nist" | "nist_800_53" => Ok(Standard::Nist80053),
+            "gpg13" => Ok(Standard::Gpg13),
+            "tsc" => Ok(Standard::Tsc),
+            _ => Err(WazuhError::InvalidInput(format!(
+                "Unknown compliance standard '{}' (expected pci, gdpr, hipaa, nist, gpg13 or tsc)",
+                s
+            ))),
+        }
+    }
+}
+
+/// A compliance filter such as `pci_dss_10.2.4` or just `gdpr`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComplianceFilter {
+    pub standard: Standard,
+    pub requirement: Option<String>,
+}
+
+impl FromStr for ComplianceFilter {
+    type Err = WazuhError;
+
+    /// Accepts the rule group naming used by the Wazuh ruleset
+    /// (`pci_dss_10.2.4`, `hipaa_164.312.b`, `nist_800_53_AU.14`) as well as
+    /// a bare standard name matching any requirement.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_lowercase();
+        for standard in Standard::ALL {
+            let prefix = format!("{}_", standard.field());
+            if lower.starts_with(&prefix) && s.len() > prefix.len() {
+                return Ok(Self {
+                    standard,
+                    requirement: Some(s[prefix.len()..].to_string()),
+                });
+            }
+        }
+
+        Ok(Self {
+            standard: s.parse()?,
+            requirement: None,
+        })
+    }
+}
+
+impl fmt::Display for ComplianceFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.requirement {
+            Some(req) => write!(f, "{}_{}", self.standard.field(), req),
+            None => write!(f, "{}", self.standard.field()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_standard() {
+        assert_eq!("pci".parse::<Standard>().unwrap(), Standard::PciDss);
+        assert_eq!("NIST-800-53".parse::<Standard>().unwrap(), Standard::Nist80053);
+        assert!("sox".parse::<Standard>().is_err());
+    }
+
+    #[test]
+    fn test_parse_compliance_filter() {
+        let filter: ComplianceFilter = "pci_dss_10.2.4".parse().unwrap();
+        assert_eq!(filter.standard, Standard::PciDss);
+        assert_eq!(filter.requirement.as_deref(), Some("10.2.4"));
+
+        let filter: ComplianceFilter = "nist_800_53_AU.14".parse().unwrap();
+        assert_eq!(filter.standard, Standard::Nist80053);
+        assert_eq!(filter.requirement.as_deref(), Some("AU.14"));
+        assert_eq!(filter.to_string(), "nist_800_53_AU.14");
+
+        let filter: ComplianceFilter = "gdpr".parse().unwrap();
+        assert_eq!(filter.standard, Standard::Gdpr);
+        assert_eq!(filter.requirement, None);
+    }
+}
@@ -1,14 +1,48 @@
 use anyhow::{Context, Result};
 use dirs::config_dir;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
+use std::io::Write;
+use std::net::{IpAddr, SocketAddr};
 use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+use crate::error::WazuhError;
+use crate::lock::{self, Resource};
 
 const DEFAULT_CONFIG_FILE: &str = "config.toml";
 const APP_NAME: &str = "wazuh-cli";
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Profile name that selects the top-level settings, unless a profile has it
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Newest JSON output format; bump it for any rename or removal of an output field
+pub const CURRENT_FORMAT_VERSION: u32 = 1;
+
+/// Shown instead of passwords, tokens and API keys
+const REDACTED: &str = "***";
+
+/// Keys holding secrets in the free-form profile tables
+const SECRET_KEYS: &[&str] = &["password", "token", "api_key"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Config {
+    /// Label for this configuration, chosen during first-run setup
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Command run when wazuh-cli is invoked without arguments,
+    /// e.g. "agent list --status active"; the interactive shell starts when unset.
+    /// Of the global flags it may only give output ones (`--format`, `--json`, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_command: Option<String>,
+
+    /// Profile used when `--profile` is not given; once a profile is applied,
+    /// the one in effect
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_profile: Option<String>,
+
     #[serde(default)]
     pub api: ApiConfig,
     
@@ -20,6 +54,45 @@ pub struct Config {
     
     #[serde(default)]
     pub tls: TlsConfig,
+
+    #[serde(default)]
+    pub indexer: IndexerConfig,
+
+    #[serde(default)]
+    pub cert_audit: CertAuditConfig,
+
+    #[serde(default)]
+    pub agents: AgentsConfig,
+
+    #[serde(default)]
+    pub stats: StatsConfig,
+
+    #[serde(default)]
+    pub escalation: EscalationConfig,
+
+    #[serde(default)]
+    pub active_response: ActiveResponseConfig,
+
+    #[serde(default)]
+    pub storage: StorageConfig,
+
+    #[serde(default)]
+    pub completion: CompletionConfig,
+
+    /// Named environments, e.g. `[profiles.prod.api]`; their settings replace
+    /// the top-level ones they name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, toml::Table>,
+}
+
+/// A profile as listed by `config get-contexts`
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileSummary {
+    pub name: String,
+    pub api_url: String,
+    pub username: Option<String>,
+    /// The profile in effect for this command
+    pub current: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,9 +108,51 @@ pub struct ApiConfig {
     
     #[serde(default = "default_timeout")]
     pub timeout: u64,
+
+    /// Timeout in seconds of endpoints known to run long, such as agent upgrades
+    #[serde(default = "default_long_timeout")]
+    pub long_timeout: u64,
     
     #[serde(default = "default_retries")]
     pub max_retries: u32,
+
+    /// Largest response body held in memory, in MB; bigger bodies are
+    /// rejected, or spooled to a temporary file by export commands
+    #[serde(default = "default_max_response_mb")]
+    pub max_response_mb: u64,
+
+    /// Milliseconds until response headers arrive after which a slow-endpoint
+    /// hint is shown
+    #[serde(default = "default_latency_budget_ms")]
+    pub latency_budget_ms: u64,
+
+    /// Per-endpoint budgets keyed by path prefix, e.g. `"/manager/logs" = 3000`;
+    /// the longest matching prefix wins
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub latency_budgets: BTreeMap<String, u64>,
+
+    /// Headers sent with every API request, e.g. tokens required by a gateway
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra_headers: BTreeMap<String, String>,
+
+    /// Preferred language of API messages (e.g. `es`, `fr-FR`), sent as
+    /// `Accept-Language`; managers that do not translate answer in English
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+
+    /// Pin `host:port` to an address (`wazuh.internal:55000:127.0.0.1`), so an
+    /// SSH port-forward can be used while TLS still verifies the real hostname
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolve: Option<String>,
+
+    /// Connect through a Unix domain socket (e.g. a forwarded socket) instead of TCP
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unix_socket: Option<PathBuf>,
+
+    /// SSH jump host (`user@jumphost` or a `~/.ssh/config` alias) through which
+    /// a local port is forwarded to the API for each run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_tunnel: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +163,25 @@ pub struct AuthConfig {
     
     #[serde(default = "default_token_expiry")]
     pub token_expiry_hours: u32,
+
+    /// Seconds before its `exp` a token is treated as expired, for clock skew
+    /// between this host and the manager
+    #[serde(default = "default_clock_skew")]
+    pub clock_skew_secs: u64,
+
+    /// Log in through `/security/user/authenticate/run_as`, so the roles come
+    /// from the authorization context (e.g. groups from an SSO provider)
+    #[serde(default)]
+    pub run_as: bool,
+
+    /// JSON file with the authorization context sent on run_as logins
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_context: Option<PathBuf>,
+
+    /// Keep the password and token from `auth login` in the OS credential
+    /// store; when off they are written to this file
+    #[serde(default = "default_keyring")]
+    pub keyring: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,6 +194,32 @@ pub struct OutputConfig {
     
     #[serde(default = "default_pager")]
     pub pager: bool,
+
+    #[serde(default)]
+    pub a11y: bool,
+
+    #[serde(default = "default_large_result_threshold")]
+    pub large_result_threshold: u64,
+
+    #[serde(default = "default_prompt_large_results")]
+    pub prompt_large_results: bool,
+
+    /// JSON output format version; field names are stable within a version
+    #[serde(default = "default_format_version")]
+    pub format_version: u32,
+
+    /// How diffs are printed: "unified" or "side-by-side"
+    #[serde(default = "default_diff_style")]
+    pub diff_style: String,
+
+    /// Unchanged lines shown around each change in diffs
+    #[serde(default = "default_diff_context")]
+    pub diff_context: usize,
+
+    /// Computed columns per table, e.g. `[output.columns.agents]`
+    /// `fqdn = 'name + ".corp.local"'`, kept in definition order
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub columns: BTreeMap<String, toml::Table>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,6 +230,191 @@ pub struct TlsConfig {
     pub ca_cert: Option<PathBuf>,
     pub client_cert: Option<PathBuf>,
     pub client_key: Option<PathBuf>,
+
+    /// Further CA bundles trusted alongside `ca_cert`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ca_certs: Vec<PathBuf>,
+
+    /// Trust the operating system's certificate store
+    #[serde(default)]
+    pub system_roots: bool,
+
+    /// Trust the public root certificates bundled with the CLI
+    #[serde(default = "default_builtin_roots")]
+    pub builtin_roots: bool,
+
+    /// Profile applied to every connection; otherwise profiles are matched by host
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
+    /// Named trust stores and client identities, e.g. `[tls.profiles.lab]`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, TlsProfile>,
+}
+
+/// Trust roots and client identity used for one set of servers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TlsProfile {
+    #[serde(default = "default_verify")]
+    pub verify: bool,
+
+    /// CA bundles, each holding one or more PEM certificates
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ca_certs: Vec<PathBuf>,
+
+    #[serde(default)]
+    pub system_roots: bool,
+
+    #[serde(default = "default_builtin_roots")]
+    pub builtin_roots: bool,
+
+    pub client_cert: Option<PathBuf>,
+    pub client_key: Option<PathBuf>,
+
+    /// Manager and indexer hosts the profile applies to when none is selected
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hosts: Vec<String>,
+}
+
+impl TlsConfig {
+    /// Settings for connections to `host`: the selected profile, else the first
+    /// profile listing the host, else the top-level settings
+    pub fn for_host(&self, host: &str) -> Result<TlsProfile, WazuhError> {
+        if let Some(name) = &self.profile {
+            return self.profiles.get(name).cloned().ok_or_else(|| {
+                WazuhError::InvalidInput(format!("TLS profile '{}' is not configured", name))
+            });
+        }
+
+        let matching = self
+            .profiles
+            .values()
+            .find(|profile| profile.hosts.iter().any(|h| h.eq_ignore_ascii_case(host)));
+        if let Some(profile) = matching {
+            return Ok(profile.clone());
+        }
+
+        Ok(TlsProfile {
+            verify: self.verify,
+            ca_certs: self.ca_cert.iter().chain(&self.ca_certs).cloned().collect(),
+            system_roots: self.system_roots,
+            builtin_roots: self.builtin_roots,
+            client_cert: self.client_cert.clone(),
+            client_key: self.client_key.clone(),
+            hosts: Vec::new(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexerConfig {
+    #[serde(default = "default_host")]
+    pub host: String,
+
+    #[serde(default = "default_indexer_port")]
+    pub port: u16,
+
+    #[serde(default = "default_protocol")]
+    pub protocol: String,
+
+    pub username: Option<String>,
+    pub password: Option<String>,
+
+    #[serde(default = "default_alerts_index")]
+    pub alerts_index: String,
+
+    /// Index pattern of archived events, filled when the manager's `logall_json` is on
+    #[serde(default = "default_archives_index")]
+    pub archives_index: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AgentsConfig {
+    /// Oldest agent version considered patched (e.g. "4.7.0")
+    pub min_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatsConfig {
+    /// Record command usage to a local file for `stats cli`; nothing is sent anywhere
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActiveResponseConfig {
+    /// Active-response commands `agent run` may trigger; none by default
+    #[serde(default)]
+    pub allowed_commands: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletionConfig {
+    /// Age after which cached agents, groups and rules are refreshed in the background
+    #[serde(default = "default_completion_ttl")]
+    pub cache_ttl_secs: u64,
+}
+
+/// Credentials for `--out s3://...` and `--out gs://...` destinations
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageConfig {
+    /// Profile of the AWS shared credentials file; the AWS_* variables, the
+    /// AWS_PROFILE (or default) profile or the instance's IAM role are used
+    /// when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aws_profile: Option<String>,
+
+    /// Region of S3 buckets; taken from the environment or the profile when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+
+    /// S3-compatible endpoint (e.g. MinIO) used instead of AWS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+
+    /// Service account key file for GCS; GOOGLE_APPLICATION_CREDENTIALS when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gcs_credentials: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EscalationConfig {
+    /// Dashboard URL linked from escalated alerts
+    pub dashboard_url: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thehive: Option<CaseManagerConfig>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iris: Option<CaseManagerConfig>,
+}
+
+/// Connection to an incident response platform used by `alerts escalate`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseManagerConfig {
+    pub url: String,
+    pub api_key: String,
+
+    #[serde(default = "default_verify")]
+    pub verify_tls: bool,
+
+    /// DFIR-IRIS customer the alerts belong to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub customer_id: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertAuditConfig {
+    /// Certificate files checked by `security cert-audit`
+    #[serde(default = "default_cert_paths")]
+    pub paths: Vec<PathBuf>,
+
+    /// Dashboard URL whose TLS certificate is also checked
+    pub dashboard_url: Option<String>,
+
+    /// Report certificates expiring within this many days
+    #[serde(default = "default_cert_warn_days")]
+    pub warn_days: i64,
 }
 
 // Default value functions
@@ -89,14 +434,63 @@ fn default_timeout() -> u64 {
     30
 }
 
+fn default_long_timeout() -> u64 {
+    600
+}
+
 fn default_retries() -> u32 {
     3
 }
 
+fn default_max_response_mb() -> u64 {
+    64
+}
+
+fn default_latency_budget_ms() -> u64 {
+    10_000
+}
+
+fn default_indexer_port() -> u16 {
+    9200
+}
+
+fn default_cert_paths() -> Vec<PathBuf> {
+    [
+        "/var/ossec/api/configuration/ssl/server.crt",
+        "/etc/filebeat/certs/filebeat.pem",
+        "/etc/filebeat/certs/root-ca.pem",
+        "/etc/wazuh-indexer/certs/indexer.pem",
+        "/etc/wazuh-dashboard/certs/dashboard.pem",
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .collect()
+}
+
+fn default_cert_warn_days() -> i64 {
+    30
+}
+
+fn default_alerts_index() -> String {
+    "wazuh-alerts-*".to_string()
+}
+
+fn default_archives_index() -> String {
+    "wazuh-archives-*".to_string()
+}
+
 fn default_token_expiry() -> u32 {
     24
 }
 
+fn default_clock_skew() -> u64 {
+    30
+}
+
+fn default_keyring() -> bool {
+    true
+}
+
 fn default_format() -> String {
     "table".to_string()
 }
@@ -109,22 +503,40 @@ fn default_pager() -> bool {
     true
 }
 
+fn default_large_result_threshold() -> u64 {
+    10000
+}
+
+fn default_prompt_large_results() -> bool {
+    true
+}
+
+// Configurations written before format versioning expect the original field names
+fn default_format_version() -> u32 {
+    1
+}
+
+fn default_diff_style() -> String {
+    "unified".to_string()
+}
+
+fn default_diff_context() -> usize {
+    crate::diff::DEFAULT_CONTEXT
+}
+
 fn default_verify() -> bool {
     true
 }
 
-// Default implementations
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            api: ApiConfig::default(),
-            auth: AuthConfig::default(),
-            output: OutputConfig::default(),
-            tls: TlsConfig::default(),
-        }
-    }
+fn default_builtin_roots() -> bool {
+    true
 }
 
+fn default_completion_ttl() -> u64 {
+    300
+}
+
+// Default implementations
 impl Default for ApiConfig {
     fn default() -> Self {
         Self {
@@ -132,7 +544,16 @@ impl Default for ApiConfig {
             port: default_port(),
             protocol: default_protocol(),
             timeout: default_timeout(),
+            long_timeout: default_long_timeout(),
             max_retries: default_retries(),
+            max_response_mb: default_max_response_mb(),
+            latency_budget_ms: default_latency_budget_ms(),
+            latency_budgets: BTreeMap::new(),
+            extra_headers: BTreeMap::new(),
+            language: None,
+            resolve: None,
+            unix_socket: None,
+            ssh_tunnel: None,
         }
     }
 }
@@ -144,6 +565,10 @@ impl Default for AuthConfig {
             password: None,
             token: None,
             token_expiry_hours: default_token_expiry(),
+            clock_skew_secs: default_clock_skew(),
+            run_as: false,
+            auth_context: None,
+            keyring: default_keyring(),
         }
     }
 }
@@ -154,6 +579,13 @@ impl Default for OutputConfig {
             format: default_format(),
             color: default_color(),
             pager: default_pager(),
+            a11y: false,
+            large_result_threshold: default_large_result_threshold(),
+            prompt_large_results: default_prompt_large_results(),
+            format_version: CURRENT_FORMAT_VERSION,
+            diff_style: default_diff_style(),
+            diff_context: default_diff_context(),
+            columns: BTreeMap::new(),
         }
     }
 }
@@ -165,6 +597,43 @@ impl Default for TlsConfig {
             ca_cert: None,
             client_cert: None,
             client_key: None,
+            ca_certs: Vec::new(),
+            system_roots: false,
+            builtin_roots: default_builtin_roots(),
+            profile: None,
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for IndexerConfig {
+    fn default() -> Self {
+        Self {
+            host: default_host(),
+            port: default_indexer_port(),
+            protocol: default_protocol(),
+            username: None,
+            password: None,
+            alerts_index: default_alerts_index(),
+            archives_index: default_archives_index(),
+        }
+    }
+}
+
+impl Default for CompletionConfig {
+    fn default() -> Self {
+        Self {
+            cache_ttl_secs: default_completion_ttl(),
+        }
+    }
+}
+
+impl Default for CertAuditConfig {
+    fn default() -> Self {
+        Self {
+            paths: default_cert_paths(),
+            dashboard_url: None,
+            warn_days: default_cert_warn_days(),
         }
     }
 }
@@ -172,11 +641,7 @@ impl Default for TlsConfig {
 impl Config {
     /// Load configuration from file or create default
     pub fn load(path: &Path) -> Result<Self> {
-        let config_path = if path.to_string_lossy() == "~/.wazuh-cli/config.toml" {
-            Self::default_config_path()?
-        } else {
-            path.to_path_buf()
-        };
+        let config_path = Self::resolve_path(path)?;
 
         if config_path.exists() {
             let contents = fs::read_to_string(&config_path)
@@ -189,29 +654,299 @@ impl Config {
         }
     }
 
-    /// Save configuration to file
+    /// Save configuration to file, readable and writable by the owner only
     pub fn save(&self, path: &Path) -> Result<()> {
-        let config_path = if path.to_string_lossy() == "~/.wazuh-cli/config.toml" {
-            Self::default_config_path()?
-        } else {
-            path.to_path_buf()
-        };
+        let _lock = lock::acquire(Resource::Config)?;
+        self.write(path)
+    }
+
+    /// Load the file at `path`, change it and save it, holding the config lock
+    /// throughout so concurrent edits from other instances are not lost
+    pub fn update<T>(path: &Path, change: impl FnOnce(&mut Config) -> Result<T>) -> Result<T> {
+        let _lock = lock::acquire(Resource::Config)?;
+        let mut config = Self::load(path)?;
+        let changed = change(&mut config)?;
+        config.write(path)?;
+        Ok(changed)
+    }
+
+    fn write(&self, path: &Path) -> Result<()> {
+        let config_path = Self::resolve_path(path)?;
+        // Replace the target of a symlinked config file, not the link
+        let config_path = fs::canonicalize(&config_path).unwrap_or(config_path);
 
         // Create parent directory if it doesn't exist
-        if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent)
-                .with_context(|| format!("Failed to create config directory: {:?}", parent))?;
-        }
+        let parent = config_path.parent().context("Config file has no parent directory")?;
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create config directory: {:?}", parent))?;
 
         let contents = toml::to_string_pretty(self)
             .context("Failed to serialize configuration")?;
-        
-        fs::write(&config_path, contents)
+
+        // Temporary files are created with 0600, which the config file keeps
+        let mut temp = NamedTempFile::new_in(parent)
+            .with_context(|| format!("Failed to create config file in {:?}", parent))?;
+        temp.write_all(contents.as_bytes())?;
+        temp.persist(&config_path)
             .with_context(|| format!("Failed to write config file: {:?}", config_path))?;
 
         Ok(())
     }
 
+    /// A warning when the config file at `path` can be read by every user on
+    /// this host, since it may hold passwords and tokens
+    #[cfg(unix)]
+    pub fn permission_warning(path: &Path) -> Option<String> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let config_path = Self::resolve_path(path).ok()?;
+        let mode = fs::metadata(&config_path).ok()?.permissions().mode() & 0o777;
+        (mode & 0o004 != 0).then(|| {
+            format!(
+                "{} is readable by every user on this host (mode {:o}); run 'chmod 600 {}'",
+                config_path.display(),
+                mode,
+                config_path.display()
+            )
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn permission_warning(_path: &Path) -> Option<String> {
+        None
+    }
+
+    /// A copy with passwords, tokens, API keys and header values masked,
+    /// including those in profiles
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        let mask = |secret: &mut Option<String>| {
+            if secret.is_some() {
+                *secret = Some(REDACTED.to_string());
+            }
+        };
+        mask(&mut config.auth.password);
+        mask(&mut config.auth.token);
+        mask(&mut config.indexer.password);
+        for value in config.api.extra_headers.values_mut() {
+            *value = REDACTED.to_string();
+        }
+        let managers = [&mut config.escalation.thehive, &mut config.escalation.iris];
+        for manager in managers.into_iter().flatten() {
+            manager.api_key = REDACTED.to_string();
+        }
+        for profile in config.profiles.values_mut() {
+            redact_table(profile, false);
+        }
+        config
+    }
+
+    /// The settings of a profile, by default `current_profile`, laid over the
+    /// top-level ones
+    pub fn with_profile(self, name: Option<&str>) -> Result<Self, WazuhError> {
+        let Some(name) = name.or(self.current_profile.as_deref()).map(str::to_string) else {
+            return Ok(self);
+        };
+        let Some(profile) = self.profiles.get(&name) else {
+            if name == DEFAULT_PROFILE {
+                return Ok(Self { current_profile: None, ..self });
+            }
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            return Err(WazuhError::InvalidInput(format!(
+                "Profile '{}' is not configured (known profiles: {})",
+                name,
+                if known.is_empty() { "none".to_string() } else { known.join(", ") }
+            )));
+        };
+
+        let invalid = |e: &dyn std::fmt::Display| {
+            WazuhError::InvalidInput(format!(
+                "Invalid settings in profile '{}': {}",
+                name,
+                first_line(e)
+            ))
+        };
+        let mut table = toml::Table::try_from(&self).map_err(|e| invalid(&e))?;
+        merge_tables(&mut table, profile.clone());
+        let mut config: Self = table.try_into().map_err(|e| invalid(&e))?;
+        config.current_profile = Some(name);
+        Ok(config)
+    }
+
+    /// The top-level settings as `default`, then every profile, resolved
+    /// against the file contents `self`; `in_use` is the profile in effect
+    pub fn profile_summaries(&self, in_use: Option<&str>) -> Vec<ProfileSummary> {
+        let mut names = vec![DEFAULT_PROFILE];
+        names.extend(self.profiles.keys().map(String::as_str).filter(|&n| n != DEFAULT_PROFILE));
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let config = self.clone().with_profile(Some(name)).ok()?;
+                Some(ProfileSummary {
+                    name: name.to_string(),
+                    api_url: config.api_url(),
+                    username: config.auth.username,
+                    current: in_use.unwrap_or(DEFAULT_PROFILE) == name,
+                })
+            })
+            .collect()
+    }
+
+    /// Set a dotted key such as `auth.username` in a profile, or in the
+    /// top-level settings when `profile` is `None`; a `None` value removes it
+    pub fn set_value(
+        &mut self,
+        profile: Option<&str>,
+        key: &str,
+        value: Option<toml::Value>,
+    ) -> Result<(), WazuhError> {
+        let invalid = |e: &dyn std::fmt::Display| {
+            WazuhError::InvalidInput(format!("Cannot set '{}': {}", key, first_line(e)))
+        };
+        let mut path: Vec<&str> = key.split('.').collect();
+        let Some(last) = path.pop().filter(|k| !k.is_empty()) else {
+            return Err(invalid(&"empty key"));
+        };
+
+        let mut table = toml::Table::try_from(&*self).map_err(|e| invalid(&e))?;
+        let mut target = &mut table;
+        if let Some(profile) = profile {
+            path.splice(0..0, ["profiles", profile]);
+        }
+        for part in path {
+            let entry = target
+                .entry(part)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            target = entry
+                .as_table_mut()
+                .ok_or_else(|| invalid(&format!("'{}' is not a table", part)))?;
+        }
+        match value {
+            Some(value) => {
+                target.insert(last.to_string(), value);
+            }
+            None => {
+                target.remove(last);
+            }
+        }
+
+        let config: Self = table.try_into().map_err(|e| invalid(&e))?;
+        // A profile is only checked when applied, so check it now
+        if let Some(profile) = profile {
+            config.clone().with_profile(Some(profile))?;
+        }
+        *self = config;
+        Ok(())
+    }
+
+    /// The value of a dotted key, `None` when it is unset or not a setting
+    pub fn value_at(&self, key: &str) -> Option<toml::Value> {
+        let table = toml::Table::try_from(self).ok()?;
+        lookup(&table, key).cloned()
+    }
+
+    /// Whether a dotted key names a setting, set or not; `profiles.NAME.KEY`
+    /// names the setting KEY of a profile
+    pub fn is_setting(&self, key: &str) -> bool {
+        if let Some((_, key)) = key.strip_prefix("profiles.").and_then(|rest| rest.split_once('.')) {
+            return Config::default().is_setting(key);
+        }
+        if self.value_at(key).is_some() {
+            return true;
+        }
+        // An unset optional setting keeps a value of its type; unknown keys are dropped
+        let samples = [
+            toml::Value::String("x".to_string()),
+            toml::Value::Integer(1),
+            toml::Value::Boolean(true),
+            toml::Value::Array(Vec::new()),
+        ];
+        samples.into_iter().any(|sample| {
+            let mut config = Config::default();
+            config.set_value(None, key, Some(sample)).is_ok() && config.value_at(key).is_some()
+        })
+    }
+
+    /// Whether a dotted key is written in a profile, or in the top-level
+    /// settings when `profile` is `None`
+    pub fn is_set(&self, profile: Option<&str>, key: &str) -> bool {
+        match profile {
+            Some(profile) => self.profiles.get(profile).and_then(|t| lookup(t, key)).is_some(),
+            None => self.value_at(key).is_some(),
+        }
+    }
+
+    /// Set a dotted key from command-line text, typed like its current value:
+    /// `true`/`false` for flags, numbers, and comma-separated or TOML arrays
+    /// for lists; returns the value as stored
+    pub fn set_from_str(
+        &mut self,
+        profile: Option<&str>,
+        key: &str,
+        text: &str,
+    ) -> Result<toml::Value, WazuhError> {
+        let invalid = |reason: &str| {
+            WazuhError::InvalidInput(format!("Cannot set '{}' to '{}': {}", key, text, reason))
+        };
+        let current = self.clone().with_profile(profile)?.value_at(key);
+        let candidates = match current {
+            Some(toml::Value::Table(_)) => {
+                return Err(WazuhError::InvalidInput(format!(
+                    "'{}' is a section; set one of its keys, e.g. {}.<name>",
+                    key, key
+                )))
+            }
+            Some(toml::Value::Boolean(_)) => {
+                let value = text.parse().map_err(|_| invalid("expected true or false"))?;
+                vec![toml::Value::Boolean(value)]
+            }
+            Some(toml::Value::Integer(_)) => {
+                let value = text.parse().map_err(|_| invalid("expected a whole number"))?;
+                vec![toml::Value::Integer(value)]
+            }
+            Some(toml::Value::Float(_)) => {
+                let value = text.parse().map_err(|_| invalid("expected a number"))?;
+                vec![toml::Value::Float(value)]
+            }
+            Some(toml::Value::Array(_)) => {
+                vec![parse_list(text).ok_or_else(|| invalid("expected a list"))?]
+            }
+            Some(_) => vec![toml::Value::String(text.to_string())],
+            // Unset optional values are strings more often than not
+            None => std::iter::once(toml::Value::String(text.to_string()))
+                .chain(parse_literal(text))
+                .collect(),
+        };
+
+        let mut error = None;
+        for value in candidates {
+            let mut updated = self.clone();
+            if let Err(e) = updated.set_value(profile, key, Some(value.clone())) {
+                error.get_or_insert(e);
+                continue;
+            }
+            // Unknown keys are dropped when the settings are read back
+            if updated.clone().with_profile(profile)?.value_at(key).is_none() {
+                return Err(WazuhError::InvalidInput(format!(
+                    "Unknown configuration key: {}",
+                    key
+                )));
+            }
+            *self = updated;
+            return Ok(value);
+        }
+        Err(error.unwrap_or_else(|| invalid("unsupported value")))
+    }
+
+    /// Map the `--config` placeholder default to the platform config directory
+    pub fn resolve_path(path: &Path) -> Result<PathBuf> {
+        if path.to_string_lossy() == "~/.wazuh-cli/config.toml" {
+            Self::default_config_path()
+        } else {
+            Ok(path.to_path_buf())
+        }
+    }
+
     /// Get default configuration path
     pub fn default_config_path() -> Result<PathBuf> {
         let config_dir = config_dir()
@@ -220,11 +955,87 @@ impl Config {
         Ok(config_dir.join(APP_NAME).join(DEFAULT_CONFIG_FILE))
     }
 
+    /// Get the directory holding local CLI state (history, caches)
+    pub fn data_dir() -> Result<PathBuf> {
+        let config_dir = config_dir()
+            .context("Failed to get system config directory")?;
+
+        Ok(config_dir.join(APP_NAME))
+    }
+
     /// Get API base URL
     pub fn api_url(&self) -> String {
         format!("{}://{}:{}", self.api.protocol, self.api.host, self.api.port)
     }
 
+    /// The authorization context for run_as logins, `None` for plain logins
+    pub fn auth_context(&self) -> Result<Option<serde_json::Value>, WazuhError> {
+        if !self.auth.run_as {
+            return Ok(None);
+        }
+        let path = self.auth.auth_context.as_ref().ok_or_else(|| {
+            WazuhError::InvalidInput(
+                "auth.run_as needs an authorization context: set auth.auth_context or pass \
+                 --auth-context FILE"
+                    .to_string(),
+            )
+        })?;
+        let contents = fs::read_to_string(path).map_err(|e| {
+            WazuhError::InvalidInput(format!("Cannot read authorization context {:?}: {}", path, e))
+        })?;
+        let context: serde_json::Value = serde_json::from_str(&contents).map_err(|e| {
+            WazuhError::InvalidInput(format!("Invalid authorization context {:?}: {}", path, e))
+        })?;
+        if !context.is_object() {
+            return Err(WazuhError::InvalidInput(format!(
+                "Authorization context {:?} must be a JSON object",
+                path
+            )));
+        }
+        Ok(Some(context))
+    }
+
+    /// Parse `api.resolve` into the hostname to override and the address to use
+    pub fn resolve_override(&self) -> Result<Option<(String, SocketAddr)>, WazuhError> {
+        let Some(resolve) = &self.api.resolve else {
+            return Ok(None);
+        };
+
+        let invalid = |reason: &str| {
+            WazuhError::InvalidInput(format!(
+                "Invalid api.resolve '{}': {} (expected HOST:PORT:ADDRESS)",
+                resolve, reason
+            ))
+        };
+
+        let mut parts = resolve.splitn(3, ':');
+        let (host, port, address) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(host), Some(port), Some(address)) if !host.is_empty() => (host, port, address),
+            _ => return Err(invalid("missing fields")),
+        };
+        let port: u16 = port.parse().map_err(|_| invalid("bad port"))?;
+        let address: IpAddr = address
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse()
+            .map_err(|_| invalid("bad address"))?;
+
+        // reqwest always connects to the port in the URL, so a different one would be ignored
+        if host == self.api.host && port != self.api.port {
+            return Err(invalid(&format!("port does not match api.port {}", self.api.port)));
+        }
+
+        Ok(Some((host.to_string(), SocketAddr::new(address, port))))
+    }
+
+    /// Get indexer base URL
+    pub fn indexer_url(&self) -> String {
+        format!(
+            "{}://{}:{}",
+            self.indexer.protocol, self.indexer.host, self.indexer.port
+        )
+    }
+
     /// Update authentication token
     pub fn update_token(&mut self, token: String) {
         self.auth.token = Some(token);
@@ -232,6 +1043,70 @@ impl Config {
 
 }
 
+/// Mask secret keys of a profile table; every value under `extra_headers` is one
+fn redact_table(table: &mut toml::Table, all: bool) {
+    for (key, value) in table.iter_mut() {
+        match value {
+            toml::Value::Table(inner) => redact_table(inner, all || key == "extra_headers"),
+            _ if all || SECRET_KEYS.contains(&key.as_str()) => {
+                *value = toml::Value::String(REDACTED.to_string())
+            }
+            _ => {}
+        }
+    }
+}
+
+/// TOML errors end with the key path and a blank line, on separate lines
+fn first_line(error: &dyn std::fmt::Display) -> String {
+    error.to_string().lines().next().unwrap_or_default().to_string()
+}
+
+fn lookup<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    let (parents, last) = match key.rsplit_once('.') {
+        Some((parents, last)) => (Some(parents), last),
+        None => (None, key),
+    };
+    let mut table = table;
+    for part in parents.into_iter().flat_map(|p| p.split('.')) {
+        table = table.get(part)?.as_table()?;
+    }
+    table.get(last)
+}
+
+/// A TOML literal such as `42`, `true` or `["a", "b"]`
+fn parse_literal(text: &str) -> Option<toml::Value> {
+    let mut table: toml::Table = toml::from_str(&format!("value = {}", text)).ok()?;
+    table.remove("value")
+}
+
+/// A TOML array, or comma-separated strings (`a, b`; empty for none)
+fn parse_list(text: &str) -> Option<toml::Value> {
+    if text.trim_start().starts_with('[') {
+        return parse_literal(text).filter(toml::Value::is_array);
+    }
+    let items = text
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| toml::Value::String(item.to_string()))
+        .collect();
+    Some(toml::Value::Array(items))
+}
+
+/// Lay `overlay` over `base`, merging tables and replacing other values
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => {
+                merge_tables(base, overlay)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -253,12 +1128,166 @@ mod tests {
         let mut config = Config::default();
         config.api.host = "test.example.com".to_string();
         config.auth.username = Some("testuser".to_string());
+        config.name = Some("lab".to_string());
         
         config.save(&config_path).unwrap();
         
         let loaded_config = Config::load(&config_path).unwrap();
         assert_eq!(loaded_config.api.host, "test.example.com");
         assert_eq!(loaded_config.auth.username, Some("testuser".to_string()));
+        assert_eq!(loaded_config.name.as_deref(), Some("lab"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&config_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+            assert!(Config::permission_warning(&config_path).is_none());
+            fs::set_permissions(&config_path, fs::Permissions::from_mode(0o644)).unwrap();
+            assert!(Config::permission_warning(&config_path).is_some());
+        }
+    }
+
+    #[test]
+    fn test_redacted() {
+        let config: Config = toml::from_str(
+            "[auth]\nusername = \"wazuh\"\npassword = \"s3cret\"\n\
+             [api.extra_headers]\nX-Api-Key = \"k\"\n\
+             [profiles.lab]\nauth = { password = \"lab\" }\n\
+             [profiles.lab.api]\nhost = \"lab\"\nextra_headers = { X-Api-Key = \"k\" }\n",
+        )
+        .unwrap();
+
+        let redacted = config.redacted();
+        assert_eq!(redacted.auth.username.as_deref(), Some("wazuh"));
+        assert_eq!(redacted.auth.password.as_deref(), Some(REDACTED));
+        assert!(redacted.auth.token.is_none());
+        assert_eq!(redacted.api.extra_headers["X-Api-Key"], REDACTED);
+        let lab = toml::Value::Table(redacted.profiles["lab"].clone());
+        assert_eq!(lab["auth"]["password"].as_str(), Some(REDACTED));
+        assert_eq!(lab["api"]["host"].as_str(), Some("lab"));
+        assert_eq!(lab["api"]["extra_headers"]["X-Api-Key"].as_str(), Some(REDACTED));
+        assert!(!toml::to_string(&redacted).unwrap().contains("s3cret"));
+    }
+
+    #[test]
+    fn test_resolve_override() {
+        let mut config = Config::default();
+        assert!(config.resolve_override().unwrap().is_none());
+
+        config.api.host = "wazuh.internal".to_string();
+        config.api.resolve = Some("wazuh.internal:55000:127.0.0.1".to_string());
+        let (host, addr) = config.resolve_override().unwrap().unwrap();
+        assert_eq!(host, "wazuh.internal");
+        assert_eq!(addr, "127.0.0.1:55000".parse().unwrap());
+
+        config.api.resolve = Some("wazuh.internal:55000:[::1]".to_string());
+        assert!(config.resolve_override().unwrap().unwrap().1.is_ipv6());
+
+        config.api.resolve = Some("wazuh.internal:9999:127.0.0.1".to_string());
+        assert!(config.resolve_override().is_err());
+        config.api.resolve = Some("wazuh.internal:127.0.0.1".to_string());
+        assert!(config.resolve_override().is_err());
+    }
+
+    #[test]
+    fn test_auth_context() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("context.json");
+        let mut config = Config::default();
+        config.auth.auth_context = Some(path.clone());
+        assert!(config.auth_context().unwrap().is_none());
+
+        config.auth.run_as = true;
+        assert!(config.auth_context().is_err());
+        fs::write(&path, r#"{"department": "soc", "groups": ["tier1"]}"#).unwrap();
+        assert_eq!(config.auth_context().unwrap().unwrap()["department"], "soc");
+        fs::write(&path, r#"["tier1"]"#).unwrap();
+        assert!(config.auth_context().is_err());
+
+        config.auth.auth_context = None;
+        assert!(config.auth_context().is_err());
+    }
+
+    #[test]
+    fn test_with_profile() {
+        let config: Config = toml::from_str(
+            "current_profile = \"staging\"\n\
+             [api]\nhost = \"wazuh.prod\"\ntimeout = 60\n\
+             [auth]\nusername = \"wazuh\"\n\
+             [profiles.staging.api]\nhost = \"wazuh.staging\"\n\
+             [profiles.lab]\napi = { host = \"wazuh.lab\", port = 8443 }\n",
+        )
+        .unwrap();
+
+        let staging = config.clone().with_profile(None).unwrap();
+        assert_eq!(staging.api_url(), "https://wazuh.staging:55000");
+        assert_eq!(staging.api.timeout, 60);
+        assert_eq!(staging.auth.username.as_deref(), Some("wazuh"));
+        assert_eq!(staging.current_profile.as_deref(), Some("staging"));
+
+        let lab = config.clone().with_profile(Some("lab")).unwrap();
+        assert_eq!(lab.api_url(), "https://wazuh.lab:8443");
+
+        let default = config.clone().with_profile(Some(DEFAULT_PROFILE)).unwrap();
+        assert_eq!(default.api.host, "wazuh.prod");
+        assert!(default.current_profile.is_none());
+        assert!(config.with_profile(Some("qa")).is_err());
+    }
+
+    #[test]
+    fn test_set_value() {
+        let mut config = Config::default();
+        config.set_value(None, "api.port", Some(8443.into())).unwrap();
+        config.set_value(Some("lab"), "auth.username", Some("admin".into())).unwrap();
+        assert_eq!(config.api.port, 8443);
+        assert!(config.auth.username.is_none());
+        assert_eq!(config.profiles["lab"]["auth"]["username"].as_str(), Some("admin"));
+
+        assert!(config.set_value(None, "api.port", Some("high".into())).is_err());
+        assert!(config.set_value(Some("lab"), "api.port", Some("high".into())).is_err());
+        assert_eq!(config.api.port, 8443);
+        config.set_value(None, "api.port", None).unwrap();
+        assert_eq!(config.api.port, 55000);
+    }
+
+    #[test]
+    fn test_set_from_str() {
+        let mut config = Config::default();
+        config.set_from_str(None, "api.port", "8443").unwrap();
+        config.set_from_str(None, "tls.verify", "false").unwrap();
+        config.set_from_str(None, "auth.password", "1234").unwrap();
+        config.set_from_str(None, "cert_audit.paths", "/etc/a.pem, /etc/b.pem").unwrap();
+        config.set_from_str(None, "api.extra_headers.X-Team", "soc").unwrap();
+        assert_eq!(config.api.port, 8443);
+        assert!(!config.tls.verify);
+        assert_eq!(config.auth.password.as_deref(), Some("1234"));
+        assert_eq!(config.cert_audit.paths.len(), 2);
+        assert_eq!(config.api.extra_headers["X-Team"], "soc");
+
+        assert!(config.set_from_str(None, "api.port", "70000").is_err());
+        assert!(config.set_from_str(None, "tls.verify", "maybe").is_err());
+        assert!(config.set_from_str(None, "api.hots", "x").is_err());
+        assert!(config.set_from_str(None, "api", "x").is_err());
+        assert_eq!(config.api.port, 8443);
+
+        config.profiles.insert("lab".to_string(), toml::Table::new());
+        config.set_from_str(Some("lab"), "api.port", "9443").unwrap();
+        assert!(config.is_set(Some("lab"), "api.port"));
+        assert!(!config.is_set(Some("lab"), "api.host"));
+        assert_eq!(config.api.port, 8443);
+    }
+
+    #[test]
+    fn test_is_setting() {
+        let config = Config::default();
+        assert!(config.is_setting("api.host"));
+        assert!(config.is_setting("api.language"));
+        assert!(config.is_setting("api.unix_socket"));
+        assert!(config.is_setting("agents.min_version"));
+        assert!(config.is_setting("profiles.lab.api.port"));
+        assert!(!config.is_setting("api.hots"));
+        assert!(!config.is_setting("profiles.lab.api.hots"));
     }
 
     #[test]
@@ -266,4 +1295,45 @@ mod tests {
         let config = Config::default();
         assert_eq!(config.api_url(), "https://localhost:55000");
     }
+
+    #[test]
+    fn test_indexer_defaults() {
+        let config: Config = toml::from_str("[indexer]\nhost = \"idx.example.com\"").unwrap();
+        assert_eq!(config.indexer_url(), "https://idx.example.com:9200");
+        assert_eq!(config.indexer.alerts_index, "wazuh-alerts-*");
+        assert_eq!(config.indexer.archives_index, "wazuh-archives-*");
+    }
+
+    #[test]
+    fn test_computed_columns_keep_order() {
+        let config: Config = toml::from_str(
+            "[output.columns.agents]\nzone = \"'dmz'\"\nfqdn = 'name + \".corp.local\"'",
+        )
+        .unwrap();
+        let agents = &config.output.columns["agents"];
+        assert_eq!(agents.keys().collect::<Vec<_>>(), ["zone", "fqdn"]);
+    }
+
+    #[test]
+    fn test_tls_profile_for_host() {
+        let mut config: Config = toml::from_str(
+            "[tls]\nca_cert = \"/etc/ca/prod.pem\"\n\
+             [tls.profiles.lab]\nhosts = [\"wazuh.lab\"]\nca_certs = [\"/etc/ca/lab.pem\"]\n\
+             client_cert = \"/etc/lab.crt\"\nclient_key = \"/etc/lab.key\"\n",
+        )
+        .unwrap();
+
+        let prod = config.tls.for_host("wazuh.prod").unwrap();
+        assert_eq!(prod.ca_certs, [PathBuf::from("/etc/ca/prod.pem")]);
+        assert!(prod.builtin_roots && prod.client_cert.is_none());
+
+        let lab = config.tls.for_host("WAZUH.lab").unwrap();
+        assert_eq!(lab.ca_certs, [PathBuf::from("/etc/ca/lab.pem")]);
+        assert_eq!(config.tls.for_host("indexer.prod").unwrap(), prod);
+
+        config.tls.profile = Some("lab".to_string());
+        assert_eq!(config.tls.for_host("wazuh.prod").unwrap(), lab);
+        config.tls.profile = Some("staging".to_string());
+        assert!(config.tls.for_host("wazuh.prod").is_err());
+    }
 }
\ No newline at end of file
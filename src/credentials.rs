@@ -0,0 +1,99 @@
+//! API credentials kept in the OS credential store (macOS Keychain, Windows
+//! Credential Manager, the Secret Service on Linux) instead of `config.toml`.
+//!
+//! `auth login` stores a password under the user and API URL, or a token under
+//! the API URL alone; they fill in `auth.password` and `auth.token` when those
+//! are not set. `auth.keyring = false` turns the store off, e.g. on servers
+//! without a Secret Service.
+
+use anyhow::{Context, Result};
+use keyring::{Entry, Error as KeyringError};
+use tracing::debug;
+
+use crate::config::Config;
+
+const PASSWORD_SERVICE: &str = "wazuh-cli";
+const TOKEN_SERVICE: &str = "wazuh-cli/token";
+
+/// A secret in the credential store
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Secret {
+    Password,
+    Token,
+}
+
+impl Secret {
+    pub fn name(self) -> &'static str {
+        match self {
+            Secret::Password => "password",
+            Secret::Token => "token",
+        }
+    }
+}
+
+/// The store entry of a secret; passwords need a username
+fn entry(config: &Config, secret: Secret) -> Result<Option<Entry>> {
+    let entry = match secret {
+        Secret::Password => match &config.auth.username {
+            Some(username) => Entry::new(
+                PASSWORD_SERVICE,
+                &format!("{}@{}", username, config.api_url()),
+            )?,
+            None => return Ok(None),
+        },
+        Secret::Token => Entry::new(TOKEN_SERVICE, &config.api_url())?,
+    };
+    Ok(Some(entry))
+}
+
+/// Fill in the password and token missing from the configuration from the
+/// credential store; an unavailable store is skipped
+pub fn apply(config: &mut Config) {
+    if !config.auth.keyring {
+        return;
+    }
+    if config.auth.password.is_none() {
+        config.auth.password = load(config, Secret::Password);
+    }
+    if config.auth.token.is_none() {
+        config.auth.token = load(config, Secret::Token);
+    }
+}
+
+fn load(config: &Config, secret: Secret) -> Option<String> {
+    let result = entry(config, secret).and_then(|entry| match entry {
+        Some(entry) => Ok(Some(entry.get_password()?)),
+        None => Ok(None),
+    });
+    match result {
+        Ok(value) => value,
+        Err(e) => {
+            if !matches!(e.downcast_ref(), Some(KeyringError::NoEntry)) {
+                debug!("No {} from the credential store: {:#}", secret.name(), e);
+            }
+            None
+        }
+    }
+}
+
+pub fn store(config: &Config, secret: Secret, value: &str) -> Result<()> {
+    let entry = entry(config, secret)?
+        .with_context(|| format!("A username is needed to store a {}", secret.name()))?;
+    entry
+        .set_password(value)
+        .with_context(|| format!("Failed to store the {} in the credential store", secret.name()))
+}
+
+/// Remove a secret; `false` when there was none
+pub fn remove(config: &Config, secret: Secret) -> Result<bool> {
+    let Some(entry) = entry(config, secret)? else {
+        return Ok(false);
+    };
+    match entry.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(KeyringError::NoEntry) => Ok(false),
+        Err(e) => Err(e).with_context(|| {
+            format!("Failed to remove the {} from the credential store", secret.name())
+        }),
+    }
+}
@@ -0,0 +1,423 @@
+//! Diff computation and rendering shared by every feature that compares files:
+//! staged configuration changes, drift checks and anything added later.
+//!
+//! Diffs are computed and stored as plain unified text, which keeps JSON output
+//! and saved changes stable; styling only happens when they are printed.
+
+use colored::Colorize;
+use similar::TextDiff;
+use std::borrow::Cow;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use crate::error::WazuhError;
+
+/// Context lines around each change unless configured otherwise
+pub const DEFAULT_CONTEXT: usize = 3;
+
+/// Narrowest column a side-by-side diff is squeezed into
+const MIN_COLUMN_WIDTH: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffStyle {
+    Unified,
+    SideBySide,
+}
+
+impl FromStr for DiffStyle {
+    type Err = WazuhError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "unified" => Ok(DiffStyle::Unified),
+            "side-by-side" | "side_by_side" | "split" => Ok(DiffStyle::SideBySide),
+            _ => Err(WazuhError::InvalidInput(format!(
+                "Unknown diff style '{}': use unified or side-by-side",
+                s
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffOptions {
+    pub style: DiffStyle,
+    /// Unchanged lines shown around each change
+    pub context: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            style: DiffStyle::Unified,
+            context: DEFAULT_CONTEXT,
+        }
+    }
+}
+
+/// `[output] diff_style` / `diff_context`, overridable per run
+static OPTIONS: RwLock<DiffOptions> = RwLock::new(DiffOptions {
+    style: DiffStyle::Unified,
+    context: DEFAULT_CONTEXT,
+});
+
+/// Select how diffs are computed and printed for the rest of the process
+pub fn set_options(options: DiffOptions) {
+    if let Ok(mut current) = OPTIONS.write() {
+        *current = options;
+    }
+}
+
+pub fn options() -> DiffOptions {
+    OPTIONS.read().map(|options| *options).unwrap_or_default()
+}
+
+/// Unified diff between two versions of a file, with the configured context
+pub fn unified(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(options().context)
+        .header(old_name, new_name)
+        .to_string()
+}
+
+/// Lines of a unified diff
+#[derive(Debug, Clone, PartialEq)]
+enum Line<'a> {
+    Header(&'a str),
+    /// Recomputed when context is trimmed, so it may not borrow from the diff
+    Hunk(Cow<'a, str>),
+    Context(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+    /// `\ No newline at end of file`
+    Marker(&'a str),
+}
+
+/// First line and line count of each side in a hunk header such as
+/// `@@ -1,5 +1,6 @@`, as written: an empty side names the line before it
+fn hunk_ranges(header: &str) -> Option<((usize, usize), (usize, usize))> {
+    let mut parts = header.split_whitespace().skip(1);
+    let mut range = |sign: char| -> Option<(usize, usize)> {
+        let range = parts.next()?.strip_prefix(sign)?;
+        match range.split_once(',') {
+            Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    Some((range('-')?, range('+')?))
+}
+
+/// Number of the first line a hunk side covers
+fn first_line((start, len): (usize, usize)) -> usize {
+    if len == 0 {
+        start + 1
+    } else {
+        start
+    }
+}
+
+/// One side of a hunk header, written the way `similar` writes it
+fn format_range(first: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", first - 1),
+        1 => first.to_string(),
+        _ => format!("{},{}", first, len),
+    }
+}
+
+fn parse(diff: &str) -> Vec<Line<'_>> {
+    // Lines left in the current hunk; only outside hunks can `---` and `+++`
+    // start file headers, inside they are removed and added lines
+    let (mut old_left, mut new_left) = (0usize, 0usize);
+    let mut lines = Vec::new();
+    for line in diff.lines() {
+        let in_hunk = old_left > 0 || new_left > 0;
+        lines.push(if !in_hunk && (line.starts_with("+++") || line.starts_with("---")) {
+            Line::Header(line)
+        } else if line.starts_with("@@") {
+            (old_left, new_left) = hunk_ranges(line)
+                .map(|(old, new)| (old.1, new.1))
+                .unwrap_or_default();
+            Line::Hunk(Cow::Borrowed(line))
+        } else if line.starts_with('\\') {
+            Line::Marker(line)
+        } else if let Some(text) = line.strip_prefix('+') {
+            new_left = new_left.saturating_sub(1);
+            Line::Added(text)
+        } else if let Some(text) = line.strip_prefix('-') {
+            old_left = old_left.saturating_sub(1);
+            Line::Removed(text)
+        } else {
+            old_left = old_left.saturating_sub(1);
+            new_left = new_left.saturating_sub(1);
+            Line::Context(line.strip_prefix(' ').unwrap_or(line))
+        });
+    }
+    lines
+}
+
+/// Drop context lines further than `context` from any change, splitting hunks
+/// whose changes end up apart and rewriting their headers to match; a stored
+/// diff can only be narrowed, not widened
+fn trim_context(lines: Vec<Line<'_>>, context: usize) -> Vec<Line<'_>> {
+    let mut trimmed = Vec::new();
+    let mut rest = lines.as_slice();
+    while let Some((line, after)) = rest.split_first() {
+        let Line::Hunk(header) = line else {
+            trimmed.push(line.clone());
+            rest = after;
+            continue;
+        };
+        let body_len = after
+            .iter()
+            .position(|l| matches!(l, Line::Header(_) | Line::Hunk(_)))
+            .unwrap_or(after.len());
+        let (body, next) = after.split_at(body_len);
+        trim_hunk(header, body, context, &mut trimmed);
+        rest = next;
+    }
+    trimmed
+}
+
+/// The lines of one hunk left after trimming its context, under new headers
+fn trim_hunk<'a>(header: &str, body: &[Line<'a>], context: usize, out: &mut Vec<Line<'a>>) {
+    let is_change = |line: &Line| matches!(line, Line::Added(_) | Line::Removed(_));
+    let changes: Vec<usize> = (0..body.len()).filter(|&i| is_change(&body[i])).collect();
+    let mut keep: Vec<bool> = (0..body.len())
+        .map(|i| match body[i] {
+            Line::Context(_) => changes.iter().any(|&c| c.abs_diff(i) <= context),
+            _ => is_change(&body[i]),
+        })
+        .collect();
+    // A marker belongs to the line before it
+    for i in 1..body.len() {
+        if matches!(body[i], Line::Marker(_)) {
+            keep[i] = keep[i - 1];
+        }
+    }
+
+    let Some((old, new)) = hunk_ranges(header) else {
+        // Without line numbers the header cannot be rewritten; keep it as it was
+        out.push(Line::Hunk(Cow::Owned(header.to_string())));
+        out.extend(body.iter().zip(&keep).filter(|(_, k)| **k).map(|(l, _)| l.clone()));
+        return;
+    };
+    // Anything after the ranges, such as the enclosing function
+    let section = header.get(2..).and_then(|h| h.find("@@")).map_or("", |i| &header[i + 4..]);
+
+    let (mut old_line, mut new_line) = (first_line(old), first_line(new));
+    let mut index = 0;
+    while index < body.len() {
+        if !keep[index] {
+            match body[index] {
+                Line::Removed(_) => old_line += 1,
+                Line::Added(_) => new_line += 1,
+                Line::Context(_) => {
+                    old_line += 1;
+                    new_line += 1;
+                }
+                _ => {}
+            }
+            index += 1;
+            continue;
+        }
+
+        let run_end = (index..body.len()).find(|&i| !keep[i]).unwrap_or(body.len());
+        let run = &body[index..run_end];
+        let old_len = run.iter().filter(|l| matches!(l, Line::Context(_) | Line::Removed(_))).count();
+        let new_len = run.iter().filter(|l| matches!(l, Line::Context(_) | Line::Added(_))).count();
+        out.push(Line::Hunk(Cow::Owned(format!(
+            "@@ -{} +{} @@{}",
+            format_range(old_line, old_len),
+            format_range(new_line, new_len),
+            section
+        ))));
+        out.extend(run.iter().cloned());
+        old_line += old_len;
+        new_line += new_len;
+        index = run_end;
+    }
+}
+
+/// Render a stored unified diff for the terminal in the configured style
+pub fn render(diff: &str, width: usize) -> Vec<String> {
+    let options = options();
+    let lines = trim_context(parse(diff), options.context);
+
+    match options.style {
+        DiffStyle::Unified => lines.iter().map(render_unified).collect(),
+        DiffStyle::SideBySide => render_side_by_side(&lines, width),
+    }
+}
+
+fn render_unified(line: &Line) -> String {
+    match line {
+        Line::Header(text) => text.bold().to_string(),
+        Line::Hunk(text) => text.cyan().to_string(),
+        Line::Added(text) => format!("+{}", text).green().to_string(),
+        Line::Removed(text) => format!("-{}", text).red().to_string(),
+        Line::Context(text) => format!(" {}", text),
+        Line::Marker(text) => text.dimmed().to_string(),
+    }
+}
+
+/// Old file on the left, new on the right; removed and added runs are paired up row by row
+fn render_side_by_side(lines: &[Line], width: usize) -> Vec<String> {
+    let column = (width.saturating_sub(3) / 2).max(MIN_COLUMN_WIDTH);
+    let mut output = Vec::new();
+    let mut removed: Vec<&str> = Vec::new();
+    let mut added: Vec<&str> = Vec::new();
+
+    for line in lines {
+        match line {
+            Line::Removed(text) => removed.push(text),
+            Line::Added(text) => added.push(text),
+            Line::Header(text) => {
+                pair_changes(&mut removed, &mut added, column, &mut output);
+                output.push(text.bold().to_string());
+            }
+            Line::Hunk(text) => {
+                pair_changes(&mut removed, &mut added, column, &mut output);
+                output.push(text.cyan().to_string());
+            }
+            Line::Context(text) => {
+                pair_changes(&mut removed, &mut added, column, &mut output);
+                output.push(side_by_side_row(Some(text), Some(text), column));
+            }
+            Line::Marker(text) => {
+                pair_changes(&mut removed, &mut added, column, &mut output);
+                output.push(text.dimmed().to_string());
+            }
+        }
+    }
+    pair_changes(&mut removed, &mut added, column, &mut output);
+    output
+}
+
+fn pair_changes(
+    removed: &mut Vec<&str>,
+    added: &mut Vec<&str>,
+    column: usize,
+    output: &mut Vec<String>,
+) {
+    for i in 0..removed.len().max(added.len()) {
+        output.push(side_by_side_row(removed.get(i).copied(), added.get(i).copied(), column));
+    }
+    removed.clear();
+    added.clear();
+}
+
+/// One row, marked `|` for a changed line, `<` removed, `>` added or blank if unchanged
+fn side_by_side_row(left: Option<&str>, right: Option<&str>, column: usize) -> String {
+    let (marker, left_text, right_text) = match (left, right) {
+        (Some(l), Some(r)) if l == r => (' ', l, r),
+        (Some(l), Some(r)) => ('|', l, r),
+        (Some(l), None) => ('<', l, ""),
+        (None, Some(r)) => ('>', "", r),
+        (None, None) => (' ', "", ""),
+    };
+    let left = pad(left_text, column);
+    let right = truncate(right_text, column);
+    let (left, right) = match marker {
+        '|' => (left.red().to_string(), right.green().to_string()),
+        '<' => (left.red().to_string(), right),
+        '>' => (left, right.green().to_string()),
+        _ => (left, right),
+    };
+    format!("{} {} {}", left, marker, right)
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+fn pad(text: &str, width: usize) -> String {
+    let text = truncate(text, width);
+    let padding = width.saturating_sub(text.chars().count());
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "--- old\n+++ new\n@@ -1,5 +1,5 @@\n a\n b\n c\n-d\n+D\n e\n";
+
+    #[test]
+    fn test_trim_context() {
+        let lines = trim_context(parse(DIFF), 1);
+        assert_eq!(
+            lines,
+            [
+                Line::Header("--- old"),
+                Line::Header("+++ new"),
+                Line::Hunk("@@ -3,3 +3,3 @@".into()),
+                Line::Context("c"),
+                Line::Removed("d"),
+                Line::Added("D"),
+                Line::Context("e"),
+            ]
+        );
+        assert_eq!(trim_context(parse(DIFF), 0).len(), 5);
+    }
+
+    #[test]
+    fn test_trim_context_splits_hunks() {
+        let diff = "@@ -1,7 +1,7 @@ section\n-a\n+A\n b\n c\n d\n e\n-f\n+F\n g\n";
+        let headers: Vec<Line> = trim_context(parse(diff), 1)
+            .into_iter()
+            .filter(|line| matches!(line, Line::Hunk(_)))
+            .collect();
+        assert_eq!(
+            headers,
+            [
+                Line::Hunk("@@ -1,2 +1,2 @@ section".into()),
+                Line::Hunk("@@ -5,3 +5,3 @@ section".into()),
+            ]
+        );
+
+        let added = trim_context(parse("@@ -2,2 +2,3 @@\n a\n+b\n c\n"), 0);
+        assert_eq!(added, [Line::Hunk("@@ -2,0 +3 @@".into()), Line::Added("b")]);
+    }
+
+    #[test]
+    fn test_parse_dashes_inside_hunks() {
+        let diff = "--- old\n+++ new\n@@ -1,2 +1,2 @@\n--- comment\n+++ comment\n a\n";
+        assert_eq!(
+            parse(diff),
+            [
+                Line::Header("--- old"),
+                Line::Header("+++ new"),
+                Line::Hunk("@@ -1,2 +1,2 @@".into()),
+                Line::Removed("-- comment"),
+                Line::Added("++ comment"),
+                Line::Context("a"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_side_by_side() {
+        let plain = |lines: Vec<String>| -> Vec<String> {
+            lines.iter().map(|l| console::strip_ansi_codes(l).to_string()).collect()
+        };
+        let lines = plain(render_side_by_side(&parse(DIFF), 43));
+        assert_eq!(lines[6], format!("{} | D", pad("d", 20)));
+        assert_eq!(lines[4], format!("{}   b", pad("b", 20)));
+
+        let added = plain(render_side_by_side(&parse("@@ -1 +1,2 @@\n a\n+b\n"), 43));
+        assert_eq!(added[2], format!("{} > b", pad("", 20)));
+    }
+
+    #[test]
+    fn test_diff_style_parsing() {
+        assert_eq!("side-by-side".parse::<DiffStyle>().unwrap(), DiffStyle::SideBySide);
+        assert!("word".parse::<DiffStyle>().is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::models::{Agent, AgentStatus};
+use crate::utils::{json_field, json_to_text};
+
+/// Rule fired by the manager when an agent stops cleanly
+const RULE_AGENT_STOPPED: &str = "506";
+/// Rule fired when the manager stops receiving keep-alives
+const RULE_AGENT_DISCONNECTED: &str = "504";
+
+/// Manager log phrases pointing at a key problem (remoted 1403/1404/1408)
+const KEY_MISMATCH_PATTERNS: &[&str] = &[
+    "wrong key",
+    "corrupt payload",
+    "invalid id",
+    "incorrectly formatted message",
+    "decrypt",
+];
+const DUPLICATE_PATTERNS: &[&str] = &["duplicate", "already in use", "duplicated"];
+const VERSION_PATTERNS: &[&str] = &["incompatible version", "version must be lower"];
+const STOPPED_PATTERNS: &[&str] = &["agent stopped", "shutdown message"];
+const NETWORK_PATTERNS: &[&str] = &["timeout", "timed out", "connection reset", "broken pipe"];
+
+/// A likely reason for an agent being offline
+#[derive(Debug, Clone, Serialize)]
+pub struct DisconnectCause {
+    pub cause: String,
+    /// Log lines or alerts supporting the diagnosis
+    pub evidence: Vec<String>,
+    pub hint: String,
+}
+
+/// Everything gathered about a disconnected agent
+#[derive(Debug, Serialize)]
+pub struct DisconnectReport {
+    pub agent_id: String,
+    pub agent_name: String,
+    pub status: AgentStatus,
+    pub last_keep_alive: Option<DateTime<Utc>>,
+    pub offline_secs: Option<i64>,
+    /// Most likely cause first
+    pub causes: Vec<DisconnectCause>,
+    pub log_lines: Vec<String>,
+    pub alerts: Vec<String>,
+    /// remoted counters at the time of the check
+    pub remoted: Value,
+}
+
+/// Match the evidence against known failure patterns, most specific first
+pub fn diagnose(
+    agent: &Agent,
+    log_lines: &[String],
+    alerts: &[Value],
+    remoted: &Value,
+) -> Vec<DisconnectCause> {
+    match agent.status {
+        AgentStatus::Active => {
+            return vec![cause(
+                "Agent is connected",
+                Vec::new(),
+                "The agent is sending keep-alives; nothing to diagnose",
+            )]
+        }
+        AgentStatus::NeverConnected => {
+            return vec![cause(
+                "Agent never connected",
+                Vec::new(),
+                "Check that the agent was installed with this key and can reach the \
+                 manager on port 1514",
+            )]
+        }
+        _ => {}
+    }
+
+    let mut causes = Vec::new();
+
+    let mut key_evidence = matching(log_lines, KEY_MISMATCH_PATTERNS);
+    let discarded = remoted["discarded_count"].as_u64().unwrap_or(0);
+    if !key_evidence.is_empty() && discarded > 0 {
+        key_evidence.push(format!("remoted discarded {} messages", discarded));
+    }
+    if !key_evidence.is_empty() {
+        causes.push(cause(
+            "Key mismatch",
+            key_evidence,
+            "Re-import the key with 'agent key push' or re-enroll the agent",
+        ));
+    }
+
+    let duplicate = matching(log_lines, DUPLICATE_PATTERNS);
+    if !duplicate.is_empty() {
+        causes.push(cause(
+            "Duplicate agent",
+            duplicate,
+            "Another host is using this agent's name, IP or key; remove the stale registration",
+        ));
+    }
+
+    let version = matching(log_lines, VERSION_PATTERNS);
+    if !version.is_empty() {
+        causes.push(cause(
+            "Incompatible agent version",
+            version,
+            "Agents must not be newer than the manager; upgrade the manager or downgrade the agent",
+        ));
+    }
+
+    let mut stopped = matching(log_lines, STOPPED_PATTERNS);
+    stopped.extend(alerts_for_rule(alerts, RULE_AGENT_STOPPED));
+    if !stopped.is_empty() {
+        causes.push(cause(
+            "Agent stopped",
+            stopped,
+            "The agent service was stopped on the host; start it and check why it stopped",
+        ));
+    }
+
+    let mut network = matching(log_lines, NETWORK_PATTERNS);
+    network.extend(alerts_for_rule(alerts, RULE_AGENT_DISCONNECTED));
+    if !network.is_empty() || causes.is_empty() {
+        causes.push(cause(
+            "Network timeout",
+            network,
+            "The manager stopped receiving keep-alives; check that the host is up and can \
+             reach the manager on port 1514",
+        ));
+    }
+
+    causes
+}
+
+fn cause(cause: &str, evidence: Vec<String>, hint: &str) -> DisconnectCause {
+    DisconnectCause {
+        cause: cause.to_string(),
+        evidence,
+        hint: hint.to_string(),
+    }
+}
+
+fn matching(lines: &[String], patterns: &[&str]) -> Vec<String> {
+    lines
+        .iter()
+        .filter(|line| {
+            let line = line.to_lowercase();
+            patterns.iter().any(|pattern| line.contains(pattern))
+        })
+        .cloned()
+        .collect()
+}
+
+fn alerts_for_rule(alerts: &[Value], rule_id: &str) -> Vec<String> {
+    alerts
+        .iter()
+        .filter(|alert| json_to_text(json_field(alert, "rule.id")) == rule_id)
+        .map(|alert| {
+            format!(
+                "{} alert {}: {}",
+                json_to_text(json_field(alert, "timestamp")),
+                rule_id,
+                json_to_text(json_field(alert, "rule.description"))
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn agent(status: &str) -> Agent {
+        serde_json::from_value(json!({"id": "001", "name": "web-01", "status": status})).unwrap()
+    }
+
+    #[test]
+    fn test_diagnose_key_mismatch() {
+        let logs = vec![
+            "2024-01-01 wazuh-remoted WARNING: (1404): Authentication error. Wrong key or \
+             corrupt payload. Message received from agent '001'"
+                .to_string(),
+        ];
+        let causes = diagnose(&agent("disconnected"), &logs, &[], &json!({"discarded_count": 3}));
+
+        assert_eq!(causes.len(), 1);
+        assert_eq!(causes[0].cause, "Key mismatch");
+        assert_eq!(causes[0].evidence.len(), 2);
+    }
+
+    #[test]
+    fn test_diagnose_from_alerts() {
+        let alerts = vec![json!({
+            "timestamp": "2024-01-01T00:00:00Z",
+            "rule": {"id": "506", "description": "Wazuh agent stopped."}
+        })];
+        let causes = diagnose(&agent("disconnected"), &[], &alerts, &json!({}));
+        assert_eq!(causes[0].cause, "Agent stopped");
+
+        let causes = diagnose(&agent("disconnected"), &[], &[], &json!({}));
+        assert_eq!(causes[0].cause, "Network timeout");
+        assert!(causes[0].evidence.is_empty());
+    }
+}
@@ -0,0 +1,384 @@
+//! Connectivity checks behind `config test`.
+//!
+//! Each step depends on the one before it (DNS, connection, TLS, login, API),
+//! so the first failure names the layer to look at and the later steps are
+//! skipped rather than failing with the same cause.
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::sync::RwLock;
+
+use crate::certs;
+use crate::client::WazuhClient;
+use crate::config::Config;
+use crate::models::ApiResponse;
+use crate::ssh::{self, Tunnel};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    /// Passed, with something to look at (e.g. a certificate about to expire)
+    Warn,
+    Fail,
+    Skip,
+}
+
+/// Outcome of one connectivity step
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn hint(mut self, hint: &str) -> Self {
+        self.hint = Some(hint.to_string());
+        self
+    }
+}
+
+const DNS: &str = "DNS resolution";
+const CONNECTION: &str = "Connection";
+const TLS: &str = "TLS handshake";
+const AUTHENTICATION: &str = "Authentication";
+const API_VERSION: &str = "API version";
+
+/// Run every step against the configured manager, stopping at the first failure
+pub async fn run(config: &Config) -> Vec<Check> {
+    let timeout = Duration::from_secs(config.api.timeout);
+    let mut checks = Vec::new();
+
+    let addrs = match resolve(config, timeout).await {
+        Ok((addrs, check)) => {
+            checks.push(check);
+            addrs
+        }
+        Err(check) => return finish(checks, check),
+    };
+
+    // Held until the checks are done, so the client below reuses the tunnel
+    let mut _tunnel = None;
+    let connected = match &config.api.ssh_tunnel {
+        Some(jump_host) => open_tunnel(config, jump_host, timeout).await.map(|(tunnel, check)| {
+            _tunnel = Some(tunnel);
+            check
+        }),
+        None => connect(config, &addrs, timeout).await,
+    };
+    match connected {
+        Ok(check) => checks.push(check),
+        Err(check) => return finish(checks, check),
+    }
+
+    let client = match WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await {
+        Ok(client) => client,
+        Err(e) => {
+            let check = Check::new(TLS, CheckStatus::Fail, format!("{:#}", e))
+                .hint("Check the [tls] settings of this profile");
+            return finish(checks, check);
+        }
+    };
+
+    match handshake(config, &client, timeout).await {
+        Ok(check) => checks.push(check),
+        Err(check) => return finish(checks, check),
+    }
+
+    match login(config, &client).await {
+        Ok(check) => checks.push(check),
+        Err(check) => return finish(checks, check),
+    }
+
+    checks.push(api_version(&client).await);
+    checks
+}
+
+/// Add the failed check, then skip the steps after it
+fn finish(mut checks: Vec<Check>, failed: Check) -> Vec<Check> {
+    let reason = format!("{} failed", failed.name);
+    checks.push(failed);
+    for name in [DNS, CONNECTION, TLS, AUTHENTICATION, API_VERSION] {
+        if !checks.iter().any(|check| check.name == name) {
+            checks.push(Check::new(name, CheckStatus::Skip, reason.clone()));
+        }
+    }
+    checks
+}
+
+async fn resolve(config: &Config, timeout: Duration) -> Result<(Vec<SocketAddr>, Check), Check> {
+    if let Some(socket) = &config.api.unix_socket {
+        let detail = format!("connecting through {}", socket.display());
+        return Ok((Vec::new(), Check::new(DNS, CheckStatus::Skip, detail)));
+    }
+    if let Some(jump_host) = &config.api.ssh_tunnel {
+        let detail = format!("{} is resolved by {}", config.api.host, jump_host);
+        return Ok((Vec::new(), Check::new(DNS, CheckStatus::Skip, detail)));
+    }
+
+    let pinned = config
+        .resolve_override()
+        .map_err(|e| Check::new(DNS, CheckStatus::Fail, e.to_string()))?;
+    if let Some((host, addr)) = pinned.filter(|(host, _)| *host == config.api.host) {
+        let detail = format!("{} pinned to {} by api.resolve", host, addr.ip());
+        return Ok((vec![addr], Check::new(DNS, CheckStatus::Pass, detail)));
+    }
+
+    let host = config.api.host.as_str();
+    let lookup = tokio::net::lookup_host((host, config.api.port));
+    let addrs: Vec<SocketAddr> = match tokio::time::timeout(timeout, lookup).await {
+        Ok(Ok(addrs)) => addrs.collect(),
+        Ok(Err(e)) => {
+            let check = Check::new(DNS, CheckStatus::Fail, format!("{}: {}", host, e))
+                .hint("Check api.host, or pin an address with api.resolve");
+            return Err(check);
+        }
+        Err(_) => {
+            let detail = format!("{}: no answer within {}s", host, timeout.as_secs());
+            return Err(Check::new(DNS, CheckStatus::Fail, detail));
+        }
+    };
+    if addrs.is_empty() {
+        let detail = format!("{}: no addresses", host);
+        return Err(Check::new(DNS, CheckStatus::Fail, detail));
+    }
+
+    let ips: Vec<String> = addrs.iter().map(|addr| addr.ip().to_string()).collect();
+    let detail = format!("{} -> {}", host, ips.join(", "));
+    Ok((addrs, Check::new(DNS, CheckStatus::Pass, detail)))
+}
+
+async fn connect(
+    config: &Config,
+    addrs: &[SocketAddr],
+    timeout: Duration,
+) -> Result<Check, Check> {
+    let started = Instant::now();
+    if let Some(socket) = &config.api.unix_socket {
+        return match connect_unix(socket).await {
+            Ok(()) => {
+                let detail = format!("{} accepts connections", socket.display());
+                Ok(Check::new(CONNECTION, CheckStatus::Pass, detail))
+            }
+            Err(e) => {
+                let detail = format!("{}: {:#}", socket.display(), e);
+                Err(Check::new(CONNECTION, CheckStatus::Fail, detail)
+                    .hint("Check that the socket forward is running"))
+            }
+        };
+    }
+
+    let mut errors = Vec::new();
+    for addr in addrs {
+        match tokio::time::timeout(timeout, TcpStream::connect(addr)).await {
+            Ok(Ok(_)) => {
+                let elapsed = started.elapsed().as_millis();
+                let detail = format!("TCP {} open ({} ms)", addr, elapsed);
+                return Ok(Check::new(CONNECTION, CheckStatus::Pass, detail));
+            }
+            Ok(Err(e)) => errors.push(format!("{}: {}", addr, e)),
+            Err(_) => errors.push(format!("{}: timed out after {}s", addr, timeout.as_secs())),
+        }
+    }
+    Err(Check::new(CONNECTION, CheckStatus::Fail, errors.join("; ")).hint(
+        "Check that the API is running and that firewalls allow the port (default 55000)",
+    ))
+}
+
+async fn open_tunnel(
+    config: &Config,
+    jump_host: &str,
+    timeout: Duration,
+) -> Result<(Arc<Tunnel>, Check), Check> {
+    let started = Instant::now();
+    match ssh::shared_tunnel(jump_host, &config.api.host, config.api.port, timeout).await {
+        Ok(tunnel) => {
+            let detail = format!(
+                "SSH tunnel through {} to {}:{} on {} ({} ms)",
+                jump_host,
+                config.api.host,
+                config.api.port,
+                tunnel.local,
+                started.elapsed().as_millis()
+            );
+            Ok((tunnel, Check::new(CONNECTION, CheckStatus::Pass, detail)))
+        }
+        Err(e) => Err(Check::new(CONNECTION, CheckStatus::Fail, format!("{:#}", e)).hint(
+            "Check that 'ssh JUMP_HOST' logs in, and that the jump host reaches the API port",
+        )),
+    }
+}
+
+#[cfg(unix)]
+async fn connect_unix(socket: &std::path::Path) -> Result<()> {
+    tokio::net::UnixStream::connect(socket).await?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn connect_unix(_socket: &std::path::Path) -> Result<()> {
+    Err(anyhow!("Unix sockets are only supported on Unix"))
+}
+
+/// Complete a request with the configured trust settings, then describe the
+/// certificate the manager presented
+async fn handshake(
+    config: &Config,
+    client: &WazuhClient,
+    timeout: Duration,
+) -> Result<Check, Check> {
+    if config.api.protocol != "https" {
+        return Ok(Check::new(TLS, CheckStatus::Skip, "plain HTTP"));
+    }
+    // The settings the client uses for this host, which may be a [tls.profiles] entry
+    let tls = config
+        .tls
+        .for_host(&config.api.host)
+        .map_err(|e| Check::new(TLS, CheckStatus::Fail, e.to_string()))?;
+
+    // Fetched without verification, so a rejected certificate can be described;
+    // only direct connections can be inspected this way
+    let url = format!("{}/", config.api_url());
+    let now = Utc::now();
+    let direct = config.api.unix_socket.is_none() && config.api.ssh_tunnel.is_none();
+    let certificate = if direct {
+        certs::fetch_peer_certificate(&url, timeout)
+            .await
+            .ok()
+            .map(|der| certs::inspect_der(&url, &der, now, config.cert_audit.warn_days))
+    } else {
+        None
+    };
+    let summary = certificate.as_ref().map(|cert| {
+        let subject = cert.subject.as_deref().unwrap_or("unknown subject");
+        match (cert.not_after, cert.days_left) {
+            (Some(not_after), Some(days)) => {
+                format!("{}, expires {} ({} days)", subject, not_after.format("%Y-%m-%d"), days)
+            }
+            _ => subject.to_string(),
+        }
+    });
+
+    if let Err(e) = client.probe(&format!("{}/", client.base_url())).await {
+        let mut detail = format!("{:#}", e);
+        if let Some(summary) = &summary {
+            detail = format!("{}; certificate: {}", detail, summary);
+        }
+        let hint = if tls.verify {
+            "Add the CA that signed the manager's certificate to the TLS profile used for \
+             api.host (tls.ca_cert, or ca_certs of its [tls.profiles] entry)"
+        } else {
+            "Check the client certificate and key in the [tls] settings"
+        };
+        return Err(Check::new(TLS, CheckStatus::Fail, detail).hint(hint));
+    }
+
+    let mut detail = if tls.verify {
+        "verified".to_string()
+    } else {
+        "not verified (verify = false)".to_string()
+    };
+    if let Some(summary) = &summary {
+        detail = format!("{}, {}", detail, summary);
+    }
+    let issues = certificate.map(|cert| cert.issues).unwrap_or_default();
+    // Days left are already in the description
+    let shown: Vec<&str> = issues
+        .iter()
+        .map(String::as_str)
+        .filter(|issue| !issue.starts_with("expires in"))
+        .collect();
+    if !shown.is_empty() {
+        detail = format!("{}; {}", detail, shown.join(", "));
+    }
+    let status = if issues.is_empty() && tls.verify {
+        CheckStatus::Pass
+    } else {
+        CheckStatus::Warn
+    };
+    Ok(Check::new(TLS, status, detail))
+}
+
+/// Log in with the configured credentials, not a token cached from earlier;
+/// the cache is left as it was
+async fn login(config: &Config, client: &WazuhClient) -> Result<Check, Check> {
+    let result = match config.auth.token {
+        Some(_) => client.authenticate().await,
+        None => client.login().await.map(|_| ()),
+    };
+    if let Err(e) = result {
+        return Err(Check::new(AUTHENTICATION, CheckStatus::Fail, format!("{:#}", e))
+            .hint("Check auth.username and the password, or run 'wazuh-cli auth login'"));
+    }
+    let detail = match (&config.auth.token, &config.auth.username) {
+        (Some(_), _) => "API token accepted".to_string(),
+        (None, Some(username)) => format!("logged in as {}", username),
+        (None, None) => "logged in".to_string(),
+    };
+    Ok(Check::new(AUTHENTICATION, CheckStatus::Pass, detail))
+}
+
+async fn api_version(client: &WazuhClient) -> Check {
+    let info = async {
+        let response = client.get("/").await?;
+        let api_response: ApiResponse<Value> = WazuhClient::parse_response(response).await?;
+        let data = api_response.data;
+        let version = data["api_version"]
+            .as_str()
+            .ok_or_else(|| anyhow!("the response has no api_version"))?;
+        let title = data["title"].as_str().unwrap_or("Wazuh API");
+        let mut detail = format!("{} {}", title, version);
+        if let Some(hostname) = data["hostname"].as_str() {
+            detail.push_str(&format!(" on {}", hostname));
+        }
+        Ok::<_, anyhow::Error>(detail)
+    };
+    match info.await {
+        Ok(detail) => Check::new(API_VERSION, CheckStatus::Pass, detail),
+        Err(e) => Check::new(API_VERSION, CheckStatus::Fail, format!("{:#}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finish_skips_later_steps() {
+        let dns = Check::new(DNS, CheckStatus::Pass, "wazuh -> 10.0.0.1");
+        let failed = Check::new(CONNECTION, CheckStatus::Fail, "refused");
+        let checks = finish(vec![dns], failed);
+
+        let statuses: Vec<CheckStatus> = checks.iter().map(|check| check.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                CheckStatus::Pass,
+                CheckStatus::Fail,
+                CheckStatus::Skip,
+                CheckStatus::Skip,
+                CheckStatus::Skip
+            ]
+        );
+        assert_eq!(checks[4].name, API_VERSION);
+        assert_eq!(checks[4].detail, "Connection failed");
+    }
+}
@@ -0,0 +1,256 @@
+//! Self-contained HTML and Markdown documents for `report generate`.
+//!
+//! Reports are built as a list of sections, each with summary facts and an
+//! optional table, and rendered without external assets so the file can be
+//! attached to a ticket or mailed as is.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fmt::Write;
+use std::str::FromStr;
+
+use crate::error::WazuhError;
+
+/// Output format of a rendered document
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocumentFormat {
+    Html,
+    Markdown,
+}
+
+impl FromStr for DocumentFormat {
+    type Err = WazuhError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "html" => Ok(DocumentFormat::Html),
+            "md" | "markdown" => Ok(DocumentFormat::Markdown),
+            other => Err(WazuhError::InvalidInput(format!(
+                "Unknown report format '{}': expected html or md",
+                other
+            ))),
+        }
+    }
+}
+
+impl DocumentFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            DocumentFormat::Html => "text/html; charset=utf-8",
+            DocumentFormat::Markdown => "text/markdown; charset=utf-8",
+        }
+    }
+}
+
+/// A table in a document section
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DocumentTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// One titled part of a document
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Section {
+    pub heading: String,
+    /// Labeled summary values shown above the table
+    pub facts: Vec<(String, String)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<DocumentTable>,
+    /// Shown instead of an empty table
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl Section {
+    pub fn new(heading: &str) -> Self {
+        Self {
+            heading: heading.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn fact(mut self, label: &str, value: impl ToString) -> Self {
+        self.facts.push((label.to_string(), value.to_string()));
+        self
+    }
+
+    /// Add a table, or `empty` as a note when there are no rows
+    pub fn table(mut self, headers: &[&str], rows: Vec<Vec<String>>, empty: &str) -> Self {
+        if rows.is_empty() {
+            self.note = Some(empty.to_string());
+        } else {
+            self.table = Some(DocumentTable {
+                headers: headers.iter().map(|h| h.to_string()).collect(),
+                rows,
+            });
+        }
+        self
+    }
+}
+
+/// A titled report made of sections
+#[derive(Debug, Clone, Serialize)]
+pub struct Document {
+    pub title: String,
+    pub generated: DateTime<Utc>,
+    pub sections: Vec<Section>,
+}
+
+/// Embedded so the HTML file renders the same wherever it is opened
+const STYLE: &str = "\
+body{font-family:-apple-system,'Segoe UI',Roboto,sans-serif;margin:2em auto;max-width:1100px;\
+color:#1f2328;padding:0 1em}\
+h1{border-bottom:2px solid #3595f6;padding-bottom:.3em}h2{margin-top:2em;color:#0b5394}\
+.meta,.note{color:#59636e}.note{font-style:italic}\
+dl{display:grid;grid-template-columns:max-content auto;gap:.3em 1.5em}\
+dt{font-weight:600}dd{margin:0}\
+table{border-collapse:collapse;width:100%;font-size:.9em}\
+th,td{border:1px solid #d1d9e0;padding:.4em .6em;text-align:left;vertical-align:top}\
+th{background:#f6f8fa}tr:nth-child(even) td{background:#fbfcfd}";
+
+impl Document {
+    pub fn new(title: &str, sections: Vec<Section>) -> Self {
+        Self {
+            title: title.to_string(),
+            generated: Utc::now(),
+            sections,
+        }
+    }
+
+    pub fn render(&self, format: DocumentFormat) -> String {
+        match format {
+            DocumentFormat::Html => self.to_html(),
+            DocumentFormat::Markdown => self.to_markdown(),
+        }
+    }
+
+    fn generated_label(&self) -> String {
+        self.generated.format("%Y-%m-%d %H:%M UTC").to_string()
+    }
+
+    fn to_html(&self) -> String {
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n\
+             <h1>{title}</h1>\n<p class=\"meta\">Generated {generated} by wazuh-cli</p>\n",
+            title = escape_html(&self.title),
+            generated = self.generated_label(),
+        );
+
+        for section in &self.sections {
+            let _ = writeln!(html, "<h2>{}</h2>", escape_html(&section.heading));
+            if !section.facts.is_empty() {
+                html.push_str("<dl>\n");
+                for (label, value) in &section.facts {
+                    let _ = writeln!(
+                        html,
+                        "<dt>{}</dt><dd>{}</dd>",
+                        escape_html(label),
+                        escape_html(value)
+                    );
+                }
+                html.push_str("</dl>\n");
+            }
+            if let Some(table) = &section.table {
+                html.push_str("<table>\n<thead><tr>");
+                for header in &table.headers {
+                    let _ = write!(html, "<th>{}</th>", escape_html(header));
+                }
+                html.push_str("</tr></thead>\n<tbody>\n");
+                for row in &table.rows {
+                    html.push_str("<tr>");
+                    for cell in row {
+                        let _ = write!(html, "<td>{}</td>", escape_html(cell));
+                    }
+                    html.push_str("</tr>\n");
+                }
+                html.push_str("</tbody>\n</table>\n");
+            }
+            if let Some(note) = &section.note {
+                let _ = writeln!(html, "<p class=\"note\">{}</p>", escape_html(note));
+            }
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut md = format!(
+            "# {}\n\n_Generated {} by wazuh-cli_\n",
+            self.title,
+            self.generated_label()
+        );
+
+        for section in &self.sections {
+            let _ = write!(md, "\n## {}\n\n", section.heading);
+            for (label, value) in &section.facts {
+                let _ = writeln!(md, "- **{}:** {}", label, value);
+            }
+            if !section.facts.is_empty() {
+                md.push('\n');
+            }
+            if let Some(table) = &section.table {
+                let _ = writeln!(md, "| {} |", markdown_row(&table.headers));
+                let _ = writeln!(md, "|{}", "---|".repeat(table.headers.len()));
+                for row in &table.rows {
+                    let _ = writeln!(md, "| {} |", markdown_row(row));
+                }
+            }
+            if let Some(note) = &section.note {
+                let _ = writeln!(md, "_{}_", note);
+            }
+        }
+        md
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Table cells with pipes escaped and line breaks flattened
+fn markdown_row(cells: &[String]) -> String {
+    cells
+        .iter()
+        .map(|cell| cell.replace('|', "\\|").replace(['\r', '\n'], " "))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let document = Document::new(
+            "Agents <weekly>",
+            vec![
+                Section::new("Summary").fact("Total", 2),
+                Section::new("Agents").table(
+                    &["ID", "Name"],
+                    vec![vec!["001".to_string(), "web|01\nprod".to_string()]],
+                    "No agents",
+                ),
+                Section::new("Empty").table(&["ID"], Vec::new(), "No agents"),
+            ],
+        );
+
+        let md = document.render(DocumentFormat::Markdown);
+        assert!(md.contains("- **Total:** 2"));
+        assert!(md.contains("| ID | Name |\n|---|---|\n| 001 | web\\|01 prod |"));
+        assert!(md.contains("_No agents_"));
+
+        let html = document.render(DocumentFormat::Html);
+        assert!(html.contains("<title>Agents &lt;weekly&gt;</title>"));
+        assert!(html.contains("<td>web|01\nprod</td>"));
+        assert!(!html.contains("<table>\n<thead><tr><th>ID</th></tr>"));
+    }
+}
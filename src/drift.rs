@@ -0,0 +1,152 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::diff;
+
+/// Configuration files keyed by their path in the baseline archive,
+/// e.g. `manager/ossec.conf` or `groups/default/agent.conf`
+pub type Snapshot = BTreeMap<String, String>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DriftKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// One file that differs from the baseline
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftEntry {
+    pub path: String,
+    pub kind: DriftKind,
+    /// Unified diff from the baseline to the current file
+    pub diff: String,
+}
+
+/// Files added, removed or changed since the baseline, in path order
+pub fn compare(baseline: &Snapshot, current: &Snapshot) -> Vec<DriftEntry> {
+    let mut drift = Vec::new();
+
+    for (path, old) in baseline {
+        match current.get(path) {
+            None => drift.push(entry(path, DriftKind::Removed, old, "")),
+            Some(new) if new != old => drift.push(entry(path, DriftKind::Modified, old, new)),
+            Some(_) => {}
+        }
+    }
+    for (path, new) in current {
+        if !baseline.contains_key(path) {
+            drift.push(entry(path, DriftKind::Added, "", new));
+        }
+    }
+
+    drift.sort_by(|a, b| a.path.cmp(&b.path));
+    drift
+}
+
+fn entry(path: &str, kind: DriftKind, old: &str, new: &str) -> DriftEntry {
+    DriftEntry {
+        path: path.to_string(),
+        kind,
+        diff: diff::unified(old, new, &format!("baseline/{}", path), &format!("current/{}", path)),
+    }
+}
+
+/// Read a `.tar.gz` baseline
+pub fn read_baseline(path: &Path) -> Result<Snapshot> {
+    let file = File::open(path).with_context(|| format!("Failed to open baseline {:?}", path))?;
+    let decoder = libflate::gzip::Decoder::new(file)
+        .with_context(|| format!("Baseline {:?} is not a gzip archive", path))?;
+
+    let mut snapshot = Snapshot::new();
+    let mut archive = tar::Archive::new(decoder);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let mut contents = String::new();
+        entry
+            .read_to_string(&mut contents)
+            .with_context(|| format!("Failed to read {} from the baseline", name))?;
+        snapshot.insert(name, contents);
+    }
+
+    Ok(snapshot)
+}
+
+/// Write a snapshot as a `.tar.gz` baseline, replacing any existing file
+pub fn write_baseline(path: &Path, snapshot: &Snapshot) -> Result<()> {
+    let file = File::create(path).with_context(|| format!("Failed to create {:?}", path))?;
+    let encoder = libflate::gzip::Encoder::new(file)?;
+
+    let mut builder = tar::Builder::new(encoder);
+    for (name, contents) in snapshot {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+        header.set_cksum();
+        builder.append_data(&mut header, name, contents.as_bytes())?;
+    }
+
+    builder.into_inner()?.finish().into_result()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(files: &[(&str, &str)]) -> Snapshot {
+        files
+            .iter()
+            .map(|(path, contents)| (path.to_string(), contents.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_compare() {
+        let baseline = snapshot(&[
+            ("manager/ossec.conf", "<ossec_config/>\n"),
+            ("rules/local_rules.xml", "<group/>\n"),
+        ]);
+        let current = snapshot(&[
+            ("manager/ossec.conf", "<ossec_config>\n</ossec_config>\n"),
+            ("groups/db/agent.conf", "<agent_config/>\n"),
+        ]);
+
+        let drift = compare(&baseline, &current);
+        let kinds: Vec<(&str, DriftKind)> =
+            drift.iter().map(|d| (d.path.as_str(), d.kind)).collect();
+        assert_eq!(
+            kinds,
+            [
+                ("groups/db/agent.conf", DriftKind::Added),
+                ("manager/ossec.conf", DriftKind::Modified),
+                ("rules/local_rules.xml", DriftKind::Removed),
+            ]
+        );
+        assert!(drift[1].diff.contains("+<ossec_config>"));
+        assert!(compare(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn test_baseline_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.tar.gz");
+        let original = snapshot(&[
+            ("manager/ossec.conf", "<ossec_config/>\n"),
+            ("groups/default/agent.conf", ""),
+        ]);
+
+        write_baseline(&path, &original).unwrap();
+        assert_eq!(read_baseline(&path).unwrap(), original);
+    }
+}
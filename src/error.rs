@@ -6,6 +6,9 @@ pub enum WazuhError {
     ApiError { code: i32, message: String },
 
 
+    #[error("Indexer error ({status}): {message}")]
+    IndexerError { status: u16, message: String },
+
     #[error("Network error: {0}")]
     NetworkError(String),
 
@@ -13,19 +16,49 @@ pub enum WazuhError {
     SerializationError(String),
 
 
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
     #[error("Resource not found: {0}")]
     NotFound(String),
 
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
 
+    #[error(
+        "Response exceeds the {limit_mb} MB in-memory limit; narrow the query with filters \
+         or a lower --limit, or raise api.max_response_mb"
+    )]
+    ResponseTooLarge { limit_mb: u64 },
+
+    #[error(
+        "The {resource} lock is held by{holder}; retry with --wait-lock to wait for it, \
+         or --no-lock to skip locking"
+    )]
+    Locked { resource: String, holder: String },
+
     #[error("Operation timed out")]
     Timeout,
 
+    #[error("Configuration drift detected in {drifted} of {total} files")]
+    Drift { drifted: usize, total: usize },
+
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
 
+/// Exit status when `drift check` finds drift, so CI can tell it from a failure
+pub const EXIT_DRIFT: i32 = 2;
+
+/// Process exit status for an error: 1, or a distinct code for outcomes that
+/// scripts act on
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    match err.downcast_ref::<WazuhError>() {
+        Some(WazuhError::Drift { .. }) => EXIT_DRIFT,
+        _ => 1,
+    }
+}
+
 impl From<reqwest::Error> for WazuhError {
     fn from(err: reqwest::Error) -> Self {
         if err.is_timeout() {
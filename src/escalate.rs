@@ -0,0 +1,376 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::debug;
+
+use crate::config::{CaseManagerConfig, EscalationConfig};
+use crate::error::WazuhError;
+use crate::utils::{json_field, json_to_text};
+
+/// Tag identifying everything the CLI creates in a case manager
+const SOURCE: &str = "wazuh";
+/// Customer of a stock DFIR-IRIS install
+const DEFAULT_IRIS_CUSTOMER: u32 = 1;
+/// DFIR-IRIS alert status "New"
+const IRIS_STATUS_NEW: u32 = 2;
+
+/// Incident response platform receiving escalated alerts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Platform {
+    TheHive,
+    Iris,
+}
+
+impl FromStr for Platform {
+    type Err = WazuhError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "thehive" | "hive" => Ok(Platform::TheHive),
+            "iris" | "dfir-iris" => Ok(Platform::Iris),
+            _ => Err(WazuhError::InvalidInput(format!(
+                "Unknown platform '{}': use thehive or iris",
+                s
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Platform::TheHive => write!(f, "TheHive"),
+            Platform::Iris => write!(f, "DFIR-IRIS"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObservableKind {
+    Ip,
+    Hostname,
+    Url,
+    Filename,
+    Md5,
+    Sha256,
+}
+
+impl ObservableKind {
+    /// TheHive data type
+    fn thehive_type(self) -> &'static str {
+        match self {
+            ObservableKind::Ip => "ip",
+            ObservableKind::Hostname => "hostname",
+            ObservableKind::Url => "url",
+            ObservableKind::Filename => "filename",
+            ObservableKind::Md5 | ObservableKind::Sha256 => "hash",
+        }
+    }
+
+    /// IDs of the stock DFIR-IRIS IOC types (`GET /manage/ioc-types/list`)
+    fn iris_type_id(self) -> u32 {
+        match self {
+            ObservableKind::Ip => 79,
+            ObservableKind::Hostname => 69,
+            ObservableKind::Url => 141,
+            ObservableKind::Filename => 37,
+            ObservableKind::Md5 => 90,
+            ObservableKind::Sha256 => 113,
+        }
+    }
+}
+
+/// An indicator taken from an alert, e.g. the source IP of a brute force
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Observable {
+    pub kind: ObservableKind,
+    pub value: String,
+}
+
+/// Alert fields mapped to observables
+const OBSERVABLE_FIELDS: &[(&str, ObservableKind)] = &[
+    ("data.srcip", ObservableKind::Ip),
+    ("data.dstip", ObservableKind::Ip),
+    ("agent.ip", ObservableKind::Ip),
+    ("agent.name", ObservableKind::Hostname),
+    ("data.url", ObservableKind::Url),
+    ("syscheck.path", ObservableKind::Filename),
+    ("syscheck.md5_after", ObservableKind::Md5),
+    ("syscheck.sha256_after", ObservableKind::Sha256),
+];
+
+/// Observables found in an alert, without duplicates
+pub fn observables(alert: &Value) -> Vec<Observable> {
+    let mut found: Vec<Observable> = Vec::new();
+    for (field, kind) in OBSERVABLE_FIELDS {
+        let Some(value) = json_field(alert, field).and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let observable = Observable {
+            kind: *kind,
+            value: value.to_string(),
+        };
+        if !value.is_empty() && !found.contains(&observable) {
+            found.push(observable);
+        }
+    }
+    found
+}
+
+/// Dashboard search for the alert, so analysts can get back to the source
+pub fn alert_link(dashboard_url: Option<&str>, alert: &Value) -> Option<String> {
+    let id = json_field(alert, "id")?.as_str()?;
+    Some(format!(
+        "{}/app/data-explorer/discover#?_q=(query:(language:kuery,query:'id:\"{}\"'))",
+        dashboard_url?.trim_end_matches('/'),
+        id
+    ))
+}
+
+/// TheHive severity (1 low to 4 critical) for a rule level
+fn thehive_severity(level: u64) -> u64 {
+    match level {
+        0..=6 => 1,
+        7..=9 => 2,
+        10..=12 => 3,
+        _ => 4,
+    }
+}
+
+/// DFIR-IRIS severity ID (2 informational to 6 critical) for a rule level
+fn iris_severity(level: u64) -> u64 {
+    match level {
+        0..=3 => 2,
+        4..=6 => 3,
+        7..=9 => 4,
+        10..=12 => 5,
+        _ => 6,
+    }
+}
+
+fn title(alert: &Value) -> String {
+    format!(
+        "[Wazuh] {} ({})",
+        json_to_text(json_field(alert, "rule.description")),
+        json_to_text(json_field(alert, "agent.name"))
+    )
+}
+
+fn description(alert: &Value, link: Option<&str>) -> String {
+    let mut text = format!(
+        "Rule {} (level {}) on agent {} at {}\n\n```\n{}\n```",
+        json_to_text(json_field(alert, "rule.id")),
+        json_to_text(json_field(alert, "rule.level")),
+        json_to_text(json_field(alert, "agent.name")),
+        json_to_text(json_field(alert, "timestamp")),
+        json_to_text(json_field(alert, "full_log")),
+    );
+    if let Some(link) = link {
+        text.push_str(&format!("\n\nWazuh: {}", link));
+    }
+    text
+}
+
+fn level(alert: &Value) -> u64 {
+    json_field(alert, "rule.level").and_then(|l| l.as_u64()).unwrap_or(0)
+}
+
+fn tags(alert: &Value) -> Vec<String> {
+    let mut tags = vec![SOURCE.to_string()];
+    if let Some(groups) = json_field(alert, "rule.groups").and_then(|g| g.as_array()) {
+        tags.extend(groups.iter().filter_map(|g| g.as_str()).map(|g| format!("rule:{}", g)));
+    }
+    tags
+}
+
+/// Body of `POST /api/v1/alert` for TheHive 5
+pub fn thehive_payload(alert: &Value, observables: &[Observable], link: Option<&str>) -> Value {
+    json!({
+        "type": "wazuh-alert",
+        "source": SOURCE,
+        "sourceRef": json_to_text(json_field(alert, "id")),
+        "externalLink": link,
+        "title": title(alert),
+        "description": description(alert, link),
+        "severity": thehive_severity(level(alert)),
+        "tags": tags(alert),
+        "observables": observables
+            .iter()
+            .map(|o| json!({ "dataType": o.kind.thehive_type(), "data": o.value }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Body of `POST /alerts/add` for DFIR-IRIS
+pub fn iris_payload(
+    alert: &Value,
+    observables: &[Observable],
+    link: Option<&str>,
+    customer_id: u32,
+) -> Value {
+    json!({
+        "alert_title": title(alert),
+        "alert_description": description(alert, link),
+        "alert_source": SOURCE,
+        "alert_source_ref": json_to_text(json_field(alert, "id")),
+        "alert_source_link": link,
+        "alert_source_content": alert,
+        "alert_severity_id": iris_severity(level(alert)),
+        "alert_status_id": IRIS_STATUS_NEW,
+        "alert_customer_id": customer_id,
+        "alert_tags": tags(alert).join(","),
+        "alert_iocs": observables
+            .iter()
+            .map(|o| json!({
+                "ioc_value": o.value,
+                "ioc_type_id": o.kind.iris_type_id(),
+                "ioc_description": "Extracted from Wazuh alert",
+                "ioc_tlp_id": 2,
+                "ioc_tags": SOURCE,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Creates alerts in the configured case manager
+pub struct Escalator {
+    platform: Platform,
+    config: CaseManagerConfig,
+    dashboard_url: Option<String>,
+    client: reqwest::Client,
+}
+
+impl Escalator {
+    pub fn new(platform: Platform, config: &EscalationConfig) -> Result<Self> {
+        let section = match platform {
+            Platform::TheHive => config.thehive.clone(),
+            Platform::Iris => config.iris.clone(),
+        };
+        let section = section.ok_or_else(|| {
+            anyhow!(
+                "{} is not configured; add url and api_key under [escalation.{}]",
+                platform,
+                match platform {
+                    Platform::TheHive => "thehive",
+                    Platform::Iris => "iris",
+                }
+            )
+        })?;
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .danger_accept_invalid_certs(!section.verify_tls)
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        Ok(Self {
+            platform,
+            config: section,
+            dashboard_url: config.dashboard_url.clone(),
+            client,
+        })
+    }
+
+    /// Request body that would be sent for an alert
+    pub fn payload(&self, alert: &Value) -> Value {
+        let observables = observables(alert);
+        let link = alert_link(self.dashboard_url.as_deref(), alert);
+        match self.platform {
+            Platform::TheHive => thehive_payload(alert, &observables, link.as_deref()),
+            Platform::Iris => iris_payload(
+                alert,
+                &observables,
+                link.as_deref(),
+                self.config.customer_id.unwrap_or(DEFAULT_IRIS_CUSTOMER),
+            ),
+        }
+    }
+
+    /// Create the alert and return its ID in the case manager
+    pub async fn escalate(&self, alert: &Value) -> Result<String> {
+        let base = self.config.url.trim_end_matches('/');
+        let url = match self.platform {
+            Platform::TheHive => format!("{}/api/v1/alert", base),
+            Platform::Iris => format!("{}/alerts/add", base),
+        };
+        debug!("POST {}", url);
+
+        let response = self
+            .client
+            .post(&url)
+            .bearer_auth(&self.config.api_key)
+            .json(&self.payload(alert))
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach {} at {}", self.platform, url))?;
+
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+        if !status.is_success() {
+            let message = ["/message", "/data/message", "/type"]
+                .iter()
+                .find_map(|p| body.pointer(p).and_then(|m| m.as_str()))
+                .unwrap_or("no details");
+            return Err(anyhow!("{} returned {}: {}", self.platform, status, message));
+        }
+
+        let id = match self.platform {
+            Platform::TheHive => body.pointer("/_id"),
+            Platform::Iris => body.pointer("/data/alert_id"),
+        };
+        Ok(json_to_text(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert() -> Value {
+        json!({
+            "id": "1700000000.123",
+            "timestamp": "2024-01-01T00:00:00Z",
+            "rule": {"id": "5712", "level": 10, "description": "SSH brute force",
+                     "groups": ["sshd", "authentication_failures"]},
+            "agent": {"id": "001", "name": "web-01", "ip": "10.0.0.5"},
+            "data": {"srcip": "203.0.113.9", "dstip": "10.0.0.5"},
+            "full_log": "Failed password for root"
+        })
+    }
+
+    #[test]
+    fn test_observables() {
+        let found = observables(&alert());
+        let values: Vec<&str> = found.iter().map(|o| o.value.as_str()).collect();
+        // agent.ip repeats data.dstip and is kept once
+        assert_eq!(values, ["203.0.113.9", "10.0.0.5", "web-01"]);
+        assert_eq!(found[2].kind, ObservableKind::Hostname);
+    }
+
+    #[test]
+    fn test_thehive_payload() {
+        let alert = alert();
+        let link = alert_link(Some("https://dashboard/"), &alert);
+        let body = thehive_payload(&alert, &observables(&alert), link.as_deref());
+
+        assert_eq!(body["sourceRef"], "1700000000.123");
+        assert_eq!(body["severity"], 3);
+        assert_eq!(body["observables"][0]["dataType"], "ip");
+        assert!(body["externalLink"]
+            .as_str()
+            .unwrap()
+            .starts_with("https://dashboard/app/"));
+        assert_eq!(body["tags"][1], "rule:sshd");
+    }
+
+    #[test]
+    fn test_platform_parsing() {
+        assert_eq!("TheHive".parse::<Platform>().unwrap(), Platform::TheHive);
+        assert_eq!("iris".parse::<Platform>().unwrap(), Platform::Iris);
+        assert!("jira".parse::<Platform>().is_err());
+        assert_eq!(iris_severity(15), 6);
+    }
+}
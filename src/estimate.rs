@@ -0,0 +1,101 @@
+use anyhow::Result;
+use dialoguer::Confirm;
+use std::io::IsTerminal;
+use std::time::Duration;
+
+use crate::config::OutputConfig;
+use crate::error::WazuhError;
+
+/// Expected size and cost of a query, measured with a cheap count request
+#[derive(Debug, Clone)]
+pub struct Estimate {
+    pub total: u64,
+    pub page_size: u64,
+    /// Round-trip time of the count request, used as the per-page cost
+    pub page_latency: Duration,
+}
+
+impl Estimate {
+    pub fn pages(&self) -> u64 {
+        self.total.div_ceil(self.page_size.max(1))
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.page_latency * self.pages().min(u32::MAX as u64) as u32
+    }
+}
+
+/// Ask before fetching a result set larger than `output.large_result_threshold`
+///
+/// Returns `Ok(false)` if the user declines. Without a terminal to prompt on,
+/// large results are refused unless prompting was disabled (`--no-prompt`).
+pub fn confirm_large_result(estimate: &Estimate, what: &str, output: &OutputConfig) -> Result<bool> {
+    if estimate.total <= output.large_result_threshold || !output.prompt_large_results {
+        return Ok(true);
+    }
+
+    let summary = format!(
+        "{} {} ({} pages, ~{})",
+        estimate.total,
+        what,
+        estimate.pages(),
+        format_duration(estimate.duration())
+    );
+
+    if !std::io::stdin().is_terminal() {
+        return Err(WazuhError::InvalidInput(format!(
+            "Query would return {}, above the configured limit of {}; re-run with --no-prompt to proceed",
+            summary, output.large_result_threshold
+        ))
+        .into());
+    }
+
+    Ok(Confirm::new()
+        .with_prompt(format!("This will fetch {}. Continue?", summary))
+        .default(false)
+        .interact()?)
+}
+
+/// Human-friendly rendering of an estimated duration
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0 => format!("{} ms", duration.as_millis()),
+        1..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate() {
+        let estimate = Estimate {
+            total: 1201,
+            page_size: 500,
+            page_latency: Duration::from_millis(300),
+        };
+        assert_eq!(estimate.pages(), 3);
+        assert_eq!(estimate.duration(), Duration::from_millis(900));
+    }
+
+    #[test]
+    fn test_small_results_skip_prompt() {
+        let estimate = Estimate {
+            total: 10,
+            page_size: 500,
+            page_latency: Duration::from_millis(1),
+        };
+        assert!(confirm_large_result(&estimate, "agents", &OutputConfig::default()).unwrap());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(250)), "250 ms");
+        assert_eq!(format_duration(Duration::from_secs(75)), "1m 15s");
+        assert_eq!(format_duration(Duration::from_secs(7260)), "2h 1m");
+    }
+}
@@ -0,0 +1,247 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+
+use crate::models::Agent;
+
+/// Tables, views and indices of the export; `versions` is a view so it can
+/// never disagree with `agents`
+const SCHEMA: &str = "
+CREATE TABLE export_info (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE agents (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    ip TEXT,
+    status TEXT NOT NULL,
+    os_platform TEXT,
+    os_name TEXT,
+    os_version TEXT,
+    version TEXT,
+    node_name TEXT,
+    last_keep_alive TEXT,
+    date_add TEXT
+);
+CREATE INDEX idx_agents_name ON agents (name);
+CREATE INDEX idx_agents_status ON agents (status);
+CREATE INDEX idx_agents_version ON agents (version);
+CREATE TABLE groups (
+    name TEXT PRIMARY KEY,
+    agent_count INTEGER NOT NULL
+);
+CREATE TABLE agent_groups (
+    agent_id TEXT NOT NULL REFERENCES agents (id),
+    group_name TEXT NOT NULL REFERENCES groups (name),
+    PRIMARY KEY (agent_id, group_name)
+);
+CREATE INDEX idx_agent_groups_group ON agent_groups (group_name);
+CREATE VIEW versions AS
+    SELECT version, COUNT(*) AS agents FROM agents GROUP BY version;
+CREATE TABLE sca_policies (
+    agent_id TEXT NOT NULL REFERENCES agents (id),
+    policy_id TEXT NOT NULL,
+    name TEXT,
+    score INTEGER,
+    pass INTEGER,
+    fail INTEGER,
+    invalid INTEGER,
+    total_checks INTEGER,
+    end_scan TEXT,
+    PRIMARY KEY (agent_id, policy_id)
+);
+CREATE INDEX idx_sca_policies_policy ON sca_policies (policy_id);
+CREATE TABLE vulnerability_counts (
+    agent_id TEXT NOT NULL REFERENCES agents (id),
+    severity TEXT NOT NULL,
+    count INTEGER NOT NULL,
+    PRIMARY KEY (agent_id, severity)
+);
+CREATE INDEX idx_vulnerability_counts_severity ON vulnerability_counts (severity);
+";
+
+/// Everything written to the database, as fetched from the API and indexer
+#[derive(Debug, Default)]
+pub struct ExportData {
+    pub source: String,
+    pub agents: Vec<Agent>,
+    /// Group name and agent count
+    pub groups: Vec<(String, u64)>,
+    /// `/sca/{agent}` items by agent ID
+    pub sca: Vec<(String, Vec<Value>)>,
+    /// Agent ID, severity and number of vulnerabilities
+    pub vulnerabilities: Vec<(String, String, u64)>,
+}
+
+/// Row counts of a finished export
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub exported_at: DateTime<Utc>,
+    pub agents: usize,
+    pub groups: usize,
+    pub memberships: usize,
+    pub sca_policies: usize,
+    pub vulnerability_counts: usize,
+}
+
+/// Write a new SQLite database, replacing any existing file at `path`
+pub fn write_sqlite(path: &Path, data: &ExportData) -> Result<ExportSummary> {
+    if path.exists() {
+        std::fs::remove_file(path).with_context(|| format!("Failed to replace {:?}", path))?;
+    }
+    let mut conn =
+        Connection::open(path).with_context(|| format!("Failed to create {:?}", path))?;
+    conn.execute_batch(SCHEMA)?;
+
+    let exported_at = Utc::now();
+    let mut summary = ExportSummary {
+        path: path.display().to_string(),
+        exported_at,
+        agents: data.agents.len(),
+        groups: data.groups.len(),
+        memberships: 0,
+        sca_policies: 0,
+        vulnerability_counts: data.vulnerabilities.len(),
+    };
+
+    // One transaction keeps large fleets from syncing to disk on every row
+    let tx = conn.transaction()?;
+    {
+        let mut info = tx.prepare("INSERT INTO export_info (key, value) VALUES (?1, ?2)")?;
+        info.execute(params!["exported_at", exported_at.to_rfc3339()])?;
+        info.execute(params!["source", data.source])?;
+        info.execute(params!["cli_version", env!("CARGO_PKG_VERSION")])?;
+
+        // Groups go first so memberships can reference them
+        let mut groups = tx.prepare("INSERT INTO groups (name, agent_count) VALUES (?1, ?2)")?;
+        for (name, count) in &data.groups {
+            groups.execute(params![name, count])?;
+        }
+
+        let mut agents = tx.prepare(
+            "INSERT INTO agents (id, name, ip, status, os_platform, os_name, os_version, version,
+                node_name, last_keep_alive, date_add)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        )?;
+        let mut memberships = tx.prepare(
+            "INSERT OR IGNORE INTO agent_groups (agent_id, group_name) VALUES (?1, ?2)",
+        )?;
+        for agent in &data.agents {
+            let os = agent.os.as_ref();
+            agents.execute(params![
+                agent.id,
+                agent.name,
+                agent.ip,
+                serde_json::to_value(&agent.status)?.as_str(),
+                os.and_then(|os| os.platform.as_deref()),
+                os.and_then(|os| os.name.as_deref()),
+                os.and_then(|os| os.version.as_deref()),
+                agent.version,
+                agent.node_name,
+                agent.last_keep_alive.map(|t| t.to_rfc3339()),
+                agent.date_add.map(|t| t.to_rfc3339()),
+            ])?;
+            for group in agent.group.iter().flatten() {
+                summary.memberships += memberships.execute(params![agent.id, group])?;
+            }
+        }
+
+        let mut sca = tx.prepare(
+            "INSERT OR REPLACE INTO sca_policies (agent_id, policy_id, name, score, pass, fail,
+                invalid, total_checks, end_scan)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+        for (agent_id, policies) in &data.sca {
+            for policy in policies {
+                let Some(policy_id) = policy["policy_id"].as_str() else {
+                    continue;
+                };
+                sca.execute(params![
+                    agent_id,
+                    policy_id,
+                    policy["name"].as_str(),
+                    policy["score"].as_i64(),
+                    policy["pass"].as_i64(),
+                    policy["fail"].as_i64(),
+                    policy["invalid"].as_i64(),
+                    policy["total_checks"].as_i64(),
+                    policy["end_scan"].as_str(),
+                ])?;
+                summary.sca_policies += 1;
+            }
+        }
+
+        let mut vulnerabilities = tx.prepare(
+            "INSERT INTO vulnerability_counts (agent_id, severity, count) VALUES (?1, ?2, ?3)",
+        )?;
+        for (agent_id, severity, count) in &data.vulnerabilities {
+            vulnerabilities.execute(params![agent_id, severity, count])?;
+        }
+    }
+    tx.commit()?;
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn agent(id: &str, version: &str, groups: &[&str]) -> Agent {
+        serde_json::from_value(json!({
+            "id": id, "name": format!("web-{}", id), "status": "active",
+            "version": version, "group": groups, "os": {"platform": "ubuntu"}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_write_sqlite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wazuh.db");
+        let data = ExportData {
+            source: "https://wazuh:55000".to_string(),
+            agents: vec![
+                agent("001", "Wazuh v4.8.0", &["default", "web"]),
+                agent("002", "Wazuh v4.8.0", &["default"]),
+            ],
+            groups: vec![("default".to_string(), 2), ("web".to_string(), 1)],
+            sca: vec![(
+                "001".to_string(),
+                vec![json!({"policy_id": "cis_ubuntu22", "name": "CIS", "score": 61})],
+            )],
+            vulnerabilities: vec![("001".to_string(), "Critical".to_string(), 3)],
+        };
+
+        let summary = write_sqlite(&path, &data).unwrap();
+        assert_eq!(summary.memberships, 3);
+        assert_eq!(summary.sca_policies, 1);
+
+        let conn = Connection::open(&path).unwrap();
+        let critical_in_web: i64 = conn
+            .query_row(
+                "SELECT SUM(v.count) FROM vulnerability_counts v
+                 JOIN agent_groups g ON g.agent_id = v.agent_id
+                 WHERE g.group_name = 'web' AND v.severity = 'Critical'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(critical_in_web, 3);
+        let versions: i64 = conn
+            .query_row("SELECT agents FROM versions WHERE version = 'Wazuh v4.8.0'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(versions, 2);
+
+        // Exporting again replaces the file instead of failing on existing tables
+        write_sqlite(&path, &data).unwrap();
+    }
+}
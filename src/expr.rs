@@ -0,0 +1,374 @@
+//! Small expression language for user-defined computed columns.
+//!
+//! Expressions combine field paths (`name`, `os.platform`), string and number
+//! literals, `now`, the operators `+ - * /` and parentheses, plus the
+//! functions `lower`, `upper` and `coalesce`. Subtracting two timestamps
+//! yields whole days, so `now - date_add` is an agent's age in days.
+
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+use crate::error::WazuhError;
+
+/// Result of evaluating an expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Number(f64),
+    Text(String),
+    Time(DateTime<Utc>),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "N/A"),
+            Value::Number(n) if n.fract() == 0.0 => write!(f, "{}", *n as i64),
+            Value::Number(n) => write!(f, "{:.2}", n),
+            Value::Text(s) => write!(f, "{}", s),
+            Value::Time(t) => write!(f, "{}", t.format("%Y-%m-%d %H:%M:%S UTC")),
+        }
+    }
+}
+
+impl Value {
+    /// Convert a JSON document value, recognising RFC 3339 timestamps
+    fn from_json(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Text(b.to_string()),
+            serde_json::Value::Number(n) => n.as_f64().map(Value::Number).unwrap_or(Value::Null),
+            serde_json::Value::String(s) => DateTime::parse_from_rfc3339(s)
+                .map(|t| Value::Time(t.with_timezone(&Utc)))
+                .unwrap_or_else(|_| Value::Text(s.clone())),
+            serde_json::Value::Array(items) => Value::Text(
+                items
+                    .iter()
+                    .map(|item| Value::from_json(item).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            serde_json::Value::Object(_) => Value::Text(value.to_string()),
+        }
+    }
+}
+
+/// Arithmetic operator
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+/// Parsed expression tree
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    Field(String),
+    Now,
+    Binary(Box<Expr>, Op, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Op(Op),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, WazuhError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(ch) if ch == c => break,
+                        Some(ch) => text.push(ch),
+                        None => {
+                            return Err(WazuhError::InvalidInput(format!(
+                                "Unterminated string in expression '{}'",
+                                input
+                            )))
+                        }
+                    }
+                }
+                tokens.push(Token::Str(text));
+            }
+            '0'..='9' => {
+                let mut number = String::new();
+                while let Some(&d) = chars.peek() {
+                    if d.is_ascii_digit() || d == '.' {
+                        number.push(d);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let value = number.parse().map_err(|_| {
+                    WazuhError::InvalidInput(format!("Invalid number '{}' in expression", number))
+                })?;
+                tokens.push(Token::Num(value));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut ident = String::new();
+                while let Some(&d) = chars.peek() {
+                    if d.is_alphanumeric() || d == '_' || d == '.' || d == '@' {
+                        ident.push(d);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            '+' | '-' | '*' | '/' => {
+                chars.next();
+                tokens.push(Token::Op(match c {
+                    '+' => Op::Add,
+                    '-' => Op::Sub,
+                    '*' => Op::Mul,
+                    _ => Op::Div,
+                }));
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            ',' => {
+                chars.next();
+                tokens.push(Token::Comma);
+            }
+            other => {
+                return Err(WazuhError::InvalidInput(format!(
+                    "Unexpected character '{}' in expression '{}'",
+                    other, input
+                )))
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), WazuhError> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            other => Err(WazuhError::InvalidInput(format!(
+                "Expected {:?} in expression, found {:?}",
+                expected, other
+            ))),
+        }
+    }
+
+    /// expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<Expr, WazuhError> {
+        let mut left = self.term()?;
+        while let Some(Token::Op(op @ (Op::Add | Op::Sub))) = self.peek().cloned() {
+            self.pos += 1;
+            left = Expr::Binary(Box::new(left), op, Box::new(self.term()?));
+        }
+        Ok(left)
+    }
+
+    /// term := atom (('*' | '/') atom)*
+    fn term(&mut self) -> Result<Expr, WazuhError> {
+        let mut left = self.atom()?;
+        while let Some(Token::Op(op @ (Op::Mul | Op::Div))) = self.peek().cloned() {
+            self.pos += 1;
+            left = Expr::Binary(Box::new(left), op, Box::new(self.atom()?));
+        }
+        Ok(left)
+    }
+
+    fn atom(&mut self) -> Result<Expr, WazuhError> {
+        match self.next() {
+            Some(Token::Num(n)) => Ok(Expr::Literal(Value::Number(n))),
+            Some(Token::Str(s)) => Ok(Expr::Literal(Value::Text(s))),
+            Some(Token::LParen) => {
+                let inner = self.expr()?;
+                self.expect(Token::RParen)?;
+                Ok(inner)
+            }
+            Some(Token::Ident(name)) if self.peek() == Some(&Token::LParen) => {
+                self.pos += 1;
+                let mut args = Vec::new();
+                if self.peek() != Some(&Token::RParen) {
+                    loop {
+                        args.push(self.expr()?);
+                        if self.peek() == Some(&Token::Comma) {
+                            self.pos += 1;
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                self.expect(Token::RParen)?;
+                Ok(Expr::Call(name, args))
+            }
+            Some(Token::Ident(name)) if name == "now" => Ok(Expr::Now),
+            Some(Token::Ident(name)) => Ok(Expr::Field(name)),
+            other => Err(WazuhError::InvalidInput(format!(
+                "Unexpected {:?} in expression",
+                other
+            ))),
+        }
+    }
+}
+
+impl Expr {
+    /// Parse an expression such as `name + ".corp.local"`
+    pub fn parse(input: &str) -> Result<Self, WazuhError> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            pos: 0,
+        };
+        let expr = parser.expr()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(WazuhError::InvalidInput(format!(
+                "Unexpected trailing input in expression '{}'",
+                input
+            )));
+        }
+        if let Some(name) = expr.unknown_function() {
+            return Err(WazuhError::InvalidInput(format!(
+                "Unknown function '{}' in expression '{}'",
+                name, input
+            )));
+        }
+        Ok(expr)
+    }
+
+    fn unknown_function(&self) -> Option<&str> {
+        match self {
+            Expr::Call(name, args) => {
+                if !matches!(name.as_str(), "lower" | "upper" | "coalesce") {
+                    return Some(name);
+                }
+                args.iter().find_map(Expr::unknown_function)
+            }
+            Expr::Binary(left, _, right) => left.unknown_function().or_else(|| right.unknown_function()),
+            _ => None,
+        }
+    }
+
+    /// Evaluate against a JSON document (e.g. a serialized agent)
+    pub fn eval(&self, doc: &serde_json::Value, now: DateTime<Utc>) -> Result<Value, String> {
+        match self {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Now => Ok(Value::Time(now)),
+            Expr::Field(path) => Ok(crate::utils::json_field(doc, path)
+                .map(Value::from_json)
+                .unwrap_or(Value::Null)),
+            Expr::Call(name, args) => {
+                let values = args
+                    .iter()
+                    .map(|arg| arg.eval(doc, now))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match (name.as_str(), values.as_slice()) {
+                    ("lower", [Value::Null]) | ("upper", [Value::Null]) => Ok(Value::Null),
+                    ("lower", [v]) => Ok(Value::Text(v.to_string().to_lowercase())),
+                    ("upper", [v]) => Ok(Value::Text(v.to_string().to_uppercase())),
+                    ("coalesce", values) => Ok(values
+                        .iter()
+                        .find(|v| **v != Value::Null)
+                        .cloned()
+                        .unwrap_or(Value::Null)),
+                    _ => Err(format!("Wrong number of arguments for {}()", name)),
+                }
+            }
+            Expr::Binary(left, op, right) => {
+                let (left, right) = (left.eval(doc, now)?, right.eval(doc, now)?);
+                match (left, *op, right) {
+                    (Value::Null, _, _) | (_, _, Value::Null) => Ok(Value::Null),
+                    (Value::Number(a), Op::Add, Value::Number(b)) => Ok(Value::Number(a + b)),
+                    (Value::Number(a), Op::Sub, Value::Number(b)) => Ok(Value::Number(a - b)),
+                    (Value::Number(a), Op::Mul, Value::Number(b)) => Ok(Value::Number(a * b)),
+                    (Value::Number(_), Op::Div, Value::Number(0.0)) => Ok(Value::Null),
+                    (Value::Number(a), Op::Div, Value::Number(b)) => Ok(Value::Number(a / b)),
+                    (Value::Time(a), Op::Sub, Value::Time(b)) => {
+                        Ok(Value::Number((a - b).num_days() as f64))
+                    }
+                    (a @ Value::Text(_), Op::Add, b) | (a, Op::Add, b @ Value::Text(_)) => {
+                        Ok(Value::Text(format!("{}{}", a, b)))
+                    }
+                    (a, op, b) => Err(format!("Cannot apply {:?} to {:?} and {:?}", op, a, b)),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn eval(expr: &str, doc: &serde_json::Value) -> Value {
+        let now = DateTime::parse_from_rfc3339("2024-03-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        Expr::parse(expr).unwrap().eval(doc, now).unwrap()
+    }
+
+    #[test]
+    fn test_computed_columns() {
+        let agent = json!({
+            "name": "web-01",
+            "date_add": "2024-01-01T00:00:00Z",
+            "os": {"platform": "ubuntu"}
+        });
+
+        assert_eq!(eval("now - date_add", &agent), Value::Number(60.0));
+        assert_eq!(
+            eval("name + \".corp.local\"", &agent),
+            Value::Text("web-01.corp.local".to_string())
+        );
+        assert_eq!(eval("upper(os.platform)", &agent).to_string(), "UBUNTU");
+        assert_eq!(eval("(1 + 2) * 3", &agent), Value::Number(9.0));
+        assert_eq!(eval("coalesce(ip, 'none')", &agent).to_string(), "none");
+        assert_eq!(eval("ip + 1", &agent), Value::Null);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Expr::parse("name +").is_err());
+        assert!(Expr::parse("\"open").is_err());
+        assert!(Expr::parse("frobnicate(name)").is_err());
+        assert!(Expr::parse("a b").is_err());
+    }
+}
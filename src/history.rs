@@ -0,0 +1,88 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use tracing::warn;
+
+use crate::config::Config;
+use crate::lock::{self, Resource};
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// A fleet change performed through the CLI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEvent {
+    pub timestamp: DateTime<Utc>,
+    pub action: HistoryAction,
+    pub agent_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryAction {
+    Added,
+    Removed,
+    Renamed,
+}
+
+impl HistoryEvent {
+    pub fn new(action: HistoryAction, agent_id: &str, agent_name: Option<&str>) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            action,
+            agent_id: agent_id.to_string(),
+            agent_name: agent_name.map(String::from),
+        }
+    }
+}
+
+/// Path of the local history log
+pub fn history_path() -> Result<PathBuf> {
+    Ok(Config::data_dir()?.join(HISTORY_FILE))
+}
+
+/// Append an event to the history log
+pub fn record(event: &HistoryEvent) -> Result<()> {
+    let path = history_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create data directory: {:?}", parent))?;
+    }
+
+    let _lock = lock::acquire(Resource::History)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open history file: {:?}", path))?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+
+    Ok(())
+}
+
+/// Record an event, logging instead of failing if the history cannot be written
+pub fn record_quietly(event: HistoryEvent) {
+    if let Err(e) = record(&event) {
+        warn!("Failed to record history event: {}", e);
+    }
+}
+
+/// Load all recorded events, skipping malformed lines
+pub fn load() -> Result<Vec<HistoryEvent>> {
+    let path = history_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read history file: {:?}", path))?;
+
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
@@ -0,0 +1,437 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use reqwest::{Client, ClientBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration as StdDuration, Instant};
+use tempfile::NamedTempFile;
+use tracing::debug;
+
+use crate::client::apply_tls;
+use crate::compliance::ComplianceFilter;
+use crate::config::Config;
+use crate::error::WazuhError;
+use crate::lock::{self, Resource};
+use crate::output::{print_warning, WarningKind};
+use crate::utils::normalize_agent_id;
+
+/// Directory under the data dir holding the last result of each indexer query
+const CACHE_DIR: &str = "indexer-cache";
+
+/// Index pattern of the vulnerability detector states (Wazuh 4.8+)
+pub const VULNERABILITIES_INDEX: &str = "wazuh-states-vulnerabilities-*";
+
+/// A search response saved for use while the indexer is down
+#[derive(Serialize, Deserialize)]
+struct CachedResponse {
+    cached_at: DateTime<Utc>,
+    response: Value,
+}
+
+/// Client for the Wazuh indexer (OpenSearch) where alerts are stored
+#[derive(Debug, Clone)]
+pub struct IndexerClient {
+    client: Client,
+    base_url: String,
+    username: Option<String>,
+    password: Option<String>,
+    alerts_index: String,
+    archives_index: String,
+}
+
+impl IndexerClient {
+    /// Create a new indexer client from the `[indexer]` configuration section
+    pub fn new(config: &Config) -> Result<Self> {
+        let client_builder =
+            ClientBuilder::new().timeout(StdDuration::from_secs(config.api.timeout));
+        let tls = config.tls.for_host(&config.indexer.host)?;
+        let mut client_builder = apply_tls(client_builder, &tls)?;
+
+        // The indexer often shares the manager's hostname behind the same port-forward
+        if let Some((host, addr)) = config.resolve_override()? {
+            client_builder = client_builder.resolve(&host, addr);
+        }
+
+        let client = client_builder
+            .build()
+            .context("Failed to build indexer HTTP client")?;
+
+        Ok(Self {
+            client,
+            base_url: config.indexer_url(),
+            username: config.indexer.username.clone(),
+            password: config.indexer.password.clone(),
+            alerts_index: config.indexer.alerts_index.clone(),
+            archives_index: config.indexer.archives_index.clone(),
+        })
+    }
+
+    /// Index pattern holding Wazuh alerts
+    pub fn alerts_index(&self) -> &str {
+        &self.alerts_index
+    }
+
+    /// Index pattern holding archived events
+    pub fn archives_index(&self) -> &str {
+        &self.archives_index
+    }
+
+    /// Run a `_search` request against an index pattern
+    pub async fn search(&self, index: &str, body: &Value) -> Result<Value> {
+        self.post(&format!("{}/_search", index), body).await
+    }
+
+    /// Count alerts matching a query, returning the count and request latency
+    pub async fn count_alerts(&self, query: &AlertQuery) -> Result<(u64, StdDuration)> {
+        self.count(&self.alerts_index, query).await
+    }
+
+    /// Count archived events matching a query, returning the count and request latency
+    pub async fn count_archives(&self, query: &AlertQuery) -> Result<(u64, StdDuration)> {
+        self.count(&self.archives_index, query).await
+    }
+
+    async fn count(&self, index: &str, query: &AlertQuery) -> Result<(u64, StdDuration)> {
+        let body = json!({ "query": { "bool": { "filter": query.filters() } } });
+
+        let started = Instant::now();
+        let response = self.post(&format!("{}/_count", index), &body).await?;
+
+        Ok((response["count"].as_u64().unwrap_or(0), started.elapsed()))
+    }
+
+    /// GET an indexer path (e.g. `_cat/indices`) and parse the JSON response
+    pub async fn get(&self, path: &str) -> Result<Value> {
+        let url = format!("{}/{}", self.base_url, path);
+        debug!("GET {}", url);
+        self.send(self.client.get(&url), &url).await
+    }
+
+    /// POST a JSON body to an indexer path and parse the JSON response
+    async fn post(&self, path: &str, body: &Value) -> Result<Value> {
+        let url = format!("{}/{}", self.base_url, path);
+        debug!("POST {} {}", url, body);
+        self.send(self.client.post(&url).json(body), &url).await
+    }
+
+    async fn send(&self, mut request: reqwest::RequestBuilder, url: &str) -> Result<Value> {
+        if let Some(username) = &self.username {
+            request = request.basic_auth(username, self.password.as_ref());
+        }
+
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to query indexer at {}", url))?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            let message = serde_json::from_str::<Value>(&body)
+                .ok()
+                .and_then(|v| v.pointer("/error/reason").and_then(|r| r.as_str()).map(String::from))
+                .unwrap_or(body);
+            return Err(WazuhError::IndexerError {
+                status: status.as_u16(),
+                message,
+            }
+            .into());
+        }
+
+        serde_json::from_str(&body).with_context(|| format!("Failed to parse indexer response: {}", body))
+    }
+
+    /// Search alerts and return the `_source` documents of the hits
+    pub async fn search_alerts(&self, query: &AlertQuery) -> Result<Vec<Value>> {
+        let key = format!("search {:?}", query);
+        let response = self
+            .search_or_cached(&key, &self.alerts_index, &query.to_search_body())
+            .await?;
+        Ok(hits(&response))
+    }
+
+    /// Search archived events; they share the alert document layout
+    pub async fn search_archives(&self, query: &AlertQuery) -> Result<Vec<Value>> {
+        let key = format!("archives {:?}", query);
+        let response = self
+            .search_or_cached(&key, &self.archives_index, &query.to_search_body())
+            .await?;
+        Ok(hits(&response))
+    }
+
+    /// Random sample of matching alerts; the same seed returns the same sample
+    pub async fn sample_alerts(&self, query: &AlertQuery, seed: Option<u64>) -> Result<Vec<Value>> {
+        let key = format!("sample {:?} {:?}", seed, query);
+        let response = self
+            .search_or_cached(&key, &self.alerts_index, &query.to_sample_body(seed))
+            .await?;
+        Ok(hits(&response))
+    }
+
+    /// Search, falling back to the last cached response when the indexer is unreachable
+    ///
+    /// `key` identifies the query independently of the absolute timestamps in
+    /// `body`, so a repeated `--since 24h` search finds the previous result.
+    /// Alerts and archives have no manager API equivalent, so the cache is
+    /// their only fallback; callers with one check [`is_unavailable`] on the
+    /// error and ask the API instead.
+    pub async fn search_or_cached(&self, key: &str, index: &str, body: &Value) -> Result<Value> {
+        let path = cache_path(&self.base_url, key)?;
+
+        match self.search(index, body).await {
+            Ok(response) => {
+                let cached = CachedResponse {
+                    cached_at: Utc::now(),
+                    response,
+                };
+                if let Err(e) = save_cached(&path, &cached) {
+                    debug!("Failed to cache indexer response: {:#}", e);
+                }
+                Ok(cached.response)
+            }
+            Err(e) if is_unavailable(&e) => match load_cached(&path) {
+                Some(cached) => {
+                    let age = Utc::now() - cached.cached_at;
+                    print_warning(
+                        WarningKind::Fallback,
+                        &format!(
+                            "Indexer unreachable ({}); showing cached results from {} \
+                             ({} minutes old)",
+                            e.root_cause(),
+                            cached.cached_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                            age.num_minutes()
+                        ),
+                    );
+                    Ok(cached.response)
+                }
+                None => Err(e.context(
+                    "Indexer is unreachable and no cached result exists for this query",
+                )),
+            },
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Whether an indexer error means the indexer is down rather than the query being wrong
+pub fn is_unavailable(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return e.is_connect() || e.is_timeout();
+        }
+        matches!(
+            cause.downcast_ref::<WazuhError>(),
+            Some(WazuhError::IndexerError { status: 502..=504, .. })
+        )
+    })
+}
+
+fn cache_path(base_url: &str, key: &str) -> Result<PathBuf> {
+    let digest = Sha256::digest(format!("{} {}", base_url, key).as_bytes());
+    Ok(Config::data_dir()?.join(CACHE_DIR).join(format!("{:x}.json", digest)))
+}
+
+/// Replace the cache file atomically, readable and writable by the owner only
+/// since it holds alert data
+fn save_cached(path: &Path, cached: &CachedResponse) -> Result<()> {
+    let dir = path.parent().context("Indexer cache has no parent directory")?;
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create cache directory: {:?}", dir))?;
+
+    let _lock = lock::acquire(Resource::Cache)?;
+    // Temporary files are created with 0600, which the cache keeps
+    let mut temp = NamedTempFile::new_in(dir)
+        .with_context(|| format!("Failed to create indexer cache in {:?}", dir))?;
+    temp.write_all(&serde_json::to_vec(cached)?)?;
+    temp.persist(path)
+        .with_context(|| format!("Failed to write indexer cache: {:?}", path))?;
+    Ok(())
+}
+
+fn load_cached(path: &Path) -> Option<CachedResponse> {
+    let contents = fs::read(path).ok()?;
+    serde_json::from_slice(&contents).ok()
+}
+
+/// Extract `_source` documents from a search response
+pub fn hits(response: &Value) -> Vec<Value> {
+    response
+        .pointer("/hits/hits")
+        .and_then(|h| h.as_array())
+        .map(|hits| hits.iter().filter_map(|h| h.get("_source").cloned()).collect())
+        .unwrap_or_default()
+}
+
+/// Filters for searching alerts in the indexer
+#[derive(Debug, Clone, Default)]
+pub struct AlertQuery {
+    /// Only alerts newer than now minus this span
+    pub since: Option<Duration>,
+    /// Free-text query in Lucene query string syntax
+    pub query: Option<String>,
+    /// Minimum rule level
+    pub level: Option<u32>,
+    pub agent: Option<String>,
+    pub rule_id: Option<String>,
+    pub compliance: Option<ComplianceFilter>,
+    pub limit: u32,
+}
+
+impl AlertQuery {
+    /// Build the `bool` filter clauses shared by searches and aggregations
+    pub fn filters(&self) -> Vec<Value> {
+        let mut filters = Vec::new();
+
+        if let Some(since) = self.since {
+            let from = Utc::now() - since;
+            filters.push(json!({ "range": { "timestamp": { "gte": from.to_rfc3339() } } }));
+        }
+        if let Some(query) = &self.query {
+            filters.push(json!({ "query_string": { "query": query } }));
+        }
+        if let Some(level) = self.level {
+            filters.push(json!({ "range": { "rule.level": { "gte": level } } }));
+        }
+        if let Some(agent) = &self.agent {
+            // IDs are stored padded (007); a numeric name still matches as given
+            let id = normalize_agent_id(agent).unwrap_or_else(|_| agent.clone());
+            filters.push(json!({
+                "bool": {
+                    "should": [
+                        { "term": { "agent.id": id } },
+                        { "term": { "agent.name": agent } }
+                    ],
+                    "minimum_should_match": 1
+                }
+            }));
+        }
+        if let Some(rule_id) = &self.rule_id {
+            filters.push(json!({ "term": { "rule.id": rule_id } }));
+        }
+        if let Some(compliance) = &self.compliance {
+            let field = format!("rule.{}", compliance.standard.field());
+            match &compliance.requirement {
+                Some(req) => filters.push(json!({ "term": { field: req } })),
+                None => filters.push(json!({ "exists": { "field": field } })),
+            }
+        }
+
+        filters
+    }
+
+    /// Build the search request body returning the newest matching alerts
+    pub fn to_search_body(&self) -> Value {
+        json!({
+            "size": self.limit,
+            "sort": [{ "timestamp": { "order": "desc" } }],
+            "query": { "bool": { "filter": self.filters() } }
+        })
+    }
+
+    /// Build a search request body returning randomly scored matching alerts
+    pub fn to_sample_body(&self, seed: Option<u64>) -> Value {
+        // A seeded random_score needs a field to hash; _seq_no is always present
+        let random_score = match seed {
+            Some(seed) => json!({ "seed": seed, "field": "_seq_no" }),
+            None => json!({}),
+        };
+
+        json!({
+            "size": self.limit,
+            "query": {
+                "function_score": {
+                    "query": { "bool": { "filter": self.filters() } },
+                    "random_score": random_score,
+                    "boost_mode": "replace"
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_response_is_private() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CACHE_DIR).join("query.json");
+        let cached = CachedResponse {
+            cached_at: Utc::now(),
+            response: json!({"hits": {"hits": [{"_source": {"rule": {"id": "5710"}}}]}}),
+        };
+        save_cached(&path, &cached).unwrap();
+
+        let loaded = load_cached(&path).unwrap();
+        assert_eq!(hits(&loaded.response).len(), 1);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_alert_query_body() {
+        let query = AlertQuery {
+            level: Some(10),
+            compliance: Some("pci_dss_10.2.4".parse().unwrap()),
+            limit: 25,
+            ..Default::default()
+        };
+        let body = query.to_search_body();
+
+        assert_eq!(body["size"], 25);
+        let filters = body["query"]["bool"]["filter"].as_array().unwrap();
+        assert_eq!(filters.len(), 2);
+        assert_eq!(filters[0]["range"]["rule.level"]["gte"], 10);
+        assert_eq!(filters[1]["term"]["rule.pci_dss"], "10.2.4");
+    }
+
+    #[test]
+    fn test_alert_sample_body() {
+        let query = AlertQuery {
+            rule_id: Some("5716".to_string()),
+            limit: 50,
+            ..Default::default()
+        };
+
+        let body = query.to_sample_body(Some(42));
+        assert_eq!(body["size"], 50);
+        let score = &body["query"]["function_score"];
+        assert_eq!(score["random_score"]["seed"], 42);
+        assert_eq!(score["query"]["bool"]["filter"][0]["term"]["rule.id"], "5716");
+    }
+
+    #[test]
+    fn test_is_unavailable() {
+        let gateway: anyhow::Error = WazuhError::IndexerError {
+            status: 503,
+            message: "unavailable".to_string(),
+        }
+        .into();
+        assert!(is_unavailable(&gateway.context("Failed to query indexer")));
+
+        let bad_query: anyhow::Error = WazuhError::IndexerError {
+            status: 400,
+            message: "parsing_exception".to_string(),
+        }
+        .into();
+        assert!(!is_unavailable(&bad_query));
+    }
+
+    #[test]
+    fn test_hits_extraction() {
+        let response = json!({"hits": {"hits": [{"_source": {"id": "1"}}, {"_source": {"id": "2"}}]}});
+        assert_eq!(hits(&response).len(), 2);
+        assert!(hits(&json!({})).is_empty());
+    }
+}
@@ -1,72 +1,468 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
 use colored::Colorize;
-use dialoguer::Input;
-use std::io::{self, Write};
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Config as EditorConfig, Editor, Helper};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tracing::debug;
 
+use crate::cli::{self, Cli, Commands};
+use crate::completion;
 use crate::config::Config;
-use crate::output::print_info;
+use crate::error::WazuhError;
+use crate::output::{self, print_info, print_warning, WarningKind};
+use crate::script::{self, Statement, Variables, Word};
 
-pub async fn start_interactive_mode(_config: &Config) -> Result<()> {
+/// Statements kept in the history file, in the data directory
+const HISTORY_FILE: &str = "shell_history";
+const HISTORY_SIZE: usize = 1000;
+
+/// Words the shell handles itself rather than as wazuh-cli commands
+const BUILTINS: &[&str] = &["clear", "exit", "foreach", "help", "quit", "set", "unset", "vars"];
+
+type LineEditor = Editor<ShellHelper, DefaultHistory>;
+
+/// Tab completion of builtins and of wazuh-cli subcommands, flags and values,
+/// the same candidates as shell completion (agents, groups, rules and services
+/// from the completion cache)
+struct ShellHelper;
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        Ok(complete_line(&line[..pos]))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+/// Where the word before the cursor starts and what it may complete to;
+/// completion restarts after `{`, `}`, `;` and `(`, so commands inside loops
+/// and substitutions complete too
+fn complete_line(line: &str) -> (usize, Vec<Pair>) {
+    let statement_start = line.rfind(['{', '}', ';', '(']).map_or(0, |i| i + 1);
+    let word_start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1).max(statement_start);
+    let word = &line[word_start..];
+
+    let mut args: Vec<OsString> = std::iter::once("wazuh-cli")
+        .chain(line[statement_start..word_start].split_whitespace())
+        .map(OsString::from)
+        .collect();
+    args.push(word.into());
+
+    let mut pairs: Vec<Pair> = Vec::new();
+    if args.len() == 2 {
+        pairs.extend(BUILTINS.iter().filter(|b| b.starts_with(word)).map(|b| Pair {
+            display: b.to_string(),
+            replacement: b.to_string(),
+        }));
+    }
+    let index = args.len() - 1;
+    let candidates =
+        clap_complete::engine::complete(&mut Cli::command(), args, index, None).unwrap_or_default();
+    pairs.extend(candidates.into_iter().filter(|c| !c.is_hide_set()).map(|candidate| {
+        let value = candidate.get_value().to_string_lossy().into_owned();
+        // Flag help is long; values such as agent IDs are told apart by theirs
+        let display = match candidate.get_help() {
+            Some(help) if !value.starts_with('-') => format!("{} ({})", value, help),
+            _ => value.clone(),
+        };
+        Pair {
+            display,
+            replacement: value,
+        }
+    }));
+    (word_start, pairs)
+}
+
+/// State of an interactive session
+struct Session<'a> {
+    config: &'a Config,
+    config_path: &'a Path,
+    vars: Variables,
+}
+
+pub async fn start_interactive_mode(config: &Config, config_path: &Path) -> Result<()> {
     println!("{}", "Wazuh CLI - Interactive Mode".bold().blue());
     println!("Type 'help' for commands, 'exit' to quit\n");
 
-    loop {
-        // Show prompt
-        print!("{} ", "wazuh>".green().bold());
-        io::stdout().flush()?;
-
-        // Read input
-        let input = Input::<String>::new()
-            .allow_empty(true)
-            .interact_text()?;
+    let mut session = Session {
+        config,
+        config_path,
+        vars: Variables::default(),
+    };
+    completion::use_config(config, config_path.to_path_buf());
+    let mut editor = new_editor()?;
+    let history = load_history(&mut editor);
 
-        let input = input.trim();
+    loop {
+        // Ctrl-D ends the session like `exit`
+        let Some(line) = read_input(&mut editor, "wazuh>")? else {
+            println!("Goodbye!");
+            break;
+        };
+        let input = line.trim();
         if input.is_empty() {
             continue;
         }
+        remember(&mut editor, history.as_deref(), line.trim_end());
 
-        // Parse command
-        let parts: Vec<&str> = input.split_whitespace().collect();
-        match parts[0] {
-            "help" | "?" => show_help(),
+        match input {
+            "help" | "?" => {
+                show_help();
+                continue;
+            }
             "exit" | "quit" | "q" => {
                 println!("Goodbye!");
                 break;
             }
-            "agents" => {
-                print_info("Agent management commands - not yet implemented in interactive mode");
-            }
-            "control" => {
-                print_info("Service control commands - not yet implemented in interactive mode");
-            }
-            "config" => {
-                print_info("Configuration commands - not yet implemented in interactive mode");
-            }
             "clear" => {
                 print!("\x1B[2J\x1B[1;1H");
+                continue;
             }
-            _ => {
-                eprintln!(
-                    "{} Unknown command: '{}'. Type 'help' for available commands.",
-                    "Error:".red().bold(),
-                    parts[0]
-                );
+            "vars" => {
+                for (name, value) in session.vars.iter() {
+                    println!("{} = {}", name.green(), value);
+                }
+                continue;
             }
+            _ => {}
+        }
+
+        let result = match script::parse(input) {
+            Ok(statements) => session.run(&statements).await,
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = result {
+            eprintln!("{} {}", "Error:".red().bold(), e);
         }
     }
 
     Ok(())
 }
 
+/// Line editor with Emacs-style editing, arrow-key history, Ctrl-R search and
+/// tab completion; lines starting with a space are left out of the history
+fn new_editor() -> Result<LineEditor> {
+    let config = EditorConfig::builder()
+        .max_history_size(HISTORY_SIZE)?
+        .history_ignore_dups(true)?
+        .history_ignore_space(true)
+        .completion_type(CompletionType::List)
+        .build();
+    let mut editor = LineEditor::with_config(config).context("Failed to start the line editor")?;
+    editor.set_helper(Some(ShellHelper));
+    Ok(editor)
+}
+
+/// Load the history of earlier sessions; the path to append to, unless the
+/// data directory is unavailable
+fn load_history(editor: &mut LineEditor) -> Option<PathBuf> {
+    let path = Config::data_dir().ok()?.join(HISTORY_FILE);
+    if path.exists() {
+        if let Err(e) = editor.load_history(&path) {
+            print_warning(
+                WarningKind::Fallback,
+                &format!("Starting without history: failed to read {:?}: {}", path, e),
+            );
+        }
+    } else if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).ok()?;
+    }
+    Some(path)
+}
+
+/// Add a statement to the history, saved right away so other sessions and a
+/// crashed one keep it
+fn remember(editor: &mut LineEditor, path: Option<&Path>, input: &str) {
+    if !matches!(editor.add_history_entry(input), Ok(true)) {
+        return;
+    }
+    if let Some(path) = path {
+        // The file is created readable by the owner only
+        if let Err(e) = editor.append_history(path) {
+            debug!("Failed to save shell history to {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Read one statement, continuing on further lines while a `{` block is open;
+/// `None` at the end of input
+fn read_input(editor: &mut LineEditor, prompt: &str) -> Result<Option<String>> {
+    let mut input = String::new();
+
+    loop {
+        let prompt = if input.is_empty() { prompt } else { "...>" };
+        let line = match editor.readline(&format!("{} ", prompt.green().bold())) {
+            Ok(line) => line,
+            // Ctrl-C abandons the statement being typed
+            Err(ReadlineError::Interrupted) => return Ok(Some(String::new())),
+            Err(ReadlineError::Eof) if input.is_empty() => return Ok(None),
+            Err(ReadlineError::Eof) => return Ok(Some(String::new())),
+            Err(e) => return Err(e).context("Failed to read input"),
+        };
+        input.push_str(&line);
+        input.push('\n');
+
+        if !script::is_incomplete(&input) {
+            return Ok(Some(input));
+        }
+    }
+}
+
+impl Session<'_> {
+    async fn run(&mut self, statements: &[Statement]) -> Result<()> {
+        for statement in statements {
+            match statement {
+                Statement::Set { name, value } => {
+                    let value = self.expand(value)?.join(" ");
+                    self.vars.set(name, value);
+                }
+                Statement::Unset(name) => self.vars.unset(name),
+                Statement::Foreach { var, items, body } => {
+                    // Loop items are split on whitespace so `$var` holding a
+                    // list set from a substitution iterates item by item
+                    let items: Vec<String> = self
+                        .expand(items)?
+                        .iter()
+                        .flat_map(|item| item.split_whitespace().map(String::from))
+                        .collect();
+
+                    for item in items {
+                        self.vars.set(var, item);
+                        Box::pin(self.run(body)).await?;
+                    }
+                }
+                Statement::Command(words) => {
+                    let args = self.expand(words)?;
+                    self.run_command(&args).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Expand variables and command substitutions into plain arguments
+    fn expand(&self, words: &[Word]) -> Result<Vec<String>> {
+        let mut args = Vec::new();
+        for word in words {
+            match word {
+                Word::Text(text) => args.push(self.vars.expand(text)?),
+                Word::Literal(text) => args.push(text.clone()),
+                Word::Substitution(command) => args.extend(self.substitute(command)?),
+            }
+        }
+        Ok(args)
+    }
+
+    /// Run a command in a child process with JSON output and collect its items
+    fn substitute(&self, command: &str) -> Result<Vec<String>> {
+        let mut args = Vec::new();
+        for statement in script::parse(command)? {
+            match statement {
+                Statement::Command(words) => args.extend(self.expand(&words)?),
+                _ => return Err(anyhow!("Only commands can be substituted: ({})", command)),
+            }
+        }
+
+        let exe = std::env::current_exe().context("Failed to locate the wazuh-cli executable")?;
+        let mut child = Command::new(exe);
+        child.arg("--config").arg(self.config_path);
+        // The same manager as this session, e.g. under --profile
+        cli::connection_flags().apply(&mut child);
+        let output = child
+            .args(["--json", "--no-prompt"])
+            .args(&args)
+            .stderr(Stdio::inherit())
+            .output()
+            .with_context(|| format!("Failed to run ({})", command))?;
+
+        if !output.status.success() {
+            return Err(anyhow!("Command substitution ({}) failed", command));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(match serde_json::from_str(&stdout) {
+            Ok(value) => script::substitution_items(&value),
+            Err(_) => stdout
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(String::from)
+                .collect(),
+        })
+    }
+
+    async fn run_command(&self, args: &[String]) -> Result<()> {
+        let argv = std::iter::once("wazuh-cli").chain(args.iter().map(String::as_str));
+        let parsed = Cli::command()
+            .try_get_matches_from(argv)
+            .and_then(|matches| Ok((Cli::from_arg_matches(&matches)?, matches)));
+        let (cli, matches) = match parsed {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                e.print()?;
+                return Ok(());
+            }
+        };
+        check_session_flags(&matches)?;
+        // The format and columns apply to this line only, defaulting to the session's
+        let flags = cli.output_flags().or_session(&matches, &cli::output_flags());
+
+        match cli.command {
+            Some(Commands::Interactive) | None => {
+                print_info("Already in interactive mode");
+                Ok(())
+            }
+            Some(command) => {
+                output::set_ndjson(flags.output == "ndjson");
+                output::set_selected_columns(&flags.fields);
+                output::set_template(flags.template.as_deref())?;
+                let json = flags.json_output();
+                Box::pin(crate::execute(command, self.config, self.config_path, json)).await
+            }
+        }
+    }
+}
+
+/// Reject global flags that are fixed for the session, which a line would
+/// otherwise silently ignore
+fn check_session_flags(matches: &ArgMatches) -> Result<()> {
+    match cli::run_wide_flag(matches) {
+        Some(flag) => Err(WazuhError::InvalidInput(format!(
+            "{} applies to the whole session; restart the shell with it, e.g. \
+             'wazuh-cli {} ... interactive'",
+            flag, flag
+        ))
+        .into()),
+        None => Ok(()),
+    }
+}
+
 fn show_help() {
     println!("{}", "Available Commands:".bold().underline());
     println!();
     println!("  {}  - Show this help message", "help".green());
-    println!("  {} - List and manage agents", "agents".green());
-    println!("  {} - Control Wazuh services", "control".green());
-    println!("  {} - Manage configuration", "config".green());
     println!("  {}  - Clear the screen", "clear".green());
     println!("  {}  - Exit interactive mode", "exit".green());
     println!();
+    println!(
+        "Any wazuh-cli command can be run directly, e.g. {}",
+        "agent list --status active".green()
+    );
+    println!();
+    println!("{}", "Scripting:".bold().underline());
+    println!();
+    println!("  {}            - Set a variable, used as $gid", "set gid = dmz".green());
+    println!("  {}              - Remove a variable", "unset gid".green());
+    println!("  {}                   - Show variables", "vars".green());
+    println!("  {}  - Items returned by a command", "(agent list --group $gid)".green());
+    println!("  {}", "foreach agent in (agent list --group $gid) { agent get $agent }".green());
+    println!();
     println!("For detailed command help, use: <command> --help");
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replacements(line: &str) -> (usize, Vec<String>) {
+        let (start, pairs) = complete_line(line);
+        (start, pairs.into_iter().map(|pair| pair.replacement).collect())
+    }
+
+    #[test]
+    fn test_complete_line() {
+        let (start, words) = replacements("fo");
+        assert_eq!((start, words), (0, vec!["foreach".to_string()]));
+
+        let (start, words) = replacements("agent li");
+        assert_eq!(start, 6);
+        assert!(words.contains(&"list".to_string()));
+
+        let (start, words) = replacements("agent list --sta");
+        assert_eq!(start, 11);
+        assert!(words.contains(&"--status".to_string()));
+
+        // Commands inside a loop body complete from the start of the command
+        let (start, words) = replacements("foreach a in (agent list) { agent re");
+        assert_eq!(start, 34);
+        assert!(words.contains(&"restart".to_string()));
+        assert!(!words.contains(&"exit".to_string()));
+    }
+
+    #[test]
+    fn test_session_flags() {
+        use clap::Parser;
+
+        let matches = |line: &[&str]| Cli::command().try_get_matches_from(line).unwrap();
+        assert!(check_session_flags(&matches(&["wazuh-cli", "-j", "agent", "list"])).is_ok());
+        assert!(check_session_flags(&matches(&["wazuh-cli", "-o", "ndjson", "agent", "list"])).is_ok());
+        for flag in [
+            &["-P", "prod"][..],
+            &["--no-color"],
+            &["--output-file", "out.json"],
+            &["--max-results", "50"],
+            &["--envelope"],
+            &["--diff-context", "5"],
+            &["-v"],
+        ] {
+            let line = [&["wazuh-cli"][..], flag, &["interactive"]].concat();
+            assert!(check_session_flags(&matches(&line)).is_err());
+        }
+
+        let cli = Cli::try_parse_from(["wazuh-cli", "-P", "prod", "-H", "X-Org: b", "--token", "t"])
+            .unwrap();
+        let mut child = Command::new("wazuh-cli");
+        cli.connection_flags().apply(&mut child);
+        let args: Vec<_> = child.get_args().collect();
+        assert_eq!(args, ["--profile", "prod", "--header", "X-Org: b"]);
+        assert!(child.get_envs().any(|(name, value)| name == "WAZUH_TOKEN" && value.is_some()));
+    }
+
+    #[test]
+    fn test_line_output_flags() {
+        use clap::Parser;
+
+        let session = Cli::try_parse_from(["wazuh-cli", "-o", "ndjson", "--fields", "id"])
+            .unwrap()
+            .output_flags();
+        let line = |args: &[&str]| {
+            let matches = Cli::command().try_get_matches_from(args).unwrap();
+            let cli = Cli::from_arg_matches(&matches).unwrap();
+            cli.output_flags().or_session(&matches, &session)
+        };
+
+        let flags = line(&["wazuh-cli", "agent", "list"]);
+        assert_eq!(flags.output, "ndjson");
+        assert_eq!(flags.fields, ["id"]);
+
+        let flags = line(&["wazuh-cli", "-o", "table", "agent", "list"]);
+        assert_eq!(flags.output, "table");
+        assert_eq!(flags.fields, ["id"]);
+        assert!(!flags.json_output());
+    }
+}
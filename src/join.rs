@@ -0,0 +1,168 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+use tracing::warn;
+
+use crate::error::WazuhError;
+use crate::utils::{json_field, json_to_text};
+
+/// Columns from a local CSV file, keyed by one of its columns
+///
+/// Only the key and the requested columns are kept, so large inventory
+/// exports can be joined without holding whole rows in memory.
+#[derive(Debug)]
+pub struct CsvLookup {
+    /// Field of the API result matched against the key column
+    field: String,
+    columns: Vec<String>,
+    rows: HashMap<String, Vec<String>>,
+}
+
+/// Extra columns joined onto a list of results, one row per result
+#[derive(Debug, Default)]
+pub struct JoinedColumns {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl CsvLookup {
+    /// Read `path`, keyed on `on` and keeping `columns`
+    ///
+    /// `on` is either a name shared by the API field and the CSV column, or
+    /// `field=column` when they differ (e.g. `name=hostname`).
+    pub fn load(path: &Path, on: &str, columns: &[String]) -> Result<Self> {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open join file: {:?}", path))?;
+        Self::from_reader(file, on, columns)
+            .with_context(|| format!("Failed to read join file: {:?}", path))
+    }
+
+    fn from_reader<R: Read>(reader: R, on: &str, columns: &[String]) -> Result<Self> {
+        let (field, key_column) = on.split_once('=').unwrap_or((on, on));
+
+        let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(reader);
+        let headers = reader.headers()?.clone();
+        let position = |name: &str| {
+            headers.iter().position(|h| h == name).ok_or_else(|| {
+                WazuhError::InvalidInput(format!(
+                    "Column '{}' not found; available columns: {}",
+                    name,
+                    headers.iter().collect::<Vec<_>>().join(", ")
+                ))
+            })
+        };
+
+        let key_index = position(key_column)?;
+        let indexes = columns
+            .iter()
+            .map(|column| position(column))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut rows = HashMap::new();
+        for record in reader.records() {
+            let record = record?;
+            let key = record.get(key_index).unwrap_or_default().to_lowercase();
+            if key.is_empty() {
+                continue;
+            }
+
+            let values = indexes
+                .iter()
+                .map(|&i| record.get(i).unwrap_or_default().to_string())
+                .collect();
+            if rows.insert(key.clone(), values).is_some() {
+                warn!("Duplicate join key '{}'; using the last row", key);
+            }
+        }
+
+        Ok(Self {
+            field: field.to_string(),
+            columns: columns.to_vec(),
+            rows,
+        })
+    }
+
+    /// Left join: every item gets a row, empty where the CSV has no match
+    pub fn join<T: Serialize>(&self, items: &[T]) -> JoinedColumns {
+        let rows = items
+            .iter()
+            .map(|item| {
+                let doc = serde_json::to_value(item).unwrap_or_default();
+                let key = json_to_text(json_field(&doc, &self.field)).to_lowercase();
+                self.rows
+                    .get(&key)
+                    .cloned()
+                    .unwrap_or_else(|| vec![String::new(); self.columns.len()])
+            })
+            .collect();
+
+        JoinedColumns {
+            columns: self.columns.clone(),
+            rows,
+        }
+    }
+}
+
+impl JoinedColumns {
+    /// Serialize items with the joined columns added as top-level fields
+    pub fn merge<T: Serialize>(&self, items: &[T]) -> Result<Vec<Value>> {
+        items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let mut doc = serde_json::to_value(item)?;
+                if let (Value::Object(map), Some(row)) = (&mut doc, self.rows.get(i)) {
+                    for (column, value) in self.columns.iter().zip(row) {
+                        map.insert(column.clone(), Value::String(value.clone()));
+                    }
+                }
+                Ok(doc)
+            })
+            .collect()
+    }
+
+    /// Values for the item at `index`, or nothing when no join was requested
+    pub fn row(&self, index: usize) -> &[String] {
+        self.rows.get(index).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const CMDB: &str = "hostname, owner, location, cost_center\n\
+                        WEB-01, alice, Berlin, 100\n\
+                        db-01,bob,\"Paris, FR\",200\n";
+
+    #[test]
+    fn test_left_join() {
+        let columns = vec!["owner".to_string(), "location".to_string()];
+        let lookup = CsvLookup::from_reader(CMDB.as_bytes(), "name=hostname", &columns).unwrap();
+
+        let agents = vec![
+            json!({"id": "001", "name": "web-01"}),
+            json!({"id": "002", "name": "mail-01"}),
+            json!({"id": "003", "name": "db-01"}),
+        ];
+        let joined = lookup.join(&agents);
+        assert_eq!(joined.row(0), ["alice", "Berlin"]);
+        assert_eq!(joined.row(1), ["", ""]);
+        assert_eq!(joined.row(2), ["bob", "Paris, FR"]);
+
+        let merged = joined.merge(&agents).unwrap();
+        assert_eq!(merged[2]["location"], "Paris, FR");
+        assert_eq!(merged[1]["owner"], "");
+    }
+
+    #[test]
+    fn test_unknown_column() {
+        let columns = vec!["team".to_string()];
+        let err = CsvLookup::from_reader(CMDB.as_bytes(), "hostname", &columns).unwrap_err();
+        assert!(err.to_string().contains("Column 'team' not found"));
+    }
+}
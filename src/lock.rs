@@ -0,0 +1,302 @@
+//! Advisory locks on local state, so concurrent CLI instances (a cron job next
+//! to an interactive session) never interleave writes to the same files.
+//!
+//! Each resource has a lock file under `<data dir>/locks`, held with an
+//! exclusive OS file lock for as long as its guard lives. The lock is released
+//! by the OS when the process exits, so a crashed instance never leaves a
+//! resource locked; the holder details written into the file are informational.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::error::WazuhError;
+use crate::output::print_info;
+
+const LOCKS_DIR: &str = "locks";
+
+/// How long a lock held by another instance is waited for unless `--wait-lock` is given
+const DEFAULT_WAIT: Duration = Duration::from_secs(10);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Local state guarded by a lock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    /// The configuration file
+    Config,
+    /// Staged configuration changes
+    Changes,
+    /// The open maintenance window and its history
+    Maintenance,
+    /// The local history log
+    History,
+    /// Opt-in usage statistics
+    Usage,
+    /// Cached indexer responses
+    Cache,
+    /// Recorded agent check-in snapshots
+    Checkins,
+    /// Cached shell completion candidates
+    Completions,
+    /// API tokens kept between invocations
+    Tokens,
+}
+
+impl Resource {
+    pub const ALL: [Resource; 9] = [
+        Resource::Config,
+        Resource::Changes,
+        Resource::Maintenance,
+        Resource::History,
+        Resource::Usage,
+        Resource::Cache,
+        Resource::Checkins,
+        Resource::Completions,
+        Resource::Tokens,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Resource::Config => "config",
+            Resource::Changes => "changes",
+            Resource::Maintenance => "maintenance",
+            Resource::History => "history",
+            Resource::Usage => "usage",
+            Resource::Cache => "cache",
+            Resource::Checkins => "checkins",
+            Resource::Completions => "completions",
+            Resource::Tokens => "tokens",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LockMode {
+    /// Wait up to `DEFAULT_WAIT` for another instance, then fail
+    Bounded,
+    /// `--wait-lock`: wait as long as it takes
+    Wait,
+    /// `--no-lock`: skip locking entirely
+    Disabled,
+}
+
+static MODE: RwLock<LockMode> = RwLock::new(LockMode::Bounded);
+
+/// Select how locks are taken for the rest of the process
+pub fn set_mode(mode: LockMode) {
+    if let Ok(mut current) = MODE.write() {
+        *current = mode;
+    }
+}
+
+fn mode() -> LockMode {
+    MODE.read().map(|mode| *mode).unwrap_or(LockMode::Bounded)
+}
+
+/// The process holding a lock, as it recorded itself in the lock file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Holder {
+    pub pid: u32,
+    pub acquired_at: DateTime<Utc>,
+}
+
+/// An exclusive lock on a resource, released when dropped
+#[derive(Debug)]
+pub struct LockGuard {
+    file: File,
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        // Clear the holder details before letting the next instance in
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
+
+fn lock_path(resource: Resource) -> Result<PathBuf> {
+    Ok(Config::data_dir()?.join(LOCKS_DIR).join(format!("{}.lock", resource.name())))
+}
+
+fn read_holder(path: &Path) -> Option<Holder> {
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Lock a resource for the lifetime of the returned guard; `None` with `--no-lock`
+pub fn acquire(resource: Resource) -> Result<Option<LockGuard>> {
+    let mode = mode();
+    if mode == LockMode::Disabled {
+        return Ok(None);
+    }
+    let max_wait = (mode == LockMode::Bounded).then_some(DEFAULT_WAIT);
+    lock_file(&lock_path(resource)?, resource, max_wait).map(Some)
+}
+
+/// Sleep between lock attempts without stalling the tokio worker this runs on
+///
+/// Locks are taken from synchronous code called by async commands; on the
+/// multi-threaded runtime the worker's other tasks move to another thread
+/// while this one waits.
+fn pause() {
+    use tokio::runtime::{Handle, RuntimeFlavor};
+
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| std::thread::sleep(POLL_INTERVAL))
+        }
+        _ => std::thread::sleep(POLL_INTERVAL),
+    }
+}
+
+/// Lock the file at `path`, waiting up to `max_wait` for another holder, or
+/// as long as it takes with `None`
+fn lock_file(path: &Path, resource: Resource, max_wait: Option<Duration>) -> Result<LockGuard> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create lock directory: {:?}", parent))?;
+    }
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .with_context(|| format!("Failed to open lock file: {:?}", path))?;
+
+    let started = Instant::now();
+    let mut announced = false;
+    loop {
+        match file.try_lock() {
+            Ok(()) => break,
+            Err(TryLockError::WouldBlock) => {
+                let holder = read_holder(path);
+                if max_wait.is_some_and(|max_wait| started.elapsed() >= max_wait) {
+                    return Err(WazuhError::Locked {
+                        resource: resource.name().to_string(),
+                        holder: describe_holder(holder.as_ref()),
+                    }
+                    .into());
+                }
+                if !announced {
+                    print_info(&format!(
+                        "Waiting for the {} lock held by{}...",
+                        resource.name(),
+                        describe_holder(holder.as_ref())
+                    ));
+                    announced = true;
+                }
+                pause();
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("Failed to lock {:?}", path));
+            }
+        }
+    }
+
+    let holder = Holder {
+        pid: std::process::id(),
+        acquired_at: Utc::now(),
+    };
+    file.set_len(0)?;
+    file.write_all(serde_json::to_string(&holder)?.as_bytes())?;
+    Ok(LockGuard { file })
+}
+
+fn describe_holder(holder: Option<&Holder>) -> String {
+    match holder {
+        Some(holder) => format!(
+            " process {} (since {})",
+            holder.pid,
+            holder.acquired_at.format("%Y-%m-%d %H:%M:%S UTC")
+        ),
+        None => " another process".to_string(),
+    }
+}
+
+/// Current state of a resource lock, for the `locks` command
+#[derive(Debug, Clone, Serialize)]
+pub struct LockStatus {
+    pub resource: &'static str,
+    pub path: String,
+    pub held: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub holder: Option<Holder>,
+}
+
+/// Probe every lock without waiting; a held lock is released again immediately
+pub fn status() -> Result<Vec<LockStatus>> {
+    Resource::ALL
+        .iter()
+        .map(|&resource| {
+            let path = lock_path(resource)?;
+            let held = match File::open(&path) {
+                Ok(file) => match file.try_lock_shared() {
+                    Ok(()) => {
+                        let _ = file.unlock();
+                        false
+                    }
+                    Err(TryLockError::WouldBlock) => true,
+                    Err(TryLockError::Error(e)) => {
+                        return Err(e).with_context(|| format!("Failed to probe {:?}", path));
+                    }
+                },
+                Err(_) => false,
+            };
+            Ok(LockStatus {
+                resource: resource.name(),
+                path: path.display().to_string(),
+                held,
+                holder: if held { read_holder(&path) } else { None },
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_is_exclusive_until_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locks").join("config.lock");
+        let guard = lock_file(&path, Resource::Config, None).unwrap();
+        let holder = read_holder(&path).unwrap();
+        assert_eq!(holder.pid, std::process::id());
+
+        let err = lock_file(&path, Resource::Config, Some(Duration::from_millis(200)))
+            .unwrap_err();
+        match err.downcast_ref::<WazuhError>() {
+            Some(WazuhError::Locked { resource, holder }) => {
+                assert_eq!(resource, "config");
+                assert!(holder.contains(&std::process::id().to_string()));
+            }
+            other => panic!("expected a lock error, got {:?}", other),
+        }
+
+        drop(guard);
+        lock_file(&path, Resource::Config, Some(Duration::from_millis(200))).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_lock_waits_for_release() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.lock");
+        let guard = lock_file(&path, Resource::Config, None).unwrap();
+
+        let release = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            drop(guard);
+        });
+        lock_file(&path, Resource::Config, Some(Duration::from_secs(5))).unwrap();
+        release.await.unwrap();
+    }
+}
@@ -1,34 +1,94 @@
 use anyhow::Result;
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
+use clap_complete::CompleteEnv;
 use colored::Colorize;
+use std::path::Path;
 use std::process;
+use std::time::Instant;
 use tracing::{error, info, Level};
 use tracing_subscriber::{fmt, EnvFilter};
 
 mod cli;
+mod certs;
+mod changes;
+mod checkins;
 mod client;
 mod commands;
+mod completion;
+mod compliance;
 mod config;
+mod credentials;
+mod diff;
+mod disconnect;
+mod doctor;
+mod document;
+mod drift;
 mod error;
+mod escalate;
+mod estimate;
+mod export;
+mod expr;
+mod history;
+mod indexer;
 mod interactive;
+mod join;
+mod lock;
+mod maintenance;
 mod models;
+mod notify;
+mod onboarding;
 mod output;
+mod progress;
+mod redirect;
+mod script;
+mod selector;
+mod ssh;
+mod storage;
+mod template;
+mod tokens;
+mod trace;
+mod usage;
 mod utils;
+mod xml;
 
 use cli::{Cli, Commands};
 use config::Config;
 
 #[tokio::main]
 async fn main() {
+    // Answer shell completion requests (COMPLETE=<shell>) before doing anything else
+    CompleteEnv::with_factory(Cli::command).complete();
+
     if let Err(e) = run().await {
         error!("Application error: {}", e);
         eprintln!("{} {}", "Error:".red().bold(), e);
-        process::exit(1);
+        process::exit(error::exit_code(&e));
     }
 }
 
 async fn run() -> Result<()> {
-    let cli = Cli::parse();
+    // Keep the raw matches so usage statistics can tell which flags were given
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches)?;
+    cli::set_connection_flags(cli.connection_flags());
+    cli::set_output_flags(cli.output_flags());
+
+    // Handle version command before reading any configuration or credentials
+    if cli.version {
+        println!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+        return Ok(());
+    }
+
+    if redirect::pager_width().is_some() {
+        redirect::exit_on_broken_pipe();
+    }
+
+    if let Some(path) = &cli.output_file {
+        if !redirect::is_redirected() {
+            let code = redirect::run_to_file(path).await?;
+            process::exit(code);
+        }
+    }
 
     // Initialize logging based on verbosity
     let log_level = match cli.verbose {
@@ -42,55 +102,346 @@ async fn run() -> Result<()> {
     let filter = EnvFilter::from_default_env()
         .add_directive(format!("wazuh_cli_rs={}", log_level).parse()?);
 
+    // Logs are diagnostics, never part of the data written to stdout
     fmt()
         .with_env_filter(filter)
         .with_target(false)
+        .with_writer(std::io::stderr)
         .init();
 
     info!("Wazuh CLI starting with log level: {}", log_level);
 
-    // Load configuration
-    let config = Config::load(&cli.config)?;
+    // Load configuration, or walk a new user through creating one
+    let config = if onboarding::should_run(&cli)? {
+        output::set_accessible(cli.a11y);
+        onboarding::run(&cli.config).await?
+    } else {
+        Config::load(&cli.config)?
+    };
+    let mut config = config.with_profile(cli.profile.as_deref())?;
     info!("Configuration loaded from: {:?}", cli.config);
 
-    // Handle version command
-    if cli.version {
-        println!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
-        return Ok(());
-    }
+    output::set_accessible(cli.a11y || config.output.a11y);
+    let color = output::color_enabled(cli.no_color, config.output.color);
+    output::init_terminal(color);
+    output::set_computed_columns(&config.output.columns)?;
+    progress::set_json_events(cli.progress == "json");
+    output::set_format_version(cli.format_version.unwrap_or(config.output.format_version))?;
+    output::set_envelope(cli.envelope);
+    output::set_ndjson(cli.output == "ndjson");
+    output::set_selected_columns(&cli.fields);
+    client::set_max_results(cli.max_results);
+    trace::set_target(cli.trace_http.as_deref())?;
+    output::set_template(cli.template.as_deref())?;
+    lock::set_mode(match (cli.wait_lock, cli.no_lock) {
+        (_, true) => lock::LockMode::Disabled,
+        (true, _) => lock::LockMode::Wait,
+        _ => lock::LockMode::Bounded,
+    });
+    diff::set_options(diff::DiffOptions {
+        style: cli.diff_style.as_deref().unwrap_or(&config.output.diff_style).parse()?,
+        context: cli.diff_context.unwrap_or(config.output.diff_context),
+    });
 
-    // Execute the appropriate command
-    match cli.command {
-        Some(Commands::Agent(agent_cmd)) => {
-            commands::agent::handle_agent_command(agent_cmd, &config, cli.json).await?;
-        }
-        Some(Commands::Control(control_cmd)) => {
-            commands::control::handle_control_command(control_cmd, &config, cli.json).await?;
+    // The child of a paged run would repeat the warning on the same terminal
+    if redirect::pager_width().is_none() {
+        if let Some(warning) = Config::permission_warning(&cli.config) {
+            output::print_warning(output::WarningKind::Insecure, &warning);
         }
-        Some(Commands::Config(config_cmd)) => {
-            commands::config::handle_config_command(config_cmd, &config, cli.json).await?;
+    }
+
+    if cli.no_prompt {
+        config.output.prompt_large_results = false;
+    }
+
+    if let Some(profile) = &cli.tls_profile {
+        config.tls.profile = Some(profile.clone());
+    }
+
+    if let Some(secs) = cli.timeout {
+        config.api.timeout = secs;
+        config.api.long_timeout = secs;
+    }
+    if let Some(token) = &cli.token {
+        config.auth.token = Some(token.clone());
+    }
+
+    if let Some(path) = &cli.auth_context {
+        config.auth.run_as = true;
+        config.auth.auth_context = Some(path.clone());
+    }
+
+    for header in &cli.headers {
+        let (name, value) = utils::parse_header(header)?;
+        // Header names are case-insensitive, so `x-org` replaces a configured `X-Org`
+        config.api.extra_headers.retain(|existing, _| !existing.eq_ignore_ascii_case(&name));
+        config.api.extra_headers.insert(name, value);
+    }
+
+    // Without arguments, run the configured default command instead of the shell
+    let (command, json) = match (cli.command.take(), &config.default_command) {
+        (None, Some(line)) => {
+            let mut default = parse_default_command(line)?;
+            merge_output_flags(&mut default, &cli);
+            output::set_ndjson(default.output == "ndjson");
+            output::set_selected_columns(&default.fields);
+            output::set_template(default.template.as_deref())?;
+            let json = default.json || default.output != "table" || default.template.is_some();
+            (default.command, cli.json || json)
         }
-        Some(Commands::Interactive) => {
-            interactive::start_interactive_mode(&config).await?;
+        (command, _) => (command, cli.json || cli.output != "table" || cli.template.is_some()),
+    };
+
+    // The envelope wraps the output of one command, not of a whole session
+    if cli.envelope && matches!(command, None | Some(Commands::Interactive)) {
+        return Err(error::WazuhError::InvalidInput(
+            "--envelope wraps a single command and cannot be used with interactive mode".to_string(),
+        )
+        .into());
+    }
+
+    // Looked up once here rather than by every client built later; local
+    // commands leave the credential store alone, since it may block or prompt
+    if !command.as_ref().is_some_and(Commands::is_local) {
+        credentials::apply(&mut config);
+    }
+
+    // Execute the appropriate command
+    match command {
+        Some(command) => {
+            if let Some(pager) = redirect::pager(config.output.pager && command.pageable()) {
+                if let Some(code) = redirect::run_paged(&pager, color).await? {
+                    process::exit(code);
+                }
+            }
+
+            let started = Instant::now();
+            let result = execute(command, &config, &cli.config, json || cli.envelope).await;
+            if config.stats.enabled {
+                let (name, flags) = usage::describe(&Cli::command(), &matches);
+                let slow = client::take_slow_endpoints();
+                usage::record_quietly(name, flags, started.elapsed(), result.is_ok(), slow);
+            }
+            output::finish_envelope(result.as_ref().err())?;
+            result
         }
         None => {
             // No command provided, start interactive mode
             info!("No command provided, starting interactive mode");
-            interactive::start_interactive_mode(&config).await?;
+            interactive::start_interactive_mode(&config, &cli.config).await
         }
     }
+}
+
+/// Parse the `default_command` setting as if it had been typed after `wazuh-cli`;
+/// like a shell line, it may only choose its output
+fn parse_default_command(line: &str) -> Result<Cli> {
+    let args = script::split_command(line)?;
+    let argv = std::iter::once("wazuh-cli").chain(args.iter().map(String::as_str));
+    let matches = Cli::command()
+        .try_get_matches_from(argv)
+        .map_err(|e| anyhow::anyhow!("Invalid default_command '{}':\n{}", line, e.render()))?;
+    if let Some(flag) = cli::run_wide_flag(&matches) {
+        return Err(error::WazuhError::InvalidInput(format!(
+            "default_command '{}' cannot set {}; give it on the command line instead",
+            line, flag
+        ))
+        .into());
+    }
+    Ok(Cli::from_arg_matches(&matches)?)
+}
+
+/// Output flags given on the command line replace those of the default command
+fn merge_output_flags(default: &mut Cli, cli: &Cli) {
+    if cli.output != "table" {
+        default.output = cli.output.clone();
+    }
+    if !cli.fields.is_empty() {
+        default.fields = cli.fields.clone();
+    }
+    if cli.template.is_some() {
+        default.template = cli.template.clone();
+    }
+}
 
-    Ok(())
+/// Dispatch a parsed command; also used by the interactive shell
+pub async fn execute(
+    command: Commands,
+    config: &Config,
+    config_path: &Path,
+    json_output: bool,
+) -> Result<()> {
+    match command {
+        Commands::Agent(agent_cmd) => {
+            commands::agent::handle_agent_command(agent_cmd, config, json_output).await
+        }
+        Commands::Ar(ar_cmd) => commands::ar::handle_ar_command(ar_cmd, config, json_output).await,
+        Commands::Tasks(tasks_cmd) => {
+            commands::tasks::handle_tasks_command(tasks_cmd, config, json_output).await
+        }
+        Commands::Control(control_cmd) => {
+            commands::control::handle_control_command(control_cmd, config, json_output).await
+        }
+        Commands::Config(config_cmd) => {
+            commands::config::handle_config_command(config_cmd, config, config_path, json_output)
+                .await
+        }
+        Commands::Auth(auth_cmd) => {
+            commands::auth::handle_auth_command(auth_cmd, config, config_path, json_output).await
+        }
+        Commands::Cluster(cluster_cmd) => {
+            commands::cluster::handle_cluster_command(cluster_cmd, config, json_output).await
+        }
+        Commands::Rules(rules_cmd) => {
+            commands::rules::handle_rules_command(rules_cmd, config, json_output).await
+        }
+        Commands::Decoders(decoders_cmd) => {
+            commands::decoders::handle_decoders_command(decoders_cmd, config, json_output).await
+        }
+        Commands::Lists(lists_cmd) => {
+            commands::lists::handle_lists_command(lists_cmd, config, json_output).await
+        }
+        Commands::Logtest(logtest_cmd) => {
+            commands::logtest::handle_logtest_command(logtest_cmd, config, json_output).await
+        }
+        Commands::Mitre(mitre_cmd) => {
+            commands::mitre::handle_mitre_command(mitre_cmd, config, json_output).await
+        }
+        Commands::Alerts(alerts_cmd) => {
+            commands::alerts::handle_alerts_command(alerts_cmd, config, json_output).await
+        }
+        Commands::Archives(archives_cmd) => {
+            commands::archives::handle_archives_command(archives_cmd, config, json_output).await
+        }
+        Commands::Report(report_cmd) => {
+            commands::report::handle_report_command(report_cmd, config, json_output).await
+        }
+        Commands::Monitor(monitor_cmd) => {
+            commands::monitor::handle_monitor_command(monitor_cmd, config, json_output).await
+        }
+        Commands::Security(security_cmd) => {
+            commands::security::handle_security_command(security_cmd, config, json_output).await
+        }
+        Commands::Drift(drift_cmd) => {
+            commands::drift::handle_drift_command(drift_cmd, config, json_output).await
+        }
+        Commands::Group(group_cmd) => {
+            commands::group::handle_group_command(group_cmd, config, json_output).await
+        }
+        Commands::Fim(fim_cmd) => {
+            commands::fim::handle_fim_command(fim_cmd, config, json_output).await
+        }
+        Commands::Rootcheck(rootcheck_cmd) => {
+            commands::rootcheck::handle_rootcheck_command(rootcheck_cmd, config, json_output).await
+        }
+        Commands::Vuln(vuln_cmd) => {
+            commands::vuln::handle_vuln_command(vuln_cmd, config, json_output).await
+        }
+        Commands::Events(events_cmd) => {
+            commands::events::handle_events_command(events_cmd, config, json_output).await
+        }
+        Commands::Inventory(inventory_cmd) => {
+            commands::inventory::handle_inventory_command(inventory_cmd, config, json_output).await
+        }
+        Commands::Export(export_cmd) => {
+            commands::export::handle_export_command(export_cmd, config, json_output).await
+        }
+        Commands::Indexer(indexer_cmd) => {
+            commands::indexer::handle_indexer_command(indexer_cmd, config, json_output).await
+        }
+        Commands::Stats(stats_cmd) => {
+            commands::stats::handle_stats_command(stats_cmd, config, config_path, json_output)
+                .await
+        }
+        Commands::Completion(completion_cmd) => {
+            commands::completion::handle_completion_command(completion_cmd, config, json_output)
+                .await
+        }
+        Commands::Selftest(selftest_cmd) => {
+            commands::selftest::handle_selftest_command(selftest_cmd, config, json_output).await
+        }
+        Commands::Locks => {
+            let locks = lock::status()?;
+            if json_output {
+                output::print_json(&locks)
+            } else {
+                output::print_lock_status(&locks);
+                Ok(())
+            }
+        }
+        Commands::Interactive => interactive::start_interactive_mode(config, config_path).await,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use clap::Parser;
 
     #[tokio::test]
     async fn test_cli_parsing() {
         // Test that the CLI structure can be parsed
-        let cli = Cli::try_parse_from(&["wazuh-cli", "--help"]);
+        let cli = Cli::try_parse_from(["wazuh-cli", "--help"]);
         assert!(cli.is_err()); // --help should cause an exit
     }
+
+    #[test]
+    fn test_parse_default_command() {
+        let cli = parse_default_command("-j agent list --status 'active'").unwrap();
+        assert!(cli.json);
+        assert!(matches!(cli.command, Some(Commands::Agent(_))));
+
+        assert!(parse_default_command("agent frobnicate").is_err());
+        assert!(parse_default_command("agent list --group $gid").is_err());
+        assert!(parse_default_command("-P prod agent list").is_err());
+        assert!(parse_default_command("--max-results 50 agent list").is_err());
+    }
+
+    #[test]
+    fn test_merge_output_flags() {
+        let mut default =
+            parse_default_command("--format ndjson --fields id,name agent list").unwrap();
+        merge_output_flags(&mut default, &Cli::try_parse_from(["wazuh-cli"]).unwrap());
+        assert_eq!(default.output, "ndjson");
+        assert_eq!(default.fields, ["id", "name"]);
+
+        let cli = Cli::try_parse_from(["wazuh-cli", "--template", "{{.id}}"]).unwrap();
+        merge_output_flags(&mut default, &cli);
+        assert_eq!(default.template.as_deref(), Some("{{.id}}"));
+        assert_eq!(default.output, "ndjson");
+    }
+
+    #[test]
+    fn test_pageable() {
+        let pageable = |line: &str| {
+            let cli = parse_default_command(line).unwrap();
+            cli.command.unwrap().pageable()
+        };
+        assert!(pageable("agent list --status active"));
+        assert!(pageable("control logs --tail 500"));
+        assert!(!pageable("control logs --follow"));
+        assert!(!pageable("agent remove 001 --yes"));
+    }
+
+    #[test]
+    fn test_group_assign() {
+        use cli::AgentGroupAction;
+        let assign = |line: &str| match parse_default_command(line).unwrap().command {
+            Some(Commands::Group(group)) => match group.action {
+                AgentGroupAction::Assign { agent, group, .. } => (agent, group),
+                _ => panic!("expected group assign"),
+            },
+            _ => panic!("expected group"),
+        };
+        assert_eq!(assign("group assign 004 web"), (Some("004".to_string()), "web".to_string()));
+        assert_eq!(assign("group assign web --pattern 'db-*'"), (None, "web".to_string()));
+        assert!(parse_default_command("group assign web").is_err());
+        assert!(parse_default_command("group assign 004 web --select 'group==db'").is_err());
+    }
+
+    #[test]
+    fn test_cli_definition() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
 }
\ No newline at end of file
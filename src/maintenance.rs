@@ -0,0 +1,179 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::future::Future;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use crate::config::Config;
+use crate::lock::{self, Resource};
+
+const ACTIVE_FILE: &str = "maintenance.json";
+const HISTORY_FILE: &str = "maintenance-history.jsonl";
+
+/// A maintenance window opened with `control maintenance enter`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    pub started_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<DateTime<Utc>>,
+    pub operator: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// SSH destination used to pause and resume integrations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
+    #[serde(default)]
+    pub desktop: bool,
+    /// analysisd/remoted statistics when entering and leaving maintenance
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats_before: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats_after: Option<serde_json::Value>,
+    pub steps: Vec<MaintenanceStep>,
+}
+
+/// One timed step of entering or leaving maintenance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceStep {
+    pub name: String,
+    pub outcome: StepOutcome,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StepOutcome {
+    Ok,
+    Skipped,
+    Failed,
+}
+
+impl fmt::Display for StepOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StepOutcome::Ok => write!(f, "OK"),
+            StepOutcome::Skipped => write!(f, "Skipped"),
+            StepOutcome::Failed => write!(f, "Failed"),
+        }
+    }
+}
+
+impl MaintenanceStep {
+    /// Run a step, recording its duration; errors mark the step failed
+    /// instead of aborting the checklist
+    pub async fn timed<F>(name: &str, step: F) -> Self
+    where
+        F: Future<Output = Result<String>>,
+    {
+        let started_at = Utc::now();
+        let started = Instant::now();
+        let result = step.await;
+
+        let (outcome, detail) = match result {
+            Ok(detail) => (StepOutcome::Ok, detail),
+            Err(e) => (StepOutcome::Failed, format!("{:#}", e)),
+        };
+
+        Self {
+            name: name.to_string(),
+            outcome,
+            started_at,
+            duration_ms: started.elapsed().as_millis() as u64,
+            detail: (!detail.is_empty()).then_some(detail),
+        }
+    }
+
+    pub fn skipped(name: &str, reason: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            outcome: StepOutcome::Skipped,
+            started_at: Utc::now(),
+            duration_ms: 0,
+            detail: Some(reason.to_string()),
+        }
+    }
+}
+
+fn active_path() -> Result<PathBuf> {
+    Ok(Config::data_dir()?.join(ACTIVE_FILE))
+}
+
+/// The currently open maintenance window, if any
+pub fn load_active() -> Result<Option<MaintenanceWindow>> {
+    let path = active_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read maintenance state: {:?}", path))?;
+    serde_json::from_str(&contents)
+        .map(Some)
+        .with_context(|| format!("Failed to parse maintenance state: {:?}", path))
+}
+
+/// Persist the open maintenance window
+pub fn save_active(window: &MaintenanceWindow) -> Result<()> {
+    let path = active_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create data directory: {:?}", parent))?;
+    }
+
+    let _lock = lock::acquire(Resource::Maintenance)?;
+    fs::write(&path, serde_json::to_string_pretty(window)?)
+        .with_context(|| format!("Failed to write maintenance state: {:?}", path))
+}
+
+/// Close the window: append it to the maintenance history and clear the active state
+pub fn finish(window: &MaintenanceWindow) -> Result<()> {
+    if window.ended_at.is_none() {
+        return Err(anyhow!("Maintenance window has not ended"));
+    }
+
+    let _lock = lock::acquire(Resource::Maintenance)?;
+    let history = Config::data_dir()?.join(HISTORY_FILE);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&history)
+        .with_context(|| format!("Failed to open maintenance history: {:?}", history))?;
+    writeln!(file, "{}", serde_json::to_string(window)?)?;
+
+    let path = active_path()?;
+    fs::remove_file(&path)
+        .with_context(|| format!("Failed to remove maintenance state: {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_timed_steps() {
+        let ok = MaintenanceStep::timed("snapshot stats", async { Ok("saved".to_string()) }).await;
+        assert_eq!(ok.outcome, StepOutcome::Ok);
+        assert_eq!(ok.detail.as_deref(), Some("saved"));
+
+        let failed =
+            MaintenanceStep::timed("drain queues", async { Err(anyhow!("timed out")) }).await;
+        assert_eq!(failed.outcome, StepOutcome::Failed);
+        assert_eq!(failed.detail.as_deref(), Some("timed out"));
+
+        let skipped = MaintenanceStep::skipped("pause integrations", "no --host given");
+        assert_eq!(skipped.outcome, StepOutcome::Skipped);
+    }
+}
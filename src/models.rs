@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::compliance::Standard;
 
 /// Generic API response wrapper
 #[derive(Debug, Serialize, Deserialize)]
@@ -23,9 +25,9 @@ pub struct Agent {
     pub os: Option<AgentOs>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "lastKeepAlive", skip_serializing_if = "Option::is_none")]
     pub last_keep_alive: Option<DateTime<Utc>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "dateAdd", skip_serializing_if = "Option::is_none")]
     pub date_add: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub group: Option<Vec<String>>,
@@ -57,6 +59,22 @@ impl std::fmt::Display for AgentStatus {
     }
 }
 
+/// Fleet overview of `agent summary`
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentSummary {
+    /// Registered agents, not counting the manager (000)
+    pub total: u64,
+    pub by_status: Vec<(AgentStatus, u64)>,
+    /// Agents running their group's current configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_synced: Option<u64>,
+    /// Agent count per OS platform, largest first
+    pub platforms: Vec<(String, u64)>,
+    pub max_agents: Option<u64>,
+    /// Most recently registered agents, newest first
+    pub recent: Vec<Agent>,
+}
+
 /// Agent operating system information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentOs {
@@ -69,15 +87,20 @@ pub struct AgentOs {
     pub codename: Option<String>,
 }
 
-/// Agent list response
+/// Paginated list response returned by most endpoints
 #[derive(Debug, Serialize, Deserialize)]
-pub struct AgentListResponse {
-    pub affected_items: Vec<Agent>,
+pub struct ListResponse<T> {
+    pub affected_items: Vec<T>,
     pub total_affected_items: u32,
+    #[serde(default)]
     pub total_failed_items: u32,
+    #[serde(default)]
     pub failed_items: Vec<serde_json::Value>,
 }
 
+/// Agent list response
+pub type AgentListResponse = ListResponse<Agent>;
+
 /// Service information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Service {
@@ -108,7 +131,36 @@ impl std::fmt::Display for ServiceStatus {
     }
 }
 
+/// Line of the manager's ossec.log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManagerLog {
+    pub timestamp: String,
+    pub tag: String,
+    pub level: String,
+    pub description: String,
+}
+
+/// Line counts of one daemon in the manager's ossec.log, by level
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManagerLogSummary {
+    #[serde(default)]
+    pub tag: String,
+    #[serde(default)]
+    pub all: u64,
+    #[serde(default)]
+    pub critical: u64,
+    #[serde(default)]
+    pub error: u64,
+    #[serde(default)]
+    pub warning: u64,
+    #[serde(default)]
+    pub info: u64,
+    #[serde(default)]
+    pub debug: u64,
+}
+
 /// Manager information
+#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ManagerInfo {
     pub compilation_date: Option<String>,
@@ -122,6 +174,7 @@ pub struct ManagerInfo {
 }
 
 /// Cluster information
+#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ClusterInfo {
     pub enabled: bool,
@@ -131,7 +184,135 @@ pub struct ClusterInfo {
     pub node_type: Option<String>,
 }
 
+/// Cluster node as listed by `/cluster/nodes`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterNode {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub node_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+}
+
+/// Health probe result for one cluster node
+#[derive(Debug, Serialize)]
+pub struct NodeHealth {
+    pub name: String,
+    pub node_type: String,
+    pub ip: Option<String>,
+    pub api_reachable: bool,
+    pub latency_ms: Option<u64>,
+    pub clock_skew_secs: Option<i64>,
+    pub daemons_running: Option<usize>,
+    pub daemons_total: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Synchronization state of one cluster node, from `/cluster/healthcheck`
+#[derive(Debug, Serialize)]
+pub struct NodeSyncStatus {
+    pub name: String,
+    pub node_type: String,
+    pub version: Option<String>,
+    pub last_keep_alive: Option<DateTime<Utc>>,
+    /// End of the last integrity check run by the master for this worker
+    pub last_integrity_check: Option<DateTime<Utc>>,
+    /// End of the last integrity synchronization
+    pub last_integrity_sync: Option<DateTime<Utc>>,
+    pub last_agent_info_sync: Option<DateTime<Utc>>,
+    /// Files the last integrity synchronization had to send, by category
+    pub files_missing: Option<u64>,
+    pub files_shared: Option<u64>,
+    pub files_extra: Option<u64>,
+    /// An integrity synchronization is running right now
+    pub syncing: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub recent_logs: Vec<String>,
+}
+
+/// Current usage of one manager limit
+#[derive(Debug, Serialize)]
+pub struct LimitUsage {
+    pub name: String,
+    pub used: u64,
+    /// `None` when the manager reports no limit
+    pub max: Option<u64>,
+    pub percent: Option<f64>,
+    pub level: LimitLevel,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LimitLevel {
+    Ok,
+    Warning,
+    Critical,
+    Unlimited,
+}
+
+/// Output of `control limits`
+#[derive(Debug, Serialize)]
+pub struct LimitsReport {
+    pub limits: Vec<LimitUsage>,
+    /// Enrollment (authd) settings that cap or gate new agents
+    pub enrollment: BTreeMap<String, String>,
+}
+
+/// Hotfixes reported by syscollector for one Windows agent
+#[derive(Debug, Serialize)]
+pub struct AgentHotfixes {
+    pub agent_id: String,
+    pub agent_name: String,
+    pub hotfixes: Vec<String>,
+    /// When syscollector last scanned the agent's hotfixes
+    pub scan_time: Option<String>,
+    /// Whether the KB given with `--missing-kb` is installed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub has_kb: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Agent counts per version, optionally broken down by OS platform
+#[derive(Debug, Serialize)]
+pub struct VersionMatrix {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_version: Option<String>,
+    /// Platform columns; empty unless a matrix was requested
+    pub platforms: Vec<String>,
+    pub rows: Vec<VersionMatrixRow>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionMatrixRow {
+    pub version: String,
+    pub below_minimum: bool,
+    /// Count per platform, in the order of `VersionMatrix::platforms`
+    pub counts: Vec<u64>,
+    pub total: u64,
+}
+
+/// Result of auditing one certificate
+#[derive(Debug, Serialize)]
+pub struct CertificateStatus {
+    /// Where the certificate came from (endpoint URL or file path)
+    pub source: String,
+    pub subject: Option<String>,
+    pub issuer: Option<String>,
+    pub not_after: Option<DateTime<Utc>>,
+    pub days_left: Option<i64>,
+    /// Key algorithm and size, e.g. `RSA 2048`
+    pub key: Option<String>,
+    pub signature: Option<String>,
+    /// Expiry and weak parameter findings
+    pub issues: Vec<String>,
+}
+
 /// Agent key information
+#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AgentKey {
     pub id: String,
@@ -139,6 +320,7 @@ pub struct AgentKey {
 }
 
 /// Configuration item
+#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConfigItem {
     pub section: String,
@@ -147,6 +329,7 @@ pub struct ConfigItem {
 }
 
 /// Statistics
+#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Stats {
     pub alerts: HashMap<String, u64>,
@@ -155,8 +338,9 @@ pub struct Stats {
     pub syscollector: HashMap<String, u64>,
 }
 
-/// Request parameters for agent operations
-#[derive(Debug, Serialize)]
+/// Request parameters for agent operations; list endpoints are fetched page by
+/// page until `limit` items, or all of them without one
+#[derive(Debug, Default, Serialize)]
 pub struct AgentParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limit: Option<u32>,
@@ -176,10 +360,10 @@ pub struct AgentParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub q: Option<String>,
     
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "os.platform", skip_serializing_if = "Option::is_none")]
     pub os_platform: Option<String>,
     
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "os.version", skip_serializing_if = "Option::is_none")]
     pub os_version: Option<String>,
     
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -195,25 +379,431 @@ pub struct AgentParams {
     pub node_name: Option<String>,
 }
 
-impl Default for AgentParams {
-    fn default() -> Self {
-        Self {
-            limit: Some(500),
-            offset: None,
-            sort: None,
-            search: None,
-            status: None,
-            q: None,
-            os_platform: None,
-            os_version: None,
-            manager: None,
-            version: None,
-            group: None,
-            node_name: None,
+/// Ruleset rule definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rule {
+    pub id: u32,
+    pub level: u32,
+    #[serde(default)]
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relative_dirname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub groups: Vec<String>,
+    #[serde(default)]
+    pub pci_dss: Vec<String>,
+    #[serde(default)]
+    pub gdpr: Vec<String>,
+    #[serde(default)]
+    pub hipaa: Vec<String>,
+    #[serde(default)]
+    pub nist_800_53: Vec<String>,
+    #[serde(default)]
+    pub gpg13: Vec<String>,
+    #[serde(default)]
+    pub tsc: Vec<String>,
+    #[serde(default)]
+    pub mitre: Vec<String>,
+    /// Matching options of the rule definition (`match`, `if_sid`, `frequency`, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl Rule {
+    /// Requirements of a compliance standard this rule maps to
+    pub fn requirements(&self, standard: Standard) -> &[String] {
+        match standard {
+            Standard::PciDss => &self.pci_dss,
+            Standard::Gdpr => &self.gdpr,
+            Standard::Hipaa => &self.hipaa,
+            Standard::Nist80053 => &self.nist_800_53,
+            Standard::Gpg13 => &self.gpg13,
+            Standard::Tsc => &self.tsc,
+        }
+    }
+
+    /// All compliance mappings in `pci_dss_10.2.4` notation
+    pub fn compliance_tags(&self) -> Vec<String> {
+        Standard::ALL
+            .iter()
+            .flat_map(|standard| {
+                self.requirements(*standard)
+                    .iter()
+                    .map(move |req| format!("{}_{}", standard.field(), req))
+            })
+            .collect()
+    }
+}
+
+/// Decoder definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Decoder {
+    pub name: String,
+    /// Position of the definition among those sharing the name
+    #[serde(default)]
+    pub position: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relative_dirname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// Options of the definition (`parent`, `prematch`, `regex`, `order`, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+/// Decoder, rule or CDB list file of the ruleset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RulesetFile {
+    pub filename: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relative_dirname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+/// CDB list with its entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdbList {
+    pub filename: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relative_dirname: Option<String>,
+    #[serde(default)]
+    pub items: Vec<CdbListItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdbListItem {
+    pub key: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+/// File or registry entry monitored by syscheck
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FimEntry {
+    pub file: String,
+    #[serde(rename = "type", default)]
+    pub entry_type: Option<String>,
+    #[serde(default)]
+    pub mtime: Option<String>,
+    /// When the entry was last scanned
+    #[serde(default)]
+    pub date: Option<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub perm: Option<String>,
+    #[serde(default)]
+    pub uname: Option<String>,
+    #[serde(default)]
+    pub gname: Option<String>,
+    #[serde(default)]
+    pub md5: Option<String>,
+    #[serde(default)]
+    pub sha1: Option<String>,
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// Number of changes seen since the entry was first scanned
+    #[serde(default)]
+    pub changes: Option<u64>,
+}
+
+/// Request parameters for syscheck queries
+#[derive(Debug, Default, Serialize)]
+pub struct FimParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+
+    /// Matched against the MD5, SHA1 and SHA256 checksums
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub entry_type: Option<String>,
+}
+
+/// Policy or rootkit finding reported by rootcheck
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RootcheckFinding {
+    #[serde(default)]
+    pub status: Option<String>,
+    pub log: String,
+    #[serde(default)]
+    pub date_first: Option<String>,
+    #[serde(default)]
+    pub date_last: Option<String>,
+    #[serde(default)]
+    pub pci_dss: Option<String>,
+    #[serde(default)]
+    pub cis: Option<String>,
+}
+
+/// Request parameters for rootcheck queries
+#[derive(Debug, Default, Serialize)]
+pub struct RootcheckParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pci_dss: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cis: Option<String>,
+}
+
+/// Task the manager runs for an agent, such as an upgrade
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Task {
+    pub task_id: u32,
+    pub agent_id: String,
+    #[serde(default)]
+    pub node: String,
+    #[serde(default)]
+    pub module: String,
+    #[serde(default)]
+    pub command: String,
+    /// In queue, In progress, Done, Failed, Cancelled, Timeout or Legacy
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    pub create_time: Option<String>,
+    pub last_update_time: Option<String>,
+}
+
+impl Task {
+    /// Whether the task can no longer change state
+    pub fn is_finished(&self) -> bool {
+        !matches!(self.status.as_str(), "In queue" | "In progress")
+    }
+}
+
+/// Request parameters for task queries
+#[derive(Debug, Default, Serialize)]
+pub struct TaskParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tasks_list: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agents_list: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+/// Start and end of an agent's most recent syscheck or rootcheck scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastScan {
+    pub start: Option<String>,
+    pub end: Option<String>,
+}
+
+/// Vulnerability detected in a package of an agent
+#[derive(Debug, Clone, Serialize)]
+pub struct Vulnerability {
+    pub cve: String,
+    pub severity: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+}
+
+/// Vulnerabilities of one agent per severity, most severe first
+#[derive(Debug, Clone, Serialize)]
+pub struct VulnerabilitySummary {
+    pub agent_id: String,
+    pub total: u64,
+    pub severities: Vec<(String, u64)>,
+}
+
+/// Request parameters for decoder queries
+#[derive(Debug, Default, Serialize)]
+pub struct DecoderParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decoder_names: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+}
+
+/// Request parameters for rule queries
+#[derive(Debug, Default, Serialize)]
+pub struct RuleParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_ids: Option<String>,
+
+    /// Compliance filter, e.g. `("pci_dss", "10.2.4")`
+    #[serde(skip)]
+    pub compliance: Option<(String, String)>,
+}
+
+impl RuleParams {
+    /// Encode as a query string, including the compliance parameter
+    pub fn to_query_string(&self) -> Result<String, serde_urlencoded::ser::Error> {
+        let mut query = serde_urlencoded::to_string(self)?;
+        if let Some((param, value)) = &self.compliance {
+            let extra = serde_urlencoded::to_string([(param, value)])?;
+            if !query.is_empty() {
+                query.push('&');
+            }
+            query.push_str(&extra);
         }
+        Ok(query)
     }
 }
 
+/// Alerts matched for a single compliance requirement
+#[derive(Debug, Serialize)]
+pub struct ComplianceRequirementSummary {
+    pub requirement: String,
+    pub alerts: u64,
+    pub agents: u64,
+    pub rules: u64,
+    pub max_level: u32,
+}
+
+/// Compliance report over a time window
+#[derive(Debug, Serialize)]
+pub struct ComplianceReport {
+    pub standard: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub total_alerts: u64,
+    pub requirements: Vec<ComplianceRequirementSummary>,
+}
+
+/// Alerts counted for one value of the grouping field
+#[derive(Debug, Serialize)]
+pub struct AlertStatsBucket {
+    pub key: String,
+    /// Rule description or agent ID, when grouping by rule or agent name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub alerts: u64,
+}
+
+/// Alerts over a time window, ranked by a grouping field
+#[derive(Debug, Serialize)]
+pub struct AlertStats {
+    pub group_by: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub total_alerts: u64,
+    pub buckets: Vec<AlertStatsBucket>,
+}
+
+/// Registrations and removals within one period
+#[derive(Debug, Serialize)]
+pub struct FleetGrowthPeriod {
+    pub period: String,
+    pub registered: u64,
+    pub removed: u64,
+    pub net: i64,
+    pub cumulative: i64,
+}
+
+/// Fleet growth over time
+#[derive(Debug, Serialize)]
+pub struct FleetGrowthReport {
+    pub group_by: String,
+    pub current_agents: u32,
+    pub periods: Vec<FleetGrowthPeriod>,
+}
+
+/// Wazuh API user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiUser {
+    pub id: u32,
+    pub username: String,
+    #[serde(default)]
+    pub allow_run_as: bool,
+    /// IDs of the roles assigned to the user
+    #[serde(default)]
+    pub roles: Vec<u32>,
+}
+
+/// RBAC role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiRole {
+    pub id: u32,
+    pub name: String,
+    /// IDs of the policies linked to the role
+    #[serde(default)]
+    pub policies: Vec<u32>,
+    /// IDs of the users holding the role
+    #[serde(default)]
+    pub users: Vec<u32>,
+}
+
+/// RBAC policy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiPolicy {
+    pub id: u32,
+    pub name: String,
+    pub policy: PolicyDocument,
+    /// IDs of the roles the policy is linked to
+    #[serde(default)]
+    pub roles: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyDocument {
+    pub actions: Vec<String>,
+    pub resources: Vec<String>,
+    pub effect: String,
+}
+
 /// Request body for adding a new agent
 #[derive(Debug, Serialize)]
 pub struct AddAgentRequest {
@@ -225,6 +815,174 @@ pub struct AddAgentRequest {
 }
 
 
+/// Agent group as listed by `/groups`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentGroup {
+    pub name: String,
+    #[serde(default)]
+    pub count: u32,
+    /// Checksum of the group's agent.conf
+    #[serde(rename = "configSum", default)]
+    pub config_sum: Option<String>,
+    /// Checksum of the merged files shared with the group's agents
+    #[serde(rename = "mergedSum", default)]
+    pub merged_sum: Option<String>,
+}
+
+/// Outcome of a pattern or selector `agent group assign` for one agent
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupAssignment {
+    pub agent_id: String,
+    pub agent_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    pub groups: Vec<String>,
+    pub status: AssignmentStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AssignmentStatus {
+    /// Would be assigned (dry run or awaiting confirmation)
+    Planned,
+    AlreadyMember,
+    Assigned,
+    Failed,
+}
+
+/// Retention compliance of one indexer index
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexRetention {
+    pub index: String,
+    pub created: Option<DateTime<Utc>>,
+    pub age_days: Option<i64>,
+    /// ISM policy attached to the index
+    pub policy_id: Option<String>,
+    /// Index age at which the policy deletes it
+    pub delete_after_days: Option<i64>,
+    pub violations: Vec<RetentionViolation>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionViolation {
+    /// No ISM policy manages the index
+    NoPolicy,
+    /// The attached policy does not exist
+    PolicyMissing,
+    /// The policy deletes the index before the required retention
+    DeletedTooEarly,
+    /// The policy keeps the index past the maximum age, or never deletes it
+    KeptTooLong,
+    /// The index is older than the maximum age
+    Overdue,
+}
+
+impl std::fmt::Display for RetentionViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RetentionViolation::NoPolicy => write!(f, "no ISM policy"),
+            RetentionViolation::PolicyMissing => write!(f, "policy not found"),
+            RetentionViolation::DeletedTooEarly => write!(f, "deleted too early"),
+            RetentionViolation::KeptTooLong => write!(f, "kept too long"),
+            RetentionViolation::Overdue => write!(f, "past maximum age"),
+        }
+    }
+}
+
+/// SCA results of one policy across the fleet
+#[derive(Debug, Clone, Serialize)]
+pub struct ScaPolicySummary {
+    pub policy_id: String,
+    pub name: String,
+    pub agents: usize,
+    pub average_score: f64,
+}
+
+/// An SCA check and the agents failing it
+#[derive(Debug, Clone, Serialize)]
+pub struct ScaCheckSummary {
+    pub policy_id: String,
+    pub check_id: String,
+    pub title: String,
+    pub failing_agents: Vec<String>,
+}
+
+/// Fleet-wide SCA panel, policies by name and checks by failing agents
+#[derive(Debug, Clone, Serialize)]
+pub struct ScaSummary {
+    pub timestamp: DateTime<Utc>,
+    pub agents_scanned: usize,
+    pub policies: Vec<ScaPolicySummary>,
+    pub checks: Vec<ScaCheckSummary>,
+}
+
+/// Outcome of `agent run` on one agent
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentRunResult {
+    pub agent_id: String,
+    pub agent_name: String,
+    pub status: RunStatus,
+    /// Active-response log lines reported back by the agent
+    pub output: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// Not active, so the command was not sent
+    Skipped,
+    /// Rejected by the manager
+    Failed,
+    /// Accepted by the manager, without waiting for the result
+    Sent,
+    /// Sent, but no result arrived before the timeout
+    TimedOut,
+    /// Sent, but the indexer could not be read for its result
+    Unknown,
+    Completed,
+}
+
+/// Enrollment key of one agent, as listed by `agent key --group/--os/--query`
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentKeyResult {
+    pub agent_id: String,
+    pub agent_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcome of a bulk restart, upgrade or removal on one agent
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkResult {
+    pub agent_id: String,
+    /// Empty for IDs the manager does not know
+    pub agent_name: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Active-response command defined in a `<command>` block of the manager configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveResponseCommand {
+    pub name: String,
+    #[serde(default)]
+    pub executable: String,
+    /// `yes` when the action is reverted after the active response's timeout
+    #[serde(default)]
+    pub timeout_allowed: String,
+    /// Whether active_response.allowed_commands lets `agent run` trigger it
+    #[serde(default)]
+    pub allowed: bool,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -243,4 +1001,18 @@ mod tests {
         assert_eq!(ServiceStatus::Stopped.to_string(), "Stopped");
         assert_eq!(ServiceStatus::Unknown.to_string(), "Unknown");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_rule_params_query_string() {
+        let params = RuleParams {
+            search: Some("ssh login".to_string()),
+            filename: Some("0095-sshd_rules.xml".to_string()),
+            compliance: Some(("pci_dss".to_string(), "10.2.4".to_string())),
+            ..Default::default()
+        };
+        assert_eq!(
+            params.to_query_string().unwrap(),
+            "search=ssh+login&filename=0095-sshd_rules.xml&pci_dss=10.2.4"
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::process::Command;
+use tracing::{debug, warn};
+
+/// Where to deliver alert notifications raised by the CLI
+#[derive(Debug, Clone, Default)]
+pub struct Notifier {
+    webhook: Option<String>,
+    desktop: bool,
+    client: reqwest::Client,
+}
+
+impl Notifier {
+    pub fn new(webhook: Option<String>, desktop: bool) -> Self {
+        Self {
+            webhook,
+            desktop,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Deliver a notification to every configured sink; failures are logged, not fatal
+    pub async fn send(&self, title: &str, message: &str) {
+        if let Some(url) = &self.webhook {
+            if let Err(e) = self.send_webhook(url, title, message).await {
+                warn!("Webhook notification failed: {}", e);
+            }
+        }
+
+        if self.desktop {
+            if let Err(e) = send_desktop(title, message) {
+                warn!("Desktop notification failed: {}", e);
+            }
+        }
+    }
+
+    async fn send_webhook(&self, url: &str, title: &str, message: &str) -> Result<()> {
+        debug!("POST {} ({})", url, title);
+        // `text` keeps the payload compatible with Slack/Mattermost incoming webhooks
+        let body = json!({
+            "title": title,
+            "message": message,
+            "text": format!("{}: {}", title, message),
+        });
+
+        self.client
+            .post(url)
+            .json(&body)
+            .send()
+            .await
+            .context("Failed to send webhook")?
+            .error_for_status()
+            .context("Webhook returned an error status")?;
+
+        Ok(())
+    }
+}
+
+fn send_desktop(title: &str, message: &str) -> Result<()> {
+    let status = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {:?} with title {:?}",
+            message, title
+        );
+        Command::new("osascript").arg("-e").arg(script).status()
+    } else {
+        Command::new("notify-send").arg(title).arg(message).status()
+    }
+    .context("Failed to launch desktop notifier")?;
+
+    if !status.success() {
+        anyhow::bail!("Desktop notifier exited with {}", status);
+    }
+    Ok(())
+}
@@ -0,0 +1,214 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use colored::Colorize;
+use dialoguer::{Confirm, Input, Password, Select};
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+use crate::certs;
+use crate::cli::{Cli, Commands};
+use crate::client::WazuhClient;
+use crate::config::Config;
+use crate::credentials::{self, Secret};
+use crate::output::{print_info, print_success, print_warning, spinner, WarningKind};
+
+/// How long to wait for the manager while fetching its certificate
+const CERTIFICATE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Run guided setup only for a human at a terminal with no configuration yet
+pub fn should_run(cli: &Cli) -> Result<bool> {
+    if cli.no_prompt || cli.version || matches!(cli.command, Some(Commands::Config(_))) {
+        return Ok(false);
+    }
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return Ok(false);
+    }
+
+    Ok(!Config::resolve_path(&cli.config)?.exists())
+}
+
+/// Ask for the connection details, test them and save the configuration
+pub async fn run(config_path: &Path) -> Result<Config> {
+    let path = Config::resolve_path(config_path)?;
+
+    println!("{}", "Welcome to wazuh-cli".bold().underline());
+    println!();
+    println!("No configuration found at {}.", path.display());
+    println!("Let's set up the connection to your Wazuh manager (Ctrl+C to abort).");
+    println!();
+
+    let mut config = Config::default();
+    config.api.host = Input::new()
+        .with_prompt("Manager host")
+        .default(config.api.host.clone())
+        .interact_text()?;
+    config.api.port = Input::new()
+        .with_prompt("API port")
+        .default(config.api.port)
+        .interact_text()?;
+    let https = Confirm::new()
+        .with_prompt("Use HTTPS?")
+        .default(true)
+        .interact()?;
+    config.api.protocol = if https { "https" } else { "http" }.to_string();
+
+    config.auth.username = Some(
+        Input::new()
+            .with_prompt("API username")
+            .default("wazuh".to_string())
+            .interact_text()?,
+    );
+    config.auth.password = Some(Password::new().with_prompt("API password").interact()?);
+
+    if https {
+        choose_tls_trust(&mut config, &path).await?;
+    }
+
+    loop {
+        let pb = spinner("Testing connection...");
+        let result = test_connection(&config).await;
+        pb.finish_and_clear();
+
+        match result {
+            Ok(()) => {
+                print_success(&format!("Connected to {}", config.api_url()));
+                break;
+            }
+            Err(e) => {
+                print_warning(
+                    WarningKind::PartialFailure,
+                    &format!("Connection test failed: {:#}", e),
+                );
+                let choice = Select::new()
+                    .with_prompt("What now?")
+                    .items(&["Retry", "Save anyway", "Abort"])
+                    .default(0)
+                    .interact()?;
+                match choice {
+                    0 => continue,
+                    1 => break,
+                    _ => return Err(anyhow!("Setup aborted; nothing was saved")),
+                }
+            }
+        }
+    }
+
+    let name: String = Input::new()
+        .with_prompt("Profile name (optional)")
+        .allow_empty(true)
+        .interact_text()?;
+    config.name = (!name.trim().is_empty()).then(|| name.trim().to_string());
+
+    // The password goes to the credential store when there is one
+    let mut saved = config.clone();
+    if config.auth.keyring {
+        let password = config.auth.password.as_deref().unwrap_or_default();
+        match credentials::store(&config, Secret::Password, password) {
+            Ok(()) => saved.auth.password = None,
+            Err(e) => print_warning(
+                WarningKind::Fallback,
+                &format!("Password saved in the configuration file instead: {:#}", e),
+            ),
+        }
+    }
+    saved.save(&path)?;
+    print_success(&format!("Configuration saved to {}", path.display()));
+    println!();
+
+    Ok(config)
+}
+
+/// Show the manager's certificate and let the user decide how to trust it
+async fn choose_tls_trust(config: &mut Config, config_path: &Path) -> Result<()> {
+    let url = config.api_url();
+    let chain = match certs::fetch_peer_chain(&url, CERTIFICATE_TIMEOUT).await {
+        Ok(chain) => chain,
+        Err(e) => {
+            print_warning(
+                WarningKind::Fallback,
+                &format!("Could not fetch the manager certificate: {:#}", e),
+            );
+            return Ok(());
+        }
+    };
+    let der = &chain[0];
+
+    let status = certs::inspect_der(&url, der, Utc::now(), 30);
+    println!();
+    println!("{}", "Manager certificate".bold());
+    println!("  Subject: {}", status.subject.as_deref().unwrap_or("N/A"));
+    println!("  Issuer: {}", status.issuer.as_deref().unwrap_or("N/A"));
+    if let Some(not_after) = status.not_after {
+        println!("  Expires: {}", not_after.format("%Y-%m-%d"));
+    }
+    println!("  SHA-256: {}", certs::fingerprint(der));
+    for issue in &status.issues {
+        println!("  {} {}", "!".yellow().bold(), issue);
+    }
+
+    // Trusting the leaf alone fails unless it is self-signed, so the issuer is saved
+    let anchor = certs::trust_anchor(&chain);
+    if let Some(anchor) = anchor.filter(|anchor| *anchor != der.as_slice()) {
+        let issuer = certs::inspect_der(&url, anchor, Utc::now(), 30);
+        println!();
+        println!("{}", "Issuing CA".bold());
+        println!("  Subject: {}", issuer.subject.as_deref().unwrap_or("N/A"));
+        println!("  SHA-256: {}", certs::fingerprint(anchor));
+    }
+    println!();
+    print_info("Compare the fingerprints with the manager's before trusting them");
+
+    let mut items = vec!["Verify with the system trust store"];
+    match anchor {
+        Some(anchor) if anchor == der.as_slice() => {
+            items.push("Trust this self-signed certificate (saved next to the configuration)")
+        }
+        Some(_) => items.push("Trust the issuing CA (saved next to the configuration)"),
+        None => print_warning(
+            WarningKind::Fallback,
+            "The manager did not send the CA that signed its certificate; to trust it, \
+             point tls.ca_cert at that CA after setup",
+        ),
+    }
+    items.push("Do not verify certificates (not recommended)");
+
+    let choice = Select::new()
+        .with_prompt("How should this certificate be trusted?")
+        .items(&items)
+        .default(0)
+        .interact()?;
+
+    match (choice, anchor) {
+        (0, _) => {}
+        (1, Some(anchor)) => {
+            let pem_path = trusted_cert_path(config_path, &config.api.host);
+            if let Some(parent) = pem_path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {:?}", parent))?;
+            }
+            fs::write(&pem_path, certs::der_to_pem(anchor))
+                .with_context(|| format!("Failed to write {:?}", pem_path))?;
+            config.tls.ca_cert = Some(pem_path);
+        }
+        _ => config.tls.verify = false,
+    }
+
+    Ok(())
+}
+
+fn trusted_cert_path(config_path: &Path, host: &str) -> PathBuf {
+    let file = format!("{}-ca.pem", host.replace([':', '/'], "_"));
+    config_path
+        .parent()
+        .map(|dir| dir.join(&file))
+        .unwrap_or_else(|| PathBuf::from(file))
+}
+
+async fn test_connection(config: &Config) -> Result<()> {
+    let client = WazuhClient::new(Arc::new(RwLock::new(config.clone()))).await?;
+    client.authenticate().await
+}
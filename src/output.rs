@@ -2,33 +2,555 @@ use anyhow::Result;
 use chrono::{DateTime, Utc};
 use colored::Colorize;
 use comfy_table::{Cell, Color, ContentArrangement, Table};
+use indicatif::{ProgressBar, ProgressStyle};
 use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::collections::BTreeMap;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tracing::warn;
 
-use crate::models::{Agent, AgentStatus, Service, ServiceStatus};
+use crate::changes::{ChangeStatus, StagedChange};
+use crate::checkins::{Anomaly, AnomalyKind};
+use crate::config::{ProfileSummary, CURRENT_FORMAT_VERSION};
+use crate::diff;
+use crate::disconnect::DisconnectReport;
+use crate::doctor::{Check, CheckStatus};
+use crate::drift::{DriftEntry, DriftKind};
+use crate::error::WazuhError;
+use crate::maintenance::{MaintenanceWindow, StepOutcome};
+use crate::expr::Expr;
+use crate::join::JoinedColumns;
+use crate::lock::LockStatus;
+use crate::template::Template;
+use crate::progress;
+use crate::redirect;
+use crate::usage::UsageSummary;
+use crate::models::{
+    ActiveResponseCommand, Agent, AgentGroup, AgentHotfixes, AgentKeyResult, AgentRunResult,
+    AgentStatus, AgentSummary, AlertStats, ApiPolicy, ApiRole, ApiUser, AssignmentStatus,
+    BulkResult, CdbList, CdbListItem, CertificateStatus, ComplianceReport, Decoder, FimEntry,
+    FleetGrowthReport, GroupAssignment, IndexRetention, LimitLevel, LimitsReport, ManagerLog,
+    ManagerLogSummary, NodeHealth, NodeSyncStatus, Rule, RootcheckFinding, RulesetFile, RunStatus,
+    ScaSummary, Service, ServiceStatus, Task, VersionMatrix, Vulnerability, VulnerabilitySummary,
+};
+use crate::utils::{json_field, json_to_text};
+
+/// Screen-reader friendly output: no tables, spinners, colors or symbols
+static ACCESSIBLE: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable accessible output for the rest of the process
+pub fn set_accessible(enabled: bool) {
+    ACCESSIBLE.store(enabled, Ordering::Relaxed);
+    if enabled {
+        colored::control::set_override(false);
+    }
+}
+
+fn accessible() -> bool {
+    ACCESSIBLE.load(Ordering::Relaxed)
+}
+
+/// ANSI colors in text, tables and spinners
+static COLOR: AtomicBool = AtomicBool::new(true);
+
+/// Whether stdout is a terminal, as opposed to a pipe or a file
+static STDOUT_TERMINAL: AtomicBool = AtomicBool::new(true);
+
+/// Decide once how output is presented: when stdout is piped or redirected
+/// there are no spinners and no colors, unless a pager is showing the colors
+pub fn init_terminal(color: bool) {
+    let terminal = std::io::stdout().is_terminal();
+    STDOUT_TERMINAL.store(terminal, Ordering::Relaxed);
+    set_color(color && (terminal || redirect::pager_width().is_some()));
+}
+
+pub fn stdout_is_terminal() -> bool {
+    STDOUT_TERMINAL.load(Ordering::Relaxed)
+}
+
+/// Enable or disable colors for the rest of the process
+fn set_color(enabled: bool) {
+    COLOR.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        colored::control::set_override(false);
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+}
+
+/// Whether colors are on: not disabled by `--no-color`, `output.color = false`
+/// or a non-empty `NO_COLOR` (https://no-color.org)
+pub fn color_enabled(flag: bool, config: bool) -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    !flag && config && !no_color
+}
+
+/// A field renamed in format version `since`; older versions keep seeing `old`
+struct FieldRename {
+    since: u32,
+    old: &'static str,
+    new: &'static str,
+}
+
+/// Compatibility shims for every breaking change to JSON field names.
+///
+/// Adding a field is not breaking. Renaming one needs an entry here and a
+/// `CURRENT_FORMAT_VERSION` bump, so scripts pinned to an older version keep working.
+const FIELD_RENAMES: &[FieldRename] = &[];
+
+/// Format version JSON output is rendered in
+static FORMAT_VERSION: AtomicU32 = AtomicU32::new(CURRENT_FORMAT_VERSION);
+
+/// Select the JSON output format version for the rest of the process
+pub fn set_format_version(version: u32) -> Result<(), WazuhError> {
+    if !(1..=CURRENT_FORMAT_VERSION).contains(&version) {
+        return Err(WazuhError::InvalidInput(format!(
+            "Unsupported output format version {}: this release supports 1 to {}",
+            version, CURRENT_FORMAT_VERSION
+        )));
+    }
+    FORMAT_VERSION.store(version, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Rename fields back to the names they had in format `version`
+fn downgrade(value: &mut serde_json::Value, version: u32, renames: &[FieldRename]) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for rename in renames.iter().rev().filter(|r| r.since > version) {
+                if let Some(field) = map.remove(rename.new) {
+                    map.insert(rename.old.to_string(), field);
+                }
+            }
+            for field in map.values_mut() {
+                downgrade(field, version, renames);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                downgrade(item, version, renames);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collect JSON output and warnings into one `{data, warnings}` document
+static ENVELOPE: AtomicBool = AtomicBool::new(false);
+
+/// Documents passed to `print_json` while the envelope is enabled
+static ENVELOPE_DATA: Mutex<Vec<serde_json::Value>> = Mutex::new(Vec::new());
+
+/// Warnings raised so far, for the envelope
+static WARNINGS: Mutex<Vec<Warning>> = Mutex::new(Vec::new());
+
+/// Enable or disable the JSON envelope for the rest of the process
+pub fn set_envelope(enabled: bool) {
+    ENVELOPE.store(enabled, Ordering::Relaxed);
+}
+
+fn envelope() -> bool {
+    ENVELOPE.load(Ordering::Relaxed)
+}
+
+/// Print JSON arrays as one compact document per line
+static NDJSON: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable NDJSON output for the rest of the process
+pub fn set_ndjson(enabled: bool) {
+    NDJSON.store(enabled, Ordering::Relaxed);
+}
+
+/// Template rendered once per item instead of printing JSON, from `--template`
+static TEMPLATE: Mutex<Option<Template>> = Mutex::new(None);
+
+/// Render JSON output through a template for the rest of the process (or line)
+pub fn set_template(source: Option<&str>) -> Result<(), WazuhError> {
+    let template = source.map(Template::parse).transpose()?;
+    if let Ok(mut current) = TEMPLATE.lock() {
+        *current = template;
+    }
+    Ok(())
+}
+
+/// Whether JSON output is written one line per item (NDJSON or a template),
+/// so items can be printed as they arrive instead of as one document
+pub fn line_per_item() -> bool {
+    !envelope()
+        && (NDJSON.load(Ordering::Relaxed) || TEMPLATE.lock().is_ok_and(|t| t.is_some()))
+}
+
+/// One rendered line per array item, or a single line for any other document
+fn template_lines(template: &Template, value: &serde_json::Value) -> Result<Vec<String>> {
+    let now = chrono::Utc::now();
+    let items = match value {
+        serde_json::Value::Array(items) => items.iter().collect(),
+        value => vec![value],
+    };
+    Ok(items
+        .into_iter()
+        .map(|item| template.render(item, now))
+        .collect::<Result<_, _>>()?)
+}
+
+/// One compact line per array item, or a single line for any other document
+fn ndjson_lines(value: &serde_json::Value) -> Result<Vec<String>> {
+    match value {
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(|item| Ok(serde_json::to_string(item)?))
+            .collect(),
+        value => Ok(vec![serde_json::to_string(value)?]),
+    }
+}
+
+/// What went wrong without stopping the command
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    /// Some items failed while the rest succeeded
+    PartialFailure,
+    /// Degraded data or behavior was used instead, e.g. a cached result
+    Fallback,
+    /// The requested operation is not available on this manager
+    Unsupported,
+    /// An API response took longer than its latency budget
+    SlowResponse,
+    /// Local files or settings expose credentials, e.g. a world-readable config file
+    Insecure,
+}
+
+/// A warning as written to stderr or into the envelope
+#[derive(Debug, Clone, Serialize)]
+pub struct Warning {
+    pub timestamp: DateTime<Utc>,
+    pub kind: WarningKind,
+    pub message: String,
+}
+
+/// Print the envelope collected during the run; a no-op unless it is enabled
+pub fn finish_envelope(error: Option<&anyhow::Error>) -> Result<()> {
+    if !envelope() {
+        return Ok(());
+    }
+
+    let data = std::mem::take(&mut *ENVELOPE_DATA.lock().unwrap_or_else(|e| e.into_inner()));
+    let warnings = std::mem::take(&mut *WARNINGS.lock().unwrap_or_else(|e| e.into_inner()));
+    let document = envelope_document(data, &warnings, error);
+    println!("{}", serde_json::to_string_pretty(&document)?);
+    Ok(())
+}
+
+fn envelope_document(
+    mut data: Vec<serde_json::Value>,
+    warnings: &[Warning],
+    error: Option<&anyhow::Error>,
+) -> serde_json::Value {
+    serde_json::json!({
+        "format_version": FORMAT_VERSION.load(Ordering::Relaxed),
+        // Commands printing several documents get them as an array
+        "data": if data.len() == 1 { data.remove(0) } else { serde_json::Value::from(data) },
+        "warnings": warnings,
+        "error": error.map(|e| format!("{:#}", e)),
+    })
+}
+
+/// User-defined computed columns, keyed by table name
+static COMPUTED_COLUMNS: OnceLock<BTreeMap<String, Vec<(String, Expr)>>> = OnceLock::new();
+
+/// Parse the `[output.columns.<table>]` expressions from the configuration
+pub fn set_computed_columns(columns: &BTreeMap<String, toml::Table>) -> Result<()> {
+    let mut parsed = BTreeMap::new();
+    for (table, definitions) in columns {
+        let mut exprs = Vec::new();
+        for (name, definition) in definitions {
+            let source = definition.as_str().ok_or_else(|| {
+                WazuhError::InvalidInput(format!(
+                    "Computed column output.columns.{}.{} must be a string expression",
+                    table, name
+                ))
+            })?;
+            let expr = Expr::parse(source).map_err(|e| {
+                WazuhError::InvalidInput(format!("output.columns.{}.{}: {}", table, name, e))
+            })?;
+            exprs.push((name.clone(), expr));
+        }
+        parsed.insert(table.clone(), exprs);
+    }
+    let _ = COMPUTED_COLUMNS.set(parsed);
+    Ok(())
+}
+
+fn computed_columns(table: &str) -> &'static [(String, Expr)] {
+    COMPUTED_COLUMNS
+        .get()
+        .and_then(|columns| columns.get(table))
+        .map(Vec::as_slice)
+        .unwrap_or(&[])
+}
+
+/// Evaluate the computed columns of a table against one serialized row
+fn computed_cells<T: Serialize>(table: &str, row: &T) -> Vec<String> {
+    let columns = computed_columns(table);
+    if columns.is_empty() {
+        return Vec::new();
+    }
+
+    let doc = serde_json::to_value(row).unwrap_or_default();
+    let now = Utc::now();
+    columns
+        .iter()
+        .map(|(name, expr)| match expr.eval(&doc, now) {
+            Ok(value) => value.to_string(),
+            Err(e) => {
+                warn!("Computed column '{}' failed: {}", name, e);
+                "N/A".to_string()
+            }
+        })
+        .collect()
+}
+
+/// Most recently started spinner, used to surface client-side events such as backoff
+static ACTIVE_SPINNER: Mutex<Option<ProgressBar>> = Mutex::new(None);
+
+/// Start a spinner with the standard style
+pub fn spinner(message: &str) -> ProgressBar {
+    if accessible() || progress::json_events() || !stdout_is_terminal() {
+        return ProgressBar::hidden();
+    }
+
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(
+        ProgressStyle::default_spinner()
+            .template("{spinner:.green} {prefix:.yellow}{msg}")
+            .unwrap(),
+    );
+    pb.set_message(message.to_string());
+    pb.enable_steady_tick(Duration::from_millis(120));
+
+    if let Ok(mut active) = ACTIVE_SPINNER.lock() {
+        *active = Some(pb.clone());
+    }
+    pb
+}
+
+fn active_spinner() -> Option<ProgressBar> {
+    ACTIVE_SPINNER
+        .lock()
+        .ok()
+        .and_then(|active| active.clone())
+        .filter(|pb| !pb.is_finished() && !pb.is_hidden())
+}
+
+/// Show that requests are paused because the API is rate limiting us
+pub fn report_backoff(delay: Duration) {
+    let message = format!("rate limited, retrying in {}s", delay.as_secs().max(1));
+    match active_spinner() {
+        Some(pb) => pb.set_prefix(format!("[{}] ", message)),
+        None => print_warning(WarningKind::Fallback, &format!("API {}", message)),
+    }
+}
+
+/// Clear the backoff notice once requests go through again
+pub fn clear_backoff() {
+    if let Some(pb) = active_spinner() {
+        pb.set_prefix("");
+    }
+}
+
+/// Columns picked with `--fields`, as column keys; empty for every column
+static SELECTED_COLUMNS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Restrict printed tables to these columns, in this order
+pub fn set_selected_columns(fields: &[String]) {
+    if let Ok(mut columns) = SELECTED_COLUMNS.lock() {
+        *columns = fields.iter().map(|field| column_key(field)).collect();
+    }
+}
+
+/// Header names compared case-insensitively, with spaces as underscores
+/// (`Last Keep Alive` is `last_keep_alive`)
+fn column_key(name: &str) -> String {
+    name.trim().to_lowercase().replace([' ', '-'], "_")
+}
+
+/// Copy of `table` with only the selected columns, or `None` to print it whole
+fn select_columns(table: &Table, fields: &[String]) -> Option<Table> {
+    let keys: Vec<String> = table
+        .header()?
+        .cell_iter()
+        .map(|cell| column_key(&cell.content()))
+        .collect();
+
+    let mut indices = Vec::new();
+    for field in fields {
+        match keys.iter().position(|key| key == field) {
+            Some(index) => indices.push(index),
+            None => print_warning(
+                WarningKind::Unsupported,
+                &format!(
+                    "No '{}' column in this table; available: {}",
+                    field,
+                    keys.iter().filter(|k| !k.is_empty()).cloned().collect::<Vec<_>>().join(",")
+                ),
+            ),
+        }
+    }
+    if indices.is_empty() {
+        return None;
+    }
+
+    let pick = |row: &comfy_table::Row| -> Vec<Cell> {
+        let cells: Vec<&Cell> = row.cell_iter().collect();
+        indices
+            .iter()
+            .map(|&i| cells.get(i).map(|&c| c.clone()).unwrap_or_else(|| Cell::new("")))
+            .collect()
+    };
+
+    let mut selected = Table::new();
+    selected.set_content_arrangement(table.content_arrangement());
+    if let Some(header) = table.header() {
+        selected.set_header(pick(header));
+    }
+    for row in table.row_iter() {
+        selected.add_row(pick(row));
+    }
+    Some(selected)
+}
+
+/// Print a table, or one labeled line per row in accessible mode
+pub fn print_table(table: &Table, subject: &str) {
+    let fields = SELECTED_COLUMNS.lock().map(|c| c.clone()).unwrap_or_default();
+    let mut selected = if fields.is_empty() { None } else { select_columns(table, &fields) };
+
+    // Behind a pager stdout is a pipe, so the layout follows the pager's terminal instead
+    if let Some(width) = redirect::pager_width() {
+        let paged = selected.get_or_insert_with(|| table.clone());
+        paged.set_width(width);
+        if colored::control::SHOULD_COLORIZE.should_colorize() {
+            paged.enforce_styling();
+        }
+    } else if !COLOR.load(Ordering::Relaxed) && stdout_is_terminal() {
+        // comfy-table styles whenever stdout is a terminal, and measures the
+        // terminal only while it does; keep the width without the styling
+        let plain = selected.get_or_insert_with(|| table.clone());
+        plain.force_no_tty();
+        if let Some((_, width)) = console::Term::stdout().size_checked() {
+            plain.set_width(width);
+        }
+    }
+    let table = selected.as_ref().unwrap_or(table);
+
+    if !accessible() {
+        println!("{table}");
+        return;
+    }
+
+    for record in table_records(table, subject) {
+        println!("{}", record);
+    }
+}
+
+/// Render table rows as `Agent 003: name=web-01, status=Active, ...`,
+/// using the first column as the record identifier
+fn table_records(table: &Table, subject: &str) -> Vec<String> {
+    let headers: Vec<String> = table
+        .header()
+        .map(|row| row.cell_iter().map(|c| c.content().to_lowercase()).collect())
+        .unwrap_or_default();
+
+    table
+        .row_iter()
+        .map(|row| {
+            let mut cells = row.cell_iter().map(|c| c.content());
+            let id = cells.next().unwrap_or_default();
+            let fields: Vec<String> = headers
+                .iter()
+                .skip(1)
+                .zip(cells)
+                .filter(|(header, _)| !header.is_empty())
+                .map(|(header, value)| format!("{}={}", header.replace(' ', "_"), value))
+                .collect();
+            format!("{} {}: {}", subject, id, fields.join(", "))
+        })
+        .collect()
+}
 
 /// Print data as JSON
 pub fn print_json<T: Serialize>(data: &T) -> Result<()> {
-    let json = serde_json::to_string_pretty(data)?;
+    let version = FORMAT_VERSION.load(Ordering::Relaxed);
+    if envelope() {
+        let mut value = serde_json::to_value(data)?;
+        downgrade(&mut value, version, FIELD_RENAMES);
+        if let Ok(mut documents) = ENVELOPE_DATA.lock() {
+            documents.push(value);
+        }
+        return Ok(());
+    }
+
+    let template = TEMPLATE.lock().ok().and_then(|t| t.clone());
+    if let Some(template) = template {
+        let mut value = serde_json::to_value(data)?;
+        downgrade(&mut value, version, FIELD_RENAMES);
+        for line in template_lines(&template, &value)? {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+
+    if NDJSON.load(Ordering::Relaxed) {
+        let mut value = serde_json::to_value(data)?;
+        downgrade(&mut value, version, FIELD_RENAMES);
+        for line in ndjson_lines(&value)? {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+
+    let json = if version < CURRENT_FORMAT_VERSION {
+        let mut value = serde_json::to_value(data)?;
+        downgrade(&mut value, version, FIELD_RENAMES);
+        serde_json::to_string_pretty(&value)?
+    } else {
+        serde_json::to_string_pretty(data)?
+    };
     println!("{}", json);
     Ok(())
 }
 
 /// Print agents in a formatted table
-pub fn print_agents_table(agents: &[Agent]) {
+pub fn print_agents_table(agents: &[Agent], joined: &JoinedColumns) {
+    let mut header = vec![
+        Cell::new("ID").add_attribute(comfy_table::Attribute::Bold),
+        Cell::new("Name").add_attribute(comfy_table::Attribute::Bold),
+        Cell::new("IP").add_attribute(comfy_table::Attribute::Bold),
+        Cell::new("Status").add_attribute(comfy_table::Attribute::Bold),
+        Cell::new("Version").add_attribute(comfy_table::Attribute::Bold),
+        Cell::new("OS").add_attribute(comfy_table::Attribute::Bold),
+        Cell::new("Last Keep Alive").add_attribute(comfy_table::Attribute::Bold),
+    ];
+    header.extend(
+        computed_columns("agents")
+            .iter()
+            .map(|(name, _)| Cell::new(name).add_attribute(comfy_table::Attribute::Bold)),
+    );
+    header.extend(
+        joined
+            .columns
+            .iter()
+            .map(|name| Cell::new(name).add_attribute(comfy_table::Attribute::Bold)),
+    );
+
     let mut table = Table::new();
     table
         .set_content_arrangement(ContentArrangement::Dynamic)
-        .set_header(vec![
-            Cell::new("ID").add_attribute(comfy_table::Attribute::Bold),
-            Cell::new("Name").add_attribute(comfy_table::Attribute::Bold),
-            Cell::new("IP").add_attribute(comfy_table::Attribute::Bold),
-            Cell::new("Status").add_attribute(comfy_table::Attribute::Bold),
-            Cell::new("Version").add_attribute(comfy_table::Attribute::Bold),
-            Cell::new("OS").add_attribute(comfy_table::Attribute::Bold),
-            Cell::new("Last Keep Alive").add_attribute(comfy_table::Attribute::Bold),
-        ]);
+        .set_header(header);
 
-    for agent in agents {
+    for (index, agent) in agents.iter().enumerate() {
         let status_cell = match agent.status {
             AgentStatus::Active => Cell::new(agent.status.to_string())
                 .fg(Color::Green)
@@ -60,7 +582,7 @@ pub fn print_agents_table(agents: &[Agent]) {
             .map(|dt| format_datetime(&dt))
             .unwrap_or_else(|| "Never".to_string());
 
-        table.add_row(vec![
+        let mut row = vec![
             Cell::new(&agent.id),
             Cell::new(&agent.name),
             Cell::new(agent.ip.as_deref().unwrap_or("N/A")),
@@ -68,10 +590,70 @@ pub fn print_agents_table(agents: &[Agent]) {
             Cell::new(agent.version.as_deref().unwrap_or("N/A")),
             Cell::new(os_info),
             Cell::new(last_keep_alive),
-        ]);
+        ];
+        row.extend(computed_cells("agents", agent).into_iter().map(Cell::new));
+        row.extend(joined.row(index).iter().map(Cell::new));
+        table.add_row(row);
+    }
+
+    print_table(&table, "Agent");
+}
+
+/// Print the morning fleet overview of `agent summary`
+pub fn print_agent_summary(summary: &AgentSummary) {
+    println!("{}", "Agent Summary".bold().underline());
+    println!();
+
+    println!("{}: {}", "Total".bold(), summary.total);
+    for (status, count) in &summary.by_status {
+        let count = match status {
+            AgentStatus::Active => count.to_string().green(),
+            AgentStatus::Disconnected if *count > 0 => count.to_string().red(),
+            AgentStatus::NeverConnected | AgentStatus::Pending if *count > 0 => {
+                count.to_string().yellow()
+            }
+            _ => count.to_string().normal(),
+        };
+        println!("  {}: {}", status.to_string().bold(), count);
+    }
+    if let Some(synced) = summary.config_synced {
+        println!("{}: {} of {}", "Configuration Synced".bold(), synced, summary.total);
+    }
+    // The manager is listed as agent 000 but does not count against the limit
+    let registered = summary.total.saturating_sub(1);
+    match summary.max_agents.filter(|max| *max > 0) {
+        Some(max) => println!(
+            "{}: {} of {} agents ({:.1}%)",
+            "Capacity".bold(),
+            registered,
+            max,
+            registered as f64 * 100.0 / max as f64
+        ),
+        None => println!("{}: {} agents, no limit", "Capacity".bold(), registered),
+    }
+
+    println!("\n{}", "Top Platforms".bold());
+    for (platform, count) in summary.platforms.iter().take(5) {
+        println!("  {}: {}", platform.bold(), count);
+    }
+    if summary.platforms.len() > 5 {
+        let others: u64 = summary.platforms.iter().skip(5).map(|(_, count)| count).sum();
+        println!("  {}: {}", "others".bold(), others);
     }
 
-    println!("{table}");
+    if summary.recent.is_empty() {
+        return;
+    }
+    println!("\n{}", "Recently Registered".bold());
+    for agent in &summary.recent {
+        println!(
+            "  {} {} ({}, {})",
+            agent.id,
+            agent.name.bold(),
+            agent.date_add.map(|dt| format_datetime(&dt)).unwrap_or_else(|| "N/A".to_string()),
+            agent.status
+        );
+    }
 }
 
 /// Print a single agent with detailed information
@@ -177,35 +759,2081 @@ pub fn print_services_table(services: &[Service]) {
         ]);
     }
 
-    println!("{table}");
+    print_table(&table, "Service");
 }
 
-/// Format a DateTime for display
-fn format_datetime(dt: &DateTime<Utc>) -> String {
-    dt.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+/// Print the phases of a logtest result and whether an alert would fire
+pub fn print_logtest(result: &serde_json::Value) {
+    let output = &result["output"];
+    let text = |path: &str| json_field(output, path).map(|value| json_to_text(Some(value)));
+
+    println!("{}", "Pre-decoding".bold().underline());
+    match output["predecoder"].as_object().filter(|fields| !fields.is_empty()) {
+        Some(fields) => {
+            for (field, value) in fields {
+                println!("  {}: {}", field.bold(), json_to_text(Some(value)));
+            }
+        }
+        None => println!("  {}", "no header parsed".dimmed()),
+    }
+
+    println!("\n{}", "Decoding".bold().underline());
+    match text("decoder.name") {
+        Some(name) => {
+            println!("  {}: {}", "decoder".bold(), name);
+            if let Some(parent) = text("decoder.parent").filter(|parent| *parent != name) {
+                println!("  {}: {}", "parent".bold(), parent);
+            }
+            if let Some(fields) = output["data"].as_object() {
+                for (field, value) in fields {
+                    println!("  {}: {}", field.bold(), json_to_text(Some(value)));
+                }
+            }
+        }
+        None => println!("  {}", "no decoder matched".dimmed()),
+    }
+
+    println!("\n{}", "Filtering".bold().underline());
+    match text("rule.id") {
+        Some(id) => {
+            println!("  {}: {}", "id".bold(), id);
+            println!("  {}: {}", "level".bold(), text("rule.level").unwrap_or_default());
+            println!(
+                "  {}: {}",
+                "description".bold(),
+                text("rule.description").unwrap_or_default()
+            );
+            if let Some(groups) = text("rule.groups") {
+                println!("  {}: {}", "groups".bold(), groups);
+            }
+        }
+        None => println!("  {}", "no rule matched".dimmed()),
+    }
+
+    println!();
+    if result["alert"].as_bool().unwrap_or(false) {
+        println!("{} Alert would be generated", "✓".green().bold());
+    } else {
+        println!("{} No alert would be generated", "✗".yellow().bold());
+    }
 }
 
-/// Print a success message
-pub fn print_success(message: &str) {
-    println!("{} {}", "✓".green().bold(), message);
+/// Print configuration converted from XML as an indented tree
+pub fn print_config_tree(value: &serde_json::Value, depth: usize) {
+    let indent = "  ".repeat(depth);
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                match value {
+                    serde_json::Value::Object(_) => {
+                        println!("{}{}:", indent, key.bold());
+                        print_config_tree(value, depth + 1);
+                    }
+                    serde_json::Value::Array(items) if items.iter().any(|i| i.is_object()) => {
+                        println!("{}{}:", indent, key.bold());
+                        print_config_tree(value, depth + 1);
+                    }
+                    _ => println!("{}{}: {}", indent, key.bold(), json_to_text(Some(value))),
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 && item.is_object() {
+                    println!();
+                }
+                match item {
+                    serde_json::Value::Object(_) => print_config_tree(item, depth),
+                    _ => println!("{}- {}", indent, json_to_text(Some(item))),
+                }
+            }
+        }
+        _ => println!("{}{}", indent, json_to_text(Some(value))),
+    }
+}
+
+/// Print a manager log line, colored by level
+pub fn print_manager_log(line: &ManagerLog) {
+    let level = match line.level.as_str() {
+        "critical" => line.level.red().bold(),
+        "error" => line.level.red(),
+        "warning" => line.level.yellow(),
+        "debug" => line.level.dimmed(),
+        _ => line.level.normal(),
+    };
+    println!(
+        "{} {} {}: {}",
+        line.timestamp.dimmed(),
+        line.tag.cyan(),
+        level,
+        line.description.trim_end()
+    );
 }
 
+/// Print manager log line counts per daemon
+pub fn print_manager_log_summary(summaries: &[ManagerLogSummary]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Daemon").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Critical").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Error").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Warning").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Info").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Debug").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("All").add_attribute(comfy_table::Attribute::Bold),
+        ]);
 
-/// Print an info message
-pub fn print_info(message: &str) {
-    println!("{} {}", "ℹ".blue().bold(), message);
+    let count_cell = |count: u64, color: Color| {
+        if count > 0 {
+            Cell::new(count).fg(color)
+        } else {
+            Cell::new(count)
+        }
+    };
+    for summary in summaries {
+        table.add_row(vec![
+            Cell::new(&summary.tag),
+            count_cell(summary.critical, Color::Red),
+            count_cell(summary.error, Color::Red),
+            count_cell(summary.warning, Color::Yellow),
+            Cell::new(summary.info),
+            Cell::new(summary.debug),
+            Cell::new(summary.all),
+        ]);
+    }
+
+    print_table(&table, "Daemon");
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::models::AgentOs;
+/// Color a cell according to rule/alert level
+fn level_cell(level: u64) -> Cell {
+    let cell = Cell::new(level);
+    match level {
+        12.. => cell.fg(Color::Red).add_attribute(comfy_table::Attribute::Bold),
+        7..=11 => cell.fg(Color::Yellow),
+        _ => cell,
+    }
+}
 
-    #[test]
-    fn test_format_datetime() {
-        let dt = DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
-            .unwrap()
-            .with_timezone(&Utc);
-        assert_eq!(format_datetime(&dt), "2024-01-01 12:00:00 UTC");
+/// Print rules in a formatted table
+pub fn print_rules_table(rules: &[Rule]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Level").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Description").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Groups").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Compliance").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for rule in rules {
+        table.add_row(vec![
+            Cell::new(rule.id),
+            level_cell(rule.level as u64),
+            Cell::new(&rule.description),
+            Cell::new(rule.groups.join(", ")),
+            Cell::new(rule.compliance_tags().join(", ")),
+        ]);
+    }
+
+    print_table(&table, "Rule");
+}
+
+/// Print a rule with its details
+pub fn print_single_rule(rule: &Rule) {
+    println!("{}", "Rule Information".bold().underline());
+    println!();
+
+    println!("{}: {}", "ID".bold(), rule.id);
+    println!("{}: {}", "Level".bold(), rule.level);
+    println!("{}: {}", "Description".bold(), rule.description);
+    if let Some(filename) = &rule.filename {
+        let dir = rule.relative_dirname.as_deref().unwrap_or("");
+        println!("{}: {}", "File".bold(), Path::new(dir).join(filename).display());
+    }
+    if let Some(status) = &rule.status {
+        println!("{}: {}", "Status".bold(), status);
     }
-}
\ No newline at end of file
+    if !rule.groups.is_empty() {
+        println!("{}: {}", "Groups".bold(), rule.groups.join(", "));
+    }
+    let compliance = rule.compliance_tags();
+    if !compliance.is_empty() {
+        println!("{}: {}", "Compliance".bold(), compliance.join(", "));
+    }
+    if !rule.mitre.is_empty() {
+        println!("{}: {}", "MITRE".bold(), rule.mitre.join(", "));
+    }
+
+    if let Some(serde_json::Value::Object(details)) = &rule.details {
+        if !details.is_empty() {
+            println!("{}: ", "Details".bold());
+            for (key, value) in details {
+                println!("  {}: {}", key.bold(), json_to_text(Some(value)));
+            }
+        }
+    }
+}
+
+/// Print decoders in a formatted table
+pub fn print_decoders_table(decoders: &[Decoder]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Name").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Position").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Parent").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("File").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Status").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for decoder in decoders {
+        let parent = decoder.details.as_ref().and_then(|d| d.get("parent"));
+        table.add_row(vec![
+            Cell::new(&decoder.name),
+            Cell::new(decoder.position),
+            Cell::new(parent.map_or("-".to_string(), |p| json_to_text(Some(p)))),
+            Cell::new(decoder.filename.as_deref().unwrap_or("N/A")),
+            Cell::new(decoder.status.as_deref().unwrap_or("N/A")),
+        ]);
+    }
+
+    print_table(&table, "Decoder");
+}
+
+/// Print a decoder definition with its details
+pub fn print_single_decoder(decoder: &Decoder) {
+    println!(
+        "{}",
+        format!("Decoder {} (position {})", decoder.name, decoder.position)
+            .bold()
+            .underline()
+    );
+    println!();
+
+    if let Some(filename) = &decoder.filename {
+        let dir = decoder.relative_dirname.as_deref().unwrap_or("");
+        println!("{}: {}", "File".bold(), Path::new(dir).join(filename).display());
+    }
+    if let Some(status) = &decoder.status {
+        println!("{}: {}", "Status".bold(), status);
+    }
+    if let Some(serde_json::Value::Object(details)) = &decoder.details {
+        for (key, value) in details {
+            println!("{}: {}", key.bold(), json_to_text(Some(value)));
+        }
+    }
+}
+
+/// Print ruleset files
+pub fn print_ruleset_files(files: &[RulesetFile]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("File").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Directory").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Status").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for file in files {
+        table.add_row(vec![
+            Cell::new(&file.filename),
+            Cell::new(file.relative_dirname.as_deref().unwrap_or("N/A")),
+            Cell::new(file.status.as_deref().unwrap_or("N/A")),
+        ]);
+    }
+
+    print_table(&table, "File");
+}
+
+/// Print CDB lists with their entry counts
+pub fn print_cdb_lists(lists: &[CdbList]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("List").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Directory").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Entries").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for list in lists {
+        table.add_row(vec![
+            Cell::new(&list.filename),
+            Cell::new(list.relative_dirname.as_deref().unwrap_or("N/A")),
+            Cell::new(list.items.len()),
+        ]);
+    }
+
+    print_table(&table, "List");
+}
+
+/// Print the entries of one CDB list
+pub fn print_cdb_list_entries(items: &[CdbListItem], filename: &str) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Key").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Value").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for item in items {
+        table.add_row(vec![Cell::new(&item.key), Cell::new(&item.value)]);
+    }
+
+    print_table(&table, "Entry");
+    println!("\nTotal: {} entries in {}", items.len(), filename);
+}
+
+/// Print file integrity monitoring entries
+pub fn print_fim_entries(entries: &[FimEntry]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("File").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Type").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Size").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Modified").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Owner").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Changes").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("SHA256").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for entry in entries {
+        let owner = match (&entry.uname, &entry.gname) {
+            (Some(user), Some(group)) => format!("{}:{}", user, group),
+            (Some(user), None) => user.clone(),
+            _ => "N/A".to_string(),
+        };
+        table.add_row(vec![
+            Cell::new(&entry.file),
+            Cell::new(entry.entry_type.as_deref().unwrap_or("N/A")),
+            Cell::new(entry.size.map_or("N/A".to_string(), |size| size.to_string())),
+            Cell::new(entry.mtime.as_deref().unwrap_or("N/A")),
+            Cell::new(owner),
+            Cell::new(entry.changes.unwrap_or(0)),
+            Cell::new(entry.sha256.as_deref().unwrap_or("N/A")),
+        ]);
+    }
+
+    print_table(&table, "File");
+}
+
+/// Print rootcheck findings
+pub fn print_rootcheck_findings(findings: &[RootcheckFinding]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Status").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Finding").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("PCI DSS").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("CIS").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Last Seen").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for finding in findings {
+        let status = finding.status.as_deref().unwrap_or("N/A");
+        let status = match status {
+            "outstanding" => Cell::new(status).fg(Color::Yellow),
+            "solved" => Cell::new(status).fg(Color::Green),
+            _ => Cell::new(status),
+        };
+        table.add_row(vec![
+            status,
+            Cell::new(&finding.log),
+            Cell::new(finding.pci_dss.as_deref().unwrap_or("-")),
+            Cell::new(finding.cis.as_deref().unwrap_or("-")),
+            Cell::new(finding.date_last.as_deref().unwrap_or("N/A")),
+        ]);
+    }
+
+    print_table(&table, "Finding");
+}
+
+fn task_status_cell(status: &str) -> Cell {
+    match status {
+        "Done" => Cell::new(status).fg(Color::Green),
+        "In queue" | "In progress" => Cell::new(status).fg(Color::Cyan),
+        "Failed" | "Timeout" => Cell::new(status).fg(Color::Red),
+        _ => Cell::new(status).fg(Color::Yellow),
+    }
+}
+
+/// Print manager tasks in a formatted table
+pub fn print_tasks(tasks: &[Task]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Agent").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Command").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Status").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Updated").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Error").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for task in tasks {
+        table.add_row(vec![
+            Cell::new(task.task_id),
+            Cell::new(&task.agent_id),
+            Cell::new(&task.command),
+            task_status_cell(&task.status),
+            Cell::new(task.last_update_time.as_deref().unwrap_or("N/A")),
+            Cell::new(task.error_message.as_deref().unwrap_or("")),
+        ]);
+    }
+
+    print_table(&table, "Task");
+}
+
+/// Print a single task with its details
+pub fn print_task(task: &Task) {
+    println!("{}", format!("Task {}", task.task_id).bold().underline());
+    println!();
+    println!("{}: {}", "Agent".bold(), task.agent_id);
+    println!("{}: {}", "Command".bold(), task.command);
+    println!("{}: {}", "Module".bold(), task.module);
+    println!("{}: {}", "Node".bold(), task.node);
+    println!("{}: {}", "Status".bold(), task.status);
+    if let Some(error) = &task.error_message {
+        println!("{}: {}", "Error".bold(), error.red());
+    }
+    println!("{}: {}", "Created".bold(), task.create_time.as_deref().unwrap_or("N/A"));
+    println!("{}: {}", "Updated".bold(), task.last_update_time.as_deref().unwrap_or("N/A"));
+}
+
+/// Severity cell colored from critical (red, bold) down to low
+fn severity_cell(severity: &str) -> Cell {
+    let cell = Cell::new(severity);
+    match severity.to_lowercase().as_str() {
+        "critical" => cell.fg(Color::Red).add_attribute(comfy_table::Attribute::Bold),
+        "high" => cell.fg(Color::Red),
+        "medium" => cell.fg(Color::Yellow),
+        "low" => cell.fg(Color::Green),
+        _ => cell,
+    }
+}
+
+/// Print vulnerabilities with severity-colored rows
+pub fn print_vulnerabilities(vulnerabilities: &[Vulnerability]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("CVE").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Severity").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Score").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Package").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Version").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Published").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for vulnerability in vulnerabilities {
+        table.add_row(vec![
+            Cell::new(&vulnerability.cve),
+            severity_cell(&vulnerability.severity),
+            Cell::new(vulnerability.score.map_or("-".to_string(), |score| format!("{:.1}", score))),
+            Cell::new(vulnerability.package.as_deref().unwrap_or("N/A")),
+            Cell::new(vulnerability.version.as_deref().unwrap_or("N/A")),
+            Cell::new(vulnerability.published.as_deref().unwrap_or("N/A")),
+        ]);
+    }
+
+    print_table(&table, "Vulnerability");
+}
+
+/// Print vulnerability counts per severity
+pub fn print_vulnerability_summary(summary: &VulnerabilitySummary) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Severity").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Count").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for (severity, count) in &summary.severities {
+        table.add_row(vec![severity_cell(severity), Cell::new(count)]);
+    }
+
+    print_table(&table, "Severity");
+    println!("\nTotal: {} vulnerabilities on agent {}", summary.total, summary.agent_id);
+}
+
+/// First sentence of a description, for one-line table cells
+fn first_sentence(text: &str) -> &str {
+    let line = text.lines().next().unwrap_or("");
+    match line.find(". ") {
+        Some(end) => &line[..=end],
+        None => line,
+    }
+}
+
+/// Print MITRE ATT&CK entries, or references when `references` is set
+pub fn print_mitre_entries(entries: &[serde_json::Value], references: bool) {
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    let columns = if references {
+        ["Source", "URL", "Description"]
+    } else {
+        ["ID", "Name", "Description"]
+    };
+    table.set_header(
+        columns
+            .iter()
+            .map(|c| Cell::new(c).add_attribute(comfy_table::Attribute::Bold))
+            .collect::<Vec<_>>(),
+    );
+
+    for entry in entries {
+        let description = entry["description"].as_str().unwrap_or("");
+        let first = if references {
+            json_to_text(entry.get("source"))
+        } else {
+            json_to_text(entry.get("external_id"))
+        };
+        let second = if references { "url" } else { "name" };
+        table.add_row(vec![
+            Cell::new(first),
+            Cell::new(json_to_text(entry.get(second))),
+            Cell::new(first_sentence(description)),
+        ]);
+    }
+
+    print_table(&table, if references { "Reference" } else { "Entry" });
+}
+
+/// Print one MITRE ATT&CK entry in full
+pub fn print_mitre_entry(entry: &serde_json::Value) {
+    println!(
+        "{}",
+        format!(
+            "{} {}",
+            json_to_text(entry.get("external_id")),
+            json_to_text(entry.get("name"))
+        )
+        .bold()
+        .underline()
+    );
+    println!();
+
+    if let Some(url) = entry["url"].as_str() {
+        println!("{}: {}", "URL".bold(), url);
+    }
+    for (key, label) in [("tactics", "Tactics"), ("platforms", "Platforms")] {
+        if entry[key].as_array().is_some_and(|values| !values.is_empty()) {
+            println!("{}: {}", label.bold(), json_to_text(entry.get(key)));
+        }
+    }
+    if let Some(description) = entry["description"].as_str() {
+        println!();
+        println!("{}", description);
+    }
+}
+
+/// Print Wazuh API users
+pub fn print_api_users(users: &[ApiUser]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Username").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Run As").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Roles").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for user in users {
+        let roles: Vec<String> = user.roles.iter().map(u32::to_string).collect();
+        table.add_row(vec![
+            Cell::new(user.id),
+            Cell::new(&user.username),
+            Cell::new(if user.allow_run_as { "yes" } else { "no" }),
+            Cell::new(roles.join(", ")),
+        ]);
+    }
+
+    print_table(&table, "User");
+}
+
+/// Print RBAC roles with their policy and user names
+pub fn print_api_roles(roles: &[ApiRole], policies: &[ApiPolicy], users: &[ApiUser]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Name").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Policies").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Users").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    let policy_name = |id: &u32| {
+        policies
+            .iter()
+            .find(|p| p.id == *id)
+            .map_or(id.to_string(), |p| p.name.clone())
+    };
+    let user_name = |id: &u32| {
+        users
+            .iter()
+            .find(|u| u.id == *id)
+            .map_or(id.to_string(), |u| u.username.clone())
+    };
+    for role in roles {
+        table.add_row(vec![
+            Cell::new(role.id),
+            Cell::new(&role.name),
+            Cell::new(role.policies.iter().map(policy_name).collect::<Vec<_>>().join(", ")),
+            Cell::new(role.users.iter().map(user_name).collect::<Vec<_>>().join(", ")),
+        ]);
+    }
+
+    print_table(&table, "Role");
+}
+
+/// Print RBAC policies
+pub fn print_api_policies(policies: &[ApiPolicy]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Name").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Effect").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Actions").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Resources").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for policy in policies {
+        let effect = match policy.policy.effect.as_str() {
+            "deny" => Cell::new("deny").fg(Color::Red),
+            effect => Cell::new(effect).fg(Color::Green),
+        };
+        table.add_row(vec![
+            Cell::new(policy.id),
+            Cell::new(&policy.name),
+            effect,
+            Cell::new(policy.policy.actions.join(", ")),
+            Cell::new(policy.policy.resources.join(", ")),
+        ]);
+    }
+
+    print_table(&table, "Policy");
+}
+
+/// Print a single-column list of names
+pub fn print_names(names: &[String], subject: &str) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![Cell::new(subject).add_attribute(comfy_table::Attribute::Bold)]);
+
+    for name in names {
+        table.add_row(vec![Cell::new(name)]);
+    }
+
+    print_table(&table, subject);
+}
+
+/// Print alert documents in a formatted table
+pub fn print_alerts_table(alerts: &[serde_json::Value]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Timestamp").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Agent").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Rule").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Level").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Description").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for alert in alerts {
+        let level = json_field(alert, "rule.level")
+            .and_then(|l| l.as_u64())
+            .unwrap_or(0);
+
+        table.add_row(vec![
+            Cell::new(json_to_text(json_field(alert, "timestamp"))),
+            Cell::new(format!(
+                "{} ({})",
+                json_to_text(json_field(alert, "agent.name")),
+                json_to_text(json_field(alert, "agent.id"))
+            )),
+            Cell::new(json_to_text(json_field(alert, "rule.id"))),
+            level_cell(level),
+            Cell::new(json_to_text(json_field(alert, "rule.description"))),
+        ]);
+    }
+
+    print_table(&table, "Alert");
+}
+
+/// Print archived events; most never matched a rule above level 0
+pub fn print_archives_table(events: &[serde_json::Value]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Timestamp").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Agent").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Location").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Decoder").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Log").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for event in events {
+        table.add_row(vec![
+            Cell::new(json_to_text(json_field(event, "timestamp"))),
+            Cell::new(format!(
+                "{} ({})",
+                json_to_text(json_field(event, "agent.name")),
+                json_to_text(json_field(event, "agent.id"))
+            )),
+            Cell::new(json_to_text(json_field(event, "location"))),
+            Cell::new(json_to_text(json_field(event, "decoder.name"))),
+            Cell::new(json_to_text(json_field(event, "full_log"))),
+        ]);
+    }
+
+    print_table(&table, "Event");
+}
+
+/// Print selected fields of sampled alerts, one column per field
+pub fn print_alert_sample(fields: &[String], rows: &[Vec<String>]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(
+            fields
+                .iter()
+                .map(|field| Cell::new(field).add_attribute(comfy_table::Attribute::Bold))
+                .collect::<Vec<_>>(),
+        );
+
+    for row in rows {
+        table.add_row(row.iter().map(Cell::new).collect::<Vec<_>>());
+    }
+
+    print_table(&table, "Alert");
+}
+
+/// Print limit usage and enrollment settings
+pub fn print_limits(report: &LimitsReport) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Limit").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Used").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Max").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Usage").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for limit in &report.limits {
+        let usage = limit
+            .percent
+            .map(|p| format!("{:.1}%", p))
+            .unwrap_or_else(|| "-".to_string());
+        let usage_cell = match limit.level {
+            LimitLevel::Critical => Cell::new(usage)
+                .fg(Color::Red)
+                .add_attribute(comfy_table::Attribute::Bold),
+            LimitLevel::Warning => Cell::new(usage).fg(Color::Yellow),
+            LimitLevel::Ok => Cell::new(usage).fg(Color::Green),
+            LimitLevel::Unlimited => Cell::new(usage),
+        };
+
+        table.add_row(vec![
+            Cell::new(&limit.name),
+            Cell::new(limit.used),
+            Cell::new(
+                limit
+                    .max
+                    .map(|max| max.to_string())
+                    .unwrap_or_else(|| "unlimited".to_string()),
+            ),
+            usage_cell,
+        ]);
+    }
+
+    print_table(&table, "Limit");
+
+    if !report.enrollment.is_empty() {
+        println!();
+        println!("{}", "Enrollment".bold());
+        for (key, value) in &report.enrollment {
+            println!("  {}: {}", key.bold(), value);
+        }
+    }
+}
+
+/// Print the hotfixes installed on one agent
+pub fn print_hotfix_list(agent: &AgentHotfixes) {
+    println!("{}: {} ({})", "Agent".bold(), agent.agent_name, agent.agent_id);
+    println!(
+        "{}: {}",
+        "Last Scan".bold(),
+        agent.scan_time.as_deref().unwrap_or("N/A")
+    );
+    println!();
+
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![Cell::new("Hotfix").add_attribute(comfy_table::Attribute::Bold)]);
+    for hotfix in &agent.hotfixes {
+        table.add_row(vec![Cell::new(hotfix)]);
+    }
+
+    print_table(&table, "Hotfix");
+}
+
+/// Print hotfix counts per agent, with the presence of `kb` when one was given
+pub fn print_hotfix_summary(agents: &[AgentHotfixes], kb: Option<&str>) {
+    let mut header = vec![
+        Cell::new("ID").add_attribute(comfy_table::Attribute::Bold),
+        Cell::new("Name").add_attribute(comfy_table::Attribute::Bold),
+        Cell::new("Hotfixes").add_attribute(comfy_table::Attribute::Bold),
+        Cell::new("Last Scan").add_attribute(comfy_table::Attribute::Bold),
+    ];
+    if let Some(kb) = kb {
+        header.push(Cell::new(kb).add_attribute(comfy_table::Attribute::Bold));
+    }
+    header.push(Cell::new("Error").add_attribute(comfy_table::Attribute::Bold));
+
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(header);
+
+    for agent in agents {
+        let mut row = vec![
+            Cell::new(&agent.agent_id),
+            Cell::new(&agent.agent_name),
+            Cell::new(agent.hotfixes.len()),
+            Cell::new(agent.scan_time.as_deref().unwrap_or("N/A")),
+        ];
+        if kb.is_some() {
+            row.push(match agent.has_kb {
+                Some(true) => Cell::new("Installed").fg(Color::Green),
+                Some(false) => Cell::new("Missing")
+                    .fg(Color::Red)
+                    .add_attribute(comfy_table::Attribute::Bold),
+                None => Cell::new("Unknown").fg(Color::Yellow),
+            });
+        }
+        row.push(Cell::new(agent.error.as_deref().unwrap_or("")));
+        table.add_row(row);
+    }
+
+    print_table(&table, "Agent");
+}
+
+/// Print a compliance report summary
+pub fn print_compliance_report(report: &ComplianceReport) {
+    println!(
+        "{}",
+        format!("{} Compliance Report", report.standard)
+            .bold()
+            .underline()
+    );
+    println!();
+    println!(
+        "{}: {} to {}",
+        "Period".bold(),
+        format_datetime(&report.from),
+        format_datetime(&report.to)
+    );
+    println!("{}: {}", "Total Alerts".bold(), report.total_alerts);
+    println!("{}: {}", "Requirements Matched".bold(), report.requirements.len());
+    println!();
+
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Requirement").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Alerts").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Agents").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Rules").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Max Level").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for row in &report.requirements {
+        table.add_row(vec![
+            Cell::new(&row.requirement),
+            Cell::new(row.alerts),
+            Cell::new(row.agents),
+            Cell::new(row.rules),
+            level_cell(row.max_level as u64),
+        ]);
+    }
+
+    print_table(&table, "Requirement");
+}
+
+/// Width of the bar column in text charts
+const CHART_WIDTH: u64 = 40;
+
+/// Print fleet growth as a table with a bar chart of registrations (█) and removals (░)
+pub fn print_fleet_growth(report: &FleetGrowthReport) {
+    println!("{}", "Fleet Growth".bold().underline());
+    println!();
+    println!("{}: {}", "Grouped By".bold(), report.group_by);
+    println!("{}: {}", "Current Agents".bold(), report.current_agents);
+    println!();
+
+    let peak = report
+        .periods
+        .iter()
+        .map(|p| p.registered.max(p.removed))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Period").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Registered").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Removed").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Net").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Cumulative").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for period in &report.periods {
+        let added = "█".repeat((period.registered * CHART_WIDTH / peak) as usize);
+        let removed = "░".repeat((period.removed * CHART_WIDTH / peak) as usize);
+        let net_cell = match period.net {
+            n if n > 0 => Cell::new(format!("+{}", n)).fg(Color::Green),
+            n if n < 0 => Cell::new(n).fg(Color::Red),
+            n => Cell::new(n),
+        };
+
+        table.add_row(vec![
+            Cell::new(&period.period),
+            Cell::new(period.registered),
+            Cell::new(period.removed),
+            net_cell,
+            Cell::new(period.cumulative),
+            Cell::new(format!("{}{}", added, removed)),
+        ]);
+    }
+
+    print_table(&table, "Period");
+}
+
+/// Print alert counts ranked by the grouping field, with their share of all alerts
+pub fn print_alert_stats(stats: &AlertStats) {
+    println!("{}", "Alert Statistics".bold().underline());
+    println!();
+    println!(
+        "{}: {} to {}",
+        "Period".bold(),
+        format_datetime(&stats.from),
+        format_datetime(&stats.to)
+    );
+    println!("{}: {}", "Total Alerts".bold(), stats.total_alerts);
+    println!("{}: {}", "Grouped By".bold(), stats.group_by);
+    println!();
+
+    let (subject, label) = match stats.group_by.as_str() {
+        "rule.id" => ("Rule", Some("Description")),
+        "agent.name" => ("Agent", Some("Agent ID")),
+        _ => ("Level", None),
+    };
+    let peak = stats.buckets.iter().map(|b| b.alerts).max().unwrap_or(0).max(1);
+    let total = stats.total_alerts.max(1);
+
+    let mut header = vec![
+        Cell::new("#").add_attribute(comfy_table::Attribute::Bold),
+        Cell::new(subject).add_attribute(comfy_table::Attribute::Bold),
+    ];
+    if let Some(label) = label {
+        header.push(Cell::new(label).add_attribute(comfy_table::Attribute::Bold));
+    }
+    header.extend([
+        Cell::new("Alerts").add_attribute(comfy_table::Attribute::Bold),
+        Cell::new("Share").add_attribute(comfy_table::Attribute::Bold),
+        Cell::new("").add_attribute(comfy_table::Attribute::Bold),
+    ]);
+
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(header);
+
+    for (rank, bucket) in stats.buckets.iter().enumerate() {
+        let key_cell = match bucket.key.parse::<u64>() {
+            Ok(level) if label.is_none() => level_cell(level),
+            _ => Cell::new(&bucket.key),
+        };
+        let mut row = vec![Cell::new(rank + 1), key_cell];
+        if label.is_some() {
+            row.push(Cell::new(bucket.label.as_deref().unwrap_or("-")));
+        }
+        row.extend([
+            Cell::new(bucket.alerts),
+            Cell::new(format!("{:.1}%", bucket.alerts as f64 * 100.0 / total as f64)),
+            Cell::new("█".repeat((bucket.alerts * CHART_WIDTH / peak) as usize)),
+        ]);
+        table.add_row(row);
+    }
+
+    print_table(&table, subject);
+}
+
+/// Clock skew beyond which a node is flagged
+const MAX_CLOCK_SKEW_SECS: i64 = 5;
+
+/// Print the cluster health matrix
+pub fn print_cluster_health(nodes: &[NodeHealth]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Node").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Type").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("IP").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("API").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Latency").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Daemons").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Clock Skew").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Error").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for node in nodes {
+        let api_cell = if node.api_reachable {
+            Cell::new("Reachable").fg(Color::Green)
+        } else {
+            Cell::new("Unreachable").fg(Color::Red).add_attribute(comfy_table::Attribute::Bold)
+        };
+
+        let daemons_cell = match (node.daemons_running, node.daemons_total) {
+            (Some(running), Some(total)) => {
+                let cell = Cell::new(format!("{}/{}", running, total));
+                if running < total {
+                    cell.fg(Color::Yellow)
+                } else {
+                    cell.fg(Color::Green)
+                }
+            }
+            _ => Cell::new("N/A"),
+        };
+
+        let skew_cell = match node.clock_skew_secs {
+            Some(skew) if skew.abs() > MAX_CLOCK_SKEW_SECS => {
+                Cell::new(format!("{:+}s", skew)).fg(Color::Red)
+            }
+            Some(skew) => Cell::new(format!("{:+}s", skew)),
+            None => Cell::new("N/A"),
+        };
+
+        table.add_row(vec![
+            Cell::new(&node.name),
+            Cell::new(&node.node_type),
+            Cell::new(node.ip.as_deref().unwrap_or("N/A")),
+            api_cell,
+            Cell::new(
+                node.latency_ms
+                    .map(|ms| format!("{} ms", ms))
+                    .unwrap_or_else(|| "N/A".to_string()),
+            ),
+            daemons_cell,
+            skew_cell,
+            Cell::new(node.error.as_deref().unwrap_or("")),
+        ]);
+    }
+
+    print_table(&table, "Node");
+}
+
+/// Sync timestamps older than this are flagged
+const STALE_SYNC_SECS: i64 = 600;
+
+/// Print master/worker synchronization state
+pub fn print_cluster_sync(nodes: &[NodeSyncStatus]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Node").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Type").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Keep Alive").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Integrity Check").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Integrity Sync").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Files (missing/shared/extra)").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Agent Info Sync").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("State").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    let now = Utc::now();
+    let age_cell = |ts: &Option<DateTime<Utc>>| match ts {
+        Some(ts) => {
+            let secs = (now - *ts).num_seconds().max(0);
+            let cell = Cell::new(format!("{} ago", format_age(secs)));
+            if secs > STALE_SYNC_SECS {
+                cell.fg(Color::Yellow)
+            } else {
+                cell
+            }
+        }
+        None => Cell::new("N/A"),
+    };
+
+    for node in nodes {
+        let files_cell = match (node.files_missing, node.files_shared, node.files_extra) {
+            (None, None, None) => Cell::new("N/A"),
+            (missing, shared, extra) => {
+                let count =
+                    |n: Option<u64>| n.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string());
+                let cell =
+                    Cell::new(format!("{}/{}/{}", count(missing), count(shared), count(extra)));
+                if missing.unwrap_or(0) + shared.unwrap_or(0) + extra.unwrap_or(0) > 0 {
+                    cell.fg(Color::Yellow)
+                } else {
+                    cell
+                }
+            }
+        };
+
+        let state_cell = if node.node_type == "master" {
+            Cell::new("-")
+        } else if node.syncing {
+            Cell::new("Syncing").fg(Color::Yellow)
+        } else {
+            Cell::new("Idle").fg(Color::Green)
+        };
+
+        table.add_row(vec![
+            Cell::new(&node.name),
+            Cell::new(&node.node_type),
+            age_cell(&node.last_keep_alive),
+            age_cell(&node.last_integrity_check),
+            age_cell(&node.last_integrity_sync),
+            files_cell,
+            age_cell(&node.last_agent_info_sync),
+            state_cell,
+        ]);
+    }
+
+    print_table(&table, "Node");
+}
+
+/// Compact age such as `45s`, `12m` or `3h`
+pub fn format_age(secs: i64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86400 => format!("{}h", s / 3600),
+        s => format!("{}d", s / 86400),
+    }
+}
+
+/// Print a stored unified diff in the configured style (`--diff-style`, `--diff-context`)
+pub fn print_diff(diff: &str) {
+    let width = console::Term::stdout().size_checked().map_or(120, |(_, cols)| cols as usize);
+    for line in diff::render(diff, width) {
+        println!("{}", line);
+    }
+}
+
+/// Print files that drifted from the baseline, optionally with their diffs
+pub fn print_drift(drift: &[DriftEntry], show_diff: bool) {
+    for entry in drift {
+        let marker = match entry.kind {
+            DriftKind::Added => "added".green(),
+            DriftKind::Removed => "removed".red(),
+            DriftKind::Modified => "modified".yellow(),
+        };
+        println!("{:>10}  {}", marker, entry.path);
+    }
+
+    if show_diff {
+        for entry in drift {
+            println!();
+            print_diff(&entry.diff);
+        }
+    }
+}
+
+/// Print staged configuration changes
+pub fn print_changes_table(changes: &[StagedChange]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Status").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Staged").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Author").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Approver").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Source").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Message").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for change in changes {
+        let status_cell = match change.status {
+            ChangeStatus::Pending => Cell::new(change.status.to_string()).fg(Color::Yellow),
+            ChangeStatus::Applied => Cell::new(change.status.to_string()).fg(Color::Green),
+            ChangeStatus::Discarded => Cell::new(change.status.to_string()),
+        };
+
+        table.add_row(vec![
+            Cell::new(&change.id),
+            status_cell,
+            Cell::new(format_datetime(&change.created_at)),
+            Cell::new(&change.author),
+            Cell::new(change.approver.as_deref().unwrap_or("")),
+            Cell::new(change.source_file.display()),
+            Cell::new(change.message.as_deref().unwrap_or("")),
+        ]);
+    }
+
+    print_table(&table, "Change");
+}
+
+/// Print each local state lock and its holder
+pub fn print_lock_status(locks: &[LockStatus]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Resource").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("State").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("PID").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Since").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Lock File").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for lock in locks {
+        let state = if lock.held {
+            Cell::new("held").fg(Color::Yellow)
+        } else {
+            Cell::new("free").fg(Color::Green)
+        };
+        let holder = lock.holder.as_ref();
+        table.add_row(vec![
+            Cell::new(lock.resource),
+            state,
+            Cell::new(holder.map(|h| h.pid.to_string()).unwrap_or_default()),
+            Cell::new(holder.map(|h| format_datetime(&h.acquired_at)).unwrap_or_default()),
+            Cell::new(&lock.path),
+        ]);
+    }
+
+    print_table(&table, "Lock");
+}
+
+/// Print settings profiles, marking the one in use
+pub fn print_profiles(profiles: &[ProfileSummary]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Profile").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Current").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("API URL").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Username").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for profile in profiles {
+        let current = if profile.current {
+            Cell::new("*").fg(Color::Green)
+        } else {
+            Cell::new("")
+        };
+        table.add_row(vec![
+            Cell::new(&profile.name),
+            current,
+            Cell::new(&profile.api_url),
+            Cell::new(profile.username.as_deref().unwrap_or("-")),
+        ]);
+    }
+
+    print_table(&table, "Profile");
+}
+
+/// Print a maintenance window and the timing of each step
+pub fn print_maintenance_window(window: &MaintenanceWindow) {
+    println!("{}: {}", "Started".bold(), format_datetime(&window.started_at));
+    if let Some(ended_at) = &window.ended_at {
+        println!("{}: {}", "Ended".bold(), format_datetime(ended_at));
+    }
+    println!("{}: {}", "Operator".bold(), window.operator);
+    if let Some(reason) = &window.reason {
+        println!("{}: {}", "Reason".bold(), reason);
+    }
+    println!();
+
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Step").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Outcome").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Started").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Duration").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Detail").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for step in &window.steps {
+        let outcome_cell = match step.outcome {
+            StepOutcome::Ok => Cell::new(step.outcome.to_string()).fg(Color::Green),
+            StepOutcome::Skipped => Cell::new(step.outcome.to_string()).fg(Color::Yellow),
+            StepOutcome::Failed => Cell::new(step.outcome.to_string()).fg(Color::Red),
+        };
+
+        table.add_row(vec![
+            Cell::new(&step.name),
+            outcome_cell,
+            Cell::new(format_datetime(&step.started_at)),
+            Cell::new(format!("{:.1}s", step.duration_ms as f64 / 1000.0)),
+            Cell::new(step.detail.as_deref().unwrap_or("")),
+        ]);
+    }
+
+    print_table(&table, "Step");
+}
+
+pub fn print_version_matrix(matrix: &VersionMatrix) {
+    let mut header = vec![Cell::new("Version").add_attribute(comfy_table::Attribute::Bold)];
+    header.extend(
+        matrix
+            .platforms
+            .iter()
+            .map(|p| Cell::new(p).add_attribute(comfy_table::Attribute::Bold)),
+    );
+    header.push(Cell::new("Total").add_attribute(comfy_table::Attribute::Bold));
+    if matrix.min_version.is_some() {
+        header.push(Cell::new("Below Minimum").add_attribute(comfy_table::Attribute::Bold));
+    }
+
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(header);
+
+    for row in &matrix.rows {
+        let version_cell = if row.below_minimum {
+            Cell::new(&row.version)
+                .fg(Color::Red)
+                .add_attribute(comfy_table::Attribute::Bold)
+        } else {
+            Cell::new(&row.version)
+        };
+
+        let mut cells = vec![version_cell];
+        cells.extend(row.counts.iter().map(|count| match count {
+            0 => Cell::new("-"),
+            count => Cell::new(count),
+        }));
+        cells.push(Cell::new(row.total));
+        if matrix.min_version.is_some() {
+            cells.push(if row.below_minimum {
+                Cell::new("Yes").fg(Color::Red)
+            } else {
+                Cell::new("No").fg(Color::Green)
+            });
+        }
+        table.add_row(cells);
+    }
+
+    print_table(&table, "Version");
+}
+
+/// Print rows as CSV, quoting fields that need it
+pub fn print_csv(headers: &[String], rows: &[Vec<String>]) {
+    println!("{}", csv_line(headers));
+    for row in rows {
+        println!("{}", csv_line(row));
+    }
+}
+
+fn csv_line(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Print rows as a Markdown table
+pub fn print_markdown(headers: &[String], rows: &[Vec<String>]) {
+    println!("{}", markdown_line(headers));
+    println!("|{}", " --- |".repeat(headers.len()));
+    for row in rows {
+        println!("{}", markdown_line(row));
+    }
+}
+
+fn markdown_line(fields: &[String]) -> String {
+    let cells: Vec<String> = fields.iter().map(|field| field.replace('|', "\\|")).collect();
+    format!("| {} |", cells.join(" | "))
+}
+
+/// Print the likely causes of a disconnection with their evidence
+pub fn print_disconnect_report(report: &DisconnectReport) {
+    println!(
+        "{}",
+        format!("Agent {} ({})", report.agent_id, report.agent_name).bold().underline()
+    );
+    println!();
+    println!("{}: {}", "Status".bold(), report.status);
+    match (&report.last_keep_alive, report.offline_secs) {
+        (Some(seen), Some(secs)) => println!(
+            "{}: {} ({} ago)",
+            "Last Keep Alive".bold(),
+            format_datetime(seen),
+            format_age(secs)
+        ),
+        _ => println!("{}: Never", "Last Keep Alive".bold()),
+    }
+
+    for (index, cause) in report.causes.iter().enumerate() {
+        println!();
+        let label = if index == 0 { "Diagnosis" } else { "Also possible" };
+        println!("{}: {}", label.bold(), cause.cause.yellow().bold());
+        for evidence in &cause.evidence {
+            println!("  - {}", evidence);
+        }
+        if cause.evidence.is_empty() && index == 0 && report.status != AgentStatus::Active {
+            println!("  - No matching manager logs or alerts");
+        }
+        println!("  {} {}", "Hint:".cyan(), cause.hint);
+    }
+
+    if !report.alerts.is_empty() {
+        println!();
+        println!("{}", "Recent agent alerts".bold());
+        for alert in &report.alerts {
+            println!("  {}", alert);
+        }
+    }
+}
+
+/// Print connectivity checks as a checklist
+pub fn print_checks(checks: &[Check]) {
+    for check in checks {
+        let mark = match check.status {
+            CheckStatus::Pass => "✓".green().bold(),
+            CheckStatus::Warn => "!".yellow().bold(),
+            CheckStatus::Fail => "✗".red().bold(),
+            CheckStatus::Skip => "-".dimmed(),
+        };
+        println!("{} {}: {}", mark, check.name.bold(), check.detail);
+        if let Some(hint) = &check.hint {
+            println!("  {} {}", "Hint:".cyan(), hint);
+        }
+    }
+}
+
+/// Print the agents matched by `agent group assign` and what happened to each
+pub fn print_group_assignments(assignments: &[GroupAssignment], group: &str) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Name").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("OS").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Groups").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Result").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for assignment in assignments {
+        let result = match assignment.status {
+            AssignmentStatus::Planned => Cell::new(format!("assign to {}", group)).fg(Color::Cyan),
+            AssignmentStatus::AlreadyMember => Cell::new("already member"),
+            AssignmentStatus::Assigned => Cell::new("assigned").fg(Color::Green),
+            AssignmentStatus::Failed => Cell::new(format!(
+                "failed: {}",
+                assignment.error.as_deref().unwrap_or("unknown error")
+            ))
+            .fg(Color::Red),
+        };
+        table.add_row(vec![
+            Cell::new(&assignment.agent_id),
+            Cell::new(&assignment.agent_name),
+            Cell::new(assignment.platform.as_deref().unwrap_or("N/A")),
+            Cell::new(assignment.groups.join(", ")),
+            result,
+        ]);
+    }
+
+    print_table(&table, "Agent");
+}
+
+/// Print agent groups
+pub fn print_agent_groups(groups: &[AgentGroup]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Name").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Agents").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Config Sum").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Merged Sum").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for group in groups {
+        table.add_row(vec![
+            Cell::new(&group.name),
+            Cell::new(group.count),
+            Cell::new(group.config_sum.as_deref().unwrap_or("N/A")),
+            Cell::new(group.merged_sum.as_deref().unwrap_or("N/A")),
+        ]);
+    }
+
+    print_table(&table, "Group");
+}
+
+/// Print detected check-in anomalies
+pub fn print_anomalies(anomalies: &[Anomaly]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Anomaly").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Group / Agent").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Detail").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for anomaly in anomalies {
+        let kind = match anomaly.kind {
+            AnomalyKind::ActiveDrop => Cell::new(anomaly.kind.to_string()).fg(Color::Red),
+            AnomalyKind::Flapping => Cell::new(anomaly.kind.to_string()).fg(Color::Yellow),
+        };
+        table.add_row(vec![kind, Cell::new(&anomaly.subject), Cell::new(&anomaly.detail)]);
+    }
+
+    print_table(&table, "Anomaly");
+}
+
+/// Print the retention compliance of each index
+pub fn print_retention_check(results: &[IndexRetention]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Index").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Age (days)").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("ISM Policy").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Deleted After (days)").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Result").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for result in results {
+        let outcome = if result.violations.is_empty() {
+            Cell::new("ok").fg(Color::Green)
+        } else {
+            let violations: Vec<String> = result.violations.iter().map(|v| v.to_string()).collect();
+            Cell::new(violations.join(", ")).fg(Color::Red)
+        };
+        let days = |days: Option<i64>| days.map(|d| d.to_string()).unwrap_or("N/A".to_string());
+        table.add_row(vec![
+            Cell::new(&result.index),
+            Cell::new(days(result.age_days)),
+            Cell::new(result.policy_id.as_deref().unwrap_or("none")),
+            Cell::new(match (&result.policy_id, result.delete_after_days) {
+                (Some(_), None) => "never".to_string(),
+                (_, after) => days(after),
+            }),
+            outcome,
+        ]);
+    }
+
+    print_table(&table, "Index");
+}
+
+/// Print the per-agent outcome of `agent run`
+pub fn print_run_results(results: &[AgentRunResult]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Name").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Status").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Output").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for result in results {
+        let status = match result.status {
+            RunStatus::Completed => Cell::new("completed").fg(Color::Green),
+            RunStatus::Sent => Cell::new("sent").fg(Color::Green),
+            RunStatus::TimedOut => Cell::new("no result").fg(Color::Yellow),
+            RunStatus::Unknown => Cell::new("unknown").fg(Color::Yellow),
+            RunStatus::Failed => Cell::new("failed").fg(Color::Red),
+            RunStatus::Skipped => Cell::new("skipped"),
+        };
+        let output = match &result.error {
+            Some(error) => error.clone(),
+            None => result.output.join("\n"),
+        };
+        table.add_row(vec![
+            Cell::new(&result.agent_id),
+            Cell::new(&result.agent_name),
+            status,
+            Cell::new(output),
+        ]);
+    }
+
+    print_table(&table, "Agent");
+}
+
+/// Print the per-agent outcome of a bulk restart, upgrade or removal
+pub fn print_bulk_results(results: &[BulkResult]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Name").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Result").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Error").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for result in results {
+        let status = if result.success {
+            Cell::new("ok").fg(Color::Green)
+        } else {
+            Cell::new("failed").fg(Color::Red)
+        };
+        table.add_row(vec![
+            Cell::new(&result.agent_id),
+            Cell::new(&result.agent_name),
+            status,
+            Cell::new(result.error.as_deref().unwrap_or_default()),
+        ]);
+    }
+
+    print_table(&table, "Agent");
+}
+
+/// Print agent keys, or why an agent's key could not be retrieved
+pub fn print_agent_keys(keys: &[AgentKeyResult]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Name").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Key").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for key in keys {
+        let value = match (&key.key, &key.error) {
+            (Some(value), _) => Cell::new(value),
+            (None, error) => Cell::new(error.as_deref().unwrap_or_default()).fg(Color::Red),
+        };
+        table.add_row(vec![Cell::new(&key.agent_id), Cell::new(&key.agent_name), value]);
+    }
+
+    print_table(&table, "Agent");
+}
+
+/// Print the active-response commands of the manager configuration
+pub fn print_ar_commands(commands: &[ActiveResponseCommand]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Command").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Executable").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Timeout").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Allowed").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for command in commands {
+        let allowed = if command.allowed {
+            Cell::new("yes").fg(Color::Green)
+        } else {
+            Cell::new("no")
+        };
+        table.add_row(vec![
+            Cell::new(&command.name),
+            Cell::new(&command.executable),
+            Cell::new(&command.timeout_allowed),
+            allowed,
+        ]);
+    }
+
+    print_table(&table, "Command");
+}
+
+/// Print the fleet SCA panel, or the agents failing one check when drilling down
+pub fn print_sca_summary(summary: &ScaSummary, top: usize, drill_down: Option<&str>) {
+    println!(
+        "{} ({} agents, {})",
+        "Configuration Assessment".bold().underline(),
+        summary.agents_scanned,
+        summary.timestamp.format("%H:%M:%S")
+    );
+    println!();
+
+    if let Some(check_id) = drill_down {
+        match summary.checks.first() {
+            Some(check) => {
+                println!("{} {} ({})", "Check".bold(), check.check_id, check.policy_id);
+                println!("{}", check.title);
+                println!();
+                println!("Failing on {} agents:", check.failing_agents.len());
+                for agent in &check.failing_agents {
+                    println!("  {}", agent);
+                }
+            }
+            None => print_info(&format!("No agent fails check {}", check_id)),
+        }
+        return;
+    }
+
+    let mut policies = Table::new();
+    policies
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Policy").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Name").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Agents").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Avg Score").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+    for policy in &summary.policies {
+        let score = format!("{:.0}%", policy.average_score);
+        let score = match policy.average_score {
+            s if s < 50.0 => Cell::new(score).fg(Color::Red),
+            s if s < 80.0 => Cell::new(score).fg(Color::Yellow),
+            _ => Cell::new(score).fg(Color::Green),
+        };
+        policies.add_row(vec![
+            Cell::new(&policy.policy_id),
+            Cell::new(&policy.name),
+            Cell::new(policy.agents),
+            score,
+        ]);
+    }
+    print_table(&policies, "Policy");
+
+    if summary.checks.is_empty() || top == 0 {
+        return;
+    }
+
+    println!();
+    let mut checks = Table::new();
+    checks
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Check").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Policy").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Title").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Failing").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+    for check in summary.checks.iter().take(top) {
+        checks.add_row(vec![
+            Cell::new(&check.check_id),
+            Cell::new(&check.policy_id),
+            Cell::new(&check.title),
+            Cell::new(check.failing_agents.len()),
+        ]);
+    }
+    print_table(&checks, "Check");
+    println!("\nUse --check <ID> to list the agents failing a check");
+}
+
+/// Print per-command usage and the most used flags
+pub fn print_usage_summary(summary: &UsageSummary, top: usize) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Command").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Runs").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Failures").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Avg").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("p95").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Last Used").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for usage in &summary.commands {
+        let failures = if usage.failures > 0 {
+            Cell::new(usage.failures).fg(Color::Red)
+        } else {
+            Cell::new(usage.failures)
+        };
+        table.add_row(vec![
+            Cell::new(&usage.command),
+            Cell::new(usage.runs),
+            failures,
+            Cell::new(format!("{} ms", usage.avg_ms)),
+            Cell::new(format!("{} ms", usage.p95_ms)),
+            Cell::new(format_datetime(&usage.last_used)),
+        ]);
+    }
+
+    print_table(&table, "Command");
+
+    if !summary.flags.is_empty() && top > 0 {
+        println!();
+        println!("{}", "Most used flags".bold());
+        for (flag, count) in summary.flags.iter().take(top) {
+            println!("  {:>5}  {}", count, flag);
+        }
+    }
+
+    if !summary.slow_endpoints.is_empty() && top > 0 {
+        println!();
+        println!("{}", "Endpoints over their latency budget".bold());
+        for (endpoint, count) in summary.slow_endpoints.iter().take(top) {
+            println!("  {:>5}  {}", count, endpoint);
+        }
+    }
+}
+
+/// Format a DateTime for display
+fn format_datetime(dt: &DateTime<Utc>) -> String {
+    dt.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+}
+
+/// Print a success message
+pub fn print_success(message: &str) {
+    if accessible() {
+        println!("Success: {}", message);
+        return;
+    }
+    println!("{} {}", "✓".green().bold(), message);
+}
+
+/// Report a warning without ever touching stdout.
+///
+/// Warnings go into the envelope when it is enabled. Otherwise they are written
+/// to stderr: as a banner on a terminal, or as one NDJSON line (`"event": "warning"`)
+/// when stderr is redirected or `--progress json` is active, so they can be parsed.
+pub fn print_warning(kind: WarningKind, message: &str) {
+    let warning = Warning {
+        timestamp: Utc::now(),
+        kind,
+        message: message.to_string(),
+    };
+
+    if envelope() {
+        if let Ok(mut warnings) = WARNINGS.lock() {
+            warnings.push(warning);
+        }
+        return;
+    }
+
+    if progress::json_events() || !std::io::stderr().is_terminal() {
+        let mut line = serde_json::to_value(&warning).unwrap_or_default();
+        line["event"] = "warning".into();
+        eprintln!("{}", line);
+        return;
+    }
+
+    let banner = if accessible() {
+        format!("Warning: {}", message)
+    } else {
+        format!("{} {}", "⚠".yellow().bold(), message.yellow())
+    };
+    // Print above a running spinner instead of on top of it
+    match active_spinner() {
+        Some(pb) => pb.suspend(|| eprintln!("{}", banner)),
+        None => eprintln!("{}", banner),
+    }
+}
+
+/// Print an info message on stderr; notes about the result are not part of the data
+pub fn print_info(message: &str) {
+    let line = if accessible() {
+        format!("Info: {}", message)
+    } else {
+        format!("{} {}", "ℹ".blue().bold(), message)
+    };
+    match active_spinner() {
+        Some(pb) => pb.suspend(|| eprintln!("{}", line)),
+        None => eprintln!("{}", line),
+    }
+}
+
+/// Print certificate audit results, flagging expiring and weak certificates
+pub fn print_certificate_audit(statuses: &[CertificateStatus], warn_days: i64) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Source").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Subject").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Expires").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Days Left").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Key").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Signature").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("Issues").add_attribute(comfy_table::Attribute::Bold),
+        ]);
+
+    for status in statuses {
+        let days_cell = match status.days_left {
+            Some(days) if days < 0 => Cell::new(days)
+                .fg(Color::Red)
+                .add_attribute(comfy_table::Attribute::Bold),
+            Some(days) if days < warn_days => Cell::new(days).fg(Color::Yellow),
+            Some(days) => Cell::new(days).fg(Color::Green),
+            None => Cell::new("N/A"),
+        };
+
+        let issues_cell = if status.issues.is_empty() {
+            Cell::new("OK").fg(Color::Green)
+        } else {
+            Cell::new(status.issues.join(", ")).fg(Color::Red)
+        };
+
+        table.add_row(vec![
+            Cell::new(&status.source),
+            Cell::new(status.subject.as_deref().unwrap_or("N/A")),
+            Cell::new(
+                status
+                    .not_after
+                    .map(|dt| format_datetime(&dt))
+                    .unwrap_or_else(|| "N/A".to_string()),
+            ),
+            days_cell,
+            Cell::new(status.key.as_deref().unwrap_or("N/A")),
+            Cell::new(status.signature.as_deref().unwrap_or("N/A")),
+            issues_cell,
+        ]);
+    }
+
+    print_table(&table, "Certificate");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_select_columns() {
+        let mut table = Table::new();
+        table.set_header(vec!["ID", "Name", "IP", "Last Keep Alive"]);
+        table.add_row(vec!["001", "web-01", "10.0.0.1", "2024-05-01"]);
+
+        let fields = vec!["last_keep_alive".to_string(), "id".to_string(), "os".to_string()];
+        let selected = select_columns(&table, &fields).unwrap();
+        let header: Vec<String> =
+            selected.header().unwrap().cell_iter().map(|c| c.content()).collect();
+        assert_eq!(header, vec!["Last Keep Alive", "ID"]);
+        let row: Vec<String> =
+            selected.row(0).unwrap().cell_iter().map(|c| c.content()).collect();
+        assert_eq!(row, vec!["2024-05-01", "001"]);
+
+        assert!(select_columns(&table, &["os".to_string()]).is_none());
+    }
+
+    #[test]
+    fn test_ndjson_lines() {
+        let lines = ndjson_lines(&json!([{ "id": "001", "tags": ["a"] }, { "id": "002" }]));
+        assert_eq!(lines.unwrap(), vec![r#"{"id":"001","tags":["a"]}"#, r#"{"id":"002"}"#]);
+
+        let lines = ndjson_lines(&json!({ "total": 2 })).unwrap();
+        assert_eq!(lines, vec![r#"{"total":2}"#]);
+    }
+
+    #[test]
+    fn test_envelope_document() {
+        let warning = Warning {
+            timestamp: Utc::now(),
+            kind: WarningKind::Fallback,
+            message: "showing cached results".to_string(),
+        };
+        let document = envelope_document(vec![json!([{"id": "001"}])], &[warning], None);
+        assert_eq!(document["data"][0]["id"], "001");
+        assert_eq!(document["warnings"][0]["kind"], "fallback");
+        assert!(document["error"].is_null());
+
+        let error = anyhow::anyhow!("drift detected");
+        let document = envelope_document(vec![json!(1), json!(2)], &[], Some(&error));
+        assert_eq!(document["data"], json!([1, 2]));
+        assert_eq!(document["error"], "drift detected");
+    }
+
+    #[test]
+    fn test_downgrade_field_renames() {
+        let renames = [FieldRename { since: 2, old: "ip", new: "address" }];
+        let mut value = json!([{"id": "001", "address": "10.0.0.1", "os": {"address": "x"}}]);
+
+        let mut current = value.clone();
+        downgrade(&mut current, 2, &renames);
+        assert_eq!(current, value);
+
+        downgrade(&mut value, 1, &renames);
+        assert_eq!(value, json!([{"id": "001", "ip": "10.0.0.1", "os": {"ip": "x"}}]));
+    }
+
+    /// Format version 1 contract: changing these names needs a version bump and a shim
+    #[test]
+    fn test_agent_json_fields_v1() {
+        let agent: Agent = serde_json::from_value(json!({
+            "id": "001", "name": "web-01", "ip": "10.0.0.1", "status": "never_connected",
+            "os": {"platform": "ubuntu"}, "version": "Wazuh v4.7.2",
+            "last_keep_alive": "2024-01-01T00:00:00Z", "date_add": "2024-01-01T00:00:00Z",
+            "group": ["default"], "node_name": "master", "manager": "wazuh"
+        }))
+        .unwrap();
+
+        let value = serde_json::to_value(&agent).unwrap();
+        let mut fields: Vec<&str> = value.as_object().unwrap().keys().map(String::as_str).collect();
+        fields.sort_unstable();
+        assert_eq!(
+            fields,
+            [
+                "date_add", "group", "id", "ip", "last_keep_alive", "manager", "name",
+                "node_name", "os", "status", "version"
+            ]
+        );
+        assert_eq!(value["status"], "never_connected");
+    }
+
+    #[test]
+    fn test_csv_and_markdown_escaping() {
+        let fields = vec!["4.7.2".to_string(), "a,b".to_string(), "say \"hi\"".to_string()];
+        assert_eq!(csv_line(&fields), "4.7.2,\"a,b\",\"say \"\"hi\"\"\"");
+        assert_eq!(markdown_line(&["a|b".to_string(), "c".to_string()]), "| a\\|b | c |");
+    }
+
+    #[test]
+    fn test_format_datetime() {
+        let dt = DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(format_datetime(&dt), "2024-01-01 12:00:00 UTC");
+    }
+
+    #[test]
+    fn test_table_records() {
+        let mut table = Table::new();
+        table.set_header(vec!["ID", "Name", "Last Keep Alive", ""]);
+        table.add_row(vec!["003", "web-01", "Never", "███"]);
+
+        assert_eq!(
+            table_records(&table, "Agent"),
+            vec!["Agent 003: name=web-01, last_keep_alive=Never"]
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+/// Emit NDJSON progress events on stderr instead of drawing spinners
+static JSON_EVENTS: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable machine-readable progress events for the rest of the process
+pub fn set_json_events(enabled: bool) {
+    JSON_EVENTS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn json_events() -> bool {
+    JSON_EVENTS.load(Ordering::Relaxed)
+}
+
+/// One line of `--progress json` output
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum ProgressEvent {
+    Start {
+        timestamp: DateTime<Utc>,
+        operation: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        total: Option<u64>,
+    },
+    PageFetched {
+        timestamp: DateTime<Utc>,
+        operation: String,
+        page: u64,
+        items: u64,
+        fetched: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        total: Option<u64>,
+    },
+    ItemDone {
+        timestamp: DateTime<Utc>,
+        operation: String,
+        item: String,
+        ok: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    Summary {
+        timestamp: DateTime<Utc>,
+        operation: String,
+        fetched: u64,
+        succeeded: u64,
+        failed: u64,
+        elapsed_ms: u64,
+    },
+}
+
+/// Progress of one long-running operation
+///
+/// Events are only written when `--progress json` is active; otherwise every
+/// method is a no-op and the command's spinner is the only feedback.
+pub struct Progress {
+    operation: String,
+    started: Instant,
+    total: Option<u64>,
+    pages: u64,
+    fetched: u64,
+    succeeded: u64,
+    failed: u64,
+}
+
+impl Progress {
+    /// Begin an operation, emitting a `start` event
+    pub fn start(operation: &str, total: Option<u64>) -> Self {
+        let progress = Self {
+            operation: operation.to_string(),
+            started: Instant::now(),
+            total,
+            pages: 0,
+            fetched: 0,
+            succeeded: 0,
+            failed: 0,
+        };
+        emit(&ProgressEvent::Start {
+            timestamp: Utc::now(),
+            operation: progress.operation.clone(),
+            total,
+        });
+        progress
+    }
+
+    /// Record a page of `items` results
+    pub fn page_fetched(&mut self, items: u64) {
+        self.pages += 1;
+        self.fetched += items;
+        emit(&ProgressEvent::PageFetched {
+            timestamp: Utc::now(),
+            operation: self.operation.clone(),
+            page: self.pages,
+            items,
+            fetched: self.fetched,
+            total: self.total,
+        });
+    }
+
+    /// Record the outcome of one item of a bulk job
+    pub fn item_done(&mut self, item: &str, error: Option<&str>) {
+        match error {
+            Some(_) => self.failed += 1,
+            None => self.succeeded += 1,
+        }
+        emit(&ProgressEvent::ItemDone {
+            timestamp: Utc::now(),
+            operation: self.operation.clone(),
+            item: item.to_string(),
+            ok: error.is_none(),
+            error: error.map(String::from),
+        });
+    }
+
+    /// Finish the operation, emitting a `summary` event
+    pub fn finish(self) {
+        emit(&self.summary());
+    }
+
+    fn summary(&self) -> ProgressEvent {
+        ProgressEvent::Summary {
+            timestamp: Utc::now(),
+            operation: self.operation.clone(),
+            fetched: self.fetched,
+            succeeded: self.succeeded,
+            failed: self.failed,
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+fn emit(event: &ProgressEvent) {
+    if !json_events() {
+        return;
+    }
+
+    if let Ok(line) = serde_json::to_string(event) {
+        let mut stderr = std::io::stderr().lock();
+        let _ = writeln!(stderr, "{}", line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_counters() {
+        let mut progress = Progress::start("agent restart", Some(3));
+        progress.page_fetched(2);
+        progress.page_fetched(1);
+        progress.item_done("001", None);
+        progress.item_done("002", Some("agent is disconnected"));
+
+        match progress.summary() {
+            ProgressEvent::Summary {
+                operation,
+                fetched,
+                succeeded,
+                failed,
+                ..
+            } => {
+                assert_eq!(operation, "agent restart");
+                assert_eq!((fetched, succeeded, failed), (3, 1, 1));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        let line = serde_json::to_value(ProgressEvent::ItemDone {
+            timestamp: Utc::now(),
+            operation: "agent restart".to_string(),
+            item: "001".to_string(),
+            ok: true,
+            error: None,
+        })
+        .unwrap();
+        assert_eq!(line["event"], "item-done");
+        assert!(line.get("error").is_none());
+    }
+}
@@ -0,0 +1,158 @@
+//! `--output-file` and the pager: run the command again with its stdout
+//! going to a file or into a pager.
+//!
+//! Commands print through `println!`, so rather than threading a writer through
+//! every one of them, the invocation is re-run as a child process whose stdout
+//! is a temporary file next to the destination, or the pager's input.
+//!
+//! The output file replaces the destination only when the command succeeds, so
+//! a failed run never leaves a truncated report behind. Bytes are written as
+//! is, without the console code page conversion that mangles table borders
+//! when redirecting on Windows.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Stdio;
+use tempfile::Builder;
+use tokio::process::Command;
+use tracing::debug;
+
+use crate::output;
+use crate::script::split_command;
+
+/// Set in the child so it prints to its stdout instead of redirecting again
+const REDIRECTED_ENV: &str = "WAZUH_CLI_REDIRECTED";
+
+/// Terminal width passed to a child writing into a pager, for table layout
+const PAGER_WIDTH_ENV: &str = "WAZUH_CLI_PAGER_WIDTH";
+
+/// Used when `$PAGER` is not set
+const DEFAULT_PAGER: &str = "less -R";
+
+/// Whether this process is the child started by [`run_to_file`]
+pub fn is_redirected() -> bool {
+    std::env::var_os(REDIRECTED_ENV).is_some()
+}
+
+/// Re-run this invocation with stdout written atomically to `path`, returning
+/// the child's exit code
+pub async fn run_to_file(path: &Path) -> Result<i32> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut builder = Builder::new();
+    // Temporary files are private by default; the output is an ordinary file
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(std::fs::Permissions::from_mode(0o644));
+    }
+    let temp = builder
+        .tempfile_in(dir)
+        .with_context(|| format!("Cannot create the output file in {:?}", dir))?;
+    let stdout = temp.reopen().context("Failed to open the temporary output file")?;
+
+    let exe = std::env::current_exe().context("Cannot locate the wazuh-cli executable")?;
+    let status = Command::new(exe)
+        .args(std::env::args_os().skip(1))
+        .env(REDIRECTED_ENV, "1")
+        // Color codes are for terminals; the file gets plain text
+        .env("NO_COLOR", "1")
+        .stdout(Stdio::from(stdout))
+        .status()
+        .await
+        .context("Failed to run the command")?;
+
+    // The child already reported its error; the temporary file is dropped with `temp`
+    if !status.success() {
+        return Ok(status.code().unwrap_or(1));
+    }
+    temp.persist(path)
+        .with_context(|| format!("Failed to write output file {:?}", path))?;
+    Ok(0)
+}
+
+/// Pager to run the command behind: `output.pager` is on, stdout is a
+/// terminal, and `$PAGER` is not set to an empty value or `cat`
+pub fn pager(enabled: bool) -> Option<String> {
+    if !enabled || is_redirected() || !output::stdout_is_terminal() {
+        return None;
+    }
+    match std::env::var("PAGER") {
+        Ok(pager) if pager.trim().is_empty() || pager.trim() == "cat" => None,
+        Ok(pager) => Some(pager),
+        Err(_) => Some(DEFAULT_PAGER.to_string()),
+    }
+}
+
+/// Width of the terminal a paged child writes for, when this is that child
+pub fn pager_width() -> Option<u16> {
+    std::env::var(PAGER_WIDTH_ENV).ok()?.parse().ok()
+}
+
+/// Re-run this invocation with stdout piped into `pager`, returning the
+/// child's exit code, or `None` when the pager cannot be started
+pub async fn run_paged(pager: &str, color: bool) -> Result<Option<i32>> {
+    let args = split_command(pager)?;
+    let Some((program, pager_args)) = args.split_first() else {
+        return Ok(None);
+    };
+
+    let mut pager = Command::new(program);
+    pager.args(pager_args).stdin(Stdio::piped());
+    // Like git: quit when the output fits one screen and leave it on the terminal
+    if std::env::var_os("LESS").is_none() {
+        pager.env("LESS", "FRX");
+    }
+    let mut pager = match pager.spawn() {
+        Ok(pager) => pager,
+        Err(e) => {
+            debug!("Pager '{}' could not be started: {}", program, e);
+            return Ok(None);
+        }
+    };
+    let input: Stdio = pager
+        .stdin
+        .take()
+        .context("Pager input is not available")?
+        .try_into()
+        .context("Failed to connect the pager")?;
+
+    let (_, width) = console::Term::stdout().size();
+    let exe = std::env::current_exe().context("Cannot locate the wazuh-cli executable")?;
+    let mut child = Command::new(exe);
+    child
+        .args(std::env::args_os().skip(1))
+        .env(REDIRECTED_ENV, "1")
+        .env(PAGER_WIDTH_ENV, width.to_string())
+        .stdout(input);
+    if color {
+        // The pager shows colors (less -R) although the child's stdout is a pipe
+        child.env("CLICOLOR_FORCE", "1");
+    }
+    let status = child.status().await.context("Failed to run the command")?;
+    // The command holds our copy of the pipe; the pager sees the end once it is dropped
+    drop(child);
+    pager.wait().await.context("Failed to wait for the pager")?;
+
+    Ok(Some(status.code().unwrap_or(1)))
+}
+
+/// Exit quietly instead of panicking when the pager is closed before all output
+/// is written; `println!` panics on a broken pipe
+pub fn exit_on_broken_pipe() {
+    let default = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .or_else(|| info.payload().downcast_ref::<&str>().copied())
+            .unwrap_or_default();
+        if message.contains("Broken pipe") {
+            std::process::exit(0);
+        }
+        default(info);
+    }));
+}
@@ -0,0 +1,450 @@
+//! Scripting constructs for the interactive shell: variables (`set gid = dmz`),
+//! command substitution (`(agent list --group $gid)`) and `foreach` loops.
+
+use std::collections::BTreeMap;
+
+use crate::error::WazuhError;
+
+/// A single word of a statement
+#[derive(Debug, Clone, PartialEq)]
+pub enum Word {
+    /// Bare or double-quoted text; `$name` and `${name}` are expanded
+    Text(String),
+    /// Single-quoted text, taken literally
+    Literal(String),
+    /// `(command ...)`, replaced by the items the command returns
+    Substitution(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Set { name: String, value: Vec<Word> },
+    Unset(String),
+    Foreach {
+        var: String,
+        items: Vec<Word>,
+        body: Vec<Statement>,
+    },
+    Command(Vec<Word>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(Word),
+    LBrace,
+    RBrace,
+    Separator,
+}
+
+fn unterminated(what: &str) -> WazuhError {
+    WazuhError::InvalidInput(format!("Unterminated {}", what))
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, WazuhError> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut chars = input.chars().peekable();
+
+    fn flush(word: &mut String, tokens: &mut Vec<Token>) {
+        if !word.is_empty() {
+            tokens.push(Token::Word(Word::Text(std::mem::take(word))));
+        }
+    }
+
+    while let Some(c) = chars.next() {
+        match c {
+            ';' | '\n' => {
+                flush(&mut word, &mut tokens);
+                tokens.push(Token::Separator);
+            }
+            c if c.is_whitespace() => flush(&mut word, &mut tokens),
+            '{' if word.ends_with('$') => {
+                // `${name}` stays part of the word
+                word.push(c);
+                loop {
+                    match chars.next() {
+                        Some('}') => break word.push('}'),
+                        Some(ch) => word.push(ch),
+                        None => return Err(unterminated("${...} variable reference")),
+                    }
+                }
+            }
+            '{' => {
+                flush(&mut word, &mut tokens);
+                tokens.push(Token::LBrace);
+            }
+            '}' => {
+                flush(&mut word, &mut tokens);
+                tokens.push(Token::RBrace);
+            }
+            '\'' | '"' => {
+                flush(&mut word, &mut tokens);
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(ch) if ch == c => break,
+                        Some(ch) => text.push(ch),
+                        None => return Err(unterminated("quoted string")),
+                    }
+                }
+                tokens.push(Token::Word(if c == '\'' {
+                    Word::Literal(text)
+                } else {
+                    Word::Text(text)
+                }));
+            }
+            '(' => {
+                flush(&mut word, &mut tokens);
+                let mut depth = 1;
+                let mut command = String::new();
+                loop {
+                    let ch = chars.next().ok_or_else(|| unterminated("command substitution"))?;
+                    match ch {
+                        '(' => depth += 1,
+                        ')' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    command.push(ch);
+                }
+                tokens.push(Token::Word(Word::Substitution(command.trim().to_string())));
+            }
+            _ => word.push(c),
+        }
+    }
+    flush(&mut word, &mut tokens);
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next_word(&mut self, what: &str) -> Result<String, WazuhError> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Word(Word::Text(text) | Word::Literal(text))) => {
+                self.pos += 1;
+                Ok(text.clone())
+            }
+            _ => Err(WazuhError::InvalidInput(format!("Expected {}", what))),
+        }
+    }
+
+    /// Collect words up to the end of the statement (or an opening brace)
+    fn words(&mut self) -> Vec<Word> {
+        let mut words = Vec::new();
+        while let Some(Token::Word(word)) = self.peek() {
+            words.push(word.clone());
+            self.pos += 1;
+        }
+        words
+    }
+
+    fn statements(&mut self, nested: bool) -> Result<Vec<Statement>, WazuhError> {
+        let mut statements = Vec::new();
+
+        loop {
+            match self.peek() {
+                None if nested => return Err(unterminated("block: missing '}'")),
+                None => return Ok(statements),
+                Some(Token::RBrace) if nested => {
+                    self.pos += 1;
+                    return Ok(statements);
+                }
+                Some(Token::RBrace) => {
+                    return Err(WazuhError::InvalidInput("Unexpected '}'".to_string()))
+                }
+                Some(Token::LBrace) => {
+                    return Err(WazuhError::InvalidInput("Unexpected '{'".to_string()))
+                }
+                Some(Token::Separator) => self.pos += 1,
+                Some(Token::Word(_)) => statements.push(self.statement()?),
+            }
+        }
+    }
+
+    fn statement(&mut self) -> Result<Statement, WazuhError> {
+        let keyword = match self.peek() {
+            Some(Token::Word(Word::Text(text))) => text.clone(),
+            _ => return Ok(Statement::Command(self.words())),
+        };
+
+        match keyword.as_str() {
+            "set" => {
+                self.pos += 1;
+                let target = self.next_word("a variable name after 'set'")?;
+                let (name, mut value) = match target.split_once('=') {
+                    Some((name, rest)) if !rest.is_empty() => {
+                        (name.to_string(), vec![Word::Text(rest.to_string())])
+                    }
+                    Some((name, _)) => (name.to_string(), Vec::new()),
+                    None => {
+                        if self.peek() == Some(&Token::Word(Word::Text("=".to_string()))) {
+                            self.pos += 1;
+                        }
+                        (target, Vec::new())
+                    }
+                };
+                value.extend(self.words());
+                validate_name(&name)?;
+                Ok(Statement::Set { name, value })
+            }
+            "unset" => {
+                self.pos += 1;
+                let name = self.next_word("a variable name after 'unset'")?;
+                validate_name(&name)?;
+                Ok(Statement::Unset(name))
+            }
+            "foreach" => {
+                self.pos += 1;
+                let var = self.next_word("a loop variable after 'foreach'")?;
+                validate_name(&var)?;
+                if self.next_word("'in'")? != "in" {
+                    return Err(WazuhError::InvalidInput(
+                        "Expected 'foreach <var> in <items> { ... }'".to_string(),
+                    ));
+                }
+                let items = self.words();
+                if self.peek() != Some(&Token::LBrace) {
+                    return Err(WazuhError::InvalidInput(
+                        "Expected '{' after foreach items".to_string(),
+                    ));
+                }
+                self.pos += 1;
+                let body = self.statements(true)?;
+                Ok(Statement::Foreach { var, items, body })
+            }
+            _ => Ok(Statement::Command(self.words())),
+        }
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), WazuhError> {
+    if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        Ok(())
+    } else {
+        Err(WazuhError::InvalidInput(format!(
+            "Invalid variable name '{}'",
+            name
+        )))
+    }
+}
+
+/// Parse a line (or block of lines) typed into the interactive shell
+pub fn parse(input: &str) -> Result<Vec<Statement>, WazuhError> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        pos: 0,
+    };
+    parser.statements(false)
+}
+
+/// Split a single configured command line (e.g. `default_command`) into arguments,
+/// honoring quotes; variables and substitutions are not available outside the shell
+pub fn split_command(input: &str) -> Result<Vec<String>, WazuhError> {
+    let words = match parse(input)?.as_slice() {
+        [Statement::Command(words)] => words.clone(),
+        _ => {
+            return Err(WazuhError::InvalidInput(format!(
+                "Expected a single command, got '{}'",
+                input
+            )))
+        }
+    };
+
+    words
+        .into_iter()
+        .map(|word| match word {
+            Word::Text(text) if text.contains('$') => Err(WazuhError::InvalidInput(format!(
+                "Variables are only available in the interactive shell: '{}'",
+                text
+            ))),
+            Word::Text(text) | Word::Literal(text) => Ok(text),
+            Word::Substitution(command) => Err(WazuhError::InvalidInput(format!(
+                "Command substitution is only available in the interactive shell: ({})",
+                command
+            ))),
+        })
+        .collect()
+}
+
+/// Whether the input has an unclosed `{` and the shell should keep reading
+pub fn is_incomplete(input: &str) -> bool {
+    tokenize(input)
+        .map(|tokens| {
+            let opened = tokens.iter().filter(|t| **t == Token::LBrace).count();
+            let closed = tokens.iter().filter(|t| **t == Token::RBrace).count();
+            opened > closed
+        })
+        .unwrap_or(false)
+}
+
+/// Shell variables set with `set` and bound by `foreach`
+#[derive(Debug, Default)]
+pub struct Variables(BTreeMap<String, String>);
+
+impl Variables {
+    pub fn set(&mut self, name: &str, value: String) {
+        self.0.insert(name.to_string(), value);
+    }
+
+    pub fn unset(&mut self, name: &str) {
+        self.0.remove(name);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.0.iter()
+    }
+
+    /// Replace `$name` and `${name}` references; unknown variables are an error
+    pub fn expand(&self, text: &str) -> Result<String, WazuhError> {
+        let mut result = String::new();
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                result.push(c);
+                continue;
+            }
+
+            let braced = chars.peek() == Some(&'{');
+            if braced {
+                chars.next();
+            }
+            let mut name = String::new();
+            while let Some(&ch) = chars.peek() {
+                if ch.is_alphanumeric() || ch == '_' {
+                    name.push(ch);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            if braced && chars.next() != Some('}') {
+                return Err(unterminated("${...} variable reference"));
+            }
+            if name.is_empty() {
+                result.push('$');
+                continue;
+            }
+
+            match self.0.get(&name) {
+                Some(value) => result.push_str(value),
+                None => {
+                    return Err(WazuhError::InvalidInput(format!(
+                        "Undefined variable ${}",
+                        name
+                    )))
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// Turn the JSON output of a substituted command into loop items: agent IDs
+/// for lists of agents, `id`/`name` fields for other objects, or one item
+/// per value.
+pub fn substitution_items(value: &serde_json::Value) -> Vec<String> {
+    use serde_json::Value;
+
+    match value {
+        Value::Array(items) => items
+            .iter()
+            .flat_map(|item| match item {
+                Value::Object(fields) => fields
+                    .get("id")
+                    .or_else(|| fields.get("name"))
+                    .map(|v| crate::utils::json_to_text(Some(v)))
+                    .into_iter()
+                    .collect(),
+                other => substitution_items(other),
+            })
+            .collect(),
+        Value::Object(fields) => match fields.get("affected_items") {
+            Some(items) => substitution_items(items),
+            None => vec![value.to_string()],
+        },
+        Value::Null => Vec::new(),
+        Value::String(s) => vec![s.clone()],
+        other => vec![other.to_string()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_statements() {
+        let statements = parse("set gid = dmz; agent list --group $gid").unwrap();
+        assert_eq!(
+            statements[0],
+            Statement::Set {
+                name: "gid".to_string(),
+                value: vec![Word::Text("dmz".to_string())]
+            }
+        );
+        assert_eq!(
+            statements[1],
+            Statement::Command(vec![
+                Word::Text("agent".to_string()),
+                Word::Text("list".to_string()),
+                Word::Text("--group".to_string()),
+                Word::Text("$gid".to_string()),
+            ])
+        );
+
+        let statements =
+            parse("foreach agent in (agent list --group $gid) {\n agent get $agent\n}").unwrap();
+        match &statements[0] {
+            Statement::Foreach { var, items, body } => {
+                assert_eq!(var, "agent");
+                assert_eq!(
+                    items,
+                    &[Word::Substitution("agent list --group $gid".to_string())]
+                );
+                assert_eq!(body.len(), 1);
+            }
+            other => panic!("expected foreach, got {:?}", other),
+        }
+
+        assert!(parse("foreach a in 1 2 { agent get $a").is_err());
+        assert!(is_incomplete("foreach a in 1 2 {"));
+        assert!(!is_incomplete("foreach a in 1 2 { agent get $a }"));
+    }
+
+    #[test]
+    fn test_expand_variables() {
+        let mut vars = Variables::default();
+        vars.set("gid", "dmz".to_string());
+
+        assert_eq!(vars.expand("group-$gid").unwrap(), "group-dmz");
+        assert_eq!(vars.expand("${gid}_web").unwrap(), "dmz_web");
+        assert_eq!(vars.expand("cost $").unwrap(), "cost $");
+        assert!(vars.expand("$missing").is_err());
+    }
+
+    #[test]
+    fn test_substitution_items() {
+        let agents = json!([{"id": "001", "name": "web"}, {"id": "002", "name": "db"}]);
+        assert_eq!(substitution_items(&agents), ["001", "002"]);
+        assert_eq!(
+            substitution_items(&json!({"affected_items": ["a", "b"]})),
+            ["a", "b"]
+        );
+    }
+}
@@ -0,0 +1,446 @@
+//! Selector language for choosing the agents a command acts on.
+//!
+//! A selector is a boolean expression of comparisons, e.g.
+//! `status==disconnected && os.platform=="windows" && last_keep_alive > 7d`.
+//! Comparisons take a field path on the left and a quoted or bare value, a
+//! number or a duration on the right, combined with `&&`, `||`, `!` and
+//! parentheses. `~` and `!~` match glob patterns (`name~'web-*'`), and a
+//! duration compares the age of a timestamp field, so `last_keep_alive > 7d`
+//! means "last seen more than seven days ago".
+//!
+//! Plain equality terms the API can filter on are sent as its `q` parameter to
+//! narrow the fetch; the whole selector is always evaluated locally as well.
+
+use chrono::{DateTime, Duration, Utc};
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::error::WazuhError;
+use crate::utils::{glob_match, json_field, parse_duration, parse_version};
+
+/// Agent fields the API accepts in its `q` filter, by selector field name
+const API_FIELDS: &[(&str, &str)] = &[
+    ("id", "id"),
+    ("name", "name"),
+    ("ip", "ip"),
+    ("status", "status"),
+    ("version", "version"),
+    ("group", "group"),
+    ("node_name", "node_name"),
+    ("manager", "manager"),
+    ("os.platform", "os.platform"),
+    ("os.name", "os.name"),
+    ("os.version", "os.version"),
+];
+
+/// Comparison operator
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cmp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Like,
+    NotLike,
+}
+
+/// Right-hand side of a comparison
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Text(String),
+    Number(f64),
+    Age(Duration),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Compare(String, Cmp, Operand),
+    Not(Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+}
+
+/// A parsed `--select` expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    source: String,
+    root: Node,
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Num(f64),
+    Age(Duration),
+    Cmp(Cmp),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | '*' | '?' | '@' | ':' | '/')
+}
+
+/// Classify a bare word: a number, a duration such as `7d`, or text such as `4.8.0`
+fn bare_word(word: String) -> Token {
+    if word.starts_with(|c: char| c.is_ascii_digit()) {
+        if let Ok(n) = word.parse() {
+            return Token::Num(n);
+        }
+        if word.ends_with(['s', 'm', 'h', 'd', 'w']) {
+            if let Ok(age) = parse_duration(&word) {
+                return Token::Age(age);
+            }
+        }
+    }
+    Token::Word(word)
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, WazuhError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        chars.next();
+        let next = chars.peek().copied();
+        let token = match (c, next) {
+            (c, _) if c.is_whitespace() => continue,
+            ('"' | '\'', _) => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(ch) if ch == c => break,
+                        Some(ch) => text.push(ch),
+                        None => {
+                            return Err(WazuhError::InvalidInput(format!(
+                                "Unterminated string in selector '{}'",
+                                input
+                            )))
+                        }
+                    }
+                }
+                Token::Str(text)
+            }
+            ('&', Some('&')) | ('|', Some('|')) | ('=', Some('=')) | ('!', Some('='))
+            | ('!', Some('~')) | ('>', Some('=')) | ('<', Some('=')) => {
+                chars.next();
+                match c {
+                    '&' => Token::And,
+                    '|' => Token::Or,
+                    '=' => Token::Cmp(Cmp::Eq),
+                    '>' => Token::Cmp(Cmp::Ge),
+                    '<' => Token::Cmp(Cmp::Le),
+                    _ if next == Some('~') => Token::Cmp(Cmp::NotLike),
+                    _ => Token::Cmp(Cmp::Ne),
+                }
+            }
+            ('=', _) => Token::Cmp(Cmp::Eq),
+            ('>', _) => Token::Cmp(Cmp::Gt),
+            ('<', _) => Token::Cmp(Cmp::Lt),
+            ('~', _) => Token::Cmp(Cmp::Like),
+            ('!', _) => Token::Not,
+            ('(', _) => Token::LParen,
+            (')', _) => Token::RParen,
+            (c, _) if is_word_char(c) => {
+                let mut word = c.to_string();
+                while let Some(&d) = chars.peek() {
+                    if !is_word_char(d) {
+                        break;
+                    }
+                    word.push(d);
+                    chars.next();
+                }
+                bare_word(word)
+            }
+            (other, _) => {
+                return Err(WazuhError::InvalidInput(format!(
+                    "Unexpected character '{}' in selector '{}'",
+                    other, input
+                )))
+            }
+        };
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// or := and ('||' and)*
+    fn or(&mut self) -> Result<Node, WazuhError> {
+        let mut left = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            left = Node::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    /// and := unary ('&&' unary)*
+    fn and(&mut self) -> Result<Node, WazuhError> {
+        let mut left = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            left = Node::And(Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    /// unary := '!' unary | '(' or ')' | field cmp value
+    fn unary(&mut self) -> Result<Node, WazuhError> {
+        match self.next() {
+            Some(Token::Not) => Ok(Node::Not(Box::new(self.unary()?))),
+            Some(Token::LParen) => {
+                let inner = self.or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(inner),
+                    other => Err(WazuhError::InvalidInput(format!(
+                        "Expected ')' in selector, found {:?}",
+                        other
+                    ))),
+                }
+            }
+            Some(Token::Word(field)) => {
+                let cmp = match self.next() {
+                    Some(Token::Cmp(cmp)) => cmp,
+                    other => {
+                        return Err(WazuhError::InvalidInput(format!(
+                            "Expected a comparison after '{}' in selector, found {:?}",
+                            field, other
+                        )))
+                    }
+                };
+                let operand = match self.next() {
+                    Some(Token::Word(text) | Token::Str(text)) => Operand::Text(text),
+                    Some(Token::Num(n)) => Operand::Number(n),
+                    Some(Token::Age(age)) => Operand::Age(age),
+                    other => {
+                        return Err(WazuhError::InvalidInput(format!(
+                            "Expected a value after '{}' in selector, found {:?}",
+                            field, other
+                        )))
+                    }
+                };
+                Ok(Node::Compare(canonical_field(&field), cmp, operand))
+            }
+            other => Err(WazuhError::InvalidInput(format!(
+                "Unexpected {:?} in selector",
+                other
+            ))),
+        }
+    }
+}
+
+/// Accept the API's camelCase names for fields the CLI serializes in snake_case
+fn canonical_field(field: &str) -> String {
+    match field {
+        "lastKeepAlive" => "last_keep_alive".to_string(),
+        "dateAdd" => "date_add".to_string(),
+        "os" => "os.platform".to_string(),
+        other => other.to_string(),
+    }
+}
+
+impl Selector {
+    /// Parse a selector such as `status==active && group==web`
+    pub fn parse(input: &str) -> Result<Self, WazuhError> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            pos: 0,
+        };
+        let root = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(WazuhError::InvalidInput(format!(
+                "Unexpected trailing input in selector '{}'",
+                input
+            )));
+        }
+        Ok(Self {
+            source: input.to_string(),
+            root,
+        })
+    }
+
+    /// The API `q` filter for the top-level equality terms it can evaluate,
+    /// or `None` if every term has to be checked locally
+    pub fn api_query(&self) -> Option<String> {
+        let mut conjuncts = Vec::new();
+        collect_conjuncts(&self.root, &mut conjuncts);
+
+        let terms: Vec<String> = conjuncts
+            .into_iter()
+            .filter_map(|node| match node {
+                Node::Compare(field, cmp @ (Cmp::Eq | Cmp::Ne), Operand::Text(value)) => {
+                    let (_, api_field) = API_FIELDS.iter().find(|(name, _)| name == field)?;
+                    // Group membership is a list, which `!=` does not cover in the API
+                    let list_inequality = *cmp == Cmp::Ne && field == "group";
+                    let reserved = value.contains([',', ';', '(', ')', '*', '?']);
+                    let pushable = !(list_inequality || reserved || value.is_empty());
+                    let op = if *cmp == Cmp::Eq { "=" } else { "!=" };
+                    pushable.then(|| format!("{}{}{}", api_field, op, value))
+                }
+                _ => None,
+            })
+            .collect();
+
+        (!terms.is_empty()).then(|| terms.join(";"))
+    }
+
+    /// Whether a document (e.g. a serialized agent) is selected
+    pub fn matches(&self, doc: &serde_json::Value, now: DateTime<Utc>) -> bool {
+        eval(&self.root, doc, now)
+    }
+}
+
+fn collect_conjuncts<'a>(node: &'a Node, out: &mut Vec<&'a Node>) {
+    match node {
+        Node::And(left, right) => {
+            collect_conjuncts(left, out);
+            collect_conjuncts(right, out);
+        }
+        other => out.push(other),
+    }
+}
+
+fn eval(node: &Node, doc: &serde_json::Value, now: DateTime<Utc>) -> bool {
+    match node {
+        Node::And(left, right) => eval(left, doc, now) && eval(right, doc, now),
+        Node::Or(left, right) => eval(left, doc, now) || eval(right, doc, now),
+        Node::Not(inner) => !eval(inner, doc, now),
+        Node::Compare(field, cmp, operand) => {
+            let negated = matches!(cmp, Cmp::Ne | Cmp::NotLike);
+            match json_field(doc, field) {
+                None | Some(serde_json::Value::Null) => negated,
+                // Lists such as groups match if any member does, and `!=` if none does
+                Some(serde_json::Value::Array(items)) if negated => {
+                    items.iter().all(|item| compare(item, *cmp, operand, now))
+                }
+                Some(serde_json::Value::Array(items)) => {
+                    items.iter().any(|item| compare(item, *cmp, operand, now))
+                }
+                Some(value) => compare(value, *cmp, operand, now),
+            }
+        }
+    }
+}
+
+fn compare(value: &serde_json::Value, cmp: Cmp, operand: &Operand, now: DateTime<Utc>) -> bool {
+    let text = match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+
+    let ordering = match operand {
+        Operand::Text(pattern) if matches!(cmp, Cmp::Like | Cmp::NotLike) => {
+            return glob_match(pattern, &text) == (cmp == Cmp::Like);
+        }
+        Operand::Text(expected) => match (parse_version(&text), parse_version(expected)) {
+            (Some(a), Some(b)) if !matches!(cmp, Cmp::Eq | Cmp::Ne) => a.cmp(&b),
+            _ => text.as_str().cmp(expected.as_str()),
+        },
+        Operand::Number(expected) => match text.parse::<f64>() {
+            Ok(n) => n.partial_cmp(expected).unwrap_or(Ordering::Less),
+            Err(_) => return cmp == Cmp::Ne,
+        },
+        Operand::Age(age) => match DateTime::parse_from_rfc3339(&text) {
+            Ok(t) => (now - t.with_timezone(&Utc)).cmp(age),
+            Err(_) => return cmp == Cmp::Ne,
+        },
+    };
+
+    match cmp {
+        Cmp::Eq => ordering == Ordering::Equal,
+        Cmp::Ne => ordering != Ordering::Equal,
+        Cmp::Gt => ordering == Ordering::Greater,
+        Cmp::Ge => ordering != Ordering::Less,
+        Cmp::Lt => ordering == Ordering::Less,
+        Cmp::Le => ordering != Ordering::Greater,
+        // Glob matches returned above
+        Cmp::Like | Cmp::NotLike => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-03-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_selector_matches() {
+        let agent = json!({
+            "id": "007", "name": "win-db-01", "status": "disconnected",
+            "version": "Wazuh v4.7.2", "group": ["default", "db"],
+            "os": {"platform": "windows"},
+            "last_keep_alive": "2024-02-20T00:00:00Z"
+        });
+        let matches = |input: &str| Selector::parse(input).unwrap().matches(&agent, now());
+
+        assert!(matches(
+            "status==disconnected && os.platform==\"windows\" && last_keep_alive > 7d"
+        ));
+        assert!(!matches("lastKeepAlive > 30d"));
+        assert!(matches("name~'win-*' && group==db"));
+        assert!(matches("group!=web && !(status==active)"));
+        assert!(!matches("group!=db"));
+        assert!(matches("version < 4.8.0 || status==active"));
+        assert!(matches("id == 7"));
+        assert!(matches("ip != 10.0.0.1"));
+        assert!(!matches("ip == 10.0.0.1"));
+    }
+
+    #[test]
+    fn test_api_query() {
+        let selector =
+            Selector::parse("status==disconnected && os.platform=='windows' && name~'db-*'")
+                .unwrap();
+        assert_eq!(
+            selector.api_query().as_deref(),
+            Some("status=disconnected;os.platform=windows")
+        );
+        // Alternatives cannot be narrowed safely, so everything is checked locally
+        let selector = Selector::parse("status==active || group==web").unwrap();
+        assert_eq!(selector.api_query(), None);
+    }
+
+    #[test]
+    fn test_selector_parse_errors() {
+        assert!(Selector::parse("status").is_err());
+        assert!(Selector::parse("status == ").is_err());
+        assert!(Selector::parse("(status==active").is_err());
+        assert!(Selector::parse("name == 'open").is_err());
+        assert!(Selector::parse("status==active status==pending").is_err());
+    }
+}
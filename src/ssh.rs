@@ -0,0 +1,315 @@
+use anyhow::{anyhow, Context, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::process::Stdio;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+/// Time between checks of whether a new tunnel accepts connections
+const TUNNEL_POLL: Duration = Duration::from_millis(100);
+
+/// Bytes of the tunnel's `ssh` error output kept for error messages
+const STDERR_KEPT: usize = 4096;
+
+/// Open tunnels by jump host and target, shared by the clients of this process
+static TUNNELS: Mutex<BTreeMap<String, Weak<Tunnel>>> = Mutex::const_new(BTreeMap::new());
+
+/// Remote host reached through the system `ssh` client
+#[derive(Debug, Clone)]
+pub struct SshTarget {
+    /// `user@host` or an alias from `~/.ssh/config`
+    pub destination: String,
+    pub port: Option<u16>,
+    pub identity: Option<String>,
+}
+
+/// Where a tunnel listens on this host
+#[derive(Debug, Clone)]
+pub enum TunnelEnd {
+    /// A local port, where Unix sockets are not available
+    #[cfg_attr(unix, allow(dead_code))]
+    Tcp(SocketAddr),
+    /// A Unix socket, which lets clients keep the remote host in their URLs
+    #[cfg(unix)]
+    Unix(std::path::PathBuf),
+}
+
+impl TunnelEnd {
+    /// A free local endpoint to forward to
+    fn allocate() -> Result<(Self, Option<tempfile::TempDir>)> {
+        #[cfg(unix)]
+        {
+            // The directory is private to this user, and so is the socket in it
+            let dir = tempfile::Builder::new()
+                .prefix("wazuh-cli-ssh-")
+                .tempdir()
+                .context("No directory for the SSH tunnel socket")?;
+            Ok((TunnelEnd::Unix(dir.path().join("api.sock")), Some(dir)))
+        }
+        #[cfg(not(unix))]
+        {
+            let addr = std::net::TcpListener::bind("127.0.0.1:0")
+                .and_then(|listener| listener.local_addr())
+                .context("No free local port for the SSH tunnel")?;
+            Ok((TunnelEnd::Tcp(addr), None))
+        }
+    }
+
+    /// Whether the forward accepts connections yet
+    async fn accepts(&self) -> bool {
+        match self {
+            TunnelEnd::Tcp(addr) => tokio::net::TcpStream::connect(addr).await.is_ok(),
+            #[cfg(unix)]
+            TunnelEnd::Unix(path) => tokio::net::UnixStream::connect(path).await.is_ok(),
+        }
+    }
+}
+
+impl fmt::Display for TunnelEnd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TunnelEnd::Tcp(addr) => write!(f, "{}", addr),
+            #[cfg(unix)]
+            TunnelEnd::Unix(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// A local endpoint forwarded through an SSH jump host; the `ssh` process is
+/// stopped when the tunnel is dropped
+#[derive(Debug)]
+pub struct Tunnel {
+    pub local: TunnelEnd,
+    _ssh: Child,
+    /// Directory of the forwarded Unix socket, removed with the tunnel
+    _dir: Option<tempfile::TempDir>,
+}
+
+/// The `-L` argument forwarding `local` to `host:port` as the jump host sees it
+fn forward_spec(local: &TunnelEnd, host: &str, port: u16) -> String {
+    let host = if host.contains(':') { format!("[{}]", host) } else { host.to_string() };
+    format!("{}:{}:{}", local, host, port)
+}
+
+/// Read a pipe until it closes, keeping only its first `STDERR_KEPT` bytes,
+/// so the process writing to it never blocks on a full pipe
+async fn drain(mut pipe: impl AsyncRead + Unpin) -> String {
+    let mut kept = Vec::new();
+    let mut buf = [0u8; 1024];
+    while let Ok(read) = pipe.read(&mut buf).await {
+        if read == 0 {
+            break;
+        }
+        let room = STDERR_KEPT.saturating_sub(kept.len());
+        kept.extend_from_slice(&buf[..read.min(room)]);
+    }
+    String::from_utf8_lossy(&kept).into_owned()
+}
+
+/// Captured result of a remote command
+#[derive(Debug)]
+pub struct RemoteOutput {
+    pub success: bool,
+    pub stdout: Vec<u8>,
+    pub stderr: String,
+}
+
+impl SshTarget {
+    /// `ssh` with `options` before the destination, where it reads them
+    ///
+    /// `--` ends the options, so a destination starting with `-` is never
+    /// read as one.
+    fn command(&self, options: &[&str]) -> Command {
+        let mut cmd = Command::new("ssh");
+        if let Some(port) = self.port {
+            cmd.arg("-p").arg(port.to_string());
+        }
+        if let Some(identity) = &self.identity {
+            cmd.arg("-i").arg(identity);
+        }
+        cmd.args(options);
+        cmd.arg("--").arg(&self.destination);
+        cmd
+    }
+
+    /// Forward a free local port to `host:port` as the target sees it, once
+    /// the forward accepts connections
+    pub async fn forward(&self, host: &str, port: u16, timeout: Duration) -> Result<Tunnel> {
+        let (local, dir) = TunnelEnd::allocate()?;
+        let forward = forward_spec(&local, host, port);
+        debug!("ssh -L {} {}", forward, self.destination);
+
+        let mut ssh = self
+            .command(&[
+                "-N",
+                "-L",
+                &forward,
+                "-o",
+                "ExitOnForwardFailure=yes",
+                "-o",
+                "BatchMode=yes",
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to launch ssh (is OpenSSH installed?)")?;
+
+        // Drained for as long as ssh runs; its output only matters if it exits
+        let stderr: Option<JoinHandle<String>> =
+            ssh.stderr.take().map(|pipe| tokio::spawn(drain(pipe)));
+        let started = Instant::now();
+        loop {
+            if let Some(status) = ssh.try_wait()? {
+                let stderr = match stderr {
+                    Some(task) => task.await.unwrap_or_default(),
+                    None => String::new(),
+                };
+                return Err(anyhow!(
+                    "SSH tunnel through {} closed ({}): {}",
+                    self.destination,
+                    status,
+                    stderr.trim()
+                ));
+            }
+            if local.accepts().await {
+                return Ok(Tunnel {
+                    local,
+                    _ssh: ssh,
+                    _dir: dir,
+                });
+            }
+            if started.elapsed() > timeout {
+                return Err(anyhow!(
+                    "SSH tunnel through {} did not open within {}s",
+                    self.destination,
+                    timeout.as_secs()
+                ));
+            }
+            tokio::time::sleep(TUNNEL_POLL).await;
+        }
+    }
+
+    /// Run a shell command on the target, optionally feeding `input` on stdin
+    ///
+    /// Secrets should be passed through `input` rather than the command line so
+    /// they never show up in remote process listings.
+    pub async fn run(&self, remote_command: &str, input: Option<&[u8]>) -> Result<RemoteOutput> {
+        debug!("ssh {} {}", self.destination, remote_command);
+
+        let mut child = self
+            .command(&[])
+            .arg(remote_command)
+            .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to launch ssh (is OpenSSH installed?)")?;
+
+        if let (Some(data), Some(mut stdin)) = (input, child.stdin.take()) {
+            stdin.write_all(data).await.context("Failed to write to ssh stdin")?;
+        }
+
+        let output = child
+            .wait_with_output()
+            .await
+            .with_context(|| format!("ssh to {} failed", self.destination))?;
+
+        Ok(RemoteOutput {
+            success: output.status.success(),
+            stdout: output.stdout,
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+/// A tunnel through `destination` to `host:port`, reusing one this process
+/// already has open
+pub async fn shared_tunnel(
+    destination: &str,
+    host: &str,
+    port: u16,
+    timeout: Duration,
+) -> Result<Arc<Tunnel>> {
+    let key = format!("{} {}:{}", destination, host, port);
+    let mut tunnels = TUNNELS.lock().await;
+    if let Some(tunnel) = tunnels.get(&key).and_then(Weak::upgrade) {
+        return Ok(tunnel);
+    }
+
+    let target = SshTarget {
+        destination: destination.to_string(),
+        port: None,
+        identity: None,
+    };
+    let tunnel = Arc::new(target.forward(host, port, timeout).await?);
+    tunnels.insert(key, Arc::downgrade(&tunnel));
+    Ok(tunnel)
+}
+
+/// Quote a string for safe use as a single POSIX shell word
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/var/ossec/etc/client.keys"), "'/var/ossec/etc/client.keys'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn test_destination_after_end_of_options() {
+        let target = SshTarget {
+            destination: "-oProxyCommand=touch /tmp/pwned".to_string(),
+            port: Some(2222),
+            identity: None,
+        };
+        let cmd = target.command(&["-N"]);
+        let args: Vec<_> = cmd.as_std().get_args().collect();
+        assert_eq!(args, ["-p", "2222", "-N", "--", "-oProxyCommand=touch /tmp/pwned"]);
+    }
+
+    #[test]
+    fn test_forward_spec() {
+        let tcp = TunnelEnd::Tcp("127.0.0.1:40000".parse().unwrap());
+        assert_eq!(forward_spec(&tcp, "10.0.0.5", 55000), "127.0.0.1:40000:10.0.0.5:55000");
+        assert_eq!(forward_spec(&tcp, "fd00::5", 55000), "127.0.0.1:40000:[fd00::5]:55000");
+        #[cfg(unix)]
+        {
+            let socket = TunnelEnd::Unix("/tmp/t/api.sock".into());
+            assert_eq!(forward_spec(&socket, "manager", 55000), "/tmp/t/api.sock:manager:55000");
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_drain_keeps_the_writer_running() {
+        // Far more than a pipe buffer holds, so the writer blocks unless drained
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg("head -c 1000000 /dev/zero | tr '\\0' x >&2")
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stderr = tokio::spawn(drain(child.stderr.take().unwrap()));
+        let status = tokio::time::timeout(Duration::from_secs(10), child.wait())
+            .await
+            .expect("the writer blocked on a full pipe")
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(stderr.await.unwrap(), "x".repeat(STDERR_KEPT));
+    }
+}
@@ -0,0 +1,95 @@
+//! Text templates for `--template`, in the spirit of `kubectl -o go-template`.
+//!
+//! `{{ ... }}` placeholders hold an expression from [`crate::expr`], so both
+//! `{{.os.name}}` and `{{upper(status)}}` work; the Go-style leading dot is
+//! optional. `\t` and `\n` in the literal text are unescaped, since shells
+//! make real tabs awkward to type.
+
+use chrono::{DateTime, Utc};
+
+use crate::error::WazuhError;
+use crate::expr::Expr;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Placeholder(Expr),
+}
+
+/// Parsed template, rendered once per output item
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, WazuhError> {
+        let mut segments = Vec::new();
+        let mut rest = source;
+
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                segments.push(Segment::Text(unescape(&rest[..start])));
+            }
+            let end = rest[start..].find("}}").ok_or_else(|| {
+                WazuhError::InvalidInput(format!("Unterminated '{{{{' in template '{}'", source))
+            })?;
+            let inner = rest[start + 2..start + end].trim();
+            let inner = inner.strip_prefix('.').unwrap_or(inner);
+            let expr = Expr::parse(inner).map_err(|e| {
+                WazuhError::InvalidInput(format!("Template placeholder '{{{{{}}}}}': {}", inner, e))
+            })?;
+            segments.push(Segment::Placeholder(expr));
+            rest = &rest[start + end + 2..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(unescape(rest)));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Render the template against one JSON document (e.g. a serialized agent)
+    pub fn render(
+        &self,
+        doc: &serde_json::Value,
+        now: DateTime<Utc>,
+    ) -> Result<String, WazuhError> {
+        let mut rendered = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => rendered.push_str(text),
+                Segment::Placeholder(expr) => {
+                    let value = expr.eval(doc, now).map_err(WazuhError::InvalidInput)?;
+                    rendered.push_str(&value.to_string());
+                }
+            }
+        }
+        Ok(rendered)
+    }
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("\\t", "\t").replace("\\n", "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render() {
+        let agent = json!({ "id": "001", "name": "web-01", "os": { "platform": "ubuntu" } });
+        let now = Utc::now();
+
+        let template = Template::parse("{{.id}} {{ .name }}\\t{{upper(os.platform)}}").unwrap();
+        assert_eq!(template.render(&agent, now).unwrap(), "001 web-01\tUBUNTU");
+
+        let template = Template::parse("{{.ip}}!").unwrap();
+        assert_eq!(template.render(&agent, now).unwrap(), "N/A!");
+
+        assert!(Template::parse("{{.id").is_err());
+        assert!(Template::parse("{{frobnicate(id)}}").is_err());
+    }
+}
@@ -0,0 +1,169 @@
+//! API tokens kept between invocations, so each command does not log in again.
+//!
+//! Tokens are stored per API URL and user in a file only the owner can read,
+//! together with the time they expire. An expired token is never returned; a
+//! token the manager rejects earlier (e.g. after a password change) is removed
+//! by the client when it logs in again.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+use crate::config::Config;
+use crate::lock::{self, Resource};
+
+const TOKENS_FILE: &str = "tokens.json";
+
+/// A token issued by the API and the time it stops being accepted
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CachedToken {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Claims of an API token that decide how long it is accepted
+#[derive(Debug, Deserialize)]
+struct TokenClaims {
+    exp: i64,
+}
+
+/// When a JWT expires, from its `exp` claim
+///
+/// The signature is not verified: the manager does that on every request,
+/// and this only saves a request with a token it would reject.
+pub fn token_expiry(token: &str) -> Option<DateTime<Utc>> {
+    let mut validation = Validation::default();
+    validation.insecure_disable_signature_validation();
+    validation.validate_exp = false;
+    validation.validate_aud = false;
+    let data =
+        jsonwebtoken::decode::<TokenClaims>(token, &DecodingKey::from_secret(&[]), &validation)
+            .ok()?;
+    DateTime::from_timestamp(data.claims.exp, 0)
+}
+
+/// The entry of a manager and user, e.g. `wazuh@https://manager:55000`; run_as
+/// logins are told apart by a digest of their authorization context
+pub fn cache_key(config: &Config) -> Option<String> {
+    let username = config.auth.username.as_deref()?;
+    let key = format!("{}@{}", username, config.api_url());
+    match config.auth_context().ok()? {
+        Some(context) => {
+            let digest = Sha256::digest(context.to_string().as_bytes());
+            Some(format!("{}#run_as:{:x}", key, digest))
+        }
+        None => Some(key),
+    }
+}
+
+fn tokens_path() -> Result<PathBuf> {
+    Ok(Config::data_dir()?.join(TOKENS_FILE))
+}
+
+/// The cached token of `key`, unless it has expired by `now` (which should
+/// include the clock skew margin)
+pub fn load(key: &str, now: DateTime<Utc>) -> Option<CachedToken> {
+    let path = tokens_path().ok()?;
+    load_from(&path, key, now)
+}
+
+fn load_from(path: &Path, key: &str, now: DateTime<Utc>) -> Option<CachedToken> {
+    read_all(path)
+        .remove(key)
+        .filter(|cached| cached.expires_at > now)
+}
+
+/// Cache a token, dropping the expired entries of other keys
+pub fn store(key: &str, token: CachedToken) -> Result<()> {
+    let path = tokens_path()?;
+    let _lock = lock::acquire(Resource::Tokens)?;
+    let mut tokens = read_all(&path);
+    tokens.retain(|_, cached| cached.expires_at > Utc::now());
+    tokens.insert(key.to_string(), token);
+    write_all(&path, &tokens)
+}
+
+/// Forget the token of `key`, e.g. after the API rejected it
+pub fn remove(key: &str) -> Result<()> {
+    let path = tokens_path()?;
+    let _lock = lock::acquire(Resource::Tokens)?;
+    let mut tokens = read_all(&path);
+    if tokens.remove(key).is_some() {
+        write_all(&path, &tokens)?;
+    }
+    Ok(())
+}
+
+/// Cached tokens by key; a missing or unreadable file holds none
+fn read_all(path: &Path) -> BTreeMap<String, CachedToken> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Replace the file atomically, readable and writable by the owner only
+fn write_all(path: &Path, tokens: &BTreeMap<String, CachedToken>) -> Result<()> {
+    let dir = path.parent().context("Token cache has no parent directory")?;
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create data directory: {:?}", dir))?;
+
+    // Temporary files are created with 0600, which the cache keeps
+    let mut temp = NamedTempFile::new_in(dir)
+        .with_context(|| format!("Failed to create token cache in {:?}", dir))?;
+    temp.write_all(serde_json::to_string_pretty(tokens)?.as_bytes())?;
+    temp.persist(path)
+        .with_context(|| format!("Failed to write token cache: {:?}", path))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_token_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(TOKENS_FILE);
+        let now = Utc::now();
+        let token = |expires_at| CachedToken {
+            token: "jwt".to_string(),
+            expires_at,
+        };
+
+        let tokens = BTreeMap::from([
+            ("wazuh@https://a:55000".to_string(), token(now + Duration::minutes(10))),
+            ("wazuh@https://b:55000".to_string(), token(now - Duration::minutes(1))),
+        ]);
+        write_all(&path, &tokens).unwrap();
+
+        assert!(load_from(&path, "wazuh@https://a:55000", now).is_some());
+        assert!(load_from(&path, "wazuh@https://b:55000", now).is_none());
+        assert!(load_from(&path, "admin@https://a:55000", now).is_none());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_token_expiry() {
+        use jsonwebtoken::{encode, EncodingKey, Header};
+
+        let claims = serde_json::json!({ "iss": "wazuh", "exp": 1_900_000_000, "rbac_roles": [1] });
+        let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(b"s")).unwrap();
+        assert_eq!(token_expiry(&token), DateTime::from_timestamp(1_900_000_000, 0));
+        assert_eq!(token_expiry("not-a-jwt"), None);
+    }
+}
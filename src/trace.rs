@@ -0,0 +1,215 @@
+//! `--trace-http`: API requests and responses written out in full, like
+//! `curl -v`, so mismatches with the API can be debugged without rebuilding
+//! requests and their authentication by hand.
+//!
+//! Credentials never reach the trace: sensitive headers such as
+//! `Authorization` are redacted, and so are `token`, `password`, `key` and
+//! `api_key` fields in JSON bodies (the login response, `user create`, agent
+//! keys).
+
+use anyhow::{Context, Result};
+use futures::{stream, StreamExt};
+use reqwest::header::{HeaderMap, AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, SET_COOKIE};
+use reqwest::{Body, Request, Response};
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Where traces are written; none unless `--trace-http` was given
+static TRACE: Mutex<Option<Box<dyn Write + Send>>> = Mutex::new(None);
+
+/// JSON fields whose values are replaced in traced bodies
+const SECRET_FIELDS: &[&str] = &["token", "password", "key", "api_key"];
+
+const REDACTED: &str = "<redacted>";
+
+/// Trace to `path` (readable by the owner only), or to stderr for `-`
+pub fn set_target(path: Option<&Path>) -> Result<()> {
+    let writer: Box<dyn Write + Send> = match path {
+        None => return Ok(()),
+        Some(path) if path == Path::new("-") => Box::new(io::stderr()),
+        Some(path) => {
+            let mut options = OpenOptions::new();
+            options.write(true).create(true).truncate(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::OpenOptionsExt;
+                options.mode(0o600);
+            }
+            let file = options
+                .open(path)
+                .with_context(|| format!("Failed to open HTTP trace file: {:?}", path))?;
+            Box::new(file)
+        }
+    };
+    if let Ok(mut trace) = TRACE.lock() {
+        *trace = Some(writer);
+    }
+    Ok(())
+}
+
+pub fn enabled() -> bool {
+    TRACE.lock().map(|trace| trace.is_some()).unwrap_or(false)
+}
+
+fn write(text: &str) {
+    if let Ok(mut trace) = TRACE.lock() {
+        if let Some(writer) = trace.as_mut() {
+            // A trace that cannot be written must not fail the request
+            let _ = writer.write_all(text.as_bytes()).and_then(|_| writer.flush());
+        }
+    }
+}
+
+/// Trace a request about to be sent; `defaults` are the client's default
+/// headers, which reqwest only adds when sending
+pub fn request(request: &Request, defaults: &HeaderMap) {
+    let mut text = format!("> {} {}\n", request.method(), request.url());
+    let mut headers = defaults.clone();
+    for (name, value) in request.headers() {
+        headers.insert(name, value.clone());
+    }
+    text.push_str(&format_headers('>', &headers));
+    if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+        text.push_str(&format_body(body));
+    }
+    write(&text);
+}
+
+/// Trace a response, which is handed back unchanged
+///
+/// Bodies up to `limit` bytes (`api.max_response_mb`) are read and traced in
+/// full; a larger one is not traced and streams on to the reader, which
+/// rejects or spools it as without tracing.
+pub async fn response(mut response: Response, elapsed: Duration, limit: u64) -> Result<Response> {
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+
+    let mut text = format!("< {} ({} ms)\n", status, elapsed.as_millis());
+    text.push_str(&format_headers('<', &headers));
+
+    let mut chunks = Vec::new();
+    let mut read = 0;
+    let oversized = loop {
+        if read > limit {
+            break true;
+        }
+        match response.chunk().await.context("Failed to read the API response")? {
+            Some(chunk) => {
+                read += chunk.len() as u64;
+                chunks.push(chunk);
+            }
+            None => break false,
+        }
+    };
+
+    let body = if oversized {
+        text.push_str(&format!("\n<body over {} MB not traced>\n", limit / (1024 * 1024)));
+        let rest = response.bytes_stream();
+        Body::wrap_stream(stream::iter(chunks.into_iter().map(Ok)).chain(rest))
+    } else {
+        let body = chunks.concat();
+        text.push_str(&format_body(&body));
+        Body::from(body)
+    };
+    text.push('\n');
+    write(&text);
+
+    let mut rebuilt = http::Response::new(body);
+    *rebuilt.status_mut() = status;
+    *rebuilt.version_mut() = version;
+    *rebuilt.headers_mut() = headers;
+    Ok(Response::from(rebuilt))
+}
+
+/// Trace a request that got no response
+pub fn failure(error: &reqwest::Error) {
+    let mut text = format!("< {}", error);
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        text.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+    write(&format!("{}\n\n", text));
+}
+
+fn format_headers(prefix: char, headers: &HeaderMap) -> String {
+    let mut text = String::new();
+    for (name, value) in headers {
+        let sensitive = value.is_sensitive()
+            || [AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE, SET_COOKIE].contains(name);
+        let value = if sensitive {
+            // Keep the scheme, which is often what is wrong
+            match value.to_str().ok().and_then(|v| v.split_once(' ')) {
+                Some((scheme, _)) => format!("{} {}", scheme, REDACTED),
+                None => REDACTED.to_string(),
+            }
+        } else {
+            String::from_utf8_lossy(value.as_bytes()).into_owned()
+        };
+        text.push_str(&format!("{} {}: {}\n", prefix, name, value));
+    }
+    text
+}
+
+fn format_body(body: &[u8]) -> String {
+    if body.is_empty() {
+        return String::new();
+    }
+    let text = match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(mut json) => {
+            redact_json(&mut json);
+            json.to_string()
+        }
+        Err(_) => String::from_utf8_lossy(body).into_owned(),
+    };
+    format!("\n{}\n", text.trim_end())
+}
+
+fn redact_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if SECRET_FIELDS.contains(&key.as_str()) && value.is_string() {
+                    *value = REDACTED.into();
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_redaction() {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer eyJhbGciOi"));
+        headers.insert("x-org", HeaderValue::from_static("blue"));
+        let text = format_headers('>', &headers);
+        assert!(text.contains("> authorization: Bearer <redacted>\n"));
+        assert!(text.contains("> x-org: blue\n"));
+
+        let body = br#"{"data":{"token":"eyJhbGciOi","users":[{"password":"s3cret"}]}}"#;
+        let text = format_body(body);
+        assert!(!text.contains("eyJhbGciOi") && !text.contains("s3cret"));
+        assert!(text.contains(r#""token":"<redacted>""#));
+
+        // Agent keys, as returned by /agents/{id}/key and sent to /agents/insert
+        let body = br#"{"data":{"affected_items":[{"id":"001","key":"MDAxIHdlYi0wMQ=="}]}}"#;
+        let text = format_body(body);
+        assert!(!text.contains("MDAxIHdlYi0wMQ==") && text.contains(r#""id":"001""#));
+        let text = format_body(br#"{"name":"web-01","key":"c2VjcmV0","api_key":"abc123"}"#);
+        assert!(!text.contains("c2VjcmV0") && !text.contains("abc123"));
+        assert_eq!(format_body(b"plain text\n"), "\nplain text\n");
+    }
+}
@@ -0,0 +1,279 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Command};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::config::Config;
+use crate::lock::{self, Resource};
+
+const USAGE_FILE: &str = "usage.jsonl";
+
+/// One CLI invocation, recorded when `[stats] enabled = true`
+///
+/// Only command and flag names are kept; argument values never leave the parser.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub timestamp: DateTime<Utc>,
+    /// Subcommand path, e.g. `agent list`
+    pub command: String,
+    /// Long names of the flags given on the command line
+    pub flags: Vec<String>,
+    pub duration_ms: u64,
+    pub success: bool,
+    /// API endpoints that exceeded their latency budget, once per slow request
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slow_endpoints: Vec<String>,
+}
+
+/// Aggregated usage of one command
+#[derive(Debug, Serialize)]
+pub struct CommandUsage {
+    pub command: String,
+    pub runs: u64,
+    pub failures: u64,
+    pub avg_ms: u64,
+    pub p95_ms: u64,
+    pub last_used: DateTime<Utc>,
+}
+
+/// Output of `stats cli`
+#[derive(Debug, Serialize)]
+pub struct UsageSummary {
+    pub since: Option<DateTime<Utc>>,
+    pub total_runs: u64,
+    pub commands: Vec<CommandUsage>,
+    /// `command --flag` usage counts, most used first
+    pub flags: Vec<(String, u64)>,
+    /// Responses over their latency budget per endpoint, most frequent first
+    pub slow_endpoints: Vec<(String, u64)>,
+}
+
+/// Subcommand path and long flag names given on the command line
+pub fn describe(command: &Command, matches: &ArgMatches) -> (String, Vec<String>) {
+    let mut path = Vec::new();
+    let mut flags = Vec::new();
+    let (mut command, mut matches) = (command, matches);
+
+    loop {
+        for arg in command.get_arguments() {
+            let id = arg.get_id().as_str();
+            if matches.value_source(id) == Some(ValueSource::CommandLine) {
+                if let Some(long) = arg.get_long() {
+                    flags.push(format!("--{}", long));
+                }
+            }
+        }
+
+        match matches.subcommand() {
+            Some((name, sub_matches)) => match command.find_subcommand(name) {
+                Some(sub_command) => {
+                    path.push(name.to_string());
+                    command = sub_command;
+                    matches = sub_matches;
+                }
+                None => break,
+            },
+            None => break,
+        }
+    }
+
+    (path.join(" "), flags)
+}
+
+/// Path of the local usage log
+pub fn usage_path() -> Result<PathBuf> {
+    Ok(Config::data_dir()?.join(USAGE_FILE))
+}
+
+/// Append one invocation, logging instead of failing if the log cannot be written
+pub fn record_quietly(
+    command: String,
+    flags: Vec<String>,
+    duration: Duration,
+    success: bool,
+    slow_endpoints: Vec<String>,
+) {
+    let record = UsageRecord {
+        timestamp: Utc::now(),
+        command,
+        flags,
+        duration_ms: duration.as_millis() as u64,
+        success,
+        slow_endpoints,
+    };
+    if let Err(e) = append(&record) {
+        warn!("Failed to record usage statistics: {}", e);
+    }
+}
+
+fn append(record: &UsageRecord) -> Result<()> {
+    let path = usage_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create data directory: {:?}", parent))?;
+    }
+
+    let _lock = lock::acquire(Resource::Usage)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open usage file: {:?}", path))?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+
+    Ok(())
+}
+
+/// Load all recorded invocations, skipping malformed lines
+pub fn load() -> Result<Vec<UsageRecord>> {
+    let path = usage_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read usage file: {:?}", path))?;
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Delete the usage log
+pub fn clear() -> Result<()> {
+    let path = usage_path()?;
+    if path.exists() {
+        fs::remove_file(&path).with_context(|| format!("Failed to remove {:?}", path))?;
+    }
+    Ok(())
+}
+
+/// Summarize invocations newer than `since`, busiest commands first
+pub fn summarize(records: &[UsageRecord], since: Option<DateTime<Utc>>) -> UsageSummary {
+    let records: Vec<&UsageRecord> = records
+        .iter()
+        .filter(|r| since.is_none_or(|since| r.timestamp >= since))
+        .collect();
+
+    let mut by_command: BTreeMap<&str, Vec<&UsageRecord>> = BTreeMap::new();
+    let mut flags: HashMap<String, u64> = HashMap::new();
+    let mut slow_endpoints: HashMap<String, u64> = HashMap::new();
+    for record in &records {
+        for endpoint in &record.slow_endpoints {
+            *slow_endpoints.entry(endpoint.clone()).or_default() += 1;
+        }
+        by_command.entry(&record.command).or_default().push(record);
+        for flag in &record.flags {
+            *flags.entry(format!("{} {}", record.command, flag)).or_default() += 1;
+        }
+    }
+
+    let mut commands: Vec<CommandUsage> = by_command
+        .into_iter()
+        .map(|(command, runs)| {
+            let mut durations: Vec<u64> = runs.iter().map(|r| r.duration_ms).collect();
+            durations.sort_unstable();
+            let p95_index = (durations.len() * 95).div_ceil(100).saturating_sub(1);
+
+            CommandUsage {
+                command: command.to_string(),
+                runs: runs.len() as u64,
+                failures: runs.iter().filter(|r| !r.success).count() as u64,
+                avg_ms: durations.iter().sum::<u64>() / durations.len() as u64,
+                p95_ms: durations[p95_index],
+                last_used: runs.iter().map(|r| r.timestamp).max().unwrap_or_default(),
+            }
+        })
+        .collect();
+    commands.sort_by(|a, b| b.runs.cmp(&a.runs).then_with(|| a.command.cmp(&b.command)));
+
+    let mut flags: Vec<(String, u64)> = flags.into_iter().collect();
+    flags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let mut slow_endpoints: Vec<(String, u64)> = slow_endpoints.into_iter().collect();
+    slow_endpoints.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    UsageSummary {
+        since,
+        total_runs: records.len() as u64,
+        commands,
+        flags,
+        slow_endpoints,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use clap::CommandFactory;
+
+    fn run(command: &str, flags: &[&str], duration_ms: u64, success: bool) -> UsageRecord {
+        UsageRecord {
+            timestamp: Utc::now(),
+            command: command.to_string(),
+            flags: flags.iter().map(|f| f.to_string()).collect(),
+            duration_ms,
+            success,
+            slow_endpoints: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_describe_invocation() {
+        let command = Cli::command();
+        let matches = command.clone().get_matches_from([
+            "wazuh-cli", "--json", "agent", "list", "--status", "active", "--group", "web",
+        ]);
+
+        let (path, flags) = describe(&command, &matches);
+        assert_eq!(path, "agent list");
+        assert_eq!(flags, ["--json", "--status", "--group"]);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let records = vec![
+            run("agent list", &["--status"], 100, true),
+            run("agent list", &[], 300, true),
+            run("agent list", &["--status"], 200, false),
+            run("alerts search", &[], 1000, true),
+        ];
+
+        let summary = summarize(&records, None);
+        assert_eq!(summary.total_runs, 4);
+        assert_eq!(summary.commands[0].command, "agent list");
+        assert_eq!(summary.commands[0].runs, 3);
+        assert_eq!(summary.commands[0].failures, 1);
+        assert_eq!(summary.commands[0].avg_ms, 200);
+        assert_eq!(summary.commands[0].p95_ms, 300);
+        assert_eq!(summary.flags[0], ("agent list --status".to_string(), 2));
+    }
+
+    #[test]
+    fn test_summarize_slow_endpoints() {
+        let mut slow = run("agent list", &[], 12_000, true);
+        slow.slow_endpoints = vec!["/agents".to_string(), "/agents".to_string()];
+        let records = vec![
+            run("agent list", &["--status"], 100, true),
+            run("agent list", &[], 300, true),
+            run("agent list", &["--status"], 200, false),
+            run("alerts search", &[], 1000, true),
+            slow,
+        ];
+
+        let summary = summarize(&records, None);
+        assert_eq!(summary.total_runs, 5);
+        assert_eq!(summary.commands[0].runs, 4);
+        assert_eq!(summary.commands[0].avg_ms, 3150);
+        assert_eq!(summary.commands[0].p95_ms, 12_000);
+        assert_eq!(summary.slow_endpoints, [("/agents".to_string(), 2)]);
+    }
+}
\ No newline at end of file
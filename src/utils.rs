@@ -1,11 +1,251 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use chrono::Duration;
 
+use crate::error::WazuhError;
 
+/// Parse a relative time span such as `30m`, `24h`, `7d` or `2w`
+pub fn parse_duration(input: &str) -> Result<Duration, WazuhError> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (amount, unit) = input.split_at(split);
 
+    let amount: i64 = amount.parse().map_err(|_| {
+        WazuhError::InvalidInput(format!(
+            "Invalid duration '{}': expected a number followed by s, m, h, d or w",
+            input
+        ))
+    })?;
 
+    match unit {
+        "s" => Ok(Duration::seconds(amount)),
+        "m" => Ok(Duration::minutes(amount)),
+        "h" | "" => Ok(Duration::hours(amount)),
+        "d" => Ok(Duration::days(amount)),
+        "w" => Ok(Duration::weeks(amount)),
+        _ => Err(WazuhError::InvalidInput(format!(
+            "Invalid duration unit '{}' in '{}': use s, m, h, d or w",
+            unit, input
+        ))),
+    }
+}
+
+/// Parse a `Name: value` HTTP header given on the command line
+pub fn parse_header(input: &str) -> Result<(String, String), WazuhError> {
+    match input.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(WazuhError::InvalidInput(format!(
+            "Invalid header '{}': expected 'Name: value'",
+            input
+        ))),
+    }
+}
+
+/// Whether a command-line agent argument is an ID rather than a name
+pub fn is_agent_id(input: &str) -> bool {
+    let input = input.trim();
+    !input.is_empty() && input.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Validate an agent ID and pad it to the manager's three digits (`7` → `007`)
+pub fn normalize_agent_id(input: &str) -> Result<String, WazuhError> {
+    let input = input.trim();
+    if !is_agent_id(input) {
+        return Err(WazuhError::InvalidInput(format!(
+            "Invalid agent ID '{}': expected a number such as 7 or 007",
+            input
+        )));
+    }
+    let id: u32 = input.parse().map_err(|_| {
+        WazuhError::InvalidInput(format!("Invalid agent ID '{}': too large", input))
+    })?;
+    Ok(format!("{:03}", id))
+}
+
+/// Check that an agent name only has the characters the manager allows
+pub fn validate_agent_name(input: &str) -> Result<(), WazuhError> {
+    let valid = input.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c));
+    if input.is_empty() || !valid {
+        return Err(WazuhError::InvalidInput(format!(
+            "'{}' is not a valid agent name: only letters, digits, '-', '_' and '.' are allowed",
+            input
+        )));
+    }
+    Ok(())
+}
+
+/// Check that a group name is a single group the manager allows, not a list
+/// or the `all` keyword
+pub fn validate_group_name(input: &str) -> Result<(), WazuhError> {
+    let valid = input.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c));
+    if input.is_empty() || !valid || input == "." || input == ".." {
+        return Err(WazuhError::InvalidInput(format!(
+            "'{}' is not a valid group name: only letters, digits, '-', '_' and '.' are allowed",
+            input
+        )));
+    }
+    if input.eq_ignore_ascii_case("all") {
+        return Err(WazuhError::InvalidInput(
+            "'all' is not a group name; delete groups one at a time".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Case-insensitive shell-style match where `*` is any run of characters and `?` one
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    // Backtrack to the last `*` on a mismatch
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Parse an agent version such as `Wazuh v4.7.2` or `4.7` into comparable parts
+pub fn parse_version(version: &str) -> Option<Vec<u64>> {
+    let start = version.find(|c: char| c.is_ascii_digit())?;
+    version[start..]
+        .split('.')
+        .map(|part| {
+            let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse().ok()
+        })
+        .collect()
+}
+
+/// Escape the characters Lucene query string syntax treats specially, so
+/// user input matches literally inside a query or phrase
+pub fn escape_lucene(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if "+-=&|><!(){}[]^\"~*?:\\/ ".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Look up a dotted field path (e.g. `rule.level`) in a JSON document
+pub fn json_field<'a>(value: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    path.split('.').try_fold(value, |current, key| current.get(key))
+}
+
+/// Render a JSON value as plain text for table cells
+pub fn json_to_text(value: Option<&serde_json::Value>) -> String {
+    match value {
+        None | Some(serde_json::Value::Null) => "N/A".to_string(),
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .map(|item| json_to_text(Some(item)))
+            .collect::<Vec<_>>()
+            .join(", "),
+        Some(other) => other.to_string(),
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30d").unwrap(), Duration::days(30));
+        assert_eq!(parse_duration("15m").unwrap(), Duration::minutes(15));
+        assert_eq!(parse_duration("2w").unwrap(), Duration::weeks(2));
+        assert_eq!(parse_duration("24").unwrap(), Duration::hours(24));
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("10y").is_err());
+    }
+
+    #[test]
+    fn test_parse_header() {
+        assert_eq!(
+            parse_header("X-Proxy-Auth: abc:def").unwrap(),
+            ("X-Proxy-Auth".to_string(), "abc:def".to_string())
+        );
+        assert!(parse_header("no-colon").is_err());
+        assert!(parse_header(": value").is_err());
+    }
+
+    #[test]
+    fn test_normalize_agent_id() {
+        assert_eq!(normalize_agent_id("7").unwrap(), "007");
+        assert_eq!(normalize_agent_id(" 0042 ").unwrap(), "042");
+        assert_eq!(normalize_agent_id("1024").unwrap(), "1024");
+        assert!(normalize_agent_id("").is_err());
+        assert!(normalize_agent_id("-7").is_err());
+        assert!(normalize_agent_id("99999999999").is_err());
+        assert!(validate_agent_name("web-01.example_net").is_ok());
+        assert!(validate_agent_name("web 01").is_err());
+    }
+
+    #[test]
+    fn test_validate_group_name() {
+        assert!(validate_group_name("web-servers_2.eu").is_ok());
+        assert!(validate_group_name("all").is_err());
+        assert!(validate_group_name("web,db").is_err());
+        assert!(validate_group_name("..").is_err());
+        assert!(validate_group_name("").is_err());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("db-*", "DB-01"));
+        assert!(glob_match("*-prod-?", "web-prod-1"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("db-*", "web-db-01"));
+        assert!(!glob_match("web-?", "web-10"));
+    }
+
+    #[test]
+    fn test_escape_lucene() {
+        assert_eq!(escape_lucene("web-01"), "web\\-01");
+        assert_eq!(escape_lucene("a \"b\" (c)"), "a\\ \\\"b\\\"\\ \\(c\\)");
+        assert_eq!(escape_lucene("plain"), "plain");
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("Wazuh v4.7.2"), Some(vec![4, 7, 2]));
+        assert_eq!(parse_version("4.10"), Some(vec![4, 10]));
+        assert!(parse_version("Wazuh v4.3.0") < parse_version("v4.10.1"));
+        assert_eq!(parse_version("unknown"), None);
+    }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_json_field() {
+        let doc = json!({"rule": {"id": "5710", "level": 5, "pci_dss": ["10.2.4", "10.2.5"]}});
+        assert_eq!(json_to_text(json_field(&doc, "rule.id")), "5710");
+        assert_eq!(json_to_text(json_field(&doc, "rule.level")), "5");
+        assert_eq!(json_to_text(json_field(&doc, "rule.pci_dss")), "10.2.4, 10.2.5");
+        assert_eq!(json_to_text(json_field(&doc, "agent.name")), "N/A");
+    }
+}
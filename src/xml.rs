@@ -0,0 +1,127 @@
+//! Well-formedness check for the XML files uploaded to the manager (agent.conf).
+//!
+//! The manager rejects a malformed file only after it has been uploaded, and a
+//! broken agent.conf is pushed to every agent of the group, so uploads are
+//! checked here first. Like the manager's own parser, several top-level
+//! elements are accepted; entities and DTDs are not validated.
+
+use crate::error::WazuhError;
+
+/// Check that tags are balanced and properly nested, and that comments,
+/// CDATA sections and attribute values are terminated
+pub fn check_well_formed(content: &str) -> Result<(), WazuhError> {
+    let mut open: Vec<(&str, usize)> = Vec::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find('<') {
+        let line = line_of(content, rest, start);
+        rest = &rest[start..];
+
+        let markup_end = if rest.starts_with("<!--") {
+            rest.find("-->").map(|end| end + 3)
+        } else if rest.starts_with("<![CDATA[") {
+            rest.find("]]>").map(|end| end + 3)
+        } else if rest.starts_with("<?") {
+            rest.find("?>").map(|end| end + 2)
+        } else if rest.starts_with("<!") {
+            rest.find('>').map(|end| end + 1)
+        } else {
+            let end = tag_end(rest).ok_or_else(|| malformed(line, "unterminated tag"))?;
+            check_tag(&rest[..end], line, &mut open)?;
+            Some(end)
+        };
+        let end = markup_end.ok_or_else(|| malformed(line, "unterminated markup"))?;
+        rest = &rest[end..];
+    }
+
+    match open.last() {
+        Some((name, line)) => Err(malformed(*line, &format!("<{}> is never closed", name))),
+        None => Ok(()),
+    }
+}
+
+/// Offset just past the `>` closing a tag, skipping `>` inside quoted values
+fn tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in tag.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return Some(index + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn check_tag<'a>(
+    tag: &'a str,
+    line: usize,
+    open: &mut Vec<(&'a str, usize)>,
+) -> Result<(), WazuhError> {
+    let inner = &tag[1..tag.len() - 1];
+    if let Some(closing) = inner.strip_prefix('/') {
+        let name = closing.trim();
+        return match open.pop() {
+            Some((expected, _)) if expected == name => Ok(()),
+            Some((expected, _)) => Err(malformed(
+                line,
+                &format!("</{}> found where </{}> was expected", name, expected),
+            )),
+            None => Err(malformed(line, &format!("</{}> closes no element", name))),
+        };
+    }
+
+    let self_closing = inner.ends_with('/');
+    let inner = inner.trim_end_matches('/');
+    let name = inner.split(|c: char| c.is_whitespace()).next().unwrap_or("");
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || "_-.:".contains(c)) {
+        return Err(malformed(line, &format!("invalid tag name '{}'", name)));
+    }
+    if !self_closing {
+        open.push((name, line));
+    }
+    Ok(())
+}
+
+/// 1-based line of `offset` in `rest`, a suffix of `content`
+fn line_of(content: &str, rest: &str, offset: usize) -> usize {
+    let position = content.len() - rest.len() + offset;
+    content[..position].matches('\n').count() + 1
+}
+
+fn malformed(line: usize, message: &str) -> WazuhError {
+    WazuhError::InvalidInput(format!("Malformed XML at line {}: {}", line, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_well_formed() {
+        let agent_conf = r#"<!-- Shared by the web servers -->
+<agent_config os="Linux">
+  <localfile>
+    <location>/var/log/nginx/access.log</location>
+    <log_format>apache</log_format>
+  </localfile>
+  <syscheck><directories check_all="yes" restrict=">x">/etc</directories></syscheck>
+  <labels><label key="env" /></labels>
+</agent_config>
+<agent_config profile="centos"></agent_config>
+"#;
+        assert!(check_well_formed(agent_conf).is_ok());
+
+        let error = check_well_formed("<agent_config>\n  <localfile>\n</agent_config>\n")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("line 3"), "{}", error);
+        assert!(error.contains("</localfile> was expected"), "{}", error);
+
+        assert!(check_well_formed("<agent_config>\n").is_err());
+        assert!(check_well_formed("<agent_config os=\"Linux>\n").is_err());
+        assert!(check_well_formed("<!-- unterminated\n<agent_config/>").is_err());
+        assert!(check_well_formed("</agent_config>").is_err());
+    }
+}